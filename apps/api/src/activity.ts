@@ -0,0 +1,34 @@
+import { db, activityEvents } from "@gitbruv/db";
+import { trackTask } from "./metrics";
+
+export const ACTIVITY_EVENT_TYPES = [
+  "repository_created",
+  "repository_forked",
+  "starred",
+  "pushed",
+  "issue_opened",
+  "issue_closed",
+  "issue_commented",
+  "pull_request_opened",
+  "pull_request_merged",
+] as const;
+
+export type ActivityEventType = (typeof ACTIVITY_EVENT_TYPES)[number];
+
+// Recording is best-effort: a failed insert is logged rather than failing the action that
+// produced the event.
+export function recordActivity(event: {
+  type: ActivityEventType;
+  actorId: string;
+  repositoryId: string;
+  payload?: Record<string, unknown>;
+}) {
+  const task = () =>
+    db
+      .insert(activityEvents)
+      .values({ ...event, payload: event.payload ?? {} })
+      .catch((error) => {
+        console.error(`[API] Failed to record ${event.type} activity:`, error);
+      });
+  trackTask("activity_event", task);
+}
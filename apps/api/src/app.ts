@@ -0,0 +1,89 @@
+import { getAllowedOrigins, getOAuthClientOrigins } from './config';
+import { createMiddleware } from 'hono/factory';
+import { mountRoutes } from './routes';
+import { initAuth } from './auth';
+import { recordRequest } from './metrics';
+import { apiError, handleError } from './errors';
+import { runWithTrace, logSpan, hasStorageFailure } from './tracing';
+import { usernameRedirectMiddleware } from './username-redirects';
+import { requestId } from 'hono/request-id';
+import { cors } from 'hono/cors';
+import { Hono } from 'hono';
+
+const loggingMiddleware = createMiddleware(async (c, next) => {
+  const startedAt = performance.now();
+  await next();
+  recordRequest(c.res.status);
+  logSpan('http.request', performance.now() - startedAt, { status: c.res.status });
+  if (c.res.status >= 500) {
+    console.error(`[API] ${c.req.method} ${c.req.path} -> ${c.res.status} (request ${c.get('requestId')})`);
+  }
+});
+
+const traceMiddleware = createMiddleware((c, next) =>
+  runWithTrace({ requestId: c.get('requestId'), method: c.req.method, path: c.req.path }, next),
+);
+
+// A handler that hit a storage outage may still have produced a normal-looking answer (an empty
+// tree, a 404 for a file that exists), so the whole response is replaced. Push and fetch results
+// already report the failure in-band, and git clients only display plain-text error bodies.
+const storageFailureMiddleware = createMiddleware(async (c, next) => {
+  await next();
+  if (c.res.status !== 503 && hasStorageFailure()) {
+    const contentType = c.res.headers.get('Content-Type') ?? '';
+    if (contentType.startsWith('application/x-git-') && contentType.endsWith('-result')) {
+      return;
+    }
+    c.res = undefined;
+    c.res = contentType.startsWith('application/x-git-')
+      ? new Response('Repository storage is temporarily unavailable, try again shortly\n', {
+          status: 503,
+          headers: { 'Content-Type': 'text/plain; charset=utf-8' },
+        })
+      : apiError(c, 503, 'Storage is temporarily unavailable');
+    c.header('Retry-After', '30');
+  }
+});
+
+// Builds the HTTP app without starting jobs or a server, so tests can drive it with app.request().
+export function createApp() {
+  const app = new Hono();
+
+  app.use('*', requestId());
+  app.use('*', traceMiddleware);
+  app.use('*', loggingMiddleware);
+  app.use('*', storageFailureMiddleware);
+
+  app.use(
+    '*',
+    cors({
+      origin: (origin, c) => {
+        if (!origin) return null;
+        const allowed = c.req.path.startsWith('/api/auth')
+          ? getOAuthClientOrigins()
+          : getAllowedOrigins();
+        return allowed.includes(origin) ? origin : null;
+      },
+      credentials: true,
+      allowMethods: ['GET', 'POST', 'PUT', 'PATCH', 'DELETE', 'OPTIONS'],
+      allowHeaders: ['Content-Type', 'Authorization', 'Cookie', 'x-internal-auth', 'X-Request-Id'],
+      exposeHeaders: ['Set-Cookie', 'X-Request-Id'],
+    }),
+  );
+
+  app.use(
+    '*',
+    createMiddleware(async (c, next) => {
+      await initAuth();
+      await next();
+    }),
+  );
+
+  app.use('*', usernameRedirectMiddleware);
+
+  mountRoutes(app);
+
+  app.onError(handleError);
+
+  return app;
+}
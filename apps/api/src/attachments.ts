@@ -0,0 +1,47 @@
+import { storageLayout } from "./storage-layout";
+
+export const MAX_ATTACHMENT_BYTES = 25 * 1024 * 1024;
+
+export const UPLOAD_URL_TTL_SECONDS = 15 * 60;
+
+// Uploads that were never finalized are removed once their URL has long expired, which also
+// catches clients that uploaded the bytes but never came back to finalize.
+export const PENDING_ATTACHMENT_TTL_MS = 24 * 60 * 60 * 1000;
+
+// Pending uploads hold storage until they're finalized or cleaned up, so each user can only have
+// so many outstanding at once.
+export const MAX_PENDING_ATTACHMENTS = 20;
+export const MAX_PENDING_ATTACHMENT_BYTES = 10 * MAX_ATTACHMENT_BYTES;
+
+export const ALLOWED_ATTACHMENT_TYPES = new Set([
+  "image/png",
+  "image/jpeg",
+  "image/gif",
+  "image/webp",
+  "video/mp4",
+  "video/webm",
+  "text/plain",
+  "text/csv",
+  "application/json",
+  "application/pdf",
+  "application/zip",
+  "application/gzip",
+  "application/x-tar",
+  "application/octet-stream",
+]);
+
+// Only raster images and video render inline; everything else downloads, so an uploaded file
+// can never run as a page on the API origin.
+export function isInlineAttachmentType(contentType: string) {
+  return contentType.startsWith("image/") || contentType.startsWith("video/");
+}
+
+export function sanitizeAttachmentFilename(filename: string): string {
+  const base = filename.split(/[\\/]/).pop() || "";
+  const cleaned = base.replace(/[^A-Za-z0-9._-]+/g, "-").replace(/^[.-]+/, "").slice(-100);
+  return cleaned || "file";
+}
+
+export function getAttachmentKey(id: string, filename: string) {
+  return storageLayout.attachmentKey(id, filename);
+}
@@ -0,0 +1,22 @@
+import { db, auditLogs } from "@gitbruv/db";
+
+export type AuditAction = "repository.delete";
+
+// Unlike activity events this is awaited: callers write the record as part of the action so a
+// destructive change can't complete without leaving a trail.
+export async function recordAudit(entry: {
+  actor: { id: string; username: string };
+  action: AuditAction;
+  targetType: "repository";
+  targetId: string;
+  metadata?: Record<string, unknown>;
+}) {
+  await db.insert(auditLogs).values({
+    actorId: entry.actor.id,
+    actorUsername: entry.actor.username,
+    action: entry.action,
+    targetType: entry.targetType,
+    targetId: entry.targetId,
+    metadata: entry.metadata ?? {},
+  });
+}
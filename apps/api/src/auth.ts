@@ -5,6 +5,7 @@ import {
   accounts,
   verifications,
   passkeys,
+  twoFactors,
   jwks,
   oauthClients,
   oauthRefreshTokens,
@@ -15,12 +16,16 @@ import { getApiUrl, getWebUrl, getTrustedOrigins, config } from './config';
 import { drizzleAdapter } from 'better-auth/adapters/drizzle';
 import { oauthProvider } from '@better-auth/oauth-provider';
 import { sendPasswordResetEmail } from './email';
+import { ApiError } from './errors';
 import { passkey } from '@better-auth/passkey';
 import { APIError } from 'better-auth/api';
-import { jwt } from 'better-auth/plugins';
+import { jwt, twoFactor } from 'better-auth/plugins';
 import { expo } from '@better-auth/expo';
 import { betterAuth } from 'better-auth';
 import { getRedis } from './redis';
+import { traceSpan } from './tracing';
+import { releaseUsername } from './username-redirects';
+import { validateUsername } from '@gitbruv/lib';
 
 function getCookieDomain(): string | undefined {
   try {
@@ -64,51 +69,6 @@ const BLOCKED_EMAIL_DOMAINS = [
   'spamgourmet.com',
 ];
 
-function containsEmoji(str: string): boolean {
-  return /\p{Extended_Pictographic}/u.test(str);
-}
-
-function isValidUsername(username: string): { valid: boolean; error?: string } {
-  if (username.length < 3) {
-    return { valid: false, error: 'Username must be at least 3 characters' };
-  }
-
-  if (username.length > 39) {
-    return { valid: false, error: 'Username must be 39 characters or less' };
-  }
-
-  if (containsEmoji(username)) {
-    return { valid: false, error: 'Username cannot contain emojis' };
-  }
-
-  if (!/^[a-zA-Z0-9_-]+$/.test(username)) {
-    return {
-      valid: false,
-      error: 'Username can only contain letters, numbers, hyphens, and underscores',
-    };
-  }
-
-  if (!/[a-zA-Z0-9]/.test(username)) {
-    return {
-      valid: false,
-      error: 'Username must contain at least one letter or number',
-    };
-  }
-
-  if (username.startsWith('-') || username.endsWith('-')) {
-    return { valid: false, error: 'Username cannot start or end with a hyphen' };
-  }
-
-  if (username.includes('--')) {
-    return {
-      valid: false,
-      error: 'Username cannot contain consecutive hyphens',
-    };
-  }
-
-  return { valid: true };
-}
-
 function isBlockedEmailDomain(email: string): boolean {
   const domain = email.split('@')[1]?.toLowerCase();
   if (!domain) return true;
@@ -131,6 +91,7 @@ export const auth = betterAuth({
       account: accounts,
       verification: verifications,
       passkey: passkeys,
+      twoFactor: twoFactors,
       jwks: jwks,
       oauthClient: oauthClients,
       oauthRefreshToken: oauthRefreshTokens,
@@ -141,14 +102,15 @@ export const auth = betterAuth({
   secondaryStorage: redis
     ? {
         get: async (key) => {
-          return await redis.get(key);
+          return await traceSpan('redis.get', { store: 'auth' }, () => redis.get(key));
         },
         set: async (key, value, ttl) => {
-          if (ttl) await redis.set(key, value, { EX: ttl });
-          else await redis.set(key, value);
+          await traceSpan('redis.set', { store: 'auth' }, () =>
+            ttl ? redis.set(key, value, { EX: ttl }) : redis.set(key, value),
+          );
         },
         delete: async (key) => {
-          await redis.del(key);
+          await traceSpan('redis.del', { store: 'auth' }, () => redis.del(key));
         },
       }
     : undefined,
@@ -222,6 +184,9 @@ export const auth = betterAuth({
       },
     }),
     expo(),
+    twoFactor({
+      issuer: 'gitbruv',
+    }),
     passkey({
       rpID: new URL(getWebUrl()).hostname,
       rpName: 'gitbruv',
@@ -264,7 +229,7 @@ export const auth = betterAuth({
 
           const username = (user as { username?: string }).username;
           if (username) {
-            const validation = isValidUsername(username);
+            const validation = validateUsername(username);
             if (!validation.valid) {
               throw new APIError('BAD_REQUEST', {
                 message: validation.error,
@@ -274,6 +239,10 @@ export const auth = betterAuth({
 
           return { data: user };
         },
+        after: async (user) => {
+          const username = (user as { username?: string }).username;
+          if (username) await releaseUsername(username);
+        },
       },
     },
   },
@@ -304,23 +273,19 @@ export const getAuth = () => {
   return authInstance;
 };
 
-export async function verifyCredentials(request: Request): Promise<Response> {
+// Failures are thrown as ApiError so the route answers with the same error body as every other
+// endpoint.
+export async function verifyCredentials(request: Request): Promise<any> {
   const secret = config.betterAuthSecret;
   if (!secret) {
     console.error('[API] verify-credentials: missing BETTER_AUTH_SECRET');
-    return new Response(JSON.stringify({ error: 'Server misconfigured' }), {
-      status: 500,
-      headers: { 'Content-Type': 'application/json' },
-    });
+    throw new ApiError(500, 'Server misconfigured');
   }
 
   const provided = request.headers.get('x-internal-auth');
   if (!provided || provided !== secret) {
     console.warn('[API] verify-credentials: invalid internal auth header');
-    return new Response(JSON.stringify({ error: 'Unauthorized' }), {
-      status: 401,
-      headers: { 'Content-Type': 'application/json' },
-    });
+    throw new ApiError(401, 'Unauthorized');
   }
 
   let body: { email?: string; password?: string } | null = null;
@@ -343,10 +308,7 @@ export async function verifyCredentials(request: Request): Promise<Response> {
     !email.includes('@')
   ) {
     console.warn('[API] verify-credentials: invalid body', { email: safeEmail });
-    return new Response(JSON.stringify({ error: 'Invalid credentials' }), {
-      status: 400,
-      headers: { 'Content-Type': 'application/json' },
-    });
+    throw new ApiError(400, 'Invalid credentials');
   }
 
   const auth = getAuth();
@@ -360,6 +322,11 @@ export async function verifyCredentials(request: Request): Promise<Response> {
       },
       headers: request.headers,
     });
+    if (result?.twoFactorRedirect) {
+      console.warn(`[API] verify-credentials: password sign-in rejected for 2FA account`, {
+        email: safeEmail,
+      });
+    }
     user = result?.user ?? result?.session?.user ?? null;
     if (user) {
       console.info(`[API] verify-credentials: sign-in successful`, {
@@ -378,21 +345,10 @@ export async function verifyCredentials(request: Request): Promise<Response> {
   }
 
   if (!user) {
-    return new Response(JSON.stringify({ error: 'Unauthorized' }), {
-      status: 401,
-      headers: { 'Content-Type': 'application/json' },
-    });
+    throw new ApiError(401, 'Unauthorized');
   }
 
-  return new Response(
-    JSON.stringify({
-      user,
-    }),
-    {
-      status: 200,
-      headers: { 'Content-Type': 'application/json' },
-    },
-  );
+  return user;
 }
 
 export type Session = Awaited<ReturnType<ReturnType<typeof betterAuth>['api']['getSession']>>;
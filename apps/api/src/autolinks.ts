@@ -0,0 +1,105 @@
+import type { ExternalReference } from "@gitbruv/db";
+
+export const MAX_EXTERNAL_REFERENCES = 20;
+const URL_PLACEHOLDER = "<num>";
+
+export type RenderedLink = {
+  text: string;
+  url: string;
+  start: number;
+  end: number;
+};
+
+function escapeRegExp(value: string) {
+  return value.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+}
+
+export function validateExternalReferences(input: unknown): { value: ExternalReference[] } | { error: string } {
+  if (!Array.isArray(input)) {
+    return { error: "External references must be an array" };
+  }
+  if (input.length > MAX_EXTERNAL_REFERENCES) {
+    return { error: `A repository can have at most ${MAX_EXTERNAL_REFERENCES} external references` };
+  }
+
+  const value: ExternalReference[] = [];
+  const prefixes = new Set<string>();
+
+  for (const entry of input) {
+    const prefix = typeof entry?.prefix === "string" ? entry.prefix.trim() : "";
+    const urlTemplate = typeof entry?.urlTemplate === "string" ? entry.urlTemplate.trim() : "";
+
+    if (!/^[A-Za-z][A-Za-z0-9_.\-/#]{0,31}$/.test(prefix)) {
+      return { error: `Invalid reference prefix "${prefix}"` };
+    }
+    if (prefixes.has(prefix.toLowerCase())) {
+      return { error: `Duplicate reference prefix "${prefix}"` };
+    }
+    if (!urlTemplate.includes(URL_PLACEHOLDER)) {
+      return { error: `URL template for "${prefix}" must contain ${URL_PLACEHOLDER}` };
+    }
+    try {
+      const url = new URL(urlTemplate.replace(URL_PLACEHOLDER, "1"));
+      if (url.protocol !== "http:" && url.protocol !== "https:") throw new Error();
+    } catch {
+      return { error: `Invalid URL template for "${prefix}"` };
+    }
+
+    prefixes.add(prefix.toLowerCase());
+    value.push({ prefix, urlTemplate, alphanumeric: entry?.alphanumeric === true });
+  }
+
+  return { value };
+}
+
+// Ranges covered by fenced code blocks, inline code and existing markdown links are left untouched.
+function protectedRanges(text: string): [number, number][] {
+  const ranges: [number, number][] = [];
+  for (const match of text.matchAll(/```[\s\S]*?(?:```|$)|`[^`\n]*`|\[[^\]\n]*\]\([^)\n]*\)|<?https?:\/\/[^\s>)]+>?/g)) {
+    ranges.push([match.index!, match.index! + match[0].length]);
+  }
+  return ranges;
+}
+
+export function findReferenceLinks(
+  text: string,
+  references: ExternalReference[],
+  issueBaseUrl?: string
+): RenderedLink[] {
+  const ranges = protectedRanges(text);
+  const isProtected = (start: number, end: number) => ranges.some(([from, to]) => start < to && end > from);
+
+  const links: RenderedLink[] = [];
+  const patterns = references.map((reference) => ({
+    regex: new RegExp(
+      `(?<![\\w/])${escapeRegExp(reference.prefix)}(${reference.alphanumeric ? "[A-Za-z0-9]+" : "\\d+"})(?![\\w])`,
+      "g"
+    ),
+    urlTemplate: reference.urlTemplate,
+  }));
+
+  if (issueBaseUrl) {
+    patterns.push({ regex: /(?<![\w/&#])#(\d+)\b/g, urlTemplate: `${issueBaseUrl}/${URL_PLACEHOLDER}` });
+  }
+
+  for (const { regex, urlTemplate } of patterns) {
+    for (const match of text.matchAll(regex)) {
+      const start = match.index!;
+      const end = start + match[0].length;
+      if (isProtected(start, end) || links.some((link) => start < link.end && end > link.start)) continue;
+      links.push({ text: match[0], url: urlTemplate.replaceAll(URL_PLACEHOLDER, encodeURIComponent(match[1])), start, end });
+    }
+  }
+
+  return links.sort((a, b) => a.start - b.start);
+}
+
+export function applyReferenceLinks(text: string, links: RenderedLink[]): string {
+  let output = "";
+  let cursor = 0;
+  for (const link of links) {
+    output += text.slice(cursor, link.start) + `[${link.text}](${link.url})`;
+    cursor = link.end;
+  }
+  return output + text.slice(cursor);
+}
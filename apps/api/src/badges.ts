@@ -0,0 +1,67 @@
+import { createHash } from "crypto";
+
+export const BADGE_COLORS = {
+  success: "#4c1",
+  failure: "#e05d44",
+  pending: "#dfb317",
+  info: "#007ec6",
+  inactive: "#9f9f9f",
+} as const;
+
+// Rough per-character advance for 11px Verdana, the shields.io face. Close enough that labels
+// don't clip without shipping font metrics.
+function textWidth(text: string) {
+  let width = 0;
+  for (const char of text) {
+    if ("il.,:;|!'".includes(char)) width += 3.5;
+    else if ("mwMW".includes(char)) width += 10;
+    else if (char >= "A" && char <= "Z") width += 7.5;
+    else width += 6.5;
+  }
+  return Math.ceil(width);
+}
+
+function escapeXml(value: string) {
+  return value.replace(/&/g, "&amp;").replace(/</g, "&lt;").replace(/>/g, "&gt;").replace(/"/g, "&quot;");
+}
+
+export function renderBadge(label: string, message: string, color: string) {
+  const labelWidth = textWidth(label) + 10;
+  const messageWidth = textWidth(message) + 10;
+  const width = labelWidth + messageWidth;
+  const safeLabel = escapeXml(label);
+  const safeMessage = escapeXml(message);
+
+  return `<svg xmlns="http://www.w3.org/2000/svg" width="${width}" height="20" role="img" aria-label="${safeLabel}: ${safeMessage}">
+  <title>${safeLabel}: ${safeMessage}</title>
+  <linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+  <clipPath id="r"><rect width="${width}" height="20" rx="3" fill="#fff"/></clipPath>
+  <g clip-path="url(#r)">
+    <rect width="${labelWidth}" height="20" fill="#555"/>
+    <rect x="${labelWidth}" width="${messageWidth}" height="20" fill="${color}"/>
+    <rect width="${width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="${labelWidth / 2}" y="15" fill="#010101" fill-opacity=".3">${safeLabel}</text>
+    <text x="${labelWidth / 2}" y="14">${safeLabel}</text>
+    <text x="${labelWidth + messageWidth / 2}" y="15" fill="#010101" fill-opacity=".3">${safeMessage}</text>
+    <text x="${labelWidth + messageWidth / 2}" y="14">${safeMessage}</text>
+  </g>
+</svg>
+`;
+}
+
+// The ETag only depends on what the badge says, so it stays valid across deploys and cache
+// flushes for as long as the underlying value doesn't change.
+export function badgeEtag(svg: string) {
+  return `"${createHash("sha1").update(svg).digest("hex").slice(0, 16)}"`;
+}
+
+// Picks the highest version-looking tag ("v1.10.0" beats "v1.9.2"), falling back to plain
+// ordering for tags that aren't versions at all.
+export function latestTag(tags: string[]): string | null {
+  if (tags.length === 0) return null;
+  const versioned = tags.filter((tag) => /^v?\d/.test(tag));
+  const candidates = versioned.length > 0 ? versioned : tags;
+  return [...candidates].sort((a, b) => b.localeCompare(a, undefined, { numeric: true }))[0];
+}
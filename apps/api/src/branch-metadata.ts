@@ -0,0 +1,107 @@
+import { db, repoBranchMetadata } from "@gitbruv/db";
+import { and, eq } from "drizzle-orm";
+import git from "isomorphic-git";
+import { getCommitByOid, getCommitCountCached, getTree, listBranches, repoCache, resolveCommittish, type GitStore } from "./git";
+import { lookupCommitCount } from "./jobs/commit-counts";
+
+export async function countCommits(repoId: string, store: GitStore, ref: string): Promise<number> {
+  const tip = await resolveCommittish(store.fs, store.dir, ref);
+  if (!tip) return 0;
+  const count = await lookupCommitCount({ id: repoId, ownerId: store.ownerId, name: store.repoName }, store, tip);
+  return count ?? getCommitCountCached(store, tip);
+}
+
+// `parentOid` lets a single new commit on top of the stored head bump the count instead of
+// recounting; pass null to recount from scratch.
+export async function refreshBranchMetadata(repoId: string, store: GitStore, branch: string, commitOid: string, parentOid: string | null) {
+  const [existing, commit, rootTree] = await Promise.all([
+    db.query.repoBranchMetadata.findFirst({
+      where: and(eq(repoBranchMetadata.repoId, repoId), eq(repoBranchMetadata.branch, branch)),
+    }),
+    getCommitByOid(store.fs, store.dir, commitOid),
+    getTree(store.fs, store.dir, branch, ""),
+  ]);
+  if (!commit || !rootTree) return false;
+
+  const commitCount =
+    existing && parentOid && existing.headOid === parentOid
+      ? existing.commitCount + 1
+      : await countCommits(repoId, store, commitOid);
+
+  const values = {
+    headOid: commitOid,
+    commitCount,
+    lastCommitOid: commitOid,
+    lastCommitMessage: commit.commit.message,
+    lastCommitAuthorName: commit.commit.author.name,
+    lastCommitAuthorEmail: commit.commit.author.email,
+    lastCommitTimestamp: new Date(commit.commit.timestamp),
+    readmeOid: rootTree.find((f) => f.name.toLowerCase() === "readme.md" && f.type === "blob")?.oid || null,
+    rootTree: rootTree.map((f) => ({ name: f.name, type: f.type, oid: f.oid, path: f.path, mode: f.mode, submoduleUrl: f.submoduleUrl })),
+    updatedAt: new Date(),
+  };
+
+  await db
+    .insert(repoBranchMetadata)
+    .values({ repoId, branch, ...values })
+    .onConflictDoUpdate({ target: [repoBranchMetadata.repoId, repoBranchMetadata.branch], set: values });
+  return true;
+}
+
+export type MetadataRepair = "current" | "rebuilt" | "removed" | "failed";
+
+// Brings a branch's row in line with the ref in storage: rebuilt when the head moved or the row
+// is incomplete, removed when the branch is gone. With `force` the row is rebuilt regardless.
+export async function repairBranchMetadata(
+  repoId: string,
+  store: GitStore,
+  branch: string,
+  options: { force?: boolean } = {}
+): Promise<MetadataRepair> {
+  const [row, tip] = await Promise.all([
+    db.query.repoBranchMetadata.findFirst({
+      where: and(eq(repoBranchMetadata.repoId, repoId), eq(repoBranchMetadata.branch, branch)),
+    }),
+    resolveCommittish(store.fs, store.dir, branch),
+  ]);
+
+  if (!tip) {
+    if (!row) return "current";
+    await db.delete(repoBranchMetadata).where(and(eq(repoBranchMetadata.repoId, repoId), eq(repoBranchMetadata.branch, branch)));
+    return "removed";
+  }
+
+  if (!options.force && row && row.headOid === tip && !(await isIncomplete(store, row))) {
+    return "current";
+  }
+
+  return (await refreshBranchMetadata(repoId, store, branch, tip, null)) ? "rebuilt" : "failed";
+}
+
+// Every branch in storage or in the table, rebuilt from its ref, then the repository's caches
+// dropped. For when the refs changed underneath the metadata wholesale.
+export async function rebuildRepoMetadata(repoId: string, store: GitStore): Promise<Record<MetadataRepair, string[]>> {
+  const stored = await db
+    .select({ branch: repoBranchMetadata.branch })
+    .from(repoBranchMetadata)
+    .where(eq(repoBranchMetadata.repoId, repoId));
+  const branches = [...new Set([...(await listBranches(store.fs, store.dir)), ...stored.map((row) => row.branch)])];
+
+  const outcomes: Record<MetadataRepair, string[]> = { current: [], rebuilt: [], removed: [], failed: [] };
+  for (const branch of branches) {
+    outcomes[await repairBranchMetadata(repoId, store, branch, { force: true })].push(branch);
+  }
+  await repoCache.invalidateRepo(store.ownerId, store.repoName);
+  return outcomes;
+}
+
+const EMPTY_TREE_OID = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+// A root tree that was never stored, or stored empty for a commit whose tree isn't, comes from a
+// build that gave up partway.
+async function isIncomplete(store: GitStore, row: typeof repoBranchMetadata.$inferSelect) {
+  if (!row.rootTree) return true;
+  if (row.rootTree.length > 0) return false;
+  const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: row.headOid });
+  return commit.tree !== EMPTY_TREE_OID;
+}
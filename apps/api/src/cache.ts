@@ -1,5 +1,6 @@
 import { createClient, type RedisClientType } from "redis";
 import { config } from "./config";
+import { traceSpan } from "./tracing";
 
 let redis: RedisClientType | null = null;
 let connectionAttempted = false;
@@ -39,18 +40,26 @@ export const CACHE_TTL = {
   tree: 60 * 30,
   file: 60 * 60,
   commits: 60 * 10,
+  overview: 60 * 10,
+  badge: 60 * 60 * 24,
+  pack: 60 * 60,
+  highlight: 60 * 60 * 24 * 7,
 } as const;
 
 function cacheKey(type: string, ...parts: string[]): string {
   return `gitbruv:${type}:${parts.join(":")}`;
 }
 
+export function highlightCacheKey(oid: string, language: string, theme: string, format: string): string {
+  return cacheKey("highlight", oid, language, theme, format);
+}
+
 export async function getCached<T>(key: string): Promise<T | null> {
   const client = await getRedisClient();
   if (!client) return null;
 
   try {
-    const data = await client.get(key);
+    const data = await traceSpan("redis.get", { key }, () => client.get(key));
     if (data) {
       return JSON.parse(data) as T;
     }
@@ -64,7 +73,7 @@ export async function setCache<T>(key: string, value: T, ttl: number): Promise<v
   if (!client) return;
 
   try {
-    await client.set(key, JSON.stringify(value), { EX: ttl });
+    await traceSpan("redis.set", { key }, () => client.set(key, JSON.stringify(value), { EX: ttl }));
   } catch {
   }
 }
@@ -74,21 +83,65 @@ export async function deleteCache(key: string): Promise<void> {
   if (!client) return;
 
   try {
-    await client.del(key);
+    await traceSpan("redis.del", { key }, () => client.del(key));
   } catch {
   }
 }
 
-export async function deleteCachePattern(pattern: string): Promise<void> {
+// Keys in `keep` survive even when they match.
+export async function deleteCachePattern(pattern: string, keep: string[] = []): Promise<void> {
   const client = await getRedisClient();
   if (!client) return;
 
+  // SCAN rather than KEYS so invalidating a large repository doesn't block Redis for every
+  // other request while the keyspace is walked.
   try {
-    const keys = await client.keys(pattern);
-    if (keys.length > 0) {
-      await client.del(keys);
-    }
+    await traceSpan("redis.scan", { pattern }, async () => {
+      for await (const scanned of client.scanIterator({ MATCH: pattern, COUNT: 500 })) {
+        const keys = keep.length > 0 ? scanned.filter((key) => !keep.includes(key)) : scanned;
+        if (keys.length > 0) {
+          await client.del(keys);
+        }
+      }
+    });
+  } catch {
+  }
+}
+
+async function getCounter(key: string): Promise<number> {
+  const client = await getRedisClient();
+  if (!client) return 0;
+
+  try {
+    const value = await traceSpan("redis.get", { key }, () => client.get(key));
+    return value ? Number(value) : 0;
   } catch {
+    return 0;
+  }
+}
+
+async function incrementCounter(key: string): Promise<void> {
+  const client = await getRedisClient();
+  if (!client) return;
+
+  try {
+    await traceSpan("redis.incr", { key }, () => client.incr(key));
+  } catch {
+  }
+}
+
+// Counts hits within a window that starts at the first one; returns 0 when Redis is unavailable.
+export async function countHit(key: string, windowSeconds: number): Promise<number> {
+  const client = await getRedisClient();
+  if (!client) return 0;
+
+  try {
+    return await traceSpan("redis.incr", { key }, async () => {
+      const [count] = await client.multi().incr(key).expire(key, windowSeconds, "NX").exec();
+      return Number(count);
+    });
+  } catch {
+    return 0;
   }
 }
 
@@ -96,8 +149,8 @@ export const repoCache = {
   branchesKey: (userId: string, repoName: string) =>
     cacheKey("branches", userId, repoName),
 
-  commitsKey: (userId: string, repoName: string, branch: string, limit: number, skip: number) =>
-    cacheKey("commits", userId, repoName, branch, String(limit), String(skip)),
+  commitsKey: (userId: string, repoName: string, tipOid: string, limit: number, skip: number) =>
+    cacheKey("commits", userId, repoName, tipOid, String(limit), String(skip)),
 
   commitCountKey: (userId: string, repoName: string, branch: string) =>
     cacheKey("commit-count", userId, repoName, branch),
@@ -111,19 +164,49 @@ export const repoCache = {
   refKey: (userId: string, repoName: string, ref: string) =>
     cacheKey("ref", userId, repoName, ref),
 
+  overviewKey: (userId: string, repoName: string, branch: string) =>
+    cacheKey("overview", userId, repoName, branch),
+
+  refsVersionKey: (userId: string, repoName: string) =>
+    cacheKey("refs-version", userId, repoName),
+
+  refsAdvertisementKey: (userId: string, repoName: string, service: string, version: number) =>
+    cacheKey("refs-advertisement", userId, repoName, service, String(version)),
+
+  badgeKey: (userId: string, repoName: string, badge: string, refsVersion: number) =>
+    cacheKey("badge", userId, repoName, badge, String(refsVersion)),
+
+  packKey: (userId: string, repoName: string, digest: string) =>
+    cacheKey("pack", userId, repoName, digest),
+
+  packHitsKey: (userId: string, repoName: string, digest: string) =>
+    cacheKey("pack-hits", userId, repoName, digest),
+
+  getRefsVersion(userId: string, repoName: string): Promise<number> {
+    return getCounter(repoCache.refsVersionKey(userId, repoName));
+  },
+
+  // Advertisements are keyed by this counter rather than deleted, so a fetch that read the refs
+  // just before a push can only write its stale copy under a version nobody will ask for again.
+  async bumpRefsVersion(userId: string, repoName: string): Promise<void> {
+    await incrementCounter(repoCache.refsVersionKey(userId, repoName));
+  },
+
+  // The refs-version counter matches the second pattern but is only ever incremented: deleting it
+  // would restart it at 1 and bring back advertisements written under the old versions.
   async invalidateRepo(userId: string, repoName: string): Promise<void> {
     await deleteCachePattern(`gitbruv:*:${userId}:${repoName}:*`);
-    await deleteCachePattern(`gitbruv:*:${userId}:${repoName}`);
-
+    await deleteCachePattern(`gitbruv:*:${userId}:${repoName}`, [repoCache.refsVersionKey(userId, repoName)]);
+    await repoCache.bumpRefsVersion(userId, repoName);
   },
 
   async invalidateBranch(userId: string, repoName: string, branch: string): Promise<void> {
-    await deleteCachePattern(`gitbruv:commits:${userId}:${repoName}:${branch}:*`);
     await deleteCache(repoCache.commitCountKey(userId, repoName, branch));
     await deleteCachePattern(`gitbruv:tree:${userId}:${repoName}:${branch}:*`);
     await deleteCachePattern(`gitbruv:file:${userId}:${repoName}:${branch}:*`);
     await deleteCache(repoCache.refKey(userId, repoName, branch));
     await deleteCache(repoCache.branchesKey(userId, repoName));
-
+    await deleteCache(repoCache.overviewKey(userId, repoName, branch));
+    await repoCache.bumpRefsVersion(userId, repoName);
   },
 };
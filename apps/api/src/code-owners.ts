@@ -0,0 +1,85 @@
+import { db, users, codeOwnerRules } from "@gitbruv/db";
+import { eq, asc, inArray, sql } from "drizzle-orm";
+
+export const CODEOWNERS_PATHS = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+export type CodeOwnerRule = { pattern: string; owners: string[] };
+
+// Owners are kept as usernames without the leading "@". Team references ("@org/team") and
+// email owners have nothing to map to here and are dropped; a rule left with no owners still
+// counts, since in CODEOWNERS it un-assigns paths an earlier rule matched.
+export function parseCodeOwners(content: string): CodeOwnerRule[] {
+  const rules: CodeOwnerRule[] = [];
+  for (const rawLine of content.split("\n")) {
+    const line = rawLine.replace(/(^|\s)#.*$/, "").trim();
+    if (!line) continue;
+    const [pattern, ...tokens] = line.split(/\s+/);
+    const owners = tokens.filter((token) => /^@[A-Za-z0-9_.-]+$/.test(token)).map((token) => token.slice(1));
+    rules.push({ pattern, owners });
+  }
+  return rules;
+}
+
+function escapeRegex(value: string) {
+  return value.replace(/[.+^${}()|[\]\\]/g, "\\$&");
+}
+
+// gitignore-style matching: a leading or inner slash anchors the pattern to the repository root,
+// otherwise it matches at any depth, and a pattern that names a directory covers everything in it.
+// CODEOWNERS differs in one place: a trailing "/*" (`docs/*`) only covers the files directly in
+// that directory, not ones nested deeper, which `codeOwners` turns on.
+export function patternToRegex(pattern: string, codeOwners = false) {
+  const anchored = pattern.startsWith("/") || pattern.slice(0, -1).includes("/");
+  let body = pattern.replace(/^\//, "").replace(/\/$/, "");
+  body = escapeRegex(body)
+    .replace(/\*\*\//g, "\u0000")
+    .replace(/\/\*\*/g, "\u0001")
+    .replace(/\*/g, "[^/]*")
+    .replace(/\?/g, "[^/]")
+    .replace(/\u0000/g, "(?:.*/)?")
+    .replace(/\u0001/g, "(?:/.*)?");
+  const oneLevel = codeOwners && pattern.endsWith("/*");
+  return new RegExp(`^${anchored ? "" : "(?:.*/)?"}${body}${oneLevel ? "" : "(?:/.*)?"}$`);
+}
+
+export function ownersForPaths(rules: CodeOwnerRule[], paths: string[]): Set<string> {
+  const compiled = rules.map((rule) => ({ regex: patternToRegex(rule.pattern, true), owners: rule.owners }));
+  const owners = new Set<string>();
+  for (const path of paths) {
+    let match: string[] | null = null;
+    for (const rule of compiled) {
+      if (rule.regex.test(path)) match = rule.owners;
+    }
+    for (const owner of match ?? []) owners.add(owner.toLowerCase());
+  }
+  return owners;
+}
+
+export async function replaceCodeOwnerRules(repositoryId: string, rules: CodeOwnerRule[]) {
+  await db.transaction(async (tx) => {
+    await tx.delete(codeOwnerRules).where(eq(codeOwnerRules.repositoryId, repositoryId));
+    if (rules.length > 0) {
+      await tx.insert(codeOwnerRules).values(rules.map((rule, position) => ({ repositoryId, position, ...rule })));
+    }
+  });
+}
+
+// Resolves the users who own any of `paths`, leaving out `excludeUserId` (the pull request's
+// author, who can't review their own change).
+export async function getCodeOwnerReviewers(repositoryId: string, paths: string[], excludeUserId: string) {
+  if (paths.length === 0) return [];
+
+  const rules = await db
+    .select({ pattern: codeOwnerRules.pattern, owners: codeOwnerRules.owners })
+    .from(codeOwnerRules)
+    .where(eq(codeOwnerRules.repositoryId, repositoryId))
+    .orderBy(asc(codeOwnerRules.position));
+  const usernames = [...ownersForPaths(rules, paths)];
+  if (usernames.length === 0) return [];
+
+  const rows = await db
+    .select({ id: users.id, username: users.username })
+    .from(users)
+    .where(inArray(sql`lower(${users.username})`, usernames));
+  return rows.filter((row) => row.id !== excludeUserId).map((row) => row.id);
+}
@@ -0,0 +1,36 @@
+import { db, commitStatuses } from "@gitbruv/db";
+import { eq, and, inArray } from "drizzle-orm";
+
+export const COMMIT_STATUS_STATES = ["pending", "success", "failure", "error"] as const;
+
+export type CommitStatusState = (typeof COMMIT_STATUS_STATES)[number];
+
+export function combineStatusStates(states: CommitStatusState[]): "pending" | "success" | "failure" | null {
+  if (states.length === 0) return null;
+  if (states.some((state) => state === "failure" || state === "error")) return "failure";
+  if (states.some((state) => state === "pending")) return "pending";
+  return "success";
+}
+
+export async function getCombinedStatuses(repoId: string, oids: string[]): Promise<Map<string, "pending" | "success" | "failure">> {
+  if (oids.length === 0) return new Map();
+
+  const rows = await db
+    .select({ commitOid: commitStatuses.commitOid, state: commitStatuses.state })
+    .from(commitStatuses)
+    .where(and(eq(commitStatuses.repositoryId, repoId), inArray(commitStatuses.commitOid, oids)));
+
+  const statesByOid = new Map<string, CommitStatusState[]>();
+  for (const row of rows) {
+    const states = statesByOid.get(row.commitOid) || [];
+    states.push(row.state);
+    statesByOid.set(row.commitOid, states);
+  }
+
+  const combined = new Map<string, "pending" | "success" | "failure">();
+  for (const [oid, states] of statesByOid) {
+    const state = combineStatusStates(states);
+    if (state) combined.set(oid, state);
+  }
+  return combined;
+}
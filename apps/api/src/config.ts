@@ -1,4 +1,5 @@
 import { normalizeUrl } from '@gitbruv/lib';
+import { existsSync, readFileSync } from 'fs';
 
 const baseOrigins = [
   'http://localhost:3000',
@@ -9,28 +10,333 @@ const baseOrigins = [
   'exp://192.168.*.*:8081',
 ];
 
-export const config = {
-  port: parseInt(process.env.PORT || '3001', 10),
-  databaseUrl: process.env.DATABASE_URL!,
-  redisUrl: process.env.REDIS_URL,
+type Config = {
+  port: number;
+  databaseUrl: string;
+  redisUrl?: string;
+  storage: {
+    backend: 's3' | 'local';
+    localPath: string;
+  };
   s3: {
-    endpoint: process.env.S3_ENDPOINT || 'https://storage.railway.app',
-    region: process.env.S3_REGION || 'auto',
-    accessKeyId: process.env.S3_ACCESS_KEY_ID!,
-    secretAccessKey: process.env.S3_SECRET_ACCESS_KEY!,
-    bucket: process.env.S3_BUCKET!,
+    endpoint: string;
+    region: string;
+    accessKeyId: string;
+    secretAccessKey: string;
+    bucket: string;
+    objectTagging: boolean;
+    maxAttempts: number;
+    timeouts: { readMs: number; writeMs: number; listMs: number };
+    circuitBreaker: { failureThreshold: number; cooldownMs: number };
+  };
+  betterAuthSecret: string;
+  nodeEnv: string;
+  apiUrl: string;
+  webUrl: string;
+  expoPublicApiUrl: string;
+  email: {
+    resendApiKey?: string;
+    fromAddress: string;
+  };
+  git: {
+    maxPackBytes: number;
+    maxBlobBytes: number;
+    warnBlobBytes: number;
+    fsckAfterPush: boolean;
+    objectCacheBytes: number;
+    packCacheMinRequests: number;
+    packCacheMaxBytes: number;
+  };
+  trace: {
+    enabled: boolean;
+    slowSpanMs: number;
+  };
+  webPush: {
+    vapidPublicKey?: string;
+    vapidPrivateKey?: string;
+    subject: string;
+  };
+};
+
+const defaults: Config = {
+  port: 3001,
+  databaseUrl: '',
+  storage: {
+    backend: 's3',
+    localPath: './data/storage',
   },
-  betterAuthSecret: process.env.BETTER_AUTH_SECRET!,
-  nodeEnv: process.env.RAILWAY_ENVIRONMENT_NAME || 'development',
-  apiUrl: process.env.RAILWAY_PUBLIC_DOMAIN || 'localhost:3001',
-  webUrl: process.env.WEB_URL || 'localhost:3000',
-  expoPublicApiUrl: process.env.EXPO_PUBLIC_API_URL!,
+  s3: {
+    endpoint: 'https://storage.railway.app',
+    region: 'auto',
+    accessKeyId: '',
+    secretAccessKey: '',
+    bucket: '',
+    objectTagging: false,
+    maxAttempts: 3,
+    timeouts: { readMs: 10_000, writeMs: 60_000, listMs: 15_000 },
+    circuitBreaker: { failureThreshold: 5, cooldownMs: 30_000 },
+  },
+  betterAuthSecret: '',
+  nodeEnv: 'development',
+  apiUrl: 'localhost:3001',
+  webUrl: 'localhost:3000',
+  expoPublicApiUrl: '',
   email: {
-    resendApiKey: process.env.RESEND_API_KEY,
-    fromAddress: process.env.EMAIL_FROM || 'GitBruv <noreply@gitbruv.dev>',
+    fromAddress: 'GitBruv <noreply@gitbruv.dev>',
+  },
+  git: {
+    maxPackBytes: 2 * 1024 * 1024 * 1024,
+    maxBlobBytes: 100 * 1024 * 1024,
+    warnBlobBytes: 50 * 1024 * 1024,
+    fsckAfterPush: false,
+    objectCacheBytes: 128 * 1024 * 1024,
+    packCacheMinRequests: 2,
+    packCacheMaxBytes: 512 * 1024 * 1024,
+  },
+  trace: {
+    enabled: false,
+    slowSpanMs: 500,
+  },
+  webPush: {
+    subject: 'mailto:noreply@gitbruv.dev',
   },
 };
 
+type EnvType = 'string' | 'integer' | 'boolean';
+
+// Environment variables win over config.toml, which wins over the defaults above. Later names
+// in a list are fallbacks (S3_BUCKET_NAME is what older .env files and the README use).
+const envOverrides: [string, string[], EnvType][] = [
+  ['port', ['PORT'], 'integer'],
+  ['databaseUrl', ['DATABASE_URL'], 'string'],
+  ['redisUrl', ['REDIS_URL'], 'string'],
+  ['storage.backend', ['STORAGE_BACKEND'], 'string'],
+  ['storage.localPath', ['STORAGE_LOCAL_PATH'], 'string'],
+  ['s3.endpoint', ['S3_ENDPOINT'], 'string'],
+  ['s3.region', ['S3_REGION'], 'string'],
+  ['s3.accessKeyId', ['S3_ACCESS_KEY_ID'], 'string'],
+  ['s3.secretAccessKey', ['S3_SECRET_ACCESS_KEY'], 'string'],
+  ['s3.bucket', ['S3_BUCKET', 'S3_BUCKET_NAME'], 'string'],
+  ['s3.objectTagging', ['S3_OBJECT_TAGGING'], 'boolean'],
+  ['s3.maxAttempts', ['S3_MAX_ATTEMPTS'], 'integer'],
+  ['s3.timeouts.readMs', ['S3_READ_TIMEOUT_MS'], 'integer'],
+  ['s3.timeouts.writeMs', ['S3_WRITE_TIMEOUT_MS'], 'integer'],
+  ['s3.timeouts.listMs', ['S3_LIST_TIMEOUT_MS'], 'integer'],
+  ['s3.circuitBreaker.failureThreshold', ['S3_BREAKER_FAILURE_THRESHOLD'], 'integer'],
+  ['s3.circuitBreaker.cooldownMs', ['S3_BREAKER_COOLDOWN_MS'], 'integer'],
+  ['betterAuthSecret', ['BETTER_AUTH_SECRET'], 'string'],
+  ['nodeEnv', ['RAILWAY_ENVIRONMENT_NAME'], 'string'],
+  ['apiUrl', ['RAILWAY_PUBLIC_DOMAIN'], 'string'],
+  ['webUrl', ['WEB_URL'], 'string'],
+  ['expoPublicApiUrl', ['EXPO_PUBLIC_API_URL'], 'string'],
+  ['email.resendApiKey', ['RESEND_API_KEY'], 'string'],
+  ['email.fromAddress', ['EMAIL_FROM'], 'string'],
+  ['git.maxPackBytes', ['GIT_MAX_PACK_BYTES'], 'integer'],
+  ['git.maxBlobBytes', ['GIT_MAX_BLOB_BYTES'], 'integer'],
+  ['git.warnBlobBytes', ['GIT_WARN_BLOB_BYTES'], 'integer'],
+  ['git.fsckAfterPush', ['GIT_FSCK_AFTER_PUSH'], 'boolean'],
+  ['git.objectCacheBytes', ['GIT_OBJECT_CACHE_BYTES'], 'integer'],
+  ['git.packCacheMinRequests', ['GIT_PACK_CACHE_MIN_REQUESTS'], 'integer'],
+  ['git.packCacheMaxBytes', ['GIT_PACK_CACHE_MAX_BYTES'], 'integer'],
+  ['trace.enabled', ['TRACE_SPANS'], 'boolean'],
+  ['trace.slowSpanMs', ['TRACE_SLOW_SPAN_MS'], 'integer'],
+  ['webPush.vapidPublicKey', ['VAPID_PUBLIC_KEY'], 'string'],
+  ['webPush.vapidPrivateKey', ['VAPID_PRIVATE_KEY'], 'string'],
+  ['webPush.subject', ['VAPID_SUBJECT'], 'string'],
+];
+
+export type ConfigProblem = { key: string; source: string; message: string; severity: 'error' | 'warning' };
+
+// Problems found while loading (unparseable values, a broken config file) are kept and reported
+// by checkConfig alongside the validation results instead of throwing at import time.
+const loadProblems: ConfigProblem[] = [];
+
+function setPath(target: Record<string, any>, path: string, value: unknown) {
+  const keys = path.split('.');
+  let node = target;
+  for (const key of keys.slice(0, -1)) {
+    node[key] ??= {};
+    node = node[key];
+  }
+  node[keys[keys.length - 1]] = value;
+}
+
+function getPath(target: Record<string, any>, path: string): unknown {
+  return path.split('.').reduce<any>((node, key) => node?.[key], target);
+}
+
+function mergeInto(target: Record<string, any>, source: Record<string, any>, prefix = '') {
+  for (const [key, value] of Object.entries(source)) {
+    const path = prefix ? `${prefix}.${key}` : key;
+    const current = target[key];
+    if (value && typeof value === 'object' && !Array.isArray(value) && current && typeof current === 'object') {
+      mergeInto(current, value, path);
+    } else if (current !== undefined && value !== undefined && typeof current !== typeof value) {
+      loadProblems.push({ key: path, source: 'config file', message: `expected a ${typeof current}`, severity: 'error' });
+    } else {
+      target[key] = value;
+    }
+  }
+}
+
+function loadConfigFile(): Record<string, any> | null {
+  const path = process.env.CONFIG_FILE || 'config.toml';
+  if (!existsSync(path)) {
+    if (process.env.CONFIG_FILE) {
+      loadProblems.push({ key: 'CONFIG_FILE', source: 'env', message: `${path} does not exist`, severity: 'error' });
+    }
+    return null;
+  }
+  try {
+    return Bun.TOML.parse(readFileSync(path, 'utf8')) as Record<string, any>;
+  } catch (error) {
+    loadProblems.push({ key: path, source: 'config file', message: `could not be parsed: ${(error as Error).message}`, severity: 'error' });
+    return null;
+  }
+}
+
+function parseEnv(value: string, type: EnvType): unknown {
+  if (type === 'integer') {
+    return /^\d+$/.test(value.trim()) ? parseInt(value, 10) : undefined;
+  }
+  if (type === 'boolean') {
+    return value === 'true' ? true : value === 'false' || value === '' ? false : undefined;
+  }
+  return value;
+}
+
+function loadConfig(): Config {
+  const loaded: Config = structuredClone(defaults);
+
+  const file = loadConfigFile();
+  if (file) {
+    mergeInto(loaded, file);
+  }
+
+  for (const [path, names, type] of envOverrides) {
+    const name = names.find((candidate) => process.env[candidate] !== undefined && process.env[candidate] !== '');
+    if (!name) continue;
+    const value = parseEnv(process.env[name]!, type);
+    if (value === undefined) {
+      loadProblems.push({ key: path, source: name, message: `expected ${type === 'integer' ? 'a whole number' : "'true' or 'false'"}`, severity: 'error' });
+      continue;
+    }
+    setPath(loaded, path, value);
+  }
+
+  return loaded;
+}
+
+export const config = loadConfig();
+
+function sourceOf(path: string): string {
+  const entry = envOverrides.find(([key]) => key === path);
+  return entry ? entry[1][0] : path;
+}
+
+function checkUrl(value: string, protocols: string[]): string | null {
+  try {
+    const url = new URL(value);
+    return protocols.includes(url.protocol) ? null : `must use ${protocols.join(' or ')}`;
+  } catch {
+    return 'is not a valid URL';
+  }
+}
+
+// Everything wrong with the effective configuration. Errors are settings the API can't work
+// without (or that are malformed); warnings are features that will be turned off.
+export function checkConfig(): ConfigProblem[] {
+  const problems = [...loadProblems];
+  const production = config.nodeEnv === 'production';
+  const add = (path: string, message: string, severity: ConfigProblem['severity'] = 'error') =>
+    problems.push({ key: path, source: sourceOf(path), message, severity });
+
+  if (!config.databaseUrl) {
+    add('databaseUrl', 'is required');
+  } else {
+    const invalid = checkUrl(config.databaseUrl, ['postgres:', 'postgresql:']);
+    if (invalid) add('databaseUrl', invalid);
+  }
+
+  if (config.redisUrl) {
+    const invalid = checkUrl(config.redisUrl, ['redis:', 'rediss:']);
+    if (invalid) add('redisUrl', invalid);
+  } else {
+    add('redisUrl', 'is not set; caching is disabled', 'warning');
+  }
+
+  const s3Credentials = [config.s3.accessKeyId, config.s3.secretAccessKey, config.s3.bucket];
+  if (config.storage.backend !== 's3' && config.storage.backend !== 'local') {
+    add('storage.backend', 'must be "s3" or "local"');
+  } else if (config.storage.backend === 'local') {
+    if (!config.storage.localPath) add('storage.localPath', 'is required for local storage');
+  } else if (s3Credentials.every((value) => !value)) {
+    add('s3.bucket', 'S3 is not configured; repositories cannot be stored', production ? 'error' : 'warning');
+  } else {
+    for (const path of ['s3.accessKeyId', 's3.secretAccessKey', 's3.bucket']) {
+      if (!getPath(config, path)) add(path, 'is required when the other S3 settings are set');
+    }
+  }
+  const invalidEndpoint = checkUrl(config.s3.endpoint, ['http:', 'https:']);
+  if (invalidEndpoint) add('s3.endpoint', invalidEndpoint);
+
+  if (!config.betterAuthSecret) {
+    add('betterAuthSecret', 'is required to sign sessions', production ? 'error' : 'warning');
+  } else if (config.betterAuthSecret.length < 32) {
+    add('betterAuthSecret', 'should be at least 32 characters', production ? 'error' : 'warning');
+  }
+
+  for (const path of ['apiUrl', 'webUrl', 'expoPublicApiUrl']) {
+    const value = getPath(config, path) as string;
+    if (!value) continue;
+    const invalid = checkUrl(normalizeUrl(value), ['http:', 'https:']);
+    if (invalid) add(path, invalid);
+  }
+
+  if (Boolean(config.webPush.vapidPublicKey) !== Boolean(config.webPush.vapidPrivateKey)) {
+    add('webPush.vapidPrivateKey', 'VAPID_PUBLIC_KEY and VAPID_PRIVATE_KEY must be set together');
+  }
+
+  for (const path of [
+    'port',
+    'git.maxPackBytes',
+    'git.maxBlobBytes',
+    'git.warnBlobBytes',
+    'git.objectCacheBytes',
+    'git.packCacheMinRequests',
+    's3.maxAttempts',
+    's3.timeouts.readMs',
+    's3.timeouts.writeMs',
+    's3.timeouts.listMs',
+    's3.circuitBreaker.failureThreshold',
+  ]) {
+    if (!((getPath(config, path) as number) > 0)) add(path, 'must be greater than zero');
+  }
+
+  if (config.git.warnBlobBytes >= config.git.maxBlobBytes) {
+    add('git.warnBlobBytes', 'is not below git.maxBlobBytes; pushes are rejected before large files are warned about', 'warning');
+  }
+
+  return problems;
+}
+
+// Logs what's misconfigured, with the variable to set for each problem. Production refuses to
+// start on errors; development only logs them so a partial setup can still be run locally.
+export function assertValidConfig() {
+  const problems = checkConfig();
+  if (problems.length === 0) return;
+
+  const lines = problems.map(
+    (problem) => `  ${problem.severity === 'error' ? 'x' : '!'} ${problem.source}: ${problem.message}`,
+  );
+  console.warn(`[Config] ${problems.length} configuration problem(s):\n${lines.join('\n')}`);
+
+  const errors = problems.filter((problem) => problem.severity === 'error');
+  if (errors.length > 0 && config.nodeEnv === 'production') {
+    throw new Error(`Invalid configuration: ${errors.map((problem) => problem.source).join(', ')}`);
+  }
+}
+
 export const getApiUrl = (): string => {
   if (config.apiUrl) {
     return normalizeUrl(config.apiUrl);
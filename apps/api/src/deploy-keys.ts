@@ -0,0 +1,33 @@
+import { db, deployKeys } from "@gitbruv/db";
+import { eq, and } from "drizzle-orm";
+import { createHash } from "crypto";
+
+export const DEPLOY_KEY_PREFIX = "gbd_";
+
+export function generateDeployKey(): string {
+  const array = new Uint8Array(32);
+  crypto.getRandomValues(array);
+  return DEPLOY_KEY_PREFIX + Array.from(array, (byte) => byte.toString(16).padStart(2, "0")).join("");
+}
+
+export function hashDeployKey(key: string): string {
+  return createHash("sha256").update(key).digest("hex");
+}
+
+export async function checkDeployKey(repoId: string, key: string): Promise<{ readOnly: boolean } | null> {
+  if (!key.startsWith(DEPLOY_KEY_PREFIX)) {
+    return null;
+  }
+
+  const row = await db.query.deployKeys.findFirst({
+    where: and(eq(deployKeys.keyHash, hashDeployKey(key)), eq(deployKeys.repositoryId, repoId)),
+  });
+
+  if (!row) {
+    return null;
+  }
+
+  await db.update(deployKeys).set({ lastUsedAt: new Date() }).where(eq(deployKeys.id, row.id));
+
+  return { readOnly: row.readOnly };
+}
@@ -162,6 +162,61 @@ This link will expire in 24 hours.
   });
 }
 
+export async function sendEmailChangeEmail(
+  to: string,
+  token: string,
+  username: string,
+): Promise<boolean> {
+  const webUrl = getWebUrl();
+  const confirmUrl = `${webUrl}/verify-email?token=${token}&change=1`;
+
+  const html = `
+<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1.0">
+  <title>Confirm your new email</title>
+</head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, 'Helvetica Neue', Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+  <div style="background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); padding: 30px;">
+    <h1 style="color: white; margin: 0; font-size: 28px;">GitBruv</h1>
+  </div>
+  <div style="background: #ffffff; padding: 30px; border: 1px solid #e5e7eb; border-top: none;">
+    <h2 style="margin-top: 0;">Confirm your new email address</h2>
+    <p>Hey @${username},</p>
+    <p>You asked to use this address for your GitBruv account. Your current email stays active until you confirm:</p>
+    <div style="text-align: center; margin: 30px 0;">
+      <a href="${confirmUrl}" style="background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); color: white; padding: 12px 30px; text-decoration: none; font-weight: 600; display: inline-block;">Confirm Email</a>
+    </div>
+    <p style="color: #6b7280; font-size: 14px;">This link will expire in 24 hours. If you didn't request this change, you can ignore this email.</p>
+    <hr style="border: none; border-top: 1px solid #e5e7eb; margin: 30px 0;">
+    <p style="color: #9ca3af; font-size: 12px;">If the button doesn't work, copy and paste this URL into your browser:</p>
+    <p style="color: #6b7280; font-size: 12px; word-break: break-all;">${confirmUrl}</p>
+  </div>
+</body>
+</html>`;
+
+  const text = `
+Confirm your new email address
+
+Hey @${username},
+
+You asked to use this address for your GitBruv account. Your current email stays active until you confirm by visiting the link below:
+
+${confirmUrl}
+
+This link will expire in 24 hours. If you didn't request this change, you can ignore this email.
+`;
+
+  return sendEmail({
+    to,
+    subject: 'Confirm your new GitBruv email',
+    html,
+    text,
+  });
+}
+
 export async function sendNotificationEmail(
   to: string,
   title: string,
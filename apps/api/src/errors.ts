@@ -0,0 +1,128 @@
+import type { Context } from "hono";
+import { HTTPException } from "hono/http-exception";
+import type { ContentfulStatusCode } from "hono/utils/http-status";
+
+export type ApiErrorCode =
+  | "bad_request"
+  | "unauthorized"
+  | "forbidden"
+  | "not_found"
+  | "conflict"
+  | "payload_too_large"
+  | "rate_limited"
+  | "internal_error"
+  | "storage_error"
+  | "service_unavailable";
+
+export type ApiErrorBody = {
+  code: ApiErrorCode;
+  message: string;
+  details?: unknown;
+  requestId?: string;
+};
+
+const CODES_BY_STATUS: Partial<Record<number, ApiErrorCode>> = {
+  400: "bad_request",
+  401: "unauthorized",
+  403: "forbidden",
+  404: "not_found",
+  409: "conflict",
+  413: "payload_too_large",
+  429: "rate_limited",
+  503: "service_unavailable",
+};
+
+export function codeForStatus(status: number): ApiErrorCode {
+  return CODES_BY_STATUS[status] ?? (status >= 500 ? "internal_error" : "bad_request");
+}
+
+// Thrown from helpers that sit below a handler; the app-level error handler turns it into the
+// same body apiError() returns.
+export class ApiError extends Error {
+  constructor(
+    readonly status: ContentfulStatusCode,
+    message: string,
+    readonly details?: unknown,
+    readonly code: ApiErrorCode = codeForStatus(status)
+  ) {
+    super(message);
+    this.name = "ApiError";
+  }
+}
+
+export function apiError(c: Context, status: ContentfulStatusCode, message: string, details?: unknown, code: ApiErrorCode = codeForStatus(status)) {
+  const body: ApiErrorBody = { code, message };
+  if (details !== undefined) body.details = details;
+  const requestId = c.get("requestId");
+  if (requestId) body.requestId = requestId;
+  return c.json(body, status);
+}
+
+function findCause(error: unknown, match: (error: Record<string, unknown>) => boolean): Record<string, unknown> | null {
+  let current = error;
+  for (let depth = 0; depth < 5 && current && typeof current === "object"; depth++) {
+    const candidate = current as Record<string, unknown>;
+    if (match(candidate)) return candidate;
+    current = candidate.cause;
+  }
+  return null;
+}
+
+// Postgres and S3 failures are mapped to a status by their error code only; driver messages,
+// SQL text and bucket keys never reach the client.
+function classify(error: unknown): ApiError | null {
+  const pg = findCause(error, (e) => e.name === "PostgresError" && typeof e.code === "string");
+  if (pg) {
+    switch (pg.code) {
+      case "23505":
+        return new ApiError(409, "Resource already exists");
+      case "23503":
+        return new ApiError(409, "Referenced resource does not exist");
+      case "22P02":
+      case "22001":
+      case "22003":
+        return new ApiError(400, "Invalid input");
+      case "57014":
+        return new ApiError(503, "Request timed out");
+      default:
+        return null;
+    }
+  }
+
+  if (findCause(error, (e) => e.name === "StorageUnavailableError")) {
+    return new ApiError(503, "Storage is temporarily unavailable", undefined, "service_unavailable");
+  }
+
+  const s3 = findCause(error, (e) => typeof e.$metadata === "object" && e.$metadata !== null);
+  if (s3) {
+    if (s3.name === "NoSuchKey" || s3.name === "NotFound") {
+      return new ApiError(404, "Not found");
+    }
+    return new ApiError(502, "Storage request failed", undefined, "storage_error");
+  }
+
+  // better-auth's APIError carries a client-safe message meant for its own HTTP responses.
+  const auth = findCause(error, (e) => e.name === "APIError" && typeof e.statusCode === "number");
+  if (auth && (auth.statusCode as number) < 500) {
+    const body = auth.body as { message?: string } | undefined;
+    return new ApiError(auth.statusCode as ContentfulStatusCode, body?.message || "Authentication failed");
+  }
+
+  return null;
+}
+
+export function handleError(error: unknown, c: Context): Response {
+  if (error instanceof ApiError) {
+    return apiError(c, error.status, error.message, error.details, error.code);
+  }
+  if (error instanceof HTTPException) {
+    return apiError(c, error.status, error.message || "Request failed");
+  }
+
+  const mapped = classify(error);
+  console.error(`[API] ${c.req.method} ${c.req.path} failed (request ${c.get("requestId") ?? "unknown"}):`, error);
+  if (mapped) {
+    return apiError(c, mapped.status, mapped.message, undefined, mapped.code);
+  }
+  return apiError(c, 500, "Internal server error");
+}
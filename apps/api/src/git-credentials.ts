@@ -0,0 +1,123 @@
+import { db, users, gitCredentials } from "@gitbruv/db";
+import { eq, and, gt, lt } from "drizzle-orm";
+import { createHash, timingSafeEqual } from "crypto";
+import type { AuthUser } from "./middleware/auth";
+
+export const GIT_CREDENTIAL_PREFIX = "gbg_";
+export const GIT_CREDENTIAL_TTL_MS = 60 * 60 * 1000;
+
+// Tokens look like gbg_<credential id>_<secret>, so verification can look the row up by id
+// and only has to compare a single hash.
+const TOKEN_PATTERN = /^gbg_([0-9a-f-]{36})_([0-9a-f]{64})$/;
+
+export function hashGitCredential(token: string): string {
+  return createHash("sha256").update(token).digest("hex");
+}
+
+type CachedCredential = { tokenHash: string; user: AuthUser; credentialExpiresAt: number; cachedUntil: number };
+
+// A clone or fetch makes several requests with the same credential; verified results are kept
+// briefly per token id so only the first one hits the database. Entries are short-lived so a
+// revocation on another instance takes effect within CACHE_TTL_MS.
+class BasicAuthCache {
+  private static readonly CACHE_TTL_MS = 60 * 1000;
+  private static readonly MAX_ENTRIES = 10000;
+  private entries = new Map<string, CachedCredential>();
+
+  get(tokenId: string, tokenHash: string): AuthUser | null {
+    const entry = this.entries.get(tokenId);
+    if (!entry) return null;
+
+    const now = Date.now();
+    if (now >= entry.cachedUntil || now >= entry.credentialExpiresAt) {
+      this.entries.delete(tokenId);
+      return null;
+    }
+
+    const expected = Buffer.from(entry.tokenHash);
+    const provided = Buffer.from(tokenHash);
+    if (expected.length !== provided.length || !timingSafeEqual(expected, provided)) {
+      return null;
+    }
+    return entry.user;
+  }
+
+  set(tokenId: string, tokenHash: string, user: AuthUser, credentialExpiresAt: Date) {
+    if (this.entries.size >= BasicAuthCache.MAX_ENTRIES) {
+      const oldest = this.entries.keys().next().value;
+      if (oldest !== undefined) this.entries.delete(oldest);
+    }
+    this.entries.set(tokenId, {
+      tokenHash,
+      user,
+      credentialExpiresAt: credentialExpiresAt.getTime(),
+      cachedUntil: Date.now() + BasicAuthCache.CACHE_TTL_MS,
+    });
+  }
+
+  evict(tokenId: string) {
+    this.entries.delete(tokenId);
+  }
+}
+
+const basicAuthCache = new BasicAuthCache();
+
+export async function issueGitCredential(userId: string) {
+  await db.delete(gitCredentials).where(and(eq(gitCredentials.userId, userId), lt(gitCredentials.expiresAt, new Date())));
+
+  const id = crypto.randomUUID();
+  const secret = new Uint8Array(32);
+  crypto.getRandomValues(secret);
+  const token = `${GIT_CREDENTIAL_PREFIX}${id}_${Array.from(secret, (byte) => byte.toString(16).padStart(2, "0")).join("")}`;
+  const expiresAt = new Date(Date.now() + GIT_CREDENTIAL_TTL_MS);
+
+  await db.insert(gitCredentials).values({ id, userId, tokenHash: hashGitCredential(token), expiresAt });
+
+  return { id, token, expiresAt };
+}
+
+export async function revokeGitCredential(userId: string, id: string): Promise<boolean> {
+  const deleted = await db
+    .delete(gitCredentials)
+    .where(and(eq(gitCredentials.id, id), eq(gitCredentials.userId, userId)))
+    .returning({ id: gitCredentials.id });
+  basicAuthCache.evict(id);
+  return deleted.length > 0;
+}
+
+export async function checkGitCredential(username: string, token: string): Promise<AuthUser | null> {
+  const match = TOKEN_PATTERN.exec(token);
+  if (!match) {
+    return null;
+  }
+
+  const tokenId = match[1];
+  const tokenHash = hashGitCredential(token);
+  const cached = basicAuthCache.get(tokenId, tokenHash);
+  if (cached) {
+    return cached.username === username ? cached : null;
+  }
+
+  const [row] = await db
+    .select({
+      tokenHash: gitCredentials.tokenHash,
+      expiresAt: gitCredentials.expiresAt,
+      id: users.id,
+      name: users.name,
+      email: users.email,
+      username: users.username,
+      avatarUrl: users.avatarUrl,
+    })
+    .from(gitCredentials)
+    .innerJoin(users, eq(users.id, gitCredentials.userId))
+    .where(and(eq(gitCredentials.id, tokenId), gt(gitCredentials.expiresAt, new Date())))
+    .limit(1);
+
+  if (!row || row.tokenHash !== tokenHash) {
+    return null;
+  }
+
+  const user: AuthUser = { id: row.id, name: row.name, email: row.email, username: row.username, avatarUrl: row.avatarUrl };
+  basicAuthCache.set(tokenId, tokenHash, user, row.expiresAt);
+  return user.username === username ? user : null;
+}
@@ -0,0 +1,103 @@
+import { open } from "fs/promises";
+import { PackFormatError } from "./pack-parse";
+
+export type ParsedBundle = {
+  refs: { name: string; oid: string }[];
+  // The oid HEAD pointed at when the bundle was made; bundles don't record which branch.
+  head: string | null;
+  // Commits the bundle's pack builds on but doesn't contain.
+  prerequisites: string[];
+  // Refs other than branches and tags (remote-tracking refs from `git bundle create --all`, notes),
+  // which repositories here don't have.
+  skipped: string[];
+  // Where the pack starts; everything from there to the end of the bundle is the pack.
+  packOffset: number;
+};
+
+const V2_SIGNATURE = "# v2 git bundle\n";
+const V3_SIGNATURE = "# v3 git bundle\n";
+const MAX_HEADER_BYTES = 1024 * 1024;
+
+// Reads the header of a git bundle (as written by `git bundle create` or buildBundle): a signature,
+// for v3 a list of @capabilities, prerequisite lines ("-<oid> <comment>"), ref lines ("<oid> <ref>"),
+// then a blank line and the pack. Only the header is checked here; the pack is validated when
+// it's unpacked. `data` only needs to cover the header and the pack signature after it.
+export function parseBundle(data: Buffer): ParsedBundle {
+  const signature = data.subarray(0, V2_SIGNATURE.length).toString("utf8");
+  if (signature !== V2_SIGNATURE && signature !== V3_SIGNATURE) {
+    throw new PackFormatError("Not a git bundle");
+  }
+
+  const headerEnd = data.indexOf("\n\n", signature.length - 1);
+  if (headerEnd === -1 || headerEnd > MAX_HEADER_BYTES) {
+    throw new PackFormatError("Bundle header is truncated");
+  }
+
+  const bundle: ParsedBundle = { refs: [], head: null, prerequisites: [], skipped: [], packOffset: headerEnd + 2 };
+  const lines = headerEnd < signature.length ? [] : data.subarray(signature.length, headerEnd).toString("utf8").split("\n");
+  const seen = new Set<string>();
+
+  for (const line of lines) {
+    if (line.startsWith("@")) {
+      if (signature !== V3_SIGNATURE) {
+        throw new PackFormatError("Capabilities are only allowed in v3 bundles");
+      }
+      // object-format is the only capability git writes that doesn't change what the pack means.
+      if (line !== "@object-format=sha1") {
+        throw new PackFormatError(`Unsupported bundle capability ${line.slice(1)}`);
+      }
+      continue;
+    }
+
+    if (line.startsWith("-")) {
+      const oid = line.slice(1, 41);
+      if (!/^[0-9a-f]{40}$/.test(oid)) {
+        throw new PackFormatError(`Invalid prerequisite line: ${line}`);
+      }
+      bundle.prerequisites.push(oid);
+      continue;
+    }
+
+    const match = line.match(/^([0-9a-f]{40}) (\S+)$/);
+    if (!match) {
+      throw new PackFormatError(`Invalid ref line: ${line}`);
+    }
+    const [, oid, name] = match;
+    if (name === "HEAD") {
+      bundle.head = oid;
+      continue;
+    }
+    if (!name.startsWith("refs/") || name.split("/").some((part) => part === "" || part === "." || part === "..")) {
+      throw new PackFormatError(`Invalid ref name ${name}`);
+    }
+    if (!name.startsWith("refs/heads/") && !name.startsWith("refs/tags/")) {
+      bundle.skipped.push(name);
+      continue;
+    }
+    if (seen.has(name)) {
+      throw new PackFormatError(`Ref ${name} appears twice`);
+    }
+    seen.add(name);
+    bundle.refs.push({ name, oid });
+  }
+
+  if (bundle.refs.length === 0) {
+    throw new PackFormatError("Bundle contains no branches or tags");
+  }
+  if (data.subarray(bundle.packOffset, bundle.packOffset + 4).toString("ascii") !== "PACK") {
+    throw new PackFormatError("Bundle does not contain a pack");
+  }
+  return bundle;
+}
+
+// Parses the header of a bundle spooled to disk, reading no more of the file than a header can take.
+export async function readBundleHeader(path: string): Promise<ParsedBundle> {
+  const handle = await open(path, "r");
+  try {
+    const head = Buffer.alloc(MAX_HEADER_BYTES + 6);
+    const { bytesRead } = await handle.read(head, 0, head.length, 0);
+    return parseBundle(head.subarray(0, bytesRead));
+  } finally {
+    await handle.close();
+  }
+}
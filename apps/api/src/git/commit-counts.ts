@@ -0,0 +1,214 @@
+import git from "isomorphic-git";
+import type { GitStore } from "./index";
+
+export type CommitCountEntry = { generation: number; count: number };
+
+export type CommitCountLoader = (oids: string[]) => Promise<Map<string, CommitCountEntry>>;
+
+const ONLY_BASE = 1;
+const ONLY_OTHERS = 2;
+
+class GenerationHeap {
+  private items: { oid: string; generation: number }[] = [];
+
+  get size() {
+    return this.items.length;
+  }
+
+  push(oid: string, generation: number) {
+    const items = this.items;
+    items.push({ oid, generation });
+    let index = items.length - 1;
+    while (index > 0) {
+      const parent = (index - 1) >>> 1;
+      if (items[parent].generation >= items[index].generation) break;
+      [items[parent], items[index]] = [items[index], items[parent]];
+      index = parent;
+    }
+  }
+
+  pop(): string | undefined {
+    const items = this.items;
+    const top = items[0];
+    const last = items.pop();
+    if (items.length > 0 && last) {
+      items[0] = last;
+      let index = 0;
+      for (;;) {
+        const left = index * 2 + 1;
+        const right = left + 1;
+        let largest = index;
+        if (left < items.length && items[left].generation > items[largest].generation) largest = left;
+        if (right < items.length && items[right].generation > items[largest].generation) largest = right;
+        if (largest === index) break;
+        [items[largest], items[index]] = [items[index], items[largest]];
+        index = largest;
+      }
+    }
+    return top?.oid;
+  }
+}
+
+// Counts commits reachable from a tip without walking all of history. Each commit's count is its
+// first parent's count plus one plus whatever only its other parents reach; that remainder is
+// found by walking both sides in generation order and stopping once nothing reachable only from
+// the other parents is left, so linear history costs O(1) per commit and a merge costs roughly
+// the length of the merged branch.
+export class CommitCountIndex {
+  private known = new Map<string, CommitCountEntry>();
+  private parents = new Map<string, string[]>();
+  private computed = new Map<string, CommitCountEntry>();
+  // Commits whose ancestry reaches a missing commit. Their counts are a lower bound, good enough
+  // to answer with, but never handed out as new entries: a stored row is reused forever, so it
+  // would stay short after the missing history arrives.
+  private incomplete = new Set<string>();
+
+  constructor(
+    private store: GitStore,
+    private load: CommitCountLoader = async () => new Map()
+  ) {}
+
+  // Entries worked out by this index that the loader didn't already have, leaving out any whose
+  // history is incomplete.
+  get newEntries(): Map<string, CommitCountEntry> {
+    return this.computed;
+  }
+
+  private async ensureLoaded(oids: string[]) {
+    const missing = oids.filter((oid) => !this.known.has(oid));
+    if (missing.length === 0) return;
+    for (const [oid, entry] of await this.load(missing)) {
+      this.known.set(oid, entry);
+    }
+  }
+
+  // Missing commits (shallow or damaged history) count as nothing, the same way walkCommits
+  // skips them. Neither they nor anything descending from them is stored, so the count is
+  // worked out again once the objects arrive.
+  private async readParents(oid: string): Promise<string[] | null> {
+    const cached = this.parents.get(oid);
+    if (cached) return cached;
+    try {
+      const { commit } = await git.readCommit({ fs: this.store.fs, dir: this.store.dir, oid });
+      this.parents.set(oid, commit.parent);
+      return commit.parent;
+    } catch (error: any) {
+      if (error.code === "NotFoundError" || error.message?.includes("Could not find")) {
+        return null;
+      }
+      throw error;
+    }
+  }
+
+  // Returns null when more than maxNewCommits would have to be computed, so request-time callers
+  // can leave large backfills to the background recount.
+  async resolve(tip: string, maxNewCommits = Infinity): Promise<CommitCountEntry | null> {
+    await this.ensureLoaded([tip]);
+    const existing = this.known.get(tip);
+    if (existing) return existing;
+
+    const unknown = new Set<string>();
+    let frontier = [tip];
+    while (frontier.length > 0) {
+      await this.ensureLoaded(frontier);
+      const next: string[] = [];
+      for (const oid of frontier) {
+        if (this.known.has(oid) || unknown.has(oid)) continue;
+        unknown.add(oid);
+        if (unknown.size > maxNewCommits) return null;
+
+        const parents = await this.readParents(oid);
+        if (parents === null) {
+          this.known.set(oid, { generation: 0, count: 0 });
+          this.incomplete.add(oid);
+          unknown.delete(oid);
+          continue;
+        }
+        next.push(...parents);
+      }
+      frontier = next;
+    }
+
+    // Parents before children, so every commit is computed from entries that already exist.
+    const visited = new Set<string>();
+    const stack: { oid: string; expanded: boolean }[] = [{ oid: tip, expanded: false }];
+    while (stack.length > 0) {
+      const top = stack.pop()!;
+      if (this.known.has(top.oid)) continue;
+      const parents = this.parents.get(top.oid) ?? [];
+      if (top.expanded) {
+        if (parents.some((parent) => this.incomplete.has(parent))) this.incomplete.add(top.oid);
+        this.setEntry(top.oid, await this.compute(parents));
+        continue;
+      }
+      if (visited.has(top.oid)) continue;
+      visited.add(top.oid);
+      stack.push({ oid: top.oid, expanded: true });
+      for (const parent of parents) {
+        if (!this.known.has(parent)) stack.push({ oid: parent, expanded: false });
+      }
+    }
+
+    return this.known.get(tip) ?? null;
+  }
+
+  private setEntry(oid: string, entry: CommitCountEntry) {
+    this.known.set(oid, entry);
+    if (!this.incomplete.has(oid)) this.computed.set(oid, entry);
+  }
+
+  private async compute(parents: string[]): Promise<CommitCountEntry> {
+    if (parents.length === 0) {
+      return { generation: 1, count: 1 };
+    }
+
+    const entries = parents.map((parent) => this.known.get(parent)!);
+    const generation = 1 + Math.max(...entries.map((entry) => entry.generation));
+    const exclusive = parents.length > 1 ? await this.countExclusive(parents[0], parents.slice(1)) : 0;
+    return { generation, count: entries[0].count + 1 + exclusive };
+  }
+
+  // Commits reachable from `others` but not from `base`. Popping in decreasing generation order
+  // means every child of a commit has been popped (and has passed its flags on) before the
+  // commit itself, so its flags are final by the time it is counted.
+  private async countExclusive(base: string, others: string[]): Promise<number> {
+    const flags = new Map<string, number>();
+    const heap = new GenerationHeap();
+    let exclusiveQueued = 0;
+
+    const mark = (oid: string, flag: number) => {
+      const current = flags.get(oid);
+      if (current === undefined) {
+        flags.set(oid, flag);
+        heap.push(oid, this.known.get(oid)!.generation);
+        if (flag === ONLY_OTHERS) exclusiveQueued++;
+      } else if ((current | flag) !== current) {
+        if (current === ONLY_OTHERS) exclusiveQueued--;
+        flags.set(oid, current | flag);
+      }
+    };
+
+    mark(base, ONLY_BASE);
+    for (const oid of others) mark(oid, ONLY_OTHERS);
+
+    let count = 0;
+    while (exclusiveQueued > 0 && heap.size > 0) {
+      const oid = heap.pop()!;
+      const flag = flags.get(oid)!;
+      const entry = this.known.get(oid)!;
+      if (flag === ONLY_OTHERS) {
+        exclusiveQueued--;
+        if (entry.count > 0) count++;
+      }
+      if (entry.count === 0) continue;
+
+      const parents = (await this.readParents(oid)) ?? [];
+      await this.ensureLoaded(parents);
+      for (const parent of parents) {
+        if (this.known.has(parent)) mark(parent, flag);
+      }
+    }
+
+    return count;
+  }
+}
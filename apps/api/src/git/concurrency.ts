@@ -0,0 +1,37 @@
+export const OBJECT_FETCH_CONCURRENCY = 16;
+
+export type Limiter = <T>(task: () => Promise<T>) => Promise<T>;
+
+// Caps the number of in-flight tasks. Only wrap leaf I/O with it: a task that awaits other
+// limited tasks while holding a slot can deadlock once every slot is taken.
+export function createLimiter(concurrency: number): Limiter {
+  let active = 0;
+  const waiting: (() => void)[] = [];
+
+  return async (task) => {
+    if (active >= concurrency) {
+      await new Promise<void>((resolve) => waiting.push(resolve));
+    } else {
+      active++;
+    }
+    try {
+      return await task();
+    } finally {
+      const next = waiting.shift();
+      if (next) {
+        next();
+      } else {
+        active--;
+      }
+    }
+  };
+}
+
+export async function mapConcurrent<T, R>(
+  items: T[],
+  concurrency: number,
+  fn: (item: T, index: number) => Promise<R>
+): Promise<R[]> {
+  const limit = createLimiter(concurrency);
+  return Promise.all(items.map((item, index) => limit(() => fn(item, index))));
+}
@@ -0,0 +1,110 @@
+import git from "isomorphic-git";
+import { resolveRefOid, type GitStore } from "./index";
+import { rewriteTree, PatchError } from "./patch";
+import { applyRefUpdates } from "./ref-transaction";
+import { storageLayout } from "../storage-layout";
+
+export type FileChange = {
+  path: string;
+  content: string | null;
+  encoding?: "utf-8" | "base64";
+};
+
+async function readEntryAt(store: GitStore, treeOid: string, path: string) {
+  const parts = path.split("/");
+  let oid = treeOid;
+  for (let i = 0; i < parts.length; i++) {
+    const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid });
+    const entry = tree.find((e) => e.path === parts[i]);
+    if (!entry) return null;
+    if (i === parts.length - 1) return entry;
+    if (entry.type !== "tree") return null;
+    oid = entry.oid;
+  }
+  return null;
+}
+
+export async function commitFileChange(
+  store: GitStore,
+  branch: string,
+  change: FileChange,
+  options: {
+    repositoryId: string;
+    message: string;
+    committer: { name: string; email: string };
+    expectedBlobOid?: string;
+    expectedHeadOid?: string;
+  }
+): Promise<{ commitOid: string; parentOid: string; blobOid: string | null; status: "added" | "modified" | "deleted" }> {
+  const path = change.path;
+  if (path.split("/").some((segment) => segment === ".." || segment === "" || segment === "." || segment === ".git")) {
+    throw new PatchError("Invalid file path", path);
+  }
+
+  const parentOid = await resolveRefOid(store, branch);
+  if (options.expectedHeadOid && options.expectedHeadOid !== parentOid) {
+    throw new PatchError("Branch has moved since the file was loaded");
+  }
+
+  const { commit: parent } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: parentOid });
+  const existing = await readEntryAt(store, parent.tree, path);
+  if (existing && existing.type !== "blob") {
+    throw new PatchError("Path is a directory", path);
+  }
+  if (options.expectedBlobOid !== undefined && (existing?.oid ?? "") !== options.expectedBlobOid) {
+    throw new PatchError(options.expectedBlobOid ? "File has changed since it was loaded" : "File already exists on the target branch", path);
+  }
+
+  let blobOid: string | null = null;
+  let status: "added" | "modified" | "deleted";
+
+  if (change.content === null) {
+    if (!existing) {
+      throw new PatchError("File does not exist on the target branch", path);
+    }
+    status = "deleted";
+  } else {
+    const blob = change.encoding === "base64" ? Buffer.from(change.content, "base64") : new TextEncoder().encode(change.content);
+    blobOid = await git.writeBlob({ fs: store.fs, dir: store.dir, blob });
+    status = existing ? "modified" : "added";
+  }
+
+  const treeOid = await rewriteTree(
+    store,
+    parent.tree,
+    new Map([[path, blobOid ? { oid: blobOid, mode: existing?.mode ?? "100644" } : null]])
+  );
+  if (!treeOid) {
+    throw new PatchError("Change would leave the repository empty");
+  }
+  if (treeOid === parent.tree) {
+    throw new PatchError("Change does not modify the file", path);
+  }
+
+  const timestamp = Math.floor(Date.now() / 1000);
+  const timezoneOffset = new Date().getTimezoneOffset();
+
+  const commitOid = await git.writeCommit({
+    fs: store.fs,
+    dir: store.dir,
+    commit: {
+      message: options.message.endsWith("\n") ? options.message : `${options.message}\n`,
+      tree: treeOid,
+      parent: [parentOid],
+      author: { ...options.committer, timestamp, timezoneOffset },
+      committer: { ...options.committer, timestamp, timezoneOffset },
+    },
+  });
+
+  const errors = await applyRefUpdates(
+    options.repositoryId,
+    storageLayout.repoPrefix(store.ownerId, store.repoName),
+    [{ ref: `refs/heads/${branch}`, oldOid: parentOid, newOid: commitOid }],
+    true
+  );
+  if (errors.size > 0) {
+    throw new PatchError("Branch was updated while the change was being committed");
+  }
+
+  return { commitOid, parentOid, blobOid, status };
+}
@@ -0,0 +1,148 @@
+import git from "isomorphic-git";
+import { createHash } from "crypto";
+import { getObject } from "../s3";
+import { storageLayout } from "../storage-layout";
+import { parsePackIndex } from "./pack-index";
+import { PackFormatError } from "./pack-parse";
+import type { GitStore } from "./index";
+
+const MAX_FSCK_OBJECTS = 100_000;
+
+export type FsckProblem = {
+  oid: string;
+  error: string;
+};
+
+export type FsckReport = {
+  ok: boolean;
+  refs: { ref: string; oid: string | null; ok: boolean; error?: string }[];
+  objects: { checked: number; missing: FsckProblem[]; corrupt: FsckProblem[]; truncated: boolean };
+  packs: { name: string; ok: boolean; error?: string }[];
+  durationMs: number;
+};
+
+async function listRefs(store: GitStore): Promise<string[]> {
+  const [branches, tags] = await Promise.all([
+    git.listBranches({ fs: store.fs, dir: store.dir }).catch(() => [] as string[]),
+    git.listTags({ fs: store.fs, dir: store.dir }).catch(() => [] as string[]),
+  ]);
+  return [...branches.map((b) => `refs/heads/${b}`), ...tags.map((t) => `refs/tags/${t}`)];
+}
+
+async function checkPacks(store: GitStore): Promise<FsckReport["packs"]> {
+  const names = await store.fs.promises.readdir(".git/objects/pack").catch(() => [] as string[]);
+  const packs = names.filter((n) => n.endsWith(".pack")).map((n) => n.slice(0, -5));
+  const indexes = names.filter((n) => n.endsWith(".idx")).map((n) => n.slice(0, -4));
+  const prefix = storageLayout.repoPrefix(store.ownerId, store.repoName);
+
+  const results: FsckReport["packs"] = [];
+  for (const name of new Set([...packs, ...indexes])) {
+    if (!packs.includes(name)) {
+      results.push({ name, ok: false, error: "index has no matching pack" });
+      continue;
+    }
+    if (!indexes.includes(name)) {
+      results.push({ name, ok: false, error: "pack has no index" });
+      continue;
+    }
+
+    const [idx, packTrailer] = await Promise.all([
+      getObject(storageLayout.packKey(prefix, name, "idx")),
+      getObject(storageLayout.packKey(prefix, name, "pack"), "bytes=-20"),
+    ]);
+    if (!idx || idx.length < 40 || !packTrailer || packTrailer.length !== 20) {
+      results.push({ name, ok: false, error: "pack or index is truncated" });
+      continue;
+    }
+
+    const idxChecksum = createHash("sha1").update(idx.subarray(0, idx.length - 20)).digest();
+    if (!idxChecksum.equals(idx.subarray(idx.length - 20))) {
+      results.push({ name, ok: false, error: "index checksum mismatch" });
+    } else if (!packTrailer.equals(idx.subarray(idx.length - 40, idx.length - 20))) {
+      results.push({ name, ok: false, error: "index does not match pack" });
+    } else {
+      try {
+        parsePackIndex(idx);
+        results.push({ name, ok: true });
+      } catch (error) {
+        results.push({ name, ok: false, error: error instanceof PackFormatError ? error.message : "index could not be read" });
+      }
+    }
+  }
+  return results;
+}
+
+export async function runFsck(store: GitStore): Promise<FsckReport> {
+  const startedAt = Date.now();
+  const report: FsckReport = {
+    ok: true,
+    refs: [],
+    objects: { checked: 0, missing: [], corrupt: [], truncated: false },
+    packs: [],
+    durationMs: 0,
+  };
+
+  const queue: string[] = [];
+  for (const ref of await listRefs(store)) {
+    try {
+      const oid = await git.resolveRef({ fs: store.fs, dir: store.dir, ref });
+      report.refs.push({ ref, oid, ok: true });
+      queue.push(oid);
+    } catch (error) {
+      report.refs.push({ ref, oid: null, ok: false, error: error instanceof Error ? error.message : "unresolvable" });
+    }
+  }
+
+  const visited = new Set<string>();
+  while (queue.length > 0) {
+    const oid = queue.pop()!;
+    if (visited.has(oid)) continue;
+    if (visited.size >= MAX_FSCK_OBJECTS) {
+      report.objects.truncated = true;
+      break;
+    }
+    visited.add(oid);
+
+    let object: { type: string; object: Uint8Array };
+    try {
+      object = (await git.readObject({ fs: store.fs, dir: store.dir, oid, format: "content" })) as typeof object;
+    } catch {
+      report.objects.missing.push({ oid, error: "object not found" });
+      continue;
+    }
+
+    const hash = createHash("sha1")
+      .update(`${object.type} ${object.object.length}\0`)
+      .update(object.object)
+      .digest("hex");
+    if (hash !== oid) {
+      report.objects.corrupt.push({ oid, error: `hash mismatch (${hash})` });
+      continue;
+    }
+
+    try {
+      if (object.type === "commit") {
+        const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid });
+        queue.push(commit.tree, ...commit.parent);
+      } else if (object.type === "tree") {
+        const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid });
+        queue.push(...tree.filter((entry) => entry.type !== "commit").map((entry) => entry.oid));
+      } else if (object.type === "tag") {
+        const { tag } = await git.readTag({ fs: store.fs, dir: store.dir, oid });
+        queue.push(tag.object);
+      }
+    } catch (error) {
+      report.objects.corrupt.push({ oid, error: error instanceof Error ? error.message : "unparseable object" });
+    }
+  }
+
+  report.objects.checked = visited.size;
+  report.packs = await checkPacks(store);
+  report.ok =
+    report.refs.every((r) => r.ok) &&
+    report.objects.missing.length === 0 &&
+    report.objects.corrupt.length === 0 &&
+    report.packs.every((p) => p.ok);
+  report.durationMs = Date.now() - startedAt;
+  return report;
+}
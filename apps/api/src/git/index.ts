@@ -1,7 +1,11 @@
-import git from "isomorphic-git";
+import git, { type ReadCommitResult } from "isomorphic-git";
 import { createS3Fs, type S3Fs } from "./s3-fs";
-import { getRepoPrefix } from "../s3";
+import { storageLayout } from "../storage-layout";
+import { getContentType } from "../mime";
 import { getCached, setCache, repoCache, CACHE_TTL } from "../cache";
+import { createLimiter, mapConcurrent, OBJECT_FETCH_CONCURRENCY, type Limiter } from "./concurrency";
+import { hasStorageFailure } from "../tracing";
+import { parseCommitTrailers, type CommitTrailers } from "./trailers";
 
 export interface CommitAuthor {
   name: string;
@@ -16,6 +20,13 @@ export interface CommitInfo {
   message: string;
   author: CommitAuthor;
   timestamp: number;
+  trailers?: CommitTrailers;
+}
+
+export interface CommitDetails extends CommitInfo {
+  committer: CommitAuthor & { timestamp: number };
+  parents: string[];
+  tree: string;
 }
 
 export interface TreeEntry {
@@ -23,7 +34,8 @@ export interface TreeEntry {
   mode: string;
   path: string;
   oid: string;
-  type: "blob" | "tree" | "commit" | string;
+  type: "blob" | "tree" | "commit" | "symlink" | string;
+  submoduleUrl?: string | null;
 }
 
 export interface DiffHunkLine {
@@ -48,6 +60,11 @@ export interface FileDiff {
   additions: number;
   deletions: number;
   hunks: DiffHunk[];
+  isBinary: boolean;
+  tooLarge: boolean;
+  oldSize: number | null;
+  newSize: number | null;
+  mimeType: string;
 }
 
 export interface CommitDiff {
@@ -69,7 +86,7 @@ export interface GitStore {
 }
 
 export function createGitStore(ownerId: string, repoName: string): GitStore {
-  const prefix = getRepoPrefix(ownerId, repoName);
+  const prefix = storageLayout.repoPrefix(ownerId, repoName);
   const fs = createS3Fs(prefix);
   return { fs, dir: "/", ownerId, repoName };
 }
@@ -144,6 +161,50 @@ export async function resolveRefOid(store: GitStore, ref: string): Promise<strin
   return git.resolveRef({ fs: store.fs, dir: store.dir, ref: normalizeRef(ref) });
 }
 
+async function peelToCommit(fs: S3Fs, dir: string, oid: string): Promise<string | null> {
+  for (let depth = 0; depth < 10; depth++) {
+    try {
+      const { type, object } = await git.readObject({ fs, dir, oid });
+      if (type === "commit") return oid;
+      if (type !== "tag") return null;
+      oid = (object as { object: string }).object;
+    } catch {
+      return null;
+    }
+  }
+  return null;
+}
+
+// Resolves a branch, tag (annotated tags are peeled), full ref, HEAD, or full/abbreviated SHA
+// to a commit oid that exists in the object store. Refs win over SHA prefixes, as in git.
+export async function resolveCommittish(fs: S3Fs, dir: string, committish: string): Promise<string | null> {
+  const candidates = committish.startsWith("refs/") || committish === "HEAD"
+    ? [committish]
+    : [`refs/heads/${committish}`, `refs/tags/${committish}`];
+
+  for (const ref of candidates) {
+    try {
+      const oid = await git.resolveRef({ fs, dir, ref });
+      return peelToCommit(fs, dir, oid);
+    } catch {
+      continue;
+    }
+  }
+
+  if (!/^[0-9a-f]{4,40}$/i.test(committish)) {
+    return null;
+  }
+
+  try {
+    const oid = committish.length === 40
+      ? committish.toLowerCase()
+      : await git.expandOid({ fs, dir, oid: committish.toLowerCase() });
+    return peelToCommit(fs, dir, oid);
+  } catch {
+    return null;
+  }
+}
+
 async function objectExists(fs: S3Fs, oid: string): Promise<boolean> {
   try {
     const prefix = oid.substring(0, 2);
@@ -159,6 +220,52 @@ async function objectExists(fs: S3Fs, oid: string): Promise<boolean> {
   }
 }
 
+// Walks every commit reachable from startOid newest-first by committer date, like `git log`.
+// Each commit is yielded once, however many children reach it; missing parents are skipped.
+export async function* walkCommits(fs: S3Fs, dir: string, startOid: string): AsyncGenerator<ReadCommitResult> {
+  const seen = new Set<string>([startOid]);
+  const queue: ReadCommitResult[] = [];
+
+  const enqueue = async (oid: string) => {
+    let entry: ReadCommitResult;
+    try {
+      entry = await git.readCommit({ fs, dir, oid });
+    } catch (error: any) {
+      if (error.code === "NotFoundError" || error.message?.includes("Could not find")) {
+        return;
+      }
+      throw error;
+    }
+
+    // Kept sorted oldest-first so the newest commit pops off the end; ties go in front of
+    // existing entries so commits with equal dates come out in the order they were found.
+    const time = entry.commit.committer.timestamp;
+    let low = 0;
+    let high = queue.length;
+    while (low < high) {
+      const mid = (low + high) >>> 1;
+      if (queue[mid].commit.committer.timestamp < time) {
+        low = mid + 1;
+      } else {
+        high = mid;
+      }
+    }
+    queue.splice(low, 0, entry);
+  };
+
+  await enqueue(startOid);
+  while (queue.length > 0) {
+    const entry = queue.pop()!;
+    yield entry;
+
+    for (const parent of entry.commit.parent) {
+      if (seen.has(parent)) continue;
+      seen.add(parent);
+      await enqueue(parent);
+    }
+  }
+}
+
 export async function getCommits(
   fs: S3Fs,
   dir: string,
@@ -167,72 +274,31 @@ export async function getCommits(
   skip: number
 ): Promise<{ commits: CommitInfo[]; hasMore: boolean }> {
   try {
-    const normalizedRef = normalizeRef(ref);
-    const exists = await refExists(fs, dir, ref);
-    if (!exists) {
-
-      return { commits: [], hasMore: false };
-    }
-
-
-
-    let commitOid: string;
-    try {
-      commitOid = await git.resolveRef({ fs, dir, ref: normalizedRef });
-    } catch (error) {
-      console.error(`[Git] getCommits: resolveRef failed, trying manual read:`, error);
-      try {
-        const refContent = await fs.promises.readFile(normalizedRef, "utf8");
-        commitOid = refContent.toString().trim();
-
-      } catch (readError) {
-        console.error(`[Git] getCommits: failed to read ref manually:`, readError);
-        return { commits: [], hasMore: false };
-      }
-    }
-
-    if (!(await objectExists(fs, commitOid))) {
-      console.error(`[Git] getCommits: commit ${commitOid} does not exist`);
+    const commitOid = await resolveCommittish(fs, dir, ref);
+    if (!commitOid) {
       return { commits: [], hasMore: false };
     }
 
     const commits: CommitInfo[] = [];
-    let count = 0;
-    let skipped = 0;
-    let currentOid: string | null = commitOid;
+    let index = 0;
     let hasMore = false;
 
-    while (currentOid && count < limit + skip) {
-      try {
-        const { commit } = await git.readCommit({ fs, dir, oid: currentOid });
-
-        if (skipped >= skip) {
-          if (count < limit) {
-            commits.push({
-              oid: currentOid,
-              message: commit.message,
-              author: {
-                name: commit.author.name,
-                email: commit.author.email,
-              },
-              timestamp: commit.author.timestamp * 1000,
-            });
-            count++;
-          } else {
-            hasMore = true;
-            break;
-          }
-        } else {
-          skipped++;
-        }
-
-        currentOid = commit.parent.length > 0 ? commit.parent[0] : null;
-      } catch (error: any) {
-        if (error.code === "NotFoundError" || error.message?.includes("Could not find")) {
-          break;
-        }
-        throw error;
+    for await (const { oid, commit } of walkCommits(fs, dir, commitOid)) {
+      if (index++ < skip) continue;
+      if (commits.length === limit) {
+        hasMore = true;
+        break;
       }
+      commits.push({
+        oid,
+        message: commit.message,
+        author: {
+          name: commit.author.name,
+          email: commit.author.email,
+        },
+        timestamp: commit.author.timestamp * 1000,
+        trailers: parseCommitTrailers(commit.message),
+      });
     }
 
     return { commits, hasMore };
@@ -244,49 +310,195 @@ export async function getCommits(
 
 export async function getCommitCount(fs: S3Fs, dir: string, ref: string): Promise<number> {
   try {
-    const normalizedRef = normalizeRef(ref);
-    const exists = await refExists(fs, dir, ref);
-    if (!exists) {
+    const commitOid = await resolveCommittish(fs, dir, ref);
+    if (!commitOid) {
       return 0;
     }
 
-    let commitOid: string;
-    try {
-      commitOid = await git.resolveRef({ fs, dir, ref: normalizedRef });
-    } catch {
-      try {
-        const refContent = await fs.promises.readFile(normalizedRef, "utf8");
-        commitOid = refContent.toString().trim();
-      } catch {
-        return 0;
-      }
+    let count = 0;
+    for await (const _ of walkCommits(fs, dir, commitOid)) {
+      count++;
     }
 
-    if (!(await objectExists(fs, commitOid))) {
-      return 0;
+    return count;
+  } catch (error) {
+    console.error("[Git] getCommitCount error:", error);
+    return 0;
+  }
+}
+
+function entryTypeForMode(mode: string): TreeEntry["type"] {
+  if (mode === "040000") return "tree";
+  if (mode === "160000") return "commit";
+  if (mode === "120000") return "symlink";
+  return "blob";
+}
+
+export function parseGitmodules(content: string): Map<string, string> {
+  const sections = new Map<string, { path?: string; url?: string }>();
+  let current: { path?: string; url?: string } | null = null;
+
+  for (const rawLine of content.split("\n")) {
+    const line = rawLine.trim();
+    if (!line || line.startsWith("#") || line.startsWith(";")) continue;
+
+    const header = line.match(/^\[submodule\s+"(.+)"\]$/);
+    if (header) {
+      current = sections.get(header[1]) ?? {};
+      sections.set(header[1], current);
+      continue;
+    }
+    if (line.startsWith("[")) {
+      current = null;
+      continue;
     }
 
-    let count = 0;
-    let currentOid: string | null = commitOid;
+    const pair = line.match(/^(\w+)\s*=\s*(.*)$/);
+    if (current && pair) {
+      const key = pair[1].toLowerCase();
+      if (key === "path" || key === "url") current[key] = pair[2];
+    }
+  }
 
-    while (currentOid) {
-      try {
-        const { commit } = await git.readCommit({ fs, dir, oid: currentOid });
-        count++;
-        currentOid = commit.parent.length > 0 ? commit.parent[0] : null;
-      } catch (error: any) {
-        if (error.code === "NotFoundError" || error.message?.includes("Could not find")) {
-          break;
-        }
-        throw error;
+  const urls = new Map<string, string>();
+  for (const section of sections.values()) {
+    if (section.path && section.url) urls.set(section.path, section.url);
+  }
+  return urls;
+}
+
+async function readGitmodules(fs: S3Fs, dir: string, rootTreeOid: string): Promise<Map<string, string>> {
+  try {
+    const { tree } = await git.readTree({ fs, dir, oid: rootTreeOid });
+    const entry = tree.find((e) => e.path === ".gitmodules" && e.type === "blob");
+    if (!entry) return new Map();
+    const { blob } = await git.readBlob({ fs, dir, oid: entry.oid });
+    return parseGitmodules(new TextDecoder().decode(blob));
+  } catch {
+    return new Map();
+  }
+}
+
+const isDirectoryEntry = (entry: TreeEntry) => entry.type === "tree" || entry.type === "commit";
+
+// Directories first, then by name. Listings are paged by the name of the last entry returned, so
+// every page has to come from this same order.
+export function compareTreeEntries(a: TreeEntry, b: TreeEntry): number {
+  if (isDirectoryEntry(a) === isDirectoryEntry(b)) return a.name.localeCompare(b.name);
+  return isDirectoryEntry(a) ? -1 : 1;
+}
+
+// Returns up to `limit` entries after the one named `after`. A cursor whose entry has since been
+// removed still resumes at the right place, since its position is found by sort order rather
+// than by lookup.
+export function pageTreeEntries<T extends TreeEntry>(
+  entries: T[],
+  after: { name: string; directory: boolean } | null,
+  limit: number
+): { entries: T[]; hasMore: boolean } {
+  let start = 0;
+  if (after) {
+    const marker = { name: after.name, type: after.directory ? "tree" : "blob" } as TreeEntry;
+    start = entries.findIndex((entry) => compareTreeEntries(entry, marker) > 0);
+    if (start === -1) start = entries.length;
+  }
+  return { entries: entries.slice(start, start + limit), hasMore: start + limit < entries.length };
+}
+
+async function treeOidAtPath(fs: S3Fs, dir: string, rootTree: string, filepath: string): Promise<string | null> {
+  let treeOid = rootTree;
+  for (const part of filepath.split("/").filter(Boolean)) {
+    const { tree } = await git.readTree({ fs, dir, oid: treeOid });
+    const entry = tree.find((e) => e.path === part);
+    if (!entry || entry.type !== "tree") return null;
+    treeOid = entry.oid;
+  }
+  return treeOid;
+}
+
+export interface EntryLastCommit {
+  path: string;
+  commitOid: string;
+  message: string;
+  authorName: string;
+  timestamp: number;
+}
+
+// How far back last-commit resolution follows first parents before giving up on the entries it
+// hasn't found yet; those are left out of the result rather than walking the whole history.
+const MAX_LAST_COMMIT_WALK = 5000;
+
+export async function resolveDirectoryTree(
+  store: GitStore,
+  ref: string,
+  filepath: string
+): Promise<{ commitOid: string; treeOid: string } | null> {
+  const commitOid = await resolveCommittish(store.fs, store.dir, ref);
+  if (!commitOid) return null;
+  const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: commitOid });
+  const treeOid = await treeOidAtPath(store.fs, store.dir, commit.tree, filepath);
+  return treeOid ? { commitOid, treeOid } : null;
+}
+
+// Finds the newest commit that changed each named entry of a directory in one first-parent walk
+// shared by all of them. Commits that leave the directory's tree untouched are skipped without
+// reading it, each tree read is reused for the next commit, and the walk stops as soon as every
+// entry is found or the directory didn't exist in the parent (so everything left was added).
+export async function getLastCommitsForEntries(
+  store: GitStore,
+  ref: string,
+  filepath: string,
+  names: string[]
+): Promise<EntryLastCommit[]> {
+  const oid = await resolveCommittish(store.fs, store.dir, ref);
+  if (!oid || names.length === 0) return [];
+
+  const pending = new Set(names);
+  const found: EntryLastCommit[] = [];
+  const entriesOf = async (treeOid: string | null) => {
+    const map = new Map<string, string>();
+    if (!treeOid) return map;
+    for (const entry of (await git.readTree({ fs: store.fs, dir: store.dir, oid: treeOid })).tree) {
+      map.set(entry.path, entry.oid);
+    }
+    return map;
+  };
+
+  const load = async (commitOid: string) => {
+    const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: commitOid });
+    return { oid: commitOid, commit, dirTree: await treeOidAtPath(store.fs, store.dir, commit.tree, filepath) };
+  };
+
+  let current: Awaited<ReturnType<typeof load>> | null = await load(oid);
+  let currentEntries: Map<string, string> | null = null;
+  for (let walked = 0; current && pending.size > 0 && walked < MAX_LAST_COMMIT_WALK; walked++) {
+    const parent: typeof current | null = current.commit.parent.length > 0 ? await load(current.commit.parent[0]) : null;
+    if (current.dirTree === parent?.dirTree) {
+      current = parent;
+      continue;
+    }
+
+    const now: Map<string, string> = currentEntries ?? (await entriesOf(current.dirTree));
+    const before = await entriesOf(parent?.dirTree ?? null);
+    for (const name of pending) {
+      const entryOid = now.get(name);
+      if (entryOid !== undefined && entryOid !== before.get(name)) {
+        pending.delete(name);
+        found.push({
+          path: filepath ? `${filepath}/${name}` : name,
+          commitOid: current.oid,
+          message: current.commit.message.split("\n")[0],
+          authorName: current.commit.author.name,
+          timestamp: current.commit.author.timestamp * 1000,
+        });
       }
     }
 
-    return count;
-  } catch (error) {
-    console.error("[Git] getCommitCount error:", error);
-    return 0;
+    current = parent;
+    currentEntries = before;
   }
+
+  return found;
 }
 
 export async function getTree(
@@ -341,13 +553,19 @@ export async function getTree(
       name: entry.path,
       path: filepath ? `${filepath}/${entry.path}` : entry.path,
       oid: entry.oid,
-      type: entry.type === "blob" ? "blob" : "tree",
+      type: entryTypeForMode(entry.mode),
     }));
 
-    entries.sort((a, b) => {
-      if (a.type === b.type) return a.name.localeCompare(b.name);
-      return a.type === "tree" ? -1 : 1;
-    });
+    if (entries.some((entry) => entry.type === "commit")) {
+      const submodules = await readGitmodules(fs, dir, commit.tree);
+      for (const entry of entries) {
+        if (entry.type === "commit") {
+          entry.submoduleUrl = submodules.get(entry.path) ?? null;
+        }
+      }
+    }
+
+    entries.sort(compareTreeEntries);
 
     return entries;
   } catch (error) {
@@ -447,6 +665,7 @@ export async function getCommitByOid(
           email: commit.author.email,
         },
         timestamp: commit.author.timestamp * 1000,
+        trailers: parseCommitTrailers(commit.message),
       },
       parent: commit.parent.length > 0 ? commit.parent[0] : null,
     };
@@ -456,7 +675,38 @@ export async function getCommitByOid(
   }
 }
 
-interface ChangedFile {
+export async function getCommitDetails(fs: S3Fs, dir: string, committish: string): Promise<CommitDetails | null> {
+  const oid = await resolveCommittish(fs, dir, committish);
+  if (!oid) {
+    return null;
+  }
+
+  try {
+    const { commit } = await git.readCommit({ fs, dir, oid });
+    return {
+      oid,
+      message: commit.message,
+      author: {
+        name: commit.author.name,
+        email: commit.author.email,
+      },
+      timestamp: commit.author.timestamp * 1000,
+      trailers: parseCommitTrailers(commit.message),
+      committer: {
+        name: commit.committer.name,
+        email: commit.committer.email,
+        timestamp: commit.committer.timestamp * 1000,
+      },
+      parents: commit.parent,
+      tree: commit.tree,
+    };
+  } catch (error) {
+    console.error(`[Git] getCommitDetails error for ${committish}:`, error);
+    return null;
+  }
+}
+
+export interface ChangedFile {
   path: string;
   status: string;
   oldOid: string | null;
@@ -468,33 +718,28 @@ async function compareTreesRecursive(
   dir: string,
   parentTreeOid: string | null,
   currentTreeOid: string,
-  basePath: string
+  basePath: string,
+  limit: Limiter = createLimiter(OBJECT_FETCH_CONCURRENCY)
 ): Promise<ChangedFile[]> {
-  const results: ChangedFile[] = [];
-
-  const currentEntries: TreeEntry[] = [];
-  const parentEntries: TreeEntry[] = [];
+  const [currentTree, parentTree] = await Promise.all([
+    limit(() => git.readTree({ fs, dir, oid: currentTreeOid })).catch((e) => {
+      console.error(`[Git] compareTreesRecursive: failed to read current tree ${currentTreeOid}:`, e);
+      return null;
+    }),
+    parentTreeOid
+      ? limit(() => git.readTree({ fs, dir, oid: parentTreeOid })).catch((e) => {
+          console.error(`[Git] compareTreesRecursive: failed to read parent tree ${parentTreeOid}:`, e);
+          return null;
+        })
+      : null,
+  ]);
 
-  try {
-    const currentTree = await git.readTree({ fs, dir, oid: currentTreeOid });
-    for (const entry of currentTree.tree) {
-      currentEntries.push(entry as TreeEntry);
-    }
-  } catch (e) {
-    console.error(`[Git] compareTreesRecursive: failed to read current tree ${currentTreeOid}:`, e);
-    return results;
+  if (!currentTree) {
+    return [];
   }
 
-  if (parentTreeOid) {
-    try {
-      const parentTree = await git.readTree({ fs, dir, oid: parentTreeOid });
-      for (const entry of parentTree.tree) {
-        parentEntries.push(entry as TreeEntry);
-      }
-    } catch (e) {
-      console.error(`[Git] compareTreesRecursive: failed to read parent tree ${parentTreeOid}:`, e);
-    }
-  }
+  const currentEntries = currentTree.tree as TreeEntry[];
+  const parentEntries = (parentTree?.tree ?? []) as TreeEntry[];
 
   const parentMap = new Map<string, TreeEntry>();
   for (const entry of parentEntries) {
@@ -506,25 +751,25 @@ async function compareTreesRecursive(
     currentMap.set(entry.path, entry);
   }
 
+  const pending: (ChangedFile[] | Promise<ChangedFile[]>)[] = [];
+
   for (const entry of currentEntries) {
     const fullPath = basePath ? `${basePath}/${entry.path}` : entry.path;
     const parentEntry = parentMap.get(entry.path);
 
     if (!parentEntry) {
       if (entry.type === "blob") {
-        results.push({ path: fullPath, status: "added", oldOid: null, newOid: entry.oid });
+        pending.push([{ path: fullPath, status: "added", oldOid: null, newOid: entry.oid }]);
       } else if (entry.type === "tree") {
-        const subResults = await compareTreesRecursive(fs, dir, null, entry.oid, fullPath);
-        results.push(...subResults);
+        pending.push(compareTreesRecursive(fs, dir, null, entry.oid, fullPath, limit));
       }
     } else if (parentEntry.oid !== entry.oid) {
       if (entry.type === "blob" && parentEntry.type === "blob") {
-        results.push({ path: fullPath, status: "modified", oldOid: parentEntry.oid, newOid: entry.oid });
+        pending.push([{ path: fullPath, status: "modified", oldOid: parentEntry.oid, newOid: entry.oid }]);
       } else if (entry.type === "tree" && parentEntry.type === "tree") {
-        const subResults = await compareTreesRecursive(fs, dir, parentEntry.oid, entry.oid, fullPath);
-        results.push(...subResults);
+        pending.push(compareTreesRecursive(fs, dir, parentEntry.oid, entry.oid, fullPath, limit));
       } else {
-        results.push({ path: fullPath, status: "modified", oldOid: parentEntry.oid, newOid: entry.oid });
+        pending.push([{ path: fullPath, status: "modified", oldOid: parentEntry.oid, newOid: entry.oid }]);
       }
     }
   }
@@ -533,116 +778,136 @@ async function compareTreesRecursive(
     if (!currentMap.has(entry.path)) {
       const fullPath = basePath ? `${basePath}/${entry.path}` : entry.path;
       if (entry.type === "blob") {
-        results.push({ path: fullPath, status: "deleted", oldOid: entry.oid, newOid: null });
+        pending.push([{ path: fullPath, status: "deleted", oldOid: entry.oid, newOid: null }]);
       } else if (entry.type === "tree") {
-        const subResults = await compareTreesRecursive(fs, dir, entry.oid, entry.oid, fullPath);
-        for (const r of subResults) {
-          results.push({ path: r.path, status: "deleted", oldOid: r.oldOid, newOid: null });
-        }
+        pending.push(
+          compareTreesRecursive(fs, dir, entry.oid, entry.oid, fullPath, limit).then((subResults) =>
+            subResults.map((r) => ({ path: r.path, status: "deleted", oldOid: r.oldOid, newOid: null }))
+          )
+        );
       }
     }
   }
 
-  return results;
+  return (await Promise.all(pending)).flat();
+}
+
+const BINARY_SNIFF_BYTES = 8000;
+const MAX_DIFF_BYTES = 1024 * 1024;
+
+// Same heuristic as git: a NUL byte near the start of the blob marks it as binary.
+function isBinaryContent(data: Uint8Array | null): boolean {
+  if (!data) return false;
+  return data.subarray(0, BINARY_SNIFF_BYTES).includes(0);
 }
 
+type BlobDiff = Pick<FileDiff, "hunks" | "isBinary" | "tooLarge" | "oldSize" | "newSize">;
+
 async function generateDiffHunks(
   fs: S3Fs,
   dir: string,
   oldOid: string | null,
   newOid: string | null,
   status: string
-): Promise<DiffHunk[]> {
+): Promise<BlobDiff> {
   try {
-    let oldContent = "";
-    let newContent = "";
-
-    if (oldOid) {
-      const { blob } = await git.readBlob({ fs, dir, oid: oldOid });
-      oldContent = new TextDecoder().decode(blob);
-    }
+    const [oldBlob, newBlob] = await Promise.all([
+      oldOid ? git.readBlob({ fs, dir, oid: oldOid }).then((r) => r.blob) : null,
+      newOid ? git.readBlob({ fs, dir, oid: newOid }).then((r) => r.blob) : null,
+    ]);
+
+    const meta = {
+      isBinary: isBinaryContent(oldBlob) || isBinaryContent(newBlob),
+      tooLarge: (oldBlob?.length ?? 0) + (newBlob?.length ?? 0) > MAX_DIFF_BYTES,
+      oldSize: oldBlob ? oldBlob.length : null,
+      newSize: newBlob ? newBlob.length : null,
+    };
 
-    if (newOid) {
-      const { blob } = await git.readBlob({ fs, dir, oid: newOid });
-      newContent = new TextDecoder().decode(blob);
+    if (meta.isBinary || meta.tooLarge) {
+      return { ...meta, hunks: [] };
     }
 
-    const oldLines = oldContent ? oldContent.split("\n") : [];
-    const newLines = newContent ? newContent.split("\n") : [];
-
-    if (status === "added") {
-      if (newLines.length === 0) return [];
-      return [{
-        oldStart: 0,
-        oldLines: 0,
-        newStart: 1,
-        newLines: newLines.length,
-        lines: newLines.map((content, i) => ({
-          type: "addition" as const,
-          content,
-          newLineNumber: i + 1,
-        })),
-      }];
-    }
+    const decoder = new TextDecoder();
+    return { ...meta, hunks: buildHunks(oldBlob ? decoder.decode(oldBlob) : "", newBlob ? decoder.decode(newBlob) : "", status) };
+  } catch (e) {
+    console.error(`[Git] generateDiffHunks error:`, e);
+    return { hunks: [], isBinary: false, tooLarge: false, oldSize: null, newSize: null };
+  }
+}
 
-    if (status === "deleted") {
-      if (oldLines.length === 0) return [];
-      return [{
-        oldStart: 1,
-        oldLines: oldLines.length,
-        newStart: 0,
-        newLines: 0,
-        lines: oldLines.map((content, i) => ({
-          type: "deletion" as const,
-          content,
-          oldLineNumber: i + 1,
-        })),
-      }];
-    }
+function buildHunks(oldContent: string, newContent: string, status: string): DiffHunk[] {
+  const oldLines = oldContent ? oldContent.split("\n") : [];
+  const newLines = newContent ? newContent.split("\n") : [];
+
+  if (status === "added") {
+    if (newLines.length === 0) return [];
+    return [{
+      oldStart: 0,
+      oldLines: 0,
+      newStart: 1,
+      newLines: newLines.length,
+      lines: newLines.map((content, i) => ({
+        type: "addition" as const,
+        content,
+        newLineNumber: i + 1,
+      })),
+    }];
+  }
 
-    const hunks: DiffHunk[] = [];
-    const diffResult = simpleDiff(oldLines, newLines);
-
-    if (diffResult.length > 0) {
-      let currentHunk: DiffHunk | null = null;
-
-      for (const change of diffResult) {
-        if (!currentHunk || change.oldLine > (currentHunk.oldStart + currentHunk.oldLines + 3)) {
-          if (currentHunk) hunks.push(currentHunk);
-          currentHunk = {
-            oldStart: Math.max(1, change.oldLine - 3),
-            oldLines: 0,
-            newStart: Math.max(1, change.newLine - 3),
-            newLines: 0,
-            lines: [],
-          };
-        }
+  if (status === "deleted") {
+    if (oldLines.length === 0) return [];
+    return [{
+      oldStart: 1,
+      oldLines: oldLines.length,
+      newStart: 0,
+      newLines: 0,
+      lines: oldLines.map((content, i) => ({
+        type: "deletion" as const,
+        content,
+        oldLineNumber: i + 1,
+      })),
+    }];
+  }
 
-        if (change.type === "delete") {
-          currentHunk.lines.push({
-            type: "deletion",
-            content: change.content,
-            oldLineNumber: change.oldLine,
-          });
-          currentHunk.oldLines++;
-        } else if (change.type === "insert") {
-          currentHunk.lines.push({
-            type: "addition",
-            content: change.content,
-            newLineNumber: change.newLine,
-          });
-          currentHunk.newLines++;
-        }
+  const hunks: DiffHunk[] = [];
+  const diffResult = simpleDiff(oldLines, newLines);
+
+  if (diffResult.length > 0) {
+    let currentHunk: DiffHunk | null = null;
+
+    for (const change of diffResult) {
+      if (!currentHunk || change.oldLine > (currentHunk.oldStart + currentHunk.oldLines + 3)) {
+        if (currentHunk) hunks.push(currentHunk);
+        currentHunk = {
+          oldStart: Math.max(1, change.oldLine - 3),
+          oldLines: 0,
+          newStart: Math.max(1, change.newLine - 3),
+          newLines: 0,
+          lines: [],
+        };
       }
 
-      if (currentHunk) hunks.push(currentHunk);
+      if (change.type === "delete") {
+        currentHunk.lines.push({
+          type: "deletion",
+          content: change.content,
+          oldLineNumber: change.oldLine,
+        });
+        currentHunk.oldLines++;
+      } else if (change.type === "insert") {
+        currentHunk.lines.push({
+          type: "addition",
+          content: change.content,
+          newLineNumber: change.newLine,
+        });
+        currentHunk.newLines++;
+      }
     }
 
-    return hunks;
-  } catch (e) {
-    console.error(`[Git] generateDiffHunks error:`, e);
-    return [];
+    if (currentHunk) hunks.push(currentHunk);
   }
+
+  return hunks;
 }
 
 interface DiffChange {
@@ -711,6 +976,10 @@ function simpleDiff(oldLines: string[], newLines: string[]): DiffChange[] {
   return changes;
 }
 
+export async function diffTrees(fs: S3Fs, dir: string, oldTreeOid: string | null, newTreeOid: string): Promise<ChangedFile[]> {
+  return compareTreesRecursive(fs, dir, oldTreeOid, newTreeOid, "");
+}
+
 export async function getCommitDiff(
   fs: S3Fs,
   dir: string,
@@ -733,6 +1002,7 @@ export async function getCommitDiff(
         email: commit.author.email,
       },
       timestamp: commit.author.timestamp * 1000,
+      trailers: parseCommitTrailers(commit.message),
     };
 
     const files: FileDiff[] = [];
@@ -759,9 +1029,12 @@ export async function getCommitDiff(
 
 
       const changedFiles = await compareTreesRecursive(fs, dir, parentTree, currentTree, "");
+      const fileHunks = await mapConcurrent(changedFiles, OBJECT_FETCH_CONCURRENCY, (file) =>
+        generateDiffHunks(fs, dir, file.oldOid, file.newOid, file.status)
+      );
 
-      for (const file of changedFiles) {
-        const hunks = await generateDiffHunks(fs, dir, file.oldOid, file.newOid, file.status);
+      for (const [index, file] of changedFiles.entries()) {
+        const { hunks, ...blobMeta } = fileHunks[index];
 
         let additionCount = 0;
         let deletionCount = 0;
@@ -781,6 +1054,8 @@ export async function getCommitDiff(
           additions: additionCount,
           deletions: deletionCount,
           hunks,
+          ...blobMeta,
+          mimeType: getContentType(file.path.split(".").pop()?.toLowerCase() || ""),
         });
       }
 
@@ -805,48 +1080,88 @@ export async function getCommitDiff(
   }
 }
 
+async function blobOidAtPath(fs: S3Fs, dir: string, commitOid: string, filepath: string): Promise<string | null> {
+  const parts = filepath.split("/").filter(Boolean);
+  const filename = parts.pop();
+  if (!filename) return null;
+
+  const { commit } = await git.readCommit({ fs, dir, oid: commitOid });
+  const treeOid = await treeOidAtPath(fs, dir, commit.tree, parts.join("/"));
+  if (!treeOid) return null;
+
+  const { tree } = await git.readTree({ fs, dir, oid: treeOid });
+  return tree.find((e) => e.path === filename && e.type === "blob")?.oid ?? null;
+}
+
+// One file's changes between any two commits, for history views that step through a file's
+// revisions without diffing every other path the commits touched. Null when the path is a file
+// in neither commit; an unchanged file comes back as modified with no hunks.
+export async function getFileDiffBetween(
+  fs: S3Fs,
+  dir: string,
+  baseOid: string,
+  headOid: string,
+  filepath: string
+): Promise<FileDiff | null> {
+  const [oldOid, newOid] = await Promise.all([
+    blobOidAtPath(fs, dir, baseOid, filepath),
+    blobOidAtPath(fs, dir, headOid, filepath),
+  ]);
+  if (!oldOid && !newOid) return null;
+
+  const status = !oldOid ? "added" : !newOid ? "deleted" : "modified";
+  const { hunks, ...blobMeta } = await generateDiffHunks(fs, dir, oldOid, newOid, status);
+  const lines = hunks.flatMap((hunk) => hunk.lines);
+
+  return {
+    path: filepath,
+    status,
+    additions: lines.filter((line) => line.type === "addition").length,
+    deletions: lines.filter((line) => line.type === "deletion").length,
+    hunks,
+    ...blobMeta,
+    mimeType: getContentType(filepath.split(".").pop()?.toLowerCase() || ""),
+  };
+}
+
+// The stored HEAD file is authoritative; repositories.default_branch is only used when it can't
+// be read. Either way HEAD is only advertised when it points at a branch that exists.
+async function resolveHeadTarget(fs: S3Fs, dir: string, defaultBranch: string | null): Promise<string | null> {
+  const stored = await git.resolveRef({ fs, dir, ref: "HEAD", depth: 2 }).catch(() => null);
+  if (stored?.startsWith("refs/heads/")) return stored;
+  return defaultBranch ? `refs/heads/${defaultBranch}` : null;
+}
+
 export async function getRefsAdvertisement(
   fs: S3Fs,
   dir: string,
-  service: string
+  service: string,
+  defaultBranch: string | null
 ): Promise<Buffer> {
   try {
-    const refs: string[] = [];
-    const capabilities =
-      service === "git-upload-pack"
-        ? "multi_ack thin-pack side-band side-band-64k ofs-delta shallow deepen-since deepen-not deepen-relative no-progress include-tag multi_ack_detailed symref=HEAD:refs/heads/main agent=gitbruv/1.0"
-        : "report-status report-status-v2 delete-refs quiet atomic ofs-delta push-options object-format=sha1 agent=gitbruv/1.0";
-
-    const branches = await git.listBranches({ fs, dir });
-    let headOid: string | null = null;
-
-    try {
-      headOid = await git.resolveRef({ fs, dir, ref: "HEAD" });
-    } catch {
-      headOid = null;
-    }
-
-    let first = true;
+    const branches = (await git.listBranches({ fs, dir })).sort();
+    const branchRefs: { name: string; oid: string }[] = [];
     for (const branch of branches) {
       try {
-        const normalizedBranch = normalizeRef(branch);
-        const oid = await git.resolveRef({ fs, dir, ref: normalizedBranch });
-        const refName = `refs/heads/${branch}`;
-
-        if (first) {
-          refs.push(`${oid} ${refName}\0${capabilities}\n`);
-          first = false;
-        } else {
-          refs.push(`${oid} ${refName}\n`);
-        }
+        const oid = await git.resolveRef({ fs, dir, ref: normalizeRef(branch) });
+        branchRefs.push({ name: `refs/heads/${branch}`, oid });
       } catch {
         continue;
       }
     }
 
-    if (refs.length === 0 && headOid) {
-      refs.push(`${headOid} refs/heads/main\0${capabilities}\n`);
-    }
+    // Without symref=HEAD clients guess the default branch from whichever ref shares HEAD's oid,
+    // which picks the wrong one whenever two branches point at the same commit.
+    const headTarget = service === "git-upload-pack" ? await resolveHeadTarget(fs, dir, defaultBranch) : null;
+    const head = headTarget ? branchRefs.find((ref) => ref.name === headTarget) : undefined;
+
+    const capabilities =
+      service === "git-upload-pack"
+        ? `multi_ack thin-pack side-band side-band-64k ofs-delta no-progress include-tag multi_ack_detailed${head ? ` symref=HEAD:${head.name}` : ""} agent=gitbruv/1.0`
+        : "report-status report-status-v2 delete-refs side-band-64k quiet atomic ofs-delta push-options object-format=sha1 agent=gitbruv/1.0";
+
+    const advertised = head ? [{ name: "HEAD", oid: head.oid }, ...branchRefs] : branchRefs;
+    const refs = advertised.map((ref, index) => (index === 0 ? `${ref.oid} ${ref.name}\0${capabilities}\n` : `${ref.oid} ${ref.name}\n`));
 
     if (refs.length === 0) {
       const zeroOid = "0".repeat(40);
@@ -872,6 +1187,22 @@ export async function getRefsAdvertisement(
   }
 }
 
+export async function getRefsAdvertisementCached(store: GitStore, service: string, defaultBranch: string | null): Promise<Buffer> {
+  const version = await repoCache.getRefsVersion(store.ownerId, store.repoName);
+  const cacheKey = repoCache.refsAdvertisementKey(store.ownerId, store.repoName, service, version);
+  const cached = await getCached<string>(cacheKey);
+  if (cached) {
+    return Buffer.from(cached, "base64");
+  }
+
+  const refs = await getRefsAdvertisement(store.fs, store.dir, service, defaultBranch);
+  // getRefsAdvertisement falls back to an empty list on errors; don't pin that for every client.
+  if (!hasStorageFailure()) {
+    await setCache(cacheKey, refs.toString("base64"), CACHE_TTL.refs);
+  }
+  return refs;
+}
+
 export async function listBranchesCached(store: GitStore): Promise<string[]> {
   const cacheKey = repoCache.branchesKey(store.ownerId, store.repoName);
   const cached = await getCached<string[]>(cacheKey);
@@ -890,13 +1221,20 @@ export async function getCommitsCached(
   limit: number,
   skip: number
 ): Promise<{ commits: CommitInfo[]; hasMore: boolean }> {
-  const cacheKey = repoCache.commitsKey(store.ownerId, store.repoName, ref, limit, skip);
+  // Keyed by the commit the ref points at, so a moved branch or tag simply misses and nothing
+  // needs invalidating on push. Callers that already resolved the ref skip the lookup.
+  const tip = /^[0-9a-f]{40}$/.test(ref) ? ref : await resolveCommittish(store.fs, store.dir, ref);
+  if (!tip) {
+    return { commits: [], hasMore: false };
+  }
+
+  const cacheKey = repoCache.commitsKey(store.ownerId, store.repoName, tip, limit, skip);
   const cached = await getCached<{ commits: CommitInfo[]; hasMore: boolean }>(cacheKey);
   if (cached && cached.commits && cached.commits.length > 0) {
     return cached;
   }
 
-  const result = await getCommits(store.fs, store.dir, ref, limit, skip);
+  const result = await getCommits(store.fs, store.dir, tip, limit, skip);
   if (result.commits.length > 0) {
     await setCache(cacheKey, result, CACHE_TTL.commits);
   }
@@ -917,6 +1255,10 @@ export async function getCommitCountCached(store: GitStore, ref: string): Promis
   return count;
 }
 
+// Huge directories are re-read from the tree object (itself cached as a git object) instead of
+// being stored in Redis as one multi-megabyte value.
+const MAX_CACHED_TREE_ENTRIES = 5000;
+
 export async function getTreeCached(
   store: GitStore,
   ref: string,
@@ -929,7 +1271,7 @@ export async function getTreeCached(
   }
 
   const tree = await getTree(store.fs, store.dir, ref, filepath);
-  if (tree) {
+  if (tree && tree.length <= MAX_CACHED_TREE_ENTRIES) {
     await setCache(cacheKey, tree, CACHE_TTL.tree);
   }
   return tree;
@@ -1086,23 +1428,25 @@ export async function compareBranches(
       ? await getCommitOidsUpTo(baseStore.fs, baseStore.dir, baseOid, mergeBaseOid)
       : [];
 
-    const commits: CommitInfo[] = [];
-    for (const oid of headCommitOids) {
-      try {
-        const { commit } = await git.readCommit({ fs: headStore.fs, dir: headStore.dir, oid });
-        commits.push({
-          oid,
-          message: commit.message,
-          author: {
-            name: commit.author.name,
-            email: commit.author.email,
-          },
-          timestamp: commit.author.timestamp * 1000,
-        });
-      } catch {
-        continue;
-      }
-    }
+    const commits = (
+      await mapConcurrent(headCommitOids, OBJECT_FETCH_CONCURRENCY, async (oid): Promise<CommitInfo | null> => {
+        try {
+          const { commit } = await git.readCommit({ fs: headStore.fs, dir: headStore.dir, oid });
+          return {
+            oid,
+            message: commit.message,
+            author: {
+              name: commit.author.name,
+              email: commit.author.email,
+            },
+            timestamp: commit.author.timestamp * 1000,
+            trailers: parseCommitTrailers(commit.message),
+          };
+        } catch {
+          return null;
+        }
+      })
+    ).filter((commit) => commit !== null);
 
     let files: FileDiff[] = [];
     let stats = { additions: 0, deletions: 0, filesChanged: 0 };
@@ -1120,8 +1464,12 @@ export async function compareBranches(
           ""
         );
 
-        for (const file of changedFiles) {
-          const hunks = await generateDiffHunks(headStore.fs, headStore.dir, file.oldOid, file.newOid, file.status);
+        const fileHunks = await mapConcurrent(changedFiles, OBJECT_FETCH_CONCURRENCY, (file) =>
+          generateDiffHunks(headStore.fs, headStore.dir, file.oldOid, file.newOid, file.status)
+        );
+
+        for (const [index, file] of changedFiles.entries()) {
+          const { hunks, ...blobMeta } = fileHunks[index];
 
           let additionCount = 0;
           let deletionCount = 0;
@@ -1141,6 +1489,8 @@ export async function compareBranches(
             additions: additionCount,
             deletions: deletionCount,
             hunks,
+            ...blobMeta,
+            mimeType: getContentType(file.path.split(".").pop()?.toLowerCase() || ""),
           });
         }
 
@@ -1168,6 +1518,28 @@ export async function compareBranches(
   }
 }
 
+// Paths touched between the merge base and the head branch, without reading any blobs. Used
+// where only the file list matters, e.g. matching CODEOWNERS.
+export async function getChangedPaths(
+  baseStore: GitStore,
+  baseBranch: string,
+  headStore: GitStore,
+  headBranch: string
+): Promise<string[]> {
+  const mergeBaseOid = await getMergeBase(baseStore, baseBranch, headStore, headBranch);
+  if (!mergeBaseOid) return [];
+
+  const headOid = await git.resolveRef({ fs: headStore.fs, dir: headStore.dir, ref: normalizeRef(headBranch) });
+  const [baseTreeOid, headTreeOid] = await Promise.all([
+    getTreeOidForCommit(headStore.fs, headStore.dir, mergeBaseOid),
+    getTreeOidForCommit(headStore.fs, headStore.dir, headOid),
+  ]);
+  if (!baseTreeOid || !headTreeOid) return [];
+
+  const changed = await compareTreesRecursive(headStore.fs, headStore.dir, baseTreeOid, headTreeOid, "");
+  return changed.map((file) => file.path);
+}
+
 async function getTreeOidForCommit(fs: S3Fs, dir: string, oid: string): Promise<string | null> {
   try {
     const { commit } = await git.readCommit({ fs, dir, oid });
@@ -1237,23 +1609,28 @@ async function copyGitObject(
 async function copyTreeRecursive(
   sourceStore: GitStore,
   targetStore: GitStore,
-  treeOid: string
+  treeOid: string,
+  limit: Limiter = createLimiter(OBJECT_FETCH_CONCURRENCY)
 ): Promise<boolean> {
   try {
-    if (!await copyGitObject(sourceStore, targetStore, treeOid, "tree")) {
+    if (!await limit(() => copyGitObject(sourceStore, targetStore, treeOid, "tree"))) {
       return false;
     }
 
-    const tree = await git.readTree({ fs: sourceStore.fs, dir: sourceStore.dir, oid: treeOid });
-    
-    for (const entry of tree.tree) {
-      if (entry.type === "blob") {
-        await copyGitObject(sourceStore, targetStore, entry.oid, "blob");
-      } else if (entry.type === "tree") {
-        await copyTreeRecursive(sourceStore, targetStore, entry.oid);
-      }
-    }
-    
+    const tree = await limit(() => git.readTree({ fs: sourceStore.fs, dir: sourceStore.dir, oid: treeOid }));
+
+    await Promise.all(
+      tree.tree.map((entry) => {
+        if (entry.type === "blob") {
+          return limit(() => copyGitObject(sourceStore, targetStore, entry.oid, "blob"));
+        }
+        if (entry.type === "tree") {
+          return copyTreeRecursive(sourceStore, targetStore, entry.oid, limit);
+        }
+        return null;
+      })
+    );
+
     return true;
   } catch (error) {
     console.error(`[Git] copyTreeRecursive error for ${treeOid}:`, error);
@@ -1311,7 +1688,7 @@ export async function performMerge(
   mergeMessage: string,
   authorName: string,
   authorEmail: string
-): Promise<{ mergeCommitOid: string } | null> {
+): Promise<{ mergeCommitOid: string; baseOid: string; headOid: string } | null> {
   try {
     console.log(`[Git] performMerge starting: base=${baseBranch}, head=${headBranch}`);
     console.log(`[Git] baseStore: owner=${baseStore.ownerId}, repo=${baseStore.repoName}`);
@@ -1397,7 +1774,7 @@ export async function performMerge(
       console.error(`[Git] WARNING: Ref verification failed! Expected ${mergeCommitOid} but got ${verifyOid}`);
     }
 
-    return { mergeCommitOid };
+    return { mergeCommitOid, baseOid, headOid };
   } catch (error) {
     console.error("[Git] performMerge error:", error);
     return null;
@@ -1436,4 +1813,30 @@ export async function isAncestor(
   return false;
 }
 
+// `defaultBranch` is the repository's, not necessarily `branch`; it picks the advertised HEAD, so
+// the warmed advertisement is the one clients are served.
+export async function warmBranchCaches(store: GitStore, branch: string, defaultBranch: string): Promise<void> {
+  const warmReadme = async () => {
+    const files = await getTreeCached(store, branch, "");
+    const readme = files?.find((f) => f.name.toLowerCase() === "readme.md" && f.type === "blob");
+    if (readme) {
+      await getFileCached(store, branch, readme.path);
+    }
+  };
+
+  const results = await Promise.allSettled([
+    warmReadme(),
+    getCommitsCached(store, branch, 30, 0),
+    getCommitCountCached(store, branch),
+    listBranchesCached(store),
+    getRefsAdvertisementCached(store, "git-upload-pack", defaultBranch),
+  ]);
+
+  for (const result of results) {
+    if (result.status === "rejected") {
+      console.error(`[Git] warmBranchCaches: ${store.repoName}@${branch} failed:`, result.reason);
+    }
+  }
+}
+
 export { repoCache };
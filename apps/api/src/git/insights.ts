@@ -0,0 +1,72 @@
+import { walkCommits, getCommitDiff, type GitStore } from "./index";
+
+export interface RepoInsights {
+  codeFrequency: [number, number, number][];
+  punchCard: [number, number, number][];
+  commitCount: number;
+  truncated: boolean;
+}
+
+// Diffing every commit is the expensive part, so history beyond this is left out of both charts
+// and the result is flagged as truncated.
+const MAX_COMMITS = 10000;
+
+const WEEK_SECONDS = 7 * 24 * 60 * 60;
+
+function weekStart(timestamp: number): number {
+  const day = Math.floor(timestamp / 86400);
+  // 1970-01-01 was a Thursday; shift so weeks start on Sunday.
+  const sunday = day - ((day + 4) % 7);
+  return sunday * 86400;
+}
+
+export async function computeRepoInsights(store: GitStore, headOid: string): Promise<RepoInsights> {
+  const punchCounts = new Map<string, number>();
+  const weeks = new Map<number, { additions: number; deletions: number }>();
+  let commitCount = 0;
+  let truncated = false;
+
+  for await (const { oid, commit } of walkCommits(store.fs, store.dir, headOid)) {
+    if (commitCount >= MAX_COMMITS) {
+      truncated = true;
+      break;
+    }
+    commitCount++;
+
+    // Punch card hours are in the author's own timezone, the way `git log` prints them.
+    const local = new Date((commit.author.timestamp - commit.author.timezoneOffset * 60) * 1000);
+    const punchKey = `${local.getUTCDay()}:${local.getUTCHours()}`;
+    punchCounts.set(punchKey, (punchCounts.get(punchKey) ?? 0) + 1);
+
+    // Merge commits only repeat changes already counted on the merged branch.
+    if (commit.parent.length > 1) continue;
+
+    const diff = await getCommitDiff(store.fs, store.dir, oid);
+    if (!diff) continue;
+
+    const week = weekStart(commit.author.timestamp);
+    const totals = weeks.get(week) ?? { additions: 0, deletions: 0 };
+    totals.additions += diff.stats.additions;
+    totals.deletions += diff.stats.deletions;
+    weeks.set(week, totals);
+  }
+
+  const codeFrequency: [number, number, number][] = [];
+  if (weeks.size > 0) {
+    const first = Math.min(...weeks.keys());
+    const last = Math.max(...weeks.keys());
+    for (let week = first; week <= last; week += WEEK_SECONDS) {
+      const totals = weeks.get(week);
+      codeFrequency.push([week, totals?.additions ?? 0, -(totals?.deletions ?? 0)]);
+    }
+  }
+
+  const punchCard: [number, number, number][] = [];
+  for (let day = 0; day < 7; day++) {
+    for (let hour = 0; hour < 24; hour++) {
+      punchCard.push([day, hour, punchCounts.get(`${day}:${hour}`) ?? 0]);
+    }
+  }
+
+  return { codeFrequency, punchCard, commitCount, truncated };
+}
@@ -0,0 +1,180 @@
+import git from "isomorphic-git";
+import { resolveRefOid, type GitStore } from "./index";
+
+export interface LanguageStat {
+  language: string;
+  bytes: number;
+  percentage: number;
+}
+
+const MAX_FILES = 5000;
+const READ_BATCH_SIZE = 25;
+
+const EXTENSION_LANGUAGES: Record<string, string> = {
+  ts: "TypeScript",
+  tsx: "TypeScript",
+  mts: "TypeScript",
+  cts: "TypeScript",
+  js: "JavaScript",
+  jsx: "JavaScript",
+  mjs: "JavaScript",
+  cjs: "JavaScript",
+  py: "Python",
+  rb: "Ruby",
+  go: "Go",
+  rs: "Rust",
+  java: "Java",
+  kt: "Kotlin",
+  kts: "Kotlin",
+  scala: "Scala",
+  swift: "Swift",
+  m: "Objective-C",
+  mm: "Objective-C++",
+  c: "C",
+  h: "C",
+  cc: "C++",
+  cpp: "C++",
+  cxx: "C++",
+  hpp: "C++",
+  hh: "C++",
+  cs: "C#",
+  fs: "F#",
+  php: "PHP",
+  pl: "Perl",
+  lua: "Lua",
+  r: "R",
+  dart: "Dart",
+  ex: "Elixir",
+  exs: "Elixir",
+  erl: "Erlang",
+  hs: "Haskell",
+  clj: "Clojure",
+  ml: "OCaml",
+  zig: "Zig",
+  nim: "Nim",
+  sol: "Solidity",
+  sh: "Shell",
+  bash: "Shell",
+  zsh: "Shell",
+  fish: "Shell",
+  ps1: "PowerShell",
+  sql: "SQL",
+  html: "HTML",
+  htm: "HTML",
+  css: "CSS",
+  scss: "SCSS",
+  sass: "Sass",
+  less: "Less",
+  vue: "Vue",
+  svelte: "Svelte",
+  astro: "Astro",
+  dockerfile: "Dockerfile",
+  tf: "HCL",
+  hcl: "HCL",
+  nix: "Nix",
+  proto: "Protocol Buffer",
+  graphql: "GraphQL",
+  gql: "GraphQL",
+};
+
+const FILENAME_LANGUAGES: Record<string, string> = {
+  dockerfile: "Dockerfile",
+  makefile: "Makefile",
+  "cmakelists.txt": "CMake",
+  gemfile: "Ruby",
+  rakefile: "Ruby",
+};
+
+const VENDORED_PATTERNS = [
+  /(^|\/)node_modules\//,
+  /(^|\/)vendor\//,
+  /(^|\/)third_party\//,
+  /(^|\/)dist\//,
+  /(^|\/)build\//,
+  /\.min\.(js|css)$/,
+  /(^|\/)(package-lock\.json|yarn\.lock|pnpm-lock\.yaml|bun\.lockb?)$/,
+];
+
+export function detectLanguage(path: string): string | null {
+  if (VENDORED_PATTERNS.some((pattern) => pattern.test(path))) {
+    return null;
+  }
+
+  const filename = path.split("/").pop()!.toLowerCase();
+  if (FILENAME_LANGUAGES[filename]) {
+    return FILENAME_LANGUAGES[filename];
+  }
+
+  const dot = filename.lastIndexOf(".");
+  if (dot <= 0) {
+    return null;
+  }
+
+  return EXTENSION_LANGUAGES[filename.slice(dot + 1)] || null;
+}
+
+async function collectFiles(store: GitStore, treeOid: string, prefix: string, files: { path: string; oid: string }[]) {
+  const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid: treeOid });
+
+  for (const entry of tree) {
+    if (files.length >= MAX_FILES) return;
+
+    const path = prefix ? `${prefix}/${entry.path}` : entry.path;
+    if (entry.type === "tree") {
+      if (!VENDORED_PATTERNS.some((pattern) => pattern.test(`${path}/`))) {
+        await collectFiles(store, entry.oid, path, files);
+      }
+    } else if (entry.type === "blob" && detectLanguage(path)) {
+      files.push({ path, oid: entry.oid });
+    }
+  }
+}
+
+export async function computeLanguageStats(
+  store: GitStore,
+  ref: string
+): Promise<{ commitOid: string; languages: LanguageStat[] } | null> {
+  let commitOid: string;
+  try {
+    commitOid = await resolveRefOid(store, ref);
+  } catch {
+    return null;
+  }
+
+  const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: commitOid });
+
+  const files: { path: string; oid: string }[] = [];
+  await collectFiles(store, commit.tree, "", files);
+
+  const totals = new Map<string, number>();
+  for (let i = 0; i < files.length; i += READ_BATCH_SIZE) {
+    const batch = files.slice(i, i + READ_BATCH_SIZE);
+    const sizes = await Promise.all(
+      batch.map(async (file) => {
+        try {
+          const { blob } = await git.readBlob({ fs: store.fs, dir: store.dir, oid: file.oid });
+          return blob.length;
+        } catch {
+          return 0;
+        }
+      })
+    );
+
+    batch.forEach((file, index) => {
+      const language = detectLanguage(file.path)!;
+      totals.set(language, (totals.get(language) || 0) + sizes[index]);
+    });
+  }
+
+  const totalBytes = [...totals.values()].reduce((sum, bytes) => sum + bytes, 0);
+  const languages = [...totals.entries()]
+    .filter(([, bytes]) => bytes > 0)
+    .map(([language, bytes]) => ({
+      language,
+      bytes,
+      percentage: Math.round((bytes / totalBytes) * 1000) / 10,
+    }))
+    .sort((a, b) => b.bytes - a.bytes);
+
+  return { commitOid, languages };
+}
@@ -0,0 +1,66 @@
+export type LargeFile = { oid: string; size: number; path: string | null };
+
+type UnpackedObject = { oid: string; type: string; data: Buffer };
+
+// Tree entries are "<mode> <name>\0" followed by the 20-byte oid.
+function parseTreeEntries(data: Buffer): { name: string; oid: string }[] {
+  const entries: { name: string; oid: string }[] = [];
+  let offset = 0;
+  while (offset < data.length) {
+    const space = data.indexOf(0x20, offset);
+    const nul = data.indexOf(0, space + 1);
+    if (space === -1 || nul === -1 || nul + 21 > data.length) break;
+    entries.push({ name: data.toString("utf8", space + 1, nul), oid: data.toString("hex", nul + 1, nul + 21) });
+    offset = nul + 21;
+  }
+  return entries;
+}
+
+// Blobs in a push that are over `threshold`, with the path they were pushed at. A new blob
+// changes the oid of every tree above it, so those trees are in the same pack and the path can
+// be rebuilt from it alone; it's null only for a blob that no pushed tree points at.
+export function findLargeFiles(objects: UnpackedObject[], threshold: number): LargeFile[] {
+  const large = objects
+    .filter((object) => object.type === "blob" && object.data.length > threshold)
+    .map((object) => ({ oid: object.oid, size: object.data.length }));
+  if (large.length === 0) return [];
+  return locateLargeFiles(objects.filter((object) => object.type === "tree"), large);
+}
+
+// The path half of findLargeFiles, for callers that don't keep every blob's data around.
+export function locateLargeFiles(trees: { oid: string; data: Buffer }[], large: { oid: string; size: number }[]): LargeFile[] {
+  const parents = new Map<string, { tree: string; name: string }>();
+  for (const tree of trees) {
+    for (const entry of parseTreeEntries(tree.data)) {
+      if (!parents.has(entry.oid)) parents.set(entry.oid, { tree: tree.oid, name: entry.name });
+    }
+  }
+
+  return large.map((blob) => {
+    const names: string[] = [];
+    const seen = new Set<string>();
+    let current = parents.get(blob.oid);
+    while (current && !seen.has(current.tree)) {
+      names.unshift(current.name);
+      seen.add(current.tree);
+      current = parents.get(current.tree);
+    }
+    return { oid: blob.oid, size: blob.size, path: names.length > 0 ? names.join("/") : null };
+  });
+}
+
+function formatMegabytes(bytes: number) {
+  return `${(bytes / 1024 / 1024).toFixed(2)} MB`;
+}
+
+// Worded after what git hosts print, so the lines read naturally among git's own output.
+export function largeFileWarnings(files: LargeFile[], warnBytes: number, maxBytes: number): string[] {
+  if (files.length === 0) return [];
+  return [
+    ...files.map(
+      (file) =>
+        `warning: File ${file.path ?? file.oid} is ${formatMegabytes(file.size)}; this is larger than the recommended maximum file size of ${formatMegabytes(warnBytes)}`
+    ),
+    `warning: Consider tracking large files with Git LFS (https://git-lfs.com); files over ${formatMegabytes(maxBytes)} are rejected`,
+  ];
+}
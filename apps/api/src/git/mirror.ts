@@ -0,0 +1,239 @@
+import git, { type GitHttpRequest, type GitHttpResponse, type HttpClient } from "isomorphic-git";
+import { lookup, type LookupAddress, type LookupOptions } from "dns";
+import { lookup as lookupAll } from "dns/promises";
+import type { LookupFunction } from "net";
+import { request as httpRequest, type IncomingMessage } from "http";
+import { request as httpsRequest } from "https";
+import type { GitStore } from "./index";
+
+export const MIN_MIRROR_INTERVAL_MINUTES = 10;
+export const MAX_MIRROR_INTERVAL_MINUTES = 7 * 24 * 60;
+export const DEFAULT_MIRROR_INTERVAL_MINUTES = 60;
+
+const FETCH_REMOTE = "mirror";
+const MAX_REDIRECTS = 5;
+
+// The whole sync, including downloading the pack, has to finish within this; an upstream that
+// stops sending would otherwise hold the mirror in "syncing" forever.
+export const MIRROR_FETCH_TIMEOUT_MS = 30 * 60 * 1000;
+
+function parseIPv4(address: string): number[] | null {
+  const parts = address.split(".").map(Number);
+  return parts.length === 4 && parts.every((part) => Number.isInteger(part) && part >= 0 && part <= 255) ? parts : null;
+}
+
+// Expands an IPv6 address into its eight 16-bit groups, including the dotted form of mapped IPv4 addresses.
+function parseIPv6(address: string): number[] | null {
+  let text = address.split("%")[0].toLowerCase();
+  const dotted = text.match(/^(.*:)(\d+\.\d+\.\d+\.\d+)$/);
+  if (dotted) {
+    const v4 = parseIPv4(dotted[2]);
+    if (!v4) return null;
+    text = `${dotted[1]}${((v4[0] << 8) | v4[1]).toString(16)}:${((v4[2] << 8) | v4[3]).toString(16)}`;
+  }
+  const [head, tail, ...rest] = text.split("::");
+  if (rest.length > 0) return null;
+  const left = head ? head.split(":") : [];
+  const right = tail === undefined ? [] : tail ? tail.split(":") : [];
+  const fill = tail === undefined ? 0 : 8 - left.length - right.length;
+  if (fill < 0) return null;
+  const groups = [...left, ...Array(fill).fill("0"), ...right].map((group) => parseInt(group, 16));
+  return groups.length === 8 && groups.every((group) => group >= 0 && group <= 0xffff) ? groups : null;
+}
+
+function isPublicIPv4([a, b]: number[]): boolean {
+  if (a === 0 || a === 10 || a === 127 || a >= 224) return false;
+  if (a === 100 && b >= 64 && b < 128) return false;
+  if (a === 169 && b === 254) return false;
+  if (a === 172 && b >= 16 && b < 32) return false;
+  if (a === 192 && (b === 168 || b === 0)) return false;
+  if (a === 198 && (b === 18 || b === 19)) return false;
+  return true;
+}
+
+// Loopback, private, link-local (which covers cloud metadata endpoints), shared and reserved ranges
+// are all unreachable from a mirror, however the upstream host name resolves.
+export function isPublicAddress(address: string): boolean {
+  const v4 = parseIPv4(address);
+  if (v4) return isPublicIPv4(v4);
+
+  const v6 = parseIPv6(address);
+  if (!v6) return false;
+  if (v6.slice(0, 5).every((group) => group === 0) && (v6[5] === 0xffff || v6[5] === 0)) {
+    return isPublicIPv4([v6[6] >> 8, v6[6] & 0xff, v6[7] >> 8, v6[7] & 0xff]);
+  }
+  if (v6[0] === 0x64 && v6[1] === 0xff9b) {
+    return isPublicIPv4([v6[6] >> 8, v6[6] & 0xff, v6[7] >> 8, v6[7] & 0xff]);
+  }
+  if ((v6[0] & 0xfe00) === 0xfc00 || (v6[0] & 0xffc0) === 0xfe80 || (v6[0] & 0xff00) === 0xff00) return false;
+  if (v6[0] === 0x2001 && v6[1] === 0x0db8) return false;
+  return true;
+}
+
+export async function validateMirrorUrl(value: unknown): Promise<{ url: string } | { error: string }> {
+  if (typeof value !== "string" || !value.trim()) {
+    return { error: "Mirror URL is required" };
+  }
+
+  let url: URL;
+  try {
+    url = new URL(value.trim());
+  } catch {
+    return { error: "Mirror URL is invalid" };
+  }
+
+  if (url.protocol !== "https:" && url.protocol !== "http:") {
+    return { error: "Mirror URL must use http or https" };
+  }
+  // Repository rows are serialized in several places, so upstream credentials are never stored.
+  if (url.username || url.password) {
+    return { error: "Mirror URL must not contain credentials" };
+  }
+
+  const resolved = await resolvesToPublicHost(url);
+  if (resolved === null) {
+    return { error: "Mirror URL host could not be resolved" };
+  }
+  if (!resolved) {
+    return { error: "Mirror URL must point to a public host" };
+  }
+
+  return { url: url.toString() };
+}
+
+// True when every address the URL's host resolves to is public, null when it doesn't resolve.
+export async function resolvesToPublicHost(url: URL): Promise<boolean | null> {
+  const hostname = url.hostname.replace(/^\[|\]$/g, "");
+  let addresses: LookupAddress[];
+  try {
+    addresses = await lookupAll(hostname, { all: true, verbatim: true });
+  } catch {
+    return null;
+  }
+  return addresses.length > 0 && addresses.every((entry) => isPublicAddress(entry.address));
+}
+
+// Checks the addresses a connection is actually made to, so a host that resolves differently
+// after validation still cannot reach an internal address.
+export const publicLookup: LookupFunction = (hostname, options, callback) => {
+  lookup(hostname, { ...(options as LookupOptions), all: true }, (error, addresses) => {
+    if (error) return callback(error, "", 0);
+    const blocked = addresses.find((entry) => !isPublicAddress(entry.address));
+    if (blocked || addresses.length === 0) {
+      return callback(new Error(`Host ${hostname} resolves to a non-public address`), "", 0);
+    }
+    if ((options as LookupOptions).all) {
+      (callback as unknown as (error: null, addresses: LookupAddress[]) => void)(null, addresses);
+    } else {
+      callback(null, addresses[0].address, addresses[0].family);
+    }
+  });
+};
+
+async function collectBody(body: GitHttpRequest["body"]): Promise<Buffer | undefined> {
+  if (!body) return undefined;
+  const chunks: Uint8Array[] = [];
+  for await (const chunk of body) chunks.push(chunk);
+  return Buffer.concat(chunks);
+}
+
+function send(url: URL, method: string, headers: Record<string, string>, body: Buffer | undefined, signal: AbortSignal): Promise<IncomingMessage> {
+  const request = url.protocol === "https:" ? httpsRequest : httpRequest;
+  return new Promise((resolve, reject) => {
+    const req = request(url, { method, headers, lookup: publicLookup, signal }, resolve);
+    req.on("error", reject);
+    req.end(body);
+  });
+}
+
+// isomorphic-git's bundled client follows redirects on its own; this one validates every hop.
+// Aborting `signal` tears down whichever request or response body is in flight.
+const createMirrorHttp = (signal: AbortSignal): HttpClient => ({
+  async request({ url, method = "GET", headers = {}, body }: GitHttpRequest): Promise<GitHttpResponse> {
+    const payload = await collectBody(body);
+    let current = url;
+
+    for (let hop = 0; ; hop++) {
+      signal.throwIfAborted();
+      const validated = await validateMirrorUrl(current);
+      if ("error" in validated) throw new Error(validated.error);
+
+      const target = new URL(validated.url);
+      const response = await send(target, method, headers, payload, signal);
+      const location = response.headers.location;
+      if (response.statusCode && response.statusCode >= 300 && response.statusCode < 400 && location) {
+        response.resume();
+        if (hop >= MAX_REDIRECTS) throw new Error("Mirror upstream redirected too many times");
+        current = new URL(location, target).toString();
+        continue;
+      }
+
+      return {
+        url: target.toString(),
+        method,
+        statusCode: response.statusCode ?? 0,
+        statusMessage: response.statusMessage ?? "",
+        headers: Object.fromEntries(
+          Object.entries(response.headers).map(([name, value]) => [name, Array.isArray(value) ? value.join(", ") : value ?? ""])
+        ),
+        body: response[Symbol.asyncIterator](),
+      };
+    }
+  },
+});
+
+export function validateMirrorInterval(value: unknown): number | null {
+  if (typeof value !== "number" || !Number.isInteger(value)) return null;
+  if (value < MIN_MIRROR_INTERVAL_MINUTES || value > MAX_MIRROR_INTERVAL_MINUTES) return null;
+  return value;
+}
+
+// Fetches every branch and tag from the upstream and force-updates the local refs to match,
+// removing any that disappeared upstream. Returns the upstream's default branch when it advertises one.
+export async function fetchMirror(
+  store: GitStore,
+  url: string,
+  timeoutMs = MIRROR_FETCH_TIMEOUT_MS
+): Promise<{ defaultBranch: string | null; refCount: number }> {
+  const { fs, dir } = store;
+  const signal = AbortSignal.timeout(timeoutMs);
+  const http = createMirrorHttp(signal);
+  const remoteRefs = await git.listServerRefs({ http, url, symrefs: true });
+
+  await git.fetch({ fs, http, dir, url, remote: FETCH_REMOTE, singleBranch: false, tags: true });
+  // Refs are only rewritten after a complete fetch, never from one cut short by the timeout.
+  signal.throwIfAborted();
+
+  const wanted = new Map<string, string>();
+  for (const ref of remoteRefs) {
+    if ((ref.ref.startsWith("refs/heads/") || ref.ref.startsWith("refs/tags/")) && !ref.ref.endsWith("^{}")) {
+      wanted.set(ref.ref, ref.oid);
+    }
+  }
+
+  for (const [ref, oid] of wanted) {
+    await git.writeRef({ fs, dir, ref, value: oid, force: true });
+  }
+
+  const local = [
+    ...(await git.listBranches({ fs, dir })).map((branch) => `refs/heads/${branch}`),
+    ...(await git.listTags({ fs, dir })).map((tag) => `refs/tags/${tag}`),
+  ];
+  for (const ref of local) {
+    if (!wanted.has(ref)) {
+      await git.deleteRef({ fs, dir, ref });
+    }
+  }
+
+  for (const branch of await git.listBranches({ fs, dir, remote: FETCH_REMOTE })) {
+    await git.deleteRef({ fs, dir, ref: `refs/remotes/${FETCH_REMOTE}/${branch}` }).catch(() => {});
+  }
+
+  const head = remoteRefs.find((ref) => ref.ref === "HEAD");
+  const target = head?.target?.startsWith("refs/heads/") ? head.target : null;
+  if (target && wanted.has(target)) {
+    await git.writeRef({ fs, dir, ref: "HEAD", value: target, symbolic: true, force: true });
+  }
+
+  return { defaultBranch: target ? target.slice("refs/heads/".length) : null, refCount: wanted.size };
+}
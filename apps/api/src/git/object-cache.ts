@@ -0,0 +1,60 @@
+import { config } from "../config";
+
+// Loose objects are content-addressed, so a key never changes once written and entries
+// can be shared by every store in the process without invalidation. Keys are either under a
+// repository prefix or under a fork network's shared prefix (objects/<network>/).
+const LOOSE_OBJECT_KEY = /(?:^|\/)objects\/(?:[0-9a-f-]{36}\/)?[0-9a-f]{2}\/[0-9a-f]{38}$/;
+
+export function isImmutableObjectKey(key: string): boolean {
+  return LOOSE_OBJECT_KEY.test(key);
+}
+
+export class ObjectCache {
+  private entries = new Map<string, Buffer>();
+  private bytes = 0;
+  private hits = 0;
+  private misses = 0;
+
+  constructor(private readonly maxBytes: number) {}
+
+  has(key: string): boolean {
+    return this.entries.has(key);
+  }
+
+  get(key: string): Buffer | undefined {
+    const value = this.entries.get(key);
+    if (!value) {
+      this.misses++;
+      return undefined;
+    }
+    this.hits++;
+    this.entries.delete(key);
+    this.entries.set(key, value);
+    return value;
+  }
+
+  set(key: string, value: Buffer) {
+    if (value.length > this.maxBytes / 8) return;
+
+    const existing = this.entries.get(key);
+    if (existing) {
+      this.bytes -= existing.length;
+      this.entries.delete(key);
+    }
+
+    this.entries.set(key, value);
+    this.bytes += value.length;
+
+    for (const [oldestKey, oldest] of this.entries) {
+      if (this.bytes <= this.maxBytes) break;
+      this.entries.delete(oldestKey);
+      this.bytes -= oldest.length;
+    }
+  }
+
+  stats() {
+    return { entries: this.entries.size, bytes: this.bytes, maxBytes: this.maxBytes, hits: this.hits, misses: this.misses };
+  }
+}
+
+export const objectCache = new ObjectCache(config.git.objectCacheBytes);
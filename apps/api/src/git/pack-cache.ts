@@ -0,0 +1,65 @@
+import { createHash } from "crypto";
+import { getCached, setCache, deleteCache, deleteCachePattern, countHit, repoCache, CACHE_TTL } from "../cache";
+import { getObject, putObject, deletePrefix } from "../s3";
+import { storageLayout } from "../storage-layout";
+import { hasStorageFailure } from "../tracing";
+import { trackTask } from "../metrics";
+import { config } from "../config";
+import { buildPack } from "./upload-pack";
+import type { GitStore } from "./index";
+
+// The pack for a (wants, common haves) pair is a pure function of immutable objects, so a cached
+// copy can never be wrong, only unused. Pushes still clear the cache, since after one the same
+// clients start asking for the new heads and the old packs are dead weight.
+function packDigest(wants: string[], commonHaves: string[]) {
+  return createHash("sha256")
+    .update([...new Set(wants)].sort().join(","))
+    .update("\0")
+    .update([...new Set(commonHaves)].sort().join(","))
+    .digest("hex");
+}
+
+function packCachePrefix(store: GitStore) {
+  return storageLayout.packCachePrefix(storageLayout.repoPrefix(store.ownerId, store.repoName));
+}
+
+// Only a pack requested more than once within the cache window is stored, so one-off fetches
+// with unique haves don't fill the bucket.
+export async function buildPackCached(
+  store: GitStore,
+  wants: string[],
+  commonHaves: string[],
+  onProgress?: (message: string) => void
+): Promise<Buffer> {
+  const digest = packDigest(wants, commonHaves);
+  const indexKey = repoCache.packKey(store.ownerId, store.repoName, digest);
+
+  const objectKey = await getCached<string>(indexKey);
+  if (objectKey) {
+    const cached = await getObject(objectKey);
+    if (cached) {
+      onProgress?.("Reusing cached pack, done.\n");
+      return cached;
+    }
+    await deleteCache(indexKey);
+  }
+
+  const hits = await countHit(repoCache.packHitsKey(store.ownerId, store.repoName, digest), CACHE_TTL.pack);
+  const pack = await buildPack(store, wants, commonHaves, onProgress);
+
+  // Objects that failed to load are left out of the pack rather than failing the walk, so a pack
+  // built during a storage outage may be incomplete and mustn't be handed to later clients.
+  if (hits >= config.git.packCacheMinRequests && pack.length <= config.git.packCacheMaxBytes && !hasStorageFailure()) {
+    const key = `${packCachePrefix(store)}/${digest}.pack`;
+    trackTask("pack_cache", () => putObject(key, pack).then(() => setCache(indexKey, key, CACHE_TTL.pack))).catch((error) => {
+      console.error(`[Git] Failed to cache pack for ${store.ownerId}/${store.repoName}:`, error);
+    });
+  }
+
+  return pack;
+}
+
+export async function clearPackCache(store: GitStore): Promise<void> {
+  await deleteCachePattern(`${repoCache.packKey(store.ownerId, store.repoName, "")}*`);
+  await deletePrefix(`${packCachePrefix(store)}/`);
+}
@@ -0,0 +1,194 @@
+import { createHash } from "crypto";
+import { PackFormatError } from "./pack-parse";
+
+const IDX_MAGIC = 0xff744f63;
+const IDX_VERSION = 2;
+const LARGE_OFFSET_FLAG = 0x80000000;
+
+export type PackIndexEntry = {
+  oid: string;
+  offset: number;
+  crc32: number;
+};
+
+const CRC32_TABLE = (() => {
+  const table = new Uint32Array(256);
+  for (let n = 0; n < 256; n++) {
+    let c = n;
+    for (let k = 0; k < 8; k++) {
+      c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+    }
+    table[n] = c >>> 0;
+  }
+  return table;
+})();
+
+export function crc32(data: Uint8Array): number {
+  let crc = 0xffffffff;
+  for (let i = 0; i < data.length; i++) {
+    crc = CRC32_TABLE[(crc ^ data[i]) & 0xff] ^ (crc >>> 8);
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
+// Builds a version 2 pack index: fan-out table, sorted object names, CRC32s of the packed
+// representations, 31-bit offsets with a 64-bit table for objects beyond 2 GiB, then the pack
+// checksum and the checksum of the index itself.
+export function createPackIndex(entries: PackIndexEntry[], packChecksum: Buffer): Buffer {
+  if (packChecksum.length !== 20) {
+    throw new Error("Pack checksum must be 20 bytes");
+  }
+
+  const sorted = [...entries].sort((a, b) => (a.oid < b.oid ? -1 : a.oid > b.oid ? 1 : 0));
+  for (let i = 1; i < sorted.length; i++) {
+    if (sorted[i].oid === sorted[i - 1].oid) {
+      throw new PackFormatError(`Duplicate object ${sorted[i].oid} in pack`);
+    }
+  }
+
+  const largeOffsets = sorted.filter((entry) => entry.offset >= LARGE_OFFSET_FLAG);
+  const size = 8 + 256 * 4 + sorted.length * (20 + 4 + 4) + largeOffsets.length * 8 + 20;
+  const idx = Buffer.alloc(size + 20);
+  let pos = 0;
+
+  idx.writeUInt32BE(IDX_MAGIC, pos);
+  idx.writeUInt32BE(IDX_VERSION, pos + 4);
+  pos += 8;
+
+  const fanout = new Uint32Array(256);
+  for (const entry of sorted) {
+    fanout[parseInt(entry.oid.slice(0, 2), 16)]++;
+  }
+  let cumulative = 0;
+  for (let i = 0; i < 256; i++) {
+    cumulative += fanout[i];
+    idx.writeUInt32BE(cumulative, pos);
+    pos += 4;
+  }
+
+  for (const entry of sorted) {
+    Buffer.from(entry.oid, "hex").copy(idx, pos);
+    pos += 20;
+  }
+
+  for (const entry of sorted) {
+    idx.writeUInt32BE(entry.crc32 >>> 0, pos);
+    pos += 4;
+  }
+
+  let largeIndex = 0;
+  for (const entry of sorted) {
+    if (entry.offset >= LARGE_OFFSET_FLAG) {
+      idx.writeUInt32BE((LARGE_OFFSET_FLAG | largeIndex++) >>> 0, pos);
+    } else {
+      idx.writeUInt32BE(entry.offset, pos);
+    }
+    pos += 4;
+  }
+
+  for (const entry of largeOffsets) {
+    idx.writeBigUInt64BE(BigInt(entry.offset), pos);
+    pos += 8;
+  }
+
+  packChecksum.copy(idx, pos);
+  pos += 20;
+
+  createHash("sha1").update(idx.subarray(0, pos)).digest().copy(idx, pos);
+  return idx;
+}
+
+export type ParsedPackIndex = {
+  objectCount: number;
+  // The object's offset in the pack, or null when the index doesn't list it.
+  find(oid: string): number | null;
+  // Every object in the index, in oid order.
+  entries(): { oid: string; offset: number }[];
+};
+
+// Reads a version 2 index written by createPackIndex or git. The structure is validated up
+// front so that lookups can't index past the buffer; a malformed index throws PackFormatError.
+export function parsePackIndex(idx: Buffer): ParsedPackIndex {
+  const fanoutEnd = 8 + 256 * 4;
+  if (idx.length < fanoutEnd + 40) {
+    throw new PackFormatError("Index is truncated");
+  }
+  if (idx.readUInt32BE(0) !== IDX_MAGIC || idx.readUInt32BE(4) !== IDX_VERSION) {
+    throw new PackFormatError("Not a version 2 pack index");
+  }
+
+  let previous = 0;
+  for (let i = 0; i < 256; i++) {
+    const count = idx.readUInt32BE(8 + i * 4);
+    if (count < previous) {
+      throw new PackFormatError("Index fan-out table is not sorted");
+    }
+    previous = count;
+  }
+  const objectCount = previous;
+
+  const namesStart = fanoutEnd;
+  const offsetsStart = namesStart + objectCount * (20 + 4);
+  const largeOffsetsStart = offsetsStart + objectCount * 4;
+  const trailerStart = idx.length - 40;
+  if (largeOffsetsStart > trailerStart || (trailerStart - largeOffsetsStart) % 8 !== 0) {
+    throw new PackFormatError(`Index is the wrong size for ${objectCount} objects`);
+  }
+  const largeOffsetCount = (trailerStart - largeOffsetsStart) / 8;
+
+  const offsetAt = (position: number) => {
+    const offset = idx.readUInt32BE(offsetsStart + position * 4);
+    if (!(offset & LARGE_OFFSET_FLAG)) {
+      return offset;
+    }
+    const large = offset & ~LARGE_OFFSET_FLAG;
+    if (large >= largeOffsetCount) {
+      throw new PackFormatError(`Index refers to missing large offset ${large}`);
+    }
+    const value = idx.readBigUInt64BE(largeOffsetsStart + large * 8);
+    if (value > BigInt(Number.MAX_SAFE_INTEGER)) {
+      throw new PackFormatError("Index offset is out of range");
+    }
+    return Number(value);
+  };
+
+  return {
+    objectCount,
+    find(oid: string) {
+      if (!/^[0-9a-f]{40}$/.test(oid)) {
+        return null;
+      }
+      const target = Buffer.from(oid, "hex");
+      const first = target[0];
+      let low = first === 0 ? 0 : idx.readUInt32BE(8 + (first - 1) * 4);
+      let high = idx.readUInt32BE(8 + first * 4);
+
+      while (low < high) {
+        const mid = (low + high) >>> 1;
+        const start = namesStart + mid * 20;
+        const cmp = idx.compare(target, 0, 20, start, start + 20);
+        if (cmp === 0) {
+          return offsetAt(mid);
+        }
+        if (cmp < 0) {
+          low = mid + 1;
+        } else {
+          high = mid;
+        }
+      }
+      return null;
+    },
+    entries() {
+      const entries: { oid: string; offset: number }[] = [];
+      for (let i = 0; i < objectCount; i++) {
+        const start = namesStart + i * 20;
+        entries.push({ oid: idx.toString("hex", start, start + 20), offset: offsetAt(i) });
+      }
+      return entries;
+    },
+  };
+}
+
+export function findObjectInIndex(idx: Buffer, oid: string): number | null {
+  return parsePackIndex(idx).find(oid);
+}
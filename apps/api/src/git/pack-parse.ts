@@ -0,0 +1,163 @@
+// Readers for the variable-length fields of a packfile and for delta instructions. Packs arrive
+// from clients, so every read is bounds-checked: a truncated or hostile pack has to fail with a
+// PackFormatError, never read past the buffer (which yields undefined and silently corrupts the
+// arithmetic) or produce an object that doesn't match its declared size.
+export class PackFormatError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "PackFormatError";
+  }
+}
+
+export const OBJ_COMMIT = 1;
+export const OBJ_TREE = 2;
+export const OBJ_BLOB = 3;
+export const OBJ_TAG = 4;
+export const OBJ_OFS_DELTA = 6;
+export const OBJ_REF_DELTA = 7;
+
+const VALID_TYPES = new Set([OBJ_COMMIT, OBJ_TREE, OBJ_BLOB, OBJ_TAG, OBJ_OFS_DELTA, OBJ_REF_DELTA]);
+
+// Sizes and offsets are built with multiplication rather than shifts, which wrap at 32 bits;
+// anything past 2^53 can't be a real object and is refused rather than rounded. Eight bytes of
+// a varint already carry more bits than that, which also bounds runs of zero continuation bytes.
+const MAX_VALUE = Number.MAX_SAFE_INTEGER;
+const MAX_VARINT_BYTES = 8;
+
+function byteAt(buf: Uint8Array, offset: number, what: string): number {
+  if (offset < 0 || offset >= buf.length) {
+    throw new PackFormatError(`Truncated ${what} at offset ${offset}`);
+  }
+  return buf[offset];
+}
+
+// The per-object header: type in bits 4-6 of the first byte, then the inflated size in 4 bits
+// followed by 7-bit groups, least significant first.
+export function readPackObjectHeader(buf: Uint8Array, offset: number): { type: number; size: number; bytesRead: number } {
+  let byte = byteAt(buf, offset, "object header");
+  const type = (byte >> 4) & 0x7;
+  if (!VALID_TYPES.has(type)) {
+    throw new PackFormatError(`Invalid object type ${type} at offset ${offset}`);
+  }
+
+  let size = byte & 0x0f;
+  let scale = 16;
+  let bytesRead = 1;
+  while (byte & 0x80) {
+    if (bytesRead === MAX_VARINT_BYTES) {
+      throw new PackFormatError(`Object size overflows at offset ${offset}`);
+    }
+    byte = byteAt(buf, offset + bytesRead, "object header");
+    size += (byte & 0x7f) * scale;
+    scale *= 128;
+    bytesRead++;
+  }
+  if (size > MAX_VALUE) {
+    throw new PackFormatError(`Object size overflows at offset ${offset}`);
+  }
+
+  return { type, size, bytesRead };
+}
+
+// The distance back to an OFS_DELTA's base. Unlike the other varints this one is big-endian and
+// adds one per continuation byte, so each length has its own range of values.
+export function readOfsOffset(buf: Uint8Array, offset: number): { value: number; bytesRead: number } {
+  let byte = byteAt(buf, offset, "delta base offset");
+  let value = byte & 0x7f;
+  let bytesRead = 1;
+  while (byte & 0x80) {
+    if (bytesRead === MAX_VARINT_BYTES) {
+      throw new PackFormatError(`Delta base offset overflows at offset ${offset}`);
+    }
+    byte = byteAt(buf, offset + bytesRead, "delta base offset");
+    value = (value + 1) * 128 + (byte & 0x7f);
+    bytesRead++;
+  }
+  if (value > MAX_VALUE) {
+    throw new PackFormatError(`Delta base offset overflows at offset ${offset}`);
+  }
+  return { value, bytesRead };
+}
+
+// The base and result sizes at the start of a delta: 7-bit groups, least significant first.
+export function readDeltaVarint(buf: Uint8Array, offset: number): { value: number; bytesRead: number } {
+  let value = 0;
+  let scale = 1;
+  let bytesRead = 0;
+  let byte: number;
+  do {
+    if (bytesRead === MAX_VARINT_BYTES) {
+      throw new PackFormatError("Delta size overflows");
+    }
+    byte = byteAt(buf, offset + bytesRead, "delta size");
+    value += (byte & 0x7f) * scale;
+    scale *= 128;
+    bytesRead++;
+  } while (byte & 0x80);
+  if (value > MAX_VALUE) {
+    throw new PackFormatError("Delta size overflows");
+  }
+  return { value, bytesRead };
+}
+
+// `maxResultBytes` is checked against the declared size before any instruction runs: a copy with
+// no size bytes copies 64 KiB, so a few bytes of delta can otherwise ask for gigabytes.
+export function applyDelta(base: Buffer, delta: Buffer, maxResultBytes: number = MAX_VALUE): Buffer {
+  const baseSize = readDeltaVarint(delta, 0);
+  const resultSize = readDeltaVarint(delta, baseSize.bytesRead);
+  let offset = baseSize.bytesRead + resultSize.bytesRead;
+
+  if (baseSize.value !== base.length) {
+    throw new PackFormatError(`Delta expects a ${baseSize.value} byte base, got ${base.length}`);
+  }
+  if (resultSize.value > maxResultBytes) {
+    throw new PackFormatError(`Delta result of ${resultSize.value} bytes exceeds the ${maxResultBytes} byte limit`);
+  }
+
+  // Collected in pieces rather than allocated up front, since the declared size is untrusted.
+  const chunks: Buffer[] = [];
+  let written = 0;
+
+  while (offset < delta.length) {
+    const cmd = delta[offset++];
+
+    if (cmd & 0x80) {
+      let copyOffset = 0;
+      let copySize = 0;
+      for (let i = 0; i < 4; i++) {
+        if (cmd & (1 << i)) copyOffset += byteAt(delta, offset++, "delta copy") * 2 ** (8 * i);
+      }
+      for (let i = 0; i < 3; i++) {
+        if (cmd & (0x10 << i)) copySize += byteAt(delta, offset++, "delta copy") * 2 ** (8 * i);
+      }
+      if (copySize === 0) copySize = 0x10000;
+
+      if (copyOffset + copySize > base.length) {
+        throw new PackFormatError(`Delta copies ${copySize} bytes from offset ${copyOffset} of a ${base.length} byte base`);
+      }
+      if (written + copySize > resultSize.value) {
+        throw new PackFormatError("Delta result is larger than declared");
+      }
+      chunks.push(base.subarray(copyOffset, copyOffset + copySize));
+      written += copySize;
+    } else if (cmd > 0) {
+      if (offset + cmd > delta.length) {
+        throw new PackFormatError("Truncated delta insert");
+      }
+      if (written + cmd > resultSize.value) {
+        throw new PackFormatError("Delta result is larger than declared");
+      }
+      chunks.push(delta.subarray(offset, offset + cmd));
+      offset += cmd;
+      written += cmd;
+    } else {
+      // Opcode 0 is reserved; git rejects it too.
+      throw new PackFormatError("Unexpected delta opcode 0");
+    }
+  }
+
+  if (written !== resultSize.value) {
+    throw new PackFormatError(`Delta produced ${written} bytes, expected ${resultSize.value}`);
+  }
+  return Buffer.concat(chunks, written);
+}
@@ -0,0 +1,380 @@
+import git from "isomorphic-git";
+import { resolveRefOid, type GitStore } from "./index";
+import { applyRefUpdates } from "./ref-transaction";
+import { storageLayout } from "../storage-layout";
+
+export interface PatchHunk {
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  lines: { op: " " | "+" | "-"; text: string; noNewline?: boolean }[];
+}
+
+export interface FilePatch {
+  oldPath: string | null;
+  newPath: string | null;
+  hunks: PatchHunk[];
+}
+
+export interface ParsedPatch {
+  subject: string | null;
+  body: string | null;
+  author: { name: string; email: string } | null;
+  files: FilePatch[];
+}
+
+export class PatchError extends Error {
+  constructor(
+    message: string,
+    public readonly path?: string
+  ) {
+    super(message);
+    this.name = "PatchError";
+  }
+}
+
+const MAX_HUNK_OFFSET = 100;
+
+// Extended `diff --git` headers that change something other than file contents. Applying the
+// hunks alone would silently drop the rename or mode change, so these patches are refused.
+const UNSUPPORTED_HEADERS = ["rename from ", "rename to ", "copy from ", "copy to ", "old mode ", "new mode "];
+
+function stripPathPrefix(raw: string): string | null {
+  const path = raw.split("\t")[0].trim();
+  if (path === "/dev/null") return null;
+  return path.replace(/^[ab]\//, "");
+}
+
+function parseEmailHeaders(lines: string[]) {
+  let subject: string | null = null;
+  let author: { name: string; email: string } | null = null;
+  const bodyLines: string[] = [];
+  let inBody = false;
+
+  for (const line of lines) {
+    if (line.startsWith("diff --git ") || line === "---") break;
+    if (!inBody) {
+      if (line.startsWith("Subject: ")) {
+        subject = line.slice("Subject: ".length).replace(/^\[PATCH[^\]]*\]\s*/, "").trim();
+      } else if (line.startsWith("From: ")) {
+        const match = line.slice("From: ".length).match(/^(.*?)\s*<([^>]+)>$/);
+        if (match) author = { name: match[1].replace(/^"|"$/g, "").trim(), email: match[2] };
+      } else if (line === "" && subject !== null) {
+        inBody = true;
+      }
+    } else {
+      bodyLines.push(line);
+    }
+  }
+
+  const body = bodyLines.join("\n").trim();
+  return { subject, author, body: body || null };
+}
+
+export function parsePatch(text: string): ParsedPatch {
+  const lines = text.replace(/\r\n/g, "\n").split("\n");
+  const { subject, author, body } = parseEmailHeaders(lines);
+
+  const files: FilePatch[] = [];
+  let current: FilePatch | null = null;
+  let hunk: PatchHunk | null = null;
+  let oldRemaining = 0;
+  let newRemaining = 0;
+  let inGitHeader = false;
+
+  for (let i = 0; i < lines.length; i++) {
+    const line = lines[i];
+
+    if (hunk && (oldRemaining > 0 || newRemaining > 0)) {
+      const op = line[0];
+      if (line.startsWith("\\ ")) {
+        // Marks the line before it, on whichever side that line belongs to.
+        const last = hunk.lines[hunk.lines.length - 1];
+        if (last) last.noNewline = true;
+        continue;
+      }
+      if (op === " " || line === "") {
+        hunk.lines.push({ op: " ", text: line.slice(1) });
+        oldRemaining--;
+        newRemaining--;
+        continue;
+      }
+      if (op === "-") {
+        hunk.lines.push({ op: "-", text: line.slice(1) });
+        oldRemaining--;
+        continue;
+      }
+      if (op === "+") {
+        hunk.lines.push({ op: "+", text: line.slice(1) });
+        newRemaining--;
+        continue;
+      }
+      throw new PatchError("Malformed hunk: unexpected line inside hunk", current?.newPath ?? current?.oldPath ?? undefined);
+    }
+
+    if (line.startsWith("\\ ")) {
+      const last = hunk?.lines[hunk.lines.length - 1];
+      if (last) last.noNewline = true;
+      continue;
+    }
+
+    if (line.startsWith("diff --git ")) {
+      inGitHeader = true;
+      hunk = null;
+      continue;
+    }
+
+    if (inGitHeader) {
+      const unsupported = UNSUPPORTED_HEADERS.find((prefix) => line.startsWith(prefix));
+      if (unsupported) {
+        throw new PatchError(`Patches with ${unsupported.trim()} headers are not supported`, line.slice(unsupported.length) || undefined);
+      }
+      const newMode = line.match(/^new file mode (\d+)/);
+      if (newMode && newMode[1] !== "100644") {
+        throw new PatchError(`New files with mode ${newMode[1]} are not supported`);
+      }
+    }
+
+    if (line.startsWith("Binary files ") || line.startsWith("GIT binary patch")) {
+      throw new PatchError("Binary patches are not supported");
+    }
+
+    if (line.startsWith("--- ") && lines[i + 1]?.startsWith("+++ ")) {
+      current = {
+        oldPath: stripPathPrefix(line.slice(4)),
+        newPath: stripPathPrefix(lines[i + 1].slice(4)),
+        hunks: [],
+      };
+      files.push(current);
+      hunk = null;
+      inGitHeader = false;
+      i++;
+      continue;
+    }
+
+    const header = line.match(/^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@/);
+    if (header) {
+      if (!current) {
+        throw new PatchError("Malformed patch: hunk without file header");
+      }
+      inGitHeader = false;
+      hunk = {
+        oldStart: parseInt(header[1], 10),
+        oldLines: header[2] !== undefined ? parseInt(header[2], 10) : 1,
+        newStart: parseInt(header[3], 10),
+        newLines: header[4] !== undefined ? parseInt(header[4], 10) : 1,
+        lines: [],
+      };
+      oldRemaining = hunk.oldLines;
+      newRemaining = hunk.newLines;
+      current.hunks.push(hunk);
+    }
+  }
+
+  if (oldRemaining > 0 || newRemaining > 0) {
+    throw new PatchError("Malformed patch: truncated hunk", current?.newPath ?? current?.oldPath ?? undefined);
+  }
+
+  if (files.length === 0) {
+    throw new PatchError("Patch does not contain any file changes");
+  }
+
+  return { subject, body, author, files };
+}
+
+function matchesAt(lines: string[], expected: string[], at: number) {
+  if (at < 0 || at + expected.length > lines.length) return false;
+  return expected.every((text, i) => lines[at + i] === text);
+}
+
+export function applyHunks(content: string, hunks: PatchHunk[], path: string): string {
+  const hadTrailingNewline = content === "" || content.endsWith("\n");
+  const lines = content === "" ? [] : content.replace(/\n$/, "").split("\n");
+  let offset = 0;
+  let trailingNewline = hadTrailingNewline;
+
+  for (const hunk of hunks) {
+    const expected = hunk.lines.filter((l) => l.op !== "+").map((l) => l.text);
+    const replacement = hunk.lines.filter((l) => l.op !== "-").map((l) => l.text);
+    const target = Math.max(0, (hunk.oldLines === 0 ? hunk.oldStart : hunk.oldStart - 1) + offset);
+
+    let position = -1;
+    for (let delta = 0; delta <= MAX_HUNK_OFFSET && position < 0; delta++) {
+      if (matchesAt(lines, expected, target - delta)) position = target - delta;
+      else if (delta > 0 && matchesAt(lines, expected, target + delta)) position = target + delta;
+    }
+
+    if (position < 0) {
+      throw new PatchError(`Hunk @@ -${hunk.oldStart},${hunk.oldLines} +${hunk.newStart},${hunk.newLines} @@ does not apply`, path);
+    }
+
+    lines.splice(position, expected.length, ...replacement);
+    offset += replacement.length - expected.length + (position - target);
+
+    // Only the new side's final line decides whether the result ends with a newline; a marker
+    // on a removed line just says the old file lacked one.
+    const lastNew = hunk.lines.filter((l) => l.op !== "-").pop();
+    if (lastNew && position + replacement.length === lines.length) {
+      trailingNewline = !lastNew.noNewline;
+    } else if (!lastNew && position === lines.length) {
+      trailingNewline = true;
+    }
+  }
+
+  if (lines.length === 0) return "";
+  return lines.join("\n") + (trailingNewline ? "\n" : "");
+}
+
+export type TreeChange = { oid: string; mode: string } | null;
+
+export async function rewriteTree(store: GitStore, treeOid: string | null, changes: Map<string, TreeChange>): Promise<string | null> {
+  const entries = treeOid ? (await git.readTree({ fs: store.fs, dir: store.dir, oid: treeOid })).tree : [];
+  const byName = new Map(entries.map((e) => [e.path, e]));
+
+  const nested = new Map<string, Map<string, TreeChange>>();
+  for (const [path, change] of changes) {
+    const slash = path.indexOf("/");
+    if (slash === -1) {
+      if (change === null) {
+        byName.delete(path);
+      } else {
+        byName.set(path, { path, oid: change.oid, mode: change.mode, type: "blob" });
+      }
+      continue;
+    }
+    const dir = path.slice(0, slash);
+    const rest = path.slice(slash + 1);
+    if (!nested.has(dir)) nested.set(dir, new Map());
+    nested.get(dir)!.set(rest, change);
+  }
+
+  for (const [dir, dirChanges] of nested) {
+    const existing = byName.get(dir);
+    if (existing && existing.type !== "tree") {
+      throw new PatchError(`${dir} is not a directory`, dir);
+    }
+    const subtree = await rewriteTree(store, existing?.oid ?? null, dirChanges);
+    if (subtree === null) {
+      byName.delete(dir);
+    } else {
+      byName.set(dir, { path: dir, oid: subtree, mode: "040000", type: "tree" });
+    }
+  }
+
+  if (byName.size === 0) return null;
+  return git.writeTree({ fs: store.fs, dir: store.dir, tree: [...byName.values()] });
+}
+
+async function readFileAt(store: GitStore, commitOid: string, path: string) {
+  try {
+    const { blob } = await git.readBlob({ fs: store.fs, dir: store.dir, oid: commitOid, filepath: path });
+    return new TextDecoder().decode(blob);
+  } catch {
+    return null;
+  }
+}
+
+async function readModeAt(store: GitStore, treeOid: string, path: string) {
+  const parts = path.split("/");
+  let oid = treeOid;
+  for (let i = 0; i < parts.length; i++) {
+    const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid });
+    const entry = tree.find((e) => e.path === parts[i]);
+    if (!entry) return null;
+    if (i === parts.length - 1) return entry.mode;
+    oid = entry.oid;
+  }
+  return null;
+}
+
+export async function applyPatchToBranch(
+  store: GitStore,
+  branch: string,
+  patch: ParsedPatch,
+  options: {
+    repositoryId: string;
+    message: string;
+    committer: { name: string; email: string };
+    expectedHeadOid?: string;
+  }
+): Promise<{ commitOid: string; parentOid: string; files: { path: string; status: "added" | "modified" | "deleted" | "renamed" }[] }> {
+  const parentOid = await resolveRefOid(store, branch);
+  if (options.expectedHeadOid && options.expectedHeadOid !== parentOid) {
+    throw new PatchError("Branch has moved since the patch was prepared");
+  }
+
+  const { commit: parent } = await git.readCommit({ fs: store.fs, dir: store.dir, oid: parentOid });
+
+  const changes = new Map<string, TreeChange>();
+  const files: { path: string; status: "added" | "modified" | "deleted" | "renamed" }[] = [];
+
+  for (const file of patch.files) {
+    const path = file.newPath ?? file.oldPath!;
+    if (path.split("/").some((segment) => segment === ".." || segment === "" || segment === ".git")) {
+      throw new PatchError("Invalid path in patch", path);
+    }
+
+    const original = file.oldPath ? await readFileAt(store, parentOid, file.oldPath) : null;
+    if (file.oldPath && original === null) {
+      throw new PatchError("File does not exist on the target branch", file.oldPath);
+    }
+    if (!file.oldPath && (await readFileAt(store, parentOid, path)) !== null) {
+      throw new PatchError("File already exists on the target branch", path);
+    }
+
+    if (!file.newPath) {
+      changes.set(file.oldPath!, null);
+      files.push({ path: file.oldPath!, status: "deleted" });
+      continue;
+    }
+
+    const updated = applyHunks(original ?? "", file.hunks, path);
+    const oid = await git.writeBlob({ fs: store.fs, dir: store.dir, blob: new TextEncoder().encode(updated) });
+    const mode = (file.oldPath && (await readModeAt(store, parent.tree, file.oldPath))) || "100644";
+
+    if (file.oldPath && file.oldPath !== file.newPath) {
+      changes.set(file.oldPath, null);
+      files.push({ path: file.newPath, status: "renamed" });
+    } else {
+      files.push({ path: file.newPath, status: file.oldPath ? "modified" : "added" });
+    }
+    changes.set(file.newPath, { oid, mode });
+  }
+
+  const treeOid = await rewriteTree(store, parent.tree, changes);
+  if (!treeOid) {
+    throw new PatchError("Patch would leave the repository empty");
+  }
+  if (treeOid === parent.tree) {
+    throw new PatchError("Patch does not change any files");
+  }
+
+  const timestamp = Math.floor(Date.now() / 1000);
+  const timezoneOffset = new Date().getTimezoneOffset();
+  const author = patch.author ?? options.committer;
+
+  const commitOid = await git.writeCommit({
+    fs: store.fs,
+    dir: store.dir,
+    commit: {
+      message: options.message.endsWith("\n") ? options.message : `${options.message}\n`,
+      tree: treeOid,
+      parent: [parentOid],
+      author: { ...author, timestamp, timezoneOffset },
+      committer: { ...options.committer, timestamp, timezoneOffset },
+    },
+  });
+
+  const errors = await applyRefUpdates(
+    options.repositoryId,
+    storageLayout.repoPrefix(store.ownerId, store.repoName),
+    [{ ref: `refs/heads/${branch}`, oldOid: parentOid, newOid: commitOid }],
+    true
+  );
+  if (errors.size > 0) {
+    throw new PatchError("Branch was updated while the patch was being applied");
+  }
+
+  return { commitOid, parentOid, files };
+}
@@ -0,0 +1,92 @@
+import { randomUUID } from "crypto";
+import { getRedisClient } from "../cache";
+
+// The push lock serializes a whole push to one repository across API instances: the ref checks,
+// the ref writes and the branch metadata and cache rebuild that follows them. The advisory lock in
+// ref-transaction.ts is narrower; it only makes each compare-and-swap of the refs atomic, so
+// writers that don't take this lock (web edits, patch applies) still can't lose an update.
+//
+// The holder renews the TTL while it works, so the expiry only matters if the holder dies.
+const PUSH_LOCK_TTL_MS = 2 * 60 * 1000;
+const RENEW_INTERVAL_MS = PUSH_LOCK_TTL_MS / 3;
+
+const localLocks = new Map<string, { token: string; expiresAt: number }>();
+
+export type PushLock = { release: () => Promise<void> };
+
+function lockKey(repositoryId: string) {
+  return `gitbruv:push-lock:${repositoryId}`;
+}
+
+// Without Redis there is only one API instance to coordinate, so an in-process lock is enough.
+function acquireLocal(key: string, token: string): boolean {
+  const held = localLocks.get(key);
+  if (held && held.expiresAt > Date.now()) return false;
+  localLocks.set(key, { token, expiresAt: Date.now() + PUSH_LOCK_TTL_MS });
+  return true;
+}
+
+function renewLocal(key: string, token: string) {
+  const held = localLocks.get(key);
+  if (held?.token === token) held.expiresAt = Date.now() + PUSH_LOCK_TTL_MS;
+}
+
+function releaseLocal(key: string, token: string) {
+  if (localLocks.get(key)?.token === token) localLocks.delete(key);
+}
+
+const RELEASE_SCRIPT = `if redis.call("GET", KEYS[1]) == ARGV[1] then return redis.call("DEL", KEYS[1]) else return 0 end`;
+const RENEW_SCRIPT = `if redis.call("GET", KEYS[1]) == ARGV[1] then return redis.call("PEXPIRE", KEYS[1], ARGV[2]) else return 0 end`;
+
+// Keeps extending the lock until it's released. The timer is unref'd so a forgotten lock can't
+// hold the process open, and release is idempotent.
+function holdLock(renew: () => Promise<void>, release: () => Promise<void>): PushLock {
+  const timer = setInterval(() => void renew(), RENEW_INTERVAL_MS);
+  timer.unref?.();
+  let released = false;
+  return {
+    release: async () => {
+      if (released) return;
+      released = true;
+      clearInterval(timer);
+      await release();
+    },
+  };
+}
+
+// Fails fast rather than queueing: the second push is told to retry, as git itself does when a
+// ref lock is taken. Returns null when another push to the repository holds the lock.
+export async function acquirePushLock(repositoryId: string): Promise<PushLock | null> {
+  const key = lockKey(repositoryId);
+  const token = randomUUID();
+  const redis = await getRedisClient();
+
+  if (!redis) {
+    if (!acquireLocal(key, token)) return null;
+    return holdLock(
+      async () => renewLocal(key, token),
+      async () => releaseLocal(key, token)
+    );
+  }
+
+  const acquired = await redis.set(key, token, { NX: true, PX: PUSH_LOCK_TTL_MS });
+  if (acquired !== "OK") return null;
+
+  // Both scripts only touch the key while it's still ours; after an expiry another push may hold it.
+  return holdLock(
+    async () => {
+      const renewed = await redis
+        .eval(RENEW_SCRIPT, { keys: [key], arguments: [token, String(PUSH_LOCK_TTL_MS)] })
+        .catch((error) => {
+          console.error(`[Git] failed to renew push lock for ${repositoryId}:`, error);
+          return null;
+        });
+      if (renewed === 0) console.warn(`[Git] push lock for ${repositoryId} expired before it was renewed`);
+    },
+    async () => {
+      await redis.eval(RELEASE_SCRIPT, { keys: [key], arguments: [token] }).catch((error) => {
+        console.error(`[Git] failed to release push lock for ${repositoryId}:`, error);
+      });
+    }
+  );
+}
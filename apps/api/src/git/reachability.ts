@@ -0,0 +1,175 @@
+import git, { type TreeEntry } from "isomorphic-git";
+import * as zlib from "zlib";
+import { getObject, putObject } from "../s3";
+import { storageLayout } from "../storage-layout";
+import { hasStorageFailure } from "../tracing";
+import { resolveCommittish, type GitStore } from "./index";
+
+// Per commit: its parents, and the trees and blobs in its tree that aren't in its first parent's
+// tree. Everything a commit reaches is then itself plus the entries of all its ancestors, so
+// upload-pack can list a fetch's objects from the commits it sends without reading the trees
+// of the history the client already has. Compared with the first parent only, merges list some
+// objects their other parents already reach; a pack may repeat objects the client has, but
+// never misses one.
+type IndexEntry = [parents: string[], objects: string[]];
+export type ReachabilityIndex = Map<string, IndexEntry>;
+
+const INDEX_VERSION = 1;
+
+// The index only ever grows, and commits it doesn't cover fall back to the full walk, so a copy
+// a minute old is slower at worst, never wrong.
+const MEMORY_TTL_MS = 60 * 1000;
+const MAX_MEMORY_INDEXES = 20;
+
+const loaded = new Map<string, { at: number; index: ReachabilityIndex }>();
+const updating = new Set<string>();
+
+function indexKey(store: GitStore) {
+  return storageLayout.reachabilityIndexKey(storageLayout.repoPrefix(store.ownerId, store.repoName));
+}
+
+async function readIndex(key: string): Promise<ReachabilityIndex | null> {
+  const data = await getObject(key);
+  if (!data) return null;
+
+  const parsed = JSON.parse(zlib.gunzipSync(data).toString("utf8")) as { version: number; commits: Record<string, IndexEntry> };
+  if (parsed.version !== INDEX_VERSION) return null;
+  return new Map(Object.entries(parsed.commits));
+}
+
+function remember(key: string, index: ReachabilityIndex) {
+  loaded.delete(key);
+  loaded.set(key, { at: Date.now(), index });
+  for (const oldest of loaded.keys()) {
+    if (loaded.size <= MAX_MEMORY_INDEXES) break;
+    loaded.delete(oldest);
+  }
+}
+
+export async function loadReachabilityIndex(store: GitStore): Promise<ReachabilityIndex | null> {
+  const key = indexKey(store);
+  const memo = loaded.get(key);
+  if (memo && Date.now() - memo.at < MEMORY_TTL_MS) {
+    return memo.index;
+  }
+
+  const index = await readIndex(key);
+  if (index) remember(key, index);
+  return index;
+}
+
+async function collectTreeDelta(store: GitStore, treeOid: string, parentTreeOid: string | null, out: string[]) {
+  if (treeOid === parentTreeOid) return;
+  out.push(treeOid);
+
+  const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid: treeOid });
+  const parentEntries = new Map<string, TreeEntry>();
+  if (parentTreeOid) {
+    for (const entry of (await git.readTree({ fs: store.fs, dir: store.dir, oid: parentTreeOid })).tree) {
+      parentEntries.set(entry.path, entry);
+    }
+  }
+
+  for (const entry of tree) {
+    if (entry.type === "commit") continue;
+    const previous = parentEntries.get(entry.path);
+    if (previous?.oid === entry.oid) continue;
+    if (entry.type === "tree") {
+      await collectTreeDelta(store, entry.oid, previous?.type === "tree" ? previous.oid : null, out);
+    } else {
+      out.push(entry.oid);
+    }
+  }
+}
+
+// Indexes every commit reachable from a branch or tag that isn't indexed yet and returns how many
+// were added. Runs after pushes and whenever upload-pack finds the index behind.
+export async function updateReachabilityIndex(store: GitStore): Promise<number> {
+  const key = indexKey(store);
+  if (updating.has(key)) return 0;
+  updating.add(key);
+
+  try {
+    const index: ReachabilityIndex = (await readIndex(key)) ?? new Map();
+
+    const refs = [
+      ...(await git.listBranches({ fs: store.fs, dir: store.dir })).map((branch) => `refs/heads/${branch}`),
+      ...(await git.listTags({ fs: store.fs, dir: store.dir })).map((tag) => `refs/tags/${tag}`),
+    ];
+    const queue: string[] = [];
+    for (const ref of refs) {
+      const oid = await resolveCommittish(store.fs, store.dir, ref);
+      if (oid) queue.push(oid);
+    }
+
+    const treeOf = async (oid: string) => (await git.readCommit({ fs: store.fs, dir: store.dir, oid })).commit.tree;
+
+    let added = 0;
+    while (queue.length > 0) {
+      const oid = queue.pop()!;
+      if (index.has(oid)) continue;
+
+      const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid });
+      const objects: string[] = [];
+      await collectTreeDelta(store, commit.tree, commit.parent.length > 0 ? await treeOf(commit.parent[0]) : null, objects);
+      index.set(oid, [commit.parent, objects]);
+      added++;
+      queue.push(...commit.parent);
+    }
+
+    // A read error swallowed somewhere below isomorphic-git could have cut an entry short, and an
+    // incomplete entry would produce packs with missing objects, so nothing is saved after one.
+    if (added > 0 && !hasStorageFailure()) {
+      const body = JSON.stringify({ version: INDEX_VERSION, commits: Object.fromEntries(index) });
+      await putObject(key, zlib.gzipSync(body));
+      remember(key, index);
+    }
+    return added;
+  } finally {
+    updating.delete(key);
+  }
+}
+
+// Lists the objects a fetch of `wants` needs given the client's common haves, or returns null
+// when a wanted commit isn't indexed yet and the caller has to walk the graph itself.
+export async function enumerateFromIndex(
+  store: GitStore,
+  index: ReachabilityIndex,
+  wants: string[],
+  commonHaves: string[]
+): Promise<string[] | null> {
+  const have = new Set<string>();
+  const haveQueue = commonHaves.filter((oid) => index.has(oid));
+  while (haveQueue.length > 0) {
+    const oid = haveQueue.pop()!;
+    if (have.has(oid)) continue;
+    have.add(oid);
+    haveQueue.push(...(index.get(oid)?.[0] ?? []));
+  }
+
+  const needed = new Set<string>();
+  const queue = [...wants];
+  while (queue.length > 0) {
+    const oid = queue.pop()!;
+    if (needed.has(oid) || have.has(oid)) continue;
+
+    const entry = index.get(oid);
+    if (entry) {
+      needed.add(oid);
+      for (const object of entry[1]) needed.add(object);
+      queue.push(...entry[0]);
+      continue;
+    }
+
+    // Annotated tags aren't indexed; they're sent along with whatever they point at.
+    try {
+      const { tag } = await git.readTag({ fs: store.fs, dir: store.dir, oid });
+      needed.add(oid);
+      queue.push(tag.object);
+    } catch {
+      return null;
+    }
+  }
+
+  return [...needed];
+}
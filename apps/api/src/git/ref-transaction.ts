@@ -0,0 +1,89 @@
+import { db } from "@gitbruv/db";
+import { sql } from "drizzle-orm";
+import { getObject, putObject, deleteObject } from "../s3";
+import { storageLayout, type RepoPrefix } from "../storage-layout";
+
+export const ZERO_OID = "0".repeat(40);
+
+export type RefUpdate = { oldOid: string; newOid: string; ref: string };
+
+function refKey(repoPrefix: RepoPrefix, ref: string) {
+  return storageLayout.refKey(repoPrefix, ref.startsWith("refs/") ? (ref as `refs/${string}`) : `refs/heads/${ref}`);
+}
+
+async function readRef(key: string) {
+  const data = await getObject(key);
+  return data ? data.toString("utf8").trim() : ZERO_OID;
+}
+
+async function writeRef(key: string, oid: string) {
+  if (oid === ZERO_OID) {
+    await deleteObject(key);
+  } else {
+    await putObject(key, Buffer.from(oid + "\n"));
+  }
+}
+
+// Applies ref updates with compare-and-swap semantics: each ref must still hold the old oid the
+// client pushed against. With `atomic`, any rejection or write failure fails every update and
+// refs already written are put back. Returns the failure reason for each ref that wasn't updated.
+//
+// S3 has no compare-and-swap, so the read-check-write runs under a Postgres advisory lock held
+// for the repository; concurrent ref writers queue up here, across instances. Pushes also hold
+// the wider push lock (push-lock.ts) around this call and the metadata rebuild after it.
+export async function applyRefUpdates(
+  repositoryId: string,
+  repoPrefix: RepoPrefix,
+  updates: RefUpdate[],
+  atomic: boolean
+): Promise<Map<string, string>> {
+  const errors = new Map<string, string>();
+  if (updates.length === 0) return errors;
+
+  const failRemaining = () => {
+    for (const update of updates) {
+      if (!errors.has(update.ref)) errors.set(update.ref, "atomic push failure");
+    }
+  };
+
+  await db.transaction(async (tx) => {
+    await tx.execute(sql`SELECT pg_advisory_xact_lock(hashtext(${`refs:${repositoryId}`}))`);
+
+    const current = await Promise.all(updates.map((update) => readRef(refKey(repoPrefix, update.ref))));
+    for (const [index, update] of updates.entries()) {
+      if (current[index] !== update.oldOid) errors.set(update.ref, "stale info");
+    }
+    if (atomic && errors.size > 0) {
+      failRemaining();
+      return;
+    }
+
+    const applied: { key: string; previous: string }[] = [];
+    for (const [index, update] of updates.entries()) {
+      if (errors.has(update.ref)) continue;
+      const key = refKey(repoPrefix, update.ref);
+      try {
+        await writeRef(key, update.newOid);
+        applied.push({ key, previous: current[index] });
+      } catch (error) {
+        console.error(`[Git] ref update failed for ${update.ref}:`, error);
+        errors.set(update.ref, "failed to write ref");
+        if (atomic) {
+          await rollback(applied);
+          failRemaining();
+          return;
+        }
+      }
+    }
+  });
+
+  return errors;
+}
+
+async function rollback(applied: { key: string; previous: string }[]) {
+  for (const { key, previous } of applied.reverse()) {
+    await writeRef(key, previous).catch((error) => {
+      console.error(`[Git] ref rollback failed for ${key}:`, error);
+    });
+  }
+}
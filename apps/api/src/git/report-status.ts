@@ -0,0 +1,39 @@
+import { createSidebandWriter, pktLine } from "./upload-pack";
+
+// The outcome of one ref update in a push; `error` is set when it was rejected.
+export type RefStatus = { ref: string; error?: string };
+
+const MAX_REASON_LENGTH = 200;
+
+// Reasons are printed by git after "! [remote rejected] <ref> (...)", so they have to stay on one
+// pkt-line and should read as a short explanation rather than a stack trace.
+export function statusReason(message: string): string {
+  const reason = message
+    .replace(/\s+/g, " ")
+    .replace(/[^\x20-\x7e]/g, "?")
+    .trim();
+  return reason.length > MAX_REASON_LENGTH ? `${reason.slice(0, MAX_REASON_LENGTH - 3)}...` : reason || "failed";
+}
+
+// A report-status (or report-status-v2; plain ok/ng lines are valid for both) answer to a push.
+// When the client asked for side-band the report rides on band 1, and `messages` go to band 2,
+// which git prints as "remote: ..." lines; without side-band the messages are dropped and only
+// the per-ref reasons reach the user.
+export function buildReportStatus(capabilities: Set<string>, unpackError: string | null, refs: RefStatus[], messages: string[] = []): Buffer {
+  const report = Buffer.concat([
+    pktLine(`unpack ${unpackError === null ? "ok" : statusReason(unpackError)}\n`),
+    ...refs.map((status) => pktLine(status.error === undefined ? `ok ${status.ref}\n` : `ng ${status.ref} ${statusReason(status.error)}\n`)),
+    Buffer.from("0000"),
+  ]);
+
+  if (!capabilities.has("side-band-64k") && !capabilities.has("side-band")) {
+    return report;
+  }
+
+  const sideband = createSidebandWriter(capabilities);
+  return Buffer.concat([
+    ...messages.flatMap((message) => sideband.progress(`${message}\n`)),
+    ...sideband.data(report),
+    ...sideband.end(),
+  ]);
+}
@@ -1,4 +1,7 @@
 import { getObject, putObject, deleteObject, listObjects, objectExists } from "../s3";
+import { objectCache, isImmutableObjectKey } from "./object-cache";
+import { readAlternates, isPrivateObjectStore } from "./shared-objects";
+import type { RepoPrefix } from "../storage-layout";
 
 export interface S3FsStats {
   type: "file" | "dir";
@@ -15,7 +18,7 @@ export interface S3FsStats {
   isSymbolicLink(): boolean;
 }
 
-export function createS3Fs(basePath: string) {
+export function createS3Fs(basePath: RepoPrefix) {
   const normalize = (filepath: string): string => {
     let path = filepath.startsWith("/") ? filepath.slice(1) : filepath;
     if (path === ".git" || path === ".git/") {
@@ -30,6 +33,51 @@ export function createS3Fs(basePath: string) {
     return `${basePath}/${path}`.replace(/\/+/g, "/").replace(/\/$/, "");
   };
 
+  // Read once per store; a repository only ever moves onto the shared layout, never back.
+  let alternates: Promise<string | null> | null = null;
+  const sharedPrefix = () => (alternates ??= readAlternates(basePath));
+
+  // Also read once per store, and only for loose object writes.
+  let privateStore: Promise<boolean> | null = null;
+  const writesPrivately = () => (privateStore ??= isPrivateObjectStore(basePath));
+
+  const objectsPrefix = `${basePath}/objects`;
+
+  // Loose objects are written to the shared prefix when the repository has one (unless it is
+  // private) and read from it first, falling back to the repository's own copy for objects not yet
+  // migrated or written while private.
+  const candidateKeys = async (key: string): Promise<string[]> => {
+    const relative = key.startsWith(`${objectsPrefix}/`) ? key.slice(objectsPrefix.length + 1) : null;
+    if (!relative || !/^[0-9a-f]{2}\/[0-9a-f]{38}$/.test(relative)) {
+      return [key];
+    }
+    const shared = await sharedPrefix();
+    return shared ? [`${shared}/${relative}`, key] : [key];
+  };
+
+  const readCandidate = async (key: string): Promise<Buffer | null> => {
+    for (const candidate of await candidateKeys(key)) {
+      const data = await readObject(candidate);
+      if (data) return data;
+    }
+    return null;
+  };
+
+  const readObject = async (key: string): Promise<Buffer | null> => {
+    if (!isImmutableObjectKey(key)) {
+      return getObject(key);
+    }
+    const cached = objectCache.get(key);
+    if (cached) {
+      return cached;
+    }
+    const data = await getObject(key);
+    if (data) {
+      objectCache.set(key, data);
+    }
+    return data;
+  };
+
   const fs = {
     promises: {
       async readFile(
@@ -37,7 +85,7 @@ export function createS3Fs(basePath: string) {
         options?: { encoding?: string } | string
       ): Promise<Buffer | string> {
         const key = normalize(filepath);
-        const data = await getObject(key);
+        const data = await readCandidate(key);
         if (!data) {
           const err = new Error(`ENOENT: no such file or directory, open '${filepath}'`) as NodeJS.ErrnoException;
           err.code = "ENOENT";
@@ -53,9 +101,12 @@ export function createS3Fs(basePath: string) {
 
       async writeFile(filepath: string, data: Buffer | Uint8Array | string): Promise<void> {
         const key = normalize(filepath);
-        await putObject(key, data instanceof Buffer ? data : Buffer.from(data));
+        const candidates = await candidateKeys(key);
+        const target = candidates.length > 1 && !(await writesPrivately()) ? candidates[0] : key;
+        await putObject(target, data instanceof Buffer ? data : Buffer.from(data));
       },
 
+      // Only the repository's own copy is ever removed: a shared object may belong to any fork.
       async unlink(filepath: string): Promise<void> {
         const key = normalize(filepath);
         await deleteObject(key);
@@ -66,6 +117,18 @@ export function createS3Fs(basePath: string) {
         const searchPrefix = prefix.endsWith("/") ? prefix : prefix + "/";
         const keys = await listObjects(searchPrefix);
 
+        // Loose object directories list the repository's own copies and the shared ones together.
+        const looseDir =
+          prefix === objectsPrefix ||
+          (prefix.startsWith(`${objectsPrefix}/`) && /^[0-9a-f]{2}$/.test(prefix.slice(objectsPrefix.length + 1)));
+        const shared = looseDir ? await sharedPrefix() : null;
+        if (shared) {
+          const sharedSearch = `${shared}${prefix.slice(objectsPrefix.length)}/`;
+          for (const sharedKey of await listObjects(sharedSearch)) {
+            keys.push(searchPrefix + sharedKey.slice(sharedSearch.length));
+          }
+        }
+
         const entries = new Set<string>();
         for (const key of keys) {
           const relative = key.slice(searchPrefix.length);
@@ -112,9 +175,15 @@ export function createS3Fs(basePath: string) {
           };
         }
 
-        const exists = await objectExists(key);
-        if (exists) {
-          const data = await getObject(key);
+        let existing: string | null = null;
+        for (const candidate of await candidateKeys(key)) {
+          if (objectCache.has(candidate) || (await objectExists(candidate))) {
+            existing = candidate;
+            break;
+          }
+        }
+        if (existing) {
+          const data = await readObject(existing);
           return {
             type: "file",
             mode: 0o100644,
@@ -0,0 +1,141 @@
+import { db, repositories } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import { getObject, putObject, deleteObject, listObjects, objectExists, deletePrefix } from "../s3";
+import { storageLayout, SHARED_OBJECTS_ROOT, type RepoPrefix } from "../storage-layout";
+
+// Loose objects of every repository in a fork network live under one prefix keyed only by oid, so
+// forking copies refs and metadata but no objects. Networks are per fork tree rather than global:
+// upload-pack serves whatever object the store can read, and a single namespace would let any
+// repository hand out another's objects to someone who knows the oid. For the same reason only
+// public repositories write into a network; see PRIVATE_OBJECTS_PATH.
+export { SHARED_OBJECTS_ROOT };
+
+// Same file and meaning as git's own alternates: the repository reads (and here also writes)
+// loose objects from the prefix it names. Without it a repository uses the legacy layout of
+// loose objects under its own prefix.
+export const ALTERNATES_PATH = "objects/info/alternates";
+
+// Marks a private repository that still reads a network's objects: new loose objects are written
+// under its own prefix instead, where the network's other repositories can't read them. Repositories
+// created private never join a network at all.
+export const PRIVATE_OBJECTS_PATH = "objects/info/private";
+
+const LOOSE_OBJECT_PATH = /^objects\/[0-9a-f]{2}\/[0-9a-f]{38}$/;
+const COPY_BATCH_SIZE = 50;
+const MAX_MIGRATION_PASSES = 3;
+
+export function sharedObjectsPrefix(networkId: string): string {
+  return storageLayout.sharedObjectsPrefix(networkId);
+}
+
+export function isLooseObjectPath(relative: string): boolean {
+  return LOOSE_OBJECT_PATH.test(relative);
+}
+
+export async function readAlternates(repoPrefix: RepoPrefix): Promise<string | null> {
+  const data = await getObject(storageLayout.repoKey(repoPrefix, ALTERNATES_PATH));
+  const line = data?.toString("utf8").split("\n")[0].trim();
+  return line || null;
+}
+
+export async function writeAlternates(repoPrefix: RepoPrefix, networkId: string): Promise<void> {
+  await putObject(storageLayout.repoKey(repoPrefix, ALTERNATES_PATH), `${sharedObjectsPrefix(networkId)}\n`);
+}
+
+export async function isPrivateObjectStore(repoPrefix: RepoPrefix): Promise<boolean> {
+  return objectExists(storageLayout.repoKey(repoPrefix, PRIVATE_OBJECTS_PATH));
+}
+
+// Called before a repository turns private and after it turns public, so a push never writes a
+// private object into the network.
+export async function setPrivateObjectStore(repoPrefix: RepoPrefix, isPrivate: boolean): Promise<void> {
+  const key = storageLayout.repoKey(repoPrefix, PRIVATE_OBJECTS_PATH);
+  if (isPrivate) {
+    await putObject(key, "");
+  } else {
+    await deleteObject(key);
+  }
+}
+
+export async function listOwnLooseObjects(repoPrefix: RepoPrefix): Promise<string[]> {
+  return (await listObjects(`${repoPrefix}/objects/`)).filter((key) => isLooseObjectPath(key.slice(repoPrefix.length + 1)));
+}
+
+// Returns null without moving the rest if the repository turned private part-way: anything written
+// after that is private, and the marker is always in place before such a write.
+async function moveLegacyObjects(repoPrefix: RepoPrefix, sharedPrefix: string): Promise<number | null> {
+  const keys = await listOwnLooseObjects(repoPrefix);
+
+  for (let i = 0; i < keys.length; i += COPY_BATCH_SIZE) {
+    if (await isPrivateObjectStore(repoPrefix)) {
+      return null;
+    }
+    await Promise.all(
+      keys.slice(i, i + COPY_BATCH_SIZE).map(async (key) => {
+        const sharedKey = `${sharedPrefix}/${key.slice(repoPrefix.length + "/objects/".length)}`;
+        if (!(await objectExists(sharedKey))) {
+          const data = await getObject(key);
+          if (!data) return;
+          await putObject(sharedKey, data);
+        }
+        await deleteObject(key);
+      })
+    );
+  }
+
+  return keys.length;
+}
+
+// Moves a repository's loose objects into a network's shared prefix (by default its current network,
+// or a new one rooted at the repository) and returns the network id, or null if the repository is
+// private. Alternates are written first so objects written while the move runs already land in the
+// shared prefix, and reads fall back to the per-repository copies until each one is moved. The
+// network id is only recorded once nothing is left behind, since forks can't see those copies.
+// This lists and copies every loose object, so it runs from the background job, never a request.
+export async function migrateRepoObjects(
+  repo: { id: string; ownerId: string; name: string; objectNetworkId: string | null },
+  networkId: string = repo.objectNetworkId ?? repo.id
+): Promise<string | null> {
+  const repoPrefix = storageLayout.repoPrefix(repo.ownerId, repo.name);
+  if (await isPrivateObjectStore(repoPrefix)) {
+    return null;
+  }
+
+  const sharedPrefix = sharedObjectsPrefix(networkId);
+  await writeAlternates(repoPrefix, networkId);
+
+  let moved = 0;
+  for (let pass = 0; pass < MAX_MIGRATION_PASSES; pass++) {
+    const count = await moveLegacyObjects(repoPrefix, sharedPrefix);
+    if (count === null) {
+      console.log(`[Git] Stopped moving objects of ${repo.ownerId}/${repo.name}: the repository is now private`);
+      return null;
+    }
+    moved += count;
+    if (count === 0) {
+      if (repo.objectNetworkId !== networkId) {
+        await db.update(repositories).set({ objectNetworkId: networkId }).where(eq(repositories.id, repo.id));
+      }
+      if (moved > 0) {
+        console.log(`[Git] Moved ${moved} loose objects of ${repo.ownerId}/${repo.name} into ${sharedPrefix}`);
+      }
+      return networkId;
+    }
+  }
+
+  throw new Error(`loose objects of ${repo.ownerId}/${repo.name} kept appearing under the repository prefix`);
+}
+
+// Shared objects can't be attributed to a single repository, so they are only removed once the
+// last repository in the network is gone.
+export async function releaseObjectNetwork(networkId: string | null): Promise<void> {
+  if (!networkId) return;
+
+  const remaining = await db.query.repositories.findFirst({
+    where: eq(repositories.objectNetworkId, networkId),
+    columns: { id: true },
+  });
+  if (!remaining) {
+    await deletePrefix(`${sharedObjectsPrefix(networkId)}/`);
+  }
+}
@@ -0,0 +1,137 @@
+import * as zlib from "zlib";
+import { getObject, listObjectsWithSize } from "../s3";
+import { storageLayout, type RepoPrefix } from "../storage-layout";
+import { parsePackIndex } from "./pack-index";
+import { readAlternates } from "./shared-objects";
+import { OBJ_BLOB, readPackObjectHeader } from "./pack-parse";
+
+export type LargeBlob = { oid: string; size: number; storedBytes: number };
+
+export type RepoSize = {
+  totalBytes: number;
+  packCount: number;
+  packBytes: number;
+  looseObjectCount: number;
+  looseBytes: number;
+  // The part of the loose objects above that lives in the repository's fork network rather than
+  // under its own prefix. Every repository in the network reads those objects, so they count
+  // towards each one's size but are only stored once.
+  sharedObjectCount: number;
+  sharedBytes: number;
+  largestBlobs: LargeBlob[];
+};
+
+export const MAX_LARGEST_BLOBS = 50;
+
+// Only the objects taking the most space in storage have their headers read; a repository's
+// largest blobs are almost always among them, and each read is a ranged request.
+const MAX_CANDIDATES = 200;
+const READ_BATCH_SIZE = 25;
+
+const LOOSE_OBJECT = /^objects\/([0-9a-f]{2})\/([0-9a-f]{38})$/;
+const PACK_FILE = /^objects\/pack\/(pack-[0-9a-f]+)\.pack$/;
+
+type Candidate = { oid: string; storedBytes: number; read: () => Promise<{ blob: boolean; size: number } | null> };
+
+// A loose object's zlib stream starts with "<type> <size>\0", so inflating the first bytes is
+// enough to tell a blob from the rest without downloading it.
+async function readLooseHeader(key: string) {
+  const head = await getObject(key, "bytes=0-63");
+  if (!head) return null;
+  const inflated = zlib.inflateSync(head, { finishFlush: zlib.constants.Z_SYNC_FLUSH });
+  const match = inflated.toString("latin1").match(/^(blob|tree|commit|tag) (\d+)\0/);
+  return match ? { blob: match[1] === "blob", size: Number(match[2]) } : null;
+}
+
+// Deltified objects report the size of the delta, not the blob, so they're left out; a blob big
+// enough to matter is rarely stored as a delta.
+async function readPackedHeader(key: string, offset: number) {
+  const head = await getObject(key, `bytes=${offset}-${offset + 15}`);
+  if (!head) return null;
+  const header = readPackObjectHeader(head, 0);
+  return { blob: header.type === OBJ_BLOB, size: header.size };
+}
+
+async function packCandidates(prefix: RepoPrefix, name: string, packBytes: number): Promise<Candidate[]> {
+  const idx = await getObject(storageLayout.packKey(prefix, name, "idx"));
+  if (!idx) return [];
+
+  const packKey = storageLayout.packKey(prefix, name, "pack");
+  const entries = parsePackIndex(idx)
+    .entries()
+    .sort((a, b) => a.offset - b.offset);
+  // Objects are stored back to back, so each one ends where the next begins; the last one ends
+  // at the 20-byte checksum.
+  return entries.map((entry, i) => ({
+    oid: entry.oid,
+    storedBytes: (i + 1 < entries.length ? entries[i + 1].offset : packBytes - 20) - entry.offset,
+    read: () => readPackedHeader(packKey, entry.offset),
+  }));
+}
+
+export async function computeRepoSize(prefix: RepoPrefix): Promise<RepoSize> {
+  const objects = await listObjectsWithSize(storageLayout.repoContents(prefix));
+  const size: RepoSize = {
+    totalBytes: 0,
+    packCount: 0,
+    packBytes: 0,
+    looseObjectCount: 0,
+    looseBytes: 0,
+    sharedObjectCount: 0,
+    sharedBytes: 0,
+    largestBlobs: [],
+  };
+  const candidates: Candidate[] = [];
+
+  for (const object of objects) {
+    size.totalBytes += object.size;
+    const path = storageLayout.repoRelativePath(object.key);
+    if (!path) continue;
+
+    const loose = path.match(LOOSE_OBJECT);
+    if (loose) {
+      size.looseObjectCount++;
+      size.looseBytes += object.size;
+      candidates.push({ oid: loose[1] + loose[2], storedBytes: object.size, read: () => readLooseHeader(object.key) });
+      continue;
+    }
+
+    const pack = path.match(PACK_FILE);
+    if (pack) {
+      size.packCount++;
+      size.packBytes += object.size;
+      candidates.push(...(await packCandidates(prefix, pack[1], object.size).catch(() => [])));
+    }
+  }
+
+  const sharedPrefix = await readAlternates(prefix);
+  if (sharedPrefix) {
+    for (const object of await listObjectsWithSize(`${sharedPrefix}/`)) {
+      const loose = `objects/${object.key.slice(sharedPrefix.length + 1)}`.match(LOOSE_OBJECT);
+      if (!loose) continue;
+      size.totalBytes += object.size;
+      size.looseObjectCount++;
+      size.looseBytes += object.size;
+      size.sharedObjectCount++;
+      size.sharedBytes += object.size;
+      candidates.push({ oid: loose[1] + loose[2], storedBytes: object.size, read: () => readLooseHeader(object.key) });
+    }
+  }
+
+  candidates.sort((a, b) => b.storedBytes - a.storedBytes);
+  const blobs = new Map<string, LargeBlob>();
+  const top = candidates.slice(0, MAX_CANDIDATES);
+  for (let i = 0; i < top.length; i += READ_BATCH_SIZE) {
+    const batch = top.slice(i, i + READ_BATCH_SIZE);
+    const headers = await Promise.all(batch.map((candidate) => candidate.read().catch(() => null)));
+    batch.forEach((candidate, j) => {
+      const header = headers[j];
+      if (header?.blob && !blobs.has(candidate.oid)) {
+        blobs.set(candidate.oid, { oid: candidate.oid, size: header.size, storedBytes: candidate.storedBytes });
+      }
+    });
+  }
+
+  size.largestBlobs = [...blobs.values()].sort((a, b) => b.size - a.size).slice(0, MAX_LARGEST_BLOBS);
+  return size;
+}
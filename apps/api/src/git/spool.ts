@@ -0,0 +1,154 @@
+import { open, unlink } from "fs/promises";
+import { tmpdir } from "os";
+import { join } from "path";
+import { randomUUID } from "crypto";
+
+export class PackTooLargeError extends Error {
+  constructor(
+    public readonly limit: number,
+    public readonly commandSection: Buffer
+  ) {
+    super(`pack exceeds maximum size of ${limit} bytes`);
+    this.name = "PackTooLargeError";
+  }
+}
+
+export type SpooledPush = {
+  commandSection: Buffer;
+  packPath: string | null;
+  packSize: number;
+  // The pack's trailing SHA-1, which names it once archived.
+  packChecksum: Buffer | null;
+  cleanup: () => Promise<void>;
+};
+
+// Index just past the flush-pkt that terminates the command list, or -1 if it hasn't arrived yet.
+function findCommandsEnd(data: Buffer): number {
+  let offset = 0;
+  while (offset + 4 <= data.length) {
+    const length = parseInt(data.subarray(offset, offset + 4).toString("ascii"), 16);
+    if (Number.isNaN(length)) return -1;
+    if (length === 0) return offset + 4;
+    if (offset + length > data.length) return -1;
+    offset += length;
+  }
+  return -1;
+}
+
+// Reads a receive-pack request body without holding the pack in memory: pkt-line commands are
+// buffered, everything after the flush-pkt is streamed to a temp file.
+export async function spoolReceivePack(body: ReadableStream<Uint8Array> | null, maxPackBytes: number): Promise<SpooledPush> {
+  const packPath = join(tmpdir(), `gitbruv-push-${randomUUID()}.pack`);
+  let handle: Awaited<ReturnType<typeof open>> | null = null;
+  let head = Buffer.alloc(0);
+  let commandSection: Buffer | null = null;
+  let packSize = 0;
+  let tail = Buffer.alloc(0);
+
+  const cleanup = async () => {
+    await handle?.close().catch(() => { /* already closed */ });
+    if (packSize > 0) {
+      await unlink(packPath).catch(() => { /* already removed */ });
+    }
+  };
+
+  const writePack = async (chunk: Buffer) => {
+    if (chunk.length === 0) return;
+    packSize += chunk.length;
+    if (packSize > maxPackBytes) {
+      throw new PackTooLargeError(maxPackBytes, commandSection ?? head);
+    }
+    handle ??= await open(packPath, "w");
+    await handle.write(chunk);
+    tail = Buffer.concat([tail, chunk.subarray(Math.max(0, chunk.length - 20))]);
+    tail = tail.subarray(Math.max(0, tail.length - 20));
+  };
+
+  try {
+    if (body) {
+      const reader = body.getReader();
+      while (true) {
+        const { done, value } = await reader.read();
+        if (done) break;
+
+        const chunk = Buffer.from(value);
+        if (commandSection) {
+          await writePack(chunk);
+          continue;
+        }
+
+        head = Buffer.concat([head, chunk]);
+        const end = findCommandsEnd(head);
+        if (end !== -1) {
+          commandSection = head.subarray(0, end);
+          await writePack(head.subarray(end));
+          head = Buffer.alloc(0);
+        } else if (head.length > 1024 * 1024) {
+          throw new Error("command section too large");
+        }
+      }
+    }
+
+    await handle?.close();
+    handle = null;
+  } catch (error) {
+    await cleanup();
+    throw error;
+  }
+
+  return {
+    commandSection: commandSection ?? head,
+    packPath: packSize > 0 ? packPath : null,
+    packSize,
+    packChecksum: tail.length === 20 ? Buffer.from(tail) : null,
+    cleanup,
+  };
+}
+
+export class SpoolTooLargeError extends Error {
+  constructor(public readonly limit: number) {
+    super(`request body exceeds maximum size of ${limit} bytes`);
+    this.name = "SpoolTooLargeError";
+  }
+}
+
+export type SpooledFile = {
+  path: string;
+  size: number;
+  cleanup: () => Promise<void>;
+};
+
+// Streams a whole request body (an uploaded bundle) to a temp file, for the same reason pushes
+// are spooled: nothing that can be gigabytes long is held in memory.
+export async function spoolRequestBody(body: ReadableStream<Uint8Array> | null, maxBytes: number, label: string): Promise<SpooledFile> {
+  const path = join(tmpdir(), `gitbruv-${label}-${randomUUID()}`);
+  const handle = await open(path, "w");
+  let size = 0;
+
+  const cleanup = async () => {
+    await unlink(path).catch(() => { /* already removed */ });
+  };
+
+  try {
+    if (body) {
+      const reader = body.getReader();
+      while (true) {
+        const { done, value } = await reader.read();
+        if (done) break;
+        size += value.length;
+        if (size > maxBytes) {
+          await reader.cancel().catch(() => { /* client already gone */ });
+          throw new SpoolTooLargeError(maxBytes);
+        }
+        await handle.write(value);
+      }
+    }
+  } catch (error) {
+    await handle.close().catch(() => { /* already closed */ });
+    await cleanup();
+    throw error;
+  }
+  await handle.close();
+
+  return { path, size, cleanup };
+}
@@ -0,0 +1,59 @@
+import git from "isomorphic-git";
+import { resolveCommittish, type GitStore } from "./index";
+import { createLimiter, OBJECT_FETCH_CONCURRENCY, type Limiter } from "./concurrency";
+
+// Objects are re-read through isomorphic-git rather than copied by path, so trees and blobs that
+// only exist inside the template's packfiles still end up in the new repository.
+async function copyTree(source: GitStore, target: GitStore, treeOid: string, limit: Limiter, copied: Set<string>) {
+  if (copied.has(treeOid)) return;
+  copied.add(treeOid);
+
+  const { object } = await limit(() => git.readObject({ fs: source.fs, dir: source.dir, oid: treeOid, format: "content" }));
+  await limit(() => git.writeObject({ fs: target.fs, dir: target.dir, type: "tree", object: object as Uint8Array, format: "content" }));
+
+  const { tree } = await limit(() => git.readTree({ fs: source.fs, dir: source.dir, oid: treeOid }));
+  await Promise.all(
+    tree.map(async (entry) => {
+      if (entry.type === "tree") {
+        await copyTree(source, target, entry.oid, limit, copied);
+      } else if (entry.type === "blob" && !copied.has(entry.oid)) {
+        copied.add(entry.oid);
+        const { blob } = await limit(() => git.readBlob({ fs: source.fs, dir: source.dir, oid: entry.oid }));
+        await limit(() => git.writeBlob({ fs: target.fs, dir: target.dir, blob }));
+      }
+    })
+  );
+}
+
+// Starts the target with a single root commit holding the template's current tree; none of the
+// template's history is carried over.
+export async function createFromTemplate(
+  source: GitStore,
+  sourceBranch: string,
+  target: GitStore,
+  targetBranch: string,
+  author: { name: string; email: string }
+): Promise<string | null> {
+  const headOid = await resolveCommittish(source.fs, source.dir, sourceBranch);
+  if (!headOid) return null;
+
+  const { commit } = await git.readCommit({ fs: source.fs, dir: source.dir, oid: headOid });
+  await copyTree(source, target, commit.tree, createLimiter(OBJECT_FETCH_CONCURRENCY), new Set());
+
+  const timestamp = Math.floor(Date.now() / 1000);
+  const timezoneOffset = new Date().getTimezoneOffset();
+  const commitOid = await git.writeCommit({
+    fs: target.fs,
+    dir: target.dir,
+    commit: {
+      message: "Initial commit\n",
+      tree: commit.tree,
+      parent: [],
+      author: { ...author, timestamp, timezoneOffset },
+      committer: { ...author, timestamp, timezoneOffset },
+    },
+  });
+
+  await target.fs.promises.writeFile(`.git/refs/heads/${targetBranch}`, commitOid + "\n");
+  return commitOid;
+}
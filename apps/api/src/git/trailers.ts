@@ -0,0 +1,38 @@
+import type { CommitAuthor } from "./index";
+
+export interface CommitTrailers {
+  coAuthors: CommitAuthor[];
+  signedOffBy: CommitAuthor[];
+  reviewedBy: CommitAuthor[];
+}
+
+const TRAILER_KEYS: Record<string, keyof CommitTrailers> = {
+  "co-authored-by": "coAuthors",
+  "signed-off-by": "signedOffBy",
+  "reviewed-by": "reviewedBy",
+};
+
+const TRAILER_LINE = /^([A-Za-z][A-Za-z0-9-]*)\s*:\s*(.*?)\s*<([^<>\s]+)>\s*$/;
+
+// Trailers live in the message's last paragraph, as `git interpret-trailers` reads them. That
+// paragraph only counts if every line looks like a "Key: value" trailer, so prose that happens
+// to contain a colon isn't picked up.
+export function parseCommitTrailers(message: string): CommitTrailers {
+  const trailers: CommitTrailers = { coAuthors: [], signedOffBy: [], reviewedBy: [] };
+  const paragraphs = message.trim().split(/\n\s*\n/);
+  if (paragraphs.length < 2) return trailers;
+
+  const lines = paragraphs[paragraphs.length - 1].split("\n").map((line) => line.trim()).filter(Boolean);
+  if (!lines.every((line) => /^[A-Za-z][A-Za-z0-9-]*\s*:/.test(line))) return trailers;
+
+  for (const line of lines) {
+    const match = TRAILER_LINE.exec(line);
+    if (!match) continue;
+    const key = TRAILER_KEYS[match[1].toLowerCase()];
+    if (!key) continue;
+    const email = match[3];
+    if (trailers[key].some((person) => person.email.toLowerCase() === email.toLowerCase())) continue;
+    trailers[key].push({ name: match[2], email });
+  }
+  return trailers;
+}
@@ -0,0 +1,435 @@
+import { createHash } from "crypto";
+import { open } from "fs/promises";
+import * as zlib from "zlib";
+import { crc32, type PackIndexEntry } from "./pack-index";
+import { locateLargeFiles, type LargeFile } from "./large-files";
+import { mapConcurrent, OBJECT_FETCH_CONCURRENCY } from "./concurrency";
+import {
+  applyDelta,
+  readOfsOffset,
+  readPackObjectHeader,
+  PackFormatError,
+  OBJ_BLOB,
+  OBJ_COMMIT,
+  OBJ_OFS_DELTA,
+  OBJ_REF_DELTA,
+  OBJ_TAG,
+  OBJ_TREE,
+} from "./pack-parse";
+import type { GitStore } from "./index";
+
+// Turns a received pack (a push, or a restored bundle) into loose objects in the store. The pack
+// is read one object at a time, from a spooled file for pushes, so memory holds the object being
+// unpacked and a bounded cache of recent delta bases rather than the whole pack.
+function typeToString(type: number): string {
+  switch (type) {
+    case OBJ_COMMIT: return "commit";
+    case OBJ_TREE: return "tree";
+    case OBJ_BLOB: return "blob";
+    case OBJ_TAG: return "tag";
+    default: return "unknown";
+  }
+}
+
+function hashObject(type: string, data: Buffer): string {
+  const header = `${type} ${data.length}\0`;
+  const store = Buffer.concat([Buffer.from(header), data]);
+  return createHash("sha1").update(store).digest("hex");
+}
+
+// Inflates the zlib stream at the start of `window`, which may run on into the next object.
+// Returns null when the window ends before the stream does, so the caller can read more.
+function inflateAt(window: Buffer, offset: number): { data: Buffer; bytesRead: number } | null {
+  let result: { buffer: Buffer; engine: { bytesWritten?: number } };
+  try {
+    result = zlib.inflateSync(window, { info: true }) as unknown as typeof result;
+  } catch (e) {
+    if ((e as { code?: string }).code === "Z_BUF_ERROR") return null;
+    throw new PackFormatError(`Failed to inflate object at offset ${offset}: ${e instanceof Error ? e.message : e}`);
+  }
+  if (typeof result.engine?.bytesWritten === "number") {
+    return { data: result.buffer, bytesRead: result.engine.bytesWritten };
+  }
+
+  // Without the engine's input count, the stream ends at the shortest prefix that inflates.
+  let consumed = window.length;
+  let low = 2;
+  let high = window.length;
+  while (low <= high) {
+    const mid = Math.floor((low + high) / 2);
+    try {
+      zlib.inflateSync(window.subarray(0, mid));
+      consumed = mid;
+      high = mid - 1;
+    } catch {
+      low = mid + 1;
+    }
+  }
+  return { data: result.buffer, bytesRead: consumed };
+}
+
+// Where a pack is read from: a spooled file (a push, or a bundle whose pack starts at `offset`),
+// or a buffer already in memory.
+export type PackInput = Buffer | { path: string; offset?: number };
+
+interface PackReader {
+  size: number;
+  read(offset: number, length: number): Promise<Buffer>;
+  close(): Promise<void>;
+}
+
+async function openPack(input: PackInput): Promise<PackReader> {
+  if (Buffer.isBuffer(input)) {
+    return {
+      size: input.length,
+      read: async (offset, length) => input.subarray(offset, Math.min(offset + length, input.length)),
+      close: async () => {},
+    };
+  }
+
+  const handle = await open(input.path, "r");
+  const start = input.offset ?? 0;
+  const size = Math.max(0, (await handle.stat()).size - start);
+  return {
+    size,
+    async read(offset, length) {
+      const buffer = Buffer.alloc(Math.max(0, Math.min(length, size - offset)));
+      const { bytesRead } = await handle.read(buffer, 0, buffer.length, start + offset);
+      return buffer.subarray(0, bytesRead);
+    },
+    close: () => handle.close(),
+  };
+}
+
+type PackEntry = {
+  type: number;
+  data: Buffer;
+  baseOffset?: number;
+  baseOid?: string;
+  next: number;
+  crc: number;
+};
+
+// Object headers are at most 8 bytes, plus up to 8 for an OFS_DELTA offset or 20 for a base oid.
+const ENTRY_HEADER_BYTES = 40;
+
+// Reads the object at `offset`. The first read covers the inflated size plus zlib's worst-case
+// overhead, which is almost always enough; a stream that runs past it is read again with more.
+async function readEntry(reader: PackReader, offset: number, limits: { blob: number; other: number }): Promise<PackEntry> {
+  const head = await reader.read(offset, ENTRY_HEADER_BYTES);
+  let header: { type: number; size: number; bytesRead: number };
+  let cursor: number;
+  const entry: Partial<PackEntry> = {};
+  try {
+    header = readPackObjectHeader(head, 0);
+    cursor = header.bytesRead;
+    if (header.type === OBJ_OFS_DELTA) {
+      const ofs = readOfsOffset(head, cursor);
+      cursor += ofs.bytesRead;
+      entry.baseOffset = offset - ofs.value;
+      // The base has to be an earlier object, which also rules out delta cycles.
+      if (ofs.value === 0 || entry.baseOffset < 12) {
+        throw new PackFormatError("Invalid delta base offset");
+      }
+    } else if (header.type === OBJ_REF_DELTA) {
+      if (cursor + 20 > head.length) {
+        throw new PackFormatError("Truncated delta base");
+      }
+      entry.baseOid = head.subarray(cursor, cursor + 20).toString("hex");
+      cursor += 20;
+    }
+  } catch (error) {
+    if (error instanceof PackFormatError) {
+      throw new PackFormatError(`${error.message} (object at offset ${offset})`);
+    }
+    throw error;
+  }
+
+  if (header.size > (header.type === OBJ_BLOB ? limits.blob : limits.other)) {
+    throw new UnpackLimitError(
+      header.type === OBJ_BLOB ? `blob exceeds maximum size of ${limits.blob} bytes` : `object at offset ${offset} is too large to unpack`
+    );
+  }
+
+  const dataStart = offset + cursor;
+  let windowSize = header.size + Math.ceil(header.size / 1024) + 64;
+  for (;;) {
+    const window = await reader.read(dataStart, windowSize);
+    if (window.length === 0) {
+      throw new PackFormatError(`Truncated object data at offset ${dataStart}`);
+    }
+    const inflated = inflateAt(window, offset);
+    if (inflated) {
+      if (inflated.data.length !== header.size) {
+        throw new PackFormatError(`Object at ${offset} inflates to ${inflated.data.length} bytes, header says ${header.size}`);
+      }
+      const compressed = window.subarray(0, inflated.bytesRead);
+      return {
+        type: header.type,
+        data: inflated.data,
+        baseOffset: entry.baseOffset,
+        baseOid: entry.baseOid,
+        next: dataStart + inflated.bytesRead,
+        crc: crc32(Buffer.concat([head.subarray(0, cursor), compressed])),
+      };
+    }
+    if (dataStart + window.length >= reader.size) {
+      throw new PackFormatError(`Truncated object data at offset ${dataStart}`);
+    }
+    windowSize *= 2;
+  }
+}
+
+class UnpackLimitError extends Error {}
+
+async function loadObjectFromStorage(baseOid: string, store: GitStore): Promise<{ type: number; data: Buffer } | null> {
+  try {
+    const prefix = baseOid.substring(0, 2);
+    const suffix = baseOid.substring(2);
+    const objectPath = `.git/objects/${prefix}/${suffix}`;
+
+    const compressed = (await store.fs.promises.readFile(objectPath).catch(() => null)) as Buffer | null;
+    if (!compressed) {
+      return null;
+    }
+
+    const decompressed = zlib.inflateSync(compressed);
+    const nullIndex = decompressed.indexOf(0);
+    if (nullIndex === -1) {
+      return null;
+    }
+
+    const header = decompressed.subarray(0, nullIndex).toString("utf8");
+    const parts = header.split(" ");
+    if (parts.length !== 2) {
+      return null;
+    }
+
+    const typeStr = parts[0];
+    let type = 0;
+    if (typeStr === "commit") type = OBJ_COMMIT;
+    else if (typeStr === "tree") type = OBJ_TREE;
+    else if (typeStr === "blob") type = OBJ_BLOB;
+    else if (typeStr === "tag") type = OBJ_TAG;
+    else return null;
+
+    const data = decompressed.subarray(nullIndex + 1);
+    return { type, data };
+  } catch (error) {
+    console.error(`[API] Failed to load object ${baseOid} from storage:`, error);
+    return null;
+  }
+}
+
+export type UnpackResult = {
+  success: boolean;
+  objectCount: number;
+  oids?: Set<string>;
+  blobSizes?: Map<string, number>;
+  largeFiles?: LargeFile[];
+  indexEntries?: PackIndexEntry[];
+  // The pack's trailing SHA-1, which names it and is recorded in its index.
+  packChecksum?: Buffer;
+  // Some delta bases came from the repository rather than the pack, so it can't be indexed alone.
+  thin?: boolean;
+  error?: string;
+};
+
+// Resolved bases kept in memory for the deltas that follow them; git orders packs so that bases
+// are usually close by. Anything evicted is read back from the store, where it was just written.
+const BASE_CACHE_BYTES = 64 * 1024 * 1024;
+const STORE_CONCURRENCY = 50;
+
+// Problems with the pack itself come back as an unsuccessful result; failures to store what was
+// unpacked are thrown, since they say nothing about the pack and shouldn't be reported as if they did.
+export async function unpackPackFile(
+  input: PackInput,
+  storeObject: (oid: string, type: string, data: Buffer) => Promise<void>,
+  store: GitStore,
+  maxBlobBytes: number,
+  collectIndex = false,
+  warnBlobBytes = Infinity
+): Promise<UnpackResult> {
+  const reader = await openPack(input);
+  try {
+    return await unpack(reader, storeObject, store, maxBlobBytes, collectIndex, warnBlobBytes);
+  } catch (error) {
+    if (error instanceof UnpackLimitError) {
+      return { success: false, objectCount: 0, error: error.message };
+    }
+    if (!(error instanceof PackFormatError)) {
+      throw error;
+    }
+    console.error("[API] unpack error:", error.message);
+    return { success: false, objectCount: 0, error: error.message };
+  } finally {
+    await reader.close();
+  }
+}
+
+async function unpack(
+  reader: PackReader,
+  storeObject: (oid: string, type: string, data: Buffer) => Promise<void>,
+  store: GitStore,
+  maxBlobBytes: number,
+  collectIndex: boolean,
+  warnBlobBytes: number
+): Promise<UnpackResult> {
+  if (reader.size < 12) {
+    return { success: false, objectCount: 0, error: "Pack file too small" };
+  }
+
+  const packHeader = await reader.read(0, 12);
+  if (packHeader.subarray(0, 4).toString("ascii") !== "PACK") {
+    return { success: false, objectCount: 0, error: "Invalid pack signature" };
+  }
+
+  const version = packHeader.readUInt32BE(4);
+  if (version !== 2 && version !== 3) {
+    return { success: false, objectCount: 0, error: `Unsupported pack version: ${version}` };
+  }
+
+  const numObjects = packHeader.readUInt32BE(8);
+
+  const resolvedAt = new Map<number, { oid: string; type: number }>();
+  const oids = new Set<string>();
+  const blobSizes = new Map<string, number>();
+  const indexEntries: PackIndexEntry[] = [];
+  const largeBlobs: { oid: string; size: number }[] = [];
+  const treeOids: string[] = [];
+  let thin = false;
+
+  const cache = new Map<string, { type: number; data: Buffer }>();
+  let cachedBytes = 0;
+  const remember = (oid: string, object: { type: number; data: Buffer }) => {
+    if (object.data.length > BASE_CACHE_BYTES / 4 || cache.has(oid)) return;
+    cache.set(oid, object);
+    cachedBytes += object.data.length;
+    for (const [key, value] of cache) {
+      if (cachedBytes <= BASE_CACHE_BYTES) break;
+      cache.delete(key);
+      cachedBytes -= value.data.length;
+    }
+  };
+
+  const writing = new Map<string, Promise<void>>();
+  let storeError: unknown = null;
+  const write = async (oid: string, type: string, data: Buffer) => {
+    if (writing.size >= STORE_CONCURRENCY) await Promise.race(writing.values());
+    if (storeError) throw storeError;
+    const task = storeObject(oid, type, data)
+      .catch((error) => {
+        storeError ??= error;
+      })
+      .finally(() => writing.delete(oid));
+    writing.set(oid, task);
+  };
+
+  const loadBase = async (oid: string, inPack: boolean) => {
+    const cached = cache.get(oid);
+    if (cached) return cached;
+    await writing.get(oid);
+    const loaded = await loadObjectFromStorage(oid, store);
+    if (loaded && !inPack) thin = true;
+    return loaded;
+  };
+
+  // Whole blobs are held to maxBlobBytes before they're inflated, and a delta's declared result to
+  // the limit for its base's type before it's applied; other objects only need a sanity bound.
+  const limits = { blob: maxBlobBytes, other: Math.max(maxBlobBytes, 64 * 1024 * 1024) };
+
+  // Resolves, hashes and stores the object at `offset`; false when its base isn't available yet.
+  const resolveAt = async (offset: number, entry: PackEntry): Promise<boolean> => {
+    let object: { type: number; data: Buffer };
+    if (entry.baseOffset !== undefined || entry.baseOid !== undefined) {
+      const baseOid = entry.baseOffset !== undefined ? resolvedAt.get(entry.baseOffset)?.oid : entry.baseOid!;
+      if (!baseOid) return false;
+      const base = await loadBase(baseOid, oids.has(baseOid));
+      if (!base) return false;
+      const maxResultBytes = typeToString(base.type) === "blob" ? limits.blob : limits.other;
+      object = { type: base.type, data: applyDelta(base.data, entry.data, maxResultBytes) };
+    } else {
+      object = { type: entry.type, data: entry.data };
+    }
+
+    const type = typeToString(object.type);
+    const oid = hashObject(type, object.data);
+    if (type === "blob" && object.data.length > maxBlobBytes) {
+      throw new UnpackLimitError(`blob ${oid} exceeds maximum size of ${maxBlobBytes} bytes`);
+    }
+    resolvedAt.set(offset, { oid, type: object.type });
+    if (collectIndex) {
+      indexEntries.push({ oid, offset, crc32: entry.crc });
+    }
+    if (oids.has(oid)) return true;
+
+    oids.add(oid);
+    if (type === "blob") {
+      blobSizes.set(oid, object.data.length);
+      if (object.data.length > warnBlobBytes) largeBlobs.push({ oid, size: object.data.length });
+    } else if (type === "tree") {
+      treeOids.push(oid);
+    }
+    remember(oid, object);
+    await write(oid, type, object.data);
+    return true;
+  };
+
+  let deferred: number[] = [];
+  let offset = 12;
+  for (let i = 0; i < numObjects; i++) {
+    const entry = await readEntry(reader, offset, limits);
+    if (!(await resolveAt(offset, entry))) {
+      deferred.push(offset);
+    }
+    offset = entry.next;
+  }
+
+  // A REF_DELTA may name a base that comes later in the pack; retry until nothing more resolves.
+  while (deferred.length > 0) {
+    const remaining: number[] = [];
+    for (const entryOffset of deferred) {
+      const entry = await readEntry(reader, entryOffset, limits);
+      if (!(await resolveAt(entryOffset, entry))) remaining.push(entryOffset);
+    }
+    if (remaining.length === deferred.length) break;
+    deferred = remaining;
+  }
+
+  await Promise.all(writing.values());
+  if (storeError) throw storeError;
+
+  if (deferred.length > 0) {
+    console.error(`[API] unpack: failed to resolve ${deferred.length} deltas, first at offset ${deferred[0]}`);
+    return { success: false, objectCount: 0, error: `missing base objects for ${deferred.length} deltas` };
+  }
+
+  let largeFiles: LargeFile[] = [];
+  if (largeBlobs.length > 0) {
+    const trees = await mapConcurrent(treeOids, OBJECT_FETCH_CONCURRENCY, async (oid) => {
+      const tree = cache.get(oid) ?? (await loadObjectFromStorage(oid, store));
+      return { oid, data: tree?.data ?? Buffer.alloc(0) };
+    });
+    largeFiles = locateLargeFiles(trees, largeBlobs);
+  }
+
+  console.log(`[API] unpack: stored ${oids.size} objects`);
+
+  return {
+    success: true,
+    objectCount: oids.size,
+    oids,
+    blobSizes,
+    largeFiles,
+    indexEntries: collectIndex ? indexEntries : undefined,
+    packChecksum: reader.size >= 32 ? await reader.read(reader.size - 20, 20) : undefined,
+    thin,
+  };
+}
+
+// Writes one object as a loose object. Objects are content-addressed, so concurrent writes of the
+// same object are harmless.
+export async function storeLooseObject(store: GitStore, oid: string, type: string, data: Buffer) {
+  const compressed = zlib.deflateSync(Buffer.concat([Buffer.from(`${type} ${data.length}\0`), data]));
+  await store.fs.promises.writeFile(`.git/objects/${oid.substring(0, 2)}/${oid.substring(2)}`, compressed);
+}
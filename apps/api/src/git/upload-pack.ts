@@ -0,0 +1,317 @@
+import git from "isomorphic-git";
+import { createHash } from "crypto";
+import * as zlib from "zlib";
+import { resolveCommittish, type GitStore } from "./index";
+import { loadReachabilityIndex, updateReachabilityIndex, enumerateFromIndex } from "./reachability";
+import { trackTask } from "../metrics";
+
+const MAX_SIDEBAND_64K_DATA = 65515;
+const MAX_SIDEBAND_DATA = 995;
+
+const SIDEBAND_DATA = 1;
+const SIDEBAND_PROGRESS = 2;
+const SIDEBAND_ERROR = 3;
+
+const PACK_TYPES: Record<string, number> = { commit: 1, tree: 2, blob: 3, tag: 4 };
+
+export type UploadPackRequest = {
+  wants: string[];
+  haves: string[];
+  capabilities: Set<string>;
+  agent: string | null;
+  done: boolean;
+};
+
+// Unlike the receive-pack command list, an upload-pack request spans several flush-pkts
+// (wants, then haves, then done), so the whole body is read.
+function readPktLines(data: Buffer): string[] {
+  const lines: string[] = [];
+  let offset = 0;
+  while (offset + 4 <= data.length) {
+    const length = parseInt(data.subarray(offset, offset + 4).toString("ascii"), 16);
+    if (Number.isNaN(length)) break;
+    if (length < 4) {
+      offset += 4;
+      continue;
+    }
+    lines.push(data.subarray(offset + 4, offset + length).toString("utf8").replace(/\n$/, ""));
+    offset += length;
+  }
+  return lines;
+}
+
+export function parseUploadPackRequest(body: Buffer): UploadPackRequest {
+  const request: UploadPackRequest = { wants: [], haves: [], capabilities: new Set(), agent: null, done: false };
+
+  for (const line of readPktLines(body)) {
+    if (line.startsWith("want ")) {
+      const [, oid, ...caps] = line.split(" ");
+      if (request.wants.length === 0) {
+        for (const cap of caps) {
+          if (cap.startsWith("agent=")) {
+            request.agent = cap.slice("agent=".length);
+          } else if (cap) {
+            request.capabilities.add(cap);
+          }
+        }
+      }
+      request.wants.push(oid);
+    } else if (line.startsWith("have ")) {
+      request.haves.push(line.slice("have ".length, "have ".length + 40));
+    } else if (line === "done") {
+      request.done = true;
+    }
+  }
+
+  return request;
+}
+
+// A fetch the client got wrong (no wants, an object we don't advertise). The message is sent to
+// git as-is, unlike internal failures.
+export class UploadPackRequestError extends Error {
+  constructor(message: string) {
+    super(message);
+    this.name = "UploadPackRequestError";
+  }
+}
+
+export function pktLine(data: string | Buffer): Buffer {
+  const payload = typeof data === "string" ? Buffer.from(data, "utf8") : data;
+  return Buffer.concat([Buffer.from((payload.length + 4).toString(16).padStart(4, "0")), payload]);
+}
+
+const FLUSH_PKT = Buffer.from("0000");
+
+export type SidebandWriter = {
+  data: (chunk: Buffer) => Buffer[];
+  progress: (message: string) => Buffer[];
+  error: (message: string) => Buffer[];
+  end: () => Buffer[];
+};
+
+// Frames output for the client: with side-band(-64k) every pkt-line carries a band byte, pack data
+// on 1, progress on 2 and fatal errors on 3. Without it the pack is raw and progress is dropped.
+export function createSidebandWriter(capabilities: Set<string>): SidebandWriter {
+  const maxData = capabilities.has("side-band-64k")
+    ? MAX_SIDEBAND_64K_DATA
+    : capabilities.has("side-band")
+      ? MAX_SIDEBAND_DATA
+      : 0;
+  const quiet = capabilities.has("no-progress");
+
+  const band = (id: number, payload: Buffer): Buffer[] => {
+    const packets: Buffer[] = [];
+    for (let offset = 0; offset < payload.length; offset += maxData) {
+      packets.push(pktLine(Buffer.concat([Buffer.from([id]), payload.subarray(offset, offset + maxData)])));
+    }
+    return packets;
+  };
+
+  return {
+    data: (chunk) => (maxData > 0 ? band(SIDEBAND_DATA, chunk) : [chunk]),
+    progress: (message) => (maxData > 0 && !quiet ? band(SIDEBAND_PROGRESS, Buffer.from(message, "utf8")) : []),
+    error: (message) =>
+      maxData > 0 ? band(SIDEBAND_ERROR, Buffer.from(message + "\n", "utf8")) : [pktLine(`ERR ${message}\n`)],
+    end: () => (maxData > 0 ? [FLUSH_PKT] : []),
+  };
+}
+
+async function readRawObject(store: GitStore, oid: string): Promise<{ type: string; object: Uint8Array } | null> {
+  try {
+    return (await git.readObject({ fs: store.fs, dir: store.dir, oid, format: "content" })) as {
+      type: string;
+      object: Uint8Array;
+    };
+  } catch {
+    return null;
+  }
+}
+
+export async function hasObject(store: GitStore, oid: string): Promise<boolean> {
+  return (await readRawObject(store, oid)) !== null;
+}
+
+// Wants have to be branch or tag tips, or commits one of them still reaches: over stateless HTTP
+// a ref can move between the advertisement and the fetch. Anything else is refused, like git does
+// without allowAnySHA1InWant, so objects left behind by a force-push (or belonging to another
+// repository in a shared object store) can't be fetched by oid. Returns the wants that failed.
+export async function findUnreachableWants(store: GitStore, wants: string[]): Promise<string[]> {
+  const { fs, dir } = store;
+  const refs = [
+    ...(await git.listBranches({ fs, dir })).map((branch) => `refs/heads/${branch}`),
+    ...(await git.listTags({ fs, dir })).map((tag) => `refs/tags/${tag}`),
+  ];
+
+  const tips = new Set<string>();
+  const tipCommits: string[] = [];
+  for (const ref of refs) {
+    const oid = await git.resolveRef({ fs, dir, ref }).catch(() => null);
+    if (oid) tips.add(oid);
+    const commit = await resolveCommittish(fs, dir, ref);
+    if (commit) {
+      tips.add(commit);
+      tipCommits.push(commit);
+    }
+  }
+
+  const pending = new Set(wants.filter((oid) => !tips.has(oid)));
+  if (pending.size === 0) return [];
+
+  const index = await loadReachabilityIndex(store).catch(() => null);
+  const queue = [...tipCommits];
+  const seen = new Set<string>();
+  while (queue.length > 0 && pending.size > 0) {
+    const oid = queue.pop()!;
+    if (seen.has(oid)) continue;
+    seen.add(oid);
+    pending.delete(oid);
+
+    const parents = index?.get(oid)?.[0] ?? (await git.readCommit({ fs, dir, oid }).then(({ commit }) => commit.parent, () => []));
+    queue.push(...parents);
+  }
+  return [...pending];
+}
+
+async function walkObjects(
+  store: GitStore,
+  roots: string[],
+  exclude: Set<string>,
+  visit: (oid: string, type: string, object: Uint8Array) => void
+) {
+  const queue = [...roots];
+  const seen = new Set<string>();
+
+  while (queue.length > 0) {
+    const oid = queue.pop()!;
+    if (seen.has(oid) || exclude.has(oid)) continue;
+    seen.add(oid);
+
+    const raw = await readRawObject(store, oid);
+    if (!raw) {
+      throw new Error(`missing object ${oid}`);
+    }
+    visit(oid, raw.type, raw.object);
+
+    if (raw.type === "commit") {
+      const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid });
+      queue.push(commit.tree, ...commit.parent);
+    } else if (raw.type === "tree") {
+      const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid });
+      queue.push(...tree.filter((entry) => entry.type !== "commit").map((entry) => entry.oid));
+    } else if (raw.type === "tag") {
+      const { tag } = await git.readTag({ fs: store.fs, dir: store.dir, oid });
+      queue.push(tag.object);
+    }
+  }
+}
+
+function packObjectHeader(type: number, size: number): Buffer {
+  const bytes: number[] = [];
+  let byte = (type << 4) | (size & 0x0f);
+  size = Math.floor(size / 16);
+  while (size > 0) {
+    bytes.push(byte | 0x80);
+    byte = size & 0x7f;
+    size = Math.floor(size / 128);
+  }
+  bytes.push(byte);
+  return Buffer.from(bytes);
+}
+
+async function readIndexedObjects(
+  store: GitStore,
+  oids: string[],
+  onProgress?: (message: string) => void
+): Promise<{ oid: string; type: string; object: Uint8Array }[]> {
+  const objects: { oid: string; type: string; object: Uint8Array }[] = [];
+  for (const oid of oids) {
+    const raw = await readRawObject(store, oid);
+    if (!raw) {
+      throw new Error(`missing object ${oid}`);
+    }
+    objects.push({ oid, type: raw.type, object: raw.object });
+    if (objects.length % 1000 === 0) {
+      onProgress?.(`Counting objects: ${objects.length}\r`);
+    }
+  }
+  return objects;
+}
+
+// Collects everything reachable from the wants that the client doesn't already have through its
+// common haves. Objects are written whole; ofs/ref deltas are never produced. The reachability
+// index lets this skip walking the history behind the haves; without it, or when it hasn't
+// caught up with the wants yet, both sides are walked in full and the index is brought up to date.
+export async function buildPack(
+  store: GitStore,
+  wants: string[],
+  commonHaves: string[],
+  onProgress?: (message: string) => void
+): Promise<Buffer> {
+  const index = await loadReachabilityIndex(store).catch((error) => {
+    console.error(`[Git] Failed to load reachability index for ${store.ownerId}/${store.repoName}:`, error);
+    return null;
+  });
+  const planned = index ? await enumerateFromIndex(store, index, wants, commonHaves) : null;
+
+  let objects: { oid: string; type: string; object: Uint8Array }[];
+  if (planned) {
+    objects = await readIndexedObjects(store, planned, onProgress);
+  } else {
+    trackTask("reachability_index", () => updateReachabilityIndex(store)).catch((error) => {
+      console.error(`[Git] Failed to update reachability index for ${store.ownerId}/${store.repoName}:`, error);
+    });
+
+    const exclude = new Set<string>();
+    await walkObjects(store, commonHaves, new Set(), (oid) => exclude.add(oid));
+
+    objects = [];
+    await walkObjects(store, wants, exclude, (oid, type, object) => {
+      objects.push({ oid, type, object });
+      if (objects.length % 1000 === 0) {
+        onProgress?.(`Counting objects: ${objects.length}\r`);
+      }
+    });
+  }
+  onProgress?.(`Counting objects: ${objects.length}, done.\n`);
+
+  const header = Buffer.alloc(12);
+  header.write("PACK", 0, "ascii");
+  header.writeUInt32BE(2, 4);
+  header.writeUInt32BE(objects.length, 8);
+
+  const parts: Buffer[] = [header];
+  for (const [index, entry] of objects.entries()) {
+    parts.push(packObjectHeader(PACK_TYPES[entry.type], entry.object.length), zlib.deflateSync(entry.object));
+    if ((index + 1) % 1000 === 0) {
+      onProgress?.(`Compressing objects: ${Math.floor(((index + 1) / objects.length) * 100)}% (${index + 1}/${objects.length})\r`);
+    }
+  }
+  onProgress?.(`Compressing objects: 100% (${objects.length}/${objects.length}), done.\n`);
+
+  const body = Buffer.concat(parts);
+  return Buffer.concat([body, createHash("sha1").update(body).digest()]);
+}
+
+// A v2 bundle is a ref list followed by a pack of everything those refs reach, which
+// `git clone <file>.bundle` accepts directly.
+export async function buildBundle(store: GitStore): Promise<Buffer | null> {
+  const refs: { name: string; oid: string }[] = [];
+  for (const branch of await git.listBranches({ fs: store.fs, dir: store.dir })) {
+    refs.push({ name: `refs/heads/${branch}`, oid: await git.resolveRef({ fs: store.fs, dir: store.dir, ref: `refs/heads/${branch}` }) });
+  }
+  for (const tag of await git.listTags({ fs: store.fs, dir: store.dir })) {
+    refs.push({ name: `refs/tags/${tag}`, oid: await git.resolveRef({ fs: store.fs, dir: store.dir, ref: `refs/tags/${tag}` }) });
+  }
+  if (refs.length === 0) {
+    return null;
+  }
+
+  try {
+    const head = await git.resolveRef({ fs: store.fs, dir: store.dir, ref: "HEAD" });
+    refs.unshift({ name: "HEAD", oid: head });
+  } catch {}
+
+  const header = "# v2 git bundle\n" + refs.map((ref) => `${ref.oid} ${ref.name}\n`).join("") + "\n";
+  const pack = await buildPack(store, [...new Set(refs.map((ref) => ref.oid))], []);
+  return Buffer.concat([Buffer.from(header, "utf8"), pack]);
+}
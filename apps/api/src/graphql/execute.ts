@@ -0,0 +1,144 @@
+import type { FieldNode, OperationNode, ValueNode } from "./parser";
+
+export type FieldDefinition<C> = {
+  // Scalar names ("String", "Int", "Boolean", "ID", "DateTime", "JSON"), an object type name, or a
+  // list of either written as "[Type]".
+  type: string;
+  resolve?: (parent: any, args: Record<string, unknown>, context: C) => unknown;
+};
+
+export type GraphQLSchema<C> = {
+  query: string;
+  types: Record<string, Record<string, FieldDefinition<C>>>;
+};
+
+export type GraphQLError = { message: string; path?: (string | number)[] };
+
+export class GraphQLArgumentError extends Error {}
+
+const MAX_DEPTH = 10;
+const MAX_COST = 100_000;
+const DEFAULT_LIST_SIZE = 30;
+
+function valueFromNode(node: ValueNode, variables: Record<string, unknown>): unknown {
+  switch (node.kind) {
+    case "variable":
+      return variables[node.name] ?? null;
+    case "literal":
+      return node.value;
+    case "list":
+      return node.values.map((value) => valueFromNode(value, variables));
+    case "object":
+      return Object.fromEntries(Object.entries(node.fields).map(([key, value]) => [key, valueFromNode(value, variables)]));
+  }
+}
+
+// Estimates how many values a selection can produce before anything is resolved: each field
+// counts once per parent value, and list fields multiply their subfields by the requested
+// `limit` (or a typical page size when there isn't one).
+function estimateCost<C>(schema: GraphQLSchema<C>, typeName: string, selections: FieldNode[], variables: Record<string, unknown>): number {
+  const objectType = schema.types[typeName];
+  let cost = 0;
+  for (const field of selections) {
+    const definition = objectType?.[field.name];
+    if (!definition) {
+      cost += 1;
+      continue;
+    }
+    const list = definition.type.startsWith("[");
+    const limit = list && field.args.limit ? valueFromNode(field.args.limit, variables) : null;
+    const size = list ? (typeof limit === "number" && limit > 0 ? limit : DEFAULT_LIST_SIZE) : 1;
+    const itemType = list ? definition.type.slice(1, -1) : definition.type;
+    cost += size * (1 + (field.selections ? estimateCost(schema, itemType, field.selections, variables) : 0));
+  }
+  return cost;
+}
+
+function serializeScalar(value: unknown) {
+  return value instanceof Date ? value.toISOString() : value;
+}
+
+export async function execute<C>(
+  schema: GraphQLSchema<C>,
+  operation: OperationNode,
+  variableValues: Record<string, unknown>,
+  context: C
+): Promise<{ data: Record<string, unknown> | null; errors?: GraphQLError[] }> {
+  const errors: GraphQLError[] = [];
+
+  const variables: Record<string, unknown> = {};
+  for (const [name, defaultValue] of Object.entries(operation.variableDefaults)) {
+    variables[name] = name in variableValues ? variableValues[name] : defaultValue ? valueFromNode(defaultValue, {}) : null;
+  }
+
+  if (estimateCost(schema, schema.query, operation.selections, variables) > MAX_COST) {
+    return { data: null, errors: [{ message: `Query is too expensive: it could return more than ${MAX_COST} values` }] };
+  }
+
+  const completeValue = async (typeName: string, value: unknown, field: FieldNode, path: (string | number)[], depth: number): Promise<unknown> => {
+    if (value === null || value === undefined) return null;
+
+    if (typeName.startsWith("[")) {
+      const itemType = typeName.slice(1, -1);
+      const items = await Promise.all(value as unknown[]);
+      return Promise.all(items.map((item, index) => completeValue(itemType, item, field, [...path, index], depth)));
+    }
+
+    const objectType = schema.types[typeName];
+    if (!objectType) {
+      if (field.selections) throw new GraphQLArgumentError(`Field "${field.name}" of type ${typeName} has no subfields`);
+      return serializeScalar(value);
+    }
+    if (!field.selections) {
+      throw new GraphQLArgumentError(`Field "${field.name}" of type ${typeName} must have a selection of subfields`);
+    }
+    return executeSelections(typeName, value, field.selections, path, depth + 1);
+  };
+
+  const executeSelections = async (
+    typeName: string,
+    parent: unknown,
+    selections: FieldNode[],
+    path: (string | number)[],
+    depth: number
+  ): Promise<Record<string, unknown>> => {
+    if (depth > MAX_DEPTH) {
+      throw new GraphQLArgumentError(`Query exceeds the maximum depth of ${MAX_DEPTH}`);
+    }
+
+    const objectType = schema.types[typeName];
+    const entries = await Promise.all(
+      selections.map(async (field): Promise<[string, unknown]> => {
+        const fieldPath = [...path, field.alias];
+        if (field.name === "__typename") return [field.alias, typeName];
+
+        const definition = objectType[field.name];
+        if (!definition) {
+          errors.push({ message: `Cannot query field "${field.name}" on type "${typeName}"`, path: fieldPath });
+          return [field.alias, null];
+        }
+
+        try {
+          const args = Object.fromEntries(Object.entries(field.args).map(([key, value]) => [key, valueFromNode(value, variables)]));
+          const raw = definition.resolve
+            ? await definition.resolve(parent, args, context)
+            : (parent as Record<string, unknown>)[field.name];
+          return [field.alias, await completeValue(definition.type, raw, field, fieldPath, depth)];
+        } catch (error) {
+          if (!(error instanceof GraphQLArgumentError)) {
+            console.error("[API] graphql resolver error:", error);
+          }
+          errors.push({
+            message: error instanceof GraphQLArgumentError ? error.message : "Internal server error",
+            path: fieldPath,
+          });
+          return [field.alias, null];
+        }
+      })
+    );
+    return Object.fromEntries(entries);
+  };
+
+  const data = await executeSelections(schema.query, {}, operation.selections, [], 0);
+  return errors.length > 0 ? { data, errors } : { data };
+}
@@ -0,0 +1,33 @@
+export type Loader<K, V> = { load: (key: K) => Promise<V> };
+
+// Collects every key requested while resolvers are fanning out and fetches them with one batch
+// call. Loaders live for a single request, so results are cached without invalidation.
+export function createLoader<K, V>(batch: (keys: K[]) => Promise<Map<K, V>>, fallback: V): Loader<K, V> {
+  const cache = new Map<K, Promise<V>>();
+  let pending: { key: K; resolve: (value: V) => void; reject: (error: unknown) => void }[] = [];
+
+  const dispatch = async () => {
+    const queued = pending;
+    pending = [];
+    try {
+      const results = await batch([...new Set(queued.map((item) => item.key))]);
+      for (const item of queued) item.resolve(results.get(item.key) ?? fallback);
+    } catch (error) {
+      for (const item of queued) item.reject(error);
+    }
+  };
+
+  return {
+    load(key) {
+      const cached = cache.get(key);
+      if (cached) return cached;
+
+      const promise = new Promise<V>((resolve, reject) => {
+        if (pending.length === 0) setImmediate(dispatch);
+        pending.push({ key, resolve, reject });
+      });
+      cache.set(key, promise);
+      return promise;
+    },
+  };
+}
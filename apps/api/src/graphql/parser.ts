@@ -0,0 +1,262 @@
+export type ValueNode =
+  | { kind: "variable"; name: string }
+  | { kind: "literal"; value: unknown }
+  | { kind: "list"; values: ValueNode[] }
+  | { kind: "object"; fields: Record<string, ValueNode> };
+
+export type FieldNode = {
+  alias: string;
+  name: string;
+  args: Record<string, ValueNode>;
+  selections: FieldNode[] | null;
+};
+
+export type OperationNode = {
+  name: string | null;
+  variableDefaults: Record<string, ValueNode | undefined>;
+  selections: FieldNode[];
+};
+
+export class GraphQLSyntaxError extends Error {}
+
+type Token = { kind: "punct" | "name" | "int" | "float" | "string" | "variable"; value: string };
+
+const PUNCTUATORS = new Set(["{", "}", "(", ")", "[", "]", ":", "=", "!", "@"]);
+
+const ESCAPES: Record<string, string> = { n: "\n", t: "\t", r: "\r", b: "\b", f: "\f" };
+
+// Selection sets, list/object values and wrapped types all recurse, so deeply nested input would
+// overflow the stack before the executor's own depth limit is reached.
+const MAX_NESTING = 32;
+const MAX_FIELDS = 500;
+
+function tokenize(source: string): Token[] {
+  const tokens: Token[] = [];
+  let i = 0;
+
+  while (i < source.length) {
+    const char = source[i];
+
+    if (char === "#") {
+      while (i < source.length && source[i] !== "\n") i++;
+      continue;
+    }
+    if (/[\s,]/.test(char)) {
+      i++;
+      continue;
+    }
+    if (source.startsWith("...", i)) {
+      throw new GraphQLSyntaxError("Fragments are not supported");
+    }
+    if (PUNCTUATORS.has(char)) {
+      tokens.push({ kind: "punct", value: char });
+      i++;
+      continue;
+    }
+    if (char === "$") {
+      const match = /^\$([_A-Za-z][_0-9A-Za-z]*)/.exec(source.slice(i));
+      if (!match) throw new GraphQLSyntaxError(`Unexpected character "$" at ${i}`);
+      tokens.push({ kind: "variable", value: match[1] });
+      i += match[0].length;
+      continue;
+    }
+    if (char === '"') {
+      let value = "";
+      i++;
+      while (i < source.length && source[i] !== '"') {
+        if (source[i] === "\\") {
+          const escaped = source[i + 1];
+          if (escaped === "u") {
+            value += String.fromCharCode(parseInt(source.slice(i + 2, i + 6), 16));
+            i += 6;
+            continue;
+          }
+          value += ESCAPES[escaped] ?? escaped;
+          i += 2;
+          continue;
+        }
+        value += source[i++];
+      }
+      if (i >= source.length) throw new GraphQLSyntaxError("Unterminated string");
+      i++;
+      tokens.push({ kind: "string", value });
+      continue;
+    }
+
+    const number = /^-?\d+(\.\d+)?([eE][+-]?\d+)?/.exec(source.slice(i));
+    if (number) {
+      tokens.push({ kind: number[1] || number[2] ? "float" : "int", value: number[0] });
+      i += number[0].length;
+      continue;
+    }
+    const name = /^[_A-Za-z][_0-9A-Za-z]*/.exec(source.slice(i));
+    if (name) {
+      tokens.push({ kind: "name", value: name[0] });
+      i += name[0].length;
+      continue;
+    }
+
+    throw new GraphQLSyntaxError(`Unexpected character "${char}" at ${i}`);
+  }
+
+  return tokens;
+}
+
+// Parses the subset of GraphQL the API executes: query operations with variables, aliases,
+// arguments and nested selections. Fragments, directives and mutations are rejected.
+export function parseOperation(source: string, operationName?: string | null): OperationNode {
+  const tokens = tokenize(source);
+  let pos = 0;
+  let depth = 0;
+  let fieldCount = 0;
+
+  const nested = <T>(parse: () => T): T => {
+    if (++depth > MAX_NESTING) throw new GraphQLSyntaxError(`Document nests deeper than ${MAX_NESTING} levels`);
+    const result = parse();
+    depth--;
+    return result;
+  };
+
+  const peek = () => tokens[pos];
+  const isPunct = (value: string) => peek()?.kind === "punct" && peek()?.value === value;
+  const expectPunct = (value: string) => {
+    if (!isPunct(value)) throw new GraphQLSyntaxError(`Expected "${value}"`);
+    pos++;
+  };
+  const expectName = () => {
+    const token = peek();
+    if (token?.kind !== "name") throw new GraphQLSyntaxError("Expected a name");
+    pos++;
+    return token.value;
+  };
+
+  const parseValue = (constant: boolean): ValueNode => {
+    const token = peek();
+    if (!token) throw new GraphQLSyntaxError("Unexpected end of document");
+    pos++;
+    switch (token.kind) {
+      case "variable":
+        if (constant) throw new GraphQLSyntaxError("Variables are not allowed here");
+        return { kind: "variable", name: token.value };
+      case "int":
+        return { kind: "literal", value: parseInt(token.value, 10) };
+      case "float":
+        return { kind: "literal", value: parseFloat(token.value) };
+      case "string":
+        return { kind: "literal", value: token.value };
+      case "name":
+        if (token.value === "true" || token.value === "false") return { kind: "literal", value: token.value === "true" };
+        if (token.value === "null") return { kind: "literal", value: null };
+        return { kind: "literal", value: token.value };
+      case "punct":
+        if (token.value === "[") {
+          const values: ValueNode[] = [];
+          while (!isPunct("]")) values.push(nested(() => parseValue(constant)));
+          pos++;
+          return { kind: "list", values };
+        }
+        if (token.value === "{") {
+          const fields: Record<string, ValueNode> = {};
+          while (!isPunct("}")) {
+            const key = expectName();
+            expectPunct(":");
+            fields[key] = nested(() => parseValue(constant));
+          }
+          pos++;
+          return { kind: "object", fields };
+        }
+    }
+    throw new GraphQLSyntaxError(`Unexpected "${token.value}"`);
+  };
+
+  const skipType = () => {
+    if (isPunct("[")) {
+      pos++;
+      nested(skipType);
+      expectPunct("]");
+    } else {
+      expectName();
+    }
+    if (isPunct("!")) pos++;
+  };
+
+  const parseSelectionSet = (): FieldNode[] => nested(() => {
+    expectPunct("{");
+    const fields: FieldNode[] = [];
+    while (!isPunct("}")) {
+      if (!peek()) throw new GraphQLSyntaxError("Unexpected end of document");
+      if (isPunct("@")) throw new GraphQLSyntaxError("Directives are not supported");
+      const alias = expectName();
+      let name = alias;
+      if (isPunct(":")) {
+        pos++;
+        name = expectName();
+      }
+      const args: Record<string, ValueNode> = {};
+      if (isPunct("(")) {
+        pos++;
+        while (!isPunct(")")) {
+          const argName = expectName();
+          expectPunct(":");
+          args[argName] = parseValue(false);
+        }
+        pos++;
+      }
+      if (isPunct("@")) throw new GraphQLSyntaxError("Directives are not supported");
+      if (++fieldCount > MAX_FIELDS) throw new GraphQLSyntaxError(`Document selects more than ${MAX_FIELDS} fields`);
+      fields.push({ alias, name, args, selections: isPunct("{") ? parseSelectionSet() : null });
+    }
+    pos++;
+    return fields;
+  });
+
+  const operations: OperationNode[] = [];
+  while (pos < tokens.length) {
+    if (isPunct("{")) {
+      operations.push({ name: null, variableDefaults: {}, selections: parseSelectionSet() });
+      continue;
+    }
+
+    const keyword = expectName();
+    if (keyword === "mutation" || keyword === "subscription") {
+      throw new GraphQLSyntaxError(`${keyword} operations are not supported`);
+    }
+    if (keyword !== "query") {
+      throw new GraphQLSyntaxError(keyword === "fragment" ? "Fragments are not supported" : `Unexpected "${keyword}"`);
+    }
+
+    const name = peek()?.kind === "name" ? expectName() : null;
+    const variableDefaults: Record<string, ValueNode | undefined> = {};
+    if (isPunct("(")) {
+      pos++;
+      while (!isPunct(")")) {
+        const variable = peek();
+        if (variable?.kind !== "variable") throw new GraphQLSyntaxError("Expected a variable definition");
+        pos++;
+        expectPunct(":");
+        skipType();
+        let defaultValue: ValueNode | undefined;
+        if (isPunct("=")) {
+          pos++;
+          defaultValue = parseValue(true);
+        }
+        variableDefaults[variable.value] = defaultValue;
+      }
+      pos++;
+    }
+    operations.push({ name, variableDefaults, selections: parseSelectionSet() });
+  }
+
+  if (operations.length === 0) {
+    throw new GraphQLSyntaxError("Document contains no operations");
+  }
+  if (operationName) {
+    const operation = operations.find((op) => op.name === operationName);
+    if (!operation) throw new GraphQLSyntaxError(`Unknown operation "${operationName}"`);
+    return operation;
+  }
+  if (operations.length > 1) {
+    throw new GraphQLSyntaxError("operationName is required when the document has several operations");
+  }
+  return operations[0];
+}
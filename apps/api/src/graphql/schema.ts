@@ -0,0 +1,261 @@
+import { db, users, repositories, issues, issueComments, issueLabels, labels, stars } from "@gitbruv/db";
+import { eq, and, desc, asc, inArray, sql } from "drizzle-orm";
+import { getReactionsGroupedBatch, type ReactionSummary } from "../reactions";
+import { createLoader } from "./loader";
+import { GraphQLArgumentError, type GraphQLSchema } from "./execute";
+
+type UserRow = typeof users.$inferSelect;
+type RepositoryRow = typeof repositories.$inferSelect;
+type IssueRow = typeof issues.$inferSelect;
+type IssueCommentRow = typeof issueComments.$inferSelect;
+type LabelRow = typeof labels.$inferSelect;
+
+function groupBy<T>(rows: T[], key: (row: T) => string): Map<string, T[]> {
+  const grouped = new Map<string, T[]>();
+  for (const row of rows) {
+    const list = grouped.get(key(row)) ?? [];
+    list.push(row);
+    grouped.set(key(row), list);
+  }
+  return grouped;
+}
+
+export function createGraphQLContext(viewerId?: string) {
+  return {
+    viewerId,
+    users: createLoader<string, UserRow | null>(async (ids) => {
+      const rows = await db.select().from(users).where(inArray(users.id, ids));
+      return new Map(rows.map((row) => [row.id, row]));
+    }, null),
+    repositories: createLoader<string, RepositoryRow | null>(async (ids) => {
+      const rows = await db.select().from(repositories).where(inArray(repositories.id, ids));
+      return new Map(rows.map((row) => [row.id, row]));
+    }, null),
+    starCounts: createLoader<string, number>(async (ids) => {
+      const rows = await db
+        .select({ repositoryId: stars.repositoryId, count: sql<number>`COUNT(*)` })
+        .from(stars)
+        .where(inArray(stars.repositoryId, ids))
+        .groupBy(stars.repositoryId);
+      return new Map(rows.map((row) => [row.repositoryId, Number(row.count)]));
+    }, 0),
+    issueLabels: createLoader<string, LabelRow[]>(async (ids) => {
+      const rows = await db
+        .select({ issueId: issueLabels.issueId, label: labels })
+        .from(issueLabels)
+        .innerJoin(labels, eq(labels.id, issueLabels.labelId))
+        .where(inArray(issueLabels.issueId, ids))
+        .orderBy(asc(labels.name));
+      return new Map([...groupBy(rows, (row) => row.issueId)].map(([id, list]) => [id, list.map((row) => row.label)]));
+    }, []),
+    issueComments: createLoader<string, IssueCommentRow[]>(async (ids) => {
+      const rows = await db
+        .select()
+        .from(issueComments)
+        .where(inArray(issueComments.issueId, ids))
+        .orderBy(asc(issueComments.createdAt));
+      return groupBy(rows, (row) => row.issueId);
+    }, []),
+    issueReactions: createLoader<string, ReactionSummary[]>((ids) => getReactionsGroupedBatch("issue", ids, viewerId), []),
+    commentReactions: createLoader<string, ReactionSummary[]>((ids) => getReactionsGroupedBatch("issue_comment", ids, viewerId), []),
+  };
+}
+
+export type GraphQLContext = ReturnType<typeof createGraphQLContext>;
+
+function stringArg(args: Record<string, unknown>, name: string): string {
+  const value = args[name];
+  if (typeof value !== "string" || !value) {
+    throw new GraphQLArgumentError(`Argument "${name}" must be a non-empty string`);
+  }
+  return value;
+}
+
+function pageArgs(args: Record<string, unknown>, defaultLimit: number) {
+  const limit = args.limit ?? defaultLimit;
+  const offset = args.offset ?? 0;
+  if (typeof limit !== "number" || !Number.isInteger(limit) || limit < 1 || limit > 100) {
+    throw new GraphQLArgumentError('Argument "limit" must be an integer between 1 and 100');
+  }
+  if (typeof offset !== "number" || !Number.isInteger(offset) || offset < 0) {
+    throw new GraphQLArgumentError('Argument "offset" must be a non-negative integer');
+  }
+  return { limit, offset };
+}
+
+// Issues are only reachable through a repository the viewer can already see, so visibility is
+// checked once where a repository is looked up rather than per issue.
+function canSee(repo: RepositoryRow | null, context: GraphQLContext) {
+  return !!repo && (repo.visibility !== "private" || repo.ownerId === context.viewerId);
+}
+
+export const schema: GraphQLSchema<GraphQLContext> = {
+  query: "Query",
+  types: {
+    Query: {
+      viewer: {
+        type: "User",
+        resolve: (_parent, _args, context) => (context.viewerId ? context.users.load(context.viewerId) : null),
+      },
+      user: {
+        type: "User",
+        resolve: async (_parent, args) => {
+          const username = stringArg(args, "username");
+          return (await db.query.users.findFirst({ where: eq(users.username, username) })) ?? null;
+        },
+      },
+      repository: {
+        type: "Repository",
+        resolve: async (_parent, args, context) => {
+          const [row] = await db
+            .select({ repo: repositories })
+            .from(repositories)
+            .innerJoin(users, eq(users.id, repositories.ownerId))
+            .where(and(eq(users.username, stringArg(args, "owner")), eq(repositories.name, stringArg(args, "name"))))
+            .limit(1);
+          return row && canSee(row.repo, context) ? row.repo : null;
+        },
+      },
+    },
+    User: {
+      id: { type: "ID" },
+      username: { type: "String" },
+      name: { type: "String" },
+      avatarUrl: { type: "String" },
+      bio: { type: "String" },
+      location: { type: "String" },
+      website: { type: "String" },
+      company: { type: "String" },
+      createdAt: { type: "DateTime" },
+      repositories: {
+        type: "[Repository]",
+        resolve: (user: UserRow, args, context) => {
+          const { limit, offset } = pageArgs(args, 30);
+          const visible =
+            user.id === context.viewerId
+              ? eq(repositories.ownerId, user.id)
+              : and(eq(repositories.ownerId, user.id), eq(repositories.visibility, "public"));
+          return db.select().from(repositories).where(visible).orderBy(desc(repositories.updatedAt)).limit(limit).offset(offset);
+        },
+      },
+    },
+    Repository: {
+      id: { type: "ID" },
+      name: { type: "String" },
+      description: { type: "String" },
+      visibility: { type: "String" },
+      defaultBranch: { type: "String" },
+      topics: { type: "[String]" },
+      createdAt: { type: "DateTime" },
+      updatedAt: { type: "DateTime" },
+      owner: {
+        type: "User",
+        resolve: (repo: RepositoryRow, _args, context) => context.users.load(repo.ownerId),
+      },
+      starCount: {
+        type: "Int",
+        resolve: (repo: RepositoryRow, _args, context) => context.starCounts.load(repo.id),
+      },
+      issues: {
+        type: "[Issue]",
+        resolve: (repo: RepositoryRow, args) => {
+          const { limit, offset } = pageArgs(args, 30);
+          const raw = args.state ?? null;
+          const state = raw === "open" || raw === "closed" ? raw : null;
+          if (raw !== null && !state) {
+            throw new GraphQLArgumentError('Argument "state" must be "open" or "closed"');
+          }
+          return db
+            .select()
+            .from(issues)
+            .where(state ? and(eq(issues.repositoryId, repo.id), eq(issues.state, state)) : eq(issues.repositoryId, repo.id))
+            .orderBy(desc(issues.createdAt))
+            .limit(limit)
+            .offset(offset);
+        },
+      },
+      issue: {
+        type: "Issue",
+        resolve: async (repo: RepositoryRow, args) => {
+          const number = args.number;
+          if (typeof number !== "number" || !Number.isInteger(number)) {
+            throw new GraphQLArgumentError('Argument "number" must be an integer');
+          }
+          return (
+            (await db.query.issues.findFirst({
+              where: and(eq(issues.repositoryId, repo.id), eq(issues.number, number)),
+            })) ?? null
+          );
+        },
+      },
+    },
+    Issue: {
+      id: { type: "ID" },
+      number: { type: "Int" },
+      title: { type: "String" },
+      body: { type: "String" },
+      state: { type: "String" },
+      locked: { type: "Boolean" },
+      createdAt: { type: "DateTime" },
+      updatedAt: { type: "DateTime" },
+      closedAt: { type: "DateTime" },
+      author: {
+        type: "User",
+        resolve: (issue: IssueRow, _args, context) => context.users.load(issue.authorId),
+      },
+      repository: {
+        type: "Repository",
+        resolve: (issue: IssueRow, _args, context) => context.repositories.load(issue.repositoryId),
+      },
+      labels: {
+        type: "[Label]",
+        resolve: (issue: IssueRow, _args, context) => context.issueLabels.load(issue.id),
+      },
+      commentCount: {
+        type: "Int",
+        resolve: async (issue: IssueRow, _args, context) => (await context.issueComments.load(issue.id)).length,
+      },
+      comments: {
+        type: "[IssueComment]",
+        resolve: async (issue: IssueRow, args, context) => {
+          const { limit, offset } = pageArgs(args, 50);
+          return (await context.issueComments.load(issue.id)).slice(offset, offset + limit);
+        },
+      },
+      reactions: {
+        type: "[Reaction]",
+        resolve: (issue: IssueRow, _args, context) => context.issueReactions.load(issue.id),
+      },
+    },
+    IssueComment: {
+      id: { type: "ID" },
+      // Hidden comments are masked the same way as the REST comment list for non-owners.
+      body: {
+        type: "String",
+        resolve: (comment: IssueCommentRow) => (comment.hiddenReason ? null : comment.body),
+      },
+      hiddenReason: { type: "String" },
+      createdAt: { type: "DateTime" },
+      updatedAt: { type: "DateTime" },
+      author: {
+        type: "User",
+        resolve: (comment: IssueCommentRow, _args, context) => context.users.load(comment.authorId),
+      },
+      reactions: {
+        type: "[Reaction]",
+        resolve: (comment: IssueCommentRow, _args, context) => context.commentReactions.load(comment.id),
+      },
+    },
+    Label: {
+      id: { type: "ID" },
+      name: { type: "String" },
+      description: { type: "String" },
+      color: { type: "String" },
+    },
+    Reaction: {
+      emoji: { type: "String" },
+      count: { type: "Int" },
+      reacted: { type: "Boolean" },
+    },
+  },
+};
@@ -0,0 +1,72 @@
+import { bundledLanguages, codeToHtml, codeToTokens } from "shiki";
+import { getLanguage } from "@gitbruv/lib";
+import { getCached, setCache, highlightCacheKey, CACHE_TTL } from "./cache";
+
+export type HighlightTheme = "light" | "dark";
+export type HighlightFormat = "html" | "tokens";
+
+export type HighlightToken = { content: string; color?: string; fontStyle?: number };
+
+export type HighlightResult =
+  | { language: string; theme: HighlightTheme; format: "html"; html: string }
+  | { language: string; theme: HighlightTheme; format: "tokens"; lines: HighlightToken[][] };
+
+// The themes the web code viewer uses, so server-rendered and client-rendered code look the same.
+const THEMES: Record<HighlightTheme, string> = {
+  light: "github-light-default",
+  dark: "github-dark-default",
+};
+
+// Highlighting is CPU-bound and runs on the request thread of a public endpoint, and each
+// lang/theme/format combination is separate uncached work; past this a client is better off
+// showing plain text than waiting.
+export const MAX_HIGHLIGHT_BYTES = 128 * 1024;
+
+const inFlight = new Map<string, Promise<HighlightResult>>();
+
+// An explicit language wins when shiki knows it; otherwise it's inferred from the file extension
+// the same way the clients do, and anything unknown is rendered as plain text.
+export function resolveHighlightLanguage(language: string | undefined, path: string | undefined): string {
+  if (language && (language === "plaintext" || Object.hasOwn(bundledLanguages, language))) {
+    return language;
+  }
+  const inferred = path ? getLanguage(path.split("/").pop()!) : "plaintext";
+  return Object.hasOwn(bundledLanguages, inferred) ? inferred : "plaintext";
+}
+
+// A blob's oid fixes its content, so results are cached by oid alone and shared across branches,
+// forks and repositories; callers check the blob is readable before asking.
+export async function highlightBlob(
+  oid: string,
+  content: string,
+  language: string,
+  theme: HighlightTheme,
+  format: HighlightFormat
+): Promise<HighlightResult> {
+  const key = highlightCacheKey(oid, language, theme, format);
+  const cached = await getCached<HighlightResult>(key);
+  if (cached) return cached;
+
+  // Concurrent requests for the same uncached result share one highlighting pass.
+  const pending = inFlight.get(key);
+  if (pending) return pending;
+
+  const work = render(content, language, theme, format).finally(() => inFlight.delete(key));
+  inFlight.set(key, work);
+  const result = await work;
+
+  await setCache(key, result, CACHE_TTL.highlight);
+  return result;
+}
+
+async function render(content: string, language: string, theme: HighlightTheme, format: HighlightFormat): Promise<HighlightResult> {
+  if (format === "html") {
+    const html = await codeToHtml(content, { lang: language, theme: THEMES[theme] });
+    return { language, theme, format, html };
+  }
+  const { tokens } = await codeToTokens(content, { lang: language, theme: THEMES[theme] });
+  const lines = tokens.map((line) =>
+    line.map((token) => ({ content: token.content, color: token.color, fontStyle: token.fontStyle && token.fontStyle > 0 ? token.fontStyle : undefined }))
+  );
+  return { language, theme, format, lines };
+}
@@ -1,45 +1,25 @@
 import { handleWebSocketUpgrade, websocketHandlers } from './websocket';
-import { config, getAllowedOrigins, getOAuthClientOrigins } from './config';
-import { createMiddleware } from 'hono/factory';
-import { mountRoutes } from './routes';
-import { initAuth } from './auth';
-import { cors } from 'hono/cors';
-import { Hono } from 'hono';
-
-const app = new Hono();
-
-const loggingMiddleware = createMiddleware(async (c, next) => {
-  await next();
-});
-
-app.use('*', loggingMiddleware);
-
-app.use(
-  '*',
-  cors({
-    origin: (origin, c) => {
-      if (!origin) return null;
-      const allowed = c.req.path.startsWith('/api/auth')
-        ? getOAuthClientOrigins()
-        : getAllowedOrigins();
-      return allowed.includes(origin) ? origin : null;
-    },
-    credentials: true,
-    allowMethods: ['GET', 'POST', 'PUT', 'PATCH', 'DELETE', 'OPTIONS'],
-    allowHeaders: ['Content-Type', 'Authorization', 'Cookie', 'x-internal-auth'],
-    exposeHeaders: ['Set-Cookie'],
-  }),
-);
-
-app.use(
-  '*',
-  createMiddleware(async (c, next) => {
-    await initAuth();
-    await next();
-  }),
-);
-
-mountRoutes(app);
+import { config, assertValidConfig } from './config';
+import { startReviewReminderJob } from './jobs/review-reminders';
+import { startAccountExportCleanupJob } from './jobs/account-export';
+import { startMirrorSyncJob } from './jobs/mirror-sync';
+import { startAttachmentCleanupJob } from './jobs/attachment-cleanup';
+import { startCommitCountJob } from './jobs/commit-counts';
+import { startSharedObjectMigrationJob } from './jobs/shared-objects';
+import { startBranchMetadataJob } from './jobs/branch-metadata';
+import { createApp } from './app';
+
+assertValidConfig();
+
+const app = createApp();
+
+startReviewReminderJob();
+startAccountExportCleanupJob();
+startMirrorSyncJob();
+startAttachmentCleanupJob();
+startCommitCountJob();
+startSharedObjectMigrationJob();
+startBranchMetadataJob();
 
 const port = config.port;
 
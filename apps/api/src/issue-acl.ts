@@ -0,0 +1,73 @@
+import { db, issues, repositories } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+
+export type IssueRole = "owner" | "author" | "reader";
+
+export type IssueAction = "read" | "comment" | "react" | "edit" | "close" | "triage" | "lock" | "delete";
+
+const ISSUE_PERMISSIONS: Record<IssueAction, IssueRole[]> = {
+  read: ["owner", "author", "reader"],
+  comment: ["owner", "author", "reader"],
+  react: ["owner", "author", "reader"],
+  edit: ["owner", "author"],
+  close: ["owner", "author"],
+  triage: ["owner", "author"],
+  lock: ["owner"],
+  delete: ["owner"],
+};
+
+// Locking only freezes the conversation; the owner can still comment and react.
+const LOCKED_ACTIONS: IssueAction[] = ["comment", "react"];
+
+export type IssueAccess = {
+  issue: typeof issues.$inferSelect;
+  repo: { id: string; ownerId: string; visibility: "public" | "private" };
+  role: IssueRole;
+  can: (action: IssueAction) => boolean;
+  denial: (action: IssueAction) => string;
+};
+
+// Returns null when the issue doesn't exist or its repository is hidden from the user, so callers
+// can answer 404 either way and private issues can't be probed by id.
+export async function getIssueAccess(issueId: string, userId?: string): Promise<IssueAccess | null> {
+  const [row] = await db
+    .select({ issue: issues, repoId: repositories.id, ownerId: repositories.ownerId, visibility: repositories.visibility })
+    .from(issues)
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .where(eq(issues.id, issueId))
+    .limit(1);
+
+  if (!row) return null;
+  if (row.visibility === "private" && userId !== row.ownerId) return null;
+
+  const role: IssueRole = userId === row.ownerId ? "owner" : userId === row.issue.authorId ? "author" : "reader";
+
+  const can = (action: IssueAction) => {
+    if (!ISSUE_PERMISSIONS[action].includes(role)) return false;
+    if (row.issue.locked && LOCKED_ACTIONS.includes(action) && role !== "owner") return false;
+    return true;
+  };
+
+  const denial = (action: IssueAction) => {
+    if (row.issue.locked && LOCKED_ACTIONS.includes(action)) return "Issue is locked";
+    if (action === "lock") return "Only repo owner can lock issues";
+    if (action === "delete") return "Only repo owner can delete issues";
+    return "Not authorized";
+  };
+
+  return {
+    issue: row.issue,
+    repo: { id: row.repoId, ownerId: row.ownerId, visibility: row.visibility },
+    role,
+    can,
+    denial,
+  };
+}
+
+export function canEditIssueComment(comment: { authorId: string }, userId: string) {
+  return comment.authorId === userId;
+}
+
+export function canDeleteIssueComment(access: IssueAccess, comment: { authorId: string }, userId: string) {
+  return comment.authorId === userId || access.role === "owner";
+}
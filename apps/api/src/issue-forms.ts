@@ -0,0 +1,261 @@
+import { getTreeCached, getFileCached, type GitStore } from "./git";
+
+const ISSUE_TEMPLATE_DIR = ".gitbruv/ISSUE_TEMPLATE";
+
+export type IssueFormFieldType = "markdown" | "input" | "textarea" | "dropdown" | "checkboxes";
+
+export interface IssueFormField {
+  type: IssueFormFieldType;
+  id?: string;
+  attributes: {
+    label?: string;
+    description?: string;
+    placeholder?: string;
+    value?: string;
+    render?: string;
+    multiple?: boolean;
+    options?: (string | { label: string; required?: boolean })[];
+  };
+  validations?: {
+    required?: boolean;
+  };
+}
+
+export interface IssueForm {
+  id: string;
+  name: string;
+  description: string;
+  title?: string;
+  labels: string[];
+  assignees: string[];
+  body: IssueFormField[];
+}
+
+export type IssueFormValues = Record<string, string | string[] | boolean[]>;
+
+export interface IssueTemplate {
+  id: string;
+  name: string;
+  about: string;
+  title?: string;
+  labels: string[];
+  assignees: string[];
+  body: string;
+}
+
+const FIELD_TYPES: IssueFormFieldType[] = ["markdown", "input", "textarea", "dropdown", "checkboxes"];
+
+function toStringList(value: unknown): string[] {
+  if (Array.isArray(value)) return value.filter((v) => typeof v === "string");
+  if (typeof value === "string") {
+    return value
+      .split(",")
+      .map((v) => v.trim())
+      .filter(Boolean);
+  }
+  return [];
+}
+
+function isOption(option: unknown): option is string | { label: string } {
+  return typeof option === "string" || (!!option && typeof option === "object" && typeof (option as { label?: unknown }).label === "string");
+}
+
+export function parseIssueForm(filename: string, source: string): IssueForm | null {
+  let doc: any;
+  try {
+    doc = Bun.YAML.parse(source);
+  } catch {
+    return null;
+  }
+
+  if (!doc || typeof doc !== "object" || typeof doc.name !== "string" || !Array.isArray(doc.body)) {
+    return null;
+  }
+
+  const fields: IssueFormField[] = [];
+  const seenIds = new Set<string>();
+
+  for (const raw of doc.body) {
+    if (!raw || typeof raw !== "object" || !FIELD_TYPES.includes(raw.type)) {
+      return null;
+    }
+
+    const attributes = raw.attributes && typeof raw.attributes === "object" ? raw.attributes : {};
+    if (raw.type !== "markdown" && typeof attributes.label !== "string") {
+      return null;
+    }
+    if ((raw.type === "dropdown" || raw.type === "checkboxes") && !(Array.isArray(attributes.options) && attributes.options.every(isOption))) {
+      return null;
+    }
+
+    const id = typeof raw.id === "string" ? raw.id : undefined;
+    if (id) {
+      if (seenIds.has(id)) return null;
+      seenIds.add(id);
+    }
+
+    fields.push({
+      type: raw.type,
+      id,
+      attributes,
+      validations: raw.validations && typeof raw.validations === "object" ? raw.validations : undefined,
+    });
+  }
+
+  return {
+    id: filename.replace(/\.ya?ml$/i, ""),
+    name: doc.name,
+    description: typeof doc.description === "string" ? doc.description : "",
+    title: typeof doc.title === "string" ? doc.title : undefined,
+    labels: toStringList(doc.labels),
+    assignees: toStringList(doc.assignees),
+    body: fields,
+  };
+}
+
+export async function loadIssueForms(store: GitStore, branch: string): Promise<IssueForm[]> {
+  const entries = await getTreeCached(store, branch, ISSUE_TEMPLATE_DIR);
+  if (!entries) return [];
+
+  const files = entries.filter((e) => e.type === "blob" && /\.ya?ml$/i.test(e.name) && e.name.toLowerCase() !== "config.yml");
+
+  const forms = await Promise.all(
+    files.map(async (entry) => {
+      const file = await getFileCached(store, branch, entry.path);
+      return file ? parseIssueForm(entry.name, file.content) : null;
+    })
+  );
+
+  return forms.filter((f): f is IssueForm => f !== null).sort((a, b) => a.name.localeCompare(b.name));
+}
+
+export function parseIssueTemplate(filename: string, source: string): IssueTemplate | null {
+  const match = source.replace(/\r\n/g, "\n").match(/^---\n([\s\S]*?)\n---\n?([\s\S]*)$/);
+  if (!match) return null;
+
+  let meta: any;
+  try {
+    meta = Bun.YAML.parse(match[1]);
+  } catch {
+    return null;
+  }
+
+  if (!meta || typeof meta !== "object" || typeof meta.name !== "string") {
+    return null;
+  }
+
+  return {
+    id: filename.replace(/\.md$/i, ""),
+    name: meta.name,
+    about: typeof meta.about === "string" ? meta.about : "",
+    title: typeof meta.title === "string" ? meta.title : undefined,
+    labels: toStringList(meta.labels),
+    assignees: toStringList(meta.assignees),
+    body: match[2].trim(),
+  };
+}
+
+export async function loadIssueTemplates(store: GitStore, branch: string): Promise<IssueTemplate[]> {
+  const entries = await getTreeCached(store, branch, ISSUE_TEMPLATE_DIR);
+  if (!entries) return [];
+
+  const files = entries.filter((e) => e.type === "blob" && /\.md$/i.test(e.name));
+
+  const templates = await Promise.all(
+    files.map(async (entry) => {
+      const file = await getFileCached(store, branch, entry.path);
+      return file ? parseIssueTemplate(entry.name, file.content) : null;
+    })
+  );
+
+  return templates.filter((t): t is IssueTemplate => t !== null).sort((a, b) => a.name.localeCompare(b.name));
+}
+
+function fieldKey(field: IssueFormField, index: number) {
+  return field.id || `field-${index}`;
+}
+
+function optionLabel(option: string | { label: string }) {
+  return typeof option === "string" ? option : option.label;
+}
+
+// `values` comes straight from the request body, so every value's shape is checked here rather
+// than trusted from the type.
+export function validateIssueFormValues(form: IssueForm, values: IssueFormValues): string[] {
+  if (!values || typeof values !== "object" || Array.isArray(values)) {
+    return ["Form fields must be an object"];
+  }
+  const errors: string[] = [];
+
+  form.body.forEach((field, index) => {
+    if (field.type === "markdown") return;
+
+    const label = field.attributes.label!;
+    const value = values[fieldKey(field, index)];
+    const required = field.validations?.required === true;
+
+    if (field.type === "input" || field.type === "textarea") {
+      if (value !== undefined && typeof value !== "string") {
+        errors.push(`${label} must be text`);
+      } else if (required && !value?.trim()) {
+        errors.push(`${label} is required`);
+      }
+      return;
+    }
+
+    if (field.type === "dropdown") {
+      const options = (field.attributes.options || []).map(optionLabel);
+      const selected: unknown = value === undefined ? [] : typeof value === "string" ? [value] : value;
+      if (!Array.isArray(selected) || !selected.every((v) => typeof v === "string" && options.includes(v))) {
+        errors.push(`${label} has an invalid selection`);
+      } else if (!field.attributes.multiple && selected.length > 1) {
+        errors.push(`${label} accepts a single selection`);
+      } else if (required && selected.length === 0) {
+        errors.push(`${label} is required`);
+      }
+      return;
+    }
+
+    const options = field.attributes.options || [];
+    const checked = value === undefined ? [] : value;
+    if (!Array.isArray(checked) || checked.length > options.length || !checked.every((v) => typeof v === "boolean")) {
+      errors.push(`${label} has invalid checkbox values`);
+      return;
+    }
+    options.forEach((option, i) => {
+      if (typeof option !== "string" && option.required && !checked[i]) {
+        errors.push(`${label}: "${option.label}" must be checked`);
+      }
+    });
+  });
+
+  return errors;
+}
+
+export function renderIssueFormBody(form: IssueForm, values: IssueFormValues): string {
+  const sections: string[] = [];
+
+  form.body.forEach((field, index) => {
+    if (field.type === "markdown") return;
+
+    const value = values[fieldKey(field, index)];
+    let rendered: string;
+
+    if (field.type === "checkboxes") {
+      const checked = (Array.isArray(value) ? value : []) as boolean[];
+      rendered = (field.attributes.options || [])
+        .map((option, i) => `- [${checked[i] ? "x" : " "}] ${optionLabel(option)}`)
+        .join("\n");
+    } else if (field.type === "dropdown") {
+      const selected = value === undefined ? [] : typeof value === "string" ? [value] : (value as string[]);
+      rendered = selected.join(", ");
+    } else {
+      const text = typeof value === "string" ? value.trim() : "";
+      rendered = text && field.attributes.render ? "```" + field.attributes.render + "\n" + text + "\n```" : text;
+    }
+
+    sections.push(`### ${field.attributes.label}\n\n${rendered || "_No response_"}`);
+  });
+
+  return sections.join("\n\n");
+}
@@ -0,0 +1,125 @@
+import { db, issues, issueReferences, issueMentions, repositories, users } from "@gitbruv/db";
+import { eq, and, inArray, sql } from "drizzle-orm";
+import git from "isomorphic-git";
+import type { GitStore } from "./git";
+import { createNotification } from "./routes/notifications";
+
+const MAX_PUSH_COMMITS = 100;
+
+export type ReferenceSource =
+  | { type: "issue"; issueId: string }
+  | { type: "pull_request"; pullRequestId: string }
+  | { type: "commit"; oid: string };
+
+export function extractIssueNumbers(text: string | null | undefined): number[] {
+  if (!text) return [];
+  const numbers = new Set<number>();
+  for (const match of text.matchAll(/(?:^|[^\w/&#])#(\d+)\b/g)) {
+    numbers.add(parseInt(match[1], 10));
+  }
+  return [...numbers];
+}
+
+export function extractMentions(text: string | null | undefined): string[] {
+  if (!text) return [];
+  const usernames = new Set<string>();
+  for (const match of text.matchAll(/(?:^|[^\w@`/])@([a-zA-Z0-9_][a-zA-Z0-9_-]{2,38})\b/g)) {
+    usernames.add(match[1].toLowerCase());
+  }
+  return [...usernames];
+}
+
+export type MentionSource = {
+  issueId: string;
+  issueNumber: number;
+  issueTitle: string;
+  repositoryId: string;
+  commentId?: string;
+};
+
+// Stores each user mentioned on an issue once and notifies them the first time. Users who
+// can't see the repository are ignored, so private repositories only ever mention the owner.
+export async function recordIssueMentions(source: MentionSource, text: string | null | undefined, actorId: string) {
+  const usernames = extractMentions(text);
+  if (usernames.length === 0) return;
+
+  const [repo] = await db
+    .select({ name: repositories.name, ownerId: repositories.ownerId, visibility: repositories.visibility, owner: users.username })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(eq(repositories.id, source.repositoryId))
+    .limit(1);
+  if (!repo) return;
+
+  const mentioned = await db
+    .select({ id: users.id })
+    .from(users)
+    .where(inArray(sql`lower(${users.username})`, usernames));
+
+  const rows = mentioned
+    .filter((user) => user.id !== actorId && (repo.visibility !== "private" || user.id === repo.ownerId))
+    .map((user) => ({ issueId: source.issueId, commentId: source.commentId ?? null, userId: user.id, actorId }));
+  if (rows.length === 0) return;
+
+  const inserted = await db.insert(issueMentions).values(rows).onConflictDoNothing().returning({ userId: issueMentions.userId });
+
+  for (const { userId } of inserted) {
+    await createNotification({
+      userId,
+      type: "mention",
+      title: `You were mentioned in ${repo.owner}/${repo.name}#${source.issueNumber}`,
+      body: source.issueTitle,
+      resourceType: "issue",
+      resourceId: source.issueId,
+      actorId,
+      repoOwner: repo.owner,
+      repoName: repo.name,
+      resourceNumber: source.issueNumber,
+    });
+  }
+}
+
+export async function recordIssueReferences(repositoryId: string, source: ReferenceSource, text: string | null | undefined, actorId?: string) {
+  const numbers = extractIssueNumbers(text);
+  if (numbers.length === 0) return;
+
+  const targets = await db
+    .select({ id: issues.id })
+    .from(issues)
+    .where(and(eq(issues.repositoryId, repositoryId), inArray(issues.number, numbers)));
+
+  const rows = targets
+    .filter((target) => !(source.type === "issue" && target.id === source.issueId))
+    .map((target) => ({
+      targetIssueId: target.id,
+      sourceType: source.type,
+      sourceRepositoryId: repositoryId,
+      sourceIssueId: source.type === "issue" ? source.issueId : null,
+      sourcePullRequestId: source.type === "pull_request" ? source.pullRequestId : null,
+      sourceCommitOid: source.type === "commit" ? source.oid : null,
+      actorId: actorId ?? null,
+    }));
+
+  if (rows.length === 0) return;
+
+  await db.insert(issueReferences).values(rows).onConflictDoNothing();
+}
+
+export async function recordPushedCommitReferences(repositoryId: string, store: GitStore, oldOid: string, newOid: string, actorId?: string) {
+  const queue = [newOid];
+  const visited = new Set<string>([oldOid]);
+
+  while (queue.length > 0 && visited.size <= MAX_PUSH_COMMITS) {
+    const oid = queue.shift()!;
+    if (visited.has(oid)) continue;
+    visited.add(oid);
+
+    try {
+      const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid });
+      await recordIssueReferences(repositoryId, { type: "commit", oid }, commit.message, actorId);
+      queue.push(...commit.parent);
+    } catch {
+      continue;
+    }
+  }
+}
@@ -0,0 +1,362 @@
+import { db, users, issues, labels, issueLabels, issueAssignees, issueComments, issueCounters } from "@gitbruv/db";
+import { eq, asc, inArray, sql } from "drizzle-orm";
+
+export const MAX_IMPORT_ISSUES = 1000;
+// issues.number is an int4. Imported numbers stay far enough below its maximum that the
+// unnumbered records of the same import, and the next issue created, still get a number.
+const MAX_IMPORTED_NUMBER = 2_147_483_647 - MAX_IMPORT_ISSUES - 1;
+
+export type IssueExportComment = {
+  author: string | null;
+  body: string | null;
+  hidden: { reason: string; hiddenAt: string | null } | null;
+  createdAt: string;
+  updatedAt: string;
+};
+
+export type IssueExportRecord = {
+  number: number;
+  title: string;
+  body: string | null;
+  state: "open" | "closed";
+  locked: boolean;
+  author: string | null;
+  labels: string[];
+  assignees: string[];
+  createdAt: string;
+  updatedAt: string;
+  closedAt: string | null;
+  comments: IssueExportComment[];
+};
+
+// Hidden comments are exported the way the comments endpoint shows them: only the repository
+// owner, who moderates the thread, gets their text back.
+export async function buildIssueExport(repositoryId: string, options: { includeHidden: boolean }): Promise<IssueExportRecord[]> {
+  const rows = await db
+    .select({ issue: issues, author: users.username })
+    .from(issues)
+    .leftJoin(users, eq(users.id, issues.authorId))
+    .where(eq(issues.repositoryId, repositoryId))
+    .orderBy(asc(issues.number));
+  if (rows.length === 0) return [];
+
+  const issueIds = rows.map((row) => row.issue.id);
+  const [labelRows, assigneeRows, commentRows] = await Promise.all([
+    db
+      .select({ issueId: issueLabels.issueId, name: labels.name })
+      .from(issueLabels)
+      .innerJoin(labels, eq(labels.id, issueLabels.labelId))
+      .where(inArray(issueLabels.issueId, issueIds))
+      .orderBy(asc(labels.name)),
+    db
+      .select({ issueId: issueAssignees.issueId, username: users.username })
+      .from(issueAssignees)
+      .innerJoin(users, eq(users.id, issueAssignees.userId))
+      .where(inArray(issueAssignees.issueId, issueIds)),
+    db
+      .select({ comment: issueComments, author: users.username })
+      .from(issueComments)
+      .leftJoin(users, eq(users.id, issueComments.authorId))
+      .where(inArray(issueComments.issueId, issueIds))
+      .orderBy(asc(issueComments.createdAt)),
+  ]);
+
+  const group = <T>(list: T[], key: (item: T) => string) => {
+    const map = new Map<string, T[]>();
+    for (const item of list) map.set(key(item), [...(map.get(key(item)) ?? []), item]);
+    return map;
+  };
+  const labelsByIssue = group(labelRows, (row) => row.issueId);
+  const assigneesByIssue = group(assigneeRows, (row) => row.issueId);
+  const commentsByIssue = group(commentRows, (row) => row.comment.issueId);
+
+  return rows.map(({ issue, author }) => ({
+    number: issue.number,
+    title: issue.title,
+    body: issue.body,
+    state: issue.state,
+    locked: issue.locked,
+    author,
+    labels: (labelsByIssue.get(issue.id) ?? []).map((row) => row.name),
+    assignees: (assigneesByIssue.get(issue.id) ?? []).map((row) => row.username),
+    createdAt: issue.createdAt.toISOString(),
+    updatedAt: issue.updatedAt.toISOString(),
+    closedAt: issue.closedAt?.toISOString() ?? null,
+    comments: (commentsByIssue.get(issue.id) ?? []).map((row) => ({
+      author: row.author,
+      body: row.comment.hiddenReason !== null && !options.includeHidden ? null : row.comment.body,
+      hidden: row.comment.hiddenReason ? { reason: row.comment.hiddenReason, hiddenAt: row.comment.hiddenAt?.toISOString() ?? null } : null,
+      createdAt: row.comment.createdAt.toISOString(),
+      updatedAt: row.comment.updatedAt.toISOString(),
+    })),
+  }));
+}
+
+// Spreadsheets evaluate cells that start with these as formulas, so such text is prefixed with a
+// quote to keep an exported title or body from running as one when the CSV is opened.
+const FORMULA_PREFIX = /^[=+\-@\t\r]/;
+
+function csvField(value: string | number | boolean | null): string {
+  let text = value === null ? "" : String(value);
+  if (typeof value === "string" && FORMULA_PREFIX.test(text)) text = `'${text}`;
+  return /[",\r\n]/.test(text) ? `"${text.replace(/"/g, '""')}"` : text;
+}
+
+// CSV is a flat, spreadsheet-friendly view: comments are reduced to a count and list columns are
+// joined with semicolons. The JSON export is the one that round-trips through import.
+export function issuesToCsv(records: IssueExportRecord[]): string {
+  const header = ["number", "title", "state", "locked", "author", "labels", "assignees", "created_at", "updated_at", "closed_at", "comments", "body"];
+  const lines = records.map((record) =>
+    [
+      record.number,
+      record.title,
+      record.state,
+      record.locked,
+      record.author,
+      record.labels.join("; "),
+      record.assignees.join("; "),
+      record.createdAt,
+      record.updatedAt,
+      record.closedAt,
+      record.comments.length,
+      record.body,
+    ]
+      .map(csvField)
+      .join(",")
+  );
+  return [header.join(","), ...lines].join("\r\n") + "\r\n";
+}
+
+type ImportComment = { author: string | null; body: string; createdAt: Date | null; updatedAt: Date | null };
+
+type ImportIssue = {
+  number: number | null;
+  title: string;
+  body: string | null;
+  state: "open" | "closed";
+  locked: boolean;
+  author: string | null;
+  labels: string[];
+  assignees: string[];
+  createdAt: Date | null;
+  updatedAt: Date | null;
+  closedAt: Date | null;
+  comments: ImportComment[];
+};
+
+function asDate(value: unknown): Date | null {
+  if (typeof value !== "string") return null;
+  const date = new Date(value);
+  return Number.isNaN(date.getTime()) ? null : date;
+}
+
+function asString(value: unknown): string | null {
+  return typeof value === "string" ? value : null;
+}
+
+// GitHub nests people and labels as objects ({ login }, { name }); our export uses plain strings.
+function names(value: unknown, key: "login" | "name"): string[] {
+  if (!Array.isArray(value)) return [];
+  return value
+    .map((item) => (typeof item === "string" ? item : asString((item as Record<string, unknown> | null)?.[key])))
+    .filter((item): item is string => !!item);
+}
+
+function login(value: unknown): string | null {
+  if (typeof value === "string") return value;
+  return asString((value as Record<string, unknown> | null)?.login);
+}
+
+function parseComment(raw: unknown): ImportComment | null {
+  if (!raw || typeof raw !== "object") return null;
+  const item = raw as Record<string, unknown>;
+  const body = asString(item.body);
+  if (body === null) return null;
+  return {
+    author: login(item.author ?? item.user),
+    body,
+    createdAt: asDate(item.createdAt ?? item.created_at),
+    updatedAt: asDate(item.updatedAt ?? item.updated_at),
+  };
+}
+
+function parseIssue(raw: unknown, index: number): ImportIssue | string {
+  if (!raw || typeof raw !== "object") return `Issue ${index + 1} is not an object`;
+  const item = raw as Record<string, unknown>;
+
+  const title = asString(item.title)?.trim();
+  if (!title) return `Issue ${index + 1} is missing a title`;
+
+  const number = item.number;
+  if (typeof number === "number" && Number.isInteger(number) && number > MAX_IMPORTED_NUMBER) {
+    return `Issue ${index + 1} ("${title}") has number ${number}, above the maximum of ${MAX_IMPORTED_NUMBER}`;
+  }
+  const state = asString(item.state)?.toLowerCase();
+
+  return {
+    number: typeof number === "number" && Number.isInteger(number) && number > 0 ? number : null,
+    title,
+    body: asString(item.body),
+    state: state === "closed" ? "closed" : "open",
+    locked: item.locked === true,
+    author: login(item.author ?? item.user),
+    labels: names(item.labels, "name"),
+    assignees: names(item.assignees, "login"),
+    createdAt: asDate(item.createdAt ?? item.created_at),
+    updatedAt: asDate(item.updatedAt ?? item.updated_at),
+    closedAt: asDate(item.closedAt ?? item.closed_at),
+    comments: Array.isArray(item.comments)
+      ? item.comments.map(parseComment).filter((comment): comment is ImportComment => !!comment)
+      : [],
+  };
+}
+
+// Accepts our own export ({ issues: [...] } or a bare array) and GitHub's REST issue objects.
+// GitHub pull requests show up in its issue listings and are skipped.
+export function parseIssueImport(payload: unknown): { issues: ImportIssue[] } | { error: string } {
+  const list = Array.isArray(payload) ? payload : (payload as { issues?: unknown } | null)?.issues;
+  if (!Array.isArray(list)) {
+    return { error: "Expected an array of issues or an object with an issues array" };
+  }
+
+  const candidates = list.filter((item) => !(item && typeof item === "object" && "pull_request" in item));
+  if (candidates.length > MAX_IMPORT_ISSUES) {
+    return { error: `At most ${MAX_IMPORT_ISSUES} issues can be imported at once` };
+  }
+
+  const parsed: ImportIssue[] = [];
+  for (const [index, raw] of candidates.entries()) {
+    const issue = parseIssue(raw, index);
+    if (typeof issue === "string") return { error: issue };
+    parsed.push(issue);
+  }
+  return { issues: parsed };
+}
+
+function highestOf(numbers: Iterable<number>): number {
+  let highest = 0;
+  for (const number of numbers) highest = Math.max(highest, number);
+  return highest;
+}
+
+const MAX_IMPORTED_ASSIGNEES = 10;
+
+function attribution(author: string | null, body: string | null): string | null {
+  if (!author) return body;
+  return `_Originally posted by @${author}_\n\n${body ?? ""}`;
+}
+
+// Numbers are kept when they are still free in the target repository; anything that collides is
+// appended after the highest number instead. The payload is only the importer's word for who wrote
+// what, so every issue and comment is authored by the importer with an attribution line naming the
+// original author. Assignees follow the same rules as assigning by hand: existing users on a public
+// repository, only the owner on a private one. Mentions/references are not re-processed so an
+// import doesn't notify anyone.
+export async function importIssues(
+  repository: { id: string; ownerId: string; visibility: "public" | "private" },
+  importerId: string,
+  records: ImportIssue[]
+) {
+  const repositoryId = repository.id;
+  const usernames = [...new Set(records.flatMap((record) => record.assignees))];
+  const userRows = usernames.length
+    ? await db.select({ id: users.id, username: users.username }).from(users).where(inArray(users.username, usernames))
+    : [];
+  const assignable = userRows.filter((row) => repository.visibility === "public" || row.id === repository.ownerId);
+  const userIds = new Map(assignable.map((row) => [row.username.toLowerCase(), row.id]));
+  const resolve = (username: string) => userIds.get(username.toLowerCase()) ?? null;
+
+  return db.transaction(async (tx) => {
+    // Serializes with issue creation, which allocates numbers through the same counter row.
+    await tx
+      .insert(issueCounters)
+      .values({ repositoryId, lastNumber: 0 })
+      .onConflictDoNothing();
+    await tx.execute(sql`SELECT 1 FROM ${issueCounters} WHERE ${issueCounters.repositoryId} = ${repositoryId} FOR UPDATE`);
+
+    const existing = await tx.select({ number: issues.number }).from(issues).where(eq(issues.repositoryId, repositoryId));
+    const taken = new Set(existing.map((row) => row.number));
+    let next = records.reduce((max, record) => Math.max(max, record.number ?? 0), highestOf(taken)) + 1;
+
+    const labelNames = [...new Set(records.flatMap((record) => record.labels))];
+    const labelIds = new Map<string, string>();
+    if (labelNames.length > 0) {
+      const existingLabels = await tx
+        .select({ id: labels.id, name: labels.name })
+        .from(labels)
+        .where(eq(labels.repositoryId, repositoryId));
+      for (const label of existingLabels) labelIds.set(label.name.toLowerCase(), label.id);
+
+      const missing = labelNames.filter((label) => !labelIds.has(label.toLowerCase()));
+      const uniqueMissing = [...new Map(missing.map((label) => [label.toLowerCase(), label])).values()];
+      if (uniqueMissing.length > 0) {
+        const created = await tx
+          .insert(labels)
+          .values(uniqueMissing.map((label) => ({ repositoryId, name: label })))
+          .returning({ id: labels.id, name: labels.name });
+        for (const label of created) labelIds.set(label.name.toLowerCase(), label.id);
+      }
+    }
+
+    const renumbered: { from: number; to: number }[] = [];
+    for (const record of records) {
+      let number: number;
+      if (record.number !== null && !taken.has(record.number)) {
+        number = record.number;
+      } else {
+        number = next++;
+        if (record.number !== null) renumbered.push({ from: record.number, to: number });
+      }
+      taken.add(number);
+
+      const createdAt = record.createdAt ?? new Date();
+      const [issue] = await tx
+        .insert(issues)
+        .values({
+          repositoryId,
+          number,
+          authorId: importerId,
+          title: record.title,
+          body: attribution(record.author, record.body),
+          state: record.state,
+          locked: record.locked,
+          closedAt: record.state === "closed" ? (record.closedAt ?? record.updatedAt ?? createdAt) : null,
+          createdAt,
+          updatedAt: record.updatedAt ?? createdAt,
+        })
+        .returning({ id: issues.id });
+
+      const issueLabelIds = [...new Set(record.labels.map((label) => labelIds.get(label.toLowerCase())!))];
+      if (issueLabelIds.length > 0) {
+        await tx.insert(issueLabels).values(issueLabelIds.map((labelId) => ({ issueId: issue.id, labelId })));
+      }
+
+      const assigneeIds = [...new Set(record.assignees.map(resolve).filter((id): id is string => !!id))].slice(0, MAX_IMPORTED_ASSIGNEES);
+      if (assigneeIds.length > 0) {
+        await tx.insert(issueAssignees).values(assigneeIds.map((userId) => ({ issueId: issue.id, userId })));
+      }
+
+      if (record.comments.length > 0) {
+        await tx.insert(issueComments).values(
+          record.comments.map((comment) => {
+            const commentCreatedAt = comment.createdAt ?? createdAt;
+            return {
+              issueId: issue.id,
+              authorId: importerId,
+              body: attribution(comment.author, comment.body)!,
+              createdAt: commentCreatedAt,
+              updatedAt: comment.updatedAt ?? commentCreatedAt,
+            };
+          })
+        );
+      }
+    }
+
+    await tx
+      .update(issueCounters)
+      .set({ lastNumber: sql`GREATEST(${issueCounters.lastNumber}, ${highestOf(taken)})` })
+      .where(eq(issueCounters.repositoryId, repositoryId));
+
+    return { imported: records.length, renumbered };
+  });
+}
@@ -0,0 +1,279 @@
+import { db, users, repositories, issues, issueComments, accountExports } from "@gitbruv/db";
+import { eq, and, lt, inArray, isNotNull } from "drizzle-orm";
+import { createHmac, timingSafeEqual } from "crypto";
+import * as zlib from "zlib";
+import { Readable } from "stream";
+import { config, getApiUrl } from "../config";
+import { uploadMultipart, headObject, deleteObject } from "../s3";
+import { createGitStore } from "../git";
+import { buildBundle } from "../git/upload-pack";
+import { createNotification } from "../routes/notifications";
+import { trackJob } from "../metrics";
+import { storageLayout } from "../storage-layout";
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+const EXPORT_RETENTION_MS = 7 * DAY_MS;
+const DOWNLOAD_LINK_TTL_MS = 60 * 60 * 1000;
+// Exports run in-process, so anything still pending after this long was lost to a restart.
+const STALE_EXPORT_MS = 6 * 60 * 60 * 1000;
+const RUN_INTERVAL_MS = 60 * 60 * 1000;
+
+type ArchiveEntry = { name: string; data: Buffer };
+
+function tarHeader(name: string, size: number, type = "0"): Buffer {
+  const header = Buffer.alloc(512);
+  Buffer.from(name, "utf8").copy(header, 0, 0, 100);
+  header.write("0000644\0", 100, 8, "ascii");
+  header.write("0000000\0", 108, 8, "ascii");
+  header.write("0000000\0", 116, 8, "ascii");
+  header.write(size.toString(8).padStart(11, "0") + "\0", 124, 12, "ascii");
+  header.write(Math.floor(Date.now() / 1000).toString(8).padStart(11, "0") + "\0", 136, 12, "ascii");
+  header.write("        ", 148, 8, "ascii");
+  header.write(type, 156, 1, "ascii");
+  header.write("ustar\0", 257, 6, "ascii");
+  header.write("00", 263, 2, "ascii");
+
+  let checksum = 0;
+  for (const byte of header) checksum += byte;
+  header.write(checksum.toString(8).padStart(6, "0") + "\0 ", 148, 8, "ascii");
+  return header;
+}
+
+function tarPadding(size: number): Buffer {
+  return Buffer.alloc((512 - (size % 512)) % 512);
+}
+
+// Names that don't fit the 100-byte ustar field get a PAX extended header carrying the full path.
+// The record's length prefix counts its own digits, so it's recomputed until it settles.
+function paxHeader(name: string): Buffer[] {
+  const record = (length: number) => `${length} path=${name}\n`;
+  let length = Buffer.byteLength(record(0));
+  while (Buffer.byteLength(record(length)) !== length) length = Buffer.byteLength(record(length));
+  const data = Buffer.from(record(length), "utf8");
+  return [tarHeader("././@PaxHeader", data.length, "x"), data, tarPadding(data.length)];
+}
+
+function* tarEntry(entry: ArchiveEntry): Generator<Buffer> {
+  if (Buffer.byteLength(entry.name) > 100) yield* paxHeader(entry.name);
+  yield tarHeader(entry.name, entry.data.length);
+  yield entry.data;
+  yield tarPadding(entry.data.length);
+}
+
+// Streams the archive through gzip as entries are produced, so only the entry being written
+// (at most one repository bundle) is held in memory.
+export function createTarGz(entries: AsyncIterable<ArchiveEntry>): ReadableStream {
+  const tar = Readable.from(
+    (async function* () {
+      for await (const entry of entries) yield* tarEntry(entry);
+      yield Buffer.alloc(1024);
+    })()
+  );
+  const gzip = zlib.createGzip();
+  tar.on("error", (error) => gzip.destroy(error));
+  return Readable.toWeb(tar.pipe(gzip)) as ReadableStream;
+}
+
+function json(value: unknown): Buffer {
+  return Buffer.from(JSON.stringify(value, null, 2) + "\n", "utf8");
+}
+
+async function* collectEntries(userId: string, includeRepositories: boolean): AsyncGenerator<ArchiveEntry> {
+  const user = await db.query.users.findFirst({ where: eq(users.id, userId) });
+  if (!user) {
+    throw new Error("User not found");
+  }
+
+  const ownedRepos = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      description: repositories.description,
+      visibility: repositories.visibility,
+      defaultBranch: repositories.defaultBranch,
+      topics: repositories.topics,
+      createdAt: repositories.createdAt,
+      updatedAt: repositories.updatedAt,
+    })
+    .from(repositories)
+    .where(eq(repositories.ownerId, userId))
+    .orderBy(repositories.name);
+
+  const authoredIssues = await db
+    .select({
+      id: issues.id,
+      repository: repositories.name,
+      repositoryOwner: users.username,
+      number: issues.number,
+      title: issues.title,
+      body: issues.body,
+      state: issues.state,
+      createdAt: issues.createdAt,
+      closedAt: issues.closedAt,
+    })
+    .from(issues)
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(eq(issues.authorId, userId))
+    .orderBy(issues.createdAt);
+
+  const authoredComments = await db
+    .select({
+      id: issueComments.id,
+      repository: repositories.name,
+      repositoryOwner: users.username,
+      issueNumber: issues.number,
+      body: issueComments.body,
+      createdAt: issueComments.createdAt,
+      updatedAt: issueComments.updatedAt,
+    })
+    .from(issueComments)
+    .innerJoin(issues, eq(issues.id, issueComments.issueId))
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(eq(issueComments.authorId, userId))
+    .orderBy(issueComments.createdAt);
+
+  const root = `${user.username}-export`;
+  yield {
+    name: `${root}/profile.json`,
+    data: json({
+      id: user.id,
+      name: user.name,
+      username: user.username,
+      email: user.email,
+      gitEmail: user.gitEmail,
+      bio: user.bio,
+      location: user.location,
+      website: user.website,
+      pronouns: user.pronouns,
+      company: user.company,
+      socialLinks: user.socialLinks,
+      preferences: user.preferences,
+      createdAt: user.createdAt,
+    }),
+  };
+  yield { name: `${root}/repositories.json`, data: json(ownedRepos) };
+  yield { name: `${root}/issues.json`, data: json(authoredIssues) };
+  yield { name: `${root}/issue-comments.json`, data: json(authoredComments) };
+
+  if (includeRepositories) {
+    for (const repo of ownedRepos) {
+      const bundle = await buildBundle(createGitStore(userId, repo.name));
+      if (bundle) {
+        yield { name: `${root}/repositories/${repo.name}.bundle`, data: bundle };
+      }
+    }
+  }
+}
+
+export async function runAccountExport(exportId: string) {
+  const [job] = await db
+    .update(accountExports)
+    .set({ status: "running" })
+    .where(and(eq(accountExports.id, exportId), eq(accountExports.status, "pending")))
+    .returning();
+  if (!job) return;
+
+  try {
+    const storageKey = storageLayout.exportKey(job.userId, job.id);
+    await uploadMultipart(storageKey, createTarGz(collectEntries(job.userId, job.includeRepositories)), "application/gzip");
+    const archive = await headObject(storageKey);
+
+    await db
+      .update(accountExports)
+      .set({
+        status: "completed",
+        storageKey,
+        sizeBytes: archive?.size ?? null,
+        completedAt: new Date(),
+        expiresAt: new Date(Date.now() + EXPORT_RETENTION_MS),
+      })
+      .where(eq(accountExports.id, job.id));
+
+    await createNotification({
+      userId: job.userId,
+      type: "account_export",
+      title: "Your account export is ready",
+      body: "Download it from your account settings within the next 7 days.",
+      sendEmail: true,
+    });
+  } catch (error) {
+    console.error("[Jobs] Account export failed:", error);
+    await db
+      .update(accountExports)
+      .set({ status: "failed", error: error instanceof Error ? error.message : String(error), completedAt: new Date() })
+      .where(eq(accountExports.id, job.id));
+  }
+}
+
+function signDownload(exportId: string, expires: number) {
+  return createHmac("sha256", config.betterAuthSecret).update(`${exportId}.${expires}`).digest("hex");
+}
+
+export function createExportDownloadUrl(exportId: string, archiveExpiresAt: Date) {
+  const expires = Math.min(Date.now() + DOWNLOAD_LINK_TTL_MS, archiveExpiresAt.getTime());
+  return `${getApiUrl()}/api/settings/export/${exportId}/download?expires=${expires}&signature=${signDownload(exportId, expires)}`;
+}
+
+export function verifyExportDownload(exportId: string, expires: string | undefined, signature: string | undefined) {
+  const expiresAt = Number(expires);
+  if (!signature || !Number.isFinite(expiresAt) || expiresAt < Date.now()) {
+    return false;
+  }
+  const expected = Buffer.from(signDownload(exportId, expiresAt));
+  const provided = Buffer.from(signature);
+  return expected.length === provided.length && timingSafeEqual(expected, provided);
+}
+
+export async function cleanupAccountExports() {
+  const now = Date.now();
+
+  const expired = await db
+    .select({ id: accountExports.id, storageKey: accountExports.storageKey })
+    .from(accountExports)
+    .where(and(isNotNull(accountExports.expiresAt), lt(accountExports.expiresAt, new Date(now))));
+
+  for (const row of expired) {
+    if (row.storageKey) {
+      try {
+        await deleteObject(row.storageKey);
+      } catch {}
+    }
+  }
+  if (expired.length > 0) {
+    await db.delete(accountExports).where(inArray(accountExports.id, expired.map((row) => row.id)));
+  }
+
+  await db
+    .update(accountExports)
+    .set({ status: "failed", error: "Export was interrupted", completedAt: new Date(now) })
+    .where(
+      and(
+        inArray(accountExports.status, ["pending", "running"]),
+        lt(accountExports.createdAt, new Date(now - STALE_EXPORT_MS))
+      )
+    );
+
+  return expired.length;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startAccountExportCleanupJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      const removed = await trackJob("account_export_cleanup", cleanupAccountExports);
+      if (removed > 0) {
+        console.log(`[Jobs] Removed ${removed} expired account export(s)`);
+      }
+    } catch (error) {
+      console.error("[Jobs] Account export cleanup failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
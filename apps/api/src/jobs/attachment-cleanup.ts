@@ -0,0 +1,47 @@
+import { db, attachments } from "@gitbruv/db";
+import { eq, and, lt, inArray } from "drizzle-orm";
+import { deleteObject } from "../s3";
+import { PENDING_ATTACHMENT_TTL_MS } from "../attachments";
+import { trackJob } from "../metrics";
+
+const RUN_INTERVAL_MS = 60 * 60 * 1000;
+const CLEANUP_BATCH_SIZE = 500;
+
+export async function cleanupPendingAttachments() {
+  const abandoned = await db
+    .select({ id: attachments.id, storageKey: attachments.storageKey })
+    .from(attachments)
+    .where(and(eq(attachments.status, "pending"), lt(attachments.createdAt, new Date(Date.now() - PENDING_ATTACHMENT_TTL_MS))))
+    .limit(CLEANUP_BATCH_SIZE);
+
+  for (const row of abandoned) {
+    try {
+      await deleteObject(row.storageKey);
+    } catch {}
+  }
+  if (abandoned.length > 0) {
+    await db.delete(attachments).where(inArray(attachments.id, abandoned.map((row) => row.id)));
+  }
+
+  return abandoned.length;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startAttachmentCleanupJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      const removed = await trackJob("attachment_cleanup", cleanupPendingAttachments);
+      if (removed > 0) {
+        console.log(`[Jobs] Removed ${removed} unfinalized upload(s)`);
+      }
+    } catch (error) {
+      console.error("[Jobs] Attachment cleanup failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
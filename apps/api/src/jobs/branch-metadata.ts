@@ -0,0 +1,75 @@
+import { db, repoBranchMetadata, repositories } from "@gitbruv/db";
+import { eq, gt } from "drizzle-orm";
+import { createGitStore, listBranches } from "../git";
+import { repairBranchMetadata } from "../branch-metadata";
+import { trackJob } from "../metrics";
+
+const RUN_INTERVAL_MS = 15 * 60 * 1000;
+const REPOS_PER_RUN = 25;
+
+// Repositories are visited in id order, picking up where the previous run stopped and wrapping
+// around at the end, so every repository gets checked without any one run reading them all.
+let position: string | null = null;
+
+// Each repository's branches come from its refs as well as the table, so a branch whose row was
+// never written (or a repository with no rows at all) gets one built, and rows for branches that
+// are gone get removed.
+async function reconcileBranchMetadata() {
+  const repos = await db
+    .select({ id: repositories.id, ownerId: repositories.ownerId, name: repositories.name })
+    .from(repositories)
+    .where(position ? gt(repositories.id, position) : undefined)
+    .orderBy(repositories.id)
+    .limit(REPOS_PER_RUN);
+
+  position = repos.length === REPOS_PER_RUN ? repos[repos.length - 1].id : null;
+
+  const counts = { rebuilt: 0, removed: 0, failed: 0 };
+  for (const repo of repos) {
+    const store = createGitStore(repo.ownerId, repo.name);
+    let branches: string[];
+    try {
+      const stored = await db
+        .select({ branch: repoBranchMetadata.branch })
+        .from(repoBranchMetadata)
+        .where(eq(repoBranchMetadata.repoId, repo.id));
+      branches = [...new Set([...(await listBranches(store.fs, store.dir)), ...stored.map((row) => row.branch)])];
+    } catch (error) {
+      counts.failed++;
+      console.error(`[Jobs] Branch metadata check failed to list branches for ${repo.name}:`, error);
+      continue;
+    }
+
+    for (const branch of branches) {
+      try {
+        const outcome = await repairBranchMetadata(repo.id, store, branch);
+        if (outcome !== "current") counts[outcome]++;
+      } catch (error) {
+        counts.failed++;
+        console.error(`[Jobs] Branch metadata check failed for ${repo.name}@${branch}:`, error);
+      }
+    }
+  }
+
+  if (counts.rebuilt + counts.removed + counts.failed > 0) {
+    console.log(`[Jobs] Branch metadata: ${counts.rebuilt} rebuilt, ${counts.removed} removed, ${counts.failed} failed`);
+  }
+  return repos.length;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startBranchMetadataJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      await trackJob("branch_metadata", reconcileBranchMetadata);
+    } catch (error) {
+      console.error("[Jobs] Branch metadata reconciliation failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
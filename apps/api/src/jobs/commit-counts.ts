@@ -0,0 +1,130 @@
+import { db, commitCounts, repositories } from "@gitbruv/db";
+import { eq, and, inArray, notInArray, sql } from "drizzle-orm";
+import { createGitStore, listBranches, resolveCommittish, type GitStore } from "../git";
+import { CommitCountIndex, type CommitCountEntry } from "../git/commit-counts";
+import { trackJob, trackTask } from "../metrics";
+
+const RUN_INTERVAL_MS = 10 * 60 * 1000;
+const BATCH_SIZE = 1000;
+const REPOS_PER_RUN = 5;
+
+// A read can fill in a short stretch of new history itself; anything longer is left to the
+// background count so no request walks a large repository.
+const MAX_REQUEST_COMMITS = 500;
+
+type CountedRepo = { id: string; ownerId: string; name: string };
+
+function loaderFor(repoId: string) {
+  return async (oids: string[]) => {
+    const entries = new Map<string, CommitCountEntry>();
+    for (let i = 0; i < oids.length; i += BATCH_SIZE) {
+      const rows = await db
+        .select({ oid: commitCounts.oid, generation: commitCounts.generation, count: commitCounts.count })
+        .from(commitCounts)
+        .where(and(eq(commitCounts.repositoryId, repoId), inArray(commitCounts.oid, oids.slice(i, i + BATCH_SIZE))));
+      for (const row of rows) {
+        entries.set(row.oid, { generation: row.generation, count: row.count });
+      }
+    }
+    return entries;
+  };
+}
+
+async function persist(repoId: string, entries: Map<string, CommitCountEntry>) {
+  const rows = [...entries].map(([oid, entry]) => ({ repositoryId: repoId, oid, ...entry }));
+  for (let i = 0; i < rows.length; i += BATCH_SIZE) {
+    await db.insert(commitCounts).values(rows.slice(i, i + BATCH_SIZE)).onConflictDoNothing();
+  }
+}
+
+const inFlight = new Set<string>();
+
+// Counts every branch tip that doesn't have a row yet. Existing rows are reused, so after the
+// first run this only covers newly pushed commits.
+export function scheduleCommitCounts(repo: CountedRepo, tips?: string[]) {
+  if (inFlight.has(repo.id)) return;
+  inFlight.add(repo.id);
+
+  const task = () =>
+    (async () => {
+      const store = createGitStore(repo.ownerId, repo.name);
+      const index = new CommitCountIndex(store, loaderFor(repo.id));
+      const oids = tips ?? (await branchTips(store));
+      for (const oid of oids) {
+        await index.resolve(oid);
+      }
+      await persist(repo.id, index.newEntries);
+    })()
+      .catch((error) => {
+        console.error(`[Jobs] Commit counting failed for ${repo.name}:`, error);
+      })
+      .finally(() => {
+        inFlight.delete(repo.id);
+      });
+  trackTask("commit_counts", task);
+}
+
+async function branchTips(store: GitStore): Promise<string[]> {
+  const branches = await listBranches(store.fs, store.dir);
+  const tips = await Promise.all(branches.map((branch) => resolveCommittish(store.fs, store.dir, branch)));
+  return tips.filter((oid): oid is string => !!oid);
+}
+
+// O(1) once the tip has a row. Returns null when the count isn't known yet and would take too
+// long to work out during a request; the caller falls back and a background count is queued.
+export async function lookupCommitCount(repo: CountedRepo, store: GitStore, tip: string): Promise<number | null> {
+  const index = new CommitCountIndex(store, loaderFor(repo.id));
+  const entry = await index.resolve(tip, MAX_REQUEST_COMMITS);
+  if (!entry) {
+    scheduleCommitCounts(repo, [tip]);
+    return null;
+  }
+  if (index.newEntries.size > 0) {
+    trackTask("commit_counts", () => persist(repo.id, index.newEntries)).catch((error) => {
+      console.error(`[Jobs] Saving commit counts failed for ${repo.name}:`, error);
+    });
+  }
+  return entry.count;
+}
+
+// Empty repositories never get a row, so each one is only tried once per process rather than
+// holding up the backfill on every run.
+const backfillAttempted = new Set<string>();
+
+// Picks up repositories that have never been counted, e.g. ones created before the table
+// existed or imported without going through receive-pack.
+async function countUncountedRepositories() {
+  const conditions = [sql`NOT EXISTS (SELECT 1 FROM ${commitCounts} WHERE ${commitCounts.repositoryId} = ${repositories.id})`];
+  if (backfillAttempted.size > 0) {
+    conditions.push(notInArray(repositories.id, [...backfillAttempted]));
+  }
+
+  const rows = await db
+    .select({ id: repositories.id, ownerId: repositories.ownerId, name: repositories.name })
+    .from(repositories)
+    .where(and(...conditions))
+    .limit(REPOS_PER_RUN);
+
+  for (const repo of rows) {
+    backfillAttempted.add(repo.id);
+    scheduleCommitCounts(repo);
+  }
+  return rows.length;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startCommitCountJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      await trackJob("commit_counts", countUncountedRepositories);
+    } catch (error) {
+      console.error("[Jobs] Commit count backfill failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
@@ -0,0 +1,109 @@
+import { db, repositories } from "@gitbruv/db";
+import { eq, and, or, ne, isNull, isNotNull, lt, sql } from "drizzle-orm";
+import { createGitStore } from "../git";
+import { fetchMirror, MIRROR_FETCH_TIMEOUT_MS } from "../git/mirror";
+import { repoCache } from "../cache";
+import { trackJob } from "../metrics";
+
+const RUN_INTERVAL_MS = 60 * 1000;
+// A sync gives up once its fetch times out, so one still marked as syncing well past that was lost
+// with the instance running it and can be claimed again.
+const STALE_SYNC_MS = MIRROR_FETCH_TIMEOUT_MS + 15 * 60 * 1000;
+
+type ClaimedMirror = { id: string; ownerId: string; name: string; mirrorUrl: string };
+
+function claimable() {
+  return or(
+    isNull(repositories.mirrorSyncStatus),
+    ne(repositories.mirrorSyncStatus, "syncing"),
+    isNull(repositories.mirrorSyncStartedAt),
+    lt(repositories.mirrorSyncStartedAt, new Date(Date.now() - STALE_SYNC_MS))
+  );
+}
+
+// Marks the mirror as syncing unless a sync is already underway, so the scheduler and the manual
+// trigger (on this instance or any other) never fetch into the same repository at once.
+export async function claimMirrorSync(repoId: string): Promise<ClaimedMirror | null> {
+  const [row] = await db
+    .update(repositories)
+    .set({ mirrorSyncStatus: "syncing", mirrorSyncStartedAt: new Date() })
+    .where(and(eq(repositories.id, repoId), isNotNull(repositories.mirrorUrl), claimable()))
+    .returning({ id: repositories.id, ownerId: repositories.ownerId, name: repositories.name, mirrorUrl: repositories.mirrorUrl });
+
+  return row ? { ...row, mirrorUrl: row.mirrorUrl! } : null;
+}
+
+export async function runMirrorSync(mirror: ClaimedMirror) {
+  try {
+    const result = await fetchMirror(createGitStore(mirror.ownerId, mirror.name), mirror.mirrorUrl);
+    await db
+      .update(repositories)
+      .set({
+        mirrorSyncStatus: "succeeded",
+        mirrorSyncStartedAt: null,
+        mirrorLastSyncedAt: new Date(),
+        mirrorLastError: null,
+        ...(result.defaultBranch ? { defaultBranch: result.defaultBranch } : {}),
+        updatedAt: new Date(),
+      })
+      .where(eq(repositories.id, mirror.id));
+  } catch (error) {
+    console.error(`[Jobs] Mirror sync failed for ${mirror.name}:`, error);
+    await db
+      .update(repositories)
+      .set({
+        mirrorSyncStatus: "failed",
+        mirrorSyncStartedAt: null,
+        mirrorLastSyncedAt: new Date(),
+        mirrorLastError: error instanceof Error ? error.message : String(error),
+      })
+      .where(eq(repositories.id, mirror.id));
+  } finally {
+    await repoCache.invalidateRepo(mirror.ownerId, mirror.name);
+  }
+}
+
+export async function syncDueMirrors() {
+  const due = await db
+    .select({ id: repositories.id })
+    .from(repositories)
+    .where(
+      and(
+        isNotNull(repositories.mirrorUrl),
+        claimable(),
+        or(
+          isNull(repositories.mirrorLastSyncedAt),
+          sql`${repositories.mirrorLastSyncedAt} + make_interval(mins => ${repositories.mirrorIntervalMinutes}) <= now()`
+        )
+      )
+    );
+
+  let synced = 0;
+  for (const { id } of due) {
+    const mirror = await claimMirrorSync(id);
+    if (!mirror) continue;
+    await runMirrorSync(mirror);
+    synced++;
+  }
+  return synced;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startMirrorSyncJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      const synced = await trackJob("mirror_sync", syncDueMirrors);
+      if (synced > 0) {
+        console.log(`[Jobs] Synced ${synced} mirror(s)`);
+      }
+    } catch (error) {
+      console.error("[Jobs] Mirror sync failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
@@ -0,0 +1,31 @@
+import { db, repoInsights } from "@gitbruv/db";
+import { createGitStore } from "../git";
+import { computeRepoInsights } from "../git/insights";
+import { trackTask } from "../metrics";
+
+// Keyed by repository and head so repeated polls while a computation runs don't start another,
+// while a push that moves the head does.
+const inFlight = new Set<string>();
+
+export function scheduleRepoInsights(repo: { id: string; ownerId: string; name: string }, headOid: string) {
+  const key = `${repo.id}:${headOid}`;
+  if (inFlight.has(key)) return;
+  inFlight.add(key);
+
+  const task = () =>
+    computeRepoInsights(createGitStore(repo.ownerId, repo.name), headOid)
+      .then(async (insights) => {
+        const values = { headOid, ...insights, computedAt: new Date() };
+        await db
+          .insert(repoInsights)
+          .values({ repositoryId: repo.id, ...values })
+          .onConflictDoUpdate({ target: repoInsights.repositoryId, set: values });
+      })
+      .catch((error) => {
+        console.error(`[Jobs] Repository insights failed for ${repo.name}:`, error);
+      })
+      .finally(() => {
+        inFlight.delete(key);
+      });
+  trackTask("repo_insights", task);
+}
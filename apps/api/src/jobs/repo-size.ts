@@ -0,0 +1,31 @@
+import { db, repoSizes } from "@gitbruv/db";
+import { computeRepoSize } from "../git/size";
+import { storageLayout } from "../storage-layout";
+import { trackTask } from "../metrics";
+import { hasStorageFailure } from "../tracing";
+
+const inFlight = new Set<string>();
+
+export function scheduleRepoSize(repo: { id: string; ownerId: string; name: string }) {
+  if (inFlight.has(repo.id)) return;
+  inFlight.add(repo.id);
+
+  const task = () =>
+    computeRepoSize(storageLayout.repoPrefix(repo.ownerId, repo.name))
+      .then(async (size) => {
+        // A scan that lost objects to a storage error would under-report; keep the last good one.
+        if (hasStorageFailure()) return;
+        const values = { ...size, computedAt: new Date() };
+        await db
+          .insert(repoSizes)
+          .values({ repositoryId: repo.id, ...values })
+          .onConflictDoUpdate({ target: repoSizes.repositoryId, set: values });
+      })
+      .catch((error) => {
+        console.error(`[Jobs] Repository size scan failed for ${repo.name}:`, error);
+      })
+      .finally(() => {
+        inFlight.delete(repo.id);
+      });
+  trackTask("repo_size", task);
+}
@@ -0,0 +1,284 @@
+import {
+  db,
+  users,
+  repositories,
+  issues,
+  issueAssignees,
+  pullRequests,
+  prAssignees,
+  prReviewers,
+  prReviews,
+  notifications,
+} from "@gitbruv/db";
+import { eq, and, gt, isNotNull, sql } from "drizzle-orm";
+import { alias } from "drizzle-orm/pg-core";
+import { createNotification } from "../routes/notifications";
+import { createGitStore, getChangedPaths } from "../git";
+import { getCodeOwnerReviewers } from "../code-owners";
+import { trackJob } from "../metrics";
+
+const headRepos = alias(repositories, "head_repos");
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+const RUN_INTERVAL_MS = 60 * 60 * 1000;
+
+type PendingItem = {
+  kind: "issue" | "pull_request";
+  repoOwner: string;
+  repoName: string;
+  number: number;
+  title: string;
+  waitingDays: number;
+};
+
+function daysSince(date: Date, now: number) {
+  return Math.floor((now - date.getTime()) / DAY_MS);
+}
+
+async function collectPendingItems(now: number) {
+  const pending = new Map<string, PendingItem[]>();
+
+  const add = (userId: string, item: PendingItem) => {
+    const list = pending.get(userId) || [];
+    list.push(item);
+    pending.set(userId, list);
+  };
+
+  const hasPullRequest = (userId: string, repoOwner: string, repoName: string, number: number) =>
+    !!pending.get(userId)?.some((i) => i.kind === "pull_request" && i.repoOwner === repoOwner && i.repoName === repoName && i.number === number);
+
+  const reviewRequests = await db
+    .select({
+      userId: prReviewers.userId,
+      requestedAt: prReviewers.requestedAt,
+      number: pullRequests.number,
+      title: pullRequests.title,
+      authorId: pullRequests.authorId,
+      reminderDays: repositories.reviewReminderDays,
+      repoName: repositories.name,
+      repoOwner: users.username,
+    })
+    .from(prReviewers)
+    .innerJoin(pullRequests, eq(pullRequests.id, prReviewers.pullRequestId))
+    .innerJoin(repositories, eq(repositories.id, pullRequests.baseRepoId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(
+      and(
+        eq(pullRequests.state, "open"),
+        eq(pullRequests.isDraft, false),
+        isNotNull(repositories.reviewReminderDays),
+        sql`NOT EXISTS (
+          SELECT 1 FROM ${prReviews}
+          WHERE ${prReviews.pullRequestId} = ${pullRequests.id}
+            AND ${prReviews.authorId} = ${prReviewers.userId}
+        )`
+      )
+    );
+
+  for (const row of reviewRequests) {
+    const waitingDays = daysSince(row.requestedAt, now);
+    if (row.userId === row.authorId || waitingDays < row.reminderDays!) continue;
+    add(row.userId, {
+      kind: "pull_request",
+      repoOwner: row.repoOwner,
+      repoName: row.repoName,
+      number: row.number,
+      title: row.title,
+      waitingDays,
+    });
+  }
+
+  // Code owners of the changed paths are reminded whether or not they're still in the reviewer
+  // list, counting from when the pull request was opened, until they've reviewed.
+  const ownedPrs = await db
+    .select({
+      id: pullRequests.id,
+      createdAt: pullRequests.createdAt,
+      number: pullRequests.number,
+      title: pullRequests.title,
+      authorId: pullRequests.authorId,
+      baseRepoId: pullRequests.baseRepoId,
+      baseBranch: pullRequests.baseBranch,
+      headBranch: pullRequests.headBranch,
+      reminderDays: repositories.reviewReminderDays,
+      repoName: repositories.name,
+      repoOwnerId: repositories.ownerId,
+      repoOwner: users.username,
+      headOwnerId: headRepos.ownerId,
+      headName: headRepos.name,
+    })
+    .from(pullRequests)
+    .innerJoin(repositories, eq(repositories.id, pullRequests.baseRepoId))
+    .innerJoin(headRepos, eq(headRepos.id, pullRequests.headRepoId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(pullRequests.state, "open"), eq(pullRequests.isDraft, false), isNotNull(repositories.reviewReminderDays)));
+
+  for (const row of ownedPrs) {
+    const waitingDays = daysSince(row.createdAt, now);
+    if (waitingDays < row.reminderDays!) continue;
+
+    let owners: string[];
+    try {
+      const changedPaths = await getChangedPaths(
+        createGitStore(row.repoOwnerId, row.repoName),
+        row.baseBranch,
+        createGitStore(row.headOwnerId, row.headName),
+        row.headBranch
+      );
+      owners = await getCodeOwnerReviewers(row.baseRepoId, changedPaths, row.authorId);
+    } catch (error) {
+      console.error(`[Jobs] Review reminders: code owners lookup failed for ${row.repoOwner}/${row.repoName}#${row.number}:`, error);
+      continue;
+    }
+    if (owners.length === 0) continue;
+
+    const reviewed = await db
+      .select({ authorId: prReviews.authorId })
+      .from(prReviews)
+      .where(eq(prReviews.pullRequestId, row.id));
+    const reviewers = new Set(reviewed.map((review) => review.authorId));
+
+    for (const userId of owners) {
+      if (reviewers.has(userId) || hasPullRequest(userId, row.repoOwner, row.repoName, row.number)) continue;
+      add(userId, {
+        kind: "pull_request",
+        repoOwner: row.repoOwner,
+        repoName: row.repoName,
+        number: row.number,
+        title: row.title,
+        waitingDays,
+      });
+    }
+  }
+
+  const assignedPrs = await db
+    .select({
+      userId: prAssignees.userId,
+      assignedAt: prAssignees.assignedAt,
+      number: pullRequests.number,
+      title: pullRequests.title,
+      reminderDays: repositories.reviewReminderDays,
+      repoName: repositories.name,
+      repoOwner: users.username,
+    })
+    .from(prAssignees)
+    .innerJoin(pullRequests, eq(pullRequests.id, prAssignees.pullRequestId))
+    .innerJoin(repositories, eq(repositories.id, pullRequests.baseRepoId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(pullRequests.state, "open"), isNotNull(repositories.reviewReminderDays)));
+
+  for (const row of assignedPrs) {
+    const waitingDays = daysSince(row.assignedAt, now);
+    if (waitingDays < row.reminderDays!) continue;
+    if (hasPullRequest(row.userId, row.repoOwner, row.repoName, row.number)) continue;
+    add(row.userId, {
+      kind: "pull_request",
+      repoOwner: row.repoOwner,
+      repoName: row.repoName,
+      number: row.number,
+      title: row.title,
+      waitingDays,
+    });
+  }
+
+  const assignedIssues = await db
+    .select({
+      userId: issueAssignees.userId,
+      assignedAt: issueAssignees.assignedAt,
+      number: issues.number,
+      title: issues.title,
+      reminderDays: repositories.reviewReminderDays,
+      repoName: repositories.name,
+      repoOwner: users.username,
+    })
+    .from(issueAssignees)
+    .innerJoin(issues, eq(issues.id, issueAssignees.issueId))
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(issues.state, "open"), isNotNull(repositories.reviewReminderDays)));
+
+  for (const row of assignedIssues) {
+    const waitingDays = daysSince(row.assignedAt, now);
+    if (waitingDays < row.reminderDays!) continue;
+    add(row.userId, {
+      kind: "issue",
+      repoOwner: row.repoOwner,
+      repoName: row.repoName,
+      number: row.number,
+      title: row.title,
+      waitingDays,
+    });
+  }
+
+  return pending;
+}
+
+async function wasRemindedRecently(userId: string, now: number) {
+  const recent = await db.query.notifications.findFirst({
+    where: and(
+      eq(notifications.userId, userId),
+      eq(notifications.type, "review_reminder"),
+      gt(notifications.createdAt, new Date(now - DAY_MS))
+    ),
+    columns: { id: true },
+  });
+  return !!recent;
+}
+
+function formatItem(item: PendingItem) {
+  const prefix = item.kind === "pull_request" ? "PR" : "Issue";
+  return `${prefix} ${item.repoOwner}/${item.repoName}#${item.number} "${item.title}" (waiting ${item.waitingDays} days)`;
+}
+
+export async function sendReviewReminders() {
+  const now = Date.now();
+  const pending = await collectPendingItems(now);
+  let sent = 0;
+
+  for (const [userId, items] of pending) {
+    if (await wasRemindedRecently(userId, now)) continue;
+
+    items.sort((a, b) => b.waitingDays - a.waitingDays);
+    const title =
+      items.length === 1
+        ? "1 item is waiting on you"
+        : `${items.length} items are waiting on you`;
+
+    const single = items.length === 1 ? items[0] : null;
+
+    await createNotification({
+      userId,
+      type: "review_reminder",
+      title,
+      body: items.map(formatItem).join("\n"),
+      resourceType: single?.kind,
+      repoOwner: single?.repoOwner,
+      repoName: single?.repoName,
+      resourceNumber: single?.number,
+      sendEmail: true,
+    });
+    sent++;
+  }
+
+  return sent;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startReviewReminderJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      const sent = await trackJob("review_reminders", sendReviewReminders);
+      if (sent > 0) {
+        console.log(`[Jobs] Sent ${sent} review reminder(s)`);
+      }
+    } catch (error) {
+      console.error("[Jobs] Review reminders failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
@@ -0,0 +1,67 @@
+import { db, repositories } from "@gitbruv/db";
+import { and, isNull, isNotNull, or, eq } from "drizzle-orm";
+import { alias } from "drizzle-orm/pg-core";
+import { migrateRepoObjects } from "../git/shared-objects";
+import { trackJob } from "../metrics";
+
+const RUN_INTERVAL_MS = 10 * 60 * 1000;
+const REPOS_PER_RUN = 5;
+
+const parents = alias(repositories, "parents");
+
+// Moves public repositories still on the per-repository object layout onto the shared one, a few
+// per run. A fork waits until its parent has moved and then joins the parent's network, so existing
+// fork trees (and forks copied whole) end up deduplicated the same way new forks are. A parent that
+// is private and off any network will never move, so its forks start networks of their own.
+export async function migrateLegacyObjectStores() {
+  const pending = await db
+    .select({
+      id: repositories.id,
+      ownerId: repositories.ownerId,
+      name: repositories.name,
+      objectNetworkId: repositories.objectNetworkId,
+      parentNetworkId: parents.objectNetworkId,
+    })
+    .from(repositories)
+    .leftJoin(parents, eq(parents.id, repositories.forkedFromId))
+    .where(
+      and(
+        isNull(repositories.objectNetworkId),
+        eq(repositories.visibility, "public"),
+        or(isNull(repositories.forkedFromId), isNotNull(parents.objectNetworkId), eq(parents.visibility, "private"))
+      )
+    )
+    .limit(REPOS_PER_RUN);
+
+  let migrated = 0;
+  for (const repo of pending) {
+    try {
+      if (await migrateRepoObjects(repo, repo.parentNetworkId ?? repo.id)) {
+        migrated++;
+      }
+    } catch (error) {
+      console.error(`[Jobs] Failed to move objects of repository ${repo.id} to shared storage:`, error);
+    }
+  }
+  return migrated;
+}
+
+let timer: ReturnType<typeof setInterval> | null = null;
+
+export function startSharedObjectMigrationJob() {
+  if (timer) return;
+
+  const run = async () => {
+    try {
+      const migrated = await trackJob("shared_object_migration", migrateLegacyObjectStores);
+      if (migrated > 0) {
+        console.log(`[Jobs] Moved ${migrated} repositories onto shared object storage`);
+      }
+    } catch (error) {
+      console.error("[Jobs] Shared object migration failed:", error);
+    }
+  };
+
+  timer = setInterval(run, RUN_INTERVAL_MS);
+  run();
+}
@@ -0,0 +1,34 @@
+import { db, labels } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+
+export const DEFAULT_LABELS = [
+  { name: "bug", description: "Something isn't working", color: "d73a4a" },
+  { name: "enhancement", description: "New feature or request", color: "a2eeef" },
+  { name: "documentation", description: "Improvements or additions to documentation", color: "0075ca" },
+  { name: "duplicate", description: "This issue or pull request already exists", color: "cfd3d7" },
+  { name: "good first issue", description: "Good for newcomers", color: "7057ff" },
+  { name: "help wanted", description: "Extra attention is needed", color: "008672" },
+  { name: "invalid", description: "This doesn't seem right", color: "e4e669" },
+  { name: "question", description: "Further information is requested", color: "d876e3" },
+  { name: "wontfix", description: "This will not be worked on", color: "ffffff" },
+];
+
+export const MAX_BULK_LABEL_ISSUES = 500;
+
+// Presets are matched against existing labels case-insensitively and only the missing ones are
+// added. The unique (repository, name) index makes a concurrent apply skip rows the other one
+// inserted rather than fail, so applying twice is a no-op.
+export async function applyDefaultLabels(repositoryId: string) {
+  const existing = await db.select({ name: labels.name }).from(labels).where(eq(labels.repositoryId, repositoryId));
+  const taken = new Set(existing.map((label) => label.name.toLowerCase()));
+  const missing = DEFAULT_LABELS.filter((label) => !taken.has(label.name));
+  if (missing.length === 0) {
+    return [];
+  }
+
+  return db
+    .insert(labels)
+    .values(missing.map((label) => ({ ...label, repositoryId })))
+    .onConflictDoNothing({ target: [labels.repositoryId, labels.name] })
+    .returning();
+}
@@ -0,0 +1,25 @@
+export const LICENSE_FILES = ["LICENSE", "LICENSE.md", "LICENSE.txt", "COPYING", "COPYING.md"];
+
+const LICENSE_PATTERNS: [RegExp, string][] = [
+  [/Apache License[\s\S]*Version 2\.0/i, "Apache-2.0"],
+  [/GNU AFFERO GENERAL PUBLIC LICENSE[\s\S]*Version 3/i, "AGPL-3.0"],
+  [/GNU LESSER GENERAL PUBLIC LICENSE[\s\S]*Version 3/i, "LGPL-3.0"],
+  [/GNU LESSER GENERAL PUBLIC LICENSE[\s\S]*Version 2\.1/i, "LGPL-2.1"],
+  [/GNU GENERAL PUBLIC LICENSE[\s\S]*Version 3/i, "GPL-3.0"],
+  [/GNU GENERAL PUBLIC LICENSE[\s\S]*Version 2/i, "GPL-2.0"],
+  [/Mozilla Public License,? (?:Version|v\.?) ?2\.0/i, "MPL-2.0"],
+  [/Permission is hereby granted, free of charge/i, "MIT"],
+  [/Redistribution and use in source and binary forms[\s\S]*Neither the name/i, "BSD-3-Clause"],
+  [/Redistribution and use in source and binary forms/i, "BSD-2-Clause"],
+  [/Permission to use, copy, modify, and\/or distribute this software/i, "ISC"],
+  [/This is free and unencumbered software released into the public domain/i, "Unlicense"],
+];
+
+// Only the opening of the file is inspected; every recognised license names itself there.
+export function detectLicense(content: string): string {
+  const head = content.slice(0, 4000);
+  for (const [pattern, id] of LICENSE_PATTERNS) {
+    if (pattern.test(head)) return id;
+  }
+  return "other";
+}
@@ -0,0 +1,163 @@
+import { randomUUID } from "crypto";
+import { constants } from "fs";
+import { access, mkdir, readFile, readdir, rename, rm, stat, unlink, writeFile, open } from "fs/promises";
+import { dirname, join, relative, resolve, sep } from "path";
+import { config } from "./config";
+import type { ObjectStorage, ObjectSummary } from "./object-storage";
+
+// Objects are plain files under the storage directory, one per key. Writes go to a temporary file
+// that is renamed into place, so a reader never sees half an object. Unlike S3, a key can't be
+// both an object and a prefix of other keys, which the storage layout never does.
+const TEMP_FILE = /\.tmp-[0-9a-f-]{36}$/;
+
+function isMissing(error: unknown) {
+  return (error as NodeJS.ErrnoException)?.code === "ENOENT";
+}
+
+function parseRange(range: string, size: number): { start: number; end: number } | null {
+  const match = range.match(/^bytes=(\d*)-(\d*)$/);
+  if (!match || (!match[1] && !match[2])) return null;
+  if (!match[1]) {
+    const length = Math.min(parseInt(match[2], 10), size);
+    return { start: size - length, end: size - 1 };
+  }
+  const start = parseInt(match[1], 10);
+  const end = match[2] ? Math.min(parseInt(match[2], 10), size - 1) : size - 1;
+  return { start, end };
+}
+
+export function createLocalStorage(root: string = config.storage.localPath): ObjectStorage {
+  const base = resolve(root);
+
+  // Keys come from the storage layout, but some embed user-supplied names, so anything that
+  // could step outside the storage directory is refused rather than normalized.
+  const pathFor = (key: string) => {
+    const parts = key.split("/");
+    if (!key || key.startsWith("/") || key.includes("\\") || key.includes("\0") || parts.some((part) => part === ".." || part === ".")) {
+      throw new Error(`Invalid storage key: ${key}`);
+    }
+    return join(base, ...parts);
+  };
+
+  const writeAtomically = async (key: string, write: (tempPath: string) => Promise<unknown>) => {
+    const path = pathFor(key);
+    await mkdir(dirname(path), { recursive: true });
+    const tempPath = `${path}.tmp-${randomUUID()}`;
+    try {
+      await write(tempPath);
+      await rename(tempPath, path);
+    } catch (error) {
+      await rm(tempPath, { force: true });
+      throw error;
+    }
+  };
+
+  const walk = async (dir: string, out: ObjectSummary[], prefix: string) => {
+    let entries;
+    try {
+      entries = await readdir(dir, { withFileTypes: true });
+    } catch (error) {
+      if (isMissing(error)) return;
+      throw error;
+    }
+
+    for (const entry of entries) {
+      const path = join(dir, entry.name);
+      const key = relative(base, path).split(sep).join("/");
+      if (entry.isDirectory()) {
+        // Only descend where keys under this directory can still start with the prefix.
+        if (`${key}/`.startsWith(prefix) || prefix.startsWith(`${key}/`)) {
+          await walk(path, out, prefix);
+        }
+      } else if (entry.isFile() && key.startsWith(prefix) && !TEMP_FILE.test(entry.name)) {
+        const info = await stat(path);
+        out.push({ key, size: info.size, lastModified: info.mtime });
+      }
+    }
+  };
+
+  return {
+    backend: "local",
+
+    async get(key: string, range?: string): Promise<Buffer | null> {
+      const path = pathFor(key);
+      try {
+        if (!range) {
+          return await readFile(path);
+        }
+
+        const handle = await open(path, "r");
+        try {
+          const { size } = await handle.stat();
+          const bounds = parseRange(range, size);
+          if (!bounds) return await readFile(path);
+          const length = Math.max(bounds.end - bounds.start + 1, 0);
+          const buffer = Buffer.alloc(length);
+          await handle.read(buffer, 0, length, bounds.start);
+          return buffer;
+        } finally {
+          await handle.close();
+        }
+      } catch (error) {
+        if (isMissing(error)) return null;
+        throw error;
+      }
+    },
+
+    async getStream(key: string): Promise<ReadableStream | null> {
+      const path = pathFor(key);
+      try {
+        await stat(path);
+      } catch (error) {
+        if (isMissing(error)) return null;
+        throw error;
+      }
+      return Bun.file(path).stream();
+    },
+
+    async head(key: string) {
+      try {
+        const info = await stat(pathFor(key));
+        return info.isFile() ? { size: info.size, contentType: undefined } : null;
+      } catch (error) {
+        if (isMissing(error)) return null;
+        throw error;
+      }
+    },
+
+    async put(key, body) {
+      await writeAtomically(key, (tempPath) => writeFile(tempPath, body));
+    },
+
+    async upload(key, body) {
+      await writeAtomically(key, (tempPath) => Bun.write(tempPath, body instanceof ReadableStream ? new Response(body) : body));
+    },
+
+    async delete(key: string): Promise<void> {
+      try {
+        await unlink(pathFor(key));
+      } catch (error) {
+        if (!isMissing(error)) throw error;
+      }
+    },
+
+    async list(prefix: string): Promise<ObjectSummary[]> {
+      const dir = prefix.endsWith("/") || !prefix.includes("/") ? prefix : prefix.slice(0, prefix.lastIndexOf("/") + 1);
+      const objects: ObjectSummary[] = [];
+      await walk(dir ? pathFor(dir.replace(/\/$/, "")) : base, objects, prefix);
+      return objects.sort((a, b) => (a.key < b.key ? -1 : a.key > b.key ? 1 : 0));
+    },
+
+    // The directory is created on first write, so a fresh install is healthy as long as it can be.
+    async ping(): Promise<boolean> {
+      await mkdir(base, { recursive: true });
+      await access(base, constants.R_OK | constants.W_OK);
+      return true;
+    },
+
+    // Clients can't upload to a directory on the server, so direct uploads need S3.
+    presignPut() {
+      return null;
+    },
+  };
+}
@@ -0,0 +1,139 @@
+import { isolateStorageFailures } from "./tracing";
+
+const BUCKET_MS = 60 * 1000;
+const MAX_BUCKETS = 60;
+const MAX_INCIDENTS = 20;
+const INCIDENT_ERROR_RATE = 0.05;
+const INCIDENT_MIN_REQUESTS = 50;
+
+type Bucket = { start: number; requests: number; errors: number };
+
+export type Incident = {
+  id: string;
+  component: string;
+  message: string;
+  startedAt: number;
+  resolvedAt: number | null;
+};
+
+export type JobState = {
+  running: boolean;
+  lastRunAt: number | null;
+  lastDurationMs: number | null;
+  lastError: string | null;
+};
+
+const buckets: Bucket[] = [];
+const incidents: Incident[] = [];
+const jobs = new Map<string, JobState>();
+const pendingTasks = new Map<string, number>();
+
+function currentBucket(now: number): Bucket {
+  const start = now - (now % BUCKET_MS);
+  const last = buckets[buckets.length - 1];
+  if (last && last.start === start) {
+    return last;
+  }
+
+  const bucket = { start, requests: 0, errors: 0 };
+  buckets.push(bucket);
+  if (buckets.length > MAX_BUCKETS) {
+    buckets.shift();
+  }
+  return bucket;
+}
+
+export function recordRequest(status: number) {
+  const bucket = currentBucket(Date.now());
+  bucket.requests++;
+  if (status >= 500) {
+    bucket.errors++;
+  }
+}
+
+export function getErrorRate(windowMinutes: number) {
+  const since = Date.now() - windowMinutes * BUCKET_MS;
+  let requests = 0;
+  let errors = 0;
+  for (const bucket of buckets) {
+    if (bucket.start >= since - BUCKET_MS) {
+      requests += bucket.requests;
+      errors += bucket.errors;
+    }
+  }
+  return { requests, errors, rate: requests > 0 ? errors / requests : 0 };
+}
+
+export function openIncident(component: string, message: string) {
+  const existing = incidents.find((incident) => incident.component === component && incident.resolvedAt === null);
+  if (existing) {
+    existing.message = message;
+    return;
+  }
+
+  incidents.push({
+    id: `${component}-${Date.now()}`,
+    component,
+    message,
+    startedAt: Date.now(),
+    resolvedAt: null,
+  });
+  if (incidents.length > MAX_INCIDENTS) {
+    incidents.shift();
+  }
+}
+
+export function resolveIncident(component: string) {
+  for (const incident of incidents) {
+    if (incident.component === component && incident.resolvedAt === null) {
+      incident.resolvedAt = Date.now();
+    }
+  }
+}
+
+export function getIncidents(): Incident[] {
+  const { requests, rate } = getErrorRate(5);
+  if (requests >= INCIDENT_MIN_REQUESTS && rate >= INCIDENT_ERROR_RATE) {
+    openIncident("api", `Elevated error rate: ${(rate * 100).toFixed(1)}% of requests failing`);
+  } else {
+    resolveIncident("api");
+  }
+  return [...incidents].reverse();
+}
+
+export async function trackJob<T>(name: string, run: () => Promise<T>): Promise<T> {
+  const state = jobs.get(name) ?? { running: false, lastRunAt: null, lastDurationMs: null, lastError: null };
+  jobs.set(name, state);
+
+  const startedAt = Date.now();
+  state.running = true;
+  state.lastRunAt = startedAt;
+  try {
+    const result = await run();
+    state.lastError = null;
+    return result;
+  } catch (error) {
+    state.lastError = error instanceof Error ? error.message : String(error);
+    throw error;
+  } finally {
+    state.running = false;
+    state.lastDurationMs = Date.now() - startedAt;
+  }
+}
+
+export function getJobStates(): Record<string, JobState> {
+  return Object.fromEntries(jobs);
+}
+
+// Background work doesn't decide the response of the request that started it, so its storage
+// failures are kept apart from the request's.
+export function trackTask<T>(name: string, task: () => Promise<T>): Promise<T> {
+  pendingTasks.set(name, (pendingTasks.get(name) || 0) + 1);
+  return isolateStorageFailures(task).finally(() => {
+    pendingTasks.set(name, Math.max(0, (pendingTasks.get(name) || 1) - 1));
+  });
+}
+
+export function getPendingTasks(): Record<string, number> {
+  return Object.fromEntries(pendingTasks);
+}
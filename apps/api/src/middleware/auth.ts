@@ -1,5 +1,7 @@
 import { createMiddleware } from "hono/factory";
 import { getAuth, type Session } from "../auth";
+import { config } from "../config";
+import { apiError } from "../errors";
 
 export type AuthUser = {
   id: string;
@@ -48,10 +50,21 @@ export const requireAuth = createMiddleware<{ Variables: AuthVariables }>(async
   const user = c.get("user");
 
   if (!user) {
-    return c.json({ error: "Unauthorized" }, 401);
+    return apiError(c, 401, "Unauthorized");
   }
 
   await next();
 });
 
 export const optionalAuth = authMiddleware;
+
+export const requireInternalAuth = createMiddleware(async (c, next) => {
+  const secret = config.betterAuthSecret;
+  const provided = c.req.header("x-internal-auth");
+
+  if (!secret || !provided || provided !== secret) {
+    return apiError(c, 401, "Unauthorized");
+  }
+
+  await next();
+});
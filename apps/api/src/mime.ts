@@ -0,0 +1,45 @@
+export function getContentType(ext: string): string {
+  const contentTypes: Record<string, string> = {
+    js: "application/javascript",
+    mjs: "application/javascript",
+    ts: "text/typescript",
+    tsx: "text/typescript",
+    jsx: "text/javascript",
+    json: "application/json",
+    html: "text/html",
+    htm: "text/html",
+    css: "text/css",
+    md: "text/markdown",
+    txt: "text/plain",
+    xml: "application/xml",
+    svg: "image/svg+xml",
+    png: "image/png",
+    jpg: "image/jpeg",
+    jpeg: "image/jpeg",
+    gif: "image/gif",
+    webp: "image/webp",
+    ico: "image/x-icon",
+    pdf: "application/pdf",
+    wasm: "application/wasm",
+    yaml: "text/yaml",
+    yml: "text/yaml",
+    toml: "text/toml",
+    sh: "text/x-shellscript",
+    bash: "text/x-shellscript",
+    zsh: "text/x-shellscript",
+    py: "text/x-python",
+    rb: "text/x-ruby",
+    go: "text/x-go",
+    rs: "text/x-rust",
+    c: "text/x-c",
+    cpp: "text/x-c++",
+    h: "text/x-c",
+    hpp: "text/x-c++",
+    java: "text/x-java",
+    kt: "text/x-kotlin",
+    swift: "text/x-swift",
+    php: "text/x-php",
+  };
+
+  return contentTypes[ext] || "application/octet-stream";
+}
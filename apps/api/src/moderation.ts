@@ -0,0 +1,107 @@
+import { db, contentReports, issues, issueComments, repositories, users } from "@gitbruv/db";
+import { eq, and, desc, inArray, sql, type SQL } from "drizzle-orm";
+import { alias } from "drizzle-orm/pg-core";
+import { encodeCursor, afterCursor } from "./pagination";
+
+export const REPORT_REASONS = ["spam", "abuse", "off_topic", "other"] as const;
+export const HIDDEN_REASONS = ["spam", "abuse", "off_topic", "outdated", "resolved", "duplicate"] as const;
+export const MAX_REPORT_DETAILS_LENGTH = 2000;
+
+export type ReportReason = (typeof REPORT_REASONS)[number];
+export type HiddenReason = (typeof HIDDEN_REASONS)[number];
+
+const repoOwners = alias(users, "repo_owners");
+
+// Shared by the per-repository queue and the admin queue; `conditions` narrows the reports and
+// the cursor is over (created_at, id), newest first.
+export async function listReports(conditions: SQL[], cursor: string[] | null, limit: number) {
+  if (cursor) {
+    conditions.push(
+      afterCursor(
+        [
+          { column: contentReports.createdAt, type: "timestamp" },
+          { column: contentReports.id, type: "uuid" },
+        ],
+        cursor,
+        "desc"
+      )
+    );
+  }
+
+  const rows = await db
+    .select({
+      id: contentReports.id,
+      subjectType: contentReports.subjectType,
+      subjectId: contentReports.subjectId,
+      reason: contentReports.reason,
+      details: contentReports.details,
+      status: contentReports.status,
+      createdAt: contentReports.createdAt,
+      resolvedAt: contentReports.resolvedAt,
+      reporter: { id: users.id, username: users.username, avatarUrl: users.avatarUrl },
+      repository: { id: repositories.id, name: repositories.name, owner: repoOwners.username },
+      sortKey: sql<string>`${contentReports.createdAt}::text`,
+    })
+    .from(contentReports)
+    .innerJoin(users, eq(users.id, contentReports.reporterId))
+    .innerJoin(repositories, eq(repositories.id, contentReports.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(and(...conditions))
+    .orderBy(desc(contentReports.createdAt), desc(contentReports.id))
+    .limit(limit + 1);
+
+  const hasMore = rows.length > limit;
+  const page = rows.slice(0, limit);
+  const subjects = await loadSubjects(page);
+
+  const reports = page.map(({ sortKey: _sortKey, ...report }) => ({
+    ...report,
+    subject: subjects.get(report.subjectId) ?? null,
+  }));
+  const last = page[page.length - 1];
+
+  return { reports, hasMore, nextCursor: hasMore && last ? encodeCursor([last.sortKey, last.id]) : null };
+}
+
+// Moderators see the reported text as written, including comments that have since been hidden.
+async function loadSubjects(reports: { subjectType: "issue" | "issue_comment"; subjectId: string }[]) {
+  const issueIds = reports.filter((report) => report.subjectType === "issue").map((report) => report.subjectId);
+  const commentIds = reports.filter((report) => report.subjectType === "issue_comment").map((report) => report.subjectId);
+
+  const [issueRows, commentRows] = await Promise.all([
+    issueIds.length > 0
+      ? db
+          .select({ id: issues.id, number: issues.number, title: issues.title, body: issues.body, authorId: issues.authorId })
+          .from(issues)
+          .where(inArray(issues.id, issueIds))
+      : [],
+    commentIds.length > 0
+      ? db
+          .select({
+            id: issueComments.id,
+            number: issues.number,
+            body: issueComments.body,
+            authorId: issueComments.authorId,
+            hiddenReason: issueComments.hiddenReason,
+          })
+          .from(issueComments)
+          .innerJoin(issues, eq(issues.id, issueComments.issueId))
+          .where(inArray(issueComments.id, commentIds))
+      : [],
+  ]);
+
+  const subjects = new Map<string, Record<string, unknown>>();
+  for (const row of issueRows) subjects.set(row.id, row);
+  for (const row of commentRows) subjects.set(row.id, row);
+  return subjects;
+}
+
+// Resolves every open report on a subject once a moderator has acted on it, e.g. by hiding it.
+export async function resolveReportsFor(subjectType: "issue" | "issue_comment", subjectId: string, userId: string) {
+  await db
+    .update(contentReports)
+    .set({ status: "resolved", resolvedById: userId, resolvedAt: new Date() })
+    .where(
+      and(eq(contentReports.subjectType, subjectType), eq(contentReports.subjectId, subjectId), eq(contentReports.status, "open"))
+    );
+}
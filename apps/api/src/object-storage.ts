@@ -0,0 +1,37 @@
+// The bucket the API keeps repositories, avatars, attachments and exports in. Everything else
+// goes through the functions in s3.ts, which hand off to whichever backend config.storage picks:
+// S3 (or anything speaking its API) in production, or a local directory for self-hosting and
+// development without one.
+export type ObjectSummary = {
+  key: string;
+  size: number;
+  lastModified: Date | null;
+};
+
+export type ObjectBody = Buffer | Uint8Array | string;
+
+export type PutOptions = {
+  contentType?: string;
+  // Recorded as object metadata (and a tag, if enabled) by backends that support it.
+  storageClass: string;
+};
+
+export interface ObjectStorage {
+  readonly backend: "s3" | "local";
+
+  // `range` is an HTTP Range header value; "bytes=a-b" and "bytes=-n" must be supported.
+  get(key: string, range?: string): Promise<Buffer | null>;
+  getStream(key: string): Promise<ReadableStream | null>;
+  head(key: string): Promise<{ size: number; contentType: string | undefined } | null>;
+  put(key: string, body: ObjectBody, options: PutOptions): Promise<void>;
+  // For bodies too large to buffer; not retried, since a stream can only be read once.
+  upload(key: string, body: Buffer | Uint8Array | ReadableStream, options: PutOptions): Promise<void>;
+  delete(key: string): Promise<void>;
+  // Every key starting with `prefix`, in lexicographic order.
+  list(prefix: string): Promise<ObjectSummary[]>;
+  // One cheap round trip for health checks, made directly rather than through the retry policy so
+  // an open circuit doesn't hide that storage has recovered. False when the backend isn't configured.
+  ping(): Promise<boolean>;
+  // A URL a client can PUT the object to directly, or null when the backend has none.
+  presignPut(key: string, options: { contentType: string; contentLength: number; expiresInSeconds: number }): string | null;
+}
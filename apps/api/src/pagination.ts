@@ -0,0 +1,63 @@
+import { sql, type AnyColumn, type SQL } from "drizzle-orm";
+
+// Cursors are opaque to clients: base64url JSON of the sort key of the last row returned. Sort
+// values are carried as Postgres text so timestamps keep their microseconds across round trips.
+export function encodeCursor(values: string[]): string {
+  return Buffer.from(JSON.stringify(values)).toString("base64url");
+}
+
+export type KeysetPart = { column: AnyColumn | SQL; type: "timestamp" | "bigint" | "uuid" | "text" };
+
+// What each cursor value has to look like. Keyset values end up in ::timestamp/::uuid casts, so a
+// value Postgres would refuse is rejected here as a bad cursor instead of failing the query.
+export type CursorPart = KeysetPart["type"] | "oid" | "integer";
+
+const TIMESTAMP = /^(\d{4})-(\d{2})-(\d{2})[ T](\d{2}):(\d{2}):(\d{2})(\.\d{1,6})?(Z|[+-]\d{2}(:?\d{2})?)?$/;
+const UUID = /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i;
+
+function isValidTimestamp(value: string): boolean {
+  const match = value.match(TIMESTAMP);
+  if (!match) return false;
+  const [year, month, day, hour, minute, second] = match.slice(1, 7).map(Number);
+  // Date.UTC rolls days like February 30 into the next month; Postgres rejects them.
+  const date = new Date(Date.UTC(year, month - 1, day));
+  return date.getUTCMonth() === month - 1 && date.getUTCDate() === day && hour < 24 && minute < 60 && second < 60;
+}
+
+const VALIDATORS: Record<CursorPart, (value: string) => boolean> = {
+  timestamp: isValidTimestamp,
+  bigint: (value) => /^-?\d{1,18}$/.test(value),
+  uuid: (value) => UUID.test(value),
+  text: () => true,
+  oid: (value) => /^[0-9a-f]{40}$/.test(value),
+  integer: (value) => /^\d{1,9}$/.test(value),
+};
+
+export function decodeCursor(cursor: string, parts: CursorPart[]): string[] | null {
+  try {
+    const values = JSON.parse(Buffer.from(cursor, "base64url").toString("utf8"));
+    if (!Array.isArray(values) || values.length !== parts.length) {
+      return null;
+    }
+    if (values.some((value, index) => typeof value !== "string" || !VALIDATORS[parts[index]](value))) {
+      return null;
+    }
+    return values;
+  } catch {
+    return null;
+  }
+}
+
+// Rows strictly after the cursor in (sort key, id) order. Callers must order by the same parts,
+// in the same direction, so ties on the sort key are broken by id.
+export function afterCursor(parts: KeysetPart[], values: string[], direction: "asc" | "desc"): SQL {
+  const columns = sql.join(
+    parts.map((part) => sql`${part.column}`),
+    sql`, `
+  );
+  const cursor = sql.join(
+    parts.map((part, index) => sql`${values[index]}::${sql.raw(part.type)}`),
+    sql`, `
+  );
+  return direction === "desc" ? sql`(${columns}) < (${cursor})` : sql`(${columns}) > (${cursor})`;
+}
@@ -0,0 +1,39 @@
+import { db, projectColumns, projectItems } from "@gitbruv/db";
+import { eq, and, asc, sql } from "drizzle-orm";
+import { trackTask } from "./metrics";
+
+async function moveCardsToClosedColumns(issueId: string) {
+  const cards = await db
+    .select({ id: projectItems.id, projectId: projectItems.projectId, columnId: projectItems.columnId })
+    .from(projectItems)
+    .where(eq(projectItems.issueId, issueId));
+
+  for (const card of cards) {
+    const [target] = await db
+      .select({ id: projectColumns.id })
+      .from(projectColumns)
+      .where(and(eq(projectColumns.projectId, card.projectId), eq(projectColumns.moveClosedIssues, true)))
+      .orderBy(asc(projectColumns.position))
+      .limit(1);
+    if (!target || target.id === card.columnId) continue;
+
+    await db
+      .update(projectItems)
+      .set({
+        columnId: target.id,
+        position: sql`(SELECT COALESCE(MAX(${projectItems.position}), -1) + 1 FROM ${projectItems} WHERE ${projectItems.columnId} = ${target.id})`,
+      })
+      .where(eq(projectItems.id, card.id));
+  }
+}
+
+// Cards for a closed issue go to the end of the first column marked moveClosedIssues on each
+// board they're on. Boards without such a column are left alone, and failures are logged
+// rather than failing the close.
+export function moveClosedIssueCards(issueId: string) {
+  const task = () =>
+    moveCardsToClosedColumns(issueId).catch((error) => {
+      console.error(`[API] Failed to move project cards for issue ${issueId}:`, error);
+    });
+  trackTask("project_automation", task);
+}
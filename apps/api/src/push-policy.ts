@@ -0,0 +1,176 @@
+import { db, pushPolicies } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import git from "isomorphic-git";
+import { diffTrees, type GitStore } from "./git";
+import { patternToRegex } from "./code-owners";
+import { ZERO_OID, type RefUpdate } from "./git/ref-transaction";
+
+export type PushPolicy = typeof pushPolicies.$inferSelect;
+
+// Walking further than this on a single push is refused rather than silently skipped, so a
+// policy can't be sidestepped by pushing a long enough history.
+const MAX_POLICY_COMMITS = 5000;
+
+export const MAX_COMMIT_MESSAGE_PATTERN_LENGTH = 200;
+
+// Only this much of each commit message is matched, which bounds the (polynomial) backtracking
+// a pattern that passes findUnsafePattern can still do.
+const MAX_TESTED_MESSAGE_LENGTH = 2048;
+
+// Owner-supplied patterns run on the event loop for every pushed commit, so ones that can
+// backtrack exponentially are refused: a repeated group that itself contains a quantifier or an
+// alternation (`(a+)+`, `(a|a)*`), and backreferences. Returns why a pattern is unsafe, or null.
+export function findUnsafePattern(pattern: string): string | null {
+  type Frame = { quantified: boolean; alternation: boolean };
+  const stack: Frame[] = [{ quantified: false, alternation: false }];
+  let last: Frame | "atom" | null = null;
+
+  for (let i = 0; i < pattern.length; i++) {
+    const ch = pattern[i];
+    const frame = stack[stack.length - 1];
+
+    if (ch === "\\") {
+      const next = pattern[i + 1];
+      if (next === "k" || (next >= "1" && next <= "9")) return "backreferences are not allowed";
+      i++;
+      last = "atom";
+      continue;
+    }
+    if (ch === "[") {
+      for (i++; i < pattern.length && pattern[i] !== "]"; i++) {
+        if (pattern[i] === "\\") i++;
+      }
+      last = "atom";
+      continue;
+    }
+    if (ch === "(") {
+      stack.push({ quantified: false, alternation: false });
+      if (pattern[i + 1] === "?") {
+        const named = pattern.slice(i + 2).match(/^<[A-Za-z_$][\w$]*>/);
+        i += named ? named[0].length + 1 : pattern[i + 2] === "<" ? 3 : 2;
+      }
+      last = null;
+      continue;
+    }
+    if (ch === ")") {
+      const group = stack.length > 1 ? stack.pop()! : frame;
+      const parent = stack[stack.length - 1];
+      parent.quantified ||= group.quantified;
+      parent.alternation ||= group.alternation;
+      last = group;
+      continue;
+    }
+    if (ch === "|") {
+      frame.alternation = true;
+      last = null;
+      continue;
+    }
+
+    const bounds = ch === "{" ? pattern.slice(i).match(/^\{(\d+)(,(\d*))?\}/) : null;
+    if (ch === "*" || ch === "+" || ch === "?" || bounds) {
+      const repeats = ch === "*" || ch === "+" || (bounds !== null && (bounds[2] !== undefined ? bounds[3] === "" || Number(bounds[3]) > 1 : Number(bounds[1]) > 1));
+      if (repeats && last !== null && last !== "atom" && (last.quantified || last.alternation)) {
+        return "repeated groups can't contain quantifiers or alternatives";
+      }
+      frame.quantified = true;
+      if (bounds) i += bounds[0].length - 1;
+      if (pattern[i + 1] === "?") i++;
+      last = null;
+      continue;
+    }
+
+    last = "atom";
+  }
+
+  return null;
+}
+
+export async function getPushPolicy(repositoryId: string): Promise<PushPolicy | null> {
+  const policy = await db.query.pushPolicies.findFirst({
+    where: eq(pushPolicies.repositoryId, repositoryId),
+  });
+  return policy ?? null;
+}
+
+export function hasPushChecks(policy: PushPolicy) {
+  return !!policy.commitMessagePattern || policy.requirePusherEmail || !!policy.maxFileSizeMb || policy.forbiddenPaths.length > 0;
+}
+
+// Returns the reason a branch update violates the policy, or null. Only commits carried by this
+// push are checked: history the repository already had was accepted earlier, possibly under a
+// different policy, and rewriting it is the owner's call. Tags aren't checked.
+//
+// The email check looks at the committer rather than the author, so pushing a reviewed branch
+// that contains other people's authored work still works.
+export async function checkPushPolicy(
+  store: GitStore,
+  policy: PushPolicy,
+  update: RefUpdate,
+  pushedOids: Set<string>,
+  blobSizes: Map<string, number>,
+  pusherEmails: string[] | null
+): Promise<string | null> {
+  if (update.newOid === ZERO_OID || !update.ref.startsWith("refs/heads/")) return null;
+
+  // Patterns saved before the safety check existed are refused here rather than run.
+  if (policy.commitMessagePattern && findUnsafePattern(policy.commitMessagePattern)) {
+    return "the commit message pattern is unsafe and must be updated in the push policy";
+  }
+  const messagePattern = policy.commitMessagePattern ? new RegExp(policy.commitMessagePattern) : null;
+  const forbidden = policy.forbiddenPaths.map((pattern) => ({ pattern, regex: patternToRegex(pattern) }));
+  const maxBytes = policy.maxFileSizeMb ? policy.maxFileSizeMb * 1024 * 1024 : null;
+  const emails = pusherEmails ? new Set(pusherEmails.map((email) => email.toLowerCase())) : null;
+
+  const queue = [update.newOid];
+  const visited = new Set<string>([update.oldOid]);
+
+  while (queue.length > 0) {
+    const oid = queue.shift()!;
+    if (visited.has(oid) || !pushedOids.has(oid)) continue;
+    visited.add(oid);
+    if (visited.size > MAX_POLICY_COMMITS) {
+      return `too many new commits to check (limit ${MAX_POLICY_COMMITS})`;
+    }
+
+    const { commit } = await git.readCommit({ fs: store.fs, dir: store.dir, oid });
+    const short = oid.slice(0, 7);
+
+    if (messagePattern && !messagePattern.test(commit.message.trim().slice(0, MAX_TESTED_MESSAGE_LENGTH))) {
+      return `commit ${short} message does not match ${policy.commitMessagePattern}`;
+    }
+
+    if (policy.requirePusherEmail) {
+      if (!emails) {
+        return "committer email checks require pushing as a user";
+      }
+      if (!emails.has(commit.committer.email.toLowerCase())) {
+        return `commit ${short} committer ${commit.committer.email} does not match your account`;
+      }
+    }
+
+    if (maxBytes !== null || forbidden.length > 0) {
+      let parentTree: string | null = null;
+      if (commit.parent.length > 0) {
+        parentTree = (await git.readCommit({ fs: store.fs, dir: store.dir, oid: commit.parent[0] })).commit.tree;
+      }
+
+      for (const file of await diffTrees(store.fs, store.dir, parentTree, commit.tree)) {
+        if (file.status === "deleted" || !file.newOid) continue;
+
+        const rule = forbidden.find((entry) => entry.regex.test(file.path));
+        if (rule) {
+          return `commit ${short} touches forbidden path ${file.path} (${rule.pattern})`;
+        }
+
+        const size = blobSizes.get(file.newOid);
+        if (maxBytes !== null && size !== undefined && size > maxBytes) {
+          return `commit ${short} adds ${file.path} larger than ${policy.maxFileSizeMb} MB`;
+        }
+      }
+    }
+
+    queue.push(...commit.parent);
+  }
+
+  return null;
+}
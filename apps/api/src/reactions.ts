@@ -0,0 +1,106 @@
+import { db, reactions, users } from "@gitbruv/db";
+import { eq, and, sql, inArray, asc } from "drizzle-orm";
+
+export const VALID_EMOJIS = ["+1", "-1", "laugh", "hooray", "confused", "heart", "rocket", "eyes"];
+
+export type ReactionSubjectType = (typeof reactions.subjectType.enumValues)[number];
+
+export type ReactionSummary = { emoji: string; count: number; reacted: boolean };
+
+function subject(subjectType: ReactionSubjectType, subjectId: string) {
+  return and(eq(reactions.subjectType, subjectType), eq(reactions.subjectId, subjectId));
+}
+
+export async function getReactionsGrouped(subjectType: ReactionSubjectType, subjectId: string, userId?: string): Promise<ReactionSummary[]> {
+  const grouped = await getReactionsGroupedBatch(subjectType, [subjectId], userId);
+  return grouped.get(subjectId) ?? [];
+}
+
+export async function getReactionsGroupedBatch(
+  subjectType: ReactionSubjectType,
+  subjectIds: string[],
+  userId?: string
+): Promise<Map<string, ReactionSummary[]>> {
+  const result = new Map<string, ReactionSummary[]>();
+  if (subjectIds.length === 0) return result;
+
+  const rows = await db
+    .select({
+      subjectId: reactions.subjectId,
+      emoji: reactions.emoji,
+      count: sql<number>`COUNT(*)`,
+      reacted: userId ? sql<boolean>`BOOL_OR(${reactions.userId} = ${userId})` : sql<boolean>`false`,
+    })
+    .from(reactions)
+    .where(and(eq(reactions.subjectType, subjectType), inArray(reactions.subjectId, subjectIds)))
+    .groupBy(reactions.subjectId, reactions.emoji);
+
+  for (const row of rows) {
+    const list = result.get(row.subjectId) ?? [];
+    list.push({ emoji: row.emoji, count: Number(row.count), reacted: !!row.reacted });
+    result.set(row.subjectId, list);
+  }
+  return result;
+}
+
+export async function toggleReaction(
+  subjectType: ReactionSubjectType,
+  subjectId: string,
+  repositoryId: string,
+  userId: string,
+  emoji: string
+): Promise<boolean> {
+  const removed = await db
+    .delete(reactions)
+    .where(and(subject(subjectType, subjectId), eq(reactions.userId, userId), eq(reactions.emoji, emoji)))
+    .returning({ id: reactions.id });
+
+  if (removed.length > 0) {
+    return false;
+  }
+
+  await db.insert(reactions).values({ subjectType, subjectId, repositoryId, userId, emoji }).onConflictDoNothing();
+  return true;
+}
+
+export async function listReactionUsers(
+  subjectType: ReactionSubjectType,
+  subjectId: string,
+  options: { emoji?: string; limit: number; offset: number }
+) {
+  const where = options.emoji
+    ? and(subject(subjectType, subjectId), eq(reactions.emoji, options.emoji))
+    : subject(subjectType, subjectId);
+
+  const rows = await db
+    .select({
+      emoji: reactions.emoji,
+      createdAt: reactions.createdAt,
+      user: { id: users.id, username: users.username, name: users.name, avatarUrl: users.avatarUrl },
+    })
+    .from(reactions)
+    .innerJoin(users, eq(users.id, reactions.userId))
+    .where(where)
+    .orderBy(asc(reactions.createdAt), asc(reactions.id))
+    .limit(options.limit + 1)
+    .offset(options.offset);
+
+  return { reactions: rows.slice(0, options.limit), hasMore: rows.length > options.limit };
+}
+
+// Subjects aren't foreign keys, so whoever deletes a subject short of its repository clears its
+// reactions too.
+export async function deleteReactions(subjectType: ReactionSubjectType, subjectIds: string[]) {
+  if (subjectIds.length === 0) return;
+  await db.delete(reactions).where(and(eq(reactions.subjectType, subjectType), inArray(reactions.subjectId, subjectIds)));
+}
+
+export function parseReactionListQuery(query: (key: string) => string | undefined): { emoji?: string; limit: number; offset: number } | { error: string } {
+  const emoji = query("emoji");
+  if (emoji && !VALID_EMOJIS.includes(emoji)) {
+    return { error: "Invalid emoji" };
+  }
+  const limit = Math.min(Math.max(parseInt(query("limit") || "30", 10) || 30, 1), 100);
+  const offset = Math.max(parseInt(query("offset") || "0", 10) || 0, 0);
+  return { emoji: emoji || undefined, limit, offset };
+}
@@ -0,0 +1,48 @@
+import { db, repoReadTokens } from "@gitbruv/db";
+import { eq, and, gt } from "drizzle-orm";
+import { createHash } from "crypto";
+
+export const READ_TOKEN_PREFIX = "gbr_";
+export const MAX_READ_TOKEN_TTL_DAYS = 90;
+
+export function generateReadToken(): string {
+  const array = new Uint8Array(32);
+  crypto.getRandomValues(array);
+  return READ_TOKEN_PREFIX + Array.from(array, (byte) => byte.toString(16).padStart(2, "0")).join("");
+}
+
+export function hashReadToken(token: string): string {
+  return createHash("sha256").update(token).digest("hex");
+}
+
+export function normalizeTokenPath(path: string): string {
+  return path.replace(/^\/+|\/+$/g, "");
+}
+
+function pathInScope(scope: string, path: string) {
+  if (scope === "") return true;
+  const normalized = normalizeTokenPath(path);
+  return normalized === scope || normalized.startsWith(`${scope}/`);
+}
+
+export async function checkReadToken(repoId: string, token: string | undefined, path: string): Promise<boolean> {
+  if (!token || !token.startsWith(READ_TOKEN_PREFIX)) {
+    return false;
+  }
+
+  const row = await db.query.repoReadTokens.findFirst({
+    where: and(
+      eq(repoReadTokens.tokenHash, hashReadToken(token)),
+      eq(repoReadTokens.repositoryId, repoId),
+      gt(repoReadTokens.expiresAt, new Date())
+    ),
+  });
+
+  if (!row || !pathInScope(row.path, path)) {
+    return false;
+  }
+
+  await db.update(repoReadTokens).set({ lastUsedAt: new Date() }).where(eq(repoReadTokens.id, row.id));
+
+  return true;
+}
@@ -0,0 +1,39 @@
+import { db, refLog } from "@gitbruv/db";
+import { trackTask } from "./metrics";
+import { ZERO_OID } from "./git/ref-transaction";
+
+export type RefLogSource = "push" | "commit" | "patch" | "merge" | "branch_delete" | "restore";
+
+// Refs are stored fully qualified so branch names and tags can't collide in the log.
+function qualify(ref: string) {
+  return ref.startsWith("refs/") ? ref : `refs/heads/${ref}`;
+}
+
+// Best-effort like the activity feed: the ref has already moved, so a failed insert is logged
+// rather than turning a successful push or merge into an error.
+export function recordRefUpdates(
+  repositoryId: string,
+  actorId: string | null | undefined,
+  source: RefLogSource,
+  updates: { ref: string; oldOid: string | null; newOid: string | null }[]
+) {
+  if (updates.length === 0) return;
+
+  const task = () =>
+    db
+      .insert(refLog)
+      .values(
+        updates.map((update) => ({
+          repositoryId,
+          actorId: actorId ?? null,
+          source,
+          ref: qualify(update.ref),
+          oldOid: update.oldOid ?? ZERO_OID,
+          newOid: update.newOid ?? ZERO_OID,
+        }))
+      )
+      .catch((error) => {
+        console.error(`[API] Failed to record ${source} ref updates:`, error);
+      });
+  trackTask("ref_log", task);
+}
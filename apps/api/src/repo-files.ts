@@ -0,0 +1,31 @@
+import { db, repositories } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import { getFile, type GitStore } from "./git";
+import { LICENSE_FILES, detectLicense } from "./license";
+import { CODEOWNERS_PATHS, parseCodeOwners, replaceCodeOwnerRules } from "./code-owners";
+import { hasStorageFailure } from "./tracing";
+
+async function readFirst(store: GitStore, branch: string, paths: string[]) {
+  for (const path of paths) {
+    const file = await getFile(store.fs, store.dir, branch, path);
+    if (file) return file.content;
+  }
+  return null;
+}
+
+// Re-reads LICENSE and CODEOWNERS from the default branch after a push. A file that has been
+// removed clears what was detected from it.
+export async function refreshRepoFiles(repositoryId: string, store: GitStore, branch: string) {
+  const [licenseContent, codeOwnersContent] = await Promise.all([
+    readFirst(store, branch, LICENSE_FILES),
+    readFirst(store, branch, CODEOWNERS_PATHS),
+  ]);
+  // getFile reports unreadable files as missing; don't clear the detected values over an outage.
+  if (hasStorageFailure()) return;
+
+  await db
+    .update(repositories)
+    .set({ license: licenseContent === null ? null : detectLicense(licenseContent) })
+    .where(eq(repositories.id, repositoryId));
+  await replaceCodeOwnerRules(repositoryId, codeOwnersContent === null ? [] : parseCodeOwners(codeOwnersContent));
+}
@@ -0,0 +1,165 @@
+import { db, repositories } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import git from "isomorphic-git";
+import { config } from "./config";
+import { putObject } from "./s3";
+import { storageLayout } from "./storage-layout";
+import { trackTask } from "./metrics";
+import { recordRefUpdates } from "./ref-log";
+import { refreshRepoFiles } from "./repo-files";
+import { scheduleCommitCounts } from "./jobs/commit-counts";
+import { rebuildRepoMetadata, type MetadataRepair } from "./branch-metadata";
+import { readBundleHeader } from "./git/bundle";
+import { PackFormatError } from "./git/pack-parse";
+import { unpackPackFile, storeLooseObject } from "./git/unpack";
+import { hasObject } from "./git/upload-pack";
+import { applyRefUpdates, ZERO_OID, type RefUpdate } from "./git/ref-transaction";
+import { acquirePushLock } from "./git/push-lock";
+import { clearPackCache } from "./git/pack-cache";
+import { updateReachabilityIndex } from "./git/reachability";
+import type { GitStore } from "./git";
+
+export class RestoreError extends Error {
+  constructor(
+    message: string,
+    readonly status: 400 | 409
+  ) {
+    super(message);
+    this.name = "RestoreError";
+  }
+}
+
+export type RestoreResult = {
+  refs: { name: string; oid: string }[];
+  deleted: string[];
+  skipped: string[];
+  objectCount: number;
+  defaultBranch: string;
+  metadata: Record<Exclude<MetadataRepair, "current">, string[]>;
+};
+
+async function listRefs(store: GitStore): Promise<Map<string, string>> {
+  const refs = new Map<string, string>();
+  const [branches, tags] = await Promise.all([
+    git.listBranches({ fs: store.fs, dir: store.dir }).catch(() => [] as string[]),
+    git.listTags({ fs: store.fs, dir: store.dir }).catch(() => [] as string[]),
+  ]);
+  for (const name of [...branches.map((b) => `refs/heads/${b}`), ...tags.map((t) => `refs/tags/${t}`)]) {
+    refs.set(name, await git.resolveRef({ fs: store.fs, dir: store.dir, ref: name }));
+  }
+  return refs;
+}
+
+// Keeps the current default branch when the bundle has it; otherwise follows the bundle's HEAD,
+// falling back to the first branch.
+function pickDefaultBranch(current: string, bundle: { refs: { name: string; oid: string }[]; head: string | null }): string | null {
+  const branches = bundle.refs.filter((ref) => ref.name.startsWith("refs/heads/"));
+  if (branches.length === 0) return null;
+  if (branches.some((ref) => ref.name === `refs/heads/${current}`)) return current;
+  const chosen = branches.find((ref) => ref.oid === bundle.head) ?? branches[0];
+  return chosen.name.slice("refs/heads/".length);
+}
+
+// Writes a bundle's objects and refs into an existing repository, for recovering from a bundle
+// export after storage was lost. An empty repository takes the bundle as-is; one with refs is
+// only overwritten with `force`, in which case refs the bundle doesn't have are deleted, so the
+// result matches the bundle exactly.
+export async function restoreFromBundle(
+  repo: { id: string; ownerId: string; name: string; defaultBranch: string },
+  store: GitStore,
+  bundlePath: string,
+  options: { force: boolean; actorId: string }
+): Promise<RestoreResult> {
+  let bundle;
+  try {
+    bundle = await readBundleHeader(bundlePath);
+  } catch (error) {
+    if (error instanceof PackFormatError) throw new RestoreError(error.message, 400);
+    throw error;
+  }
+
+  const lock = await acquirePushLock(repo.id);
+  if (!lock) {
+    throw new RestoreError("A push to this repository is in progress, try again", 409);
+  }
+
+  try {
+    const existing = await listRefs(store);
+    if (existing.size > 0 && !options.force) {
+      throw new RestoreError("Repository is not empty; restore with force to replace its branches and tags", 409);
+    }
+
+    for (const oid of bundle.prerequisites) {
+      if (!(await hasObject(store, oid))) {
+        throw new RestoreError(`Bundle is incremental and depends on commit ${oid}, which this repository doesn't have`, 400);
+      }
+    }
+
+    const unpacked = await unpackPackFile(
+      { path: bundlePath, offset: bundle.packOffset },
+      (oid, type, object) => storeLooseObject(store, oid, type, object),
+      store,
+      config.git.maxBlobBytes
+    );
+    if (!unpacked.success) {
+      throw new RestoreError(`Bundle pack is invalid: ${unpacked.error}`, 400);
+    }
+
+    for (const ref of bundle.refs) {
+      if (!unpacked.oids?.has(ref.oid) && !(await hasObject(store, ref.oid))) {
+        throw new RestoreError(`Bundle is missing the object ${ref.name} points at`, 400);
+      }
+    }
+
+    const wanted = new Map(bundle.refs.map((ref) => [ref.name, ref.oid]));
+    const updates: RefUpdate[] = [
+      ...bundle.refs
+        .filter((ref) => existing.get(ref.name) !== ref.oid)
+        .map((ref) => ({ ref: ref.name, oldOid: existing.get(ref.name) ?? ZERO_OID, newOid: ref.oid })),
+      ...[...existing]
+        .filter(([name]) => !wanted.has(name))
+        .map(([name, oid]) => ({ ref: name, oldOid: oid, newOid: ZERO_OID })),
+    ];
+
+    const repoPrefix = storageLayout.repoPrefix(repo.ownerId, repo.name);
+    const errors = await applyRefUpdates(repo.id, repoPrefix, updates, true);
+    if (errors.size > 0) {
+      const [ref, reason] = [...errors][0];
+      throw new RestoreError(`Could not update ${ref}: ${reason}`, 409);
+    }
+    recordRefUpdates(repo.id, options.actorId, "restore", updates);
+
+    const defaultBranch = pickDefaultBranch(repo.defaultBranch, bundle) ?? repo.defaultBranch;
+    if (defaultBranch !== repo.defaultBranch || existing.size === 0) {
+      await putObject(storageLayout.refKey(repoPrefix, "HEAD"), `ref: refs/heads/${defaultBranch}\n`);
+      await db.update(repositories).set({ defaultBranch, updatedAt: new Date() }).where(eq(repositories.id, repo.id));
+    }
+
+    const metadata = await rebuildRepoMetadata(repo.id, store);
+
+    trackTask("pack_cache", () => clearPackCache(store)).catch((error) => {
+      console.error("[API] restore: clearing cached packs failed:", error);
+    });
+    trackTask("reachability_index", () => updateReachabilityIndex(store)).catch((error) => {
+      console.error("[API] restore: updating reachability index failed:", error);
+    });
+    trackTask("repo_files", () => refreshRepoFiles(repo.id, store, defaultBranch)).catch((error) => {
+      console.error("[API] restore: license/CODEOWNERS refresh failed:", error);
+    });
+    const tips = bundle.refs.filter((ref) => ref.name.startsWith("refs/heads/")).map((ref) => ref.oid);
+    if (tips.length > 0) {
+      scheduleCommitCounts({ id: repo.id, ownerId: repo.ownerId, name: repo.name }, tips);
+    }
+
+    return {
+      refs: bundle.refs,
+      deleted: updates.filter((update) => update.newOid === ZERO_OID).map((update) => update.ref),
+      skipped: bundle.skipped,
+      objectCount: unpacked.objectCount,
+      defaultBranch,
+      metadata: { rebuilt: metadata.rebuilt, removed: metadata.removed, failed: metadata.failed },
+    };
+  } finally {
+    await lock.release();
+  }
+}
@@ -0,0 +1,107 @@
+import { Hono } from "hono";
+import { db, users, repositories, contentReports } from "@gitbruv/db";
+import { eq, and } from "drizzle-orm";
+import { requireInternalAuth } from "../middleware/auth";
+import { listObjectsWithSize, classifyKey, STORAGE_CLASSES, type StorageClass } from "../s3";
+import { storageLayout, AVATARS_ROOT } from "../storage-layout";
+import { listReports } from "../moderation";
+import { decodeCursor } from "../pagination";
+import { apiError } from "../errors";
+
+const app = new Hono();
+
+app.use("/api/admin/*", requireInternalAuth);
+
+type ClassSummary = {
+  objects: number;
+  bytes: number;
+  oldest: Date | null;
+  newest: Date | null;
+};
+
+async function summarizePrefix(prefix: string) {
+  const objects = await listObjectsWithSize(prefix);
+
+  const classes = Object.fromEntries(
+    STORAGE_CLASSES.map((cls) => [cls, { objects: 0, bytes: 0, oldest: null, newest: null }])
+  ) as Record<StorageClass, ClassSummary>;
+
+  for (const obj of objects) {
+    const summary = classes[classifyKey(obj.key)];
+    summary.objects++;
+    summary.bytes += obj.size;
+    if (obj.lastModified) {
+      if (!summary.oldest || obj.lastModified < summary.oldest) summary.oldest = obj.lastModified;
+      if (!summary.newest || obj.lastModified > summary.newest) summary.newest = obj.lastModified;
+    }
+  }
+
+  return {
+    totalObjects: objects.length,
+    totalBytes: objects.reduce((sum, obj) => sum + obj.size, 0),
+    classes,
+  };
+}
+
+app.get("/api/admin/storage/repos/:owner/:name", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const [row] = await db
+    .select({ userId: users.id, name: repositories.name })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, owner), eq(repositories.name, name)))
+    .limit(1);
+
+  if (!row) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const prefix = storageLayout.repoContents(storageLayout.repoPrefix(row.userId, row.name));
+  return c.json({ prefix, ...(await summarizePrefix(prefix)) });
+});
+
+app.get("/api/admin/storage/avatars", async (c) => {
+  return c.json({ prefix: `${AVATARS_ROOT}/`, ...(await summarizePrefix(`${AVATARS_ROOT}/`)) });
+});
+
+app.get("/api/admin/reports", async (c) => {
+  const status = c.req.query("status") || "open";
+  const limit = Math.min(parseInt(c.req.query("limit") || "50", 10) || 50, 200);
+  const cursorParam = c.req.query("cursor");
+
+  if (status !== "open" && status !== "resolved" && status !== "dismissed") {
+    return apiError(c, 400, "Invalid status");
+  }
+
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  return c.json(await listReports([eq(contentReports.status, status)], cursor, limit));
+});
+
+app.patch("/api/admin/reports/:id", async (c) => {
+  const body = await c.req.json<{ status?: string }>();
+
+  if (body.status !== "resolved" && body.status !== "dismissed" && body.status !== "open") {
+    return apiError(c, 400, "status must be 'open', 'resolved' or 'dismissed'");
+  }
+
+  const reopening = body.status === "open";
+  const updated = await db
+    .update(contentReports)
+    .set({ status: body.status, resolvedById: null, resolvedAt: reopening ? null : new Date() })
+    .where(eq(contentReports.id, c.req.param("id")))
+    .returning({ id: contentReports.id });
+
+  if (updated.length === 0) {
+    return apiError(c, 404, "Report not found");
+  }
+
+  return c.json({ success: true });
+});
+
+export default app;
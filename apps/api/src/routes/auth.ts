@@ -3,6 +3,7 @@ import { db, users, verifications, accounts } from '@gitbruv/db';
 import { getAuth, verifyCredentials } from '../auth';
 import { eq, and, gt } from 'drizzle-orm';
 import { Hono } from 'hono';
+import { apiError } from '../errors';
 
 const app = new Hono();
 
@@ -13,9 +14,9 @@ function generateToken(): string {
 }
 
 app.post('/api/auth/verify-credentials', async (c) => {
-  const response = await verifyCredentials(c.req.raw);
+  const user = await verifyCredentials(c.req.raw);
 
-  return response;
+  return c.json({ user });
 });
 
 app.post('/api/auth/forgot-password', async (c) => {
@@ -24,7 +25,7 @@ app.post('/api/auth/forgot-password', async (c) => {
     const email = body?.email?.toLowerCase().trim();
 
     if (!email || !email.includes('@')) {
-      return c.json({ error: 'Valid email is required' }, 400);
+      return apiError(c, 400, 'Valid email is required');
     }
 
     const user = await db.query.users.findFirst({
@@ -54,7 +55,7 @@ app.post('/api/auth/forgot-password', async (c) => {
     return c.json({ success: true });
   } catch (err) {
     console.error('[Auth] Forgot password error:', err);
-    return c.json({ error: 'Failed to process request' }, 500);
+    return apiError(c, 500, 'Failed to process request');
   }
 });
 
@@ -64,11 +65,11 @@ app.post('/api/auth/reset-password', async (c) => {
     const { token, password } = body || {};
 
     if (!token || typeof token !== 'string') {
-      return c.json({ error: 'Token is required' }, 400);
+      return apiError(c, 400, 'Token is required');
     }
 
     if (!password || typeof password !== 'string' || password.length < 8) {
-      return c.json({ error: 'Password must be at least 8 characters' }, 400);
+      return apiError(c, 400, 'Password must be at least 8 characters');
     }
 
     const verification = await db.query.verifications.findFirst({
@@ -76,7 +77,7 @@ app.post('/api/auth/reset-password', async (c) => {
     });
 
     if (!verification || !verification.identifier.startsWith('password-reset:')) {
-      return c.json({ error: 'Invalid or expired token' }, 400);
+      return apiError(c, 400, 'Invalid or expired token');
     }
 
     const email = verification.identifier.replace('password-reset:', '');
@@ -86,7 +87,7 @@ app.post('/api/auth/reset-password', async (c) => {
     });
 
     if (!user) {
-      return c.json({ error: 'User not found' }, 404);
+      return apiError(c, 404, 'User not found');
     }
 
     const hashedPassword = await Bun.password.hash(password, {
@@ -104,7 +105,7 @@ app.post('/api/auth/reset-password', async (c) => {
     return c.json({ success: true });
   } catch (err) {
     console.error('[Auth] Reset password error:', err);
-    return c.json({ error: 'Failed to reset password' }, 500);
+    return apiError(c, 500, 'Failed to reset password');
   }
 });
 
@@ -114,7 +115,7 @@ app.post('/api/auth/resend-verification', async (c) => {
     const email = body?.email?.toLowerCase().trim();
 
     if (!email || !email.includes('@')) {
-      return c.json({ error: 'Valid email is required' }, 400);
+      return apiError(c, 400, 'Valid email is required');
     }
 
     const user = await db.query.users.findFirst({
@@ -148,7 +149,7 @@ app.post('/api/auth/resend-verification', async (c) => {
     return c.json({ success: true });
   } catch (err) {
     console.error('[Auth] Resend verification error:', err);
-    return c.json({ error: 'Failed to send verification email' }, 500);
+    return apiError(c, 500, 'Failed to send verification email');
   }
 });
 
@@ -157,7 +158,7 @@ app.get('/api/auth/verify-email', async (c) => {
     const token = c.req.query('token');
 
     if (!token) {
-      return c.json({ error: 'Token is required' }, 400);
+      return apiError(c, 400, 'Token is required');
     }
 
     const verification = await db.query.verifications.findFirst({
@@ -165,7 +166,7 @@ app.get('/api/auth/verify-email', async (c) => {
     });
 
     if (!verification || !verification.identifier.startsWith('email-verification:')) {
-      return c.json({ error: 'Invalid or expired token' }, 400);
+      return apiError(c, 400, 'Invalid or expired token');
     }
 
     const email = verification.identifier.replace('email-verification:', '');
@@ -180,7 +181,7 @@ app.get('/api/auth/verify-email', async (c) => {
     return c.json({ success: true });
   } catch (err) {
     console.error('[Auth] Verify email error:', err);
-    return c.json({ error: 'Failed to verify email' }, 500);
+    return apiError(c, 500, 'Failed to verify email');
   }
 });
 
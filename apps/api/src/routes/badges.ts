@@ -0,0 +1,131 @@
+import { Hono, type Context } from "hono";
+import git from "isomorphic-git";
+import { db, users, repositories, issues } from "@gitbruv/db";
+import { eq, and, sql } from "drizzle-orm";
+import { createGitStore, getFileCached, resolveRefOid, type GitStore } from "../git";
+import { getCached, setCache, repoCache, CACHE_TTL } from "../cache";
+import { getCombinedStatuses } from "../commit-statuses";
+import { renderBadge, badgeEtag, latestTag, BADGE_COLORS } from "../badges";
+import { detectLicense, LICENSE_FILES } from "../license";
+import { hasStorageFailure } from "../tracing";
+import { apiError } from "../errors";
+
+const app = new Hono();
+
+// Badges are embedded in READMEs and fetched through image proxies, so they're only served for
+// public repositories and never depend on who is asking.
+async function getPublicRepo(owner: string, name: string) {
+  const [row] = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      ownerId: repositories.ownerId,
+      defaultBranch: repositories.defaultBranch,
+      license: repositories.license,
+    })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, owner), eq(repositories.name, name), eq(repositories.visibility, "public")))
+    .limit(1);
+  return row ?? null;
+}
+
+// Values read from git only change when refs do, so they're cached under the repository's
+// refs version and dropped automatically by the next push.
+async function cachedGitValue(store: GitStore, badge: string, compute: () => Promise<string>) {
+  const version = await repoCache.getRefsVersion(store.ownerId, store.repoName);
+  const key = repoCache.badgeKey(store.ownerId, store.repoName, badge, version);
+  const cached = await getCached<string>(key);
+  if (cached !== null) {
+    return cached;
+  }
+  const value = await compute();
+  if (!hasStorageFailure()) {
+    await setCache(key, value, CACHE_TTL.badge);
+  }
+  return value;
+}
+
+function badgeResponse(c: Context, label: string, message: string, color: string) {
+  const svg = renderBadge(label, message, color);
+  const etag = badgeEtag(svg);
+  const headers = {
+    "Content-Type": "image/svg+xml; charset=utf-8",
+    "Cache-Control": "public, max-age=300",
+    ETag: etag,
+  };
+  if (c.req.header("if-none-match") === etag) {
+    return new Response(null, { status: 304, headers });
+  }
+  return new Response(svg, { status: 200, headers });
+}
+
+app.get("/badges/:owner/:name/build.svg", async (c) => {
+  const repo = await getPublicRepo(c.req.param("owner"), c.req.param("name"));
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const branch = c.req.query("branch") || repo.defaultBranch;
+  const oid = await resolveRefOid(createGitStore(repo.ownerId, repo.name), branch).catch(() => null);
+  const state = oid ? (await getCombinedStatuses(repo.id, [oid])).get(oid) : undefined;
+
+  if (state === "success") return badgeResponse(c, "build", "passing", BADGE_COLORS.success);
+  if (state === "failure") return badgeResponse(c, "build", "failing", BADGE_COLORS.failure);
+  if (state === "pending") return badgeResponse(c, "build", "pending", BADGE_COLORS.pending);
+  return badgeResponse(c, "build", "unknown", BADGE_COLORS.inactive);
+});
+
+app.get("/badges/:owner/:name/issues.svg", async (c) => {
+  const repo = await getPublicRepo(c.req.param("owner"), c.req.param("name"));
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const [result] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(issues)
+    .where(and(eq(issues.repositoryId, repo.id), eq(issues.state, "open")));
+  const count = Number(result?.count) || 0;
+
+  return badgeResponse(c, "issues", `${count} open`, count > 0 ? BADGE_COLORS.pending : BADGE_COLORS.success);
+});
+
+app.get("/badges/:owner/:name/license.svg", async (c) => {
+  const repo = await getPublicRepo(c.req.param("owner"), c.req.param("name"));
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  // Detected on push; repositories that haven't been pushed to since detection shipped fall back
+  // to reading the file.
+  const store = createGitStore(repo.ownerId, repo.name);
+  const license = repo.license ?? await cachedGitValue(store, "license", async () => {
+    for (const path of LICENSE_FILES) {
+      const file = await getFileCached(store, repo.defaultBranch, path);
+      if (file) return detectLicense(file.content);
+    }
+    return "";
+  });
+
+  return license
+    ? badgeResponse(c, "license", license, BADGE_COLORS.info)
+    : badgeResponse(c, "license", "none", BADGE_COLORS.inactive);
+});
+
+app.get("/badges/:owner/:name/tag.svg", async (c) => {
+  const repo = await getPublicRepo(c.req.param("owner"), c.req.param("name"));
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const store = createGitStore(repo.ownerId, repo.name);
+  const tag = await cachedGitValue(store, "tag", async () => {
+    const tags = await git.listTags({ fs: store.fs, dir: store.dir }).catch(() => [] as string[]);
+    return latestTag(tags) ?? "";
+  });
+
+  return tag ? badgeResponse(c, "tag", tag, BADGE_COLORS.info) : badgeResponse(c, "tag", "none", BADGE_COLORS.inactive);
+});
+
+export default app;
@@ -0,0 +1,187 @@
+import { Hono } from "hono";
+import { db, users, repositories, stars, follows, issues, issueAssignees, pullRequests, prReviewers, activityEvents } from "@gitbruv/db";
+import { eq, and, or, desc, gt, inArray, sql } from "drizzle-orm";
+import { alias } from "drizzle-orm/pg-core";
+import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+
+const app = new Hono<{ Variables: AuthVariables }>();
+
+app.use("*", authMiddleware);
+
+const REPO_LIMIT = 10;
+const ITEM_LIMIT = 20;
+const EVENTS_PER_REPO = 3;
+
+// First visits (and the first one after this shipped) look back a week for starred-repo pushes.
+const DEFAULT_LOOKBACK_MS = 7 * 24 * 60 * 60 * 1000;
+
+const repoOwners = alias(users, "repo_owners");
+const eventActors = alias(users, "event_actors");
+
+function visibleTo(userId: string) {
+  return or(eq(repositories.visibility, "public"), eq(repositories.ownerId, userId));
+}
+
+async function getOwnRepositories(userId: string) {
+  const repos = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      description: repositories.description,
+      visibility: repositories.visibility,
+      updatedAt: repositories.updatedAt,
+    })
+    .from(repositories)
+    .where(eq(repositories.ownerId, userId))
+    .orderBy(desc(repositories.updatedAt))
+    .limit(REPO_LIMIT);
+
+  if (repos.length === 0) return [];
+
+  const events = await db
+    .select({
+      id: activityEvents.id,
+      type: activityEvents.type,
+      repositoryId: activityEvents.repositoryId,
+      payload: activityEvents.payload,
+      createdAt: activityEvents.createdAt,
+      actor: eventActors.username,
+    })
+    .from(activityEvents)
+    .innerJoin(eventActors, eq(eventActors.id, activityEvents.actorId))
+    .where(
+      inArray(
+        activityEvents.id,
+        // Latest few events per repository, ranked in one pass instead of a query per repo.
+        sql`(SELECT id FROM (
+          SELECT ${activityEvents.id} AS id,
+            ROW_NUMBER() OVER (PARTITION BY ${activityEvents.repositoryId} ORDER BY ${activityEvents.createdAt} DESC) AS position
+          FROM ${activityEvents}
+          WHERE ${inArray(activityEvents.repositoryId, repos.map((repo) => repo.id))}
+        ) ranked WHERE position <= ${EVENTS_PER_REPO})`
+      )
+    )
+    .orderBy(desc(activityEvents.createdAt));
+
+  const eventsByRepo = new Map<string, typeof events>();
+  for (const event of events) {
+    const list = eventsByRepo.get(event.repositoryId) ?? [];
+    list.push(event);
+    eventsByRepo.set(event.repositoryId, list);
+  }
+
+  return repos.map((repo) => ({
+    ...repo,
+    recentActivity: (eventsByRepo.get(repo.id) ?? []).map(({ repositoryId: _repositoryId, ...event }) => event),
+  }));
+}
+
+async function getStarredWithNewCommits(userId: string, since: Date) {
+  const lastPushedAt = sql<Date>`MAX(${activityEvents.createdAt})`;
+  const rows = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      owner: repoOwners.username,
+      description: repositories.description,
+      newPushes: sql<number>`COUNT(*)`,
+      lastPushedAt,
+    })
+    .from(stars)
+    .innerJoin(repositories, eq(repositories.id, stars.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .innerJoin(
+      activityEvents,
+      and(eq(activityEvents.repositoryId, repositories.id), eq(activityEvents.type, "pushed"), gt(activityEvents.createdAt, since))
+    )
+    .where(and(eq(stars.userId, userId), visibleTo(userId)))
+    .groupBy(repositories.id, repoOwners.username)
+    .orderBy(desc(lastPushedAt))
+    .limit(REPO_LIMIT);
+
+  return rows.map((row) => ({ ...row, newPushes: Number(row.newPushes) }));
+}
+
+// Only public repositories, even ones the viewer could otherwise see: the feed is about what the
+// people they follow are doing in the open, matching their public event stream.
+async function getFollowedActivity(userId: string) {
+  return db
+    .select({
+      id: activityEvents.id,
+      type: activityEvents.type,
+      payload: activityEvents.payload,
+      createdAt: activityEvents.createdAt,
+      actor: { id: eventActors.id, username: eventActors.username, name: eventActors.name, avatarUrl: eventActors.avatarUrl },
+      repository: { id: repositories.id, name: repositories.name, owner: repoOwners.username },
+    })
+    .from(follows)
+    .innerJoin(activityEvents, eq(activityEvents.actorId, follows.followingId))
+    .innerJoin(eventActors, eq(eventActors.id, activityEvents.actorId))
+    .innerJoin(repositories, eq(repositories.id, activityEvents.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(and(eq(follows.followerId, userId), eq(repositories.visibility, "public")))
+    .orderBy(desc(activityEvents.createdAt))
+    .limit(ITEM_LIMIT);
+}
+
+async function getAssignedIssues(userId: string) {
+  return db
+    .select({
+      id: issues.id,
+      number: issues.number,
+      title: issues.title,
+      updatedAt: issues.updatedAt,
+      repository: { name: repositories.name, owner: repoOwners.username },
+    })
+    .from(issueAssignees)
+    .innerJoin(issues, eq(issues.id, issueAssignees.issueId))
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(and(eq(issueAssignees.userId, userId), eq(issues.state, "open"), visibleTo(userId)))
+    .orderBy(desc(issues.updatedAt))
+    .limit(ITEM_LIMIT);
+}
+
+async function getReviewRequests(userId: string) {
+  return db
+    .select({
+      id: pullRequests.id,
+      number: pullRequests.number,
+      title: pullRequests.title,
+      isDraft: pullRequests.isDraft,
+      requestedAt: prReviewers.requestedAt,
+      updatedAt: pullRequests.updatedAt,
+      repository: { name: repositories.name, owner: repoOwners.username },
+    })
+    .from(prReviewers)
+    .innerJoin(pullRequests, eq(pullRequests.id, prReviewers.pullRequestId))
+    .innerJoin(repositories, eq(repositories.id, pullRequests.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(and(eq(prReviewers.userId, userId), eq(pullRequests.state, "open"), visibleTo(userId)))
+    .orderBy(desc(prReviewers.requestedAt))
+    .limit(ITEM_LIMIT);
+}
+
+app.get("/api/dashboard", requireAuth, async (c) => {
+  const user = c.get("user")!;
+
+  const visit = await db.query.users.findFirst({
+    where: eq(users.id, user.id),
+    columns: { dashboardVisitedAt: true },
+  });
+  const since = visit?.dashboardVisitedAt ?? new Date(Date.now() - DEFAULT_LOOKBACK_MS);
+
+  const [repos, starredUpdates, followedActivity, assignedIssues, reviewRequests] = await Promise.all([
+    getOwnRepositories(user.id),
+    getStarredWithNewCommits(user.id, since),
+    getFollowedActivity(user.id),
+    getAssignedIssues(user.id),
+    getReviewRequests(user.id),
+  ]);
+
+  await db.update(users).set({ dashboardVisitedAt: new Date() }).where(eq(users.id, user.id));
+
+  return c.json({ since, repos, starredUpdates, followedActivity, assignedIssues, reviewRequests });
+});
+
+export default app;
@@ -6,17 +6,23 @@ import {
   discussions,
   discussionCategories,
   discussionComments,
-  discussionReactions,
 } from "@gitbruv/db";
 import { eq, sql, and, desc } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import {
+  VALID_EMOJIS,
+  getReactionsGrouped,
+  toggleReaction,
+  listReactionUsers,
+  deleteReactions,
+  parseReactionListQuery,
+} from "../reactions";
+import { apiError } from "../errors";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
-const VALID_EMOJIS = ["+1", "-1", "laugh", "hooray", "confused", "heart", "rocket", "eyes"];
-
 async function getRepoAndCheckAccess(owner: string, name: string, userId?: string) {
   const result = await db
     .select({
@@ -39,58 +45,6 @@ async function getRepoAndCheckAccess(owner: string, name: string, userId?: strin
   return { repoId: row.id, ownerId: row.ownerId };
 }
 
-async function getDiscussionReactionsGrouped(discussionId: string, userId?: string) {
-  const counts = await db
-    .select({
-      emoji: discussionReactions.emoji,
-      count: sql<number>`COUNT(*)`,
-    })
-    .from(discussionReactions)
-    .where(eq(discussionReactions.discussionId, discussionId))
-    .groupBy(discussionReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: discussionReactions.emoji })
-      .from(discussionReactions)
-      .where(and(eq(discussionReactions.discussionId, discussionId), eq(discussionReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
-}
-
-async function getCommentReactionsGrouped(commentId: string, userId?: string) {
-  const counts = await db
-    .select({
-      emoji: discussionReactions.emoji,
-      count: sql<number>`COUNT(*)`,
-    })
-    .from(discussionReactions)
-    .where(eq(discussionReactions.commentId, commentId))
-    .groupBy(discussionReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: discussionReactions.emoji })
-      .from(discussionReactions)
-      .where(and(eq(discussionReactions.commentId, commentId), eq(discussionReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
-}
-
 async function enrichDiscussion(discussion: any, currentUserId?: string) {
   const author = await db.query.users.findFirst({
     where: eq(users.id, discussion.authorId),
@@ -108,7 +62,7 @@ async function enrichDiscussion(discussion: any, currentUserId?: string) {
     .from(discussionComments)
     .where(eq(discussionComments.discussionId, discussion.id));
 
-  const reactions = await getDiscussionReactionsGrouped(discussion.id, currentUserId);
+  const reactions = await getReactionsGrouped("discussion", discussion.id, currentUserId);
 
   return {
     id: discussion.id,
@@ -138,7 +92,7 @@ app.get("/api/repositories/:owner/:name/discussions", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const conditions = [eq(discussions.repositoryId, repoAccess.repoId)];
@@ -169,7 +123,7 @@ app.get("/api/repositories/:owner/:name/discussions/categories", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const categories = await db
@@ -189,15 +143,15 @@ app.post("/api/repositories/:owner/:name/discussions/categories", requireAuth, a
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (user.id !== repoAccess.ownerId) {
-    return c.json({ error: "Only repo owner can create categories" }, 403);
+    return apiError(c, 403, "Only repo owner can create categories");
   }
 
   if (!body.name?.trim()) {
-    return c.json({ error: "Category name is required" }, 400);
+    return apiError(c, 400, "Category name is required");
   }
 
   const [inserted] = await db
@@ -221,7 +175,7 @@ app.get("/api/repositories/:owner/:name/discussions/:number", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const discussion = await db.query.discussions.findFirst({
@@ -229,7 +183,7 @@ app.get("/api/repositories/:owner/:name/discussions/:number", async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const enriched = await enrichDiscussion(discussion, currentUser?.id);
@@ -244,15 +198,15 @@ app.post("/api/repositories/:owner/:name/discussions", requireAuth, async (c) =>
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (!body.title?.trim()) {
-    return c.json({ error: "Title is required" }, 400);
+    return apiError(c, 400, "Title is required");
   }
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Body is required" }, 400);
+    return apiError(c, 400, "Body is required");
   }
 
   const [maxNumber] = await db
@@ -286,7 +240,7 @@ app.patch("/api/discussions/:id", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -294,7 +248,7 @@ app.patch("/api/discussions/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== discussion.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   const updates: Record<string, any> = { updatedAt: new Date() };
@@ -316,7 +270,7 @@ app.patch("/api/discussions/:id/pin", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -324,7 +278,7 @@ app.patch("/api/discussions/:id/pin", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can pin discussions" }, 403);
+    return apiError(c, 403, "Only repo owner can pin discussions");
   }
 
   await db
@@ -344,7 +298,7 @@ app.patch("/api/discussions/:id/lock", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -352,7 +306,7 @@ app.patch("/api/discussions/:id/lock", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can lock discussions" }, 403);
+    return apiError(c, 403, "Only repo owner can lock discussions");
   }
 
   await db
@@ -372,7 +326,7 @@ app.delete("/api/discussions/:id", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -380,10 +334,16 @@ app.delete("/api/discussions/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete discussions" }, 403);
+    return apiError(c, 403, "Only repo owner can delete discussions");
   }
 
+  const comments = await db
+    .select({ id: discussionComments.id })
+    .from(discussionComments)
+    .where(eq(discussionComments.discussionId, id));
   await db.delete(discussions).where(eq(discussions.id, id));
+  await deleteReactions("discussion", [id]);
+  await deleteReactions("discussion_comment", comments.map((comment) => comment.id));
 
   return c.json({ success: true });
 });
@@ -397,7 +357,7 @@ app.get("/api/discussions/:id/comments", async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   const comments = await db
@@ -413,7 +373,7 @@ app.get("/api/discussions/:id/comments", async (c) => {
         columns: { id: true, username: true, name: true, avatarUrl: true },
       });
 
-      const reactions = await getCommentReactionsGrouped(comment.id, currentUser?.id);
+      const reactions = await getReactionsGrouped("discussion_comment", comment.id, currentUser?.id);
 
       return {
         id: comment.id,
@@ -437,7 +397,7 @@ app.post("/api/discussions/:id/comments", requireAuth, async (c) => {
   const body = await c.req.json<{ body: string; parentId?: string }>();
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Comment cannot be empty" }, 400);
+    return apiError(c, 400, "Comment cannot be empty");
   }
 
   const discussion = await db.query.discussions.findFirst({
@@ -445,11 +405,11 @@ app.post("/api/discussions/:id/comments", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   if (discussion.isLocked) {
-    return c.json({ error: "Discussion is locked" }, 403);
+    return apiError(c, 403, "Discussion is locked");
   }
 
   const [inserted] = await db
@@ -483,7 +443,7 @@ app.patch("/api/discussions/comments/:id/answer", requireAuth, async (c) => {
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
   }
 
   const discussion = await db.query.discussions.findFirst({
@@ -491,11 +451,11 @@ app.patch("/api/discussions/comments/:id/answer", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
   if (user.id !== discussion.authorId) {
-    return c.json({ error: "Only discussion author can mark answers" }, 403);
+    return apiError(c, 403, "Only discussion author can mark answers");
   }
 
   if (comment.isAnswer) {
@@ -536,7 +496,7 @@ app.post("/api/discussions/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
   const discussion = await db.query.discussions.findFirst({
@@ -544,28 +504,11 @@ app.post("/api/discussions/:id/reactions", requireAuth, async (c) => {
   });
 
   if (!discussion) {
-    return c.json({ error: "Discussion not found" }, 404);
+    return apiError(c, 404, "Discussion not found");
   }
 
-  const existing = await db.query.discussionReactions.findFirst({
-    where: and(
-      eq(discussionReactions.discussionId, id),
-      eq(discussionReactions.userId, user.id),
-      eq(discussionReactions.emoji, body.emoji)
-    ),
-  });
-
-  if (existing) {
-    await db.delete(discussionReactions).where(eq(discussionReactions.id, existing.id));
-    return c.json({ added: false });
-  } else {
-    await db.insert(discussionReactions).values({
-      discussionId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
-  }
+  const added = await toggleReaction("discussion", id, discussion.repositoryId, user.id, body.emoji);
+  return c.json({ added });
 });
 
 app.post("/api/discussions/comments/:id/reactions", requireAuth, async (c) => {
@@ -574,36 +517,66 @@ app.post("/api/discussions/comments/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
   const comment = await db.query.discussionComments.findFirst({
     where: eq(discussionComments.id, id),
+    with: { discussion: { columns: { repositoryId: true } } },
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
   }
 
-  const existing = await db.query.discussionReactions.findFirst({
-    where: and(
-      eq(discussionReactions.commentId, id),
-      eq(discussionReactions.userId, user.id),
-      eq(discussionReactions.emoji, body.emoji)
-    ),
+  const added = await toggleReaction("discussion_comment", id, comment.discussion.repositoryId, user.id, body.emoji);
+  return c.json({ added });
+});
+
+async function canViewDiscussionRepo(discussionId: string, userId?: string) {
+  const [row] = await db
+    .select({ ownerId: repositories.ownerId, visibility: repositories.visibility })
+    .from(discussions)
+    .innerJoin(repositories, eq(repositories.id, discussions.repositoryId))
+    .where(eq(discussions.id, discussionId))
+    .limit(1);
+  return !!row && (row.visibility !== "private" || row.ownerId === userId);
+}
+
+app.get("/api/discussions/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
+  }
+
+  if (!(await canViewDiscussionRepo(id, currentUser?.id))) {
+    return apiError(c, 404, "Discussion not found");
+  }
+
+  return c.json(await listReactionUsers("discussion", id, query));
+});
+
+app.get("/api/discussions/comments/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
+  }
+
+  const comment = await db.query.discussionComments.findFirst({
+    where: eq(discussionComments.id, id),
   });
 
-  if (existing) {
-    await db.delete(discussionReactions).where(eq(discussionReactions.id, existing.id));
-    return c.json({ added: false });
-  } else {
-    await db.insert(discussionReactions).values({
-      commentId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
+  if (!comment || !(await canViewDiscussionRepo(comment.discussionId, currentUser?.id))) {
+    return apiError(c, 404, "Comment not found");
   }
+
+  return c.json(await listReactionUsers("discussion_comment", id, query));
 });
 
 export default app;
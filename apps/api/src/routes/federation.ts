@@ -0,0 +1,232 @@
+import { Hono } from "hono";
+import { db, users, repositories, stars } from "@gitbruv/db";
+import { eq, sql, and, gt } from "drizzle-orm";
+import { alias, unionAll } from "drizzle-orm/pg-core";
+import { auth } from "../auth";
+import { getApiUrl, getWebUrl } from "../config";
+import { apiError } from "../errors";
+import { encodeCursor, decodeCursor, afterCursor, type KeysetPart } from "../pagination";
+
+const app = new Hono();
+
+const ACTIVITY_PAGE_SIZE = 50;
+
+const repoOwners = alias(users, "repo_owners");
+
+async function sign(document: Record<string, unknown>) {
+  const body = JSON.stringify(document);
+  const digest = new Bun.CryptoHasher("sha256").update(body).digest("base64url");
+  const { token } = await auth.api.signJWT({
+    body: {
+      payload: {
+        iss: getApiUrl(),
+        sub: document.id as string,
+        digest: `sha-256=${digest}`,
+      },
+    },
+  });
+
+  return {
+    document,
+    signature: {
+      type: "jwt",
+      jwksUri: `${getApiUrl()}/api/auth/jwks`,
+      token,
+    },
+  };
+}
+
+function actorId(username: string) {
+  return `${getApiUrl()}/api/federation/users/${username}`;
+}
+
+function repoId(owner: string, name: string) {
+  return `${getApiUrl()}/api/federation/repositories/${owner}/${name}`;
+}
+
+app.get("/.well-known/gitbruv-federation", (c) => {
+  const apiUrl = getApiUrl();
+  return c.json({
+    software: "gitbruv",
+    protocol: "gitbruv-federation",
+    version: 1,
+    instance: getWebUrl(),
+    jwksUri: `${apiUrl}/api/auth/jwks`,
+    endpoints: {
+      users: `${apiUrl}/api/federation/users/{username}`,
+      repositories: `${apiUrl}/api/federation/repositories/{owner}/{name}`,
+      activity: `${apiUrl}/api/federation/activity`,
+    },
+  });
+});
+
+app.get("/api/federation/users/:username", async (c) => {
+  const username = c.req.param("username");
+
+  const user = await db.query.users.findFirst({
+    where: eq(users.username, username),
+  });
+
+  if (!user) {
+    return apiError(c, 404, "User not found");
+  }
+
+  const [repoCount] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(repositories)
+    .where(and(eq(repositories.ownerId, user.id), eq(repositories.visibility, "public")));
+
+  return c.json(
+    await sign({
+      type: "Person",
+      id: actorId(user.username),
+      username: user.username,
+      name: user.name,
+      bio: user.bio,
+      avatarUrl: user.avatarUrl,
+      url: `${getWebUrl()}/${user.username}`,
+      publicRepositories: Number(repoCount?.count) || 0,
+      createdAt: user.createdAt,
+    })
+  );
+});
+
+app.get("/api/federation/repositories/:owner/:name", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const [row] = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      description: repositories.description,
+      visibility: repositories.visibility,
+      defaultBranch: repositories.defaultBranch,
+      createdAt: repositories.createdAt,
+      updatedAt: repositories.updatedAt,
+      username: users.username,
+    })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, owner), eq(repositories.name, name)))
+    .limit(1);
+
+  if (!row || row.visibility !== "public") {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const [starCount] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(stars)
+    .where(eq(stars.repositoryId, row.id));
+
+  return c.json(
+    await sign({
+      type: "Repository",
+      id: repoId(row.username, row.name),
+      name: row.name,
+      description: row.description,
+      owner: actorId(row.username),
+      defaultBranch: row.defaultBranch,
+      cloneUrl: `${getApiUrl()}/${row.username}/${row.name}.git`,
+      url: `${getWebUrl()}/${row.username}/${row.name}`,
+      starCount: Number(starCount?.count) || 0,
+      createdAt: row.createdAt,
+      updatedAt: row.updatedAt,
+    })
+  );
+});
+
+// Repository creations and stars merged into one stream, ordered by (created_at, kind, id) in SQL
+// and paged with a keyset cursor on that triple, so rows sharing a timestamp are neither skipped
+// nor repeated. `since` is only used for the first page.
+app.get("/api/federation/activity", async (c) => {
+  const cursorParam = c.req.query("cursor");
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "text", "text"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const sinceParam = c.req.query("since");
+  const since = sinceParam ? new Date(sinceParam) : new Date(0);
+  if (isNaN(since.getTime())) {
+    return apiError(c, 400, "Invalid since timestamp");
+  }
+
+  const createdKeyset: KeysetPart[] = [
+    { column: repositories.createdAt, type: "timestamp" },
+    { column: sql`'Create'`, type: "text" },
+    { column: sql`${repositories.id}::text`, type: "text" },
+  ];
+  const starredKeyset: KeysetPart[] = [
+    { column: stars.createdAt, type: "timestamp" },
+    { column: sql`'Star'`, type: "text" },
+    { column: sql`${stars.userId} || ':' || ${stars.repositoryId}::text`, type: "text" },
+  ];
+
+  const created = db
+    .select({
+      kind: sql<string>`'Create'`.as("kind"),
+      id: sql<string>`${repositories.id}::text`.as("id"),
+      createdAt: repositories.createdAt,
+      sortKey: sql<string>`${repositories.createdAt}::text`.as("sort_key"),
+      actor: users.username,
+      owner: sql<string>`${users.username}`.as("owner"),
+      name: repositories.name,
+    })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(
+      and(
+        eq(repositories.visibility, "public"),
+        cursor ? afterCursor(createdKeyset, cursor, "asc") : gt(repositories.createdAt, since)
+      )
+    );
+
+  const starred = db
+    .select({
+      kind: sql<string>`'Star'`.as("kind"),
+      id: sql<string>`${stars.userId} || ':' || ${stars.repositoryId}::text`.as("id"),
+      createdAt: stars.createdAt,
+      sortKey: sql<string>`${stars.createdAt}::text`.as("sort_key"),
+      actor: users.username,
+      owner: sql<string>`${repoOwners.username}`.as("owner"),
+      name: repositories.name,
+    })
+    .from(stars)
+    .innerJoin(users, eq(users.id, stars.userId))
+    .innerJoin(repositories, eq(repositories.id, stars.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(
+      and(
+        eq(repositories.visibility, "public"),
+        cursor ? afterCursor(starredKeyset, cursor, "asc") : gt(stars.createdAt, since)
+      )
+    );
+
+  const rows = await unionAll(created, starred)
+    .orderBy(sql`created_at`, sql`kind`, sql`id`)
+    .limit(ACTIVITY_PAGE_SIZE);
+
+  const items = rows.map((row) => ({
+    type: row.kind,
+    actor: actorId(row.actor),
+    object: repoId(row.owner, row.name),
+    published: row.createdAt,
+  }));
+  const last = rows[rows.length - 1];
+  const next = last ? encodeCursor([last.sortKey, last.kind, last.id]) : (cursorParam ?? null);
+
+  const self = cursorParam ? `cursor=${cursorParam}` : `since=${since.toISOString()}`;
+  return c.json(
+    await sign({
+      type: "OrderedCollection",
+      id: `${getApiUrl()}/api/federation/activity?${self}`,
+      totalItems: items.length,
+      next,
+      orderedItems: items,
+    })
+  );
+});
+
+export default app;
@@ -0,0 +1,283 @@
+import { Hono } from "hono";
+import { db, users, repositories, activityEvents } from "@gitbruv/db";
+import { eq, and, desc, sql, type SQL } from "drizzle-orm";
+import { alias } from "drizzle-orm/pg-core";
+import { createGitStore, getCommitsCached, resolveCommittish } from "../git";
+import { encodeCursor, decodeCursor, afterCursor } from "../pagination";
+import { getApiUrl, getWebUrl } from "../config";
+import { apiError } from "../errors";
+
+const app = new Hono();
+
+const FEED_ENTRY_LIMIT = 30;
+const MAX_EVENTS_PAGE_SIZE = 100;
+
+const repoOwners = alias(users, "repo_owners");
+
+type EventRow = {
+  id: string;
+  type: string;
+  payload: Record<string, unknown>;
+  createdAt: Date;
+  sortKey: string;
+  actor: { id: string; username: string; name: string; avatarUrl: string | null };
+  repository: { id: string; name: string; owner: string };
+};
+
+// Events are only ever served for repositories that are public right now, so making a
+// repository private also hides its history from every feed.
+async function loadEvents(conditions: SQL[], limit: number, cursor: string[] | null) {
+  const where = [eq(repositories.visibility, "public"), ...conditions];
+  if (cursor) {
+    where.push(
+      afterCursor(
+        [
+          { column: activityEvents.createdAt, type: "timestamp" },
+          { column: activityEvents.id, type: "uuid" },
+        ],
+        cursor,
+        "desc"
+      )
+    );
+  }
+
+  const rows = await db
+    .select({
+      id: activityEvents.id,
+      type: activityEvents.type,
+      payload: activityEvents.payload,
+      createdAt: activityEvents.createdAt,
+      sortKey: sql<string>`${activityEvents.createdAt}::text`,
+      actor: { id: users.id, username: users.username, name: users.name, avatarUrl: users.avatarUrl },
+      repository: { id: repositories.id, name: repositories.name, owner: repoOwners.username },
+    })
+    .from(activityEvents)
+    .innerJoin(users, eq(users.id, activityEvents.actorId))
+    .innerJoin(repositories, eq(repositories.id, activityEvents.repositoryId))
+    .innerJoin(repoOwners, eq(repoOwners.id, repositories.ownerId))
+    .where(and(...where))
+    .orderBy(desc(activityEvents.createdAt), desc(activityEvents.id))
+    .limit(limit + 1);
+
+  const hasMore = rows.length > limit;
+  const events: EventRow[] = rows.slice(0, limit);
+  const last = events[events.length - 1];
+  const nextCursor = hasMore && last ? encodeCursor([last.sortKey, last.id]) : null;
+  return { events, hasMore, nextCursor };
+}
+
+function serializeEvent(event: EventRow) {
+  return {
+    id: event.id,
+    type: event.type,
+    actor: event.actor,
+    repository: event.repository,
+    payload: event.payload,
+    createdAt: event.createdAt,
+  };
+}
+
+function parseEventsQuery(limitParam: string | undefined, cursorParam: string | undefined) {
+  const limit = Math.min(Math.max(parseInt(limitParam || "30", 10) || 30, 1), MAX_EVENTS_PAGE_SIZE);
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  return { limit, cursor, invalidCursor: !!cursorParam && !cursor };
+}
+
+function escapeXml(value: string) {
+  return value
+    .replace(/&/g, "&amp;")
+    .replace(/</g, "&lt;")
+    .replace(/>/g, "&gt;")
+    .replace(/"/g, "&quot;")
+    .replace(/'/g, "&apos;");
+}
+
+type AtomEntry = { id: string; title: string; link: string; updated: Date; author: string; content?: string };
+
+function renderAtom(feed: { id: string; title: string; link: string; self: string; entries: AtomEntry[] }) {
+  const updated = feed.entries[0]?.updated ?? new Date(0);
+  const entries = feed.entries.map(
+    (entry) =>
+      `  <entry>
+    <id>${escapeXml(entry.id)}</id>
+    <title>${escapeXml(entry.title)}</title>
+    <link rel="alternate" type="text/html" href="${escapeXml(entry.link)}"/>
+    <updated>${entry.updated.toISOString()}</updated>
+    <author><name>${escapeXml(entry.author)}</name></author>${
+      entry.content ? `\n    <content type="text">${escapeXml(entry.content)}</content>` : ""
+    }
+  </entry>`
+  );
+
+  return `<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>${escapeXml(feed.id)}</id>
+  <title>${escapeXml(feed.title)}</title>
+  <link rel="alternate" type="text/html" href="${escapeXml(feed.link)}"/>
+  <link rel="self" type="application/atom+xml" href="${escapeXml(feed.self)}"/>
+  <updated>${updated.toISOString()}</updated>
+${entries.join("\n")}
+</feed>
+`;
+}
+
+function atomResponse(body: string) {
+  return new Response(body, {
+    headers: { "Content-Type": "application/atom+xml; charset=utf-8" },
+  });
+}
+
+function describeEvent(event: EventRow): { title: string; path: string } {
+  const repo = `${event.repository.owner}/${event.repository.name}`;
+  const actor = event.actor.username;
+  const payload = event.payload;
+  const number = payload.number as number | undefined;
+
+  switch (event.type) {
+    case "repository_created":
+      return { title: `${actor} created ${repo}`, path: repo };
+    case "repository_forked":
+      return { title: `${actor} forked ${payload.source} to ${repo}`, path: repo };
+    case "starred":
+      return { title: `${actor} starred ${repo}`, path: repo };
+    case "pushed": {
+      const branch = String(payload.ref).replace("refs/heads/", "");
+      return { title: `${actor} pushed to ${branch} in ${repo}`, path: `${repo}/commits/${branch}` };
+    }
+    case "issue_opened":
+      return { title: `${actor} opened issue #${number} in ${repo}`, path: `${repo}/issues/${number}` };
+    case "issue_closed":
+      return { title: `${actor} closed issue #${number} in ${repo}`, path: `${repo}/issues/${number}` };
+    case "issue_commented":
+      return { title: `${actor} commented on issue #${number} in ${repo}`, path: `${repo}/issues/${number}` };
+    case "pull_request_opened":
+      return { title: `${actor} opened pull request #${number} in ${repo}`, path: `${repo}/pulls/${number}` };
+    case "pull_request_merged":
+      return { title: `${actor} merged pull request #${number} in ${repo}`, path: `${repo}/pulls/${number}` };
+    default:
+      return { title: `${actor} updated ${repo}`, path: repo };
+  }
+}
+
+app.get("/api/events", async (c) => {
+  const { limit, cursor, invalidCursor } = parseEventsQuery(c.req.query("limit"), c.req.query("cursor"));
+  if (invalidCursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const { events, hasMore, nextCursor } = await loadEvents([], limit, cursor);
+  return c.json({ events: events.map(serializeEvent), hasMore, nextCursor });
+});
+
+app.get("/api/users/:username/events", async (c) => {
+  const { limit, cursor, invalidCursor } = parseEventsQuery(c.req.query("limit"), c.req.query("cursor"));
+  if (invalidCursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const user = await db.query.users.findFirst({
+    where: eq(users.username, c.req.param("username")),
+    columns: { id: true },
+  });
+  if (!user) {
+    return apiError(c, 404, "User not found");
+  }
+
+  const { events, hasMore, nextCursor } = await loadEvents([eq(activityEvents.actorId, user.id)], limit, cursor);
+  return c.json({ events: events.map(serializeEvent), hasMore, nextCursor });
+});
+
+app.get("/api/repositories/:owner/:name/events", async (c) => {
+  const { limit, cursor, invalidCursor } = parseEventsQuery(c.req.query("limit"), c.req.query("cursor"));
+  if (invalidCursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const [repo] = await db
+    .select({ id: repositories.id })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, c.req.param("owner")), eq(repositories.name, c.req.param("name")), eq(repositories.visibility, "public")))
+    .limit(1);
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { events, hasMore, nextCursor } = await loadEvents([eq(activityEvents.repositoryId, repo.id)], limit, cursor);
+  return c.json({ events: events.map(serializeEvent), hasMore, nextCursor });
+});
+
+// Feed readers fetch without a session, so both Atom feeds only ever cover public repositories.
+app.get("/api/repositories/:owner/:name/commits.atom", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const [repo] = await db
+    .select({ id: repositories.id, name: repositories.name, ownerId: repositories.ownerId, defaultBranch: repositories.defaultBranch })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, owner), eq(repositories.name, name), eq(repositories.visibility, "public")))
+    .limit(1);
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const branch = c.req.query("branch") || repo.defaultBranch;
+  const store = createGitStore(repo.ownerId, repo.name);
+  const tip = await resolveCommittish(store.fs, store.dir, branch);
+  const { commits } = tip ? await getCommitsCached(store, tip, FEED_ENTRY_LIMIT, 0) : { commits: [] };
+
+  const repoUrl = `${getWebUrl()}/${owner}/${repo.name}`;
+  return atomResponse(
+    renderAtom({
+      id: `${repoUrl}/commits/${branch}`,
+      title: `Recent commits to ${owner}/${repo.name}:${branch}`,
+      link: `${repoUrl}/commits/${branch}`,
+      self: `${getApiUrl()}/api/repositories/${owner}/${repo.name}/commits.atom${c.req.query("branch") ? `?branch=${encodeURIComponent(branch)}` : ""}`,
+      entries: commits.map((commit) => ({
+        id: `${repoUrl}/commits/${branch}/${commit.oid}`,
+        title: commit.message.split("\n")[0],
+        link: `${repoUrl}/commits/${branch}/${commit.oid}`,
+        updated: new Date(commit.timestamp),
+        author: commit.author.name,
+        content: commit.message.trim(),
+      })),
+    })
+  );
+});
+
+app.get("/users/:feed{[^/]+\\.atom}", async (c) => {
+  const username = c.req.param("feed").replace(/\.atom$/, "");
+
+  const user = await db.query.users.findFirst({
+    where: eq(users.username, username),
+    columns: { id: true, username: true },
+  });
+  if (!user) {
+    return apiError(c, 404, "User not found");
+  }
+
+  const { events } = await loadEvents([eq(activityEvents.actorId, user.id)], FEED_ENTRY_LIMIT, null);
+
+  const webUrl = getWebUrl();
+  return atomResponse(
+    renderAtom({
+      id: `${webUrl}/${user.username}`,
+      title: `${user.username}'s activity`,
+      link: `${webUrl}/${user.username}`,
+      self: `${getApiUrl()}/users/${user.username}.atom`,
+      entries: events.map((event) => {
+        const { title, path } = describeEvent(event);
+        return {
+          id: `urn:uuid:${event.id}`,
+          title,
+          link: `${webUrl}/${path}`,
+          updated: event.createdAt,
+          author: event.actor.username,
+        };
+      }),
+    })
+  );
+});
+
+export default app;
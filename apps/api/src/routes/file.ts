@@ -3,6 +3,9 @@ import { db, users, repositories } from "@gitbruv/db";
 import { eq, and } from "drizzle-orm";
 import { authMiddleware, type AuthVariables } from "../middleware/auth";
 import { createGitStore, getFile } from "../git";
+import { checkReadToken } from "../read-tokens";
+import { getContentType } from "../mime";
+import { apiError } from "../errors";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
@@ -17,6 +20,7 @@ app.get("/file/:username/:repo/:branch/*", async (c) => {
 
   const result = await db
     .select({
+      id: repositories.id,
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
       userId: users.id,
@@ -29,28 +33,42 @@ app.get("/file/:username/:repo/:branch/*", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
-  if (row.visibility === "private" && currentUser?.id !== row.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+  const isPrivate = row.visibility === "private";
+  if (isPrivate && currentUser?.id !== row.ownerId) {
+    const allowed = await checkReadToken(row.id, c.req.query("token"), filePath);
+    if (!allowed) {
+      return apiError(c, 404, "Repository not found");
+    }
   }
 
   const store = createGitStore(row.userId, row.repoName);
   const file = await getFile(store.fs, store.dir, branch, filePath);
 
   if (!file) {
-    return c.json({ error: "File not found" }, 404);
+    return apiError(c, 404, "File not found");
   }
 
   const ext = filePath.split(".").pop()?.toLowerCase() || "";
   const contentType = getContentType(ext);
 
+  // Public files may be stored by shared caches but must be revalidated on every use, so making
+  // the repository private stops them being served straight away. Revalidation is cheap: the
+  // blob oid is the ETag.
+  const etag = `"${file.oid}"`;
+  const cacheControl = isPrivate ? "private, no-store" : "public, no-cache";
+  if (!isPrivate && c.req.header("if-none-match") === etag) {
+    return new Response(null, { status: 304, headers: { ETag: etag, "Cache-Control": cacheControl } });
+  }
+
   return new Response(file.content, {
     status: 200,
     headers: {
       "Content-Type": contentType,
-      "Cache-Control": "public, max-age=3600",
+      "Cache-Control": cacheControl,
+      ...(isPrivate ? {} : { ETag: etag }),
     },
   });
 });
@@ -66,50 +84,4 @@ app.options("/file/:username/:repo/:branch/*", (_c) => {
   });
 });
 
-function getContentType(ext: string): string {
-  const contentTypes: Record<string, string> = {
-    js: "application/javascript",
-    mjs: "application/javascript",
-    ts: "text/typescript",
-    tsx: "text/typescript",
-    jsx: "text/javascript",
-    json: "application/json",
-    html: "text/html",
-    htm: "text/html",
-    css: "text/css",
-    md: "text/markdown",
-    txt: "text/plain",
-    xml: "application/xml",
-    svg: "image/svg+xml",
-    png: "image/png",
-    jpg: "image/jpeg",
-    jpeg: "image/jpeg",
-    gif: "image/gif",
-    webp: "image/webp",
-    ico: "image/x-icon",
-    pdf: "application/pdf",
-    wasm: "application/wasm",
-    yaml: "text/yaml",
-    yml: "text/yaml",
-    toml: "text/toml",
-    sh: "text/x-shellscript",
-    bash: "text/x-shellscript",
-    zsh: "text/x-shellscript",
-    py: "text/x-python",
-    rb: "text/x-ruby",
-    go: "text/x-go",
-    rs: "text/x-rust",
-    c: "text/x-c",
-    cpp: "text/x-c++",
-    h: "text/x-c",
-    hpp: "text/x-c++",
-    java: "text/x-java",
-    kt: "text/x-kotlin",
-    swift: "text/x-swift",
-    php: "text/x-php",
-  };
-
-  return contentTypes[ext] || "application/octet-stream";
-}
-
 export default app;
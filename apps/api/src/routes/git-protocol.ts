@@ -1,18 +1,47 @@
 import { Hono } from "hono";
 import { db, users, repositories, branchProtectionRules } from "@gitbruv/db";
 import { eq, and } from "drizzle-orm";
-import { authMiddleware, type AuthUser, type AuthVariables } from "../middleware/auth";
-import { createGitStore, getRefsAdvertisement, repoCache, isAncestor } from "../git";
+import { authMiddleware, requireAuth, type AuthUser, type AuthVariables } from "../middleware/auth";
+import { createGitStore, getRefsAdvertisementCached, repoCache, isAncestor, warmBranchCaches } from "../git";
+import { computeLanguageStats } from "../git/languages";
+import { runFsck } from "../git/fsck";
+import { createPackIndex } from "../git/pack-index";
+import { PackFormatError } from "../git/pack-parse";
+import { unpackPackFile, storeLooseObject, type UnpackResult } from "../git/unpack";
+import { largeFileWarnings } from "../git/large-files";
+import { checkDeployKey, DEPLOY_KEY_PREFIX } from "../deploy-keys";
+import { checkGitCredential, issueGitCredential, revokeGitCredential, GIT_CREDENTIAL_PREFIX } from "../git-credentials";
+import { recordPushedCommitReferences } from "../issue-references";
+import { trackTask } from "../metrics";
 import { getAuth } from "../auth";
-import { putObject, deleteObject, getObject } from "../s3";
-import { createHash } from "crypto";
+import { putObject, deleteObject, uploadMultipart } from "../s3";
+import { parseUploadPackRequest, createSidebandWriter, buildPack, hasObject, findUnreachableWants, pktLine, UploadPackRequestError } from "../git/upload-pack";
+import { spoolReceivePack, PackTooLargeError, type SpooledPush } from "../git/spool";
+import { config } from "../config";
 import * as zlib from "zlib";
+import { apiError } from "../errors";
+import { recordActivity } from "../activity";
+import { scheduleCommitCounts } from "../jobs/commit-counts";
+import { refreshRepoFiles } from "../repo-files";
+import { refreshBranchMetadata, repairBranchMetadata } from "../branch-metadata";
+import { applyRefUpdates, type RefUpdate } from "../git/ref-transaction";
+import { acquirePushLock, type PushLock } from "../git/push-lock";
+import { buildPackCached, clearPackCache } from "../git/pack-cache";
+import { updateReachabilityIndex } from "../git/reachability";
+import { recordRefUpdates } from "../ref-log";
+import { getPushPolicy, hasPushChecks, checkPushPolicy } from "../push-policy";
+import { storageLayout } from "../storage-layout";
+import { buildReportStatus, type RefStatus } from "../git/report-status";
+import { StorageUnavailableError } from "../s3-policy";
+import { getRequestId, hasStorageFailure, isolateStorageFailures } from "../tracing";
+
+const ARCHIVE_PACK_BYTES = 64 * 1024 * 1024;
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
-async function resolveBasicAuthUser(authHeader: string | undefined): Promise<AuthUser | null> {
+function decodeBasicAuth(authHeader: string | undefined): { identifier: string; password: string } | null {
   if (!authHeader || !authHeader.startsWith("Basic ")) {
     return null;
   }
@@ -36,27 +65,38 @@ async function resolveBasicAuthUser(authHeader: string | undefined): Promise<Aut
     return null;
   }
 
-  let email = identifier;
-  if (!identifier.includes("@")) {
-    const userRow = await db
-      .select({
-        id: users.id,
-        name: users.name,
-        email: users.email,
-        username: users.username,
-        avatarUrl: users.avatarUrl,
-      })
-      .from(users)
-      .where(eq(users.username, identifier))
-      .limit(1);
+  return { identifier, password };
+}
 
-    if (!userRow[0]) {
-      return null;
-    }
+async function resolveBasicAuthUser(authHeader: string | undefined): Promise<AuthUser | null> {
+  const credentials = decodeBasicAuth(authHeader);
+  if (!credentials) {
+    return null;
+  }
+
+  const { identifier, password } = credentials;
 
-    email = userRow[0].email;
+  if (password.startsWith(GIT_CREDENTIAL_PREFIX)) {
+    return await checkGitCredential(identifier, password);
+  }
+
+  const [userRow] = await db
+    .select({ email: users.email, twoFactorEnabled: users.twoFactorEnabled })
+    .from(users)
+    .where(identifier.includes("@") ? eq(users.email, identifier) : eq(users.username, identifier))
+    .limit(1);
+
+  if (!userRow) {
+    return null;
+  }
+
+  // Accounts with two-factor authentication must use a token instead of their password over HTTP.
+  if (userRow.twoFactorEnabled) {
+    return null;
   }
 
+  const email = userRow.email;
+
   const auth = getAuth();
   try {
     const result: any = await auth.api.signInEmail({
@@ -91,6 +131,26 @@ async function resolveGitUser(c: { get: (key: string) => AuthUser | undefined; r
   return await resolveBasicAuthUser(c.req.header("authorization"));
 }
 
+type GitAccess = { read: boolean; write: boolean; user: AuthUser | null };
+
+async function resolveGitAccess(
+  c: { get: (key: string) => AuthUser | undefined; req: { header: (name: string) => string | undefined } },
+  repo: { id: string; ownerId: string; visibility: string }
+): Promise<GitAccess> {
+  const credentials = decodeBasicAuth(c.req.header("authorization"));
+  if (credentials?.password.startsWith(DEPLOY_KEY_PREFIX)) {
+    const key = await checkDeployKey(repo.id, credentials.password);
+    if (key) {
+      return { read: true, write: !key.readOnly, user: null };
+    }
+    return { read: repo.visibility !== "private", write: false, user: null };
+  }
+
+  const currentUser = await resolveGitUser(c);
+  const isOwner = !!currentUser && currentUser.id === repo.ownerId;
+  return { read: isOwner || repo.visibility !== "private", write: isOwner, user: currentUser };
+}
+
 async function getRepoAndStore(owner: string, name: string) {
   const repoName = name.replace(/\.git$/, "");
 
@@ -100,6 +160,8 @@ async function getRepoAndStore(owner: string, name: string) {
       name: repositories.name,
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
+      defaultBranch: repositories.defaultBranch,
+      mirrorUrl: repositories.mirrorUrl,
       userId: users.id,
     })
     .from(repositories)
@@ -119,12 +181,39 @@ async function getRepoAndStore(owner: string, name: string) {
       name: row.name,
       ownerId: row.ownerId,
       visibility: row.visibility,
+      defaultBranch: row.defaultBranch,
+      mirrorUrl: row.mirrorUrl,
     },
     store,
     userId: row.userId,
   };
 }
 
+function refreshLanguageStats(repoId: string, userId: string, repoName: string, branch: string) {
+  const task = () =>
+    computeLanguageStats(createGitStore(userId, repoName), branch)
+      .then(async (stats) => {
+        if (!stats) return;
+        await db
+          .update(repositories)
+          .set({ languages: stats.languages, languagesCommitOid: stats.commitOid })
+          .where(eq(repositories.id, repoId));
+      })
+      .catch((error) => {
+        console.error("[API] receive-pack: language stats refresh failed:", error);
+      });
+  trackTask("language_stats", task);
+}
+
+// git shows plain-text error bodies to the user as "remote: ..." lines, but not JSON ones.
+function gitHttpError(status: number, message: string): Response {
+  return new Response(`${message}\n`, { status, headers: { "Content-Type": "text/plain; charset=utf-8" } });
+}
+
+function readOnlyMirror(): Response {
+  return gitHttpError(403, "Repository is a read-only mirror");
+}
+
 function unauthorizedBasic(): Response {
   return new Response("Unauthorized", {
     status: 401,
@@ -134,34 +223,49 @@ function unauthorizedBasic(): Response {
   });
 }
 
+// Lets the web UI hand out clone credentials without the user's password, which also gives
+// two-factor accounts a way to use HTTP git.
+app.post("/api/git/credentials", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const { id, token, expiresAt } = await issueGitCredential(user.id);
+  return c.json({ id, username: user.username, token, expiresAt }, 201);
+});
+
+app.delete("/api/git/credentials/:id", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const revoked = await revokeGitCredential(user.id, c.req.param("id"));
+  if (!revoked) {
+    return apiError(c, 404, "Credential not found");
+  }
+  return c.json({ success: true });
+});
+
 app.get("/:owner/:name/info/refs", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const service = c.req.query("service");
-  const currentUser = await resolveGitUser(c);
 
   if (!service || (service !== "git-upload-pack" && service !== "git-receive-pack")) {
-    return c.json({ error: "Invalid service" }, 404);
+    return gitHttpError(404, "Invalid service");
   }
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return gitHttpError(404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const access = await resolveGitAccess(c, repo);
 
-  if (service === "git-receive-pack") {
-    if (!currentUser || currentUser.id !== repo.ownerId) {
-      return unauthorizedBasic();
-    }
-  } else if (repo.visibility === "private") {
-    if (!currentUser || currentUser.id !== repo.ownerId) {
-      return unauthorizedBasic();
-    }
+  if (service === "git-receive-pack" ? !access.write : !access.read) {
+    return unauthorizedBasic();
+  }
+
+  if (service === "git-receive-pack" && repo.mirrorUrl) {
+    return readOnlyMirror();
   }
 
-  const refs = await getRefsAdvertisement(store.fs, store.dir, service);
+  const refs = await getRefsAdvertisementCached(store, service, repo.defaultBranch);
 
   const packet = `# service=${service}\n`;
   const packetLen = (packet.length + 4).toString(16).padStart(4, "0");
@@ -185,41 +289,107 @@ app.get("/:owner/:name/info/refs", async (c) => {
 app.post("/:owner/:name/git-upload-pack", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
-  const currentUser = await resolveGitUser(c);
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return gitHttpError(404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+  const access = await resolveGitAccess(c, repo);
+
+  if (!access.read) {
+    return unauthorizedBasic();
   }
 
-  const { repo } = result;
+  let body = Buffer.from(await c.req.arrayBuffer());
+  if (c.req.header("content-encoding") === "gzip") {
+    body = zlib.gunzipSync(body);
+  }
 
-  if (repo.visibility === "private") {
-    if (!currentUser || currentUser.id !== repo.ownerId) {
-      return unauthorizedBasic();
+  const request = parseUploadPackRequest(body);
+  const sideband = createSidebandWriter(request.capabilities);
+  const multiAck = request.capabilities.has("multi_ack_detailed")
+    ? "detailed"
+    : request.capabilities.has("multi_ack")
+      ? "basic"
+      : null;
+
+  const common: string[] = [];
+  for (const oid of request.haves) {
+    if (await hasObject(store, oid)) {
+      common.push(oid);
     }
   }
 
-  try {
-    return new Response("0008NAK\n", {
-      status: 200,
-      headers: {
-        "Content-Type": "application/x-git-upload-pack-result",
-        "Cache-Control": "no-cache",
-      },
-    });
-  } catch (error) {
-    console.error("[API] upload-pack error:", error);
-    return new Response("0008NAK\n", {
-      status: 200,
-      headers: {
-        "Content-Type": "application/x-git-upload-pack-result",
-        "Cache-Control": "no-cache",
-      },
-    });
+  const negotiation: Buffer[] = [];
+  if (!request.done) {
+    for (const oid of common) {
+      if (multiAck === "detailed") {
+        negotiation.push(pktLine(`ACK ${oid} common\n`));
+      } else if (multiAck === "basic") {
+        negotiation.push(pktLine(`ACK ${oid} continue\n`));
+      }
+    }
+    if (common.length === 0 || multiAck) {
+      negotiation.push(pktLine("NAK\n"));
+    }
+    return uploadPackResponse(Buffer.concat(negotiation));
   }
+
+  if (common.length > 0) {
+    negotiation.push(pktLine(`ACK ${common[common.length - 1]}\n`));
+  } else {
+    negotiation.push(pktLine("NAK\n"));
+  }
+
+  const stream = new ReadableStream<Uint8Array>({
+    async start(controller) {
+      const send = (packets: Buffer[]) => packets.forEach((packet) => controller.enqueue(packet));
+      send(negotiation);
+
+      try {
+        if (request.wants.length === 0) {
+          throw new UploadPackRequestError("no wants received");
+        }
+        const [unreachable] = await findUnreachableWants(store, request.wants);
+        if (unreachable) {
+          throw new UploadPackRequestError(`upload-pack: not our ref ${unreachable}`);
+        }
+
+        const onProgress = (message: string) => send(sideband.progress(message));
+        const pack =
+          repo.visibility === "public"
+            ? await buildPackCached(store, request.wants, common, onProgress)
+            : await buildPack(store, request.wants, common, onProgress);
+        // A read that failed during an outage looks like a missing object, so the pack may be
+        // incomplete; the client would only find out as "bad object" errors after downloading it.
+        if (hasStorageFailure()) {
+          throw new StorageUnavailableError("Storage failed while building a pack", "failed");
+        }
+        send(sideband.data(pack));
+        send(sideband.end());
+      } catch (error) {
+        console.error(`[API] upload-pack error for ${owner}/${name}:`, error);
+        send(sideband.error(describeFetchFailure(error)));
+      }
+      controller.close();
+    },
+  });
+
+  return uploadPackResponse(stream);
 });
 
+function uploadPackResponse(body: Buffer | ReadableStream<Uint8Array>): Response {
+  return new Response(body, {
+    status: 200,
+    headers: {
+      "Content-Type": "application/x-git-upload-pack-result",
+      "Cache-Control": "no-cache",
+    },
+  });
+}
+
 function parsePktLines(data: Buffer): string[] {
   const lines: string[] = [];
   let offset = 0;
@@ -246,429 +416,117 @@ function parsePktLines(data: Buffer): string[] {
   return lines;
 }
 
-const OBJ_COMMIT = 1;
-const OBJ_TREE = 2;
-const OBJ_BLOB = 3;
-const OBJ_TAG = 4;
-const OBJ_OFS_DELTA = 6;
-const OBJ_REF_DELTA = 7;
-
-interface PackObject {
-  type: number;
-  data: Buffer;
-  offset: number;
-  baseOid?: string;
-  baseOffset?: number;
-}
-
-function readPackVarInt(buf: Buffer, offset: number): { value: number; type: number; bytesRead: number } {
-  let byte = buf[offset];
-  const type = (byte >> 4) & 0x7;
-  let value = byte & 0x0f;
-  let shift = 4;
-  let bytesRead = 1;
-
-  while (byte & 0x80) {
-    byte = buf[offset + bytesRead];
-    value |= (byte & 0x7f) << shift;
-    shift += 7;
-    bytesRead++;
-  }
-
-  return { value, type, bytesRead };
-}
-
-function readOfsOffset(buf: Buffer, offset: number): { value: number; bytesRead: number } {
-  let byte = buf[offset];
-  let value = byte & 0x7f;
-  let bytesRead = 1;
-
-  while (byte & 0x80) {
-    byte = buf[offset + bytesRead];
-    value = ((value + 1) << 7) | (byte & 0x7f);
-    bytesRead++;
-  }
-
-  return { value, bytesRead };
-}
-
-function applyDelta(base: Buffer, delta: Buffer): Buffer {
-  let offset = 0;
-
-  let _baseSize = 0;
-  let shift = 0;
-  while (offset < delta.length) {
-    const byte = delta[offset++];
-    _baseSize |= (byte & 0x7f) << shift;
-    shift += 7;
-    if (!(byte & 0x80)) break;
-  }
-
-  let _resultSize = 0;
-  shift = 0;
-  while (offset < delta.length) {
-    const byte = delta[offset++];
-    _resultSize |= (byte & 0x7f) << shift;
-    shift += 7;
-    if (!(byte & 0x80)) break;
-  }
-
-  const result: Buffer[] = [];
-  let _resultLen = 0;
-
-  while (offset < delta.length) {
-    const cmd = delta[offset++];
-
-    if (cmd & 0x80) {
-      let copyOffset = 0;
-      let copySize = 0;
-
-      if (cmd & 0x01) copyOffset = delta[offset++];
-      if (cmd & 0x02) copyOffset |= delta[offset++] << 8;
-      if (cmd & 0x04) copyOffset |= delta[offset++] << 16;
-      if (cmd & 0x08) copyOffset |= delta[offset++] << 24;
-
-      if (cmd & 0x10) copySize = delta[offset++];
-      if (cmd & 0x20) copySize |= delta[offset++] << 8;
-      if (cmd & 0x40) copySize |= delta[offset++] << 16;
-
-      if (copySize === 0) copySize = 0x10000;
-
-      result.push(base.subarray(copyOffset, copyOffset + copySize));
-      _resultLen += copySize;
-    } else if (cmd > 0) {
-      result.push(delta.subarray(offset, offset + cmd));
-      offset += cmd;
-      _resultLen += cmd;
+function parseRefUpdates(commandSection: Buffer): RefUpdate[] {
+  const updates: RefUpdate[] = [];
+  for (const line of parsePktLines(commandSection)) {
+    const parts = line.trim().split(/\s+/);
+    if (parts.length >= 3 && parts[0].length === 40 && parts[1].length === 40) {
+      updates.push({ oldOid: parts[0], newOid: parts[1], ref: parts[2].split("\0")[0] });
     }
   }
-
-  return Buffer.concat(result);
+  return updates;
 }
 
-function typeToString(type: number): string {
-  switch (type) {
-    case OBJ_COMMIT: return "commit";
-    case OBJ_TREE: return "tree";
-    case OBJ_BLOB: return "blob";
-    case OBJ_TAG: return "tag";
-    default: return "unknown";
-  }
+// Capabilities ride on the first command line after a NUL, e.g. "<old> <new> <ref>\0atomic report-status".
+function parseReceiveCapabilities(commandSection: Buffer): Set<string> {
+  const [first] = parsePktLines(commandSection);
+  const capabilities = first?.split("\0")[1];
+  return new Set(capabilities ? capabilities.trim().split(/\s+/) : []);
 }
 
-function hashObject(type: string, data: Buffer): string {
-  const header = `${type} ${data.length}\0`;
-  const store = Buffer.concat([Buffer.from(header), data]);
-  return createHash("sha1").update(store).digest("hex");
+function reportStatusResponse(capabilities: Set<string>, unpackError: string | null, refs: RefStatus[], messages: string[] = []): Response {
+  return new Response(buildReportStatus(capabilities, unpackError, refs, messages), {
+    status: 200,
+    headers: {
+      "Content-Type": "application/x-git-receive-pack-result",
+      "Cache-Control": "no-cache",
+    },
+  });
 }
 
-function inflateObject(buf: Buffer, offset: number, _expectedSize: number): { data: Buffer; bytesRead: number } {
-  const remaining = buf.subarray(offset);
-
-  try {
-    const result = zlib.inflateSync(remaining);
-
-    let consumed = 0;
-    let low = 2;
-    let high = remaining.length;
-
-    while (low <= high) {
-      const mid = Math.floor((low + high) / 2);
-      try {
-        zlib.inflateSync(remaining.subarray(0, mid));
-        consumed = mid;
-        high = mid - 1;
-      } catch {
-        low = mid + 1;
-      }
-    }
-
-    return { data: result, bytesRead: consumed || remaining.length };
-  } catch {
-    try {
-      const result = zlib.inflateRawSync(remaining);
-
-      let consumed = 0;
-      let low = 1;
-      let high = remaining.length;
-
-      while (low <= high) {
-        const mid = Math.floor((low + high) / 2);
-        try {
-          zlib.inflateRawSync(remaining.subarray(0, mid));
-          consumed = mid;
-          high = mid - 1;
-        } catch {
-          low = mid + 1;
-        }
-      }
-
-      return { data: result, bytesRead: consumed || remaining.length };
-    } catch (e) {
-      throw new Error(`Failed to inflate at offset ${offset}: ${e}`);
-    }
+// The message git prints as "remote error: ..." when a fetch fails after negotiation. Storage
+// checks come first because a failed read also shows up as an unknown object.
+function describeFetchFailure(error: unknown): string {
+  if (error instanceof StorageUnavailableError || hasStorageFailure()) {
+    return "repository storage is temporarily unavailable, try again shortly";
   }
-}
-
-async function loadObjectFromStorage(baseOid: string, basePath: string): Promise<{ type: number; data: Buffer } | null> {
-  try {
-    const prefix = baseOid.substring(0, 2);
-    const suffix = baseOid.substring(2);
-    const objectPath = `${basePath}/objects/${prefix}/${suffix}`;
-
-    const compressed = await getObject(objectPath);
-    if (!compressed) {
-      return null;
-    }
-
-    const decompressed = zlib.inflateSync(compressed);
-    const nullIndex = decompressed.indexOf(0);
-    if (nullIndex === -1) {
-      return null;
-    }
-
-    const header = decompressed.subarray(0, nullIndex).toString("utf8");
-    const parts = header.split(" ");
-    if (parts.length !== 2) {
-      return null;
-    }
-
-    const typeStr = parts[0];
-    let type = 0;
-    if (typeStr === "commit") type = OBJ_COMMIT;
-    else if (typeStr === "tree") type = OBJ_TREE;
-    else if (typeStr === "blob") type = OBJ_BLOB;
-    else if (typeStr === "tag") type = OBJ_TAG;
-    else return null;
-
-    const data = decompressed.subarray(nullIndex + 1);
-    return { type, data };
-  } catch (error) {
-    console.error(`[API] Failed to load object ${baseOid} from storage:`, error);
-    return null;
+  if (error instanceof UploadPackRequestError) {
+    return error.message;
   }
+  const requestId = getRequestId();
+  return `the server failed to build the pack${requestId ? ` (request ${requestId})` : ""}`;
 }
 
-async function unpackPackFile(
-  packData: Buffer,
-  storeObject: (oid: string, type: string, data: Buffer) => Promise<void>,
-  basePath: string
-): Promise<{ success: boolean; objectCount: number; error?: string }> {
-  try {
-    if (packData.length < 12) {
-      return { success: false, objectCount: 0, error: "Pack file too small" };
-    }
-
-    const signature = packData.subarray(0, 4).toString("ascii");
-    if (signature !== "PACK") {
-      return { success: false, objectCount: 0, error: "Invalid pack signature" };
-    }
-
-    const version = packData.readUInt32BE(4);
-    if (version !== 2 && version !== 3) {
-      return { success: false, objectCount: 0, error: `Unsupported pack version: ${version}` };
-    }
-
-    const numObjects = packData.readUInt32BE(8);
-
-    const objects = new Map<number, PackObject>();
-    const refDeltas: { obj: PackObject; baseOid: string }[] = [];
-    let offset = 12;
-
-    for (let i = 0; i < numObjects; i++) {
-      const objOffset = offset;
-      const header = readPackVarInt(packData, offset);
-      offset += header.bytesRead;
-
-      const obj: PackObject = {
-        type: header.type,
-        data: Buffer.alloc(0),
-        offset: objOffset,
-      };
-
-      if (header.type === OBJ_OFS_DELTA) {
-        const ofs = readOfsOffset(packData, offset);
-        offset += ofs.bytesRead;
-        obj.baseOffset = objOffset - ofs.value;
-      } else if (header.type === OBJ_REF_DELTA) {
-        obj.baseOid = packData.subarray(offset, offset + 20).toString("hex");
-        offset += 20;
-        refDeltas.push({ obj, baseOid: obj.baseOid });
-      }
-
-      const inflated = inflateObject(packData, offset, header.value);
-      obj.data = inflated.data;
-      offset += inflated.bytesRead;
-      objects.set(objOffset, obj);
-    }
-
-    const baseObjects = new Map<string, { type: number; data: Buffer }>();
-
-    if (refDeltas.length > 0) {
-      console.log(`[API] unpack: loading ${refDeltas.length} REF_DELTA base objects from storage`);
-      const uniqueBaseOids = [...new Set(refDeltas.map(d => d.baseOid))];
-      console.log(`[API] unpack: ${uniqueBaseOids.length} unique base objects to load`);
-
-      const LOAD_BATCH_SIZE = 20;
-      for (let i = 0; i < uniqueBaseOids.length; i += LOAD_BATCH_SIZE) {
-        const batch = uniqueBaseOids.slice(i, i + LOAD_BATCH_SIZE);
-        const results = await Promise.all(
-          batch.map(async (baseOid) => {
-            const baseObj = await loadObjectFromStorage(baseOid, basePath);
-            return { baseOid, baseObj };
-          })
-        );
-
-        for (const { baseOid, baseObj } of results) {
-          if (baseObj) {
-            baseObjects.set(baseOid, baseObj);
-          } else {
-            console.warn(`[API] unpack: REF_DELTA base object ${baseOid} not found in storage`);
-          }
-        }
-      }
-      console.log(`[API] unpack: loaded ${baseObjects.size} base objects`);
-    }
-
-    const resolveDelta = (obj: PackObject): { type: number; data: Buffer } | null => {
-      if (obj.type !== OBJ_OFS_DELTA && obj.type !== OBJ_REF_DELTA) {
-        return { type: obj.type, data: obj.data };
-      }
-
-      let base: PackObject | undefined;
-      let baseData: { type: number; data: Buffer } | null = null;
-
-      if (obj.baseOffset !== undefined) {
-        base = objects.get(obj.baseOffset);
-        if (base) {
-          baseData = resolveDelta(base);
-        }
-      } else if (obj.baseOid) {
-        baseData = baseObjects.get(obj.baseOid) || null;
-      }
-
-      if (!baseData) {
-        return null;
-      }
-
-      const resolvedData = applyDelta(baseData.data, obj.data);
-      return { type: baseData.type, data: resolvedData };
+// What the user is told when a push fails outright. Pack problems are the client's to fix and are
+// shown as-is; anything else is ours, so it gets a generic message with the request id to quote.
+function describePushFailure(error: unknown): { unpack: string; ref: string; message: string } {
+  if (error instanceof PackFormatError) {
+    return { unpack: error.message, ref: "unpacker error", message: `error: the pack could not be read: ${error.message}` };
+  }
+  if (error instanceof StorageUnavailableError || hasStorageFailure()) {
+    return {
+      unpack: "storage unavailable",
+      ref: "storage temporarily unavailable, try again",
+      message: "error: repository storage is temporarily unavailable; nothing was updated, try again shortly",
     };
-
-    const objectsToStore: { oid: string; type: string; data: Buffer }[] = [];
-    let failed = 0;
-
-    for (const [objOffset, obj] of objects) {
-      const resolved = resolveDelta(obj);
-      if (!resolved) {
-        failed++;
-        if (failed <= 10) {
-          console.error(`[API] unpack: failed to resolve delta for object at offset ${objOffset}, type=${obj.type}`);
-        }
-        continue;
-      }
-
-      const typeStr = typeToString(resolved.type);
-      const oid = hashObject(typeStr, resolved.data);
-      objectsToStore.push({ oid, type: typeStr, data: resolved.data });
-    }
-
-    if (failed > 0) {
-      console.warn(`[API] unpack: failed to resolve ${failed} objects`);
-    }
-
-    console.log(`[API] unpack: storing ${objectsToStore.length} objects in parallel batches`);
-
-    const BATCH_SIZE = 50;
-    let stored = 0;
-
-    for (let i = 0; i < objectsToStore.length; i += BATCH_SIZE) {
-      const batch = objectsToStore.slice(i, i + BATCH_SIZE);
-      await Promise.all(batch.map(obj => storeObject(obj.oid, obj.type, obj.data)));
-      stored += batch.length;
-
-      if (stored % 500 === 0 || stored === objectsToStore.length) {
-        console.log(`[API] unpack: stored ${stored}/${objectsToStore.length} objects`);
-      }
-    }
-
-    return { success: true, objectCount: stored };
-  } catch (error) {
-    console.error("[API] unpack error:", error);
-    return { success: false, objectCount: 0, error: error instanceof Error ? error.message : "Unknown error" };
   }
+  const requestId = getRequestId();
+  return {
+    unpack: "internal error",
+    ref: "internal server error",
+    message: `error: the server failed to process this push${requestId ? ` (request ${requestId})` : ""}`,
+  };
 }
 
 app.post("/:owner/:name/git-receive-pack", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
-  const currentUser = await resolveGitUser(c);
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return gitHttpError(404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const access = await resolveGitAccess(c, repo);
 
-  if (!currentUser || currentUser.id !== repo.ownerId) {
+  if (!access.write) {
     return unauthorizedBasic();
   }
 
-  console.log(`[API] receive-pack: received request for ${owner}/${name}`);
-  const body = await c.req.arrayBuffer();
-  const requestData = Buffer.from(body);
-  console.log(`[API] receive-pack: received ${requestData.length} bytes`);
+  if (repo.mirrorUrl) {
+    return readOnlyMirror();
+  }
 
+  console.log(`[API] receive-pack: received request for ${owner}/${name}`);
+  let spooled: SpooledPush;
   try {
-
-    const packSignature = Buffer.from([0x50, 0x41, 0x43, 0x4b]);
-    let packStart = -1;
-
-    for (let i = 0; i <= requestData.length - 4; i++) {
-      if (requestData.slice(i, i + 4).equals(packSignature)) {
-        packStart = i;
-        break;
-      }
-    }
-
-    if (packStart === -1) {
-      const unpackOk = "unpack ok\n";
-      const unpackOkLen = unpackOk.length + 4;
-      const response = Buffer.concat([
-        Buffer.from(unpackOkLen.toString(16).padStart(4, "0") + unpackOk, "ascii"),
-        Buffer.from("0000", "ascii"),
-      ]);
-      return new Response(response, {
-        status: 200,
-        headers: {
-          "Content-Type": "application/x-git-receive-pack-result",
-          "Cache-Control": "no-cache",
-        },
-      });
+    spooled = await spoolReceivePack(c.req.raw.body, config.git.maxPackBytes);
+  } catch (error) {
+    if (error instanceof PackTooLargeError) {
+      console.warn(`[API] receive-pack: rejected push to ${owner}/${name}: ${error.message}`);
+      return reportStatusResponse(
+        parseReceiveCapabilities(error.commandSection),
+        error.message,
+        parseRefUpdates(error.commandSection).map((update) => ({ ref: update.ref, error: "pack too large" })),
+        [`error: ${error.message}; split the push into smaller pieces or remove large files from history`]
+      );
     }
+    throw error;
+  }
+  console.log(`[API] receive-pack: received ${spooled.commandSection.length + spooled.packSize} bytes`);
 
-    const commandSection = requestData.slice(0, packStart);
-    const packData = requestData.slice(packStart);
-
-    console.log(`[API] receive-pack: command section ${commandSection.length} bytes, pack data ${packData.length} bytes`);
-
-    const commands = parsePktLines(commandSection);
-    console.log(`[API] receive-pack: parsed ${commands.length} commands`);
+  let pushLock: PushLock | null = null;
+  let archivedPackKey: string | null = null;
+  let archiveIndexed = false;
+  try {
+    const commandSection = spooled.commandSection;
 
-    const updates: { oldOid: string; newOid: string; ref: string }[] = [];
+    console.log(`[API] receive-pack: command section ${commandSection.length} bytes, pack data ${spooled.packSize} bytes`);
 
-    for (const line of commands) {
-      const parts = line.trim().split(/\s+/);
-      if (parts.length >= 3 && parts[0].length === 40 && parts[1].length === 40) {
-        const refName = parts[2].split("\0")[0];
-        updates.push({
-          oldOid: parts[0],
-          newOid: parts[1],
-          ref: refName,
-        });
-      }
-    }
+    const updates = parseRefUpdates(commandSection);
+    const capabilities = parseReceiveCapabilities(commandSection);
+    const atomic = capabilities.has("atomic");
 
     console.log(`[API] receive-pack: processing ${updates.length} ref updates`);
 
@@ -678,7 +536,7 @@ app.post("/:owner/:name/git-receive-pack", async (c) => {
       .from(branchProtectionRules)
       .where(eq(branchProtectionRules.repositoryId, repo.id));
 
-    const rejectedRefLines: string[] = [];
+    const rejectedRefs: RefStatus[] = [];
     const rejectedRefSet = new Set<string>();
 
     if (protectionRules.length > 0) {
@@ -695,67 +553,100 @@ app.post("/:owner/:name/git-receive-pack", async (c) => {
         const isDelete = update.newOid === "0".repeat(40);
 
         if (isDelete && rule.preventDeletion) {
-          rejectedRefLines.push(`ng ${update.ref} protected branch - deletion not allowed`);
+          rejectedRefs.push({ ref: update.ref, error: "protected branch - deletion not allowed" });
           rejectedRefSet.add(update.ref);
           continue;
         }
 
         if (!isDelete && rule.preventDirectPush) {
-          rejectedRefLines.push(`ng ${update.ref} protected branch - direct push not allowed, use a pull request`);
+          rejectedRefs.push({ ref: update.ref, error: "protected branch - direct push not allowed, use a pull request" });
           rejectedRefSet.add(update.ref);
           continue;
         }
       }
 
-      if (rejectedRefLines.length > 0) {
-        console.log(`[API] receive-pack: rejected ${rejectedRefLines.length} refs due to branch protection (pre-unpack)`);
+      if (rejectedRefs.length > 0) {
+        console.log(`[API] receive-pack: rejected ${rejectedRefs.length} refs due to branch protection (pre-unpack)`);
       }
     }
 
     // Filter to only allowed updates for the rest of the flow
     let allowedUpdates = updates.filter(u => !rejectedRefSet.has(u.ref));
 
-    // If all refs were rejected, skip unpacking entirely
-    if (allowedUpdates.length === 0 && rejectedRefLines.length > 0) {
-      let response = "";
-      const unpackOk = "unpack ok\n";
-      const unpackOkLen = unpackOk.length + 4;
-      response += unpackOkLen.toString(16).padStart(4, "0") + unpackOk;
-      for (const errorLine of rejectedRefLines) {
-        const line = errorLine + "\n";
-        const lineLen = line.length + 4;
-        response += lineLen.toString(16).padStart(4, "0") + line;
+    // An atomic push is all-or-nothing: one rejected ref fails the rest before anything is unpacked.
+    const failAtomicPush = () => {
+      for (const update of allowedUpdates) {
+        rejectedRefs.push({ ref: update.ref, error: "atomic push failure" });
+        rejectedRefSet.add(update.ref);
       }
-      response += "0000";
-      return new Response(Buffer.from(response, "ascii"), {
-        status: 200,
-        headers: {
-          "Content-Type": "application/x-git-receive-pack-result",
-          "Cache-Control": "no-cache",
-        },
-      });
+      allowedUpdates = [];
+    };
+    if (atomic && rejectedRefSet.size > 0) {
+      failAtomicPush();
     }
 
-    const storeObject = async (oid: string, type: string, data: Buffer) => {
-      const prefix = oid.substring(0, 2);
-      const suffix = oid.substring(2);
-      const objectPath = `repos/${result.userId}/${repo.name}/objects/${prefix}/${suffix}`;
+    // If all refs were rejected, skip unpacking entirely
+    if (allowedUpdates.length === 0 && rejectedRefs.length > 0) {
+      return reportStatusResponse(capabilities, null, rejectedRefs);
+    }
 
-      const header = `${type} ${data.length}\0`;
-      const store = Buffer.concat([Buffer.from(header), data]);
-      const compressed = zlib.deflateSync(store);
+    const storeObject = (oid: string, type: string, data: Buffer) => storeLooseObject(store, oid, type, data);
 
-      await putObject(objectPath, compressed);
-    };
+    const repoPrefix = storageLayout.repoPrefix(result.userId, repo.name);
+
+    // Pushes that only delete refs, or point them at objects the server already has, carry no pack.
+    let unpackResult: UnpackResult = { success: true, objectCount: 0, oids: new Set(), blobSizes: new Map() };
+    if (spooled.packPath) {
+      // Large packs are kept in S3 next to the loose objects, and dropped again if they can't be
+      // indexed. The pack is streamed from the spool file, never read into memory whole.
+      if (spooled.packSize >= ARCHIVE_PACK_BYTES && spooled.packChecksum) {
+        archivedPackKey = storageLayout.packKey(repoPrefix, `pack-${spooled.packChecksum.toString("hex")}`, "pack");
+        console.log(`[API] receive-pack: uploading pack to ${archivedPackKey}`);
+        await uploadMultipart(archivedPackKey, Bun.file(spooled.packPath).stream(), "application/x-git-packed-objects");
+      }
 
-    const basePath = `repos/${result.userId}/${repo.name}`;
-    console.log(`[API] receive-pack: unpacking pack file (${packData.length} bytes)`);
-    const unpackResult = await unpackPackFile(packData, storeObject, basePath);
-    if (!unpackResult.success) {
-      console.error(`[API] receive-pack: unpack failed: ${unpackResult.error}`);
-      throw new Error(unpackResult.error || "Failed to unpack");
+      console.log(`[API] receive-pack: unpacking pack file (${spooled.packSize} bytes)`);
+      unpackResult = await unpackPackFile({ path: spooled.packPath }, storeObject, store, config.git.maxBlobBytes, !!archivedPackKey, config.git.warnBlobBytes);
+      if (!unpackResult.success) {
+        console.error(`[API] receive-pack: unpack failed: ${unpackResult.error}`);
+        return reportStatusResponse(
+          capabilities,
+          unpackResult.error || "failed",
+          [...allowedUpdates.map((update) => ({ ref: update.ref, error: "unpacker error" })), ...rejectedRefs],
+          [`error: ${unpackResult.error || "the pack could not be unpacked"}`]
+        );
+      }
+      // Thin packs reference bases outside the pack and can't be indexed on their own.
+      if (archivedPackKey && !unpackResult.thin && unpackResult.indexEntries && unpackResult.packChecksum) {
+        const idx = createPackIndex(unpackResult.indexEntries, unpackResult.packChecksum);
+        await putObject(archivedPackKey.replace(/\.pack$/, ".idx"), idx);
+        archiveIndexed = true;
+      }
+      console.log(`[API] receive-pack: unpacked ${unpackResult.objectCount} objects`);
     }
-    console.log(`[API] receive-pack: unpacked ${unpackResult.objectCount} objects`);
+
+    // Objects are content-addressed and safe to write concurrently; everything from reading the
+    // old ref values to rebuilding branch metadata is not, so it runs under a per-repository lock.
+    if (allowedUpdates.length > 0) {
+      pushLock = await acquirePushLock(repo.id);
+      if (!pushLock) {
+        console.warn(`[API] receive-pack: another push to ${owner}/${name} is in progress`);
+        for (const update of allowedUpdates) {
+          rejectedRefs.push({ ref: update.ref, error: "another push is in progress, try again" });
+          rejectedRefSet.add(update.ref);
+        }
+        allowedUpdates = [];
+      }
+    }
+
+    for (const update of allowedUpdates) {
+      if (update.newOid === "0".repeat(40) || unpackResult.oids?.has(update.newOid)) continue;
+      if (!(await hasObject(store, update.newOid))) {
+        rejectedRefs.push({ ref: update.ref, error: "missing necessary objects" });
+        rejectedRefSet.add(update.ref);
+      }
+    }
+    allowedUpdates = allowedUpdates.filter(u => !rejectedRefSet.has(u.ref));
 
     // Force push detection (after unpack so objects are available for ancestry check)
     if (protectionRules.length > 0 && allowedUpdates.length > 0) {
@@ -776,7 +667,7 @@ app.post("/:owner/:name/git-receive-pack", async (c) => {
 
         const ancestor = await isAncestor(store.fs, store.dir, update.oldOid, update.newOid);
         if (!ancestor) {
-          rejectedRefLines.push(`ng ${update.ref} protected branch - force push not allowed`);
+          rejectedRefs.push({ ref: update.ref, error: "protected branch - force push not allowed" });
           rejectedRefSet.add(update.ref);
         }
       }
@@ -789,18 +680,48 @@ app.post("/:owner/:name/git-receive-pack", async (c) => {
       }
     }
 
-    for (const update of allowedUpdates) {
-      const refPath = update.ref.startsWith("refs/") ? update.ref : `refs/heads/${update.ref}`;
-      const refKey = `repos/${result.userId}/${repo.name}/${refPath}`;
-
-      if (update.newOid === "0".repeat(40)) {
-        await deleteObject(refKey).catch(() => { /* intentional no-op */ });
-      } else {
-        await putObject(refKey, Buffer.from(update.newOid + "\n"));
+    const pushPolicy = await getPushPolicy(repo.id);
+    if (pushPolicy && hasPushChecks(pushPolicy) && allowedUpdates.length > 0) {
+      let pusherEmails: string[] | null = null;
+      if (access.user) {
+        const pusher = await db.query.users.findFirst({ where: eq(users.id, access.user.id), columns: { gitEmail: true } });
+        pusherEmails = [access.user.email, pusher?.gitEmail].filter((email): email is string => !!email);
+      }
 
+      for (const update of allowedUpdates) {
+        const violation = await checkPushPolicy(
+          store,
+          pushPolicy,
+          update,
+          unpackResult.oids ?? new Set(),
+          unpackResult.blobSizes ?? new Map(),
+          pusherEmails
+        ).catch((error) => {
+          console.error(`[API] receive-pack: push policy check failed for ${update.ref}:`, error);
+          return "could not be checked";
+        });
+        if (violation) {
+          rejectedRefs.push({ ref: update.ref, error: `push policy: ${violation}` });
+          rejectedRefSet.add(update.ref);
+        }
       }
+      allowedUpdates = allowedUpdates.filter(u => !rejectedRefSet.has(u.ref));
     }
 
+    if (atomic && rejectedRefSet.size > 0) {
+      failAtomicPush();
+    }
+
+    // Plain "ok"/"ng" lines are valid for both report-status and report-status-v2; v2 only adds
+    // option lines for refs the server rewrote, which never happens here.
+    const refErrors = await applyRefUpdates(repo.id, repoPrefix, allowedUpdates, atomic);
+    for (const [ref, reason] of refErrors) {
+      rejectedRefs.push({ ref, error: reason });
+      rejectedRefSet.add(ref);
+    }
+    allowedUpdates = allowedUpdates.filter(u => !rejectedRefSet.has(u.ref));
+    recordRefUpdates(repo.id, access.user?.id, "push", allowedUpdates);
+
     for (const update of allowedUpdates) {
       const branch = update.ref.startsWith("refs/heads/")
         ? update.ref.replace("refs/heads/", "")
@@ -808,49 +729,126 @@ app.post("/:owner/:name/git-receive-pack", async (c) => {
       await repoCache.invalidateBranch(result.userId, repo.name, branch);
     }
 
-    console.log(`[API] receive-pack: building response for ${allowedUpdates.length} allowed, ${rejectedRefLines.length} rejected`);
+    // The lock stays held until every pushed branch's metadata row and warm caches match the new
+    // refs, so the next push can't interleave its rebuild with this one. The task owns it from here.
+    const heldLock = pushLock;
+    pushLock = null;
+    const branchUpdates = allowedUpdates.filter((update) => update.ref.startsWith("refs/heads/"));
+    trackTask("branch_metadata", async () => {
+      try {
+        for (const update of branchUpdates) {
+          const branch = update.ref.replace("refs/heads/", "");
+          try {
+            if (update.newOid === "0".repeat(40)) {
+              await repairBranchMetadata(repo.id, store, branch);
+              continue;
+            }
+            const parentOid = update.oldOid === "0".repeat(40) ? null : update.oldOid;
+            await refreshBranchMetadata(repo.id, store, branch, update.newOid, parentOid);
+            await warmBranchCaches(store, branch, repo.defaultBranch);
+          } catch (error) {
+            console.error(`[API] receive-pack: refreshing metadata for ${owner}/${name}@${branch} failed:`, error);
+          }
+        }
+      } finally {
+        await heldLock?.release();
+      }
+    }).catch(() => { /* logged per branch above */ });
 
-    let response = "";
-    const unpackOk = "unpack ok\n";
-    const unpackOkLen = unpackOk.length + 4;
-    response += unpackOkLen.toString(16).padStart(4, "0") + unpackOk;
+    if (allowedUpdates.length > 0) {
+      trackTask("pack_cache", () => clearPackCache(store)).catch((error) => {
+        console.error("[API] receive-pack: clearing cached packs failed:", error);
+      });
+      trackTask("reachability_index", () => updateReachabilityIndex(store)).catch((error) => {
+        console.error("[API] receive-pack: updating reachability index failed:", error);
+      });
+    }
 
     for (const update of allowedUpdates) {
-      const line = `ok ${update.ref}\n`;
-      const lineLen = line.length + 4;
-      response += lineLen.toString(16).padStart(4, "0") + line;
+      if (update.newOid === "0".repeat(40) || !update.ref.startsWith("refs/heads/")) continue;
+      trackTask("issue_references", () => recordPushedCommitReferences(repo.id, store, update.oldOid, update.newOid, access.user?.id)).catch((error) => {
+        console.error("[API] receive-pack: recording issue references failed:", error);
+      });
     }
 
-    for (const errorLine of rejectedRefLines) {
-      const line = errorLine + "\n";
-      const lineLen = line.length + 4;
-      response += lineLen.toString(16).padStart(4, "0") + line;
+    const pushedTips = allowedUpdates
+      .filter((update) => update.newOid !== "0".repeat(40) && update.ref.startsWith("refs/heads/"))
+      .map((update) => update.newOid);
+    if (pushedTips.length > 0) {
+      scheduleCommitCounts({ id: repo.id, ownerId: result.userId, name: repo.name }, pushedTips);
     }
 
-    response += "0000";
+    const largeFiles = allowedUpdates.length > 0 ? (unpackResult.largeFiles ?? []) : [];
+    if (largeFiles.length > 0) {
+      console.warn(
+        `[API] receive-pack: ${owner}/${name} received ${largeFiles.length} file(s) over ${config.git.warnBlobBytes} bytes: ${largeFiles.map((file) => `${file.path ?? file.oid} (${file.size})`).join(", ")}`
+      );
+    }
 
-    console.log(`[API] receive-pack: sending response (${response.length} bytes)`);
+    // Deploy-key pushes have no user to attribute them to, so they stay out of the activity feed.
+    const pusherId = access.user?.id;
+    if (pusherId) {
+      for (const update of allowedUpdates) {
+        if (update.newOid === "0".repeat(40) || !update.ref.startsWith("refs/heads/")) continue;
+        recordActivity({
+          type: "pushed",
+          actorId: pusherId,
+          repositoryId: repo.id,
+          payload: { ref: update.ref, before: update.oldOid, after: update.newOid, ...(largeFiles.length > 0 && { largeFiles }) },
+        });
+      }
+    }
 
-    return new Response(Buffer.from(response, "ascii"), {
-      status: 200,
-      headers: {
-        "Content-Type": "application/x-git-receive-pack-result",
-        "Cache-Control": "no-cache",
-      },
-    });
+    const defaultBranchUpdate = allowedUpdates.find(
+      (u) => u.ref === `refs/heads/${repo.defaultBranch}` && u.newOid !== "0".repeat(40)
+    );
+    if (defaultBranchUpdate) {
+      refreshLanguageStats(repo.id, result.userId, repo.name, repo.defaultBranch);
+      trackTask("repo_files", () => refreshRepoFiles(repo.id, store, repo.defaultBranch)).catch((error) => {
+        console.error("[API] receive-pack: license/CODEOWNERS refresh failed:", error);
+      });
+    }
+
+    if (config.git.fsckAfterPush && allowedUpdates.length > 0) {
+      trackTask("fsck", () => runFsck(store))
+        .then((report) => {
+          if (!report.ok) {
+            console.error(`[API] receive-pack: integrity check failed for ${owner}/${name}:`, JSON.stringify(report));
+          }
+        })
+        .catch((error) => {
+          console.error("[API] receive-pack: integrity check errored:", error);
+        });
+    }
+
+    console.log(`[API] receive-pack: building response for ${allowedUpdates.length} allowed, ${rejectedRefs.length} rejected`);
+
+    // isomorphic-git reads a failed storage request as a missing object, so refs rejected during an
+    // outage may have been turned away for the wrong reason.
+    const messages = largeFileWarnings(largeFiles, config.git.warnBlobBytes, config.git.maxBlobBytes);
+    if (hasStorageFailure() && rejectedRefs.length > 0) {
+      messages.push("warning: repository storage was unavailable during this push; rejected refs may succeed if you push again");
+    }
+
+    return reportStatusResponse(capabilities, null, [...allowedUpdates.map((update) => ({ ref: update.ref })), ...rejectedRefs], messages);
   } catch (error) {
     console.error("[API] receive-pack error:", error);
-    const errorText = error instanceof Error ? error.message : "unpack error";
-    const errorLine = `ng ${errorText}\n`;
-    const errorLen = errorLine.length + 4;
-    const response = errorLen.toString(16).padStart(4, "0") + errorLine + "0000";
-    return new Response(Buffer.from(response, "ascii"), {
-      status: 200,
-      headers: {
-        "Content-Type": "application/x-git-receive-pack-result",
-        "Cache-Control": "no-cache",
-      },
-    });
+    const failure = describePushFailure(error);
+    return reportStatusResponse(
+      parseReceiveCapabilities(spooled.commandSection),
+      failure.unpack,
+      parseRefUpdates(spooled.commandSection).map((update) => ({ ref: update.ref, error: failure.ref })),
+      [failure.message]
+    );
+  } finally {
+    await pushLock?.release();
+    await spooled.cleanup();
+    const unindexedPack = archiveIndexed ? null : archivedPackKey;
+    if (unindexedPack) {
+      await isolateStorageFailures(() => deleteObject(unindexedPack)).catch((error) => {
+        console.warn(`[API] receive-pack: failed to remove unindexed pack ${unindexedPack}:`, error);
+      });
+    }
   }
 });
 
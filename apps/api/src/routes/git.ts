@@ -1,17 +1,50 @@
 import { Hono } from "hono";
-import { db, users, repositories, stars, repoBranchMetadata } from "@gitbruv/db";
-import { eq, sql, and } from "drizzle-orm";
-import { authMiddleware, type AuthVariables } from "../middleware/auth";
+import { db, users, repositories, stars, repoBranchMetadata, repoInsights, repoSizes, issues, pullRequests, branchProtectionRules, commitStatuses, refLog } from "@gitbruv/db";
+import { eq, sql, and, desc, inArray } from "drizzle-orm";
+import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
 import {
   createGitStore,
   listBranchesCached,
   getCommitsCached,
-  getCommitCountCached,
   getTreeCached,
+  pageTreeEntries,
   getFileCached,
   getBlobByOid,
   getCommitDiff,
+  getFileDiffBetween,
+  getCommitByOid,
+  getCommitDetails,
+  resolveRefOid,
+  resolveCommittish,
+  isAncestor,
+  warmBranchCaches,
+  type CommitInfo,
+  type GitStore,
+  type TreeEntry,
 } from "../git";
+import { computeLanguageStats } from "../git/languages";
+import { parsePatch, applyPatchToBranch, PatchError } from "../git/patch";
+import { commitFileChange } from "../git/contents";
+import { runFsck } from "../git/fsck";
+import { recordRefUpdates } from "../ref-log";
+import { applyRefUpdates, ZERO_OID } from "../git/ref-transaction";
+import { storageLayout } from "../storage-layout";
+import { getCached, setCache, repoCache, CACHE_TTL } from "../cache";
+import { checkReadToken } from "../read-tokens";
+import { findReferenceLinks, applyReferenceLinks } from "../autolinks";
+import { getEntryLastCommits } from "../tree-entry-commits";
+import { getCombinedStatuses, combineStatusStates, COMMIT_STATUS_STATES, type CommitStatusState } from "../commit-statuses";
+import { encodeCursor, decodeCursor, afterCursor } from "../pagination";
+import { apiError } from "../errors";
+import { config } from "../config";
+import { trackTask } from "../metrics";
+import { scheduleRepoInsights } from "../jobs/repo-insights";
+import { scheduleRepoSize } from "../jobs/repo-size";
+import { MAX_LARGEST_BLOBS } from "../git/size";
+import { countCommits, rebuildRepoMetadata, refreshBranchMetadata } from "../branch-metadata";
+import { restoreFromBundle, RestoreError } from "../repo-restore";
+import { spoolRequestBody, SpoolTooLargeError, type SpooledFile } from "../git/spool";
+import { highlightBlob, resolveHighlightLanguage, MAX_HIGHLIGHT_BYTES } from "../highlight";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
@@ -76,6 +109,8 @@ async function getRepoAndStore(owner: string, name: string) {
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
       defaultBranch: repositories.defaultBranch,
+      forkedFromId: repositories.forkedFromId,
+      mirrorUrl: repositories.mirrorUrl,
       createdAt: repositories.createdAt,
       updatedAt: repositories.updatedAt,
       userId: users.id,
@@ -99,6 +134,8 @@ async function getRepoAndStore(owner: string, name: string) {
       ownerId: row.ownerId,
       visibility: row.visibility,
       defaultBranch: row.defaultBranch,
+      forkedFromId: row.forkedFromId,
+      mirrorUrl: row.mirrorUrl,
       createdAt: row.createdAt,
       updatedAt: row.updatedAt,
     },
@@ -107,6 +144,8 @@ async function getRepoAndStore(owner: string, name: string) {
   };
 }
 
+// Exact once the tip has a commit_counts row; until the background count gets there, falls back
+// to the cached full walk.
 async function getUsersByEmails(emails: string[]): Promise<Map<string, { id: string; username: string; avatarUrl: string | null }>> {
   if (emails.length === 0) return new Map();
 
@@ -123,6 +162,14 @@ async function getUsersByEmails(emails: string[]): Promise<Map<string, { id: str
   return new Map(result.map((u) => [u.email, { id: u.id, username: u.username, avatarUrl: u.avatarUrl }]));
 }
 
+function withUser<T extends { email: string }>(
+  person: T,
+  userMap: Map<string, { id: string; username: string; avatarUrl: string | null }>
+) {
+  const user = userMap.get(person.email);
+  return { ...person, username: user?.username, userId: user?.id, avatarUrl: user?.avatarUrl };
+}
+
 app.get("/api/repositories/:owner/:name/branches", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -130,42 +177,208 @@ app.get("/api/repositories/:owner/:name/branches", async (c) => {
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const branches = await listBranchesCached(store);
   return c.json({ branches });
 });
 
+app.get("/api/repositories/:owner/:name/branches/stale", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+  const days = parseInt(c.req.query("days") || "90", 10);
+
+  if (!Number.isInteger(days) || days < 0) {
+    return apiError(c, 400, "Invalid days");
+  }
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  let defaultOid: string;
+  try {
+    defaultOid = await resolveRefOid(store, repo.defaultBranch);
+  } catch {
+    return c.json({ branches: [], defaultBranch: repo.defaultBranch, days });
+  }
+
+  const cutoff = Date.now() - days * 24 * 60 * 60 * 1000;
+  const branches = (await listBranchesCached(store)).filter((branch) => branch !== repo.defaultBranch);
+
+  const candidates = await Promise.all(
+    branches.map(async (branch) => {
+      try {
+        const headOid = await resolveRefOid(store, branch);
+        const head = await getCommitByOid(store.fs, store.dir, headOid);
+        if (!head || head.commit.timestamp > cutoff) return null;
+        if (!(await isAncestor(store.fs, store.dir, headOid, defaultOid))) return null;
+        return {
+          name: branch,
+          headOid,
+          lastCommit: {
+            message: head.commit.message.split("\n")[0],
+            author: head.commit.author.name,
+            timestamp: head.commit.timestamp,
+          },
+        };
+      } catch {
+        return null;
+      }
+    })
+  );
+
+  const stale = candidates
+    .filter((branch) => branch !== null)
+    .sort((a, b) => a.lastCommit.timestamp - b.lastCommit.timestamp);
+
+  return c.json({ branches: stale, defaultBranch: repo.defaultBranch, days });
+});
+
+app.delete("/api/repositories/:owner/:name/branches", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+  const body = await c.req.json<{ branches?: string[] }>();
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Unauthorized");
+  }
+
+  if (repo.mirrorUrl) {
+    return apiError(c, 400, "Repository is a read-only mirror");
+  }
+
+  if (!Array.isArray(body.branches) || body.branches.length === 0 || body.branches.some((b) => typeof b !== "string")) {
+    return apiError(c, 400, "Branches must be a non-empty array of branch names");
+  }
+
+  const requested = [...new Set(body.branches)];
+  const protectedRules = await db
+    .select({ branchName: branchProtectionRules.branchName })
+    .from(branchProtectionRules)
+    .where(
+      and(
+        eq(branchProtectionRules.repositoryId, repo.id),
+        inArray(branchProtectionRules.branchName, requested),
+        eq(branchProtectionRules.preventDeletion, true)
+      )
+    );
+  const protectedBranches = new Set(protectedRules.map((rule) => rule.branchName));
+
+  const skipped: { branch: string; reason: string }[] = [];
+  const deletions: { branch: string; oldOid: string }[] = [];
+
+  for (const branch of requested) {
+    if (branch === repo.defaultBranch) {
+      skipped.push({ branch, reason: "Cannot delete the default branch" });
+    } else if (protectedBranches.has(branch)) {
+      skipped.push({ branch, reason: "Branch is protected from deletion" });
+    } else {
+      const oldOid = await resolveRefOid(store, branch).catch(() => null);
+      if (oldOid) {
+        deletions.push({ branch, oldOid });
+      } else {
+        skipped.push({ branch, reason: "Branch not found" });
+      }
+    }
+  }
+
+  // Each delete only lands if the branch still points where it did when it was read, so a push
+  // racing this request keeps its commits.
+  const refErrors = await applyRefUpdates(
+    repo.id,
+    storageLayout.repoPrefix(store.ownerId, store.repoName),
+    deletions.map(({ branch, oldOid }) => ({ ref: `refs/heads/${branch}`, oldOid, newOid: ZERO_OID })),
+    false
+  );
+
+  const deleted: string[] = [];
+  const deletedOids: { ref: string; oldOid: string; newOid: null }[] = [];
+  for (const { branch, oldOid } of deletions) {
+    const error = refErrors.get(`refs/heads/${branch}`);
+    if (error) {
+      skipped.push({ branch, reason: error === "stale info" ? "Branch was updated while it was being deleted" : "Failed to delete branch" });
+      continue;
+    }
+    deleted.push(branch);
+    deletedOids.push({ ref: branch, oldOid, newOid: null });
+  }
+
+  if (deleted.length > 0) {
+    recordRefUpdates(repo.id, user.id, "branch_delete", deletedOids);
+    await db
+      .delete(repoBranchMetadata)
+      .where(and(eq(repoBranchMetadata.repoId, repo.id), inArray(repoBranchMetadata.branch, deleted)));
+    for (const branch of deleted) {
+      await repoCache.invalidateBranch(store.ownerId, store.repoName, branch);
+    }
+  }
+
+  return c.json({ deleted, skipped });
+});
+
 app.get("/api/repositories/:owner/:name/commits", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
   const limit = parseInt(c.req.query("limit") || "30", 10);
-  const skip = parseInt(c.req.query("skip") || "0", 10);
+  const cursorParam = c.req.query("cursor");
+
+  // Commit cursors pin the tip the first page was read from, so pushes between pages don't
+  // shift later pages.
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["oid", "integer"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const ref = c.req.query("ref") || c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const tip = cursor ? cursor[0] : await resolveCommittish(store.fs, store.dir, ref);
+  const skip = cursor ? parseInt(cursor[1], 10) : parseInt(c.req.query("skip") || "0", 10);
+  if (!tip) {
+    return c.json({ commits: [], hasMore: false, nextCursor: null });
   }
 
-  const { commits, hasMore } = await getCommitsCached(store, branch, limit, skip);
+  const { commits, hasMore } = await getCommitsCached(store, tip, limit, skip);
 
-  const emails = commits.map((c) => c.author.email);
-  const userMap = await getUsersByEmails(emails);
+  const emails = commits.flatMap((c) => [c.author.email, ...(c.trailers?.coAuthors ?? []).map((coAuthor) => coAuthor.email)]);
+  const [userMap, statusMap] = await Promise.all([
+    getUsersByEmails([...new Set(emails)]),
+    getCombinedStatuses(repo.id, commits.map((commit) => commit.oid)),
+  ]);
 
   const enrichedCommits = commits.map((commit) => {
     const user = userMap.get(commit.author.email);
@@ -177,39 +390,78 @@ app.get("/api/repositories/:owner/:name/commits", async (c) => {
         userId: user?.id,
         avatarUrl: user?.avatarUrl,
       },
+      coAuthors: (commit.trailers?.coAuthors ?? []).map((coAuthor) => withUser(coAuthor, userMap)),
+      status: statusMap.get(commit.oid) ?? null,
     };
   });
 
-  return c.json({ commits: enrichedCommits, hasMore });
+  return c.json({
+    commits: enrichedCommits,
+    hasMore,
+    nextCursor: hasMore ? encodeCursor([tip, String(skip + limit)]) : null,
+  });
 });
 
 app.get("/api/repositories/:owner/:name/commits/count", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const branch = c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
-  const metadata = await db.query.repoBranchMetadata.findFirst({
-    where: and(eq(repoBranchMetadata.repoId, repo.id), eq(repoBranchMetadata.branch, branch)),
-  });
+  const count = await countCommits(repo.id, store, branch);
+  return c.json({ count });
+});
+
+app.get("/api/repositories/:owner/:name/commits/:ref", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const ref = c.req.param("ref");
+  const currentUser = c.get("user");
 
-  if (metadata) {
-    return c.json({ count: metadata.commitCount });
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
   }
 
-  const count = await getCommitCountCached(store, branch);
-  return c.json({ count });
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const commit = await getCommitDetails(store.fs, store.dir, ref);
+  if (!commit) {
+    return apiError(c, 404, "Commit not found");
+  }
+
+  const coAuthors = commit.trailers?.coAuthors ?? [];
+  const [userMap, statusMap] = await Promise.all([
+    getUsersByEmails([commit.author.email, commit.committer.email, ...coAuthors.map((coAuthor) => coAuthor.email)]),
+    getCombinedStatuses(repo.id, [commit.oid]),
+  ]);
+  const author = userMap.get(commit.author.email);
+  const committer = userMap.get(commit.committer.email);
+
+  return c.json({
+    commit: {
+      ...commit,
+      author: { ...commit.author, username: author?.username, userId: author?.id, avatarUrl: author?.avatarUrl },
+      committer: { ...commit.committer, username: committer?.username, userId: committer?.id, avatarUrl: committer?.avatarUrl },
+      coAuthors: coAuthors.map((coAuthor) => withUser(coAuthor, userMap)),
+      status: statusMap.get(commit.oid) ?? null,
+    },
+  });
 });
 
 app.get("/api/repositories/:owner/:name/commits/:oid/diff", async (c) => {
@@ -220,18 +472,18 @@ app.get("/api/repositories/:owner/:name/commits/:oid/diff", async (c) => {
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const diff = await getCommitDiff(store.fs, store.dir, oid);
   if (!diff) {
-    return c.json({ error: "Commit not found" }, 404);
+    return apiError(c, 404, "Commit not found");
   }
 
   const userMap = await getUsersByEmails([diff.commit.author.email]);
@@ -253,42 +505,223 @@ app.get("/api/repositories/:owner/:name/commits/:oid/diff", async (c) => {
   });
 });
 
+// Hunks for a single file between two committish values. Both ends are returned as commit oids
+// so a client can cache the result against them rather than against moving branch names.
+app.get("/api/repositories/:owner/:name/diff", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+  const path = c.req.query("path");
+  const base = c.req.query("base");
+  const head = c.req.query("head");
+
+  if (!path || !base || !head) {
+    return apiError(c, 400, "path, base and head are required");
+  }
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    const allowed = await checkReadToken(repo.id, c.req.query("token"), path);
+    if (!allowed) {
+      return apiError(c, 404, "Repository not found");
+    }
+  }
+
+  const [baseOid, headOid] = await Promise.all([
+    resolveCommittish(store.fs, store.dir, base),
+    resolveCommittish(store.fs, store.dir, head),
+  ]);
+  if (!baseOid) {
+    return apiError(c, 404, `Base ${base} not found`);
+  }
+  if (!headOid) {
+    return apiError(c, 404, `Head ${head} not found`);
+  }
+
+  const file = await getFileDiffBetween(store.fs, store.dir, baseOid, headOid, path);
+  if (!file) {
+    return apiError(c, 404, "File not found");
+  }
+
+  return c.json({ base: baseOid, head: headOid, file });
+});
+
+app.get("/api/repositories/:owner/:name/statuses/:oid", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const oid = c.req.param("oid");
+  const currentUser = c.get("user");
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const statuses = await db
+    .select({
+      id: commitStatuses.id,
+      state: commitStatuses.state,
+      context: commitStatuses.context,
+      targetUrl: commitStatuses.targetUrl,
+      description: commitStatuses.description,
+      createdAt: commitStatuses.createdAt,
+      updatedAt: commitStatuses.updatedAt,
+      creator: {
+        id: users.id,
+        username: users.username,
+        avatarUrl: users.avatarUrl,
+      },
+    })
+    .from(commitStatuses)
+    .leftJoin(users, eq(users.id, commitStatuses.creatorId))
+    .where(and(eq(commitStatuses.repositoryId, repo.id), eq(commitStatuses.commitOid, oid)))
+    .orderBy(desc(commitStatuses.updatedAt));
+
+  return c.json({
+    oid,
+    state: combineStatusStates(statuses.map((status) => status.state)),
+    statuses,
+  });
+});
+
+app.post("/api/repositories/:owner/:name/statuses/:oid", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const oid = c.req.param("oid");
+  const user = c.get("user")!;
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Unauthorized");
+  }
+
+  const body = await c.req.json<{ state?: string; context?: string; targetUrl?: string | null; description?: string | null }>();
+
+  if (!body.state || !COMMIT_STATUS_STATES.includes(body.state as CommitStatusState)) {
+    return apiError(c, 400, `State must be one of: ${COMMIT_STATUS_STATES.join(", ")}`);
+  }
+
+  const context = body.context?.trim() || "default";
+  if (context.length > 255) {
+    return apiError(c, 400, "Context is too long");
+  }
+
+  if (body.targetUrl) {
+    try {
+      const url = new URL(body.targetUrl);
+      if (url.protocol !== "http:" && url.protocol !== "https:") throw new Error();
+    } catch {
+      return apiError(c, 400, "Invalid target URL");
+    }
+  }
+
+  if (!/^[0-9a-f]{40}$/.test(oid) || !(await getCommitByOid(store.fs, store.dir, oid))) {
+    return apiError(c, 404, "Commit not found");
+  }
+
+  const values = {
+    state: body.state as CommitStatusState,
+    targetUrl: body.targetUrl || null,
+    description: body.description?.trim() || null,
+    creatorId: user.id,
+    updatedAt: new Date(),
+  };
+
+  const [status] = await db
+    .insert(commitStatuses)
+    .values({ repositoryId: repo.id, commitOid: oid, context, ...values })
+    .onConflictDoUpdate({
+      target: [commitStatuses.repositoryId, commitStatuses.commitOid, commitStatuses.context],
+      set: values,
+    })
+    .returning();
+
+  return c.json(status);
+});
+
+const DEFAULT_TREE_PAGE_SIZE = 1000;
+const MAX_TREE_PAGE_SIZE = 1000;
+
+// Tree cursors carry the last entry's name and whether it was a directory, which is all the
+// listing order needs to resume.
+function parseTreePage(limitParam: string | undefined, cursorParam: string | undefined) {
+  const limit = Math.min(Math.max(parseInt(limitParam || String(DEFAULT_TREE_PAGE_SIZE), 10) || DEFAULT_TREE_PAGE_SIZE, 1), MAX_TREE_PAGE_SIZE);
+  if (!cursorParam) {
+    return { limit, after: null };
+  }
+
+  const cursor = decodeCursor(cursorParam, ["text", "text"]);
+  if (!cursor || (cursor[0] !== "d" && cursor[0] !== "f")) {
+    return null;
+  }
+  return { limit, after: { directory: cursor[0] === "d", name: cursor[1] } };
+}
+
+function treePageCursor(entry: TreeEntry) {
+  return encodeCursor([entry.type === "tree" || entry.type === "commit" ? "d" : "f", entry.name]);
+}
+
 app.get("/api/repositories/:owner/:name/tree", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
   const path = c.req.query("path") || "";
 
+  const page = parseTreePage(c.req.query("limit"), c.req.query("cursor"));
+  if (!page) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const branch = c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    const allowed = await checkReadToken(repo.id, c.req.query("token"), path);
+    if (!allowed) {
+      return apiError(c, 404, "Repository not found");
+    }
   }
 
+  let files: TreeEntry[] | null = null;
   if (path === "") {
     const metadata = await db.query.repoBranchMetadata.findFirst({
       where: and(eq(repoBranchMetadata.repoId, repo.id), eq(repoBranchMetadata.branch, branch)),
     });
-
-    if (metadata?.rootTree) {
-      return c.json({
-        files: metadata.rootTree,
-        isEmpty: (metadata.rootTree as any[]).length === 0,
-      });
-    }
+    files = (metadata?.rootTree as TreeEntry[] | null | undefined) ?? null;
   }
+  files ??= (await getTreeCached(store, branch, path)) || [];
 
-  const files = await getTreeCached(store, branch, path);
+  const { entries, hasMore } = pageTreeEntries(files, page.after, page.limit);
 
   return c.json({
-    files: files || [],
-    isEmpty: !files || files.length === 0,
+    files: entries,
+    isEmpty: files.length === 0,
+    total: files.length,
+    hasMore,
+    nextCursor: hasMore ? treePageCursor(entries[entries.length - 1]) : null,
   });
 });
 
@@ -296,59 +729,102 @@ app.get("/api/repositories/:owner/:name/tree-commits", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
   const path = c.req.query("path") || "";
 
+  // Takes the same limit and cursor as the tree listing, so a client resolves last commits for
+  // exactly the page it shows and can skip pages (or whole directories) it doesn't need them for.
+  const page = parseTreePage(c.req.query("limit"), c.req.query("cursor"));
+  if (!page) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const branch = c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const files = await getTreeCached(store, branch, path);
   if (!files || files.length === 0) {
-    return c.json({ files: [] });
+    return c.json({ files: [], pending: false });
   }
 
-  return c.json({ files: files.map((f) => ({ ...f, lastCommit: null })) });
+  // Entries without a stored result are resolved in the background; `pending` tells the client
+  // to ask again rather than wait on a history walk here.
+  const { entries } = pageTreeEntries(files, page.after, page.limit);
+  const lastCommits = await getEntryLastCommits(
+    store,
+    repo.id,
+    branch,
+    path,
+    entries.map((entry) => entry.name)
+  );
+
+  return c.json(lastCommits);
 });
 
 app.get("/api/repositories/:owner/:name/file", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
   const path = c.req.query("path");
+  const include = new Set((c.req.query("include") || "").split(",").filter(Boolean));
 
   if (!path) {
-    return c.json({ error: "Path is required" }, 400);
+    return apiError(c, 400, "Path is required");
   }
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const branch = c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    const allowed = await checkReadToken(repo.id, c.req.query("token"), path);
+    if (!allowed) {
+      return apiError(c, 404, "Repository not found");
+    }
   }
 
   const file = await getFileCached(store, branch, path);
   if (!file) {
-    return c.json({ error: "File not found" }, 404);
+    return apiError(c, 404, "File not found");
+  }
+
+  if (!include.has("lastCommit")) {
+    return c.json({
+      content: file.content,
+      oid: file.oid,
+      path,
+    });
   }
 
+  // Same stored results as the tree listing; a file whose directory hasn't been resolved yet
+  // comes back with `lastCommitPending` while the walk runs in the background.
+  const separator = path.lastIndexOf("/");
+  const { files, pending } = await getEntryLastCommits(
+    store,
+    repo.id,
+    branch,
+    separator === -1 ? "" : path.slice(0, separator),
+    [path.slice(separator + 1)]
+  );
+
   return c.json({
     content: file.content,
     oid: file.oid,
     path,
+    lastCommit: files[0] ?? null,
+    lastCommitPending: pending,
   });
 });
 
@@ -356,17 +832,17 @@ app.get("/api/repositories/:owner/:name/readme-oid", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
   const currentUser = c.get("user");
-  const branch = c.req.query("branch") || "main";
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
+  const branch = c.req.query("branch") || repo.defaultBranch;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const metadata = await db.query.repoBranchMetadata.findFirst({
@@ -393,28 +869,726 @@ app.get("/api/repositories/:owner/:name/readme", async (c) => {
   const oid = c.req.query("oid");
 
   if (!oid) {
-    return c.json({ error: "OID is required" }, 400);
+    return apiError(c, 400, "OID is required");
   }
 
   const result = await getRepoAndStore(owner, name);
   if (!result) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const { repo, store } = result;
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const content = await getBlobByOid(store.fs, store.dir, oid);
   if (!content) {
-    return c.json({ error: "Readme not found" }, 404);
+    return apiError(c, 404, "Readme not found");
   }
 
   return c.json({ content });
 });
 
+app.get("/api/repositories/:owner/:name/highlight/:oid", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const oid = c.req.param("oid");
+  const currentUser = c.get("user");
+  const theme = c.req.query("theme") || "light";
+  const format = c.req.query("format") || "html";
+
+  if (!/^[0-9a-f]{40}$/.test(oid)) {
+    return apiError(c, 400, "Invalid OID");
+  }
+  if (theme !== "light" && theme !== "dark") {
+    return apiError(c, 400, "Theme must be light or dark");
+  }
+  if (format !== "html" && format !== "tokens") {
+    return apiError(c, 400, "Format must be html or tokens");
+  }
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const content = await getBlobByOid(store.fs, store.dir, oid);
+  if (content === null) {
+    return apiError(c, 404, "Blob not found");
+  }
+  if (Buffer.byteLength(content) > MAX_HIGHLIGHT_BYTES) {
+    return apiError(c, 413, `Files over ${MAX_HIGHLIGHT_BYTES} bytes are not highlighted`);
+  }
+  if (content.includes("\0")) {
+    return apiError(c, 400, "Binary files can't be highlighted");
+  }
+
+  const language = resolveHighlightLanguage(c.req.query("lang"), c.req.query("path"));
+  const highlighted = await highlightBlob(oid, content, language, theme, format);
+  c.header("Cache-Control", repo.visibility === "private" ? "private, max-age=86400" : "public, max-age=86400");
+  return c.json(highlighted);
+});
+
+type GitOverview = {
+  branches: string[];
+  files: Awaited<ReturnType<typeof getTreeCached>>;
+  commitCount: number;
+  lastCommit: CommitInfo | null;
+  readme: { oid: string; content: string } | null;
+};
+
+async function getGitOverview(repoId: string, store: GitStore, branch: string): Promise<GitOverview> {
+  const cacheKey = repoCache.overviewKey(store.ownerId, store.repoName, branch);
+  const cached = await getCached<GitOverview>(cacheKey);
+  if (cached) {
+    return cached;
+  }
+
+  const metadata = await db.query.repoBranchMetadata.findFirst({
+    where: and(eq(repoBranchMetadata.repoId, repoId), eq(repoBranchMetadata.branch, branch)),
+  });
+
+  const treePromise = metadata?.rootTree
+    ? Promise.resolve(metadata.rootTree as GitOverview["files"])
+    : getTreeCached(store, branch, "");
+
+  const countPromise = countCommits(repoId, store, branch);
+
+  const readmePromise = (async () => {
+    let readmeOid = metadata?.readmeOid || null;
+    if (!readmeOid) {
+      const files = await treePromise;
+      readmeOid = files?.find((f) => f.name.toLowerCase() === "readme.md" && f.type === "blob")?.oid || null;
+    }
+    if (!readmeOid) return null;
+    const content = await getBlobByOid(store.fs, store.dir, readmeOid);
+    return content === null ? null : { oid: readmeOid, content };
+  })();
+
+  const lastCommitPromise = (async (): Promise<CommitInfo | null> => {
+    if (metadata) {
+      return {
+        oid: metadata.lastCommitOid,
+        message: metadata.lastCommitMessage,
+        author: { name: metadata.lastCommitAuthorName, email: metadata.lastCommitAuthorEmail },
+        timestamp: metadata.lastCommitTimestamp.getTime(),
+      };
+    }
+    const { commits } = await getCommitsCached(store, branch, 1, 0);
+    return commits[0] || null;
+  })();
+
+  const [branches, files, commitCount, readme, lastCommit] = await Promise.all([
+    listBranchesCached(store),
+    treePromise,
+    countPromise,
+    readmePromise,
+    lastCommitPromise,
+  ]);
+
+  const overview = { branches, files, commitCount, lastCommit, readme };
+  await setCache(cacheKey, overview, CACHE_TTL.overview);
+  return overview;
+}
+
+async function getOpenIssueCount(repoId: string) {
+  const [row] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(issues)
+    .where(and(eq(issues.repositoryId, repoId), eq(issues.state, "open")));
+  return Number(row?.count) || 0;
+}
+
+async function getOpenPullRequestCount(repoId: string) {
+  const [row] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(pullRequests)
+    .where(and(eq(pullRequests.baseRepoId, repoId), eq(pullRequests.state, "open")));
+  return Number(row?.count) || 0;
+}
+
+app.get("/api/repositories/:owner/:name/overview", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const branch = c.req.query("branch") || repo.defaultBranch;
+
+  const [git, ownerRow, starCount, starred, forkedFrom, forkCount, openIssues, openPullRequests] = await Promise.all([
+    getGitOverview(repo.id, store, branch),
+    db.query.users.findFirst({
+      where: eq(users.id, repo.ownerId),
+      columns: { id: true, username: true, name: true, avatarUrl: true },
+    }),
+    db
+      .select({ count: sql<number>`COUNT(*)` })
+      .from(stars)
+      .where(eq(stars.repositoryId, repo.id))
+      .then(([row]) => Number(row?.count) || 0),
+    currentUser
+      ? db.query.stars
+          .findFirst({ where: and(eq(stars.userId, currentUser.id), eq(stars.repositoryId, repo.id)) })
+          .then((row) => !!row)
+      : Promise.resolve(false),
+    getForkedFromInfo(repo.forkedFromId, currentUser?.id),
+    getForkCount(repo.id),
+    getOpenIssueCount(repo.id),
+    getOpenPullRequestCount(repo.id),
+  ]);
+
+  let lastCommit = null;
+  if (git.lastCommit) {
+    const userMap = await getUsersByEmails([git.lastCommit.author.email]);
+    const user = userMap.get(git.lastCommit.author.email);
+    lastCommit = {
+      ...git.lastCommit,
+      author: {
+        ...git.lastCommit.author,
+        username: user?.username,
+        userId: user?.id,
+        avatarUrl: user?.avatarUrl,
+      },
+    };
+  }
+
+  return c.json({
+    repo: {
+      id: repo.id,
+      name: repo.name,
+      description: repo.description,
+      visibility: repo.visibility,
+      defaultBranch: repo.defaultBranch,
+      createdAt: repo.createdAt,
+      updatedAt: repo.updatedAt,
+      owner: ownerRow,
+      starCount,
+      starred,
+      forkedFrom,
+      forkCount,
+    },
+    isOwner: currentUser?.id === repo.ownerId,
+    branch,
+    branches: git.branches,
+    files: git.files || [],
+    isEmpty: !git.files || git.files.length === 0,
+    commitCount: git.commitCount,
+    lastCommit,
+    readme: git.readme,
+    counts: {
+      openIssues,
+      openPullRequests,
+    },
+  });
+});
+
+app.post("/api/repositories/:owner/:name/render", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+  const body = await c.req.json<{ text?: string }>();
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (typeof body.text !== "string") {
+    return apiError(c, 400, "Text is required");
+  }
+
+  const stored = await db.query.repositories.findFirst({
+    where: eq(repositories.id, repo.id),
+    columns: { externalReferences: true },
+  });
+  const externalReferences = stored?.externalReferences || [];
+
+  const links = findReferenceLinks(body.text, externalReferences, `/${owner}/${repo.name}/issues`);
+
+  return c.json({
+    markdown: applyReferenceLinks(body.text, links),
+    links,
+    externalReferences,
+  });
+});
+
+app.get("/api/repositories/:owner/:name/languages", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const stored = await db.query.repositories.findFirst({
+    where: eq(repositories.id, repo.id),
+    columns: { languages: true, languagesCommitOid: true },
+  });
+
+  let headOid: string | null = null;
+  try {
+    headOid = await resolveRefOid(store, repo.defaultBranch);
+  } catch {
+    return c.json({ languages: [] });
+  }
+
+  if (stored?.languages && stored.languagesCommitOid === headOid) {
+    return c.json({ languages: stored.languages });
+  }
+
+  const stats = await computeLanguageStats(store, repo.defaultBranch);
+  if (!stats) {
+    return c.json({ languages: [] });
+  }
+
+  await db
+    .update(repositories)
+    .set({ languages: stats.languages, languagesCommitOid: stats.commitOid })
+    .where(eq(repositories.id, repo.id));
+
+  return c.json({ languages: stats.languages });
+});
+
+// Like GitHub's statistics API: stats for the current head are served from Postgres, otherwise a
+// background computation is started and 202 tells the client to poll again.
+async function getRepoInsights(owner: string, name: string, currentUserId: string | undefined) {
+  const result = await getRepoAndStore(owner, name);
+  if (!result) return { status: "not_found" as const };
+
+  const { repo, store } = result;
+  if (repo.visibility === "private" && currentUserId !== repo.ownerId) {
+    return { status: "not_found" as const };
+  }
+
+  const headOid = await resolveCommittish(store.fs, store.dir, repo.defaultBranch);
+  if (!headOid) return { status: "empty" as const };
+
+  const stored = await db.query.repoInsights.findFirst({ where: eq(repoInsights.repositoryId, repo.id) });
+  if (stored?.headOid === headOid) {
+    return { status: "ready" as const, insights: stored };
+  }
+
+  scheduleRepoInsights({ id: repo.id, ownerId: repo.ownerId, name: repo.name }, headOid);
+  return { status: "computing" as const };
+}
+
+app.get("/api/repositories/:owner/:name/stats/code-frequency", async (c) => {
+  const result = await getRepoInsights(c.req.param("owner"), c.req.param("name"), c.get("user")?.id);
+
+  switch (result.status) {
+    case "not_found":
+      return apiError(c, 404, "Repository not found");
+    case "empty":
+      return c.json({ weeks: [], truncated: false });
+    case "computing":
+      return c.json({ status: "computing" }, 202);
+    case "ready":
+      return c.json({ weeks: result.insights.codeFrequency, truncated: result.insights.truncated });
+  }
+});
+
+app.get("/api/repositories/:owner/:name/stats/punch-card", async (c) => {
+  const result = await getRepoInsights(c.req.param("owner"), c.req.param("name"), c.get("user")?.id);
+
+  switch (result.status) {
+    case "not_found":
+      return apiError(c, 404, "Repository not found");
+    case "empty":
+      return c.json({ punchCard: [], truncated: false });
+    case "computing":
+      return c.json({ status: "computing" }, 202);
+    case "ready":
+      return c.json({ punchCard: result.insights.punchCard, truncated: result.insights.truncated });
+  }
+});
+
+// Scanning lists every key under the repository, so a stored result is served while a fresh one
+// is computed in the background once it's older than this.
+const REPO_SIZE_MAX_AGE_MS = 60 * 60 * 1000;
+
+app.get("/api/repositories/:owner/:name/size", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+  const limit = Math.min(Math.max(parseInt(c.req.query("limit") || "10", 10) || 10, 1), MAX_LARGEST_BLOBS);
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo } = result;
+  if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const stored = await db.query.repoSizes.findFirst({ where: eq(repoSizes.repositoryId, repo.id) });
+  if (!stored || Date.now() - stored.computedAt.getTime() > REPO_SIZE_MAX_AGE_MS) {
+    scheduleRepoSize({ id: repo.id, ownerId: repo.ownerId, name: repo.name });
+  }
+  if (!stored) {
+    return c.json({ status: "computing" }, 202);
+  }
+
+  return c.json({
+    totalBytes: stored.totalBytes,
+    packCount: stored.packCount,
+    packBytes: stored.packBytes,
+    looseObjectCount: stored.looseObjectCount,
+    looseBytes: stored.looseBytes,
+    sharedObjectCount: stored.sharedObjectCount,
+    sharedBytes: stored.sharedBytes,
+    largestBlobs: stored.largestBlobs.slice(0, limit),
+    computedAt: stored.computedAt,
+  });
+});
+
+app.post("/api/repositories/:owner/:name/apply-patch", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can apply patches");
+  }
+
+  if (repo.mirrorUrl) {
+    return apiError(c, 400, "Repository is a read-only mirror");
+  }
+
+  let body: { patch?: string; branch?: string; message?: string; expectedHeadOid?: string };
+  if (c.req.header("Content-Type")?.includes("application/json")) {
+    body = await c.req.json();
+  } else {
+    body = {
+      patch: await c.req.text(),
+      branch: c.req.query("branch"),
+      message: c.req.query("message"),
+      expectedHeadOid: c.req.query("expectedHeadOid"),
+    };
+  }
+
+  if (!body.patch?.trim()) {
+    return apiError(c, 400, "Patch is required");
+  }
+
+  const branch = body.branch || repo.defaultBranch;
+
+  const rule = await db.query.branchProtectionRules.findFirst({
+    where: and(eq(branchProtectionRules.repositoryId, repo.id), eq(branchProtectionRules.branchName, branch)),
+  });
+  if (rule?.preventDirectPush) {
+    return apiError(c, 403, "Branch is protected - use a pull request");
+  }
+
+  let parsed: ReturnType<typeof parsePatch>;
+  try {
+    parsed = parsePatch(body.patch);
+  } catch (error) {
+    if (error instanceof PatchError) {
+      return apiError(c, 400, error.message, { path: error.path });
+    }
+    throw error;
+  }
+
+  try {
+    const message =
+      body.message?.trim() ||
+      [parsed.subject, parsed.body].filter(Boolean).join("\n\n") ||
+      `Apply patch to ${parsed.files.length} file${parsed.files.length === 1 ? "" : "s"}`;
+
+    const applied = await applyPatchToBranch(store, branch, parsed, {
+      repositoryId: repo.id,
+      message,
+      committer: { name: user.name, email: user.email },
+      expectedHeadOid: body.expectedHeadOid,
+    });
+
+    recordRefUpdates(repo.id, user.id, "patch", [{ ref: branch, oldOid: applied.parentOid, newOid: applied.commitOid }]);
+    await repoCache.invalidateBranch(store.ownerId, store.repoName, branch);
+
+    return c.json({ branch, ...applied });
+  } catch (error) {
+    if (error instanceof PatchError) {
+      return apiError(c, 409, error.message, { path: error.path });
+    }
+    if (error instanceof Error && error.name === "NotFoundError") {
+      return apiError(c, 404, "Branch not found");
+    }
+    throw error;
+  }
+});
+
+app.post("/api/repositories/:owner/:name/contents/:path{.+}", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const path = c.req.param("path");
+  const user = c.get("user")!;
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can edit files");
+  }
+
+  if (repo.mirrorUrl) {
+    return apiError(c, 400, "Repository is a read-only mirror");
+  }
+
+  const body = await c.req.json<{
+    branch?: string;
+    message?: string;
+    content?: string | null;
+    encoding?: "utf-8" | "base64";
+    delete?: boolean;
+    sha?: string;
+    expectedHeadOid?: string;
+  }>();
+
+  if (!body.delete && typeof body.content !== "string") {
+    return apiError(c, 400, "Content is required");
+  }
+  if (body.encoding && body.encoding !== "utf-8" && body.encoding !== "base64") {
+    return apiError(c, 400, "Encoding must be utf-8 or base64");
+  }
+
+  const branch = body.branch || repo.defaultBranch;
+
+  const rule = await db.query.branchProtectionRules.findFirst({
+    where: and(eq(branchProtectionRules.repositoryId, repo.id), eq(branchProtectionRules.branchName, branch)),
+  });
+  if (rule?.preventDirectPush) {
+    return apiError(c, 403, "Branch is protected - use a pull request");
+  }
+
+  try {
+    const committed = await commitFileChange(
+      store,
+      branch,
+      { path, content: body.delete ? null : body.content!, encoding: body.encoding },
+      {
+        repositoryId: repo.id,
+        message: body.message?.trim() || `${body.delete ? "Delete" : body.sha ? "Update" : "Create"} ${path}`,
+        committer: { name: user.name, email: user.email },
+        expectedBlobOid: body.sha ?? (body.delete ? undefined : ""),
+        expectedHeadOid: body.expectedHeadOid,
+      }
+    );
+
+    recordRefUpdates(repo.id, user.id, "commit", [{ ref: branch, oldOid: committed.parentOid, newOid: committed.commitOid }]);
+    await repoCache.invalidateBranch(store.ownerId, store.repoName, branch);
+    await refreshBranchMetadata(repo.id, store, branch, committed.commitOid, committed.parentOid);
+    trackTask("cache_warm", () => warmBranchCaches(store, branch, repo.defaultBranch)).catch(() => { /* logged in warmBranchCaches */ });
+
+    return c.json({ branch, path, ...committed });
+  } catch (error) {
+    if (error instanceof PatchError) {
+      return apiError(c, 409, error.message, { path: error.path });
+    }
+    if (error instanceof Error && error.name === "NotFoundError") {
+      return apiError(c, 404, "Branch not found");
+    }
+    throw error;
+  }
+});
+
+app.post("/api/repositories/:owner/:name/fsck", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (result.repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can check repository integrity");
+  }
+
+  const report = await runFsck(result.store);
+  return c.json(report);
+});
+
+// Rebuilds the stored branch metadata from the refs themselves, for when it has drifted (a push
+// that bypassed the API, a build that gave up) and the owner doesn't want to wait for the
+// periodic reconciliation.
+app.post("/api/repositories/:owner/:name/metadata/refresh", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can refresh repository metadata");
+  }
+
+  const outcomes = await rebuildRepoMetadata(repo.id, store);
+  return c.json({ rebuilt: outcomes.rebuilt, removed: outcomes.removed, failed: outcomes.failed });
+});
+
+// Takes a bundle from an account export (or `git bundle create --all`) as the raw request body.
+app.post("/api/repositories/:owner/:name/restore", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+  const force = c.req.query("force") === "true";
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const { repo, store } = result;
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can restore the repository");
+  }
+  if (repo.mirrorUrl) {
+    return apiError(c, 409, "Repository is a read-only mirror");
+  }
+
+  const declaredLength = parseInt(c.req.header("Content-Length") || "0", 10);
+  if (declaredLength > config.git.maxPackBytes) {
+    return apiError(c, 413, `Bundle exceeds the maximum size of ${config.git.maxPackBytes} bytes`);
+  }
+  let spooled: SpooledFile;
+  try {
+    spooled = await spoolRequestBody(c.req.raw.body, config.git.maxPackBytes, "bundle");
+  } catch (error) {
+    if (error instanceof SpoolTooLargeError) {
+      return apiError(c, 413, `Bundle exceeds the maximum size of ${config.git.maxPackBytes} bytes`);
+    }
+    throw error;
+  }
+
+  try {
+    return c.json(await restoreFromBundle(repo, store, spooled.path, { force, actorId: user.id }));
+  } catch (error) {
+    if (error instanceof RestoreError) {
+      return apiError(c, error.status, error.message);
+    }
+    throw error;
+  } finally {
+    await spooled.cleanup();
+  }
+});
+
+// Entries outlive the branch itself, so a deleted or force-pushed branch can be recovered from
+// the old oid as long as the objects haven't been pruned.
+app.get("/api/repositories/:owner/:name/reflog/:branch{.+}", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const branch = c.req.param("branch");
+  const user = c.get("user")!;
+  const limit = Math.min(Math.max(parseInt(c.req.query("limit") || "50", 10) || 50, 1), 100);
+  const cursorParam = c.req.query("cursor");
+
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const result = await getRepoAndStore(owner, name);
+  if (!result) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (result.repo.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can view the reflog");
+  }
+
+  const ref = branch.startsWith("refs/") ? branch : `refs/heads/${branch}`;
+  const conditions = [eq(refLog.repositoryId, result.repo.id), eq(refLog.ref, ref)];
+  if (cursor) {
+    conditions.push(
+      afterCursor(
+        [
+          { column: refLog.createdAt, type: "timestamp" },
+          { column: refLog.id, type: "uuid" },
+        ],
+        cursor,
+        "desc"
+      )
+    );
+  }
+
+  const rows = await db
+    .select({
+      id: refLog.id,
+      ref: refLog.ref,
+      oldOid: refLog.oldOid,
+      newOid: refLog.newOid,
+      source: refLog.source,
+      createdAt: refLog.createdAt,
+      sortKey: sql<string>`${refLog.createdAt}::text`,
+      actor: { id: users.id, username: users.username, name: users.name, avatarUrl: users.avatarUrl },
+    })
+    .from(refLog)
+    .leftJoin(users, eq(users.id, refLog.actorId))
+    .where(and(...conditions))
+    .orderBy(desc(refLog.createdAt), desc(refLog.id))
+    .limit(limit + 1);
+
+  const hasMore = rows.length > limit;
+  const entries = rows.slice(0, limit);
+  const last = entries[entries.length - 1];
+
+  return c.json({
+    entries: entries.map(({ sortKey, ...entry }) => entry),
+    hasMore,
+    nextCursor: hasMore && last ? encodeCursor([last.sortKey, last.id]) : null,
+  });
+});
+
 app.get("/api/repositories/:owner/:name/info", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -431,9 +1605,23 @@ app.get("/api/repositories/:owner/:name/info", async (c) => {
       createdAt: repositories.createdAt,
       updatedAt: repositories.updatedAt,
       forkedFromId: repositories.forkedFromId,
+      isTemplate: repositories.isTemplate,
+      mirrorUrl: repositories.mirrorUrl,
+      mirrorIntervalMinutes: repositories.mirrorIntervalMinutes,
+      mirrorSyncStatus: repositories.mirrorSyncStatus,
+      mirrorLastSyncedAt: repositories.mirrorLastSyncedAt,
+      mirrorLastError: repositories.mirrorLastError,
+      license: repositories.license,
       username: users.username,
       userName: users.name,
       avatarUrl: users.avatarUrl,
+      // Counted alongside the row so the header's numbers cost no extra round trips.
+      starCount: sql<number>`(SELECT COUNT(*) FROM stars WHERE repository_id = ${repositories.id})`,
+      forkCount: sql<number>`(SELECT COUNT(*) FROM repositories AS forks WHERE forks.forked_from_id = ${repositories.id})`,
+      openIssueCount: sql<number>`(SELECT COUNT(*) FROM issues WHERE repository_id = ${repositories.id} AND state = 'open')`,
+      starred: currentUser
+        ? sql<boolean>`EXISTS (SELECT 1 FROM stars WHERE repository_id = ${repositories.id} AND user_id = ${currentUser.id})`
+        : sql<boolean>`false`,
     })
     .from(repositories)
     .innerJoin(users, eq(users.id, repositories.ownerId))
@@ -442,29 +1630,15 @@ app.get("/api/repositories/:owner/:name/info", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (row.visibility === "private" && currentUser?.id !== row.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
-  }
-
-  const [starCount] = await db
-    .select({ count: sql<number>`COUNT(*)` })
-    .from(stars)
-    .where(eq(stars.repositoryId, row.id));
-
-  let starred = false;
-  if (currentUser) {
-    const existing = await db.query.stars.findFirst({
-      where: and(eq(stars.userId, currentUser.id), eq(stars.repositoryId, row.id)),
-    });
-    starred = !!existing;
+    return apiError(c, 404, "Repository not found");
   }
 
   const isOwner = currentUser?.id === row.ownerId;
   const forkedFrom = await getForkedFromInfo(row.forkedFromId, currentUser?.id);
-  const forkCount = await getForkCount(row.id);
 
   return c.json({
     repo: {
@@ -481,10 +1655,22 @@ app.get("/api/repositories/:owner/:name/info", async (c) => {
         name: row.userName,
         avatarUrl: row.avatarUrl,
       },
-      starCount: Number(starCount?.count) || 0,
-      starred,
+      starCount: Number(row.starCount) || 0,
+      starred: Boolean(row.starred),
       forkedFrom,
-      forkCount,
+      forkCount: Number(row.forkCount) || 0,
+      openIssueCount: Number(row.openIssueCount) || 0,
+      isTemplate: row.isTemplate,
+      license: row.license,
+      mirror: row.mirrorUrl
+        ? {
+            url: row.mirrorUrl,
+            intervalMinutes: row.mirrorIntervalMinutes,
+            status: row.mirrorSyncStatus,
+            lastSyncedAt: row.mirrorLastSyncedAt,
+            lastError: isOwner ? row.mirrorLastError : null,
+          }
+        : null,
     },
     isOwner,
   });
@@ -507,11 +1693,11 @@ app.get("/api/repositories/:owner/:name/page-data", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (row.visibility === "private" && currentUser?.id !== row.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   return c.json({ isOwner: currentUser?.id === row.ownerId });
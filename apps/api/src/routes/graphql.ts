@@ -0,0 +1,41 @@
+import { Hono } from "hono";
+import { authMiddleware, type AuthVariables } from "../middleware/auth";
+import { parseOperation, GraphQLSyntaxError } from "../graphql/parser";
+import { execute } from "../graphql/execute";
+import { schema, createGraphQLContext } from "../graphql/schema";
+
+const app = new Hono<{ Variables: AuthVariables }>();
+
+app.use("*", authMiddleware);
+
+app.post("/api/graphql", async (c) => {
+  const body = await c.req.json<{ query?: unknown; variables?: unknown; operationName?: unknown }>().catch(() => null);
+
+  if (!body || typeof body.query !== "string") {
+    return c.json({ errors: [{ message: "Request body must include a query string" }] }, 400);
+  }
+  if (body.variables != null && (typeof body.variables !== "object" || Array.isArray(body.variables))) {
+    return c.json({ errors: [{ message: "variables must be an object" }] }, 400);
+  }
+
+  let operation;
+  try {
+    operation = parseOperation(body.query, typeof body.operationName === "string" ? body.operationName : null);
+  } catch (error) {
+    if (error instanceof GraphQLSyntaxError) {
+      return c.json({ errors: [{ message: error.message }] }, 400);
+    }
+    throw error;
+  }
+
+  const result = await execute(
+    schema,
+    operation,
+    (body.variables as Record<string, unknown> | null) ?? {},
+    createGraphQLContext(c.get("user")?.id)
+  );
+
+  return c.json(result);
+});
+
+export default app;
@@ -1,7 +1,13 @@
 import { Hono } from "hono";
-import { getObject, listObjects } from "../s3";
+import { getObject, listObjects, objectStorage } from "../s3";
+import { getStorageCircuitState } from "../s3-policy";
+import { getRedis } from "../redis";
+import { config } from "../config";
+import { getErrorRate, getIncidents, getJobStates, getPendingTasks, openIncident, resolveIncident } from "../metrics";
 import { db, users, repositories } from "@gitbruv/db";
-import { eq, and } from "drizzle-orm";
+import { eq, and, sql } from "drizzle-orm";
+import { apiError } from "../errors";
+import { storageLayout } from "../storage-layout";
 
 const app = new Hono();
 
@@ -13,6 +19,168 @@ app.get("/api/health", (c) => {
   return c.json({ status: "ok", version: "1.0.0" });
 });
 
+const DEPENDENCY_CHECK_TTL_MS = 30 * 1000;
+const READINESS_CHECK_TTL_MS = 5 * 1000;
+const DEPENDENCY_TIMEOUT_MS = 2000;
+
+type DependencyStatus = "ok" | "down" | "unconfigured";
+
+type DependencyCheck = { status: DependencyStatus; latencyMs: number | null; error?: string; circuit?: string; backend?: string };
+
+type DependencyName = "postgres" | "s3" | "redis";
+
+const DEPENDENCY_LABELS: Record<DependencyName, string> = {
+  postgres: "Database",
+  s3: "Storage",
+  redis: "Cache",
+};
+
+let dependencyCache: ({ checkedAt: number } & Record<DependencyName, DependencyCheck>) | null = null;
+let inFlightCheck: Promise<NonNullable<typeof dependencyCache>> | null = null;
+
+async function probe(run: () => Promise<unknown>): Promise<DependencyCheck> {
+  const startedAt = performance.now();
+  let timer: ReturnType<typeof setTimeout> | undefined;
+  try {
+    await Promise.race([
+      run(),
+      new Promise((_, reject) => {
+        timer = setTimeout(() => reject(new Error(`Timed out after ${DEPENDENCY_TIMEOUT_MS}ms`)), DEPENDENCY_TIMEOUT_MS);
+      }),
+    ]);
+    return { status: "ok", latencyMs: Math.round(performance.now() - startedAt) };
+  } catch (error) {
+    return {
+      status: "down",
+      latencyMs: Math.round(performance.now() - startedAt),
+      error: error instanceof Error && error.message.startsWith("Timed out") ? error.message : "Unreachable",
+    };
+  } finally {
+    clearTimeout(timer);
+  }
+}
+
+function checkPostgres(): Promise<DependencyCheck> {
+  return probe(() => db.execute(sql`SELECT 1`));
+}
+
+// Reported under "s3" whichever backend is in use; `backend` says which one answered. Only the S3
+// backend goes through runS3, so only it has a circuit to report.
+async function checkS3(): Promise<DependencyCheck> {
+  const backend = objectStorage.backend;
+  let configured = true;
+  const result = await probe(async () => {
+    configured = await objectStorage.ping();
+  });
+  if (!configured) return { status: "unconfigured", latencyMs: null, backend };
+  return backend === "s3" ? { ...result, backend, circuit: getStorageCircuitState() } : { ...result, backend };
+}
+
+async function checkRedis(): Promise<DependencyCheck> {
+  if (!config.redisUrl) return { status: "unconfigured", latencyMs: null };
+  return probe(async () => {
+    const redis = await getRedis();
+    if (!redis) throw new Error("Redis is not connected");
+    await redis.ping();
+  });
+}
+
+async function runDependencyChecks() {
+  const [postgres, s3, redis] = await Promise.all([checkPostgres(), checkS3(), checkRedis()]);
+  const results = { postgres, s3, redis };
+  for (const component of Object.keys(results) as DependencyName[]) {
+    if (results[component].status === "down") {
+      openIncident(component, `${DEPENDENCY_LABELS[component]} is unreachable`);
+    } else {
+      resolveIncident(component);
+    }
+  }
+
+  dependencyCache = { checkedAt: Date.now(), ...results };
+  return dependencyCache;
+}
+
+async function checkDependencies(maxAgeMs = DEPENDENCY_CHECK_TTL_MS) {
+  if (dependencyCache && Date.now() - dependencyCache.checkedAt < maxAgeMs) {
+    return dependencyCache;
+  }
+  // Concurrent probes from several orchestrator checks share one round of dependency calls.
+  inFlightCheck ??= runDependencyChecks().finally(() => {
+    inFlightCheck = null;
+  });
+  return inFlightCheck;
+}
+
+// Liveness only says the process can serve requests; dependency outages are readiness's job so
+// an orchestrator doesn't restart healthy pods while the database is down.
+app.get("/healthz", (c) => {
+  return c.json({ status: "ok" });
+});
+
+app.get("/readyz", async (c) => {
+  const dependencies = await checkDependencies(READINESS_CHECK_TTL_MS);
+  const failing = (["postgres", "s3", "redis"] as const).filter((name) => dependencies[name].status === "down");
+
+  return c.json(
+    {
+      status: failing.length > 0 ? "unavailable" : "ok",
+      failing,
+      checkedAt: new Date(dependencies.checkedAt).toISOString(),
+    },
+    failing.length > 0 ? 503 : 200
+  );
+});
+
+app.get("/health/details", async (c) => {
+  const dependencies = await checkDependencies(READINESS_CHECK_TTL_MS);
+  const memory = process.memoryUsage();
+
+  return c.json({
+    status: (["postgres", "s3", "redis"] as const).some((name) => dependencies[name].status === "down") ? "degraded" : "ok",
+    version: "1.0.0",
+    uptimeSeconds: Math.round(process.uptime()),
+    memory: {
+      rssBytes: memory.rss,
+      heapUsedBytes: memory.heapUsed,
+    },
+    dependencies: {
+      postgres: dependencies.postgres,
+      s3: dependencies.s3,
+      redis: dependencies.redis,
+    },
+    checkedAt: new Date(dependencies.checkedAt).toISOString(),
+  });
+});
+
+app.get("/api/status", async (c) => {
+  const dependencies = await checkDependencies();
+  const incidents = getIncidents();
+  const activeIncidents = incidents.filter((incident) => incident.resolvedAt === null);
+
+  return c.json({
+    status: activeIncidents.length > 0 ? "degraded" : "ok",
+    errorRates: {
+      last5m: getErrorRate(5),
+      last1h: getErrorRate(60),
+    },
+    queues: {
+      backgroundTasks: getPendingTasks(),
+      jobs: getJobStates(),
+    },
+    dependencies: {
+      postgres: dependencies.postgres.status,
+      s3: dependencies.s3.status,
+      redis: dependencies.redis.status,
+      checkedAt: new Date(dependencies.checkedAt).toISOString(),
+    },
+    incidents: incidents.map((incident) => ({
+      ...incident,
+      startedAt: new Date(incident.startedAt).toISOString(),
+      resolvedAt: incident.resolvedAt ? new Date(incident.resolvedAt).toISOString() : null,
+    })),
+  });
+});
+
 app.get("/api/debug/repo/:owner/:name", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -31,10 +199,10 @@ app.get("/api/debug/repo/:owner/:name", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
-  const prefix = `repos/${row.userId}/${row.name}/`;
+  const prefix = storageLayout.repoContents(storageLayout.repoPrefix(row.userId, row.name));
   const keys = await listObjects(prefix);
 
   const grouped: Record<string, string[]> = {};
@@ -56,11 +224,11 @@ app.get("/api/debug/repo/:owner/:name", async (c) => {
 
 app.get("/api/avatar/:filename", async (c) => {
   const filename = c.req.param("filename");
-  const key = `avatars/${filename}`;
+  const key = storageLayout.avatarKeyForFilename(filename);
 
   const data = await getObject(key);
   if (!data) {
-    return c.json({ error: "Avatar not found" }, 404);
+    return apiError(c, 404, "Avatar not found");
   }
 
   const ext = filename.split(".").pop()?.toLowerCase() || "png";
@@ -3,13 +3,21 @@ import pullRequests from './pull-requests';
 import repositories from './repositories';
 import gitProtocol from './git-protocol';
 import discussions from './discussions';
+import federation from './federation';
+import moderation from './moderation';
+import dashboard from './dashboard';
 import wellKnown from './well-known';
 import settings from './settings';
 import projects from './projects';
+import graphql from './graphql';
+import uploads from './uploads';
 import search from './search';
 import issues from './issues';
 import health from './health';
+import badges from './badges';
 import users from './users';
+import feeds from './feeds';
+import admin from './admin';
 import oauth from './oauth';
 import type { Hono } from 'hono';
 import file from './file';
@@ -20,17 +28,25 @@ export function mountRoutes(app: Hono) {
   app.route('/', health);
   app.route('/', auth);
   app.route('/', users);
+  app.route('/', dashboard);
   app.route('/', repositories);
   app.route('/', git);
   app.route('/', gitProtocol);
   app.route('/', file);
+  app.route('/', badges);
+  app.route('/', uploads);
   app.route('/', issues);
   app.route('/', pullRequests);
   app.route('/', settings);
   app.route('/', search);
+  app.route('/', graphql);
   app.route('/', notifications);
   app.route('/', discussions);
   app.route('/', projects);
+  app.route('/', moderation);
   app.route('/', oauth);
   app.route('/', wellKnown);
+  app.route('/', federation);
+  app.route('/', feeds);
+  app.route('/', admin);
 }
@@ -1,4 +1,4 @@
-import { Hono } from "hono";
+import { Hono, type Context } from "hono";
 import {
   db,
   users,
@@ -8,16 +8,43 @@ import {
   issueLabels,
   issueAssignees,
   issueComments,
-  issueReactions,
+  issueCounters,
+  issueReferences,
+  issueMentions,
+  issueDuplicates,
+  pullRequests,
 } from "@gitbruv/db";
-import { eq, sql, and, desc } from "drizzle-orm";
+import { eq, sql, and, desc, inArray } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import { createGitStore, getCommitByOid } from "../git";
+import { updateFor, findInvalidField } from "../update-builder";
+import { loadIssueForms, loadIssueTemplates, validateIssueFormValues, renderIssueFormBody, type IssueFormValues } from "../issue-forms";
+import { recordIssueReferences, recordIssueMentions } from "../issue-references";
+import { getIssueAccess, canEditIssueComment, canDeleteIssueComment, type IssueAccess } from "../issue-acl";
+import { buildIssueExport, issuesToCsv, parseIssueImport, importIssues } from "../issue-transfer";
+import {
+  VALID_EMOJIS,
+  getReactionsGrouped,
+  getReactionsGroupedBatch,
+  toggleReaction,
+  listReactionUsers,
+  deleteReactions,
+  parseReactionListQuery,
+} from "../reactions";
+import { encodeCursor, decodeCursor, afterCursor } from "../pagination";
+import { apiError } from "../errors";
+import { recordActivity } from "../activity";
+import { applyDefaultLabels, MAX_BULK_LABEL_ISSUES } from "../labels";
+import { moveClosedIssueCards } from "../project-automation";
+import { HIDDEN_REASONS, resolveReportsFor, type HiddenReason } from "../moderation";
+
+const ISSUE_STATE_REASONS = ["completed", "not_planned", "duplicate"] as const;
+type IssueStateReason = (typeof ISSUE_STATE_REASONS)[number];
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
-const VALID_EMOJIS = ["+1", "-1", "laugh", "hooray", "confused", "heart", "rocket", "eyes"];
 
 async function getRepoAndCheckAccess(owner: string, name: string, userId?: string) {
   const result = await db
@@ -40,7 +67,19 @@ async function getRepoAndCheckAccess(owner: string, name: string, userId?: strin
     return null;
   }
 
-  return { repoId: row.id, ownerId: row.ownerId };
+  return { repoId: row.id, ownerId: row.ownerId, visibility: row.visibility };
+}
+
+async function getIssueFormsForRepo(repoId: string) {
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, repoId),
+  });
+  if (!repo) {
+    return [];
+  }
+
+  const store = createGitStore(repo.ownerId, repo.name);
+  return loadIssueForms(store, repo.defaultBranch);
 }
 
 async function getIssueLabels(issueId: string) {
@@ -70,61 +109,138 @@ async function getIssueAssignees(issueId: string) {
     .where(eq(issueAssignees.issueId, issueId));
 }
 
-async function getIssueReactionsGrouped(issueId: string, userId?: string) {
-  const counts = await db
+const MAX_ISSUE_ASSIGNEES = 10;
+
+// Returns the ids that can't be assigned: unknown users, and anyone but the owner on a private
+// repository, since nobody else can see its issues.
+async function findInvalidAssignees(repo: { ownerId: string; visibility: "public" | "private" }, userIds: string[]) {
+  if (userIds.length === 0) return [];
+  if (repo.visibility === "private") {
+    return userIds.filter((userId) => userId !== repo.ownerId);
+  }
+
+  const found = await db.select({ id: users.id }).from(users).where(inArray(users.id, userIds));
+  const existing = new Set(found.map((row) => row.id));
+  return userIds.filter((userId) => !existing.has(userId));
+}
+
+async function addIssueAssignees(
+  c: Context<{ Variables: AuthVariables }>,
+  access: IssueAccess,
+  userIds: string[]
+) {
+  const requested = [...new Set(userIds)];
+  const invalid = await findInvalidAssignees(access.repo, requested);
+  if (invalid.length > 0) {
+    return apiError(c, 400, "Some assignees don't exist or can't access this repository", { assignees: invalid });
+  }
+
+  const current = await getIssueAssignees(access.issue.id);
+  const total = new Set([...current.map((assignee) => assignee.id), ...requested]).size;
+  if (total > MAX_ISSUE_ASSIGNEES) {
+    return apiError(c, 400, `Issues can have at most ${MAX_ISSUE_ASSIGNEES} assignees`);
+  }
+
+  if (requested.length > 0) {
+    await db
+      .insert(issueAssignees)
+      .values(requested.map((userId) => ({ issueId: access.issue.id, userId })))
+      .onConflictDoNothing();
+  }
+
+  return c.json({ assignees: await getIssueAssignees(access.issue.id) });
+}
+
+async function getCommentCount(issueId: string): Promise<number> {
+  const [result] = await db.select({ count: sql<number>`COUNT(*)` }).from(issueComments).where(eq(issueComments.issueId, issueId));
+  return result?.count || 0;
+}
+
+async function getUsersByIds(userIds: string[]) {
+  if (userIds.length === 0) return new Map<string, { id: string; username: string; name: string; avatarUrl: string | null }>();
+
+  const rows = await db
     .select({
-      emoji: issueReactions.emoji,
-      count: sql<number>`COUNT(*)`,
+      id: users.id,
+      username: users.username,
+      name: users.name,
+      avatarUrl: users.avatarUrl,
     })
-    .from(issueReactions)
-    .where(eq(issueReactions.issueId, issueId))
-    .groupBy(issueReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: issueReactions.emoji })
-      .from(issueReactions)
-      .where(and(eq(issueReactions.issueId, issueId), eq(issueReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
+    .from(users)
+    .where(inArray(users.id, userIds));
+
+  return new Map(rows.map((u) => [u.id, u]));
+}
+
+function groupBy<T, V>(rows: T[], key: (row: T) => string, value: (row: T) => V) {
+  const map = new Map<string, V[]>();
+  for (const row of rows) {
+    const k = key(row);
+    const list = map.get(k) || [];
+    list.push(value(row));
+    map.set(k, list);
+  }
+  return map;
 }
 
-async function getCommentReactionsGrouped(commentId: string, userId?: string) {
-  const counts = await db
+async function getIssueLabelsBatch(issueIds: string[]) {
+  if (issueIds.length === 0) return new Map();
+
+  const rows = await db
     .select({
-      emoji: issueReactions.emoji,
-      count: sql<number>`COUNT(*)`,
+      issueId: issueLabels.issueId,
+      id: labels.id,
+      name: labels.name,
+      description: labels.description,
+      color: labels.color,
     })
-    .from(issueReactions)
-    .where(eq(issueReactions.commentId, commentId))
-    .groupBy(issueReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: issueReactions.emoji })
-      .from(issueReactions)
-      .where(and(eq(issueReactions.commentId, commentId), eq(issueReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
+    .from(labels)
+    .innerJoin(issueLabels, eq(issueLabels.labelId, labels.id))
+    .where(inArray(issueLabels.issueId, issueIds))
+    .orderBy(labels.name);
+
+  return groupBy(
+    rows,
+    (r) => r.issueId,
+    (r) => ({ id: r.id, name: r.name, description: r.description, color: r.color })
+  );
 }
 
-async function getCommentCount(issueId: string): Promise<number> {
-  const [result] = await db.select({ count: sql<number>`COUNT(*)` }).from(issueComments).where(eq(issueComments.issueId, issueId));
-  return result?.count || 0;
+async function getIssueAssigneesBatch(issueIds: string[]) {
+  if (issueIds.length === 0) return new Map();
+
+  const rows = await db
+    .select({
+      issueId: issueAssignees.issueId,
+      id: users.id,
+      username: users.username,
+      name: users.name,
+      avatarUrl: users.avatarUrl,
+    })
+    .from(users)
+    .innerJoin(issueAssignees, eq(issueAssignees.userId, users.id))
+    .where(inArray(issueAssignees.issueId, issueIds));
+
+  return groupBy(
+    rows,
+    (r) => r.issueId,
+    (r) => ({ id: r.id, username: r.username, name: r.name, avatarUrl: r.avatarUrl })
+  );
+}
+
+async function getCommentCountsBatch(issueIds: string[]) {
+  if (issueIds.length === 0) return new Map<string, number>();
+
+  const rows = await db
+    .select({
+      issueId: issueComments.issueId,
+      count: sql<number>`COUNT(*)`,
+    })
+    .from(issueComments)
+    .where(inArray(issueComments.issueId, issueIds))
+    .groupBy(issueComments.issueId);
+
+  return new Map(rows.map((r) => [r.issueId, Number(r.count)]));
 }
 
 app.get("/api/repositories/:owner/:name/issues", async (c) => {
@@ -135,10 +251,30 @@ app.get("/api/repositories/:owner/:name/issues", async (c) => {
   const state: "open" | "closed" = stateParam === "closed" ? "closed" : "open";
   const limit = parseInt(c.req.query("limit") || "30", 10);
   const offset = parseInt(c.req.query("offset") || "0", 10);
+  const cursorParam = c.req.query("cursor");
+
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const conditions = [eq(issues.repositoryId, repoAccess.repoId), eq(issues.state, state)];
+  if (cursor) {
+    conditions.push(
+      afterCursor(
+        [
+          { column: issues.createdAt, type: "timestamp" },
+          { column: issues.id, type: "uuid" },
+        ],
+        cursor,
+        "desc"
+      )
+    );
   }
 
   const query = db
@@ -148,64 +284,62 @@ app.get("/api/repositories/:owner/:name/issues", async (c) => {
       title: issues.title,
       body: issues.body,
       state: issues.state,
+      stateReason: issues.stateReason,
       locked: issues.locked,
       createdAt: issues.createdAt,
       updatedAt: issues.updatedAt,
       closedAt: issues.closedAt,
       authorId: issues.authorId,
       closedById: issues.closedById,
+      sortKey: sql<string>`${issues.createdAt}::text`,
     })
     .from(issues)
-    .where(and(eq(issues.repositoryId, repoAccess.repoId), eq(issues.state, state)))
-    .orderBy(desc(issues.createdAt))
+    .where(and(...conditions))
+    .orderBy(desc(issues.createdAt), desc(issues.id))
     .limit(limit + 1)
-    .offset(offset);
+    .offset(cursor ? 0 : offset);
 
   const rows = await query;
 
   const hasMore = rows.length > limit;
   const issueRows = rows.slice(0, limit);
 
-  const issueList = await Promise.all(
-    issueRows.map(async (row) => {
-      const author = await db.query.users.findFirst({
-        where: eq(users.id, row.authorId),
-        columns: { id: true, username: true, name: true, avatarUrl: true },
-      });
-
-      const closedBy = row.closedById
-        ? await db.query.users.findFirst({
-            where: eq(users.id, row.closedById),
-            columns: { id: true, username: true, name: true, avatarUrl: true },
-          })
-        : null;
-
-      const issueLabelsData = await getIssueLabels(row.id);
-      const assignees = await getIssueAssignees(row.id);
-      const reactions = await getIssueReactionsGrouped(row.id, currentUser?.id);
-      const commentCount = await getCommentCount(row.id);
+  const issueIds = issueRows.map((row) => row.id);
+  const userIds = [
+    ...new Set(issueRows.flatMap((row) => (row.closedById ? [row.authorId, row.closedById] : [row.authorId]))),
+  ];
+
+  const [userMap, labelMap, assigneeMap, reactionMap, commentCountMap] = await Promise.all([
+    getUsersByIds(userIds),
+    getIssueLabelsBatch(issueIds),
+    getIssueAssigneesBatch(issueIds),
+    getReactionsGroupedBatch("issue", issueIds, currentUser?.id),
+    getCommentCountsBatch(issueIds),
+  ]);
+
+  const issueList = issueRows.map((row) => ({
+    id: row.id,
+    number: row.number,
+    title: row.title,
+    body: row.body,
+    state: row.state,
+    stateReason: row.stateReason,
+    locked: row.locked,
+    author: userMap.get(row.authorId) || { id: row.authorId, username: "unknown", name: "Unknown", avatarUrl: null },
+    labels: labelMap.get(row.id) || [],
+    assignees: assigneeMap.get(row.id) || [],
+    reactions: reactionMap.get(row.id) || [],
+    commentCount: commentCountMap.get(row.id) || 0,
+    createdAt: row.createdAt,
+    updatedAt: row.updatedAt,
+    closedAt: row.closedAt,
+    closedBy: row.closedById ? userMap.get(row.closedById) || null : null,
+  }));
 
-      return {
-        id: row.id,
-        number: row.number,
-        title: row.title,
-        body: row.body,
-        state: row.state,
-        locked: row.locked,
-        author: author || { id: row.authorId, username: "unknown", name: "Unknown", avatarUrl: null },
-        labels: issueLabelsData,
-        assignees,
-        reactions,
-        commentCount,
-        createdAt: row.createdAt,
-        updatedAt: row.updatedAt,
-        closedAt: row.closedAt,
-        closedBy,
-      };
-    })
-  );
+  const last = issueRows[issueRows.length - 1];
+  const nextCursor = hasMore && last ? encodeCursor([last.sortKey, last.id]) : null;
 
-  return c.json({ issues: issueList, hasMore });
+  return c.json({ issues: issueList, hasMore, nextCursor });
 });
 
 app.post("/api/repositories/:owner/:name/issues", requireAuth, async (c) => {
@@ -217,45 +351,117 @@ app.post("/api/repositories/:owner/:name/issues", requireAuth, async (c) => {
     body?: string;
     labels?: string[];
     assignees?: string[];
+    form?: string;
+    fields?: IssueFormValues;
   }>();
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (!body.title?.trim()) {
-    return c.json({ error: "Title cannot be empty" }, 400);
+    return apiError(c, 400, "Title cannot be empty");
   }
 
-  const [maxNumber] = await db
-    .select({ max: sql<number>`COALESCE(MAX(number), 0)` })
-    .from(issues)
-    .where(eq(issues.repositoryId, repoAccess.repoId));
+  if (body.form) {
+    const forms = await getIssueFormsForRepo(repoAccess.repoId);
+    const form = forms.find((f) => f.id === body.form);
+    if (!form) {
+      return apiError(c, 404, "Issue form not found");
+    }
 
-  const [inserted] = await db
-    .insert(issues)
-    .values({
-      repositoryId: repoAccess.repoId,
-      authorId: user.id,
-      title: body.title,
-      body: body.body,
-      number: (maxNumber?.max || 0) + 1,
-    })
-    .returning();
+    const fields = body.fields || {};
+    const errors = validateIssueFormValues(form, fields);
+    if (errors.length > 0) {
+      return apiError(c, 400, "Invalid form submission", errors);
+    }
+
+    body.body = renderIssueFormBody(form, fields);
+
+    if (form.labels.length > 0) {
+      const formLabels = await db
+        .select({ id: labels.id })
+        .from(labels)
+        .where(and(eq(labels.repositoryId, repoAccess.repoId), inArray(labels.name, form.labels)));
+      body.labels = [...new Set([...(body.labels || []), ...formLabels.map((l) => l.id)])];
+    }
 
-  if (body.labels?.length) {
-    for (const labelId of body.labels) {
-      await db.insert(issueLabels).values({ issueId: inserted.id, labelId }).onConflictDoNothing();
+    if (form.assignees.length > 0) {
+      const formAssignees = await db
+        .select({ id: users.id })
+        .from(users)
+        .where(inArray(users.username, form.assignees));
+      body.assignees = [...new Set([...(body.assignees || []), ...formAssignees.map((u) => u.id)])];
     }
   }
 
   if (body.assignees?.length) {
-    for (const assigneeId of body.assignees) {
-      await db.insert(issueAssignees).values({ issueId: inserted.id, userId: assigneeId }).onConflictDoNothing();
+    body.assignees = [...new Set(body.assignees)];
+    if (body.assignees.length > MAX_ISSUE_ASSIGNEES) {
+      return apiError(c, 400, `Issues can have at most ${MAX_ISSUE_ASSIGNEES} assignees`);
+    }
+    const invalid = await findInvalidAssignees(repoAccess, body.assignees);
+    if (invalid.length > 0) {
+      return apiError(c, 400, "Some assignees don't exist or can't access this repository", { assignees: invalid });
     }
   }
 
+  const inserted = await db.transaction(async (tx) => {
+    const [counter] = await tx
+      .insert(issueCounters)
+      .values({
+        repositoryId: repoAccess.repoId,
+        lastNumber: sql`(SELECT COALESCE(MAX(${issues.number}), 0) + 1 FROM ${issues} WHERE ${issues.repositoryId} = ${repoAccess.repoId})`,
+      })
+      .onConflictDoUpdate({
+        target: issueCounters.repositoryId,
+        set: { lastNumber: sql`${issueCounters.lastNumber} + 1` },
+      })
+      .returning({ number: issueCounters.lastNumber });
+
+    const [issue] = await tx
+      .insert(issues)
+      .values({
+        repositoryId: repoAccess.repoId,
+        authorId: user.id,
+        title: body.title,
+        body: body.body,
+        number: counter.number,
+      })
+      .returning();
+
+    if (body.labels?.length) {
+      await tx
+        .insert(issueLabels)
+        .values(body.labels.map((labelId) => ({ issueId: issue.id, labelId })))
+        .onConflictDoNothing();
+    }
+
+    if (body.assignees?.length) {
+      await tx
+        .insert(issueAssignees)
+        .values(body.assignees.map((assigneeId) => ({ issueId: issue.id, userId: assigneeId })))
+        .onConflictDoNothing();
+    }
+
+    return issue;
+  });
+
+  await recordIssueReferences(repoAccess.repoId, { type: "issue", issueId: inserted.id }, `${inserted.title}\n${inserted.body || ""}`, user.id);
+  await recordIssueMentions(
+    { issueId: inserted.id, issueNumber: inserted.number, issueTitle: inserted.title, repositoryId: repoAccess.repoId },
+    inserted.body,
+    user.id
+  );
+
+  recordActivity({
+    type: "issue_opened",
+    actorId: user.id,
+    repositoryId: repoAccess.repoId,
+    payload: { issueId: inserted.id, number: inserted.number, title: inserted.title },
+  });
+
   const issueLabelsData = await getIssueLabels(inserted.id);
   const assignees = await getIssueAssignees(inserted.id);
 
@@ -278,6 +484,91 @@ app.post("/api/repositories/:owner/:name/issues", requireAuth, async (c) => {
   });
 });
 
+app.get("/api/repositories/:owner/:name/issue-forms", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+
+  const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
+  if (!repoAccess) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const forms = await getIssueFormsForRepo(repoAccess.repoId);
+  return c.json({ forms });
+});
+
+app.get("/api/repositories/:owner/:name/issue-templates", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+
+  const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
+  if (!repoAccess) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, repoAccess.repoId),
+  });
+  if (!repo) {
+    return c.json({ templates: [] });
+  }
+
+  const templates = await loadIssueTemplates(createGitStore(repo.ownerId, repo.name), repo.defaultBranch);
+  return c.json({ templates });
+});
+
+app.get("/api/repositories/:owner/:name/issues/export", async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const currentUser = c.get("user");
+  const format = c.req.query("format") || "json";
+
+  if (format !== "json" && format !== "csv") {
+    return apiError(c, 400, "Format must be 'json' or 'csv'");
+  }
+
+  const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
+  if (!repoAccess) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const records = await buildIssueExport(repoAccess.repoId, { includeHidden: currentUser?.id === repoAccess.ownerId });
+  const filename = `${owner}-${name}-issues.${format}`;
+  c.header("Content-Disposition", `attachment; filename="${filename}"`);
+
+  if (format === "csv") {
+    c.header("Content-Type", "text/csv; charset=utf-8");
+    return c.body(issuesToCsv(records));
+  }
+  return c.json({ repository: `${owner}/${name}`, exportedAt: new Date().toISOString(), issues: records });
+});
+
+app.post("/api/repositories/:owner/:name/issues/import", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+
+  const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
+  if (!repoAccess) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (repoAccess.ownerId !== user.id) {
+    return apiError(c, 403, "Only the repository owner can import issues");
+  }
+
+  const payload = await c.req.json().catch(() => null);
+  const parsed = parseIssueImport(payload);
+  if ("error" in parsed) {
+    return apiError(c, 400, parsed.error);
+  }
+
+  const result = await importIssues({ id: repoAccess.repoId, ownerId: repoAccess.ownerId, visibility: repoAccess.visibility }, user.id, parsed.issues);
+  return c.json(result);
+});
+
 app.get("/api/repositories/:owner/:name/issues/count", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -285,7 +576,7 @@ app.get("/api/repositories/:owner/:name/issues/count", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const [openCount] = await db
@@ -309,7 +600,7 @@ app.get("/api/repositories/:owner/:name/issues/:number", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const issue = await db.query.issues.findFirst({
@@ -317,7 +608,7 @@ app.get("/api/repositories/:owner/:name/issues/:number", async (c) => {
   });
 
   if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+    return apiError(c, 404, "Issue not found");
   }
 
   const author = await db.query.users.findFirst({
@@ -334,8 +625,9 @@ app.get("/api/repositories/:owner/:name/issues/:number", async (c) => {
 
   const issueLabelsData = await getIssueLabels(issue.id);
   const assignees = await getIssueAssignees(issue.id);
-  const reactions = await getIssueReactionsGrouped(issue.id, currentUser?.id);
+  const reactions = await getReactionsGrouped("issue", issue.id, currentUser?.id);
   const commentCount = await getCommentCount(issue.id);
+  const { referencedBy, references, mentions, duplicateOf, duplicates } = await loadIssueReferences(issue.id, repoAccess.ownerId, name);
 
   return c.json({
     id: issue.id,
@@ -343,6 +635,7 @@ app.get("/api/repositories/:owner/:name/issues/:number", async (c) => {
     title: issue.title,
     body: issue.body,
     state: issue.state,
+    stateReason: issue.stateReason,
     locked: issue.locked,
     author: author || { id: issue.authorId, username: "unknown", name: "Unknown", avatarUrl: null },
     labels: issueLabelsData,
@@ -353,6 +646,11 @@ app.get("/api/repositories/:owner/:name/issues/:number", async (c) => {
     updatedAt: issue.updatedAt,
     closedAt: issue.closedAt,
     closedBy,
+    referencedBy,
+    references,
+    mentions,
+    duplicateOf,
+    duplicates,
   });
 });
 
@@ -363,49 +661,131 @@ app.patch("/api/issues/:id", requireAuth, async (c) => {
     title?: string;
     body?: string;
     state?: string;
+    stateReason?: string;
+    duplicateOf?: number;
     locked?: boolean;
   }>();
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
+  }
 
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  if (!access.can("edit")) {
+    return apiError(c, 403, access.denial("edit"));
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
+  const { issue } = access;
 
-  if (user.id !== issue.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+  const invalidField = findInvalidField(body, {
+    title: "string",
+    body: "nullable-string",
+    state: "string",
+    stateReason: "string",
+    duplicateOf: "number",
+    locked: "boolean",
+  });
+  if (invalidField) {
+    return apiError(c, 400, `Invalid value for ${invalidField}`);
   }
 
   if (body.title !== undefined && !body.title.trim()) {
-    return c.json({ error: "Title cannot be empty" }, 400);
+    return apiError(c, 400, "Title cannot be empty");
   }
 
   if (body.state && body.state !== "open" && body.state !== "closed") {
-    return c.json({ error: "Invalid state" }, 400);
+    return apiError(c, 400, "Invalid state");
+  }
+
+  if (body.locked !== undefined && body.locked !== issue.locked && !access.can("lock")) {
+    return apiError(c, 403, access.denial("lock"));
+  }
+
+  const nextState = (body.state as "open" | "closed" | undefined) ?? issue.state;
+  const closing = nextState === "closed" && issue.state === "open";
+  const reopening = nextState === "open" && issue.state === "closed";
+
+  let stateReason = body.stateReason as IssueStateReason | undefined;
+  if (stateReason !== undefined && !ISSUE_STATE_REASONS.includes(stateReason)) {
+    return apiError(c, 400, "Invalid state reason");
+  }
+  if (body.duplicateOf !== undefined) {
+    stateReason ??= "duplicate";
+    if (stateReason !== "duplicate") {
+      return apiError(c, 400, "duplicateOf can only be set when closing as a duplicate");
+    }
+  }
+  if (stateReason !== undefined && nextState !== "closed") {
+    return apiError(c, 400, "A state reason can only be set on a closed issue");
+  }
+  if (closing) {
+    stateReason ??= "completed";
+  }
+
+  let duplicateOf: { id: string; number: number } | undefined;
+  if (body.duplicateOf !== undefined) {
+    if (body.duplicateOf === issue.number) {
+      return apiError(c, 400, "An issue cannot be a duplicate of itself");
+    }
+    duplicateOf = await db.query.issues.findFirst({
+      where: and(eq(issues.repositoryId, issue.repositoryId), eq(issues.number, body.duplicateOf)),
+      columns: { id: true, number: true },
+    });
+    if (!duplicateOf) {
+      return apiError(c, 400, `Issue #${body.duplicateOf} does not exist in this repository`);
+    }
+  } else if (stateReason === "duplicate" && issue.stateReason !== "duplicate") {
+    return apiError(c, 400, "duplicateOf is required when closing as a duplicate");
   }
 
-  const updates: Record<string, any> = { updatedAt: new Date() };
-  if (body.title !== undefined) updates.title = body.title;
-  if (body.body !== undefined) updates.body = body.body;
+  const updates = updateFor(issues)
+    .set("updatedAt", new Date())
+    .set("title", body.title)
+    .set("body", body.body)
+    .set("locked", body.locked)
+    .set("stateReason", stateReason);
+
   if (body.state !== undefined) {
-    updates.state = body.state;
-    if (body.state === "closed" && issue.state === "open") {
-      updates.closedAt = new Date();
-      updates.closedById = user.id;
-    } else if (body.state === "open" && issue.state === "closed") {
-      updates.closedAt = null;
-      updates.closedById = null;
+    updates.set("state", nextState);
+    if (closing) {
+      updates.set("closedAt", new Date()).set("closedById", user.id);
+    } else if (reopening) {
+      updates.set("closedAt", null).set("closedById", null).set("stateReason", null);
     }
   }
-  if (body.locked !== undefined) updates.locked = body.locked;
 
-  await db.update(issues).set(updates).where(eq(issues.id, id));
+  await db.update(issues).set(updates.build()).where(eq(issues.id, id));
+
+  if (duplicateOf) {
+    await db
+      .insert(issueDuplicates)
+      .values({ issueId: id, duplicateOfId: duplicateOf.id, actorId: user.id })
+      .onConflictDoUpdate({
+        target: issueDuplicates.issueId,
+        set: { duplicateOfId: duplicateOf.id, actorId: user.id, createdAt: new Date() },
+      });
+  } else if (reopening || (stateReason !== undefined && stateReason !== "duplicate")) {
+    await db.delete(issueDuplicates).where(eq(issueDuplicates.issueId, id));
+  }
+
+  if (closing) {
+    moveClosedIssueCards(id);
+    recordActivity({
+      type: "issue_closed",
+      actorId: user.id,
+      repositoryId: issue.repositoryId,
+      payload: { issueId: id, number: issue.number, title: body.title ?? issue.title },
+    });
+  }
+
+  if (body.title !== undefined || body.body !== undefined) {
+    await recordIssueReferences(issue.repositoryId, { type: "issue", issueId: id }, `${body.title ?? ""}\n${body.body ?? ""}`, user.id);
+    await recordIssueMentions(
+      { issueId: id, issueNumber: issue.number, issueTitle: body.title ?? issue.title, repositoryId: issue.repositoryId },
+      body.body,
+      user.id
+    );
+  }
 
   return c.json({ success: true });
 });
@@ -414,27 +794,149 @@ app.delete("/api/issues/:id", requireAuth, async (c) => {
   const id = c.req.param("id");
   const user = c.get("user")!;
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
-
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
-
-  if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete issues" }, 403);
+  if (!access.can("delete")) {
+    return apiError(c, 403, access.denial("delete"));
   }
 
+  const comments = await db.select({ id: issueComments.id }).from(issueComments).where(eq(issueComments.issueId, id));
   await db.delete(issues).where(eq(issues.id, id));
+  await deleteReactions("issue", [id]);
+  await deleteReactions("issue_comment", comments.map((comment) => comment.id));
 
   return c.json({ success: true });
 });
 
+async function loadIssueReferences(issueId: string, ownerId: string, repoName: string) {
+  const [issueSources, pullRequestSources, commitSources, targets, mentions, duplicateOfRows, duplicates] = await Promise.all([
+    db
+      .select({
+        id: issues.id,
+        number: issues.number,
+        title: issues.title,
+        state: issues.state,
+        createdAt: issueReferences.createdAt,
+      })
+      .from(issueReferences)
+      .innerJoin(issues, eq(issues.id, issueReferences.sourceIssueId))
+      .where(and(eq(issueReferences.targetIssueId, issueId), eq(issueReferences.sourceType, "issue"))),
+    db
+      .select({
+        id: pullRequests.id,
+        number: pullRequests.number,
+        title: pullRequests.title,
+        state: pullRequests.state,
+        createdAt: issueReferences.createdAt,
+      })
+      .from(issueReferences)
+      .innerJoin(pullRequests, eq(pullRequests.id, issueReferences.sourcePullRequestId))
+      .where(and(eq(issueReferences.targetIssueId, issueId), eq(issueReferences.sourceType, "pull_request"))),
+    db
+      .select({
+        oid: issueReferences.sourceCommitOid,
+        createdAt: issueReferences.createdAt,
+      })
+      .from(issueReferences)
+      .where(and(eq(issueReferences.targetIssueId, issueId), eq(issueReferences.sourceType, "commit"))),
+    db
+      .select({
+        id: issues.id,
+        number: issues.number,
+        title: issues.title,
+        state: issues.state,
+        createdAt: issueReferences.createdAt,
+      })
+      .from(issueReferences)
+      .innerJoin(issues, eq(issues.id, issueReferences.targetIssueId))
+      .where(eq(issueReferences.sourceIssueId, issueId)),
+    db
+      .select({ id: users.id, username: users.username, name: users.name, avatarUrl: users.avatarUrl })
+      .from(issueMentions)
+      .innerJoin(users, eq(users.id, issueMentions.userId))
+      .where(eq(issueMentions.issueId, issueId))
+      .orderBy(issueMentions.createdAt),
+    db
+      .select({
+        id: issues.id,
+        number: issues.number,
+        title: issues.title,
+        state: issues.state,
+        createdAt: issueDuplicates.createdAt,
+      })
+      .from(issueDuplicates)
+      .innerJoin(issues, eq(issues.id, issueDuplicates.duplicateOfId))
+      .where(eq(issueDuplicates.issueId, issueId))
+      .limit(1),
+    db
+      .select({
+        id: issues.id,
+        number: issues.number,
+        title: issues.title,
+        state: issues.state,
+        createdAt: issueDuplicates.createdAt,
+      })
+      .from(issueDuplicates)
+      .innerJoin(issues, eq(issues.id, issueDuplicates.issueId))
+      .where(eq(issueDuplicates.duplicateOfId, issueId))
+      .orderBy(issueDuplicates.createdAt),
+  ]);
+
+  const store = createGitStore(ownerId, repoName);
+  const commits = await Promise.all(
+    commitSources.map(async (row) => {
+      const commit = row.oid ? await getCommitByOid(store.fs, store.dir, row.oid) : null;
+      return {
+        type: "commit" as const,
+        oid: row.oid,
+        message: commit?.commit.message.split("\n")[0] ?? null,
+        createdAt: row.createdAt,
+      };
+    })
+  );
+
+  const referencedBy = [
+    ...issueSources.map((row) => ({ type: "issue" as const, ...row })),
+    ...pullRequestSources.map((row) => ({ type: "pull_request" as const, ...row })),
+    ...commits,
+  ].sort((a, b) => new Date(a.createdAt).getTime() - new Date(b.createdAt).getTime());
+
+  return {
+    referencedBy,
+    references: targets.map((row) => ({ type: "issue" as const, ...row })),
+    mentions,
+    duplicateOf: duplicateOfRows[0] ?? null,
+    duplicates,
+  };
+}
+
+app.get("/api/issues/:id/references", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const [issue] = await db
+    .select({
+      id: issues.id,
+      repositoryId: issues.repositoryId,
+      repoName: repositories.name,
+      ownerId: repositories.ownerId,
+      visibility: repositories.visibility,
+    })
+    .from(issues)
+    .innerJoin(repositories, eq(repositories.id, issues.repositoryId))
+    .where(eq(issues.id, id))
+    .limit(1);
+
+  if (!issue || (issue.visibility === "private" && currentUser?.id !== issue.ownerId)) {
+    return apiError(c, 404, "Issue not found");
+  }
+
+  return c.json(await loadIssueReferences(id, issue.ownerId, issue.repoName));
+});
+
 app.get("/api/repositories/:owner/:name/labels", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -442,7 +944,7 @@ app.get("/api/repositories/:owner/:name/labels", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const labelsData = await db
@@ -451,12 +953,118 @@ app.get("/api/repositories/:owner/:name/labels", async (c) => {
       name: labels.name,
       description: labels.description,
       color: labels.color,
+      openIssues: sql<number>`COUNT(${issues.id}) FILTER (WHERE ${issues.state} = 'open')`,
+      totalIssues: sql<number>`COUNT(${issues.id})`,
     })
     .from(labels)
+    .leftJoin(issueLabels, eq(issueLabels.labelId, labels.id))
+    .leftJoin(issues, eq(issues.id, issueLabels.issueId))
     .where(eq(labels.repositoryId, repoAccess.repoId))
+    .groupBy(labels.id)
     .orderBy(labels.name);
 
-  return c.json({ labels: labelsData });
+  return c.json({
+    labels: labelsData.map((label) => ({ ...label, openIssues: Number(label.openIssues), totalIssues: Number(label.totalIssues) })),
+  });
+});
+
+app.post("/api/repositories/:owner/:name/labels/defaults", requireAuth, async (c) => {
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const user = c.get("user")!;
+
+  const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
+  if (!repoAccess) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (user.id !== repoAccess.ownerId) {
+    return apiError(c, 403, "Only repo owner can create labels");
+  }
+
+  const created = await applyDefaultLabels(repoAccess.repoId);
+
+  return c.json({ labels: created }, created.length > 0 ? 201 : 200);
+});
+
+async function getBulkLabelTarget(c: Context<{ Variables: AuthVariables }>) {
+  const user = c.get("user")!;
+  const body = await c.req.json<{ issues?: unknown }>().catch(() => ({}) as { issues?: unknown });
+
+  if (
+    !Array.isArray(body.issues) ||
+    body.issues.length === 0 ||
+    !body.issues.every((number) => Number.isInteger(number) && (number as number) > 0)
+  ) {
+    return { error: apiError(c, 400, "issues must be a non-empty array of issue numbers") };
+  }
+  if (body.issues.length > MAX_BULK_LABEL_ISSUES) {
+    return { error: apiError(c, 400, `At most ${MAX_BULK_LABEL_ISSUES} issues can be updated at once`) };
+  }
+
+  const repoAccess = await getRepoAndCheckAccess(c.req.param("owner"), c.req.param("name"), user.id);
+  if (!repoAccess) {
+    return { error: apiError(c, 404, "Repository not found") };
+  }
+  if (user.id !== repoAccess.ownerId) {
+    return { error: apiError(c, 403, "Only repo owner can bulk edit labels") };
+  }
+
+  const label = await db.query.labels.findFirst({
+    where: and(eq(labels.id, c.req.param("labelId")), eq(labels.repositoryId, repoAccess.repoId)),
+    columns: { id: true },
+  });
+  if (!label) {
+    return { error: apiError(c, 404, "Label not found") };
+  }
+
+  return { repoId: repoAccess.repoId, labelId: label.id, numbers: [...new Set(body.issues as number[])] };
+}
+
+app.post("/api/repositories/:owner/:name/labels/:labelId/issues", requireAuth, async (c) => {
+  const target = await getBulkLabelTarget(c);
+  if ("error" in target) {
+    return target.error;
+  }
+
+  // One INSERT ... SELECT so the numbers are resolved and labelled without a round trip per issue.
+  const added = await db
+    .insert(issueLabels)
+    .select(
+      db
+        .select({ issueId: issues.id, labelId: sql<string>`${target.labelId}::uuid`.as("label_id") })
+        .from(issues)
+        .where(and(eq(issues.repositoryId, target.repoId), inArray(issues.number, target.numbers)))
+    )
+    .onConflictDoNothing()
+    .returning({ issueId: issueLabels.issueId });
+
+  return c.json({ updated: added.length });
+});
+
+app.delete("/api/repositories/:owner/:name/labels/:labelId/issues", requireAuth, async (c) => {
+  const target = await getBulkLabelTarget(c);
+  if ("error" in target) {
+    return target.error;
+  }
+
+  const removed = await db
+    .delete(issueLabels)
+    .where(
+      and(
+        eq(issueLabels.labelId, target.labelId),
+        inArray(
+          issueLabels.issueId,
+          db
+            .select({ id: issues.id })
+            .from(issues)
+            .where(and(eq(issues.repositoryId, target.repoId), inArray(issues.number, target.numbers)))
+        )
+      )
+    )
+    .returning({ issueId: issueLabels.issueId });
+
+  return c.json({ updated: removed.length });
 });
 
 app.post("/api/repositories/:owner/:name/labels", requireAuth, async (c) => {
@@ -471,15 +1079,15 @@ app.post("/api/repositories/:owner/:name/labels", requireAuth, async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (user.id !== repoAccess.ownerId) {
-    return c.json({ error: "Only repo owner can create labels" }, 403);
+    return apiError(c, 403, "Only repo owner can create labels");
   }
 
   if (!body.name?.trim()) {
-    return c.json({ error: "Name cannot be empty" }, 400);
+    return apiError(c, 400, "Name cannot be empty");
   }
 
   const [label] = await db
@@ -509,7 +1117,7 @@ app.patch("/api/labels/:id", requireAuth, async (c) => {
   });
 
   if (!label) {
-    return c.json({ error: "Label not found" }, 404);
+    return apiError(c, 404, "Label not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -517,18 +1125,25 @@ app.patch("/api/labels/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can update labels" }, 403);
+    return apiError(c, 403, "Only repo owner can update labels");
   }
 
-  const [updated] = await db
-    .update(labels)
-    .set({
-      name: body.name ?? label.name,
-      description: body.description ?? label.description,
-      color: body.color ?? label.color,
-    })
-    .where(eq(labels.id, id))
-    .returning();
+  const invalidField = findInvalidField(body, {
+    name: "string",
+    description: "nullable-string",
+    color: "string",
+  });
+  if (invalidField) {
+    return apiError(c, 400, `Invalid value for ${invalidField}`);
+  }
+
+  const updates = updateFor(labels).set("name", body.name).set("description", body.description).set("color", body.color);
+
+  if (updates.isEmpty()) {
+    return c.json(label);
+  }
+
+  const [updated] = await db.update(labels).set(updates.build()).where(eq(labels.id, id)).returning();
 
   return c.json(updated);
 });
@@ -542,7 +1157,7 @@ app.delete("/api/labels/:id", requireAuth, async (c) => {
   });
 
   if (!label) {
-    return c.json({ error: "Label not found" }, 404);
+    return apiError(c, 404, "Label not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -550,7 +1165,7 @@ app.delete("/api/labels/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete labels" }, 403);
+    return apiError(c, 403, "Only repo owner can delete labels");
   }
 
   await db.delete(labels).where(eq(labels.id, id));
@@ -563,20 +1178,13 @@ app.post("/api/issues/:id/labels", requireAuth, async (c) => {
   const user = c.get("user")!;
   const body = await c.req.json<{ labels: string[] }>();
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
-
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
-
-  if (user.id !== issue.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+  if (!access.can("triage")) {
+    return apiError(c, 403, access.denial("triage"));
   }
 
   for (const labelId of body.labels) {
@@ -591,20 +1199,13 @@ app.delete("/api/issues/:id/labels/:labelId", requireAuth, async (c) => {
   const labelId = c.req.param("labelId");
   const user = c.get("user")!;
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
-
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
-
-  if (user.id !== issue.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+  if (!access.can("triage")) {
+    return apiError(c, 403, access.denial("triage"));
   }
 
   await db.delete(issueLabels).where(and(eq(issueLabels.issueId, id), eq(issueLabels.labelId, labelId)));
@@ -617,27 +1218,36 @@ app.post("/api/issues/:id/assignees", requireAuth, async (c) => {
   const user = c.get("user")!;
   const body = await c.req.json<{ assignees: string[] }>();
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
+  if (!Array.isArray(body.assignees) || body.assignees.some((assignee) => typeof assignee !== "string")) {
+    return apiError(c, 400, "Assignees must be a list of user ids");
+  }
 
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
+  if (!access.can("triage")) {
+    return apiError(c, 403, access.denial("triage"));
+  }
+
+  return addIssueAssignees(c, access, body.assignees);
+});
 
-  if (user.id !== issue.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+app.post("/api/issues/:id/assignees/me", requireAuth, async (c) => {
+  const id = c.req.param("id");
+  const user = c.get("user")!;
+
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  for (const assigneeId of body.assignees) {
-    await db.insert(issueAssignees).values({ issueId: id, userId: assigneeId }).onConflictDoNothing();
+  if (!access.can("triage")) {
+    return apiError(c, 403, access.denial("triage"));
   }
 
-  return c.json({ success: true });
+  return addIssueAssignees(c, access, [user.id]);
 });
 
 app.delete("/api/issues/:id/assignees/:userId", requireAuth, async (c) => {
@@ -645,20 +1255,13 @@ app.delete("/api/issues/:id/assignees/:userId", requireAuth, async (c) => {
   const userId = c.req.param("userId");
   const user = c.get("user")!;
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
-
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const repo = await db.query.repositories.findFirst({
-    where: eq(repositories.id, issue.repositoryId),
-  });
-
-  if (user.id !== issue.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+  if (!access.can("triage")) {
+    return apiError(c, 403, access.denial("triage"));
   }
 
   await db.delete(issueAssignees).where(and(eq(issueAssignees.issueId, id), eq(issueAssignees.userId, userId)));
@@ -666,43 +1269,110 @@ app.delete("/api/issues/:id/assignees/:userId", requireAuth, async (c) => {
   return c.json({ success: true });
 });
 
+const DEFAULT_COMMENT_PAGE_SIZE = 50;
+const MAX_COMMENT_PAGE_SIZE = 100;
+
+// Pages walk the thread in the requested order: `after` continues past the last comment of a
+// page and `before` goes back from the first, so a client can load either end of a long thread
+// first and fill in towards the middle.
 app.get("/api/issues/:id/comments", async (c) => {
   const id = c.req.param("id");
   const currentUser = c.get("user");
+  const order = c.req.query("order") || "oldest";
+  const limit = Math.min(Math.max(parseInt(c.req.query("limit") || String(DEFAULT_COMMENT_PAGE_SIZE), 10) || DEFAULT_COMMENT_PAGE_SIZE, 1), MAX_COMMENT_PAGE_SIZE);
+  const include = new Set((c.req.query("include") || "").split(",").filter(Boolean));
+  const afterParam = c.req.query("after");
+  const beforeParam = c.req.query("before");
+
+  if (order !== "oldest" && order !== "newest") {
+    return apiError(c, 400, "Order must be oldest or newest");
+  }
+  if (afterParam && beforeParam) {
+    return apiError(c, 400, "Only one of after and before can be given");
+  }
 
-  const comments = await db
+  const cursorParam = afterParam ?? beforeParam;
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const access = await getIssueAccess(id, currentUser?.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
+  }
+
+  // Going backwards reads the opposite direction and flips the page back afterwards.
+  const ascending = (order === "oldest") !== !!beforeParam;
+  const conditions = [eq(issueComments.issueId, id)];
+  if (cursor) {
+    conditions.push(
+      afterCursor(
+        [
+          { column: issueComments.createdAt, type: "timestamp" },
+          { column: issueComments.id, type: "uuid" },
+        ],
+        cursor,
+        ascending ? "asc" : "desc"
+      )
+    );
+  }
+
+  const rows = await db
     .select({
       id: issueComments.id,
       body: issueComments.body,
+      hiddenReason: issueComments.hiddenReason,
+      hiddenAt: issueComments.hiddenAt,
       createdAt: issueComments.createdAt,
       updatedAt: issueComments.updatedAt,
       authorId: issueComments.authorId,
+      sortKey: sql<string>`${issueComments.createdAt}::text`,
     })
     .from(issueComments)
-    .where(eq(issueComments.issueId, id))
-    .orderBy(issueComments.createdAt);
-
-  const commentsList = await Promise.all(
-    comments.map(async (comment) => {
-      const author = await db.query.users.findFirst({
-        where: eq(users.id, comment.authorId),
-        columns: { id: true, username: true, name: true, avatarUrl: true },
-      });
+    .where(and(...conditions))
+    .orderBy(
+      ascending ? issueComments.createdAt : desc(issueComments.createdAt),
+      ascending ? issueComments.id : desc(issueComments.id)
+    )
+    .limit(limit + 1);
 
-      const reactions = await getCommentReactionsGrouped(comment.id, currentUser?.id);
+  const hasMore = rows.length > limit;
+  const comments = rows.slice(0, limit);
+  if (beforeParam) comments.reverse();
+
+  const commentIds = comments.map((comment) => comment.id);
+  const [userMap, reactionMap] = await Promise.all([
+    getUsersByIds([...new Set(comments.map((comment) => comment.authorId))]),
+    include.has("reactions") ? getReactionsGroupedBatch("issue_comment", commentIds, currentUser?.id) : null,
+  ]);
+
+  const commentsList = comments.map((comment) => {
+    // Hidden comments stay in the thread so replies still make sense, but only the repo owner
+    // (who moderates it) gets the original text back.
+    const masked = comment.hiddenReason !== null && access.role !== "owner";
+
+    return {
+      id: comment.id,
+      body: masked ? null : comment.body,
+      hidden: comment.hiddenReason ? { reason: comment.hiddenReason, hiddenAt: comment.hiddenAt } : null,
+      author: userMap.get(comment.authorId) || { id: comment.authorId, username: "unknown", name: "Unknown", avatarUrl: null },
+      ...(reactionMap ? { reactions: reactionMap.get(comment.id) || [] } : {}),
+      createdAt: comment.createdAt,
+      updatedAt: comment.updatedAt,
+    };
+  });
 
-      return {
-        id: comment.id,
-        body: comment.body,
-        author: author || { id: comment.authorId, username: "unknown", name: "Unknown", avatarUrl: null },
-        reactions,
-        createdAt: comment.createdAt,
-        updatedAt: comment.updatedAt,
-      };
-    })
-  );
+  const cursorOf = (comment: (typeof comments)[number]) => encodeCursor([comment.sortKey, comment.id]);
+  const first = comments[0];
+  const last = comments[comments.length - 1];
 
-  return c.json({ comments: commentsList });
+  return c.json({
+    comments: commentsList,
+    hasMore,
+    nextCursor: last && (beforeParam ? true : hasMore) ? cursorOf(last) : null,
+    previousCursor: first && (beforeParam ? hasMore : !!afterParam) ? cursorOf(first) : null,
+  });
 });
 
 app.post("/api/issues/:id/comments", requireAuth, async (c) => {
@@ -711,17 +1381,20 @@ app.post("/api/issues/:id/comments", requireAuth, async (c) => {
   const body = await c.req.json<{ body: string }>();
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Comment cannot be empty" }, 400);
+    return apiError(c, 400, "Comment cannot be empty");
   }
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
+  }
 
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  if (!access.can("comment")) {
+    return apiError(c, 403, access.denial("comment"));
   }
 
+  const { issue } = access;
+
   const [inserted] = await db
     .insert(issueComments)
     .values({
@@ -731,6 +1404,20 @@ app.post("/api/issues/:id/comments", requireAuth, async (c) => {
     })
     .returning();
 
+  await recordIssueReferences(issue.repositoryId, { type: "issue", issueId: id }, inserted.body, user.id);
+  await recordIssueMentions(
+    { issueId: id, issueNumber: issue.number, issueTitle: issue.title, repositoryId: issue.repositoryId, commentId: inserted.id },
+    inserted.body,
+    user.id
+  );
+
+  recordActivity({
+    type: "issue_commented",
+    actorId: user.id,
+    repositoryId: issue.repositoryId,
+    payload: { issueId: id, number: issue.number, title: issue.title, commentId: inserted.id },
+  });
+
   return c.json({
     id: inserted.id,
     body: inserted.body,
@@ -750,16 +1437,16 @@ app.patch("/api/issues/comments/:id", requireAuth, async (c) => {
     where: eq(issueComments.id, id),
   });
 
-  if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+  if (!comment || !(await getIssueAccess(comment.issueId, user.id))) {
+    return apiError(c, 404, "Comment not found");
   }
 
-  if (user.id !== comment.authorId) {
-    return c.json({ error: "Only comment author can edit" }, 403);
+  if (!canEditIssueComment(comment, user.id)) {
+    return apiError(c, 403, "Only comment author can edit");
   }
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Comment cannot be empty" }, 400);
+    return apiError(c, 400, "Comment cannot be empty");
   }
 
   await db.update(issueComments).set({ body: body.body, updatedAt: new Date() }).where(eq(issueComments.id, id));
@@ -767,33 +1454,85 @@ app.patch("/api/issues/comments/:id", requireAuth, async (c) => {
   return c.json({ success: true });
 });
 
-app.delete("/api/issues/comments/:id", requireAuth, async (c) => {
+app.post("/api/issues/comments/:id/hide", requireAuth, async (c) => {
   const id = c.req.param("id");
   const user = c.get("user")!;
+  const body = await c.req.json<{ reason?: string }>();
+
+  if (!HIDDEN_REASONS.includes(body.reason as HiddenReason)) {
+    return apiError(c, 400, `reason must be one of ${HIDDEN_REASONS.join(", ")}`);
+  }
 
   const comment = await db.query.issueComments.findFirst({
     where: eq(issueComments.id, id),
   });
 
-  if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+  const access = comment ? await getIssueAccess(comment.issueId, user.id) : null;
+  if (!comment || !access) {
+    return apiError(c, 404, "Comment not found");
   }
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, comment.issueId),
+  if (access.role !== "owner") {
+    return apiError(c, 403, "Only repo owner can hide comments");
+  }
+
+  await db
+    .update(issueComments)
+    .set({ hiddenReason: body.reason as HiddenReason, hiddenAt: new Date(), hiddenById: user.id })
+    .where(eq(issueComments.id, id));
+  await resolveReportsFor("issue_comment", id, user.id);
+
+  return c.json({ success: true });
+});
+
+app.delete("/api/issues/comments/:id/hide", requireAuth, async (c) => {
+  const id = c.req.param("id");
+  const user = c.get("user")!;
+
+  const comment = await db.query.issueComments.findFirst({
+    where: eq(issueComments.id, id),
   });
 
-  const repo = issue
-    ? await db.query.repositories.findFirst({
-        where: eq(repositories.id, issue.repositoryId),
-      })
-    : null;
+  const access = comment ? await getIssueAccess(comment.issueId, user.id) : null;
+  if (!comment || !access) {
+    return apiError(c, 404, "Comment not found");
+  }
+
+  if (access.role !== "owner") {
+    return apiError(c, 403, "Only repo owner can unhide comments");
+  }
 
-  if (user.id !== comment.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+  await db
+    .update(issueComments)
+    .set({ hiddenReason: null, hiddenAt: null, hiddenById: null })
+    .where(eq(issueComments.id, id));
+
+  return c.json({ success: true });
+});
+
+app.delete("/api/issues/comments/:id", requireAuth, async (c) => {
+  const id = c.req.param("id");
+  const user = c.get("user")!;
+
+  const comment = await db.query.issueComments.findFirst({
+    where: eq(issueComments.id, id),
+  });
+
+  if (!comment) {
+    return apiError(c, 404, "Comment not found");
+  }
+
+  const access = await getIssueAccess(comment.issueId, user.id);
+  if (!access) {
+    return apiError(c, 404, "Comment not found");
+  }
+
+  if (!canDeleteIssueComment(access, comment, user.id)) {
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(issueComments).where(eq(issueComments.id, id));
+  await deleteReactions("issue_comment", [id]);
 
   return c.json({ success: true });
 });
@@ -804,34 +1543,20 @@ app.post("/api/issues/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
-  const issue = await db.query.issues.findFirst({
-    where: eq(issues.id, id),
-  });
-
-  if (!issue) {
-    return c.json({ error: "Issue not found" }, 404);
+  const access = await getIssueAccess(id, user.id);
+  if (!access) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const existing = await db.query.issueReactions.findFirst({
-    where: and(eq(issueReactions.issueId, id), eq(issueReactions.userId, user.id), eq(issueReactions.emoji, body.emoji)),
-  });
-
-  if (existing) {
-    await db
-      .delete(issueReactions)
-      .where(and(eq(issueReactions.issueId, id), eq(issueReactions.userId, user.id), eq(issueReactions.emoji, body.emoji)));
-    return c.json({ added: false });
-  } else {
-    await db.insert(issueReactions).values({
-      issueId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
+  if (!access.can("react")) {
+    return apiError(c, 403, access.denial("react"));
   }
+
+  const added = await toggleReaction("issue", id, access.repo.id, user.id, body.emoji);
+  return c.json({ added });
 });
 
 app.post("/api/issues/comments/:id/reactions", requireAuth, async (c) => {
@@ -840,7 +1565,7 @@ app.post("/api/issues/comments/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
   const comment = await db.query.issueComments.findFirst({
@@ -848,26 +1573,56 @@ app.post("/api/issues/comments/:id/reactions", requireAuth, async (c) => {
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
+  }
+
+  const access = await getIssueAccess(comment.issueId, user.id);
+  if (!access) {
+    return apiError(c, 404, "Comment not found");
+  }
+
+  if (!access.can("react")) {
+    return apiError(c, 403, access.denial("react"));
+  }
+
+  const added = await toggleReaction("issue_comment", id, access.repo.id, user.id, body.emoji);
+  return c.json({ added });
+});
+
+app.get("/api/issues/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
+  }
+
+  if (!(await getIssueAccess(id, currentUser?.id))) {
+    return apiError(c, 404, "Issue not found");
   }
 
-  const existing = await db.query.issueReactions.findFirst({
-    where: and(eq(issueReactions.commentId, id), eq(issueReactions.userId, user.id), eq(issueReactions.emoji, body.emoji)),
+  return c.json(await listReactionUsers("issue", id, query));
+});
+
+app.get("/api/issues/comments/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
+  }
+
+  const comment = await db.query.issueComments.findFirst({
+    where: eq(issueComments.id, id),
   });
 
-  if (existing) {
-    await db
-      .delete(issueReactions)
-      .where(and(eq(issueReactions.commentId, id), eq(issueReactions.userId, user.id), eq(issueReactions.emoji, body.emoji)));
-    return c.json({ added: false });
-  } else {
-    await db.insert(issueReactions).values({
-      commentId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
+  if (!comment || !(await getIssueAccess(comment.issueId, currentUser?.id))) {
+    return apiError(c, 404, "Comment not found");
   }
+
+  return c.json(await listReactionUsers("issue_comment", id, query));
 });
 
 export default app;
@@ -0,0 +1,137 @@
+import { Hono } from "hono";
+import { db, users, repositories, contentReports, issueComments } from "@gitbruv/db";
+import { eq, and } from "drizzle-orm";
+import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import { getIssueAccess } from "../issue-acl";
+import { REPORT_REASONS, MAX_REPORT_DETAILS_LENGTH, listReports, type ReportReason } from "../moderation";
+import { decodeCursor } from "../pagination";
+import { apiError } from "../errors";
+
+const app = new Hono<{ Variables: AuthVariables }>();
+
+app.use("*", authMiddleware);
+
+const MAX_LIMIT = 100;
+
+app.post("/api/reports", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const body = await c.req.json<{ subjectType?: string; subjectId?: string; reason?: string; details?: string }>();
+
+  if (body.subjectType !== "issue" && body.subjectType !== "issue_comment") {
+    return apiError(c, 400, "subjectType must be 'issue' or 'issue_comment'");
+  }
+  if (typeof body.subjectId !== "string") {
+    return apiError(c, 400, "subjectId is required");
+  }
+  if (!REPORT_REASONS.includes(body.reason as ReportReason)) {
+    return apiError(c, 400, `reason must be one of ${REPORT_REASONS.join(", ")}`);
+  }
+  if (body.details !== undefined && (typeof body.details !== "string" || body.details.length > MAX_REPORT_DETAILS_LENGTH)) {
+    return apiError(c, 400, `details must be a string of at most ${MAX_REPORT_DETAILS_LENGTH} characters`);
+  }
+
+  let issueId = body.subjectId;
+  if (body.subjectType === "issue_comment") {
+    const comment = await db.query.issueComments.findFirst({
+      where: eq(issueComments.id, body.subjectId),
+      columns: { issueId: true },
+    });
+    if (!comment) {
+      return apiError(c, 404, "Comment not found");
+    }
+    issueId = comment.issueId;
+  }
+
+  // Anything the reporter can read can be reported; getIssueAccess hides private repositories.
+  const access = await getIssueAccess(issueId, user.id);
+  if (!access) {
+    return apiError(c, 404, body.subjectType === "issue" ? "Issue not found" : "Comment not found");
+  }
+
+  const [report] = await db
+    .insert(contentReports)
+    .values({
+      reporterId: user.id,
+      repositoryId: access.repo.id,
+      subjectType: body.subjectType,
+      subjectId: body.subjectId,
+      reason: body.reason as ReportReason,
+      details: body.details?.trim() || null,
+    })
+    .onConflictDoNothing()
+    .returning({ id: contentReports.id });
+
+  if (!report) {
+    return apiError(c, 409, "You have already reported this");
+  }
+
+  return c.json({ id: report.id }, 201);
+});
+
+app.get("/api/repositories/:owner/:name/reports", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const status = c.req.query("status") || "open";
+  const limit = Math.min(parseInt(c.req.query("limit") || "30", 10) || 30, MAX_LIMIT);
+  const cursorParam = c.req.query("cursor");
+
+  if (status !== "open" && status !== "resolved" && status !== "dismissed") {
+    return apiError(c, 400, "Invalid status");
+  }
+
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const [repo] = await db
+    .select({ id: repositories.id, ownerId: repositories.ownerId })
+    .from(repositories)
+    .innerJoin(users, eq(users.id, repositories.ownerId))
+    .where(and(eq(users.username, owner), eq(repositories.name, name)))
+    .limit(1);
+
+  if (!repo || repo.ownerId !== user.id) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  return c.json(
+    await listReports([eq(contentReports.repositoryId, repo.id), eq(contentReports.status, status)], cursor, limit)
+  );
+});
+
+app.patch("/api/reports/:id", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const id = c.req.param("id");
+  const body = await c.req.json<{ status?: string }>();
+
+  if (body.status !== "resolved" && body.status !== "dismissed" && body.status !== "open") {
+    return apiError(c, 400, "status must be 'open', 'resolved' or 'dismissed'");
+  }
+
+  const [report] = await db
+    .select({ id: contentReports.id, ownerId: repositories.ownerId })
+    .from(contentReports)
+    .innerJoin(repositories, eq(repositories.id, contentReports.repositoryId))
+    .where(eq(contentReports.id, id))
+    .limit(1);
+
+  if (!report || report.ownerId !== user.id) {
+    return apiError(c, 404, "Report not found");
+  }
+
+  const reopening = body.status === "open";
+  await db
+    .update(contentReports)
+    .set({
+      status: body.status,
+      resolvedById: reopening ? null : user.id,
+      resolvedAt: reopening ? null : new Date(),
+    })
+    .where(eq(contentReports.id, id));
+
+  return c.json({ success: true });
+});
+
+export default app;
@@ -1,9 +1,14 @@
 import { Hono } from "hono";
-import { db, users, notifications } from "@gitbruv/db";
+import { db, users, notifications, pushSubscriptions } from "@gitbruv/db";
 import { eq, sql, and, desc } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
 import { notifyUser } from "../websocket";
 import { sendNotificationEmail } from "../email";
+import { isWebPushConfigured, sendPushToUser } from "../web-push";
+import { trackTask } from "../metrics";
+import { config } from "../config";
+import { apiError } from "../errors";
+import { resolvesToPublicHost } from "../git/mirror";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
@@ -81,11 +86,11 @@ app.patch("/api/notifications/:id/read", requireAuth, async (c) => {
   });
 
   if (!notification) {
-    return c.json({ error: "Notification not found" }, 404);
+    return apiError(c, 404, "Notification not found");
   }
 
   if (notification.userId !== user.id) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db
@@ -116,11 +121,11 @@ app.delete("/api/notifications/:id", requireAuth, async (c) => {
   });
 
   if (!notification) {
-    return c.json({ error: "Notification not found" }, 404);
+    return apiError(c, 404, "Notification not found");
   }
 
   if (notification.userId !== user.id) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(notifications).where(eq(notifications.id, id));
@@ -128,6 +133,70 @@ app.delete("/api/notifications/:id", requireAuth, async (c) => {
   return c.json({ success: true });
 });
 
+app.get("/api/notifications/push/public-key", (c) => {
+  if (!isWebPushConfigured()) {
+    return apiError(c, 404, "Push notifications are not configured");
+  }
+
+  return c.json({ publicKey: config.webPush.vapidPublicKey });
+});
+
+app.post("/api/notifications/push/subscriptions", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const body = await c.req.json<{ endpoint?: string; keys?: { p256dh?: string; auth?: string } }>();
+
+  if (!body.endpoint || !body.keys?.p256dh || !body.keys?.auth) {
+    return apiError(c, 400, "Endpoint and keys are required");
+  }
+
+  let endpoint: URL;
+  try {
+    endpoint = new URL(body.endpoint);
+    if (endpoint.protocol !== "https:") throw new Error();
+  } catch {
+    return apiError(c, 400, "Invalid push endpoint");
+  }
+  // The server posts to this URL later, so it has to be a public host like any mirror upstream.
+  if (!(await resolvesToPublicHost(endpoint))) {
+    return apiError(c, 400, "Push endpoint must point to a public host");
+  }
+
+  const values = {
+    userId: user.id,
+    p256dh: body.keys.p256dh,
+    auth: body.keys.auth,
+    userAgent: c.req.header("user-agent") || null,
+  };
+
+  // Re-subscribing refreshes the caller's own row; an endpoint registered to someone else is left alone.
+  const [saved] = await db
+    .insert(pushSubscriptions)
+    .values({ endpoint: body.endpoint, ...values })
+    .onConflictDoUpdate({ target: pushSubscriptions.endpoint, set: values, setWhere: eq(pushSubscriptions.userId, user.id) })
+    .returning({ id: pushSubscriptions.id });
+
+  if (!saved) {
+    return apiError(c, 409, "Push endpoint is already registered");
+  }
+
+  return c.json({ success: true });
+});
+
+app.delete("/api/notifications/push/subscriptions", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const body = await c.req.json<{ endpoint?: string }>();
+
+  if (!body.endpoint) {
+    return apiError(c, 400, "Endpoint is required");
+  }
+
+  await db
+    .delete(pushSubscriptions)
+    .where(and(eq(pushSubscriptions.userId, user.id), eq(pushSubscriptions.endpoint, body.endpoint)));
+
+  return c.json({ success: true });
+});
+
 export type NotificationType =
   | "issue_comment"
   | "issue_assigned"
@@ -137,7 +206,9 @@ export type NotificationType =
   | "pr_merged"
   | "pr_assigned"
   | "mention"
-  | "discussion_reply";
+  | "discussion_reply"
+  | "review_reminder"
+  | "account_export";
 
 export type CreateNotificationInput = {
   userId: string;
@@ -177,18 +248,29 @@ export async function createNotification(input: CreateNotificationInput) {
     notification: enriched,
   });
 
+  let actionUrl: string | undefined;
+  if (input.repoOwner && input.repoName && input.resourceNumber) {
+    const resourcePath = input.resourceType === "issue" ? "issues" : input.resourceType === "pull_request" ? "pulls" : "discussions";
+    actionUrl = `/${input.repoOwner}/${input.repoName}/${resourcePath}/${input.resourceNumber}`;
+  }
+
+  trackTask("web_push", () =>
+    sendPushToUser(input.userId, {
+      title: input.title,
+      body: input.body,
+      url: actionUrl,
+      tag: inserted.id,
+    })
+  ).catch((error) => {
+    console.error("[API] Failed to send push notification:", error);
+  });
+
   if (input.sendEmail) {
     const user = await db.query.users.findFirst({
       where: eq(users.id, input.userId),
     });
 
     if (user?.email && user.preferences?.emailNotifications !== false) {
-      let actionUrl: string | undefined;
-      if (input.repoOwner && input.repoName && input.resourceNumber) {
-        const resourcePath = input.resourceType === "issue" ? "issues" : input.resourceType === "pull_request" ? "pulls" : "discussions";
-        actionUrl = `/${input.repoOwner}/${input.repoName}/${resourcePath}/${input.resourceNumber}`;
-      }
-
       await sendNotificationEmail(user.email, input.title, input.body || "", actionUrl, "View");
 
       await db
@@ -1,6 +1,7 @@
 import { db, oauthClients } from '@gitbruv/db';
 import { eq } from 'drizzle-orm';
 import { Hono } from 'hono';
+import { apiError } from '../errors';
 
 const app = new Hono();
 
@@ -13,7 +14,7 @@ app.get('/oauth/consent', async (c) => {
     const rawQueryString = new URL(c.req.url).search.slice(1);
 
     if (!clientId || !scope || !redirectUri) {
-      return c.json({ error: 'Missing required parameters' }, 400);
+      return apiError(c, 400, 'Missing required parameters');
     }
 
     // Get client information
@@ -22,7 +23,7 @@ app.get('/oauth/consent', async (c) => {
     });
 
     if (!client) {
-      return c.json({ error: 'Invalid client' }, 400);
+      return apiError(c, 400, 'Invalid client');
     }
 
     // Return HTML consent page
@@ -152,7 +153,7 @@ app.get('/oauth/consent', async (c) => {
     });
   } catch (error) {
     console.error('[OAuth] Consent page error:', error);
-    return c.json({ error: 'Internal server error' }, 500);
+    return apiError(c, 500, 'Internal server error');
   }
 });
 
@@ -9,8 +9,9 @@ import {
   issues,
   pullRequests,
 } from "@gitbruv/db";
-import { eq, sql, and, asc } from "drizzle-orm";
+import { eq, sql, and, asc, inArray } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import { apiError } from "../errors";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
@@ -38,6 +39,14 @@ async function getRepoAndCheckAccess(owner: string, name: string, userId?: strin
   return { repoId: row.id, ownerId: row.ownerId };
 }
 
+async function columnBelongsTo(columnId: string, projectId: string) {
+  const column = await db.query.projectColumns.findFirst({
+    where: and(eq(projectColumns.id, columnId), eq(projectColumns.projectId, projectId)),
+    columns: { id: true },
+  });
+  return !!column;
+}
+
 async function enrichProjectItem(item: any) {
   if (item.issueId) {
     const issue = await db.query.issues.findFirst({
@@ -106,7 +115,7 @@ app.get("/api/repositories/:owner/:name/projects", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const projectList = await db
@@ -126,15 +135,15 @@ app.post("/api/repositories/:owner/:name/projects", requireAuth, async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (user.id !== repoAccess.ownerId) {
-    return c.json({ error: "Only repo owner can create projects" }, 403);
+    return apiError(c, 403, "Only repo owner can create projects");
   }
 
   if (!body.name?.trim()) {
-    return c.json({ error: "Project name is required" }, 400);
+    return apiError(c, 400, "Project name is required");
   }
 
   const [inserted] = await db
@@ -152,6 +161,7 @@ app.post("/api/repositories/:owner/:name/projects", requireAuth, async (c) => {
       projectId: inserted.id,
       name: defaultColumns[i],
       position: i,
+      moveClosedIssues: defaultColumns[i] === "Done",
     });
   }
 
@@ -167,7 +177,7 @@ app.get("/api/projects/:id", async (c) => {
   });
 
   if (!project) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -175,11 +185,11 @@ app.get("/api/projects/:id", async (c) => {
   });
 
   if (!repo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const columns = await db
@@ -202,6 +212,7 @@ app.get("/api/projects/:id", async (c) => {
         id: column.id,
         name: column.name,
         position: column.position,
+        moveClosedIssues: column.moveClosedIssues,
         items: enrichedItems,
       };
     })
@@ -227,7 +238,7 @@ app.patch("/api/projects/:id", requireAuth, async (c) => {
   });
 
   if (!project) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -235,7 +246,7 @@ app.patch("/api/projects/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can update projects" }, 403);
+    return apiError(c, 403, "Only repo owner can update projects");
   }
 
   const updates: Record<string, any> = { updatedAt: new Date() };
@@ -256,7 +267,7 @@ app.delete("/api/projects/:id", requireAuth, async (c) => {
   });
 
   if (!project) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -264,7 +275,7 @@ app.delete("/api/projects/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete projects" }, 403);
+    return apiError(c, 403, "Only repo owner can delete projects");
   }
 
   await db.delete(projects).where(eq(projects.id, id));
@@ -275,14 +286,14 @@ app.delete("/api/projects/:id", requireAuth, async (c) => {
 app.post("/api/projects/:id/columns", requireAuth, async (c) => {
   const id = c.req.param("id");
   const user = c.get("user")!;
-  const body = await c.req.json<{ name: string }>();
+  const body = await c.req.json<{ name: string; moveClosedIssues?: boolean }>();
 
   const project = await db.query.projects.findFirst({
     where: eq(projects.id, id),
   });
 
   if (!project) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -290,7 +301,7 @@ app.post("/api/projects/:id/columns", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can add columns" }, 403);
+    return apiError(c, 403, "Only repo owner can add columns");
   }
 
   const [maxPosition] = await db
@@ -304,6 +315,7 @@ app.post("/api/projects/:id/columns", requireAuth, async (c) => {
       projectId: id,
       name: body.name,
       position: (maxPosition?.max ?? -1) + 1,
+      moveClosedIssues: body.moveClosedIssues === true,
     })
     .returning();
 
@@ -313,14 +325,14 @@ app.post("/api/projects/:id/columns", requireAuth, async (c) => {
 app.patch("/api/projects/columns/:id", requireAuth, async (c) => {
   const id = c.req.param("id");
   const user = c.get("user")!;
-  const body = await c.req.json<{ name?: string; position?: number }>();
+  const body = await c.req.json<{ name?: string; position?: number; moveClosedIssues?: boolean }>();
 
   const column = await db.query.projectColumns.findFirst({
     where: eq(projectColumns.id, id),
   });
 
   if (!column) {
-    return c.json({ error: "Column not found" }, 404);
+    return apiError(c, 404, "Column not found");
   }
 
   const project = await db.query.projects.findFirst({
@@ -334,12 +346,13 @@ app.patch("/api/projects/columns/:id", requireAuth, async (c) => {
     : null;
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can update columns" }, 403);
+    return apiError(c, 403, "Only repo owner can update columns");
   }
 
   const updates: Record<string, any> = {};
   if (body.name !== undefined) updates.name = body.name;
   if (body.position !== undefined) updates.position = body.position;
+  if (typeof body.moveClosedIssues === "boolean") updates.moveClosedIssues = body.moveClosedIssues;
 
   if (Object.keys(updates).length > 0) {
     await db.update(projectColumns).set(updates).where(eq(projectColumns.id, id));
@@ -357,7 +370,7 @@ app.delete("/api/projects/columns/:id", requireAuth, async (c) => {
   });
 
   if (!column) {
-    return c.json({ error: "Column not found" }, 404);
+    return apiError(c, 404, "Column not found");
   }
 
   const project = await db.query.projects.findFirst({
@@ -371,7 +384,7 @@ app.delete("/api/projects/columns/:id", requireAuth, async (c) => {
     : null;
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete columns" }, 403);
+    return apiError(c, 403, "Only repo owner can delete columns");
   }
 
   await db.delete(projectColumns).where(eq(projectColumns.id, id));
@@ -394,7 +407,7 @@ app.post("/api/projects/:id/items", requireAuth, async (c) => {
   });
 
   if (!project) {
-    return c.json({ error: "Project not found" }, 404);
+    return apiError(c, 404, "Project not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -402,15 +415,39 @@ app.post("/api/projects/:id/items", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can add items" }, 403);
+    return apiError(c, 403, "Only repo owner can add items");
   }
 
   if (!body.columnId) {
-    return c.json({ error: "Column is required" }, 400);
+    return apiError(c, 400, "Column is required");
   }
 
   if (!body.issueId && !body.pullRequestId && !body.noteContent) {
-    return c.json({ error: "Must provide an issue, PR, or note content" }, 400);
+    return apiError(c, 400, "Must provide an issue, PR, or note content");
+  }
+
+  if (!(await columnBelongsTo(body.columnId, id))) {
+    return apiError(c, 400, "Column does not belong to this project");
+  }
+
+  if (body.issueId) {
+    const issue = await db.query.issues.findFirst({
+      where: and(eq(issues.id, body.issueId), eq(issues.repositoryId, project.repositoryId)),
+      columns: { id: true },
+    });
+    if (!issue) {
+      return apiError(c, 400, "Issue not found in this repository");
+    }
+  }
+
+  if (body.pullRequestId) {
+    const pr = await db.query.pullRequests.findFirst({
+      where: and(eq(pullRequests.id, body.pullRequestId), eq(pullRequests.repositoryId, project.repositoryId)),
+      columns: { id: true },
+    });
+    if (!pr) {
+      return apiError(c, 400, "Pull request not found in this repository");
+    }
   }
 
   const [maxPosition] = await db
@@ -445,7 +482,7 @@ app.patch("/api/projects/items/:id", requireAuth, async (c) => {
   });
 
   if (!item) {
-    return c.json({ error: "Item not found" }, 404);
+    return apiError(c, 404, "Item not found");
   }
 
   const project = await db.query.projects.findFirst({
@@ -459,7 +496,11 @@ app.patch("/api/projects/items/:id", requireAuth, async (c) => {
     : null;
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can update items" }, 403);
+    return apiError(c, 403, "Only repo owner can update items");
+  }
+
+  if (body.columnId !== undefined && !(await columnBelongsTo(body.columnId, item.projectId))) {
+    return apiError(c, 400, "Column does not belong to this project");
   }
 
   const updates: Record<string, any> = {};
@@ -479,7 +520,7 @@ app.post("/api/projects/items/reorder", requireAuth, async (c) => {
   const body = await c.req.json<{ items: { id: string; columnId: string; position: number }[] }>();
 
   if (!body.items?.length) {
-    return c.json({ error: "Items array is required" }, 400);
+    return apiError(c, 400, "Items array is required");
   }
 
   const firstItem = await db.query.projectItems.findFirst({
@@ -487,7 +528,7 @@ app.post("/api/projects/items/reorder", requireAuth, async (c) => {
   });
 
   if (!firstItem) {
-    return c.json({ error: "Item not found" }, 404);
+    return apiError(c, 404, "Item not found");
   }
 
   const project = await db.query.projects.findFirst({
@@ -501,15 +542,35 @@ app.post("/api/projects/items/reorder", requireAuth, async (c) => {
     : null;
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can reorder items" }, 403);
-  }
-
-  for (const item of body.items) {
-    await db
-      .update(projectItems)
-      .set({ columnId: item.columnId, position: item.position })
-      .where(eq(projectItems.id, item.id));
-  }
+    return apiError(c, 403, "Only repo owner can reorder items");
+  }
+
+  // Every card and column in the batch has to be on the same board as the first card, otherwise
+  // an owner could move cards onto (or off of) boards in repositories they don't own.
+  const itemIds = [...new Set(body.items.map((item) => item.id))];
+  const columnIds = [...new Set(body.items.map((item) => item.columnId))];
+  const [ownedItems, ownedColumns] = await Promise.all([
+    db
+      .select({ id: projectItems.id })
+      .from(projectItems)
+      .where(and(eq(projectItems.projectId, firstItem.projectId), inArray(projectItems.id, itemIds))),
+    db
+      .select({ id: projectColumns.id })
+      .from(projectColumns)
+      .where(and(eq(projectColumns.projectId, firstItem.projectId), inArray(projectColumns.id, columnIds))),
+  ]);
+  if (ownedItems.length !== itemIds.length || ownedColumns.length !== columnIds.length) {
+    return apiError(c, 400, "All items and columns must belong to the same project");
+  }
+
+  await db.transaction(async (tx) => {
+    for (const item of body.items) {
+      await tx
+        .update(projectItems)
+        .set({ columnId: item.columnId, position: item.position })
+        .where(eq(projectItems.id, item.id));
+    }
+  });
 
   return c.json({ success: true });
 });
@@ -523,7 +584,7 @@ app.delete("/api/projects/items/:id", requireAuth, async (c) => {
   });
 
   if (!item) {
-    return c.json({ error: "Item not found" }, 404);
+    return apiError(c, 404, "Item not found");
   }
 
   const project = await db.query.projects.findFirst({
@@ -537,7 +598,7 @@ app.delete("/api/projects/items/:id", requireAuth, async (c) => {
     : null;
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete items" }, 403);
+    return apiError(c, 403, "Only repo owner can delete items");
   }
 
   await db.delete(projectItems).where(eq(projectItems.id, id));
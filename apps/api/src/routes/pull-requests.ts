@@ -9,20 +9,34 @@ import {
   prLabels,
   prAssignees,
   prReviewers,
-  prReactions,
   labels,
   branchProtectionRules,
+  repoBranchMetadata,
 } from "@gitbruv/db";
-import { eq, sql, and, desc } from "drizzle-orm";
+import { eq, sql, and, desc, ne } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
-import { createGitStore, getCommits, getCommitDiff, performMerge, repoCache, resolveRefOid } from "../git";
+import { createGitStore, getCommits, getCommitDiff, getChangedPaths, performMerge, repoCache, resolveRefOid } from "../git";
+import { getCodeOwnerReviewers } from "../code-owners";
+import { recordIssueReferences } from "../issue-references";
+import {
+  VALID_EMOJIS,
+  getReactionsGrouped,
+  toggleReaction,
+  listReactionUsers,
+  deleteReactions,
+  parseReactionListQuery,
+} from "../reactions";
+import { notifyUser } from "../websocket";
+import { apiError } from "../errors";
+import { recordActivity } from "../activity";
+import { recordRefUpdates } from "../ref-log";
+import { applyRefUpdates, ZERO_OID } from "../git/ref-transaction";
+import { storageLayout } from "../storage-layout";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
-const VALID_EMOJIS = ["+1", "-1", "laugh", "hooray", "confused", "heart", "rocket", "eyes"];
-
 async function getRepoAndCheckAccess(owner: string, name: string, userId?: string) {
   const result = await db
     .select({
@@ -120,58 +134,6 @@ async function getPRReviews(prId: string) {
   );
 }
 
-async function getReactionsGrouped(prId: string, userId?: string) {
-  const counts = await db
-    .select({
-      emoji: prReactions.emoji,
-      count: sql<number>`COUNT(*)`,
-    })
-    .from(prReactions)
-    .where(eq(prReactions.pullRequestId, prId))
-    .groupBy(prReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: prReactions.emoji })
-      .from(prReactions)
-      .where(and(eq(prReactions.pullRequestId, prId), eq(prReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
-}
-
-async function getCommentReactionsGrouped(commentId: string, userId?: string) {
-  const counts = await db
-    .select({
-      emoji: prReactions.emoji,
-      count: sql<number>`COUNT(*)`,
-    })
-    .from(prReactions)
-    .where(eq(prReactions.commentId, commentId))
-    .groupBy(prReactions.emoji);
-
-  let userEmojis: string[] = [];
-  if (userId) {
-    const userReactions = await db
-      .select({ emoji: prReactions.emoji })
-      .from(prReactions)
-      .where(and(eq(prReactions.commentId, commentId), eq(prReactions.userId, userId)));
-    userEmojis = userReactions.map((r) => r.emoji);
-  }
-
-  return counts.map((c) => ({
-    emoji: c.emoji,
-    count: c.count,
-    reacted: userEmojis.includes(c.emoji),
-  }));
-}
-
 async function getCommentCount(prId: string): Promise<number> {
   const [result] = await db
     .select({ count: sql<number>`COUNT(*)` })
@@ -234,7 +196,7 @@ async function enrichPullRequest(pr: any, currentUserId?: string) {
   const assignees = await getPRAssignees(pr.id);
   const reviewers = await getPRReviewers(pr.id);
   const reviews = await getPRReviews(pr.id);
-  const reactions = await getReactionsGrouped(pr.id, currentUserId);
+  const reactions = await getReactionsGrouped("pull_request", pr.id, currentUserId);
   const commentCount = await getCommentCount(pr.id);
 
   const headRepo = await getRepoInfo(pr.headRepoId);
@@ -281,7 +243,7 @@ app.get("/api/repositories/:owner/:name/pulls", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   let stateCondition;
@@ -335,15 +297,15 @@ app.post("/api/repositories/:owner/:name/pulls", requireAuth, async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, user.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (!body.title?.trim()) {
-    return c.json({ error: "Title cannot be empty" }, 400);
+    return apiError(c, 400, "Title cannot be empty");
   }
 
   if (!body.headBranch?.trim()) {
-    return c.json({ error: "Head branch is required" }, 400);
+    return apiError(c, 400, "Head branch is required");
   }
 
   const baseBranch = body.baseBranch || repoAccess.defaultBranch;
@@ -354,7 +316,7 @@ app.post("/api/repositories/:owner/:name/pulls", requireAuth, async (c) => {
   if (body.headRepoOwner && body.headRepoName) {
     const headRepoAccess = await getRepoAndCheckAccess(body.headRepoOwner, body.headRepoName, user.id);
     if (!headRepoAccess) {
-      return c.json({ error: "Head repository not found" }, 404);
+      return apiError(c, 404, "Head repository not found");
     }
     headRepoId = headRepoAccess.repoId;
     headRepoOwnerId = headRepoAccess.ownerId;
@@ -368,7 +330,7 @@ app.post("/api/repositories/:owner/:name/pulls", requireAuth, async (c) => {
   });
 
   if (!headRepo || !baseRepo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const headStore = createGitStore(headRepoOwnerId, headRepo.name);
@@ -378,11 +340,11 @@ app.post("/api/repositories/:owner/:name/pulls", requireAuth, async (c) => {
   const baseCommits = await getCommits(baseStore.fs, baseStore.dir, baseBranch, 1, 0);
 
   if (headCommits.commits.length === 0) {
-    return c.json({ error: "Head branch not found" }, 400);
+    return apiError(c, 400, "Head branch not found");
   }
 
   if (baseCommits.commits.length === 0) {
-    return c.json({ error: "Base branch not found" }, 400);
+    return apiError(c, 400, "Base branch not found");
   }
 
   const headOid = headCommits.commits[0].oid;
@@ -429,6 +391,29 @@ app.post("/api/repositories/:owner/:name/pulls", requireAuth, async (c) => {
     }
   }
 
+  // CODEOWNERS of the base repository are requested on top of anyone picked explicitly. A failure
+  // here shouldn't lose the pull request that was just created.
+  try {
+    const changedPaths = await getChangedPaths(baseStore, baseBranch, headStore, body.headBranch);
+    const codeOwners = await getCodeOwnerReviewers(repoAccess.repoId, changedPaths, user.id);
+    if (codeOwners.length > 0) {
+      await db
+        .insert(prReviewers)
+        .values(codeOwners.map((userId) => ({ pullRequestId: inserted.id, userId })))
+        .onConflictDoNothing();
+    }
+  } catch (error) {
+    console.error("[API] Failed to request CODEOWNERS reviews:", error);
+  }
+
+  await recordIssueReferences(repoAccess.repoId, { type: "pull_request", pullRequestId: inserted.id }, `${inserted.title}\n${inserted.body || ""}`, user.id);
+  recordActivity({
+    type: "pull_request_opened",
+    actorId: user.id,
+    repositoryId: repoAccess.repoId,
+    payload: { pullRequestId: inserted.id, number: inserted.number, title: inserted.title },
+  });
+
   const enriched = await enrichPullRequest(inserted, user.id);
   return c.json(enriched);
 });
@@ -440,7 +425,7 @@ app.get("/api/repositories/:owner/:name/pulls/count", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const [openCount] = await db
@@ -475,7 +460,7 @@ app.get("/api/repositories/:owner/:name/pulls/:number", async (c) => {
 
   const repoAccess = await getRepoAndCheckAccess(owner, name, currentUser?.id);
   if (!repoAccess) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const pr = await db.query.pullRequests.findFirst({
@@ -483,7 +468,7 @@ app.get("/api/repositories/:owner/:name/pulls/:number", async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const enriched = await enrichPullRequest(pr, currentUser?.id);
@@ -504,7 +489,7 @@ app.patch("/api/pulls/:id", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -512,19 +497,19 @@ app.patch("/api/pulls/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   if (body.title !== undefined && !body.title.trim()) {
-    return c.json({ error: "Title cannot be empty" }, 400);
+    return apiError(c, 400, "Title cannot be empty");
   }
 
   if (body.state && body.state !== "open" && body.state !== "closed") {
-    return c.json({ error: "Invalid state" }, 400);
+    return apiError(c, 400, "Invalid state");
   }
 
   if (pr.merged && body.state) {
-    return c.json({ error: "Cannot change state of merged pull request" }, 400);
+    return apiError(c, 400, "Cannot change state of merged pull request");
   }
 
   const updates: Record<string, any> = { updatedAt: new Date() };
@@ -543,6 +528,10 @@ app.patch("/api/pulls/:id", requireAuth, async (c) => {
 
   await db.update(pullRequests).set(updates).where(eq(pullRequests.id, id));
 
+  if (body.title !== undefined || body.body !== undefined) {
+    await recordIssueReferences(pr.repositoryId, { type: "pull_request", pullRequestId: id }, `${body.title ?? ""}\n${body.body ?? ""}`, user.id);
+  }
+
   return c.json({ success: true });
 });
 
@@ -555,7 +544,7 @@ app.delete("/api/pulls/:id", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -563,10 +552,13 @@ app.delete("/api/pulls/:id", requireAuth, async (c) => {
   });
 
   if (user.id !== repo?.ownerId) {
-    return c.json({ error: "Only repo owner can delete pull requests" }, 403);
+    return apiError(c, 403, "Only repo owner can delete pull requests");
   }
 
+  const comments = await db.select({ id: prComments.id }).from(prComments).where(eq(prComments.pullRequestId, id));
   await db.delete(pullRequests).where(eq(pullRequests.id, id));
+  await deleteReactions("pull_request", [id]);
+  await deleteReactions("pr_comment", comments.map((comment) => comment.id));
 
   return c.json({ success: true });
 });
@@ -580,7 +572,7 @@ app.get("/api/pulls/:id/diff", async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -588,11 +580,11 @@ app.get("/api/pulls/:id/diff", async (c) => {
   });
 
   if (!repo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const headRepo = await db.query.repositories.findFirst({
@@ -600,7 +592,7 @@ app.get("/api/pulls/:id/diff", async (c) => {
   });
 
   if (!headRepo) {
-    return c.json({ error: "Head repository not found" }, 404);
+    return apiError(c, 404, "Head repository not found");
   }
 
   const headRepoOwner = await db.query.users.findFirst({
@@ -608,14 +600,14 @@ app.get("/api/pulls/:id/diff", async (c) => {
   });
 
   if (!headRepoOwner) {
-    return c.json({ error: "Head repository owner not found" }, 404);
+    return apiError(c, 404, "Head repository owner not found");
   }
 
   const headStore = createGitStore(headRepoOwner.id, headRepo.name);
   const diff = await getCommitDiff(headStore.fs, headStore.dir, pr.headOid);
 
   if (!diff) {
-    return c.json({ error: "Could not compute diff" }, 500);
+    return apiError(c, 500, "Could not compute diff");
   }
 
   return c.json({
@@ -635,7 +627,7 @@ app.get("/api/pulls/:id/commits", async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -643,11 +635,11 @@ app.get("/api/pulls/:id/commits", async (c) => {
   });
 
   if (!repo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (repo.visibility === "private" && currentUser?.id !== repo.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const headRepo = await db.query.repositories.findFirst({
@@ -655,7 +647,7 @@ app.get("/api/pulls/:id/commits", async (c) => {
   });
 
   if (!headRepo) {
-    return c.json({ error: "Head repository not found" }, 404);
+    return apiError(c, 404, "Head repository not found");
   }
 
   const headRepoOwner = await db.query.users.findFirst({
@@ -663,7 +655,7 @@ app.get("/api/pulls/:id/commits", async (c) => {
   });
 
   if (!headRepoOwner) {
-    return c.json({ error: "Head repository owner not found" }, 404);
+    return apiError(c, 404, "Head repository owner not found");
   }
 
   const headStore = createGitStore(headRepoOwner.id, headRepo.name);
@@ -672,6 +664,74 @@ app.get("/api/pulls/:id/commits", async (c) => {
   return c.json({ commits, hasMore });
 });
 
+// `mergedHeadOid` is the head commit that was merged; the branch is only deleted if it still points
+// there, so commits pushed to it during the merge aren't lost.
+async function deleteMergedHeadBranch(
+  pr: { id: string; number: number; headBranch: string },
+  headRepo: { id: string; name: string; ownerId: string; defaultBranch: string },
+  mergedHeadOid: string,
+  actorId: string
+): Promise<boolean> {
+  if (pr.headBranch === headRepo.defaultBranch) {
+    return false;
+  }
+
+  const rule = await db.query.branchProtectionRules.findFirst({
+    where: and(eq(branchProtectionRules.repositoryId, headRepo.id), eq(branchProtectionRules.branchName, pr.headBranch)),
+  });
+  if (rule?.preventDeletion) {
+    return false;
+  }
+
+  const [otherOpen] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(pullRequests)
+    .where(
+      and(
+        eq(pullRequests.headRepoId, headRepo.id),
+        eq(pullRequests.headBranch, pr.headBranch),
+        eq(pullRequests.state, "open"),
+        ne(pullRequests.id, pr.id)
+      )
+    );
+  if (Number(otherOpen?.count) > 0) {
+    return false;
+  }
+
+  const errors = await applyRefUpdates(
+    headRepo.id,
+    storageLayout.repoPrefix(headRepo.ownerId, headRepo.name),
+    [{ ref: `refs/heads/${pr.headBranch}`, oldOid: mergedHeadOid, newOid: ZERO_OID }],
+    true
+  ).catch((error) => {
+    console.error("[API] merge: failed to delete head branch:", error);
+    return null;
+  });
+  if (!errors || errors.size > 0) {
+    if (errors?.get(`refs/heads/${pr.headBranch}`) === "stale info") {
+      console.warn(`[API] merge: head branch ${pr.headBranch} moved during the merge; keeping it`);
+    }
+    return false;
+  }
+
+  recordRefUpdates(headRepo.id, actorId, "branch_delete", [{ ref: pr.headBranch, oldOid: mergedHeadOid, newOid: null }]);
+  await db
+    .delete(repoBranchMetadata)
+    .where(and(eq(repoBranchMetadata.repoId, headRepo.id), eq(repoBranchMetadata.branch, pr.headBranch)));
+  await repoCache.invalidateBranch(headRepo.ownerId, headRepo.name, pr.headBranch);
+
+  notifyUser(headRepo.ownerId, {
+    type: "branch_deleted",
+    repositoryId: headRepo.id,
+    branch: pr.headBranch,
+    reason: "merged",
+    pullRequestId: pr.id,
+    pullRequestNumber: pr.number,
+  });
+
+  return true;
+}
+
 app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   const id = c.req.param("id");
   const user = c.get("user")!;
@@ -684,19 +744,19 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   if (pr.state !== "open") {
-    return c.json({ error: "Pull request is not open" }, 400);
+    return apiError(c, 400, "Pull request is not open");
   }
 
   if (pr.merged) {
-    return c.json({ error: "Pull request is already merged" }, 400);
+    return apiError(c, 400, "Pull request is already merged");
   }
 
   if (pr.isDraft) {
-    return c.json({ error: "Cannot merge a draft pull request. Mark it as ready for review first." }, 400);
+    return apiError(c, 400, "Cannot merge a draft pull request. Mark it as ready for review first.");
   }
 
   const baseRepo = await db.query.repositories.findFirst({
@@ -704,11 +764,15 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   });
 
   if (!baseRepo) {
-    return c.json({ error: "Base repository not found" }, 404);
+    return apiError(c, 404, "Base repository not found");
   }
 
   if (user.id !== baseRepo.ownerId && user.id !== pr.authorId) {
-    return c.json({ error: "Not authorized to merge" }, 403);
+    return apiError(c, 403, "Not authorized to merge");
+  }
+
+  if (baseRepo.mirrorUrl) {
+    return apiError(c, 400, "Cannot merge into a read-only mirror");
   }
 
   const headRepo = await db.query.repositories.findFirst({
@@ -716,7 +780,7 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   });
 
   if (!headRepo) {
-    return c.json({ error: "Head repository not found" }, 404);
+    return apiError(c, 404, "Head repository not found");
   }
 
   const baseStore = createGitStore(baseRepo.ownerId, baseRepo.name);
@@ -735,7 +799,7 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   try {
     currentHeadOid = await resolveRefOid(headStore, pr.headBranch);
   } catch {
-    return c.json({ error: "Could not resolve head branch ref" }, 500);
+    return apiError(c, 500, "Could not resolve head branch ref");
   }
 
   if (protectionRule?.requireReviews && protectionRule.requiredReviewCount > 0) {
@@ -758,9 +822,7 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
       .length;
 
     if (approvalCount < protectionRule.requiredReviewCount) {
-      return c.json({
-        error: `This pull request requires at least ${protectionRule.requiredReviewCount} approving review(s) for the current commit before merging. Currently has ${approvalCount}.`
-      }, 403);
+      return apiError(c, 403, `This pull request requires at least ${protectionRule.requiredReviewCount} approving review(s) for the current commit before merging. Currently has ${approvalCount}.`);
     }
   }
 
@@ -768,10 +830,10 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   try {
     const recheckHeadOid = await resolveRefOid(headStore, pr.headBranch);
     if (recheckHeadOid !== currentHeadOid) {
-      return c.json({ error: "The head branch was updated during merge validation. Please retry." }, 409);
+      return apiError(c, 409, "The head branch was updated during merge validation. Please retry.");
     }
   } catch {
-    return c.json({ error: "Could not resolve head branch ref" }, 500);
+    return apiError(c, 500, "Could not resolve head branch ref");
   }
 
   const mergeMessage = body.commitMessage || `Merge pull request #${pr.number} from ${pr.headBranch}\n\n${pr.title}`;
@@ -787,7 +849,7 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
   );
 
   if (!mergeResult) {
-    return c.json({ error: "Failed to perform merge" }, 500);
+    return apiError(c, 500, "Failed to perform merge");
   }
 
   await db
@@ -802,9 +864,24 @@ app.post("/api/pulls/:id/merge", requireAuth, async (c) => {
     })
     .where(eq(pullRequests.id, id));
 
+  recordRefUpdates(baseRepo.id, user.id, "merge", [
+    { ref: pr.baseBranch, oldOid: mergeResult.baseOid, newOid: mergeResult.mergeCommitOid },
+  ]);
   await repoCache.invalidateBranch(baseRepo.ownerId, baseRepo.name, pr.baseBranch);
 
-  return c.json({ success: true, mergeCommitOid: mergeResult.mergeCommitOid });
+  recordActivity({
+    type: "pull_request_merged",
+    actorId: user.id,
+    repositoryId: baseRepo.id,
+    payload: { pullRequestId: pr.id, number: pr.number, title: pr.title, mergeCommitOid: mergeResult.mergeCommitOid },
+  });
+
+  let branchDeleted = false;
+  if (baseRepo.deleteBranchOnMerge && (headRepo.id === baseRepo.id || headRepo.ownerId === user.id)) {
+    branchDeleted = await deleteMergedHeadBranch(pr, headRepo, mergeResult.headOid, user.id);
+  }
+
+  return c.json({ success: true, mergeCommitOid: mergeResult.mergeCommitOid, branchDeleted });
 });
 
 app.patch("/api/pulls/:id/ready", requireAuth, async (c) => {
@@ -816,7 +893,7 @@ app.patch("/api/pulls/:id/ready", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -824,11 +901,11 @@ app.patch("/api/pulls/:id/ready", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   if (!pr.isDraft) {
-    return c.json({ error: "Pull request is not a draft" }, 400);
+    return apiError(c, 400, "Pull request is not a draft");
   }
 
   await db
@@ -848,7 +925,7 @@ app.patch("/api/pulls/:id/draft", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -856,15 +933,15 @@ app.patch("/api/pulls/:id/draft", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   if (pr.isDraft) {
-    return c.json({ error: "Pull request is already a draft" }, 400);
+    return apiError(c, 400, "Pull request is already a draft");
   }
 
   if (pr.merged) {
-    return c.json({ error: "Cannot convert merged pull request to draft" }, 400);
+    return apiError(c, 400, "Cannot convert merged pull request to draft");
   }
 
   await db
@@ -884,7 +961,7 @@ app.post("/api/pulls/:id/reviews", requireAuth, async (c) => {
   }>();
 
   if (!["approved", "changes_requested", "commented"].includes(body.state)) {
-    return c.json({ error: "Invalid review state" }, 400);
+    return apiError(c, 400, "Invalid review state");
   }
 
   const pr = await db.query.pullRequests.findFirst({
@@ -892,7 +969,7 @@ app.post("/api/pulls/:id/reviews", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const [inserted] = await db
@@ -924,7 +1001,7 @@ app.get("/api/pulls/:id/reviews", async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const reviews = await getPRReviews(id);
@@ -942,7 +1019,7 @@ app.get("/api/pulls/:id/comments", async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const commentsQuery = db
@@ -975,7 +1052,7 @@ app.get("/api/pulls/:id/comments", async (c) => {
         columns: { id: true, username: true, name: true, avatarUrl: true },
       });
 
-      const reactions = await getCommentReactionsGrouped(comment.id, currentUser?.id);
+      const reactions = await getReactionsGrouped("pr_comment", comment.id, currentUser?.id);
 
       return {
         id: comment.id,
@@ -1025,7 +1102,7 @@ app.post("/api/pulls/:id/comments", requireAuth, async (c) => {
   }>();
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Comment cannot be empty" }, 400);
+    return apiError(c, 400, "Comment cannot be empty");
   }
 
   const pr = await db.query.pullRequests.findFirst({
@@ -1033,12 +1110,12 @@ app.post("/api/pulls/:id/comments", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const isInline = body.filePath && body.lineNumber !== undefined;
   if (isInline && !body.side) {
-    return c.json({ error: "Side is required for inline comments" }, 400);
+    return apiError(c, 400, "Side is required for inline comments");
   }
 
   if (body.replyToId) {
@@ -1046,7 +1123,7 @@ app.post("/api/pulls/:id/comments", requireAuth, async (c) => {
       where: eq(prComments.id, body.replyToId),
     });
     if (!parentComment) {
-      return c.json({ error: "Parent comment not found" }, 404);
+      return apiError(c, 404, "Parent comment not found");
     }
   }
 
@@ -1064,6 +1141,8 @@ app.post("/api/pulls/:id/comments", requireAuth, async (c) => {
     })
     .returning();
 
+  await recordIssueReferences(pr.repositoryId, { type: "pull_request", pullRequestId: id }, inserted.body, user.id);
+
   return c.json({
     id: inserted.id,
     body: inserted.body,
@@ -1089,15 +1168,15 @@ app.patch("/api/pulls/comments/:id", requireAuth, async (c) => {
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
   }
 
   if (user.id !== comment.authorId) {
-    return c.json({ error: "Only comment author can edit" }, 403);
+    return apiError(c, 403, "Only comment author can edit");
   }
 
   if (!body.body?.trim()) {
-    return c.json({ error: "Comment cannot be empty" }, 400);
+    return apiError(c, 400, "Comment cannot be empty");
   }
 
   await db.update(prComments).set({ body: body.body, updatedAt: new Date() }).where(eq(prComments.id, id));
@@ -1114,7 +1193,7 @@ app.delete("/api/pulls/comments/:id", requireAuth, async (c) => {
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
   }
 
   const pr = await db.query.pullRequests.findFirst({
@@ -1128,10 +1207,11 @@ app.delete("/api/pulls/comments/:id", requireAuth, async (c) => {
     : null;
 
   if (user.id !== comment.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(prComments).where(eq(prComments.id, id));
+  await deleteReactions("pr_comment", [id]);
 
   return c.json({ success: true });
 });
@@ -1146,7 +1226,7 @@ app.post("/api/pulls/:id/labels", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1154,7 +1234,7 @@ app.post("/api/pulls/:id/labels", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   for (const labelId of body.labels) {
@@ -1174,7 +1254,7 @@ app.delete("/api/pulls/:id/labels/:labelId", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1182,7 +1262,7 @@ app.delete("/api/pulls/:id/labels/:labelId", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(prLabels).where(and(eq(prLabels.pullRequestId, id), eq(prLabels.labelId, labelId)));
@@ -1200,7 +1280,7 @@ app.post("/api/pulls/:id/assignees", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1208,7 +1288,7 @@ app.post("/api/pulls/:id/assignees", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   for (const assigneeId of body.assignees) {
@@ -1228,7 +1308,7 @@ app.delete("/api/pulls/:id/assignees/:userId", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1236,7 +1316,7 @@ app.delete("/api/pulls/:id/assignees/:userId", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(prAssignees).where(and(eq(prAssignees.pullRequestId, id), eq(prAssignees.userId, userId)));
@@ -1254,7 +1334,7 @@ app.post("/api/pulls/:id/reviewers", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1262,7 +1342,7 @@ app.post("/api/pulls/:id/reviewers", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   for (const reviewerId of body.reviewers) {
@@ -1282,7 +1362,7 @@ app.delete("/api/pulls/:id/reviewers/:userId", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
   const repo = await db.query.repositories.findFirst({
@@ -1290,7 +1370,7 @@ app.delete("/api/pulls/:id/reviewers/:userId", requireAuth, async (c) => {
   });
 
   if (user.id !== pr.authorId && user.id !== repo?.ownerId) {
-    return c.json({ error: "Not authorized" }, 403);
+    return apiError(c, 403, "Not authorized");
   }
 
   await db.delete(prReviewers).where(and(eq(prReviewers.pullRequestId, id), eq(prReviewers.userId, userId)));
@@ -1304,7 +1384,7 @@ app.post("/api/pulls/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
   const pr = await db.query.pullRequests.findFirst({
@@ -1312,26 +1392,11 @@ app.post("/api/pulls/:id/reactions", requireAuth, async (c) => {
   });
 
   if (!pr) {
-    return c.json({ error: "Pull request not found" }, 404);
+    return apiError(c, 404, "Pull request not found");
   }
 
-  const existing = await db.query.prReactions.findFirst({
-    where: and(eq(prReactions.pullRequestId, id), eq(prReactions.userId, user.id), eq(prReactions.emoji, body.emoji)),
-  });
-
-  if (existing) {
-    await db
-      .delete(prReactions)
-      .where(and(eq(prReactions.pullRequestId, id), eq(prReactions.userId, user.id), eq(prReactions.emoji, body.emoji)));
-    return c.json({ added: false });
-  } else {
-    await db.insert(prReactions).values({
-      pullRequestId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
-  }
+  const added = await toggleReaction("pull_request", id, pr.repositoryId, user.id, body.emoji);
+  return c.json({ added });
 });
 
 app.post("/api/pulls/comments/:id/reactions", requireAuth, async (c) => {
@@ -1340,34 +1405,66 @@ app.post("/api/pulls/comments/:id/reactions", requireAuth, async (c) => {
   const body = await c.req.json<{ emoji: string }>();
 
   if (!VALID_EMOJIS.includes(body.emoji)) {
-    return c.json({ error: "Invalid emoji" }, 400);
+    return apiError(c, 400, "Invalid emoji");
   }
 
   const comment = await db.query.prComments.findFirst({
     where: eq(prComments.id, id),
+    with: { pullRequest: { columns: { repositoryId: true } } },
   });
 
   if (!comment) {
-    return c.json({ error: "Comment not found" }, 404);
+    return apiError(c, 404, "Comment not found");
+  }
+
+  const added = await toggleReaction("pr_comment", id, comment.pullRequest.repositoryId, user.id, body.emoji);
+  return c.json({ added });
+});
+
+async function canViewPullRequestRepo(pullRequestId: string, userId?: string) {
+  const [row] = await db
+    .select({ ownerId: repositories.ownerId, visibility: repositories.visibility })
+    .from(pullRequests)
+    .innerJoin(repositories, eq(repositories.id, pullRequests.repositoryId))
+    .where(eq(pullRequests.id, pullRequestId))
+    .limit(1);
+  return !!row && (row.visibility !== "private" || row.ownerId === userId);
+}
+
+app.get("/api/pulls/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
   }
 
-  const existing = await db.query.prReactions.findFirst({
-    where: and(eq(prReactions.commentId, id), eq(prReactions.userId, user.id), eq(prReactions.emoji, body.emoji)),
+  if (!(await canViewPullRequestRepo(id, currentUser?.id))) {
+    return apiError(c, 404, "Pull request not found");
+  }
+
+  return c.json(await listReactionUsers("pull_request", id, query));
+});
+
+app.get("/api/pulls/comments/:id/reactions", async (c) => {
+  const id = c.req.param("id");
+  const currentUser = c.get("user");
+
+  const query = parseReactionListQuery((key) => c.req.query(key));
+  if ("error" in query) {
+    return apiError(c, 400, query.error);
+  }
+
+  const comment = await db.query.prComments.findFirst({
+    where: eq(prComments.id, id),
   });
 
-  if (existing) {
-    await db
-      .delete(prReactions)
-      .where(and(eq(prReactions.commentId, id), eq(prReactions.userId, user.id), eq(prReactions.emoji, body.emoji)));
-    return c.json({ added: false });
-  } else {
-    await db.insert(prReactions).values({
-      commentId: id,
-      userId: user.id,
-      emoji: body.emoji,
-    });
-    return c.json({ added: true });
+  if (!comment || !(await canViewPullRequestRepo(comment.pullRequestId, currentUser?.id))) {
+    return apiError(c, 404, "Comment not found");
   }
+
+  return c.json(await listReactionUsers("pr_comment", id, query));
 });
 
 export default app;
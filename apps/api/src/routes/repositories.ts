@@ -1,14 +1,58 @@
 import { Hono } from "hono";
-import { db, users, repositories, stars, repoBranchMetadata, branchProtectionRules } from "@gitbruv/db";
-import { eq, sql, desc, and } from "drizzle-orm";
+import { randomUUID } from "crypto";
+import {
+  db,
+  users,
+  repositories,
+  stars,
+  repoBranchMetadata,
+  branchProtectionRules,
+  pushPolicies,
+  repoReadTokens,
+  deployKeys,
+  issues,
+  pullRequests,
+  type ExternalReference,
+} from "@gitbruv/db";
+import { eq, sql, desc, asc, and, arrayContains, inArray } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
-import { putObject, deletePrefix, getRepoPrefix, copyPrefix, listObjects } from "../s3";
+import { putObject, deletePrefix, copyPrefix, listObjectsWithSize } from "../s3";
+import { storageLayout } from "../storage-layout";
 import { repoCache } from "../cache";
+import { createGitStore, listBranches } from "../git";
+import { updateFor, findInvalidField } from "../update-builder";
+import { generateReadToken, hashReadToken, normalizeTokenPath, MAX_READ_TOKEN_TTL_DAYS } from "../read-tokens";
+import { generateDeployKey, hashDeployKey } from "../deploy-keys";
+import { validateExternalReferences } from "../autolinks";
+import { validateMirrorUrl, validateMirrorInterval, DEFAULT_MIRROR_INTERVAL_MINUTES, MIN_MIRROR_INTERVAL_MINUTES, MAX_MIRROR_INTERVAL_MINUTES } from "../git/mirror";
+import { createFromTemplate } from "../git/template";
+import {
+  migrateRepoObjects,
+  writeAlternates,
+  isLooseObjectPath,
+  listOwnLooseObjects,
+  releaseObjectNetwork,
+  setPrivateObjectStore,
+  PRIVATE_OBJECTS_PATH,
+} from "../git/shared-objects";
+import { claimMirrorSync, runMirrorSync } from "../jobs/mirror-sync";
+import { trackTask } from "../metrics";
+import { encodeCursor, decodeCursor, afterCursor, type KeysetPart } from "../pagination";
+import { apiError } from "../errors";
+import { recordActivity } from "../activity";
+import { applyDefaultLabels } from "../labels";
+import { recordAudit } from "../audit";
+import { getPushPolicy, findUnsafePattern, MAX_COMMIT_MESSAGE_PATTERN_LENGTH, type PushPolicy } from "../push-policy";
+import { config } from "../config";
+import { validateRepositoryName } from "@gitbruv/lib";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
+const MAX_TOPICS = 20;
+const TOPIC_PATTERN = /^[a-z0-9][a-z0-9-]{0,49}$/;
+
 async function getForkCount(repoId: string): Promise<number> {
   const [countRow] = await db
     .select({ count: sql<number>`COUNT(*)` })
@@ -62,13 +106,61 @@ app.post("/api/repositories", requireAuth, async (c) => {
   const body = await c.req.json<{
     name: string;
     description?: string;
-    visibility: string;
+    visibility?: string;
+    mirrorUrl?: string;
+    mirrorIntervalMinutes?: number;
+    templateId?: string;
+    defaultLabels?: boolean;
   }>();
 
   const normalizedName = body.name.toLowerCase().replace(/ /g, "-");
 
-  if (!/^[a-zA-Z0-9_.-]+$/.test(normalizedName)) {
-    return c.json({ error: "Invalid repository name" }, 400);
+  const nameValidation = validateRepositoryName(normalizedName);
+  if (!nameValidation.valid) {
+    return apiError(c, 400, nameValidation.error ?? "Invalid repository name");
+  }
+
+  if (body.visibility !== undefined && body.visibility !== "public" && body.visibility !== "private") {
+    return apiError(c, 400, "Visibility must be 'public' or 'private'");
+  }
+
+  let visibility = body.visibility;
+  if (visibility === undefined) {
+    const owner = await db.query.users.findFirst({
+      where: eq(users.id, user.id),
+      columns: { defaultRepositoryVisibility: true },
+    });
+    visibility = owner?.defaultRepositoryVisibility ?? "public";
+  }
+
+  let template: typeof repositories.$inferSelect | undefined;
+  if (body.templateId !== undefined) {
+    if (body.mirrorUrl !== undefined) {
+      return apiError(c, 400, "A mirror cannot be created from a template");
+    }
+    if (typeof body.templateId !== "string") {
+      return apiError(c, 400, "Invalid template");
+    }
+    template = await db.query.repositories.findFirst({
+      where: and(eq(repositories.id, body.templateId), eq(repositories.isTemplate, true)),
+    });
+    if (!template || (template.visibility === "private" && template.ownerId !== user.id)) {
+      return apiError(c, 404, "Template repository not found");
+    }
+  }
+
+  let mirror: { url: string; intervalMinutes: number } | null = null;
+  if (body.mirrorUrl !== undefined) {
+    const validated = await validateMirrorUrl(body.mirrorUrl);
+    if ("error" in validated) {
+      return apiError(c, 400, validated.error);
+    }
+    const intervalMinutes =
+      body.mirrorIntervalMinutes === undefined ? DEFAULT_MIRROR_INTERVAL_MINUTES : validateMirrorInterval(body.mirrorIntervalMinutes);
+    if (intervalMinutes === null) {
+      return apiError(c, 400, `Mirror interval must be between ${MIN_MIRROR_INTERVAL_MINUTES} and ${MAX_MIRROR_INTERVAL_MINUTES} minutes`);
+    }
+    mirror = { url: validated.url, intervalMinutes };
   }
 
   const existing = await db.query.repositories.findFirst({
@@ -76,23 +168,68 @@ app.post("/api/repositories", requireAuth, async (c) => {
   });
 
   if (existing) {
-    return c.json({ error: "Repository already exists" }, 400);
+    return apiError(c, 400, "Repository already exists");
   }
 
+  // New public repositories start on the shared object layout as the root of their own fork network.
+  // Private ones keep their objects to themselves until they are made public.
+  const repoId = randomUUID();
+  const objectNetworkId = visibility === "public" ? repoId : null;
   const [repo] = await db
     .insert(repositories)
     .values({
+      id: repoId,
+      objectNetworkId,
       name: normalizedName,
       description: body.description,
-      visibility: body.visibility as "public" | "private",
+      visibility,
       ownerId: user.id,
+      mirrorUrl: mirror?.url,
+      mirrorIntervalMinutes: mirror?.intervalMinutes,
+      mirrorSyncStatus: mirror ? "pending" : undefined,
     })
     .returning();
 
-  const repoPrefix = getRepoPrefix(user.id, normalizedName);
-  await putObject(`${repoPrefix}/HEAD`, "ref: refs/heads/main\n");
-  await putObject(`${repoPrefix}/config`, "[core]\n\trepositoryformatversion = 0\n\tfilemode = true\n\tbare = true\n");
-  await putObject(`${repoPrefix}/description`, "Unnamed repository; edit this file to name the repository.\n");
+  const repoPrefix = storageLayout.repoPrefix(user.id, normalizedName);
+  await putObject(storageLayout.refKey(repoPrefix, "HEAD"), "ref: refs/heads/main\n");
+  await putObject(storageLayout.repoKey(repoPrefix, "config"), "[core]\n\trepositoryformatversion = 0\n\tfilemode = true\n\tbare = true\n");
+  await putObject(storageLayout.repoKey(repoPrefix, "description"), "Unnamed repository; edit this file to name the repository.\n");
+  if (objectNetworkId) {
+    await writeAlternates(repoPrefix, objectNetworkId);
+  }
+
+  if (template) {
+    try {
+      await createFromTemplate(
+        createGitStore(template.ownerId, template.name),
+        template.defaultBranch,
+        createGitStore(user.id, normalizedName),
+        repo.defaultBranch,
+        { name: user.name, email: user.email }
+      );
+    } catch (error) {
+      console.error(`[API] Failed to copy template ${template.name} into ${normalizedName}:`, error);
+      await db.delete(repositories).where(eq(repositories.id, repo.id));
+      await deletePrefix(storageLayout.repoContents(repoPrefix));
+      await releaseObjectNetwork(repo.objectNetworkId);
+      return apiError(c, 500, "Failed to create repository from template");
+    }
+  }
+
+  if (mirror) {
+    const claimed = await claimMirrorSync(repo.id);
+    if (claimed) {
+      trackTask("mirror_sync", () => runMirrorSync(claimed));
+    }
+  }
+
+  if (body.defaultLabels === true) {
+    await applyDefaultLabels(repo.id).catch((error) => {
+      console.error(`[API] Failed to add default labels to ${normalizedName}:`, error);
+    });
+  }
+
+  recordActivity({ type: "repository_created", actorId: user.id, repositoryId: repo.id });
 
   return c.json(repo);
 });
@@ -109,6 +246,7 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
       name: repositories.name,
       description: repositories.description,
       ownerId: repositories.ownerId,
+      objectNetworkId: repositories.objectNetworkId,
       visibility: repositories.visibility,
       defaultBranch: repositories.defaultBranch,
       createdAt: repositories.createdAt,
@@ -125,11 +263,11 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
 
   const source = sourceResult[0];
   if (!source) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (source.visibility === "private" && user.id !== source.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const existingFork = await db.query.repositories.findFirst({
@@ -176,8 +314,9 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
 
   const targetName = ("name" in body && body.name ? body.name : source.name).toLowerCase().replace(/ /g, "-");
 
-  if (!/^[a-zA-Z0-9_.-]+$/.test(targetName)) {
-    return c.json({ error: "Invalid repository name" }, 400);
+  const nameValidation = validateRepositoryName(targetName);
+  if (!nameValidation.valid) {
+    return apiError(c, 400, nameValidation.error ?? "Invalid repository name");
   }
 
   const existingName = await db.query.repositories.findFirst({
@@ -185,12 +324,22 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
   });
 
   if (existingName) {
-    return c.json({ error: "Repository with this name already exists" }, 400);
+    return apiError(c, 400, "Repository with this name already exists");
   }
 
+  // A fork of a public source whose loose objects are all in its network joins that network, so
+  // only refs and metadata are copied. Any other source is copied whole, and the background job
+  // moves the fork onto a network later. Moving the source here would hold the request for as long
+  // as it takes to copy every object.
+  const sourcePrefix = storageLayout.repoPrefix(source.ownerId, source.name);
+  const targetPrefix = storageLayout.repoPrefix(user.id, targetName);
+  const sharesObjects =
+    source.visibility === "public" && !!source.objectNetworkId && (await listOwnLooseObjects(sourcePrefix)).length === 0;
+
   const [forkRepo] = await db
     .insert(repositories)
     .values({
+      objectNetworkId: sharesObjects ? source.objectNetworkId : null,
       name: targetName,
       description: ("description" in body ? body.description : source.description) ?? null,
       visibility: "public",
@@ -199,9 +348,17 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
     })
     .returning();
 
-  const sourcePrefix = getRepoPrefix(source.ownerId, source.name);
-  const targetPrefix = getRepoPrefix(user.id, targetName);
-  await copyPrefix(sourcePrefix, targetPrefix);
+  // Alternates are copied either way: a source part-way through its move has objects on both sides.
+  // Archived packs of large pushes only duplicate loose objects that are also in the network, so a
+  // sharing fork leaves them behind too.
+  await copyPrefix(
+    storageLayout.repoContents(sourcePrefix),
+    storageLayout.repoContents(targetPrefix),
+    (relativeKey) =>
+      !relativeKey.startsWith("pack-cache/") &&
+      relativeKey !== PRIVATE_OBJECTS_PATH &&
+      !(sharesObjects && (isLooseObjectPath(relativeKey) || relativeKey.startsWith("objects/pack/")))
+  );
 
   const sourceMetadata = await db.query.repoBranchMetadata.findMany({
     where: eq(repoBranchMetadata.repoId, source.id),
@@ -229,6 +386,13 @@ app.post("/api/repositories/:owner/:name/fork", requireAuth, async (c) => {
 
   const forkedFrom = await getForkedFromInfo(source.id, user.id);
 
+  recordActivity({
+    type: "repository_forked",
+    actorId: user.id,
+    repositoryId: forkRepo.id,
+    payload: { sourceRepositoryId: source.id, source: `${source.username}/${source.name}` },
+  });
+
   return c.json({
     repo: {
       id: forkRepo.id,
@@ -258,13 +422,45 @@ app.get("/api/repositories/public", async (c) => {
   const sortBy = c.req.query("sortBy") || "updated";
   const limit = parseInt(c.req.query("limit") || "20", 10);
   const offset = parseInt(c.req.query("offset") || "0", 10);
+  const topic = c.req.query("topic")?.trim().toLowerCase();
+  const language = c.req.query("language")?.trim();
+  const cursorParam = c.req.query("cursor");
 
-  const orderBy =
+  const cursor = cursorParam ? decodeCursor(cursorParam, [sortBy === "stars" ? "bigint" : "timestamp", "uuid"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const conditions = [eq(repositories.visibility, "public")];
+  if (topic) {
+    conditions.push(arrayContains(repositories.topics, [topic]));
+  }
+  if (language) {
+    conditions.push(
+      sql`EXISTS (SELECT 1 FROM jsonb_array_elements(${repositories.languages}) AS lang WHERE lower(lang->>'language') = lower(${language}))`
+    );
+  }
+
+  const starCount = sql<number>`(SELECT COUNT(*) FROM stars WHERE repository_id = ${repositories.id})`;
+  // Correlated subqueries keep the listing a single round trip; each one hits an indexed
+  // foreign key and only runs for the page being returned.
+  const openIssueCount = sql<number>`(SELECT COUNT(*) FROM issues WHERE repository_id = ${repositories.id} AND state = 'open')`;
+  const forkCount = sql<number>`(SELECT COUNT(*) FROM repositories AS forks WHERE forks.forked_from_id = ${repositories.id})`;
+  const primaryLanguage = sql<string | null>`(
+    SELECT lang->>'language' FROM jsonb_array_elements(${repositories.languages}) AS lang
+    ORDER BY (lang->>'bytes')::bigint DESC LIMIT 1
+  )`;
+  const lastPushedAt = sql<string | null>`(SELECT MAX(updated_at) FROM repo_branch_metadata WHERE repo_id = ${repositories.id})`;
+  const sortKey: KeysetPart =
     sortBy === "stars"
-      ? desc(sql`star_count`)
+      ? { column: starCount, type: "bigint" }
       : sortBy === "created"
-        ? desc(repositories.createdAt)
-        : desc(repositories.updatedAt);
+        ? { column: repositories.createdAt, type: "timestamp" }
+        : { column: repositories.updatedAt, type: "timestamp" };
+
+  if (cursor) {
+    conditions.push(afterCursor([sortKey, { column: repositories.id, type: "uuid" }], cursor, "desc"));
+  }
 
   const result = await db
     .select({
@@ -274,19 +470,25 @@ app.get("/api/repositories/public", async (c) => {
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
       defaultBranch: repositories.defaultBranch,
+      topics: repositories.topics,
       createdAt: repositories.createdAt,
       updatedAt: repositories.updatedAt,
       username: users.username,
       userName: users.name,
       avatarUrl: users.avatarUrl,
-      starCount: sql<number>`(SELECT COUNT(*) FROM stars WHERE repository_id = ${repositories.id})`.as("star_count"),
+      starCount: starCount.as("star_count"),
+      openIssueCount,
+      forkCount,
+      primaryLanguage,
+      lastPushedAt,
+      sortKey: sql<string>`${sortKey.column}::text`,
     })
     .from(repositories)
     .innerJoin(users, eq(users.id, repositories.ownerId))
-    .where(eq(repositories.visibility, "public"))
-    .orderBy(orderBy)
+    .where(and(...conditions))
+    .orderBy(sortBy === "stars" ? desc(sql`star_count`) : desc(sortKey.column), desc(repositories.id))
     .limit(limit + 1)
-    .offset(offset);
+    .offset(cursor ? 0 : offset);
 
   const hasMore = result.length > limit;
   const repos = result.slice(0, limit).map((row) => ({
@@ -295,6 +497,7 @@ app.get("/api/repositories/public", async (c) => {
     description: row.description,
     visibility: row.visibility,
     defaultBranch: row.defaultBranch,
+    topics: row.topics,
     createdAt: row.createdAt,
     updatedAt: row.updatedAt,
     owner: {
@@ -304,14 +507,24 @@ app.get("/api/repositories/public", async (c) => {
       avatarUrl: row.avatarUrl,
     },
     starCount: Number(row.starCount) || 0,
+    openIssueCount: Number(row.openIssueCount) || 0,
+    forkCount: Number(row.forkCount) || 0,
+    primaryLanguage: row.primaryLanguage,
+    lastPushedAt: row.lastPushedAt ? new Date(row.lastPushedAt) : null,
   }));
 
-  return c.json({ repos, hasMore });
+  const last = result[repos.length - 1];
+  const nextCursor = hasMore && last ? encodeCursor([last.sortKey, last.id]) : null;
+
+  return c.json({ repos, hasMore, nextCursor });
 });
 
 app.get("/api/repositories/user/:username", async (c) => {
   const username = c.req.param("username");
   const currentUser = c.get("user");
+  const sortBy = c.req.query("sortBy") || "updated";
+  const limit = Math.min(parseInt(c.req.query("limit") || "100", 10), 100);
+  const offset = parseInt(c.req.query("offset") || "0", 10);
 
   const userResult = await db.query.users.findFirst({
     where: eq(users.username, username),
@@ -319,39 +532,60 @@ app.get("/api/repositories/user/:username", async (c) => {
   });
 
   if (!userResult) {
-    return c.json({ repos: [] });
+    return c.json({ repos: [], hasMore: false });
   }
 
   const isOwner = currentUser?.id === userResult.id;
+  const starCount = sql<number>`COUNT(${stars.userId})`;
 
-  const reposResult = await db.query.repositories.findMany({
-    where: isOwner
-      ? eq(repositories.ownerId, userResult.id)
-      : and(eq(repositories.ownerId, userResult.id), eq(repositories.visibility, "public")),
-    orderBy: desc(repositories.updatedAt),
-  });
-
-  const reposWithStars = await Promise.all(
-    reposResult.map(async (repo) => {
-      const [starCount] = await db
-        .select({ count: sql<number>`COUNT(*)` })
-        .from(stars)
-        .where(eq(stars.repositoryId, repo.id));
+  const orderBy =
+    sortBy === "stars"
+      ? [desc(starCount), desc(repositories.updatedAt)]
+      : sortBy === "name"
+        ? [asc(repositories.name)]
+        : [desc(repositories.updatedAt)];
 
-      return {
-        ...repo,
-        owner: {
-          id: userResult.id,
-          username: userResult.username,
-          name: userResult.name,
-          avatarUrl: userResult.avatarUrl,
-        },
-        starCount: Number(starCount?.count) || 0,
-      };
+  const reposResult = await db
+    .select({
+      id: repositories.id,
+      name: repositories.name,
+      description: repositories.description,
+      ownerId: repositories.ownerId,
+      forkedFromId: repositories.forkedFromId,
+      visibility: repositories.visibility,
+      defaultBranch: repositories.defaultBranch,
+      topics: repositories.topics,
+      createdAt: repositories.createdAt,
+      updatedAt: repositories.updatedAt,
+      starCount,
+      starred: currentUser ? sql<boolean>`COALESCE(BOOL_OR(${stars.userId} = ${currentUser.id}), false)` : sql<boolean>`false`,
     })
-  );
+    .from(repositories)
+    .leftJoin(stars, eq(stars.repositoryId, repositories.id))
+    .where(
+      isOwner
+        ? eq(repositories.ownerId, userResult.id)
+        : and(eq(repositories.ownerId, userResult.id), eq(repositories.visibility, "public"))
+    )
+    .groupBy(repositories.id)
+    .orderBy(...orderBy)
+    .limit(limit + 1)
+    .offset(offset);
 
-  return c.json({ repos: reposWithStars });
+  const hasMore = reposResult.length > limit;
+  const repos = reposResult.slice(0, limit).map((repo) => ({
+    ...repo,
+    owner: {
+      id: userResult.id,
+      username: userResult.username,
+      name: userResult.name,
+      avatarUrl: userResult.avatarUrl,
+    },
+    starCount: Number(repo.starCount) || 0,
+    starred: Boolean(repo.starred),
+  }));
+
+  return c.json({ repos, hasMore });
 });
 
 app.post("/api/repositories/:id/star", requireAuth, async (c) => {
@@ -370,6 +604,7 @@ app.post("/api/repositories/:id/star", requireAuth, async (c) => {
       userId: user.id,
       repositoryId: id,
     });
+    recordActivity({ type: "starred", actorId: user.id, repositoryId: id });
     return c.json({ starred: true });
   }
 });
@@ -389,6 +624,156 @@ app.get("/api/repositories/:id/is-starred", async (c) => {
   return c.json({ starred: !!existing });
 });
 
+const MAX_STARRED_STATE_IDS = 100;
+const UUID_PATTERN = /^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$/i;
+
+async function getStarCount(repoId: string): Promise<number> {
+  const [result] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(stars)
+    .where(eq(stars.repositoryId, repoId));
+  return Number(result?.count) || 0;
+}
+
+// Listings ask for the signed-in user's stars in one call instead of one is-starred per row.
+// Ids that aren't repositories, or aren't starred, come back false; a signed-out caller has none.
+app.get("/api/repositories/starred", async (c) => {
+  const currentUser = c.get("user");
+  const ids = [...new Set((c.req.query("ids") ?? "").split(",").filter(Boolean))];
+
+  if (ids.length > MAX_STARRED_STATE_IDS) {
+    return apiError(c, 400, `At most ${MAX_STARRED_STATE_IDS} repository ids can be checked at once`);
+  }
+
+  const starred: Record<string, boolean> = Object.fromEntries(ids.map((id) => [id, false]));
+  const validIds = ids.filter((id) => UUID_PATTERN.test(id));
+  if (currentUser && validIds.length > 0) {
+    const rows = await db
+      .select({ repositoryId: stars.repositoryId })
+      .from(stars)
+      .where(and(eq(stars.userId, currentUser.id), inArray(stars.repositoryId, validIds)));
+    for (const row of rows) starred[row.repositoryId] = true;
+  }
+
+  return c.json({ starred });
+});
+
+// Unlike the toggle above, these are idempotent: starring twice leaves one star, and a retried
+// request can't flip the state back.
+app.put("/api/repositories/:owner/:name/star", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const repo = await getRepoByOwnerName(c.req.param("owner"), c.req.param("name"));
+  if (!repo || (repo.visibility === "private" && repo.ownerId !== user.id)) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  const inserted = await db
+    .insert(stars)
+    .values({ userId: user.id, repositoryId: repo.id })
+    .onConflictDoNothing()
+    .returning({ repositoryId: stars.repositoryId });
+  if (inserted.length > 0) {
+    recordActivity({ type: "starred", actorId: user.id, repositoryId: repo.id });
+  }
+
+  return c.json({ starred: true, starCount: await getStarCount(repo.id) });
+});
+
+app.delete("/api/repositories/:owner/:name/star", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const repo = await getRepoByOwnerName(c.req.param("owner"), c.req.param("name"));
+  if (!repo || (repo.visibility === "private" && repo.ownerId !== user.id)) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  await db.delete(stars).where(and(eq(stars.userId, user.id), eq(stars.repositoryId, repo.id)));
+
+  return c.json({ starred: false, starCount: await getStarCount(repo.id) });
+});
+
+// Deploy keys
+
+async function getOwnedRepoById(id: string, userId: string) {
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, id),
+  });
+  if (!repo) return { error: "Repository not found", status: 404 as const };
+  if (repo.ownerId !== userId) return { error: "Unauthorized", status: 403 as const };
+  return { repo };
+}
+
+app.get("/api/repositories/:id/keys", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const result = await getOwnedRepoById(c.req.param("id"), user.id);
+  if (!result.repo) return apiError(c, result.status, result.error);
+
+  const keys = await db
+    .select({
+      id: deployKeys.id,
+      title: deployKeys.title,
+      readOnly: deployKeys.readOnly,
+      lastUsedAt: deployKeys.lastUsedAt,
+      createdAt: deployKeys.createdAt,
+    })
+    .from(deployKeys)
+    .where(eq(deployKeys.repositoryId, result.repo.id))
+    .orderBy(desc(deployKeys.createdAt));
+
+  return c.json({ keys });
+});
+
+app.post("/api/repositories/:id/keys", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const result = await getOwnedRepoById(c.req.param("id"), user.id);
+  if (!result.repo) return apiError(c, result.status, result.error);
+
+  const body = await c.req.json<{ title?: string; readOnly?: boolean }>();
+
+  const title = body.title?.trim();
+  if (!title) {
+    return apiError(c, 400, "Title is required");
+  }
+  if (body.readOnly !== undefined && typeof body.readOnly !== "boolean") {
+    return apiError(c, 400, "readOnly must be a boolean");
+  }
+
+  const key = generateDeployKey();
+
+  const [inserted] = await db
+    .insert(deployKeys)
+    .values({
+      repositoryId: result.repo.id,
+      createdById: user.id,
+      title,
+      keyHash: hashDeployKey(key),
+      readOnly: body.readOnly ?? true,
+    })
+    .returning();
+
+  return c.json({
+    id: inserted.id,
+    title: inserted.title,
+    readOnly: inserted.readOnly,
+    createdAt: inserted.createdAt,
+    key,
+  });
+});
+
+app.delete("/api/repositories/:id/keys/:keyId", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const result = await getOwnedRepoById(c.req.param("id"), user.id);
+  if (!result.repo) return apiError(c, result.status, result.error);
+
+  const deleted = await db
+    .delete(deployKeys)
+    .where(and(eq(deployKeys.id, c.req.param("keyId")), eq(deployKeys.repositoryId, result.repo.id)))
+    .returning({ id: deployKeys.id });
+
+  if (deleted.length === 0) return apiError(c, 404, "Deploy key not found");
+
+  return c.json({ success: true });
+});
+
 app.get("/api/repositories/:owner/:name", async (c) => {
   const owner = c.req.param("owner");
   const name = c.req.param("name");
@@ -402,9 +787,11 @@ app.get("/api/repositories/:owner/:name", async (c) => {
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
       defaultBranch: repositories.defaultBranch,
+      topics: repositories.topics,
       createdAt: repositories.createdAt,
       updatedAt: repositories.updatedAt,
       forkedFromId: repositories.forkedFromId,
+      license: repositories.license,
       username: users.username,
       userName: users.name,
       avatarUrl: users.avatarUrl,
@@ -416,11 +803,11 @@ app.get("/api/repositories/:owner/:name", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (row.visibility === "private" && currentUser?.id !== row.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const [starCount] = await db
@@ -437,6 +824,8 @@ app.get("/api/repositories/:owner/:name", async (c) => {
     description: row.description,
     visibility: row.visibility,
     defaultBranch: row.defaultBranch,
+    topics: row.topics,
+    license: row.license,
     createdAt: row.createdAt,
     updatedAt: row.updatedAt,
     owner: {
@@ -464,6 +853,7 @@ app.get("/api/repositories/:owner/:name/with-stars", async (c) => {
       ownerId: repositories.ownerId,
       visibility: repositories.visibility,
       defaultBranch: repositories.defaultBranch,
+      topics: repositories.topics,
       createdAt: repositories.createdAt,
       updatedAt: repositories.updatedAt,
       forkedFromId: repositories.forkedFromId,
@@ -478,11 +868,11 @@ app.get("/api/repositories/:owner/:name/with-stars", async (c) => {
 
   const row = result[0];
   if (!row) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (row.visibility === "private" && currentUser?.id !== row.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const [starCount] = await db
@@ -507,6 +897,7 @@ app.get("/api/repositories/:owner/:name/with-stars", async (c) => {
     description: row.description,
     visibility: row.visibility,
     defaultBranch: row.defaultBranch,
+    topics: row.topics,
     createdAt: row.createdAt,
     updatedAt: row.updatedAt,
     owner: {
@@ -542,11 +933,11 @@ app.get("/api/repositories/:owner/:name/forks", async (c) => {
 
   const source = sourceResult[0];
   if (!source) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (source.visibility === "private" && currentUser?.id !== source.ownerId) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   const forkRows = await db
@@ -603,35 +994,61 @@ app.get("/api/repositories/:owner/:name/forks", async (c) => {
 app.delete("/api/repositories/:id", requireAuth, async (c) => {
   const user = c.get("user")!;
   const id = c.req.param("id");
+  const body = await c.req.json<{ confirm?: string; dryRun?: boolean }>().catch(() => ({}) as { confirm?: string; dryRun?: boolean });
 
   const repo = await db.query.repositories.findFirst({
     where: eq(repositories.id, id),
   });
 
   if (!repo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (repo.ownerId !== user.id) {
-    return c.json({ error: "Unauthorized" }, 401);
+    return apiError(c, 403, "Only the owner can delete this repository");
   }
 
-  console.log(`[API] Deleting repository ${user.id}/${repo.name}`);
-  const repoPrefix = getRepoPrefix(user.id, repo.name);
+  const fullName = `${user.username}/${repo.name}`;
+  if (body.dryRun !== true && body.confirm !== fullName) {
+    return apiError(c, 400, `Confirm deletion by sending "${fullName}" as confirm`);
+  }
 
-  const keys = await listObjects(repoPrefix);
-  console.log(`[API] Found ${keys.length} objects to delete`);
+  const repoPrefix = storageLayout.repoPrefix(user.id, repo.name);
+
+  const [objects, [issueCount], [pullRequestCount]] = await Promise.all([
+    listObjectsWithSize(storageLayout.repoContents(repoPrefix)),
+    db.select({ count: sql<number>`COUNT(*)` }).from(issues).where(eq(issues.repositoryId, id)),
+    db.select({ count: sql<number>`COUNT(*)` }).from(pullRequests).where(eq(pullRequests.repositoryId, id)),
+  ]);
+  const report = {
+    repository: fullName,
+    objects: objects.length,
+    bytes: objects.reduce((total, object) => total + object.size, 0),
+    issues: Number(issueCount?.count ?? 0),
+    pullRequests: Number(pullRequestCount?.count ?? 0),
+  };
 
-  await deletePrefix(repoPrefix);
-  console.log(`[API] Deleted all objects for repository`);
+  if (body.dryRun === true) {
+    return c.json({ dryRun: true, ...report });
+  }
 
-  await repoCache.invalidateRepo(user.id, repo.name);
-  console.log(`[API] Invalidated Redis cache for repository`);
+  // Written before anything is purged so a deletion that fails halfway is still on record.
+  await recordAudit({
+    actor: user,
+    action: "repository.delete",
+    targetType: "repository",
+    targetId: id,
+    metadata: { ...report, visibility: repo.visibility },
+  });
 
+  console.log(`[API] Deleting repository ${fullName} (${objects.length} objects)`);
+
+  await deletePrefix(storageLayout.repoContents(repoPrefix));
+  await repoCache.invalidateRepo(user.id, repo.name);
   await db.delete(repositories).where(eq(repositories.id, id));
-  console.log(`[API] Deleted repository record`);
+  await releaseObjectNetwork(repo.objectNetworkId);
 
-  return c.json({ success: true });
+  return c.json({ success: true, ...report });
 });
 
 app.patch("/api/repositories/:id", requireAuth, async (c) => {
@@ -641,6 +1058,11 @@ app.patch("/api/repositories/:id", requireAuth, async (c) => {
     name?: string;
     description?: string;
     visibility?: string;
+    reviewReminderDays?: number | null;
+    deleteBranchOnMerge?: boolean;
+    isTemplate?: boolean;
+    externalReferences?: unknown;
+    mirrorIntervalMinutes?: number;
   }>();
 
   const repo = await db.query.repositories.findFirst({
@@ -648,18 +1070,35 @@ app.patch("/api/repositories/:id", requireAuth, async (c) => {
   });
 
   if (!repo) {
-    return c.json({ error: "Repository not found" }, 404);
+    return apiError(c, 404, "Repository not found");
   }
 
   if (repo.ownerId !== user.id) {
-    return c.json({ error: "Unauthorized" }, 401);
+    return apiError(c, 401, "Unauthorized");
+  }
+
+  const invalidField = findInvalidField(body, {
+    name: "string",
+    description: "nullable-string",
+    visibility: "string",
+    reviewReminderDays: "nullable-number",
+    deleteBranchOnMerge: "boolean",
+    isTemplate: "boolean",
+  });
+  if (invalidField) {
+    return apiError(c, 400, `Invalid value for ${invalidField}`);
+  }
+
+  if (body.visibility !== undefined && body.visibility !== "public" && body.visibility !== "private") {
+    return apiError(c, 400, "Visibility must be 'public' or 'private'");
   }
 
   const newName = body.name ? body.name.toLowerCase().replace(/ /g, "-") : repo.name;
 
   if (body.name) {
-    if (!/^[a-zA-Z0-9_.-]+$/.test(newName)) {
-      return c.json({ error: "Invalid repository name" }, 400);
+    const nameValidation = validateRepositoryName(newName);
+    if (!nameValidation.valid) {
+      return apiError(c, 400, nameValidation.error ?? "Invalid repository name");
     }
 
     if (newName !== repo.name) {
@@ -668,25 +1107,184 @@ app.patch("/api/repositories/:id", requireAuth, async (c) => {
       });
 
       if (existing) {
-        return c.json({ error: "Repository with this name already exists" }, 400);
+        return apiError(c, 400, "Repository with this name already exists");
       }
     }
   }
 
-  const [updated] = await db
-    .update(repositories)
-    .set({
-      name: newName,
-      description: body.description ?? repo.description,
-      visibility: (body.visibility as "public" | "private") ?? repo.visibility,
-      updatedAt: new Date(),
-    })
-    .where(eq(repositories.id, id))
-    .returning();
+  if (body.mirrorIntervalMinutes !== undefined) {
+    if (!repo.mirrorUrl) {
+      return apiError(c, 400, "Repository is not a mirror");
+    }
+    if (validateMirrorInterval(body.mirrorIntervalMinutes) === null) {
+      return apiError(c, 400, `Mirror interval must be between ${MIN_MIRROR_INTERVAL_MINUTES} and ${MAX_MIRROR_INTERVAL_MINUTES} minutes`);
+    }
+  }
+
+  if (body.reviewReminderDays != null) {
+    if (!Number.isInteger(body.reviewReminderDays) || body.reviewReminderDays < 1 || body.reviewReminderDays > 90) {
+      return apiError(c, 400, "Review reminder days must be between 1 and 90");
+    }
+  }
+
+  let externalReferences: ExternalReference[] | null | undefined;
+  if (body.externalReferences === null) {
+    externalReferences = null;
+  } else if (body.externalReferences !== undefined) {
+    const validated = validateExternalReferences(body.externalReferences);
+    if ("error" in validated) {
+      return apiError(c, 400, validated.error);
+    }
+    externalReferences = validated.value;
+  }
+
+  const updates = updateFor(repositories)
+    .set("name", newName)
+    .set("description", body.description)
+    .set("visibility", body.visibility as "public" | "private" | undefined)
+    .set("reviewReminderDays", body.reviewReminderDays)
+    .set("deleteBranchOnMerge", body.deleteBranchOnMerge)
+    .set("isTemplate", body.isTemplate)
+    .set("externalReferences", externalReferences)
+    .set("mirrorIntervalMinutes", body.mirrorIntervalMinutes)
+    .set("updatedAt", new Date());
+
+  // The marker goes in before the repository is private and comes out after it is public, so no push
+  // in between writes a private object into the network.
+  const repoPrefix = storageLayout.repoPrefix(repo.ownerId, repo.name);
+  if (body.visibility === "private" && repo.visibility !== "private") {
+    await setPrivateObjectStore(repoPrefix, true);
+  }
+
+  const [updated] = await db.update(repositories).set(updates.build()).where(eq(repositories.id, id)).returning();
+
+  // Objects pushed while the repository was private move into its network in the background; until
+  // then forks of it are copied whole.
+  if (updated.visibility === "public" && repo.visibility === "private") {
+    const updatedPrefix = storageLayout.repoPrefix(updated.ownerId, updated.name);
+    await setPrivateObjectStore(updatedPrefix, false);
+    if (updated.objectNetworkId) {
+      trackTask("shared_object_migration", () => migrateRepoObjects(updated)).catch((error) => {
+        console.error(`[API] Failed to move objects of ${updated.id} into its network:`, error);
+      });
+    }
+  }
+
+  // Cached trees, files and overviews were filled under the old visibility (or name); drop them
+  // so nothing from before the change can be served from Redis afterwards.
+  if (updated.visibility !== repo.visibility || updated.name !== repo.name) {
+    await repoCache.invalidateRepo(repo.ownerId, repo.name);
+  }
 
   return c.json(updated);
 });
 
+app.patch("/api/repositories/:id/topics", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const id = c.req.param("id");
+  const body = await c.req.json<{ topics?: unknown }>();
+
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, id),
+  });
+
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 401, "Unauthorized");
+  }
+
+  if (!Array.isArray(body.topics) || body.topics.some((topic) => typeof topic !== "string")) {
+    return apiError(c, 400, "Topics must be an array of strings");
+  }
+
+  const topics = [...new Set((body.topics as string[]).map((topic) => topic.trim().toLowerCase()).filter(Boolean))];
+
+  if (topics.length > MAX_TOPICS) {
+    return apiError(c, 400, `A repository can have at most ${MAX_TOPICS} topics`);
+  }
+
+  const invalid = topics.find((topic) => !TOPIC_PATTERN.test(topic));
+  if (invalid) {
+    return apiError(c, 400, `Invalid topic "${invalid}": use lowercase letters, numbers and hyphens, up to 50 characters`);
+  }
+
+  await db.update(repositories).set({ topics, updatedAt: new Date() }).where(eq(repositories.id, id));
+
+  return c.json({ topics });
+});
+
+app.patch("/api/repositories/:id/default-branch", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const id = c.req.param("id");
+  const body = await c.req.json<{ branch?: unknown }>();
+
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, id),
+  });
+
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 401, "Unauthorized");
+  }
+
+  if (repo.mirrorUrl) {
+    return apiError(c, 400, "The default branch of a mirror follows its upstream");
+  }
+
+  if (typeof body.branch !== "string" || !body.branch.trim()) {
+    return apiError(c, 400, "Branch is required");
+  }
+
+  const branch = body.branch.trim().replace(/^refs\/heads\//, "");
+  const store = createGitStore(repo.ownerId, repo.name);
+  const branches = await listBranches(store.fs, store.dir);
+  if (!branches.includes(branch)) {
+    return apiError(c, 404, "Branch not found");
+  }
+
+  await putObject(storageLayout.refKey(storageLayout.repoPrefix(repo.ownerId, repo.name), "HEAD"), `ref: refs/heads/${branch}\n`);
+  await db.update(repositories).set({ defaultBranch: branch, updatedAt: new Date() }).where(eq(repositories.id, id));
+  await repoCache.invalidateRepo(repo.ownerId, repo.name);
+
+  return c.json({ defaultBranch: branch });
+});
+
+app.post("/api/repositories/:id/mirror/sync", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const id = c.req.param("id");
+
+  const repo = await db.query.repositories.findFirst({
+    where: eq(repositories.id, id),
+  });
+
+  if (!repo) {
+    return apiError(c, 404, "Repository not found");
+  }
+
+  if (repo.ownerId !== user.id) {
+    return apiError(c, 401, "Unauthorized");
+  }
+
+  if (!repo.mirrorUrl) {
+    return apiError(c, 400, "Repository is not a mirror");
+  }
+
+  const claimed = await claimMirrorSync(repo.id);
+  if (!claimed) {
+    return apiError(c, 409, "A sync is already in progress");
+  }
+
+  trackTask("mirror_sync", () => runMirrorSync(claimed));
+
+  return c.json({ status: "syncing" });
+});
+
 // Branch protection rules
 
 async function getRepoByOwnerName(owner: string, name: string) {
@@ -694,6 +1292,7 @@ async function getRepoByOwnerName(owner: string, name: string) {
     .select({
       id: repositories.id,
       ownerId: repositories.ownerId,
+      visibility: repositories.visibility,
     })
     .from(repositories)
     .innerJoin(users, eq(users.id, repositories.ownerId))
@@ -708,8 +1307,8 @@ app.get("/api/repositories/:owner/:name/branch-protection", requireAuth, async (
   const name = c.req.param("name");
 
   const repo = await getRepoByOwnerName(owner, name);
-  if (!repo) return c.json({ error: "Repository not found" }, 404);
-  if (repo.ownerId !== user.id) return c.json({ error: "Unauthorized" }, 403);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
 
   const rules = await db
     .select()
@@ -725,8 +1324,8 @@ app.post("/api/repositories/:owner/:name/branch-protection", requireAuth, async
   const name = c.req.param("name");
 
   const repo = await getRepoByOwnerName(owner, name);
-  if (!repo) return c.json({ error: "Repository not found" }, 404);
-  if (repo.ownerId !== user.id) return c.json({ error: "Unauthorized" }, 403);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
 
   const body = await c.req.json<{
     branchName: string;
@@ -743,19 +1342,19 @@ app.post("/api/repositories/:owner/:name/branch-protection", requireAuth, async
       : "";
 
   if (!normalizedBranchName) {
-    return c.json({ error: "Branch name is required" }, 400);
+    return apiError(c, 400, "Branch name is required");
   }
 
   const booleanFlags = ["preventDirectPush", "preventForcePush", "preventDeletion", "requireReviews"] as const;
   for (const flag of booleanFlags) {
     if (body[flag] !== undefined && typeof body[flag] !== "boolean") {
-      return c.json({ error: `${flag} must be a boolean` }, 400);
+      return apiError(c, 400, `${flag} must be a boolean`);
     }
   }
 
   if (body.requiredReviewCount !== undefined) {
     if (!Number.isFinite(body.requiredReviewCount) || !Number.isInteger(body.requiredReviewCount) || body.requiredReviewCount < 1 || body.requiredReviewCount > 10) {
-      return c.json({ error: "Required review count must be an integer between 1 and 10" }, 400);
+      return apiError(c, 400, "Required review count must be an integer between 1 and 10");
     }
   }
 
@@ -776,7 +1375,7 @@ app.post("/api/repositories/:owner/:name/branch-protection", requireAuth, async
     return c.json(rule);
   } catch (err: any) {
     if (err?.code === "23505" || err?.constraint?.includes("branch_protection_rules_repo_branch_unique")) {
-      return c.json({ error: "A protection rule for this branch already exists" }, 400);
+      return apiError(c, 400, "A protection rule for this branch already exists");
     }
     throw err;
   }
@@ -789,8 +1388,8 @@ app.patch("/api/repositories/:owner/:name/branch-protection/:ruleId", requireAut
   const ruleId = c.req.param("ruleId");
 
   const repo = await getRepoByOwnerName(owner, name);
-  if (!repo) return c.json({ error: "Repository not found" }, 404);
-  if (repo.ownerId !== user.id) return c.json({ error: "Unauthorized" }, 403);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
 
   const rule = await db.query.branchProtectionRules.findFirst({
     where: and(
@@ -799,7 +1398,7 @@ app.patch("/api/repositories/:owner/:name/branch-protection/:ruleId", requireAut
     ),
   });
 
-  if (!rule) return c.json({ error: "Protection rule not found" }, 404);
+  if (!rule) return apiError(c, 404, "Protection rule not found");
 
   const body = await c.req.json<{
     preventDirectPush?: boolean;
@@ -812,13 +1411,13 @@ app.patch("/api/repositories/:owner/:name/branch-protection/:ruleId", requireAut
   const booleanFlags = ["preventDirectPush", "preventForcePush", "preventDeletion", "requireReviews"] as const;
   for (const flag of booleanFlags) {
     if (body[flag] !== undefined && typeof body[flag] !== "boolean") {
-      return c.json({ error: `${flag} must be a boolean` }, 400);
+      return apiError(c, 400, `${flag} must be a boolean`);
     }
   }
 
   if (body.requiredReviewCount !== undefined) {
     if (!Number.isFinite(body.requiredReviewCount) || !Number.isInteger(body.requiredReviewCount) || body.requiredReviewCount < 1 || body.requiredReviewCount > 10) {
-      return c.json({ error: "Required review count must be an integer between 1 and 10" }, 400);
+      return apiError(c, 400, "Required review count must be an integer between 1 and 10");
     }
   }
 
@@ -845,8 +1444,8 @@ app.delete("/api/repositories/:owner/:name/branch-protection/:ruleId", requireAu
   const ruleId = c.req.param("ruleId");
 
   const repo = await getRepoByOwnerName(owner, name);
-  if (!repo) return c.json({ error: "Repository not found" }, 404);
-  if (repo.ownerId !== user.id) return c.json({ error: "Unauthorized" }, 403);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
 
   const rule = await db.query.branchProtectionRules.findFirst({
     where: and(
@@ -855,11 +1454,211 @@ app.delete("/api/repositories/:owner/:name/branch-protection/:ruleId", requireAu
     ),
   });
 
-  if (!rule) return c.json({ error: "Protection rule not found" }, 404);
+  if (!rule) return apiError(c, 404, "Protection rule not found");
 
   await db.delete(branchProtectionRules).where(eq(branchProtectionRules.id, ruleId));
 
   return c.json({ success: true });
 });
 
+// Push policy
+
+const MAX_FORBIDDEN_PATHS = 50;
+
+function serializePushPolicy(policy: PushPolicy | null) {
+  return {
+    commitMessagePattern: policy?.commitMessagePattern ?? null,
+    requirePusherEmail: policy?.requirePusherEmail ?? false,
+    maxFileSizeMb: policy?.maxFileSizeMb ?? null,
+    forbiddenPaths: policy?.forbiddenPaths ?? [],
+  };
+}
+
+app.get("/api/repositories/:owner/:name/push-policy", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const repo = await getRepoByOwnerName(owner, name);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
+
+  return c.json(serializePushPolicy(await getPushPolicy(repo.id)));
+});
+
+app.put("/api/repositories/:owner/:name/push-policy", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const repo = await getRepoByOwnerName(owner, name);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
+
+  const body = await c.req.json<{
+    commitMessagePattern?: string | null;
+    requirePusherEmail?: boolean;
+    maxFileSizeMb?: number | null;
+    forbiddenPaths?: string[];
+  }>();
+
+  const invalidField = findInvalidField(body, {
+    commitMessagePattern: "nullable-string",
+    requirePusherEmail: "boolean",
+    maxFileSizeMb: "nullable-number",
+  });
+  if (invalidField) {
+    return apiError(c, 400, `Invalid ${invalidField}`);
+  }
+
+  // Patterns end up in "ng" pkt-lines, which can't span lines, and run against every pushed
+  // commit, so keep them short and single-line.
+  let commitMessagePattern = body.commitMessagePattern;
+  if (typeof commitMessagePattern === "string") {
+    commitMessagePattern = commitMessagePattern.trim() || null;
+  }
+  if (commitMessagePattern) {
+    if (commitMessagePattern.length > MAX_COMMIT_MESSAGE_PATTERN_LENGTH || /[\r\n]/.test(commitMessagePattern)) {
+      return apiError(c, 400, `Commit message pattern must be a single line of at most ${MAX_COMMIT_MESSAGE_PATTERN_LENGTH} characters`);
+    }
+    try {
+      new RegExp(commitMessagePattern);
+    } catch {
+      return apiError(c, 400, "Commit message pattern is not a valid regular expression");
+    }
+    const unsafe = findUnsafePattern(commitMessagePattern);
+    if (unsafe) {
+      return apiError(c, 400, `Commit message pattern is too expensive to match: ${unsafe}`);
+    }
+  }
+
+  if (body.maxFileSizeMb != null) {
+    const maxMb = Math.floor(config.git.maxBlobBytes / (1024 * 1024));
+    if (!Number.isInteger(body.maxFileSizeMb) || body.maxFileSizeMb < 1 || body.maxFileSizeMb > maxMb) {
+      return apiError(c, 400, `Maximum file size must be between 1 and ${maxMb} MB`);
+    }
+  }
+
+  let forbiddenPaths: string[] | undefined;
+  if (body.forbiddenPaths !== undefined) {
+    if (!Array.isArray(body.forbiddenPaths) || body.forbiddenPaths.some((path) => typeof path !== "string")) {
+      return apiError(c, 400, "Forbidden paths must be an array of strings");
+    }
+    forbiddenPaths = [...new Set(body.forbiddenPaths.map((path) => path.trim()).filter(Boolean))];
+    if (forbiddenPaths.length > MAX_FORBIDDEN_PATHS) {
+      return apiError(c, 400, `At most ${MAX_FORBIDDEN_PATHS} forbidden paths are allowed`);
+    }
+    if (forbiddenPaths.some((path) => /\s/.test(path))) {
+      return apiError(c, 400, "Forbidden paths cannot contain whitespace");
+    }
+  }
+
+  const updates = updateFor(pushPolicies)
+    .set("commitMessagePattern", commitMessagePattern)
+    .set("requirePusherEmail", body.requirePusherEmail)
+    .set("maxFileSizeMb", body.maxFileSizeMb)
+    .set("forbiddenPaths", forbiddenPaths)
+    .set("updatedAt", new Date());
+
+  const [policy] = await db
+    .insert(pushPolicies)
+    .values({ ...updates.build(), repositoryId: repo.id })
+    .onConflictDoUpdate({ target: pushPolicies.repositoryId, set: updates.build() })
+    .returning();
+
+  return c.json(serializePushPolicy(policy));
+});
+
+// Read tokens
+
+app.get("/api/repositories/:owner/:name/read-tokens", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const repo = await getRepoByOwnerName(owner, name);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
+
+  const tokens = await db
+    .select({
+      id: repoReadTokens.id,
+      name: repoReadTokens.name,
+      path: repoReadTokens.path,
+      expiresAt: repoReadTokens.expiresAt,
+      lastUsedAt: repoReadTokens.lastUsedAt,
+      createdAt: repoReadTokens.createdAt,
+    })
+    .from(repoReadTokens)
+    .where(eq(repoReadTokens.repositoryId, repo.id))
+    .orderBy(desc(repoReadTokens.createdAt));
+
+  return c.json({ tokens });
+});
+
+app.post("/api/repositories/:owner/:name/read-tokens", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+
+  const repo = await getRepoByOwnerName(owner, name);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
+
+  const body = await c.req.json<{ name?: string; path?: string; expiresInDays?: number }>();
+
+  const expiresInDays = body.expiresInDays ?? 7;
+  if (!Number.isInteger(expiresInDays) || expiresInDays < 1 || expiresInDays > MAX_READ_TOKEN_TTL_DAYS) {
+    return apiError(c, 400, `Expiration must be between 1 and ${MAX_READ_TOKEN_TTL_DAYS} days`);
+  }
+
+  const path = normalizeTokenPath(body.path || "");
+  if (path.split("/").some((segment) => segment === "..")) {
+    return apiError(c, 400, "Invalid path");
+  }
+
+  const token = generateReadToken();
+
+  const [inserted] = await db
+    .insert(repoReadTokens)
+    .values({
+      repositoryId: repo.id,
+      createdById: user.id,
+      name: body.name?.trim() || null,
+      tokenHash: hashReadToken(token),
+      path,
+      expiresAt: new Date(Date.now() + expiresInDays * 24 * 60 * 60 * 1000),
+    })
+    .returning();
+
+  return c.json({
+    id: inserted.id,
+    name: inserted.name,
+    path: inserted.path,
+    expiresAt: inserted.expiresAt,
+    createdAt: inserted.createdAt,
+    token,
+  });
+});
+
+app.delete("/api/repositories/:owner/:name/read-tokens/:tokenId", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const owner = c.req.param("owner");
+  const name = c.req.param("name");
+  const tokenId = c.req.param("tokenId");
+
+  const repo = await getRepoByOwnerName(owner, name);
+  if (!repo) return apiError(c, 404, "Repository not found");
+  if (repo.ownerId !== user.id) return apiError(c, 403, "Unauthorized");
+
+  const deleted = await db
+    .delete(repoReadTokens)
+    .where(and(eq(repoReadTokens.id, tokenId), eq(repoReadTokens.repositoryId, repo.id)))
+    .returning({ id: repoReadTokens.id });
+
+  if (deleted.length === 0) return apiError(c, 404, "Token not found");
+
+  return c.json({ success: true });
+});
+
 export default app;
@@ -1,8 +1,19 @@
 import { Hono } from "hono";
-import { db, users, repositories, accounts } from "@gitbruv/db";
-import { eq, ne, and } from "drizzle-orm";
+import { db, users, repositories, accounts, verifications, accountExports } from "@gitbruv/db";
+import { eq, ne, and, gt, like, desc, inArray } from "drizzle-orm";
+import { createHash, randomBytes } from "crypto";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
-import { putObject, deleteObject, deletePrefix, getRepoPrefix } from "../s3";
+import { putObject, deleteObject, deletePrefix, getObjectStream } from "../s3";
+import { storageLayout } from "../storage-layout";
+import { updateFor, findInvalidField } from "../update-builder";
+import { sendEmailChangeEmail } from "../email";
+import { runAccountExport, createExportDownloadUrl, verifyExportDownload } from "../jobs/account-export";
+import { trackTask } from "../metrics";
+import { apiError } from "../errors";
+import { isolateStorageFailures } from "../tracing";
+import { releaseObjectNetwork } from "../git/shared-objects";
+import { recordUsernameChange } from "../username-redirects";
+import { validateUsername } from "@gitbruv/lib";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
@@ -23,7 +34,7 @@ app.get("/api/settings", requireAuth, async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   return c.json({
@@ -48,14 +59,27 @@ app.patch("/api/settings/profile", requireAuth, async (c) => {
     defaultRepositoryVisibility?: string;
   }>();
 
+  const invalidField = findInvalidField(body, {
+    name: "string",
+    username: "string",
+    bio: "nullable-string",
+    location: "nullable-string",
+    website: "nullable-string",
+    pronouns: "nullable-string",
+    company: "nullable-string",
+    gitEmail: "nullable-string",
+    defaultRepositoryVisibility: "string",
+  });
+  if (invalidField) {
+    return apiError(c, 400, `Invalid value for ${invalidField}`);
+  }
+
   const normalizedUsername = body.username?.toLowerCase().replace(/ /g, "-");
 
   if (normalizedUsername) {
-    if (!/^[a-zA-Z0-9_-]+$/.test(normalizedUsername)) {
-      return c.json({ error: "Username can only contain letters, numbers, underscores, and hyphens" }, 400);
-    }
-    if (normalizedUsername.length < 3) {
-      return c.json({ error: "Username must be at least 3 characters" }, 400);
+    const validation = validateUsername(normalizedUsername);
+    if (!validation.valid) {
+      return apiError(c, 400, validation.error ?? "Invalid username");
     }
 
     const existing = await db.query.users.findFirst({
@@ -63,12 +87,12 @@ app.patch("/api/settings/profile", requireAuth, async (c) => {
     });
 
     if (existing) {
-      return c.json({ error: "Username is already taken" }, 400);
+      return apiError(c, 400, "Username is already taken");
     }
   }
 
   if (body.defaultRepositoryVisibility && body.defaultRepositoryVisibility !== "public" && body.defaultRepositoryVisibility !== "private") {
-    return c.json({ error: "defaultRepositoryVisibility must be 'public' or 'private'" }, 400);
+    return apiError(c, 400, "defaultRepositoryVisibility must be 'public' or 'private'");
   }
 
   const currentUser = await db.query.users.findFirst({
@@ -77,21 +101,22 @@ app.patch("/api/settings/profile", requireAuth, async (c) => {
 
   const finalUsername = normalizedUsername || currentUser?.username;
 
-  await db
-    .update(users)
-    .set({
-      name: body.name ?? currentUser?.name,
-      username: finalUsername,
-      bio: body.bio ?? currentUser?.bio,
-      location: body.location ?? currentUser?.location,
-      website: body.website ?? currentUser?.website,
-      pronouns: body.pronouns ?? currentUser?.pronouns,
-      company: body.company ?? currentUser?.company,
-      gitEmail: body.gitEmail ?? currentUser?.gitEmail,
-      defaultRepositoryVisibility: (body.defaultRepositoryVisibility as "public" | "private") ?? currentUser?.defaultRepositoryVisibility,
-      updatedAt: new Date(),
-    })
-    .where(eq(users.id, user.id));
+  const updates = updateFor(users)
+    .set("name", body.name)
+    .set("username", normalizedUsername || undefined)
+    .set("bio", body.bio)
+    .set("location", body.location)
+    .set("website", body.website)
+    .set("pronouns", body.pronouns)
+    .set("company", body.company)
+    .set("gitEmail", body.gitEmail)
+    .set("defaultRepositoryVisibility", body.defaultRepositoryVisibility as "public" | "private" | undefined)
+    .set("updatedAt", new Date());
+
+  await db.update(users).set(updates.build()).where(eq(users.id, user.id));
+  if (normalizedUsername && currentUser && normalizedUsername !== currentUser.username) {
+    await recordUsernameChange(user.id, currentUser.username, normalizedUsername);
+  }
 
   return c.json({ success: true, username: finalUsername });
 });
@@ -163,28 +188,94 @@ app.patch("/api/settings/word-wrap", requireAuth, async (c) => {
   return c.json({ success: true, wordWrap: body.wordWrap });
 });
 
+const EMAIL_CHANGE_PREFIX = "email-change:";
+const EMAIL_CHANGE_TTL_MS = 24 * 60 * 60 * 1000;
+
+function hashEmailChangeToken(token: string): string {
+  return createHash("sha256").update(token).digest("hex");
+}
+
+// The new address is only written once its owner follows the emailed link; until then the pending
+// change lives in verifications and the current email keeps working.
 app.patch("/api/settings/email", requireAuth, async (c) => {
   const user = c.get("user")!;
   const body = await c.req.json<{ email: string }>();
 
+  const email = typeof body.email === "string" ? body.email.trim().toLowerCase() : "";
+  if (!/^[^\s@]+@[^\s@]+\.[^\s@]+$/.test(email)) {
+    return apiError(c, 400, "Invalid email address");
+  }
+
+  if (email === user.email.toLowerCase()) {
+    return apiError(c, 400, "This is already your email address");
+  }
+
+  const existing = await db.query.users.findFirst({
+    where: and(eq(users.email, email), ne(users.id, user.id)),
+  });
+
+  if (existing) {
+    return apiError(c, 400, "Email already in use");
+  }
+
+  const token = randomBytes(32).toString("hex");
+  const value = JSON.stringify({ userId: user.id, email });
+
+  await db
+    .delete(verifications)
+    .where(and(like(verifications.identifier, `${EMAIL_CHANGE_PREFIX}%`), like(verifications.value, `{"userId":"${user.id}",%`)));
+  await db.insert(verifications).values({
+    id: crypto.randomUUID(),
+    identifier: EMAIL_CHANGE_PREFIX + hashEmailChangeToken(token),
+    value,
+    expiresAt: new Date(Date.now() + EMAIL_CHANGE_TTL_MS),
+  });
+
+  const sent = await sendEmailChangeEmail(email, token, user.username);
+  if (!sent) {
+    return apiError(c, 502, "Failed to send confirmation email");
+  }
+
+  return c.json({ success: true, pendingEmail: email });
+});
+
+app.post("/api/settings/email/verify", async (c) => {
+  const body = await c.req.json<{ token?: string }>();
+  if (typeof body.token !== "string" || !body.token) {
+    return apiError(c, 400, "Token is required");
+  }
+
+  const identifier = EMAIL_CHANGE_PREFIX + hashEmailChangeToken(body.token);
+  const pending = await db.query.verifications.findFirst({
+    where: and(eq(verifications.identifier, identifier), gt(verifications.expiresAt, new Date())),
+  });
+
+  if (!pending) {
+    return apiError(c, 400, "Invalid or expired confirmation link");
+  }
+
+  const { userId, email } = JSON.parse(pending.value) as { userId: string; email: string };
+
   const existing = await db.query.users.findFirst({
-    where: and(eq(users.email, body.email), ne(users.id, user.id)),
+    where: and(eq(users.email, email), ne(users.id, userId)),
   });
 
+  await db.delete(verifications).where(eq(verifications.id, pending.id));
+
   if (existing) {
-    return c.json({ error: "Email already in use" }, 400);
+    return apiError(c, 400, "Email already in use");
   }
 
-  const [updated] = await db
+  await db
     .update(users)
     .set({
-      email: body.email,
+      email,
+      emailVerified: true,
       updatedAt: new Date(),
     })
-    .where(eq(users.id, user.id))
-    .returning();
+    .where(eq(users.id, userId));
 
-  return c.json(updated);
+  return c.json({ success: true, email });
 });
 
 app.post("/api/settings/avatar", requireAuth, async (c) => {
@@ -193,17 +284,17 @@ app.post("/api/settings/avatar", requireAuth, async (c) => {
   const file = formData.get("avatar") as File | null;
 
   if (!file) {
-    return c.json({ error: "No avatar file provided" }, 400);
+    return apiError(c, 400, "No avatar file provided");
   }
 
   const contentType = file.type;
   if (!contentType.startsWith("image/")) {
-    return c.json({ error: "File must be an image" }, 400);
+    return apiError(c, 400, "File must be an image");
   }
 
   const data = await file.arrayBuffer();
   if (data.byteLength > 5 * 1024 * 1024) {
-    return c.json({ error: "File size must be less than 5MB" }, 400);
+    return apiError(c, 400, "File size must be less than 5MB");
   }
 
   const currentUser = await db.query.users.findFirst({
@@ -214,7 +305,7 @@ app.post("/api/settings/avatar", requireAuth, async (c) => {
     const withoutQuery = currentUser.avatarUrl.split("?")[0];
     const filename = withoutQuery.replace("/api/avatar/", "");
     if (filename) {
-      const oldKey = `avatars/${filename}`;
+      const oldKey = storageLayout.avatarKeyForFilename(filename);
       try {
         await deleteObject(oldKey);
       } catch {}
@@ -222,7 +313,7 @@ app.post("/api/settings/avatar", requireAuth, async (c) => {
   }
 
   const ext = file.name.split(".").pop() || "png";
-  const key = `avatars/${user.id}.${ext}`;
+  const key = storageLayout.avatarKey(user.id, ext);
 
   await putObject(key, Buffer.from(data), contentType);
 
@@ -251,7 +342,7 @@ app.delete("/api/settings/avatar", requireAuth, async (c) => {
     const withoutQuery = currentUser.avatarUrl.split("?")[0];
     const filename = withoutQuery.replace("/api/avatar/", "");
     if (filename) {
-      const oldKey = `avatars/${filename}`;
+      const oldKey = storageLayout.avatarKeyForFilename(filename);
       try {
         await deleteObject(oldKey);
       } catch {}
@@ -321,12 +412,12 @@ app.patch("/api/settings/password", requireAuth, async (c) => {
   });
 
   if (!account || !account.password) {
-    return c.json({ error: "No password set for this account" }, 400);
+    return apiError(c, 400, "No password set for this account");
   }
 
   const valid = await Bun.password.verify(body.currentPassword, account.password);
   if (!valid) {
-    return c.json({ error: "Current password is incorrect" }, 400);
+    return apiError(c, 400, "Current password is incorrect");
   }
 
   const newHash = await Bun.password.hash(body.newPassword, { algorithm: "bcrypt", cost: 12 });
@@ -347,26 +438,123 @@ app.delete("/api/settings/account", requireAuth, async (c) => {
 
   const repos = await db.query.repositories.findMany({
     where: eq(repositories.ownerId, user.id),
-    columns: { name: true },
+    columns: { name: true, objectNetworkId: true },
   });
 
-  for (const repo of repos) {
-    const repoPrefix = getRepoPrefix(user.id, repo.name);
+  // Storage cleanup is best-effort: the account is deleted even if some files are left behind.
+  await isolateStorageFailures(async () => {
+    for (const repo of repos) {
+      const repoPrefix = storageLayout.repoPrefix(user.id, repo.name);
+      try {
+        await deletePrefix(storageLayout.repoContents(repoPrefix));
+      } catch {}
+    }
+
+    const avatarPrefix = storageLayout.userAvatarsPrefix(user.id);
     try {
-      await deletePrefix(repoPrefix);
+      await deletePrefix(avatarPrefix);
     } catch {}
-  }
 
-  const avatarPrefix = `avatars/${user.id}`;
-  try {
-    await deletePrefix(avatarPrefix);
-  } catch {}
+    try {
+      await deletePrefix(storageLayout.userExportsPrefix(user.id));
+    } catch {}
+  });
 
   await db.delete(users).where(eq(users.id, user.id));
 
+  await isolateStorageFailures(async () => {
+    for (const networkId of new Set(repos.map((repo) => repo.objectNetworkId))) {
+      try {
+        await releaseObjectNetwork(networkId);
+      } catch {}
+    }
+  });
+
   return c.json({ success: true });
 });
 
+function serializeExport(row: typeof accountExports.$inferSelect) {
+  const downloadable = row.status === "completed" && row.expiresAt && row.expiresAt.getTime() > Date.now();
+  return {
+    id: row.id,
+    status: row.status,
+    includeRepositories: row.includeRepositories,
+    sizeBytes: row.sizeBytes,
+    error: row.error,
+    createdAt: row.createdAt,
+    completedAt: row.completedAt,
+    expiresAt: row.expiresAt,
+    downloadUrl: downloadable ? createExportDownloadUrl(row.id, row.expiresAt!) : null,
+  };
+}
+
+app.post("/api/settings/export", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const body: { includeRepositories?: unknown } = await c.req.json().catch(() => ({}));
+
+  if (body.includeRepositories !== undefined && typeof body.includeRepositories !== "boolean") {
+    return apiError(c, 400, "includeRepositories must be a boolean");
+  }
+
+  const inProgress = await db.query.accountExports.findFirst({
+    where: and(eq(accountExports.userId, user.id), inArray(accountExports.status, ["pending", "running"])),
+  });
+  if (inProgress) {
+    return apiError(c, 409, "An export is already in progress");
+  }
+
+  const [row] = await db
+    .insert(accountExports)
+    .values({ userId: user.id, includeRepositories: body.includeRepositories === true })
+    .returning();
+
+  trackTask("account_export", () => runAccountExport(row.id)).catch((error) => {
+    console.error("[API] Account export failed:", error);
+  });
+
+  return c.json({ export: serializeExport(row) });
+});
+
+app.get("/api/settings/export", requireAuth, async (c) => {
+  const user = c.get("user")!;
+
+  const rows = await db.query.accountExports.findMany({
+    where: eq(accountExports.userId, user.id),
+    orderBy: desc(accountExports.createdAt),
+    limit: 5,
+  });
+
+  return c.json({ exports: rows.map(serializeExport) });
+});
+
+// Signed links let the browser download the archive directly; they outlive neither the hour nor the archive.
+app.get("/api/settings/export/:id/download", async (c) => {
+  const id = c.req.param("id");
+  if (!verifyExportDownload(id, c.req.query("expires"), c.req.query("signature"))) {
+    return apiError(c, 403, "Download link is invalid or has expired");
+  }
+
+  const row = await db.query.accountExports.findFirst({
+    where: eq(accountExports.id, id),
+  });
+  if (!row || row.status !== "completed" || !row.storageKey) {
+    return apiError(c, 404, "Export not found");
+  }
+
+  const stream = await getObjectStream(row.storageKey);
+  if (!stream) {
+    return apiError(c, 404, "Export not found");
+  }
+
+  return new Response(stream, {
+    headers: {
+      "Content-Type": "application/gzip",
+      "Content-Disposition": `attachment; filename="gitbruv-export-${row.createdAt.toISOString().slice(0, 10)}.tar.gz"`,
+      "Cache-Control": "private, no-store",
+    },
+  });
+});
+
 app.get("/api/settings/current-user", requireAuth, async (c) => {
   const user = c.get("user")!;
 
@@ -375,7 +563,7 @@ app.get("/api/settings/current-user", requireAuth, async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   return c.json({
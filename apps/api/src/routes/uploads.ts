@@ -0,0 +1,179 @@
+import { Hono } from "hono";
+import { db, attachments, repositories } from "@gitbruv/db";
+import { eq, and, count, sum } from "drizzle-orm";
+import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import { presignPutUrl, headObject, getObjectStream, deleteObject } from "../s3";
+import {
+  ALLOWED_ATTACHMENT_TYPES,
+  MAX_ATTACHMENT_BYTES,
+  MAX_PENDING_ATTACHMENTS,
+  MAX_PENDING_ATTACHMENT_BYTES,
+  UPLOAD_URL_TTL_SECONDS,
+  getAttachmentKey,
+  isInlineAttachmentType,
+  sanitizeAttachmentFilename,
+} from "../attachments";
+import { getApiUrl } from "../config";
+import { apiError } from "../errors";
+import { isolateStorageFailures } from "../tracing";
+
+const app = new Hono<{ Variables: AuthVariables }>();
+
+app.use("*", authMiddleware);
+
+function attachmentUrl(attachment: { id: string; filename: string }) {
+  return `${getApiUrl()}/api/attachments/${attachment.id}/${encodeURIComponent(attachment.filename)}`;
+}
+
+app.post("/api/uploads", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const body = await c.req.json<{ filename?: string; contentType?: string; size?: number; repositoryId?: string }>();
+
+  if (typeof body.filename !== "string" || !body.filename.trim()) {
+    return apiError(c, 400, "Filename is required");
+  }
+  if (typeof body.contentType !== "string" || !ALLOWED_ATTACHMENT_TYPES.has(body.contentType)) {
+    return apiError(c, 400, "Unsupported content type");
+  }
+  if (typeof body.size !== "number" || !Number.isInteger(body.size) || body.size <= 0) {
+    return apiError(c, 400, "Size must be a positive integer");
+  }
+  if (body.size > MAX_ATTACHMENT_BYTES) {
+    return apiError(c, 413, `Attachments are limited to ${MAX_ATTACHMENT_BYTES / (1024 * 1024)} MB`);
+  }
+
+  if (body.repositoryId !== undefined) {
+    const repo = await db.query.repositories.findFirst({
+      where: eq(repositories.id, body.repositoryId),
+      columns: { ownerId: true, visibility: true },
+    });
+    if (!repo || (repo.visibility === "private" && repo.ownerId !== user.id)) {
+      return apiError(c, 404, "Repository not found");
+    }
+  }
+
+  const [pending] = await db
+    .select({ uploads: count(), bytes: sum(attachments.sizeBytes).mapWith(Number) })
+    .from(attachments)
+    .where(and(eq(attachments.uploaderId, user.id), eq(attachments.status, "pending")));
+  if (pending.uploads >= MAX_PENDING_ATTACHMENTS || (pending.bytes ?? 0) + body.size > MAX_PENDING_ATTACHMENT_BYTES) {
+    return apiError(c, 429, "Too many unfinished uploads - finalize or wait for them to expire before starting more");
+  }
+
+  const id = crypto.randomUUID();
+  const filename = sanitizeAttachmentFilename(body.filename);
+  const storageKey = getAttachmentKey(id, filename);
+
+  const uploadUrl = presignPutUrl(storageKey, {
+    contentType: body.contentType,
+    contentLength: body.size,
+    expiresInSeconds: UPLOAD_URL_TTL_SECONDS,
+  });
+  if (!uploadUrl) {
+    return apiError(c, 503, "Storage is not configured");
+  }
+
+  await db.insert(attachments).values({
+    id,
+    uploaderId: user.id,
+    repositoryId: body.repositoryId ?? null,
+    storageKey,
+    filename,
+    contentType: body.contentType,
+    sizeBytes: body.size,
+  });
+
+  return c.json(
+    {
+      id,
+      uploadUrl,
+      method: "PUT",
+      headers: { "Content-Type": body.contentType },
+      expiresAt: new Date(Date.now() + UPLOAD_URL_TTL_SECONDS * 1000),
+    },
+    201
+  );
+});
+
+app.post("/api/uploads/:id/finalize", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const id = c.req.param("id");
+
+  const attachment = await db.query.attachments.findFirst({
+    where: and(eq(attachments.id, id), eq(attachments.uploaderId, user.id)),
+  });
+  if (!attachment) {
+    return apiError(c, 404, "Upload not found");
+  }
+
+  if (attachment.status === "pending") {
+    const stored = await headObject(attachment.storageKey);
+    if (!stored) {
+      return apiError(c, 409, "The file has not been uploaded yet");
+    }
+    if (stored.size !== attachment.sizeBytes || stored.contentType !== attachment.contentType) {
+      await isolateStorageFailures(() => deleteObject(attachment.storageKey)).catch(() => {});
+      await db.delete(attachments).where(eq(attachments.id, id));
+      return apiError(c, 400, "Uploaded file does not match the requested size or content type");
+    }
+
+    await db
+      .update(attachments)
+      .set({ status: "finalized", finalizedAt: new Date() })
+      .where(and(eq(attachments.id, id), eq(attachments.status, "pending")));
+  }
+
+  const url = attachmentUrl(attachment);
+  const markdown = attachment.contentType.startsWith("image/") ? `![${attachment.filename}](${url})` : `[${attachment.filename}](${url})`;
+
+  return c.json({
+    id: attachment.id,
+    filename: attachment.filename,
+    contentType: attachment.contentType,
+    size: attachment.sizeBytes,
+    url,
+    markdown,
+  });
+});
+
+app.get("/api/attachments/:id/:filename", async (c) => {
+  const currentUser = c.get("user");
+  const id = c.req.param("id");
+
+  const [attachment] = await db
+    .select({
+      storageKey: attachments.storageKey,
+      filename: attachments.filename,
+      contentType: attachments.contentType,
+      sizeBytes: attachments.sizeBytes,
+      repoOwnerId: repositories.ownerId,
+      repoVisibility: repositories.visibility,
+    })
+    .from(attachments)
+    .leftJoin(repositories, eq(repositories.id, attachments.repositoryId))
+    .where(and(eq(attachments.id, id), eq(attachments.status, "finalized")))
+    .limit(1);
+
+  if (!attachment || (attachment.repoVisibility === "private" && currentUser?.id !== attachment.repoOwnerId)) {
+    return apiError(c, 404, "Attachment not found");
+  }
+
+  const stream = await getObjectStream(attachment.storageKey);
+  if (!stream) {
+    return apiError(c, 404, "Attachment not found");
+  }
+
+  const disposition = isInlineAttachmentType(attachment.contentType) ? "inline" : "attachment";
+  return new Response(stream, {
+    headers: {
+      "Content-Type": attachment.contentType,
+      "Content-Length": String(attachment.sizeBytes),
+      "Content-Disposition": `${disposition}; filename="${attachment.filename}"`,
+      "X-Content-Type-Options": "nosniff",
+      // Revalidated on each use so an attachment stops being served once its repository goes private.
+      "Cache-Control": attachment.repoVisibility === "private" ? "private, no-store" : "public, no-cache",
+    },
+  });
+});
+
+export default app;
@@ -1,12 +1,16 @@
-import { Hono } from "hono";
-import { db, users, repositories, stars } from "@gitbruv/db";
-import { eq, sql, desc, asc, and } from "drizzle-orm";
+import { Hono, type Context } from "hono";
+import { db, users, repositories, stars, follows } from "@gitbruv/db";
+import { eq, sql, desc, asc, and, or, type SQL } from "drizzle-orm";
 import { authMiddleware, requireAuth, type AuthVariables } from "../middleware/auth";
+import { encodeCursor, decodeCursor, afterCursor, type KeysetPart } from "../pagination";
+import { apiError } from "../errors";
 
 const app = new Hono<{ Variables: AuthVariables }>();
 
 app.use("*", authMiddleware);
 
+const MAX_FOLLOWS_PAGE_SIZE = 100;
+
 function cacheBustAvatarUrl(avatarUrl: string | null, updatedAt: Date): string | null {
   if (!avatarUrl) return null;
   if (avatarUrl.includes("v=")) return avatarUrl;
@@ -21,7 +25,7 @@ app.get("/api/users/me", requireAuth, async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   return c.json(result);
@@ -39,7 +43,7 @@ app.get("/api/users/me/summary", requireAuth, async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   return c.json({
@@ -52,8 +56,19 @@ app.get("/api/users/public", async (c) => {
   const sortBy = c.req.query("sortBy") || "newest";
   const limit = parseInt(c.req.query("limit") || "20", 10);
   const offset = parseInt(c.req.query("offset") || "0", 10);
+  const cursorParam = c.req.query("cursor");
+
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "text"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
 
-  const orderBy = sortBy === "oldest" ? asc(users.createdAt) : desc(users.createdAt);
+  const direction = sortBy === "oldest" ? "asc" : "desc";
+  const order = direction === "asc" ? asc : desc;
+  const keyset: KeysetPart[] = [
+    { column: users.createdAt, type: "timestamp" },
+    { column: users.id, type: "text" },
+  ];
 
   const usersResult = await db
     .select({
@@ -64,14 +79,16 @@ app.get("/api/users/public", async (c) => {
       bio: users.bio,
       createdAt: users.createdAt,
       updatedAt: users.updatedAt,
+      sortKey: sql<string>`${users.createdAt}::text`,
     })
     .from(users)
-    .orderBy(orderBy)
+    .where(cursor ? afterCursor(keyset, cursor, direction) : undefined)
+    .orderBy(order(users.createdAt), order(users.id))
     .limit(limit + 1)
-    .offset(offset);
+    .offset(cursor ? 0 : offset);
 
   const result = await Promise.all(
-    usersResult.map(async (user) => {
+    usersResult.map(async ({ sortKey: _sortKey, ...user }) => {
       const [repoCountResult] = await db
         .select({ count: sql<number>`COUNT(*)` })
         .from(repositories)
@@ -87,10 +104,12 @@ app.get("/api/users/public", async (c) => {
 
   const hasMore = result.length > limit;
   const usersData = result.slice(0, limit);
+  const last = usersResult[usersData.length - 1];
 
   return c.json({
     users: usersData,
     hasMore,
+    nextCursor: hasMore && last ? encodeCursor([last.sortKey, last.id]) : null,
   });
 });
 
@@ -129,7 +148,7 @@ app.get("/api/users/:username", async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   return c.json(result);
@@ -144,7 +163,7 @@ app.get("/api/users/:username/profile", async (c) => {
   });
 
   if (!result) {
-    return c.json({ error: "User not found" }, 404);
+    return apiError(c, 404, "User not found");
   }
 
   const isOwnProfile = currentUser?.id === result.id;
@@ -171,6 +190,21 @@ app.get("/api/users/:username/profile", async (c) => {
     response.lastActiveAt = result.lastActiveAt;
   }
 
+  const [[followerResult], [followingResult]] = await Promise.all([
+    db.select({ count: sql<number>`COUNT(*)` }).from(follows).where(eq(follows.followingId, result.id)),
+    db.select({ count: sql<number>`COUNT(*)` }).from(follows).where(eq(follows.followerId, result.id)),
+  ]);
+  response.followerCount = Number(followerResult?.count) || 0;
+  response.followingCount = Number(followingResult?.count) || 0;
+
+  if (currentUser && !isOwnProfile) {
+    const follow = await db.query.follows.findFirst({
+      where: and(eq(follows.followerId, currentUser.id), eq(follows.followingId, result.id)),
+      columns: { createdAt: true },
+    });
+    response.isFollowing = !!follow;
+  }
+
   if (isOwnProfile) {
     response.email = result.email;
     response.emailVerified = result.emailVerified;
@@ -181,6 +215,9 @@ app.get("/api/users/:username/profile", async (c) => {
 
 app.get("/api/users/:username/starred", async (c) => {
   const username = c.req.param("username");
+  const currentUser = c.get("user");
+  const limit = Math.min(parseInt(c.req.query("limit") || "30", 10), 100);
+  const offset = parseInt(c.req.query("offset") || "0", 10);
 
   const userResult = await db.query.users.findFirst({
     where: eq(users.username, username),
@@ -188,9 +225,13 @@ app.get("/api/users/:username/starred", async (c) => {
   });
 
   if (!userResult) {
-    return c.json({ repos: [] });
+    return c.json({ repos: [], hasMore: false, total: 0 });
   }
 
+  const visibleCondition = currentUser
+    ? or(eq(repositories.visibility, "public"), eq(repositories.ownerId, currentUser.id))
+    : eq(repositories.visibility, "public");
+
   const starredRepos = await db
     .select({
       id: repositories.id,
@@ -210,10 +251,19 @@ app.get("/api/users/:username/starred", async (c) => {
     .from(stars)
     .innerJoin(repositories, eq(stars.repositoryId, repositories.id))
     .innerJoin(users, eq(repositories.ownerId, users.id))
-    .where(and(eq(stars.userId, userResult.id), eq(repositories.visibility, "public")))
-    .orderBy(desc(stars.createdAt));
+    .where(and(eq(stars.userId, userResult.id), visibleCondition))
+    .orderBy(desc(stars.createdAt), desc(stars.repositoryId))
+    .limit(limit + 1)
+    .offset(offset);
+
+  const [totalResult] = await db
+    .select({ count: sql<number>`COUNT(*)` })
+    .from(stars)
+    .innerJoin(repositories, eq(stars.repositoryId, repositories.id))
+    .where(and(eq(stars.userId, userResult.id), visibleCondition));
 
-  const repos = starredRepos.map((r) => ({
+  const hasMore = starredRepos.length > limit;
+  const repos = starredRepos.slice(0, limit).map((r) => ({
     id: r.id,
     name: r.name,
     description: r.description,
@@ -231,7 +281,116 @@ app.get("/api/users/:username/starred", async (c) => {
     },
   }));
 
-  return c.json({ repos });
+  return c.json({ repos, hasMore, total: Number(totalResult?.count) || 0 });
+});
+
+async function findUserId(username: string) {
+  const user = await db.query.users.findFirst({
+    where: eq(users.username, username),
+    columns: { id: true },
+  });
+  return user?.id ?? null;
+}
+
+app.put("/api/users/:username/follow", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const targetId = await findUserId(c.req.param("username"));
+  if (!targetId) {
+    return apiError(c, 404, "User not found");
+  }
+  if (targetId === user.id) {
+    return apiError(c, 400, "You can't follow yourself");
+  }
+
+  await db.insert(follows).values({ followerId: user.id, followingId: targetId }).onConflictDoNothing();
+  return c.json({ following: true });
+});
+
+app.delete("/api/users/:username/follow", requireAuth, async (c) => {
+  const user = c.get("user")!;
+  const targetId = await findUserId(c.req.param("username"));
+  if (!targetId) {
+    return apiError(c, 404, "User not found");
+  }
+
+  await db.delete(follows).where(and(eq(follows.followerId, user.id), eq(follows.followingId, targetId)));
+  return c.json({ following: false });
 });
 
+async function handleFollowList(c: Context<{ Variables: AuthVariables }>, direction: "followers" | "following") {
+  const limit = Math.min(Math.max(parseInt(c.req.query("limit") || "30", 10) || 30, 1), MAX_FOLLOWS_PAGE_SIZE);
+  const cursorParam = c.req.query("cursor");
+  const cursor = cursorParam ? decodeCursor(cursorParam, ["timestamp", "text"]) : null;
+  if (cursorParam && !cursor) {
+    return apiError(c, 400, "Invalid cursor");
+  }
+
+  const userId = await findUserId(c.req.param("username")!);
+  if (!userId) {
+    return apiError(c, 404, "User not found");
+  }
+
+  return c.json(await listFollows(userId, direction, limit, cursor));
+}
+
+// Followers and following are the same query from opposite ends of the edge; `listed` is the
+// side whose users are returned, newest follow first.
+async function listFollows(
+  subjectId: string,
+  direction: "followers" | "following",
+  limit: number,
+  cursor: string[] | null
+) {
+  const [subject, listed] =
+    direction === "followers" ? [follows.followingId, follows.followerId] : [follows.followerId, follows.followingId];
+
+  const conditions: SQL[] = [eq(subject, subjectId)];
+  if (cursor) {
+    conditions.push(
+      afterCursor(
+        [
+          { column: follows.createdAt, type: "timestamp" },
+          { column: listed, type: "text" },
+        ],
+        cursor,
+        "desc"
+      )
+    );
+  }
+
+  const rows = await db
+    .select({
+      id: users.id,
+      username: users.username,
+      name: users.name,
+      avatarUrl: users.avatarUrl,
+      updatedAt: users.updatedAt,
+      bio: users.bio,
+      followedAt: follows.createdAt,
+      sortKey: sql<string>`${follows.createdAt}::text`,
+    })
+    .from(follows)
+    .innerJoin(users, eq(users.id, listed))
+    .where(and(...conditions))
+    .orderBy(desc(follows.createdAt), desc(listed))
+    .limit(limit + 1);
+
+  const hasMore = rows.length > limit;
+  const page = rows.slice(0, limit);
+  const last = page[page.length - 1];
+
+  return {
+    users: page.map(({ sortKey: _sortKey, updatedAt, ...row }) => ({
+      ...row,
+      avatarUrl: cacheBustAvatarUrl(row.avatarUrl, updatedAt),
+    })),
+    hasMore,
+    nextCursor: hasMore && last ? encodeCursor([last.sortKey, last.id]) : null,
+  };
+}
+
+app.get("/api/users/:username/followers", (c) => handleFollowList(c, "followers"));
+
+app.get("/api/users/:username/following", (c) => handleFollowList(c, "following"));
+
 export default app;
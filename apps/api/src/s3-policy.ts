@@ -0,0 +1,173 @@
+import { config } from "./config";
+import { markStorageFailure } from "./tracing";
+
+export type StorageOperation = "read" | "write" | "list";
+
+// Raised when storage can't answer at all (timeouts, 5xx after retries, an open circuit, no
+// bucket configured). Missing objects are never reported this way; they stay null/404.
+export class StorageUnavailableError extends Error {
+  constructor(
+    message: string,
+    readonly reason: "timeout" | "failed" | "circuit_open" | "not_configured",
+    options?: { cause?: unknown }
+  ) {
+    super(message, options);
+    this.name = "StorageUnavailableError";
+  }
+}
+
+export function isMissingObjectError(error: any): boolean {
+  return error?.name === "NoSuchKey" || error?.name === "NotFound" || error?.$metadata?.httpStatusCode === 404;
+}
+
+const RETRYABLE_NAMES = new Set(["SlowDown", "RequestTimeout", "RequestTimeTooSkewed", "InternalError", "ServiceUnavailable", "ThrottlingException"]);
+
+// Client errors (bad keys, denied access) won't get better on retry and say nothing about
+// whether storage is up, so only server-side and transport failures are retried and counted.
+function isRetryable(error: any): boolean {
+  if (error instanceof StorageUnavailableError) return error.reason === "timeout";
+  if (RETRYABLE_NAMES.has(error?.name)) return true;
+  const status = error?.$metadata?.httpStatusCode;
+  if (typeof status === "number") return status >= 500 || status === 429;
+  return true;
+}
+
+class CircuitBreaker {
+  private failures = 0;
+  private openedAt: number | null = null;
+  private probing = false;
+
+  constructor(
+    private readonly failureThreshold: number,
+    private readonly cooldownMs: number
+  ) {}
+
+  get state(): "closed" | "open" | "half_open" {
+    if (this.openedAt === null) return "closed";
+    return Date.now() - this.openedAt >= this.cooldownMs ? "half_open" : "open";
+  }
+
+  // After the cooldown a single call is let through as a probe; everything else keeps failing
+  // fast until that probe succeeds or re-opens the circuit.
+  acquire(): boolean {
+    const state = this.state;
+    if (state === "closed") return true;
+    if (state === "half_open" && !this.probing) {
+      this.probing = true;
+      return true;
+    }
+    return false;
+  }
+
+  recordSuccess() {
+    this.failures = 0;
+    this.openedAt = null;
+    this.probing = false;
+  }
+
+  recordFailure() {
+    this.failures++;
+    if (this.probing || this.failures >= this.failureThreshold) {
+      if (this.openedAt === null || this.probing) {
+        console.error(`[S3] Circuit opened after ${this.failures} consecutive failures`);
+      }
+      this.openedAt = Date.now();
+      this.probing = false;
+    }
+  }
+}
+
+const breaker = new CircuitBreaker(config.s3.circuitBreaker.failureThreshold, config.s3.circuitBreaker.cooldownMs);
+
+export function getStorageCircuitState() {
+  return breaker.state;
+}
+
+const TIMEOUTS: Record<StorageOperation, () => number> = {
+  read: () => config.s3.timeouts.readMs,
+  write: () => config.s3.timeouts.writeMs,
+  list: () => config.s3.timeouts.listMs,
+};
+
+const BASE_BACKOFF_MS = 100;
+const MAX_BACKOFF_MS = 2000;
+
+function backoff(attempt: number) {
+  // Full jitter: anywhere from zero up to the exponential cap, so retries from many requests
+  // hitting the same outage don't line up.
+  const cap = Math.min(MAX_BACKOFF_MS, BASE_BACKOFF_MS * 2 ** attempt);
+  return new Promise((resolve) => setTimeout(resolve, Math.random() * cap));
+}
+
+// The timer covers the whole call unless `fn` reports progress: each report re-arms it, so a long
+// multipart upload only times out when a single part stalls.
+async function withTimeout<T>(operation: StorageOperation, fn: (signal: AbortSignal, progress: () => void) => Promise<T>): Promise<T> {
+  const controller = new AbortController();
+  const timeoutMs = TIMEOUTS[operation]();
+  let timedOut = false;
+  const arm = () =>
+    setTimeout(() => {
+      timedOut = true;
+      controller.abort();
+    }, timeoutMs);
+  let timer = arm();
+  const progress = () => {
+    if (timedOut) return;
+    clearTimeout(timer);
+    timer = arm();
+  };
+  try {
+    return await fn(controller.signal, progress);
+  } catch (error) {
+    if (timedOut) {
+      throw new StorageUnavailableError(`S3 ${operation} timed out after ${timeoutMs}ms`, "timeout", { cause: error });
+    }
+    throw error;
+  } finally {
+    clearTimeout(timer);
+  }
+}
+
+// Runs one S3 call under the retry, timeout and circuit-breaker policy. `fn` should include
+// reading the body so a connection dropped mid-download is retried too. Calls whose input can
+// only be consumed once (streamed uploads) pass `retry: false`.
+export async function runS3<T>(
+  operation: StorageOperation,
+  fn: (signal: AbortSignal, progress: () => void) => Promise<T>,
+  options: { retry?: boolean } = {}
+): Promise<T> {
+  if (!breaker.acquire()) {
+    markStorageFailure();
+    throw new StorageUnavailableError("Storage circuit is open", "circuit_open");
+  }
+
+  const maxAttempts = options.retry === false ? 1 : Math.max(1, config.s3.maxAttempts);
+  let lastError: unknown;
+  for (let attempt = 0; attempt < maxAttempts; attempt++) {
+    try {
+      const result = await withTimeout(operation, fn);
+      breaker.recordSuccess();
+      return result;
+    } catch (error) {
+      if (!isRetryable(error)) {
+        // A 404 or 403 is still an answer from a healthy service.
+        breaker.recordSuccess();
+        throw error;
+      }
+      lastError = error;
+      if (attempt < maxAttempts - 1) {
+        await backoff(attempt);
+      }
+    }
+  }
+
+  breaker.recordFailure();
+  markStorageFailure();
+  if (lastError instanceof StorageUnavailableError) throw lastError;
+  throw new StorageUnavailableError(`S3 ${operation} failed after ${maxAttempts} attempts`, "failed", { cause: lastError });
+}
+
+export function storageNotConfigured(): never {
+  markStorageFailure();
+  throw new StorageUnavailableError("S3 is not configured", "not_configured");
+}
@@ -0,0 +1,240 @@
+import { S3Client, HeadBucketCommand, GetObjectCommand, PutObjectCommand, DeleteObjectCommand, ListObjectsV2Command, HeadObjectCommand } from "@aws-sdk/client-s3";
+import { Upload } from "@aws-sdk/lib-storage";
+import { createHash, createHmac } from "crypto";
+import { config } from "./config";
+import { traceSpan } from "./tracing";
+import { runS3, isMissingObjectError, storageNotConfigured } from "./s3-policy";
+import type { ObjectStorage, ObjectSummary, PutOptions } from "./object-storage";
+
+const encodeRfc3986 = (value: string) =>
+  encodeURIComponent(value).replace(/[!'()*]/g, (char) => `%${char.charCodeAt(0).toString(16).toUpperCase()}`);
+
+export type PresignRequest = {
+  method: string;
+  endpoint: URL;
+  // Already RFC 3986-encoded, as it appears in the canonical request.
+  path: string;
+  // Lower-case header names the request must be sent with; host is always signed.
+  headers: Record<string, string>;
+  expiresInSeconds: number;
+};
+
+export type SigningCredentials = { accessKeyId: string; secretAccessKey: string; region: string };
+
+// SigV4 query-string signing, done by hand because the SDK's presigner isn't a dependency.
+export function presignSigV4(request: PresignRequest, credentials: SigningCredentials, now = new Date()): string {
+  const amzDate = now.toISOString().replace(/[-:]/g, "").replace(/\.\d{3}/, "");
+  const date = amzDate.slice(0, 8);
+  const scope = `${date}/${credentials.region}/s3/aws4_request`;
+  const headers = Object.entries({ ...request.headers, host: request.endpoint.host }).sort(([a], [b]) => (a < b ? -1 : 1));
+  const signedHeaders = headers.map(([name]) => name).join(";");
+
+  const params: [string, string][] = [
+    ["X-Amz-Algorithm", "AWS4-HMAC-SHA256"],
+    ["X-Amz-Credential", `${credentials.accessKeyId}/${scope}`],
+    ["X-Amz-Date", amzDate],
+    ["X-Amz-Expires", String(request.expiresInSeconds)],
+    ["X-Amz-SignedHeaders", signedHeaders],
+  ];
+  const canonicalQuery = params
+    .map(([name, value]) => `${encodeRfc3986(name)}=${encodeRfc3986(value)}`)
+    .sort()
+    .join("&");
+
+  const canonicalRequest = [
+    request.method,
+    request.path,
+    canonicalQuery,
+    headers.map(([name, value]) => `${name}:${value.trim()}\n`).join(""),
+    signedHeaders,
+    "UNSIGNED-PAYLOAD",
+  ].join("\n");
+  const stringToSign = ["AWS4-HMAC-SHA256", amzDate, scope, createHash("sha256").update(canonicalRequest).digest("hex")].join("\n");
+
+  const signingKey = [date, credentials.region, "s3", "aws4_request"].reduce<Buffer | string>(
+    (signing, part) => createHmac("sha256", signing).update(part).digest(),
+    `AWS4${credentials.secretAccessKey}`
+  );
+  const signature = createHmac("sha256", signingKey).update(stringToSign).digest("hex");
+
+  return `${request.endpoint.origin}${request.path}?${canonicalQuery}&X-Amz-Signature=${signature}`;
+}
+
+export function createS3Storage(): ObjectStorage {
+  const configured = Boolean(
+    config.s3.endpoint && config.s3.region && config.s3.bucket && config.s3.accessKeyId && config.s3.secretAccessKey
+  );
+  const bucket = config.s3.bucket;
+
+  const s3Client = configured
+    ? new S3Client({
+      endpoint: config.s3.endpoint,
+      region: config.s3.region,
+      credentials: {
+        accessKeyId: config.s3.accessKeyId,
+        secretAccessKey: config.s3.secretAccessKey,
+      },
+      forcePathStyle: true,
+      // Retries are handled by runS3 so timeouts and the circuit breaker see every attempt.
+      maxAttempts: 1,
+    })
+    : null;
+
+  s3Client?.middlewareStack.add(
+    (next, context) => (args) => {
+      const input = args.input as { Key?: string; Prefix?: string };
+      return traceSpan(`s3.${context.commandName}`, { key: input.Key ?? input.Prefix }, () => next(args));
+    },
+    { step: "initialize", name: "traceSpan" }
+  );
+
+  const storageClassParams = (storageClass: string) => ({
+    Metadata: { "storage-class": storageClass },
+    Tagging: config.s3.objectTagging ? `storage-class=${storageClass}` : undefined,
+  });
+
+  const head = async (key: string) => {
+    const client = s3Client ?? storageNotConfigured();
+    try {
+      const response = await runS3("read", (abortSignal) => client.send(new HeadObjectCommand({ Bucket: bucket, Key: key }), { abortSignal }));
+      return { size: response.ContentLength ?? 0, contentType: response.ContentType };
+    } catch (error) {
+      if (isMissingObjectError(error)) {
+        return null;
+      }
+      throw error;
+    }
+  };
+
+  return {
+    backend: "s3",
+
+    async get(key: string, range?: string): Promise<Buffer | null> {
+      const client = s3Client ?? storageNotConfigured();
+      try {
+        return await runS3("read", async (abortSignal) => {
+          const response = await client.send(new GetObjectCommand({ Bucket: bucket, Key: key, Range: range }), { abortSignal });
+          if (!response.Body) {
+            return null;
+          }
+          return Buffer.from(await response.Body.transformToByteArray());
+        });
+      } catch (error) {
+        if (isMissingObjectError(error)) {
+          return null;
+        }
+        throw error;
+      }
+    },
+
+    // Only opening the stream is covered by the retry policy; once bytes are flowing to the client
+    // a failure can't be retried transparently.
+    async getStream(key: string): Promise<ReadableStream | null> {
+      const client = s3Client ?? storageNotConfigured();
+      try {
+        const response = await runS3("read", (abortSignal) => client.send(new GetObjectCommand({ Bucket: bucket, Key: key }), { abortSignal }));
+        return response.Body ? response.Body.transformToWebStream() : null;
+      } catch (error) {
+        if (isMissingObjectError(error)) {
+          return null;
+        }
+        throw error;
+      }
+    },
+
+    head,
+
+    async put(key: string, body, options: PutOptions): Promise<void> {
+      const client = s3Client ?? storageNotConfigured();
+      await runS3("write", (abortSignal) =>
+        client.send(
+          new PutObjectCommand({
+            Bucket: bucket,
+            Key: key,
+            Body: body,
+            ContentType: options.contentType,
+            ...storageClassParams(options.storageClass),
+          }),
+          { abortSignal }
+        )
+      );
+    },
+
+    async upload(key: string, body, options: PutOptions): Promise<void> {
+      const client = s3Client ?? storageNotConfigured();
+      const upload = new Upload({
+        client,
+        params: {
+          Bucket: bucket,
+          Key: key,
+          Body: body,
+          ContentType: options.contentType,
+          ...storageClassParams(options.storageClass),
+        },
+      });
+
+      // The write timeout applies to each part: every finished part restarts it.
+      await runS3(
+        "write",
+        async (abortSignal, progress) => {
+          abortSignal.addEventListener("abort", () => void upload.abort());
+          upload.on("httpUploadProgress", progress);
+          await upload.done();
+        },
+        { retry: false }
+      );
+    },
+
+    async delete(key: string): Promise<void> {
+      const client = s3Client ?? storageNotConfigured();
+      await runS3("write", (abortSignal) => client.send(new DeleteObjectCommand({ Bucket: bucket, Key: key }), { abortSignal }));
+    },
+
+    async list(prefix: string): Promise<ObjectSummary[]> {
+      const client = s3Client ?? storageNotConfigured();
+      const objects: ObjectSummary[] = [];
+      let continuationToken: string | undefined;
+
+      do {
+        const response = await runS3("list", (abortSignal) =>
+          client.send(new ListObjectsV2Command({ Bucket: bucket, Prefix: prefix, ContinuationToken: continuationToken }), { abortSignal })
+        );
+
+        for (const obj of response.Contents || []) {
+          if (obj.Key) {
+            objects.push({ key: obj.Key, size: obj.Size || 0, lastModified: obj.LastModified || null });
+          }
+        }
+
+        continuationToken = response.NextContinuationToken;
+      } while (continuationToken);
+
+      return objects;
+    },
+
+    async ping(): Promise<boolean> {
+      if (!s3Client) return false;
+      await s3Client.send(new HeadBucketCommand({ Bucket: bucket }));
+      return true;
+    },
+
+    // Content-Type and Content-Length are signed headers, so the upload has to match both exactly.
+    presignPut(key: string, options: { contentType: string; contentLength: number; expiresInSeconds: number }): string | null {
+      if (!configured) {
+        return null;
+      }
+
+      const endpoint = new URL(config.s3.endpoint);
+      return presignSigV4(
+        {
+          method: "PUT",
+          endpoint,
+          path: `${endpoint.pathname.replace(/\/$/, "")}/${encodeRfc3986(bucket)}/${key.split("/").map(encodeRfc3986).join("/")}`,
+          headers: { "content-length": String(options.contentLength), "content-type": options.contentType },
+          expiresInSeconds: options.expiresInSeconds,
+        },
+        config.s3
+      );
+    },
+  };
+}
@@ -1,135 +1,58 @@
-import { S3Client, GetObjectCommand, PutObjectCommand, DeleteObjectCommand, ListObjectsV2Command, HeadObjectCommand } from "@aws-sdk/client-s3";
-import { Upload } from "@aws-sdk/lib-storage";
 import { config } from "./config";
+import { createS3Storage } from "./s3-storage";
+import { createLocalStorage } from "./local-storage";
+import { storageLayout, AVATARS_ROOT, ATTACHMENTS_ROOT, SHARED_OBJECTS_ROOT } from "./storage-layout";
+import type { ObjectStorage, ObjectSummary } from "./object-storage";
 
-const s3Configured = Boolean(
-  config.s3.endpoint && config.s3.region && config.s3.bucket && config.s3.accessKeyId && config.s3.secretAccessKey
-);
-
-export const s3Client = s3Configured
-  ? new S3Client({
-    endpoint: config.s3.endpoint,
-    region: config.s3.region,
-    credentials: {
-      accessKeyId: config.s3.accessKeyId,
-      secretAccessKey: config.s3.secretAccessKey,
-    },
-    forcePathStyle: true,
-  })
-  : null;
-
-export const bucket = config.s3.bucket;
-
-export const getRepoPrefix = (owner: string, repo: string): string => {
-  return `repos/${owner}/${repo}`;
-};
+export type { ObjectSummary };
 
-export const getObject = async (key: string): Promise<Buffer | null> => {
-  if (!s3Client) {
-    return null;
-  }
-  try {
-    const response = await s3Client.send(
-      new GetObjectCommand({
-        Bucket: bucket,
-        Key: key,
-      })
-    );
-
-    if (!response.Body) {
-      return null;
-    }
+// Named after the backend it was written for; every call goes to the one config.storage picks.
+export const objectStorage: ObjectStorage = config.storage.backend === "local" ? createLocalStorage() : createS3Storage();
 
-    const bytes = await response.Body.transformToByteArray();
-    return Buffer.from(bytes);
-  } catch (error: any) {
-    if (error.name === "NoSuchKey" || error.$metadata?.httpStatusCode === 404) {
-      return null;
-    }
-    throw error;
-  }
-};
+export type StorageClass = "pack" | "loose" | "ref" | "repo-meta" | "avatar" | "attachment" | "other";
 
-export const putObject = async (key: string, body: Buffer | Uint8Array | string, contentType?: string): Promise<void> => {
-  if (!s3Client) {
-    throw new Error("S3 is not configured");
-  }
-  await s3Client.send(
-    new PutObjectCommand({
-      Bucket: bucket,
-      Key: key,
-      Body: body,
-      ContentType: contentType,
-    })
-  );
-};
+export const STORAGE_CLASSES: StorageClass[] = ["pack", "loose", "ref", "repo-meta", "avatar", "attachment", "other"];
 
-export const deleteObject = async (key: string): Promise<void> => {
-  if (!s3Client) {
-    throw new Error("S3 is not configured");
-  }
-  await s3Client.send(
-    new DeleteObjectCommand({
-      Bucket: bucket,
-      Key: key,
-    })
-  );
+const SHARED_LOOSE_OBJECT_KEY = new RegExp(`^${SHARED_OBJECTS_ROOT}/[^/]+/[0-9a-f]{2}/`);
+
+export const classifyKey = (key: string): StorageClass => {
+  if (key.startsWith(`${AVATARS_ROOT}/`)) return "avatar";
+  if (key.startsWith(`${ATTACHMENTS_ROOT}/`)) return "attachment";
+  if (SHARED_LOOSE_OBJECT_KEY.test(key)) return "loose";
+
+  const relative = storageLayout.repoRelativePath(key);
+  if (relative === null) return "other";
+
+  if (relative.startsWith("objects/pack/")) return "pack";
+  if (/^objects\/[0-9a-f]{2}\//.test(relative)) return "loose";
+  if (relative.startsWith("refs/") || relative === "HEAD" || relative === "packed-refs") return "ref";
+  return "repo-meta";
 };
 
-export const listObjects = async (prefix: string): Promise<string[]> => {
-  if (!s3Client) {
-    return [];
-  }
-  const keys: string[] = [];
-  let continuationToken: string | undefined;
-
-  do {
-    const response = await s3Client.send(
-      new ListObjectsV2Command({
-        Bucket: bucket,
-        Prefix: prefix,
-        ContinuationToken: continuationToken,
-      })
-    );
-
-    if (response.Contents) {
-      for (const obj of response.Contents) {
-        if (obj.Key) {
-          keys.push(obj.Key);
-        }
-      }
-    }
+export const getObject = (key: string, range?: string): Promise<Buffer | null> => objectStorage.get(key, range);
+
+export const putObject = (key: string, body: Buffer | Uint8Array | string, contentType?: string): Promise<void> =>
+  objectStorage.put(key, body, { contentType, storageClass: classifyKey(key) });
 
-    continuationToken = response.NextContinuationToken;
-  } while (continuationToken);
+export const deleteObject = (key: string): Promise<void> => objectStorage.delete(key);
 
-  return keys;
+export const listObjects = async (prefix: string): Promise<string[]> => {
+  const objects = await listObjectsWithSize(prefix);
+  return objects.map((object) => object.key);
 };
 
+export const listObjectsWithSize = (prefix: string): Promise<ObjectSummary[]> => objectStorage.list(prefix);
+
+export const headObject = (key: string): Promise<{ size: number; contentType: string | undefined } | null> => objectStorage.head(key);
+
 export const objectExists = async (key: string): Promise<boolean> => {
-  if (!s3Client) {
-    return false;
-  }
-  try {
-    await s3Client.send(
-      new HeadObjectCommand({
-        Bucket: bucket,
-        Key: key,
-      })
-    );
-    return true;
-  } catch (error: any) {
-    if (error.name === "NotFound" || error.$metadata?.httpStatusCode === 404) {
-      return false;
-    }
-    throw error;
-  }
+  return (await headObject(key)) !== null;
 };
 
+export const presignPutUrl = (key: string, options: { contentType: string; contentLength: number; expiresInSeconds: number }): string | null =>
+  objectStorage.presignPut(key, options);
+
 export const deletePrefix = async (prefix: string): Promise<void> => {
-  if (!s3Client) {
-    throw new Error("S3 is not configured");
-  }
   const keys = await listObjects(prefix);
 
   if (keys.length === 0) {
@@ -143,58 +66,27 @@ export const deletePrefix = async (prefix: string): Promise<void> => {
   }
 };
 
-export const uploadMultipart = async (
+export const uploadMultipart = (
   key: string,
   body: Buffer | Uint8Array | ReadableStream,
   contentType?: string
-): Promise<void> => {
-  if (!s3Client) {
-    throw new Error("S3 is not configured");
-  }
-  const upload = new Upload({
-    client: s3Client,
-    params: {
-      Bucket: bucket,
-      Key: key,
-      Body: body,
-      ContentType: contentType,
-    },
-  });
-
-  await upload.done();
-};
+): Promise<void> => objectStorage.upload(key, body, { contentType, storageClass: classifyKey(key) });
 
-export const getObjectStream = async (key: string): Promise<ReadableStream | null> => {
-  if (!s3Client) {
-    return null;
-  }
-  try {
-    const response = await s3Client.send(
-      new GetObjectCommand({
-        Bucket: bucket,
-        Key: key,
-      })
-    );
-
-    if (!response.Body) {
-      return null;
-    }
+export const getObjectStream = (key: string): Promise<ReadableStream | null> => objectStorage.getStream(key);
 
-    return response.Body.transformToWebStream();
-  } catch (error: any) {
-    if (error.name === "NoSuchKey" || error.$metadata?.httpStatusCode === 404) {
-      return null;
-    }
-    throw error;
-  }
-};
-
-export const copyPrefix = async (sourcePrefix: string, targetPrefix: string): Promise<void> => {
+export const copyPrefix = async (
+  sourcePrefix: string,
+  targetPrefix: string,
+  include: (relativeKey: string) => boolean = () => true
+): Promise<void> => {
   const keys = await listObjects(sourcePrefix);
   const normalizedSource = sourcePrefix.replace(/\/$/, "");
   const normalizedTarget = targetPrefix.replace(/\/$/, "");
 
   for (const key of keys) {
+    if (!include(key.slice(normalizedSource.length + 1))) {
+      continue;
+    }
     const data = await getObject(key);
     if (!data) {
       continue;
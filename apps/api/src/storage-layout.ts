@@ -0,0 +1,60 @@
+// Every key the API reads or writes in the bucket is built here, so a change to where something
+// lives is one edit rather than a search for string templates. The branded types only exist for
+// the compiler: they keep, say, a repository prefix from being passed where a full key is meant.
+type StorageKey<Kind extends string> = string & { readonly __storageKey: Kind };
+
+export type RepoPrefix = StorageKey<"repo">;
+export type PackKey = StorageKey<"pack">;
+export type RefKey = StorageKey<"ref">;
+export type AvatarKey = StorageKey<"avatar">;
+
+export const REPOS_ROOT = "repos";
+export const AVATARS_ROOT = "avatars";
+export const ATTACHMENTS_ROOT = "attachments";
+export const EXPORTS_ROOT = "exports";
+
+// Loose objects of a fork network; see git/shared-objects.ts.
+export const SHARED_OBJECTS_ROOT = "objects";
+
+const REPO_KEY = new RegExp(`^${REPOS_ROOT}/[^/]+/[^/]+/(.*)$`);
+
+export const storageLayout = {
+  repoPrefix: (ownerId: string, repoName: string) => `${REPOS_ROOT}/${ownerId}/${repoName}` as RepoPrefix,
+
+  // For listing or deleting a whole repository. The trailing slash matters: without it "repos/u/app"
+  // also matches every key of "repos/u/app-old".
+  repoContents: (prefix: RepoPrefix) => `${prefix}/`,
+
+  // Any file of the bare repository, by its path relative to the git directory.
+  repoKey: (prefix: RepoPrefix, path: string) => `${prefix}/${path}`,
+
+  // The key's path relative to the repository's git directory, or null outside any repository.
+  repoRelativePath: (key: string): string | null => key.match(REPO_KEY)?.[1] ?? null,
+
+  refKey: (prefix: RepoPrefix, ref: "HEAD" | "packed-refs" | `refs/${string}`) => `${prefix}/${ref}` as RefKey,
+
+  packDir: (prefix: RepoPrefix) => `${prefix}/objects/pack`,
+
+  // `name` is the file name without extension, e.g. "pack-<sha1 of the pack>".
+  packKey: (prefix: RepoPrefix, name: string, ext: "pack" | "idx") => `${prefix}/objects/pack/${name}.${ext}` as PackKey,
+
+  packCachePrefix: (prefix: RepoPrefix) => `${prefix}/pack-cache`,
+
+  reachabilityIndexKey: (prefix: RepoPrefix) => `${prefix}/reachability-index.json.gz`,
+
+  sharedObjectsPrefix: (networkId: string) => `${SHARED_OBJECTS_ROOT}/${networkId}`,
+
+  avatarKey: (userId: string, ext: string) => `${AVATARS_ROOT}/${userId}.${ext}` as AvatarKey,
+
+  // Avatar URLs carry the file name, which is all that's needed to find the object again.
+  avatarKeyForFilename: (filename: string) => `${AVATARS_ROOT}/${filename}` as AvatarKey,
+
+  // The trailing dot keeps one user's prefix from matching another id that starts the same way.
+  userAvatarsPrefix: (userId: string) => `${AVATARS_ROOT}/${userId}.`,
+
+  attachmentKey: (id: string, filename: string) => `${ATTACHMENTS_ROOT}/${id}/${filename}`,
+
+  exportKey: (userId: string, exportId: string) => `${EXPORTS_ROOT}/${userId}/${exportId}.tar.gz`,
+
+  userExportsPrefix: (userId: string) => `${EXPORTS_ROOT}/${userId}/`,
+};
@@ -0,0 +1,84 @@
+import { AsyncLocalStorage } from "node:async_hooks";
+import { setQueryObserver } from "@gitbruv/db";
+import { config } from "./config";
+
+type TraceContext = { requestId: string; method: string; path: string; storageFailed?: boolean };
+
+const storage = new AsyncLocalStorage<TraceContext>();
+
+export function runWithTrace<T>(context: TraceContext, fn: () => T): T {
+  return storage.run(context, fn);
+}
+
+// Work started during a request (including fire-and-forget tasks) keeps the request's ID, so a
+// slow S3 read can be tied back to the push or page load that caused it.
+export function getRequestId(): string | undefined {
+  return storage.getStore()?.requestId;
+}
+
+// isomorphic-git treats any failed read as a missing file, so an S3 outage can surface as an
+// empty tree rather than an error. Storage failures are flagged on the request instead, and
+// the response is swapped for a 503 once the handler finishes.
+export function markStorageFailure() {
+  const context = storage.getStore();
+  if (context) context.storageFailed = true;
+}
+
+export function hasStorageFailure(): boolean {
+  return storage.getStore()?.storageFailed === true;
+}
+
+// For storage calls whose failure the caller handles itself (best-effort cleanup, background work):
+// they run with their own flag, so they keep the request's ID but can't turn its response into a 503.
+export function isolateStorageFailures<T>(fn: () => T): T {
+  const context = storage.getStore();
+  return context ? storage.run({ ...context, storageFailed: false }, fn) : fn();
+}
+
+function emit(span: string, elapsedMs: number | null, fields: Record<string, unknown>) {
+  const context = storage.getStore();
+  console.log(
+    JSON.stringify({
+      level: "trace",
+      span,
+      request_id: context?.requestId ?? null,
+      route: context ? `${context.method} ${context.path}` : null,
+      elapsed_ms: elapsedMs === null ? undefined : Math.round(elapsedMs * 100) / 100,
+      ...fields,
+    })
+  );
+}
+
+// Spans are always logged when tracing is on, and otherwise only once they cross the slow
+// threshold, so production logs carry the outliers without a line per cache hit.
+export function logSpan(span: string, elapsedMs: number, fields: Record<string, unknown> = {}) {
+  if (config.trace.enabled || elapsedMs >= config.trace.slowSpanMs) {
+    emit(span, elapsedMs, fields);
+  }
+}
+
+export function traceEvent(span: string, fields: Record<string, unknown> = {}) {
+  if (config.trace.enabled) {
+    emit(span, null, fields);
+  }
+}
+
+export async function traceSpan<T>(span: string, fields: Record<string, unknown>, fn: () => Promise<T>): Promise<T> {
+  const startedAt = performance.now();
+  try {
+    const result = await fn();
+    logSpan(span, performance.now() - startedAt, fields);
+    return result;
+  } catch (error) {
+    logSpan(span, performance.now() - startedAt, { ...fields, error: error instanceof Error ? error.name : "Error" });
+    throw error;
+  }
+}
+
+const MAX_STATEMENT_LENGTH = 200;
+
+setQueryObserver((query) =>
+  traceEvent("db.query", {
+    statement: query.length > MAX_STATEMENT_LENGTH ? `${query.slice(0, MAX_STATEMENT_LENGTH)}...` : query,
+  })
+);
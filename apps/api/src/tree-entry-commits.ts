@@ -0,0 +1,116 @@
+import { db, treeEntryCommits } from "@gitbruv/db";
+import { and, eq, inArray } from "drizzle-orm";
+import git from "isomorphic-git";
+import { getLastCommitsForEntries, resolveDirectoryTree, type EntryLastCommit, type GitStore } from "./git";
+import { hasStorageFailure } from "./tracing";
+import { trackTask } from "./metrics";
+
+const INSERT_BATCH_SIZE = 500;
+
+const backfilling = new Set<string>();
+
+// Results are keyed by the directory's tree oid rather than the commit, so they survive pushes
+// that don't touch the directory and are shared by every branch that has the same tree. The
+// catch is a directory reverted to an earlier state, which reports the commits from back then;
+// for a "last changed" column that's an acceptable trade for not re-walking on every push.
+export async function getEntryLastCommits(
+  store: GitStore,
+  repositoryId: string,
+  ref: string,
+  filepath: string,
+  names: string[]
+): Promise<{ files: EntryLastCommit[]; pending: boolean }> {
+  if (names.length === 0) {
+    return { files: [], pending: false };
+  }
+
+  const directory = await resolveDirectoryTree(store, ref, filepath);
+  if (!directory) {
+    return { files: [], pending: false };
+  }
+
+  const rows = await db
+    .select()
+    .from(treeEntryCommits)
+    .where(
+      and(
+        eq(treeEntryCommits.repositoryId, repositoryId),
+        eq(treeEntryCommits.treeOid, directory.treeOid),
+        inArray(treeEntryCommits.name, names)
+      )
+    );
+
+  const files: EntryLastCommit[] = [];
+  for (const row of rows) {
+    if (!row.commitOid) continue;
+    files.push({
+      path: filepath ? `${filepath}/${row.name}` : row.name,
+      commitOid: row.commitOid,
+      message: row.message ?? "",
+      authorName: row.authorName ?? "",
+      timestamp: row.timestamp ?? 0,
+    });
+  }
+
+  const pending = rows.length < new Set(names).size;
+  if (pending) {
+    scheduleBackfill(store, repositoryId, directory.commitOid, directory.treeOid, filepath);
+  }
+
+  return { files, pending };
+}
+
+// Resolves every entry of the directory, not just the page that was asked for, since the walk
+// costs the same and the next page then needs no walk at all.
+function scheduleBackfill(store: GitStore, repositoryId: string, commitOid: string, treeOid: string, filepath: string) {
+  const key = `${repositoryId}:${treeOid}`;
+  if (backfilling.has(key)) return;
+  backfilling.add(key);
+
+  const task = () =>
+    (async () => {
+      const existing = await db
+        .select({ name: treeEntryCommits.name })
+        .from(treeEntryCommits)
+        .where(and(eq(treeEntryCommits.repositoryId, repositoryId), eq(treeEntryCommits.treeOid, treeOid)));
+      const known = new Set(existing.map((row) => row.name));
+
+      const { tree } = await git.readTree({ fs: store.fs, dir: store.dir, oid: treeOid });
+      const names = tree.map((entry) => entry.path).filter((name) => !known.has(name));
+      if (names.length === 0) return;
+
+      const found = new Map(
+        (await getLastCommitsForEntries(store, commitOid, filepath, names)).map((entry) => [entry.path.split("/").pop()!, entry])
+      );
+
+      // Entries the walk gave up on are stored without a commit so they aren't walked for again,
+      // which also means a walk cut short by a storage error must not be stored at all.
+      if (hasStorageFailure()) return;
+
+      const values = names.map((name) => {
+        const entry = found.get(name);
+        return {
+          repositoryId,
+          treeOid,
+          name,
+          commitOid: entry?.commitOid ?? null,
+          message: entry?.message ?? null,
+          authorName: entry?.authorName ?? null,
+          timestamp: entry?.timestamp ?? null,
+        };
+      });
+      for (let i = 0; i < values.length; i += INSERT_BATCH_SIZE) {
+        await db
+          .insert(treeEntryCommits)
+          .values(values.slice(i, i + INSERT_BATCH_SIZE))
+          .onConflictDoNothing();
+      }
+    })()
+      .catch((error) => {
+        console.error(`[Git] Failed to backfill last commits for ${store.ownerId}/${store.repoName}:${filepath || "/"}:`, error);
+      })
+      .finally(() => {
+        backfilling.delete(key);
+      });
+  trackTask("tree_entry_commits", task);
+}
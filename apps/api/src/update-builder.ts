@@ -0,0 +1,55 @@
+import type { InferInsertModel } from "drizzle-orm";
+import type { PgTable } from "drizzle-orm/pg-core";
+
+type FieldType = "string" | "boolean" | "number" | "nullable-string" | "nullable-number";
+
+export class UpdateBuilder<TTable extends PgTable> {
+  private values: Partial<InferInsertModel<TTable>> = {};
+
+  set<K extends keyof InferInsertModel<TTable>>(column: K, value: InferInsertModel<TTable>[K] | undefined): this {
+    if (value !== undefined) {
+      this.values[column] = value;
+    }
+    return this;
+  }
+
+  has(column: keyof InferInsertModel<TTable>): boolean {
+    return column in this.values;
+  }
+
+  isEmpty(): boolean {
+    return Object.keys(this.values).length === 0;
+  }
+
+  build(): Partial<InferInsertModel<TTable>> {
+    return { ...this.values };
+  }
+}
+
+export function updateFor<TTable extends PgTable>(_table: TTable): UpdateBuilder<TTable> {
+  return new UpdateBuilder<TTable>();
+}
+
+function matchesType(value: unknown, type: FieldType): boolean {
+  switch (type) {
+    case "string":
+      return typeof value === "string";
+    case "boolean":
+      return typeof value === "boolean";
+    case "number":
+      return typeof value === "number" && Number.isFinite(value);
+    case "nullable-string":
+      return value === null || typeof value === "string";
+    case "nullable-number":
+      return value === null || (typeof value === "number" && Number.isFinite(value));
+  }
+}
+
+export function findInvalidField(body: Record<string, unknown>, schema: Record<string, FieldType>): string | null {
+  for (const [field, type] of Object.entries(schema)) {
+    if (body[field] !== undefined && !matchesType(body[field], type)) {
+      return field;
+    }
+  }
+  return null;
+}
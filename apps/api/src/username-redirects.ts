@@ -0,0 +1,70 @@
+import { db, users, usernameHistory } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import { createMiddleware } from "hono/factory";
+
+// Every route that takes a username in its path, with the username as the first group. The
+// repository patterns skip the literal segments that share their position with an owner.
+const USERNAME_PATHS = [
+  /^\/api\/repositories\/user\/([^/]+)(?:\/|$)/d,
+  /^\/api\/repositories\/(?!user\/|public\/)([^/]+)\/[^/]+/d,
+  /^\/api\/users\/([^/]+)(?:\/|$)/d,
+  /^\/api\/federation\/(?:repositories|users)\/([^/]+)(?:\/|$)/d,
+  /^\/badges\/([^/]+)\//d,
+  /^\/file\/([^/]+)\//d,
+  /^\/([^/]+)\/[^/]+\/(?:info\/refs|git-upload-pack|git-receive-pack)$/d,
+];
+
+// Called when a user gives up `oldUsername`. A name that was released before and taken again
+// belongs to the new holder, so it's dropped from the history then.
+export async function recordUsernameChange(userId: string, oldUsername: string, newUsername: string) {
+  await db.delete(usernameHistory).where(eq(usernameHistory.username, newUsername));
+  if (oldUsername === newUsername) return;
+  await db
+    .insert(usernameHistory)
+    .values({ username: oldUsername, userId })
+    .onConflictDoUpdate({ target: usernameHistory.username, set: { userId, changedAt: new Date() } });
+}
+
+export async function releaseUsername(username: string) {
+  await db.delete(usernameHistory).where(eq(usernameHistory.username, username));
+}
+
+// The user's current username if `username` is one they've renamed away from and nobody holds it now.
+export async function resolveRenamedUsername(username: string): Promise<string | null> {
+  const [previous] = await db
+    .select({ current: users.username })
+    .from(usernameHistory)
+    .innerJoin(users, eq(users.id, usernameHistory.userId))
+    .where(eq(usernameHistory.username, username))
+    .limit(1);
+  if (!previous || previous.current === username) return null;
+
+  const holder = await db.query.users.findFirst({ where: eq(users.username, username), columns: { id: true } });
+  return holder ? null : previous.current;
+}
+
+// Old links and clones keep working after a rename: a request under a previous username that
+// would 404 is sent to the same path under the current one. Waiting for the 404 keeps the lookup
+// off every other request. GET and HEAD get a 301, which git follows for info/refs and then uses
+// the new URL for the rest of the fetch or push; other methods get a 308 so the body is resent.
+export const usernameRedirectMiddleware = createMiddleware(async (c, next) => {
+  await next();
+  if (c.res.status !== 404) return;
+
+  const path = c.req.path;
+  for (const pattern of USERNAME_PATHS) {
+    const match = pattern.exec(path);
+    if (!match?.indices?.[1]) continue;
+
+    const current = await resolveRenamedUsername(decodeURIComponent(match[1]));
+    if (!current) return;
+
+    const [start, end] = match.indices[1];
+    const url = new URL(c.req.url);
+    const location = path.slice(0, start) + encodeURIComponent(current) + path.slice(end) + url.search;
+    const method = c.req.method;
+    c.res = undefined;
+    c.res = c.redirect(location, method === "GET" || method === "HEAD" ? 301 : 308);
+    return;
+  }
+});
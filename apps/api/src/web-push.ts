@@ -0,0 +1,163 @@
+import { db, pushSubscriptions } from "@gitbruv/db";
+import { eq, inArray } from "drizzle-orm";
+import { createECDH, createPrivateKey, createCipheriv, hkdfSync, randomBytes, sign } from "crypto";
+import { request } from "https";
+import { config } from "./config";
+import { publicLookup } from "./git/mirror";
+
+const RECORD_SIZE = 4096;
+const PUSH_TTL_SECONDS = 24 * 60 * 60;
+const VAPID_EXPIRY_SECONDS = 12 * 60 * 60;
+// Push services answer within a second or two; one that holds the socket open mustn't stall
+// delivery to the user's other subscriptions.
+const PUSH_REQUEST_TIMEOUT_MS = 10 * 1000;
+
+export type PushSubscriptionKeys = {
+  endpoint: string;
+  p256dh: string;
+  auth: string;
+};
+
+export type PushPayload = {
+  title: string;
+  body?: string;
+  url?: string;
+  tag?: string;
+};
+
+function base64url(data: Buffer | string): string {
+  return Buffer.from(data).toString("base64url");
+}
+
+export function isWebPushConfigured(): boolean {
+  return !!config.webPush.vapidPublicKey && !!config.webPush.vapidPrivateKey;
+}
+
+function vapidAuthorization(endpoint: string): string {
+  const publicKey = Buffer.from(config.webPush.vapidPublicKey!, "base64url");
+  const privateKey = createPrivateKey({
+    key: {
+      kty: "EC",
+      crv: "P-256",
+      d: config.webPush.vapidPrivateKey!,
+      x: base64url(publicKey.subarray(1, 33)),
+      y: base64url(publicKey.subarray(33, 65)),
+    },
+    format: "jwk",
+  });
+
+  const header = base64url(JSON.stringify({ typ: "JWT", alg: "ES256" }));
+  const claims = base64url(
+    JSON.stringify({
+      aud: new URL(endpoint).origin,
+      exp: Math.floor(Date.now() / 1000) + VAPID_EXPIRY_SECONDS,
+      sub: config.webPush.subject,
+    })
+  );
+  const signature = sign("sha256", Buffer.from(`${header}.${claims}`), { key: privateKey, dsaEncoding: "ieee-p1363" });
+
+  return `vapid t=${header}.${claims}.${base64url(signature)}, k=${config.webPush.vapidPublicKey}`;
+}
+
+// RFC 8291 message encryption using the aes128gcm content coding (RFC 8188), single record.
+// `keys` fixes the otherwise random server key pair and salt, for known-answer tests.
+export function encryptPayload(
+  subscription: Pick<PushSubscriptionKeys, "p256dh" | "auth">,
+  payload: string,
+  keys: { serverPrivateKey?: Buffer; salt?: Buffer } = {}
+): Buffer {
+  const userAgentPublicKey = Buffer.from(subscription.p256dh, "base64url");
+  const authSecret = Buffer.from(subscription.auth, "base64url");
+
+  const ecdh = createECDH("prime256v1");
+  if (keys.serverPrivateKey) {
+    ecdh.setPrivateKey(keys.serverPrivateKey);
+  } else {
+    ecdh.generateKeys();
+  }
+  const serverPublicKey = ecdh.getPublicKey();
+  const sharedSecret = ecdh.computeSecret(userAgentPublicKey);
+
+  const keyInfo = Buffer.concat([Buffer.from("WebPush: info\0"), userAgentPublicKey, serverPublicKey]);
+  const ikm = Buffer.from(hkdfSync("sha256", sharedSecret, authSecret, keyInfo, 32));
+
+  const salt = keys.salt ?? randomBytes(16);
+  const contentKey = Buffer.from(hkdfSync("sha256", ikm, salt, Buffer.from("Content-Encoding: aes128gcm\0"), 16));
+  const nonce = Buffer.from(hkdfSync("sha256", ikm, salt, Buffer.from("Content-Encoding: nonce\0"), 12));
+
+  const cipher = createCipheriv("aes-128-gcm", contentKey, nonce);
+  const ciphertext = Buffer.concat([cipher.update(Buffer.from(payload)), cipher.update(Buffer.from([0x02])), cipher.final(), cipher.getAuthTag()]);
+
+  const header = Buffer.alloc(21);
+  salt.copy(header, 0);
+  header.writeUInt32BE(RECORD_SIZE, 16);
+  header.writeUInt8(serverPublicKey.length, 20);
+
+  return Buffer.concat([header, serverPublicKey, ciphertext]);
+}
+
+// Endpoints come from clients, so the connection goes through the same public-address lookup as
+// mirror fetches: a stored endpoint can never be pointed at an internal host, even by re-resolving.
+export function sendWebPush(subscription: PushSubscriptionKeys, payload: PushPayload): Promise<number> {
+  const url = new URL(subscription.endpoint);
+  if (url.protocol !== "https:") {
+    return Promise.reject(new Error("Push endpoints must use https"));
+  }
+
+  const body = encryptPayload(subscription, JSON.stringify(payload));
+  return new Promise((resolve, reject) => {
+    const req = request(
+      url,
+      {
+        method: "POST",
+        lookup: publicLookup,
+        headers: {
+          Authorization: vapidAuthorization(subscription.endpoint),
+          "Content-Encoding": "aes128gcm",
+          "Content-Type": "application/octet-stream",
+          "Content-Length": String(body.length),
+          TTL: String(PUSH_TTL_SECONDS),
+        },
+      },
+      (response) => {
+        response.resume();
+        resolve(response.statusCode ?? 0);
+      }
+    );
+    req.setTimeout(PUSH_REQUEST_TIMEOUT_MS, () => {
+      req.destroy(new Error(`Push endpoint ${url.host} did not respond within ${PUSH_REQUEST_TIMEOUT_MS} ms`));
+    });
+    req.on("error", reject);
+    req.end(body);
+  });
+}
+
+export async function sendPushToUser(userId: string, payload: PushPayload): Promise<void> {
+  if (!isWebPushConfigured()) {
+    return;
+  }
+
+  const subscriptions = await db.query.pushSubscriptions.findMany({
+    where: eq(pushSubscriptions.userId, userId),
+  });
+
+  const expired: string[] = [];
+  await Promise.all(
+    subscriptions.map(async (subscription) => {
+      try {
+        const status = await sendWebPush(subscription, payload);
+        if (status === 404 || status === 410) {
+          expired.push(subscription.id);
+        } else if (status >= 400) {
+          console.warn(`[WebPush] Delivery to ${new URL(subscription.endpoint).host} failed with status ${status}`);
+        }
+      } catch (error) {
+        console.error("[WebPush] Delivery error:", error);
+      }
+    })
+  );
+
+  if (expired.length > 0) {
+    await db.delete(pushSubscriptions).where(inArray(pushSubscriptions.id, expired));
+  }
+}
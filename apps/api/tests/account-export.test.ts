@@ -0,0 +1,40 @@
+import { describe, expect, test } from "bun:test";
+import { gunzipSync } from "zlib";
+import { createTarGz } from "../src/jobs/account-export";
+
+async function* entries(list: { name: string; data: Buffer }[]) {
+  for (const entry of list) yield entry;
+}
+
+async function readArchive(stream: ReadableStream) {
+  const tar = gunzipSync(Buffer.from(await new Response(stream).arrayBuffer()));
+  const files: { name: string; type: string; data: Buffer }[] = [];
+  for (let offset = 0; offset + 512 <= tar.length && tar[offset] !== 0; ) {
+    const header = tar.subarray(offset, offset + 512);
+    const size = parseInt(header.toString("ascii", 124, 135), 8);
+    files.push({
+      name: header.toString("utf8", 0, 100).replace(/\0.*$/s, ""),
+      type: header.toString("ascii", 156, 157),
+      data: tar.subarray(offset + 512, offset + 512 + size),
+    });
+    offset += 512 + Math.ceil(size / 512) * 512;
+  }
+  return files;
+}
+
+describe("account export archive", () => {
+  test("streams entries into a gzipped tar", async () => {
+    const files = await readArchive(createTarGz(entries([{ name: "alice-export/profile.json", data: Buffer.from("{}\n") }])));
+    expect(files).toEqual([{ name: "alice-export/profile.json", type: "0", data: Buffer.from("{}\n") }]);
+  });
+
+  test("carries long names in a PAX header", async () => {
+    const name = `alice-export/repositories/${"a".repeat(180)}.bundle`;
+    const [pax, file] = await readArchive(createTarGz(entries([{ name, data: Buffer.from("bundle") }])));
+
+    expect(pax.type).toBe("x");
+    const record = pax.data.toString("utf8");
+    expect(record).toBe(`${Buffer.byteLength(record)} path=${name}\n`);
+    expect(file.data.toString()).toBe("bundle");
+  });
+});
@@ -0,0 +1,147 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { randomBytes } from "crypto";
+import { getCached, setCache, repoCache, CACHE_TTL } from "../src/cache";
+import { buildSingleFileCommit, hasTestDatabase, hasTestRedis, pushBranch, request, signUp, ZERO_OID, type TestUser } from "./helpers";
+
+describe.skipIf(!hasTestRedis)("repository cache invalidation", () => {
+  test("removes every key of the repository, past a single SCAN page", async () => {
+    const owner = `owner-${randomBytes(4).toString("hex")}`;
+    const keys = Array.from({ length: 1200 }, (_, i) => repoCache.fileKey(owner, "repo", "main", `file-${i}.txt`));
+    await Promise.all(keys.map((key) => setCache(key, { content: "x", oid: "0".repeat(40) }, CACHE_TTL.file)));
+    await setCache(repoCache.branchesKey(owner, "repo"), ["main"], CACHE_TTL.branches);
+    await setCache(repoCache.fileKey(owner, "repo-2", "main", "file-0.txt"), { content: "kept" }, CACHE_TTL.file);
+    const version = await repoCache.getRefsVersion(owner, "repo");
+
+    await repoCache.invalidateRepo(owner, "repo");
+
+    expect(await Promise.all(keys.map((key) => getCached(key)))).toEqual(keys.map(() => null));
+    expect(await getCached(repoCache.branchesKey(owner, "repo"))).toBeNull();
+    expect(await getCached(repoCache.fileKey(owner, "repo-2", "main", "file-0.txt"))).toEqual({ content: "kept" });
+    expect(await repoCache.getRefsVersion(owner, "repo")).toBe(version + 1);
+  });
+
+  test("keeps counting refs versions up rather than restarting them", async () => {
+    const owner = `owner-${randomBytes(4).toString("hex")}`;
+    for (let i = 0; i < 3; i++) await repoCache.bumpRefsVersion(owner, "repo");
+
+    await repoCache.invalidateRepo(owner, "repo");
+
+    expect(await repoCache.getRefsVersion(owner, "repo")).toBe(4);
+  });
+});
+
+describe.skipIf(!hasTestDatabase || !hasTestRedis)("repository settings drop cached content", () => {
+  let owner: TestUser;
+  let repoId: string;
+  let repo = "cached-repo";
+
+  beforeAll(async () => {
+    owner = await signUp("cacher");
+    const created = await request("/api/repositories", { method: "POST", user: owner, json: { name: repo, visibility: "public" } });
+    repoId = ((await created.json()) as { id: string }).id;
+    const commit = buildSingleFileCommit("README.md", "# Cached\n", "Initial commit", { name: owner.username, email: owner.email });
+    await pushBranch(owner, repo, "main", ZERO_OID, commit);
+  });
+
+  async function readmeIsCached() {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/file?path=README.md`);
+    expect(response.status).toBe(200);
+    return (await getCached(repoCache.fileKey(owner.id, repo, "main", "README.md"))) !== null;
+  }
+
+  test("changing visibility invalidates the repository", async () => {
+    expect(await readmeIsCached()).toBe(true);
+
+    const response = await request(`/api/repositories/${repoId}`, { method: "PATCH", user: owner, json: { visibility: "private" } });
+    expect(response.status).toBe(200);
+    expect(await getCached(repoCache.fileKey(owner.id, repo, "main", "README.md"))).toBeNull();
+
+    await request(`/api/repositories/${repoId}`, { method: "PATCH", user: owner, json: { visibility: "public" } });
+  });
+
+  test("renaming invalidates the old name", async () => {
+    expect(await readmeIsCached()).toBe(true);
+
+    const renamed = `${repo}-renamed`;
+    const response = await request(`/api/repositories/${repoId}`, { method: "PATCH", user: owner, json: { name: renamed } });
+    expect(response.status).toBe(200);
+    expect(await getCached(repoCache.fileKey(owner.id, repo, "main", "README.md"))).toBeNull();
+    repo = renamed;
+  });
+});
+
+describe.skipIf(!hasTestDatabase)("raw file caching", () => {
+  let owner: TestUser;
+  let blobOid: string;
+
+  beforeAll(async () => {
+    owner = await signUp("raw");
+    const commit = buildSingleFileCommit("notes.txt", "raw contents\n", "Initial commit", { name: owner.username, email: owner.email });
+    blobOid = commit.blobOid;
+    for (const [name, visibility] of [["raw-public", "public"], ["raw-private", "private"]]) {
+      await request("/api/repositories", { method: "POST", user: owner, json: { name, visibility } });
+      await pushBranch(owner, name, "main", ZERO_OID, commit);
+    }
+  });
+
+  test("public files revalidate against the blob oid", async () => {
+    const response = await request(`/file/${owner.username}/raw-public/main/notes.txt`);
+    expect(response.status).toBe(200);
+    expect(response.headers.get("Cache-Control")).toBe("public, no-cache");
+    expect(response.headers.get("ETag")).toBe(`"${blobOid}"`);
+
+    const revalidated = await request(`/file/${owner.username}/raw-public/main/notes.txt`, { headers: { "If-None-Match": `"${blobOid}"` } });
+    expect(revalidated.status).toBe(304);
+  });
+
+  test("private files are never stored or revalidated", async () => {
+    const response = await request(`/file/${owner.username}/raw-private/main/notes.txt`, { user: owner });
+    expect(response.status).toBe(200);
+    expect(response.headers.get("Cache-Control")).toBe("private, no-store");
+    expect(response.headers.get("ETag")).toBeNull();
+
+    const conditional = await request(`/file/${owner.username}/raw-private/main/notes.txt`, {
+      user: owner,
+      headers: { "If-None-Match": `"${blobOid}"` },
+    });
+    expect(conditional.status).toBe(200);
+    expect(await conditional.text()).toBe("raw contents\n");
+  });
+
+  test("private files stay hidden from anonymous readers", async () => {
+    const response = await request(`/file/${owner.username}/raw-private/main/notes.txt`);
+    expect(response.status).toBe(404);
+  });
+});
+
+// The advertisement is cached per refs version; a push must bump it so the next fetch sees the
+// new tip rather than the copy cached by the fetch before it.
+describe.skipIf(!hasTestDatabase)("refs advertisement cache", () => {
+  let owner: TestUser;
+  const repo = "advertised";
+
+  async function advertisement() {
+    const response = await request(`/${owner.username}/${repo}/info/refs?service=git-upload-pack`);
+    expect(response.status).toBe(200);
+    return response.text();
+  }
+
+  beforeAll(async () => {
+    owner = await signUp("advertiser");
+    await request("/api/repositories", { method: "POST", user: owner, json: { name: repo, visibility: "public" } });
+  });
+
+  test("a fetch after a push advertises the new tip", async () => {
+    const first = buildSingleFileCommit("README.md", "one\n", "First", { name: owner.username, email: owner.email });
+    await pushBranch(owner, repo, "main", ZERO_OID, first);
+    expect(await advertisement()).toContain(`${first.commitOid} refs/heads/main`);
+
+    const second = buildSingleFileCommit("README.md", "two\n", "Second", { name: owner.username, email: owner.email });
+    const { report } = await pushBranch(owner, repo, "feature", ZERO_OID, second);
+    expect(report).toContain("ok refs/heads/feature");
+
+    const refs = await advertisement();
+    expect(refs).toContain(`${first.commitOid} refs/heads/main`);
+    expect(refs).toContain(`${second.commitOid} refs/heads/feature`);
+  });
+});
@@ -0,0 +1,51 @@
+import { describe, expect, test } from "bun:test";
+import { ownersForPaths, parseCodeOwners, patternToRegex } from "../src/code-owners";
+
+// Examples from GitHub's CODEOWNERS documentation.
+const cases: [pattern: string, path: string, matches: boolean][] = [
+  ["*", "README.md", true],
+  ["*", "src/deep/file.ts", true],
+  ["*.js", "index.js", true],
+  ["*.js", "src/app/index.js", true],
+  ["*.js", "index.ts", false],
+  ["/build/logs/", "build/logs/today.log", true],
+  ["/build/logs/", "build/logs/2024/today.log", true],
+  ["/build/logs/", "src/build/logs/today.log", false],
+  ["docs/*", "docs/getting-started.md", true],
+  ["docs/*", "docs/build-app/troubleshooting.md", false],
+  ["docs/*", "src/docs/getting-started.md", false],
+  ["apps/", "apps/web/page.tsx", true],
+  ["apps/", "packages/apps/index.ts", true],
+  ["/docs/", "docs/guide/intro.md", true],
+  ["/docs/", "src/docs/intro.md", false],
+  ["/scripts/", "scripts/deploy.sh", true],
+  ["**/logs", "logs/a.log", true],
+  ["**/logs", "deeply/nested/logs/a.log", true],
+  ["**/logs", "logsfile", false],
+  ["/apps/github", "apps/github/index.ts", true],
+  ["/apps/github", "apps/github", true],
+  ["/apps/github", "apps/githubber/index.ts", false],
+  ["src/?.ts", "src/a.ts", true],
+  ["src/?.ts", "src/ab.ts", false],
+];
+
+describe("patternToRegex", () => {
+  for (const [pattern, path, matches] of cases) {
+    test(`${pattern} ${matches ? "matches" : "does not match"} ${path}`, () => {
+      expect(patternToRegex(pattern, true).test(path)).toBe(matches);
+    });
+  }
+
+  test("keeps gitignore semantics for a trailing /* outside CODEOWNERS", () => {
+    expect(patternToRegex("docs/*").test("docs/build-app/troubleshooting.md")).toBe(true);
+  });
+});
+
+describe("ownersForPaths", () => {
+  test("the last matching rule wins and owners are lowercased", () => {
+    const rules = parseCodeOwners(["* @Default", "*.ts @TS-Owner @team/org", "/src/generated/ # nobody owns generated code"].join("\n"));
+    expect([...ownersForPaths(rules, ["README.md"])]).toEqual(["default"]);
+    expect([...ownersForPaths(rules, ["src/index.ts"])]).toEqual(["ts-owner"]);
+    expect([...ownersForPaths(rules, ["src/generated/api.ts"])]).toEqual([]);
+  });
+});
@@ -0,0 +1,46 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { db, repositories, stars } from "@gitbruv/db";
+import { sql } from "drizzle-orm";
+import { hasTestDatabase, request, signUp, type TestUser } from "./helpers";
+
+type ActivityPage = {
+  document: { next: string | null; orderedItems: { type: string; actor: string; object: string; published: string }[] };
+};
+
+describe.skipIf(!hasTestDatabase)("federation activity", () => {
+  let owner: TestUser;
+  let stargazer: TestUser;
+
+  // More rows than a page share one microsecond timestamp, and stars share it with the
+  // repositories, so a page boundary has to fall inside the tie.
+  beforeAll(async () => {
+    owner = await signUp("federated");
+    stargazer = await signUp("stargazer");
+    const tied = sql`'2001-02-03 04:05:06.123456'::timestamp`;
+    const created = await db
+      .insert(repositories)
+      .values(Array.from({ length: 70 }, (_, i) => ({ name: `activity-${i}`, ownerId: owner.id, visibility: "public" as const, createdAt: tied })))
+      .returning({ id: repositories.id });
+    await db.insert(stars).values(created.slice(0, 40).map((repo) => ({ userId: stargazer.id, repositoryId: repo.id, createdAt: tied })));
+  });
+
+  test("walking every page returns each item exactly once", async () => {
+    const seen = new Map<string, number>();
+    let cursor: string | null = null;
+    for (let pages = 0; pages < 1000; pages++) {
+      const response = await request(`/api/federation/activity${cursor ? `?cursor=${cursor}` : ""}`);
+      expect(response.status).toBe(200);
+      const { document } = (await response.json()) as ActivityPage;
+      if (document.orderedItems.length === 0) break;
+      for (const item of document.orderedItems) {
+        const key = `${item.type} ${item.actor} ${item.object}`;
+        seen.set(key, (seen.get(key) ?? 0) + 1);
+      }
+      cursor = document.next;
+    }
+
+    const ours = [...seen].filter(([key]) => key.includes(`/users/${owner.username} `) || key.includes(`/users/${stargazer.username} `));
+    expect(ours).toHaveLength(110);
+    expect([...seen.values()].every((count) => count === 1)).toBe(true);
+  });
+});
@@ -0,0 +1,119 @@
+import { describe, expect, test } from "bun:test";
+import { parseOperation, GraphQLSyntaxError } from "../src/graphql/parser";
+import { execute, type GraphQLSchema } from "../src/graphql/execute";
+
+describe("parseOperation", () => {
+  test("parses variables, aliases, arguments and nested selections", () => {
+    const operation = parseOperation(`
+      query Repos($limit: Int = 5, $tags: [String!]) {
+        top: repositories(limit: $limit, filter: { topics: ["git", "rust"], archived: false }) { name }
+      }
+    `);
+
+    expect(operation.name).toBe("Repos");
+    expect(operation.variableDefaults).toEqual({ limit: { kind: "literal", value: 5 }, tags: undefined });
+    expect(operation.selections).toEqual([
+      {
+        alias: "top",
+        name: "repositories",
+        args: {
+          limit: { kind: "variable", name: "limit" },
+          filter: {
+            kind: "object",
+            fields: {
+              topics: { kind: "list", values: [{ kind: "literal", value: "git" }, { kind: "literal", value: "rust" }] },
+              archived: { kind: "literal", value: false },
+            },
+          },
+        },
+        selections: [{ alias: "name", name: "name", args: {}, selections: null }],
+      },
+    ]);
+  });
+
+  test("picks the named operation", () => {
+    expect(parseOperation("query A { a } query B { b }", "B").selections[0].name).toBe("b");
+    expect(() => parseOperation("query A { a } query B { b }")).toThrow(GraphQLSyntaxError);
+  });
+
+  test("rejects unsupported syntax", () => {
+    expect(() => parseOperation("mutation { a }")).toThrow("mutation operations are not supported");
+    expect(() => parseOperation("{ a { ...F } }")).toThrow("Fragments are not supported");
+    expect(() => parseOperation("{ a @skip(if: true) }")).toThrow("Directives are not supported");
+    expect(() => parseOperation("{ a { b }")).toThrow(GraphQLSyntaxError);
+  });
+
+  test("rejects deep nesting before it can overflow the stack", () => {
+    const depth = 100_000;
+    expect(() => parseOperation("{ a".repeat(depth) + " }".repeat(depth))).toThrow("nests deeper");
+    expect(() => parseOperation(`{ a(x: ${"[".repeat(depth)}1${"]".repeat(depth)}) }`)).toThrow("nests deeper");
+    expect(() => parseOperation(`query($x: ${"[".repeat(depth)}Int${"]".repeat(depth)}) { a }`)).toThrow("nests deeper");
+  });
+
+  test("rejects documents with too many fields", () => {
+    expect(() => parseOperation(`{ ${Array.from({ length: 600 }, (_, i) => `f${i}: a`).join(" ")} }`)).toThrow("more than");
+  });
+});
+
+type Node = { name: string; children?: Node[] };
+
+const schema: GraphQLSchema<null> = {
+  query: "Query",
+  types: {
+    Query: {
+      root: { type: "Node", resolve: () => ({ name: "root" }) },
+      nodes: {
+        type: "[Node]",
+        resolve: (_parent, args) => Array.from({ length: Number(args.limit ?? 2) }, (_, i) => ({ name: `n${i}` })),
+      },
+      failing: {
+        type: "String",
+        resolve: () => {
+          throw new Error("boom");
+        },
+      },
+    },
+    Node: {
+      name: { type: "String" },
+      self: { type: "Node", resolve: (parent: Node) => parent },
+      children: { type: "[Node]", resolve: (_parent, args) => Array.from({ length: Number(args.limit ?? 1) }, (_, i) => ({ name: `c${i}` })) },
+    },
+  },
+};
+
+describe("execute", () => {
+  const run = (query: string, variables: Record<string, unknown> = {}) => execute(schema, parseOperation(query), variables, null);
+
+  test("resolves aliases, lists and variables", async () => {
+    const result = await run("query($n: Int) { root { name } many: nodes(limit: $n) { name __typename } }", { n: 3 });
+    expect(result).toEqual({
+      data: {
+        root: { name: "root" },
+        many: [
+          { name: "n0", __typename: "Node" },
+          { name: "n1", __typename: "Node" },
+          { name: "n2", __typename: "Node" },
+        ],
+      },
+    });
+  });
+
+  test("reports unknown fields and resolver failures without leaking errors", async () => {
+    const result = await run("{ missing failing root { name } }");
+    expect(result.data).toEqual({ missing: null, failing: null, root: { name: "root" } });
+    expect(result.errors).toEqual([
+      { message: 'Cannot query field "missing" on type "Query"', path: ["missing"] },
+      { message: "Internal server error", path: ["failing"] },
+    ]);
+  });
+
+  test("stops at the maximum depth", async () => {
+    const result = await run(`{ root { ${"self { ".repeat(12)}name${" }".repeat(12)} } }`);
+    expect(result.errors?.[0].message).toContain("maximum depth");
+  });
+
+  test("refuses queries whose lists could multiply past the cost limit", async () => {
+    const result = await run("{ nodes(limit: 100) { children(limit: 100) { children(limit: 100) { name } } } }");
+    expect(result).toEqual({ data: null, errors: [{ message: expect.stringContaining("too expensive") }] });
+  });
+});
@@ -0,0 +1,143 @@
+import { createHash, randomBytes } from "crypto";
+import { deflateSync } from "zlib";
+import { createApp } from "../src/app";
+
+export const hasTestDatabase = Boolean(process.env.TEST_DATABASE_URL);
+export const hasTestRedis = Boolean(process.env.TEST_REDIS_URL);
+
+export const app = createApp();
+
+export type TestUser = {
+  id: string;
+  username: string;
+  email: string;
+  password: string;
+  cookie: string;
+};
+
+export function request(path: string, init: RequestInit & { user?: TestUser; json?: unknown } = {}) {
+  const { user, json, ...rest } = init;
+  const headers = new Headers(rest.headers);
+  if (user) headers.set("Cookie", user.cookie);
+  if (json !== undefined) headers.set("Content-Type", "application/json");
+  return app.request(path, { ...rest, headers, body: json !== undefined ? JSON.stringify(json) : rest.body });
+}
+
+// Usernames get a random suffix so runs don't collide in a database that isn't reset between them.
+export async function signUp(prefix = "user"): Promise<TestUser> {
+  const username = `${prefix}-${randomBytes(4).toString("hex")}`;
+  const email = `${username}@example.com`;
+  const password = "correct-horse-battery-staple";
+
+  const response = await request("/api/auth/sign-up/email", {
+    method: "POST",
+    json: { email, password, name: username, username },
+  });
+  if (!response.ok) {
+    throw new Error(`Sign-up failed with ${response.status}: ${await response.text()}`);
+  }
+
+  const body = (await response.json()) as { user: { id: string } };
+  const cookie = response.headers
+    .getSetCookie()
+    .map((header) => header.split(";")[0])
+    .join("; ");
+  return { id: body.user.id, username, email, password, cookie };
+}
+
+type PackObject = { type: "commit" | "tree" | "blob"; content: Buffer };
+
+const OBJECT_TYPES = { commit: 1, tree: 2, blob: 3 } as const;
+
+export function objectId(object: PackObject) {
+  return createHash("sha1")
+    .update(`${object.type} ${object.content.length}\0`)
+    .update(object.content)
+    .digest("hex");
+}
+
+// A version 2 packfile with every object stored whole (no deltas), which is all the server
+// needs to accept and index a push.
+export function buildPack(objects: PackObject[]): Buffer {
+  const header = Buffer.alloc(12);
+  header.write("PACK", 0, "ascii");
+  header.writeUInt32BE(2, 4);
+  header.writeUInt32BE(objects.length, 8);
+
+  const entries = objects.map((object) => {
+    let size = object.content.length;
+    const bytes = [(OBJECT_TYPES[object.type] << 4) | (size & 0x0f)];
+    size >>= 4;
+    while (size > 0) {
+      bytes[bytes.length - 1] |= 0x80;
+      bytes.push(size & 0x7f);
+      size >>= 7;
+    }
+    return Buffer.concat([Buffer.from(bytes), deflateSync(object.content)]);
+  });
+
+  const body = Buffer.concat([header, ...entries]);
+  return Buffer.concat([body, createHash("sha1").update(body).digest()]);
+}
+
+export type TestCommit = { commitOid: string; blobOid: string; pack: Buffer };
+
+// One commit on an empty history with a single file at the root.
+export function buildSingleFileCommit(path: string, contents: string, message: string, author: { name: string; email: string }): TestCommit {
+  const blob: PackObject = { type: "blob", content: Buffer.from(contents) };
+  const blobOid = objectId(blob);
+  const tree: PackObject = {
+    type: "tree",
+    content: Buffer.concat([Buffer.from(`100644 ${path}\0`), Buffer.from(blobOid, "hex")]),
+  };
+  const signature = `${author.name} <${author.email}> 1700000000 +0000`;
+  const commit: PackObject = {
+    type: "commit",
+    content: Buffer.from(`tree ${objectId(tree)}\nauthor ${signature}\ncommitter ${signature}\n\n${message}\n`),
+  };
+  return { commitOid: objectId(commit), blobOid, pack: buildPack([commit, tree, blob]) };
+}
+
+function pktLine(line: string) {
+  return `${(line.length + 4).toString(16).padStart(4, "0")}${line}`;
+}
+
+// Pushes one ref update. `pack` is null for updates that need no objects (deletes, or a new ref at
+// a commit the server already has), which is how git sends them too.
+export async function pushRef(
+  user: TestUser,
+  repo: string,
+  update: { ref: string; oldOid: string; newOid: string; pack: Buffer | null },
+  capabilities = "report-status"
+) {
+  const commands = pktLine(`${update.oldOid} ${update.newOid} ${update.ref}\0${capabilities}\n`) + "0000";
+  const response = await request(`/${user.username}/${repo}/git-receive-pack`, {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/x-git-receive-pack-request",
+      Authorization: `Basic ${Buffer.from(`${user.username}:${user.password}`).toString("base64")}`,
+    },
+    body: Buffer.concat([Buffer.from(commands, "ascii"), update.pack ?? Buffer.alloc(0)]),
+  });
+  return { status: response.status, report: await response.text() };
+}
+
+export function pushBranch(user: TestUser, repo: string, branch: string, oldOid: string, commit: TestCommit) {
+  return pushRef(user, repo, { ref: `refs/heads/${branch}`, oldOid, newOid: commit.commitOid, pack: commit.pack });
+}
+
+export const ZERO_OID = "0".repeat(40);
+
+// One stateless upload-pack round that sends `done` straight away, as a clone does.
+export async function fetchPack(owner: string, repo: string, wants: string[], user?: TestUser) {
+  const lines = wants.map((oid, index) => pktLine(index === 0 ? `want ${oid} side-band-64k\n` : `want ${oid}\n`));
+  const response = await request(`/${owner}/${repo}/git-upload-pack`, {
+    method: "POST",
+    headers: {
+      "Content-Type": "application/x-git-upload-pack-request",
+      ...(user ? { Authorization: `Basic ${Buffer.from(`${user.username}:${user.password}`).toString("base64")}` } : {}),
+    },
+    body: `${lines.join("")}0000${pktLine("done\n")}`,
+  });
+  return { status: response.status, body: Buffer.from(await response.arrayBuffer()) };
+}
@@ -0,0 +1,20 @@
+import { describe, expect, test } from "bun:test";
+import { resolveHighlightLanguage } from "../src/highlight";
+
+describe("resolveHighlightLanguage", () => {
+  test("uses an explicit language shiki knows", () => {
+    expect(resolveHighlightLanguage("rust", "src/main.py")).toBe("rust");
+    expect(resolveHighlightLanguage("plaintext", "src/main.rs")).toBe("plaintext");
+  });
+
+  test("falls back to the file extension for unknown languages", () => {
+    expect(resolveHighlightLanguage("not-a-language", "src/main.rs")).toBe("rust");
+    expect(resolveHighlightLanguage(undefined, "LICENSE")).toBe("plaintext");
+  });
+
+  test("ignores inherited object keys", () => {
+    for (const language of ["toString", "constructor", "__proto__", "hasOwnProperty"]) {
+      expect(resolveHighlightLanguage(language, undefined)).toBe("plaintext");
+    }
+  });
+});
@@ -0,0 +1,100 @@
+import { describe, expect, test } from "bun:test";
+import { parseIssueForm, validateIssueFormValues, renderIssueFormBody, type IssueForm, type IssueFormValues } from "../src/issue-forms";
+
+const BUG_REPORT = `
+name: Bug report
+description: Something is broken
+labels: [bug, triage]
+body:
+  - type: markdown
+    attributes:
+      value: Thanks for reporting!
+  - type: input
+    id: version
+    attributes:
+      label: Version
+    validations:
+      required: true
+  - type: dropdown
+    id: os
+    attributes:
+      label: Operating system
+      options: [Linux, macOS, Windows]
+  - type: checkboxes
+    attributes:
+      label: Checklist
+      options:
+        - label: I searched existing issues
+          required: true
+        - label: I can reproduce it
+`;
+
+function parse(source: string) {
+  return parseIssueForm("bug.yml", source);
+}
+
+describe("parseIssueForm", () => {
+  test("parses fields, labels and options", () => {
+    const form = parse(BUG_REPORT)!;
+    expect(form.id).toBe("bug");
+    expect(form.name).toBe("Bug report");
+    expect(form.labels).toEqual(["bug", "triage"]);
+    expect(form.body.map((field) => field.type)).toEqual(["markdown", "input", "dropdown", "checkboxes"]);
+    expect(form.body[2].attributes.options).toEqual(["Linux", "macOS", "Windows"]);
+  });
+
+  test("rejects invalid YAML and forms without a name or body", () => {
+    expect(parse("name: [unclosed")).toBeNull();
+    expect(parse("body: []")).toBeNull();
+    expect(parse("name: No body")).toBeNull();
+  });
+
+  test("rejects unknown field types, missing labels and duplicate ids", () => {
+    expect(parse("name: x\nbody:\n  - type: slider\n    attributes:\n      label: A")).toBeNull();
+    expect(parse("name: x\nbody:\n  - type: input\n    attributes: {}")).toBeNull();
+    expect(parse("name: x\nbody:\n  - type: input\n    id: a\n    attributes:\n      label: A\n  - type: input\n    id: a\n    attributes:\n      label: B")).toBeNull();
+  });
+
+  test("rejects options that are neither strings nor labelled objects", () => {
+    expect(parse("name: x\nbody:\n  - type: dropdown\n    attributes:\n      label: A\n      options: [null]")).toBeNull();
+    expect(parse("name: x\nbody:\n  - type: dropdown\n    attributes:\n      label: A\n      options: [1, 2]")).toBeNull();
+    expect(parse("name: x\nbody:\n  - type: checkboxes\n    attributes:\n      label: A\n      options:\n        - required: true")).toBeNull();
+    expect(parse("name: x\nbody:\n  - type: dropdown\n    attributes:\n      label: A\n      options: Linux")).toBeNull();
+  });
+});
+
+describe("validateIssueFormValues", () => {
+  const form = parse(BUG_REPORT) as IssueForm;
+  const valid = { version: "1.2.3", os: "Linux", "field-3": [true, false] };
+
+  test("accepts a complete submission and renders it", () => {
+    expect(validateIssueFormValues(form, valid)).toEqual([]);
+    expect(renderIssueFormBody(form, valid)).toBe(
+      "### Version\n\n1.2.3\n\n### Operating system\n\nLinux\n\n### Checklist\n\n- [x] I searched existing issues\n- [ ] I can reproduce it"
+    );
+  });
+
+  test("reports required fields and required checkboxes", () => {
+    expect(validateIssueFormValues(form, { os: "Linux" })).toEqual([
+      "Version is required",
+      'Checklist: "I searched existing issues" must be checked',
+    ]);
+  });
+
+  test("rejects selections that aren't options", () => {
+    expect(validateIssueFormValues(form, { ...valid, os: "BeOS" })).toEqual(["Operating system has an invalid selection"]);
+    expect(validateIssueFormValues(form, { ...valid, os: ["Linux", "macOS"] })).toEqual(["Operating system accepts a single selection"]);
+  });
+
+  test("rejects values of the wrong shape instead of throwing", () => {
+    const malformed = (values: unknown) => validateIssueFormValues(form, values as IssueFormValues);
+    expect(malformed({ ...valid, os: 5 })).toEqual(["Operating system has an invalid selection"]);
+    expect(malformed({ ...valid, os: { label: "Linux" } })).toEqual(["Operating system has an invalid selection"]);
+    expect(malformed({ ...valid, os: null })).toEqual(["Operating system has an invalid selection"]);
+    expect(malformed({ ...valid, version: 3 })).toEqual(["Version must be text"]);
+    expect(malformed({ ...valid, "field-3": "yes" })).toEqual(["Checklist has invalid checkbox values"]);
+    expect(malformed({ ...valid, "field-3": [1] })).toEqual(["Checklist has invalid checkbox values"]);
+    expect(malformed("fields")).toEqual(["Form fields must be an object"]);
+    expect(malformed(null)).toEqual(["Form fields must be an object"]);
+  });
+});
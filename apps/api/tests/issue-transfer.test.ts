@@ -0,0 +1,84 @@
+import { beforeAll, describe, expect, test } from "bun:test";
+import { issuesToCsv, parseIssueImport, type IssueExportRecord } from "../src/issue-transfer";
+import { hasTestDatabase, request, signUp, type TestUser } from "./helpers";
+
+const record = (overrides: Partial<IssueExportRecord>): IssueExportRecord => ({
+  number: 1,
+  title: "Title",
+  body: null,
+  state: "open",
+  locked: false,
+  author: "alice",
+  labels: [],
+  assignees: [],
+  createdAt: "2024-01-01T00:00:00.000Z",
+  updatedAt: "2024-01-01T00:00:00.000Z",
+  closedAt: null,
+  comments: [],
+  ...overrides,
+});
+
+describe("issuesToCsv", () => {
+  test("quotes cells a spreadsheet would evaluate as formulas", () => {
+    const [, row] = issuesToCsv([record({ title: "=HYPERLINK(\"http://x\")", body: "@SUM(A1)", author: "-user" })]).split("\r\n");
+    expect(row).toStartWith(`1,"'=HYPERLINK(""http://x"")",open,false,'-user,`);
+    expect(row).toEndWith(",0,'@SUM(A1)");
+  });
+
+  test("leaves ordinary text alone", () => {
+    const [, row] = issuesToCsv([record({ title: "Crash on start", body: "a - b" })]).split("\r\n");
+    expect(row).toStartWith("1,Crash on start,open,false,alice,");
+    expect(row).toEndWith(",0,a - b");
+  });
+});
+
+describe("parseIssueImport", () => {
+  test("keeps in-range numbers and renumbers invalid ones", () => {
+    const parsed = parseIssueImport([{ title: "Kept", number: 7 }, { title: "Renumbered", number: -1 }, { title: "Also", number: 1.5 }]);
+    expect("issues" in parsed && parsed.issues.map((issue) => issue.number)).toEqual([7, null, null]);
+  });
+
+  test("rejects numbers an int4 column can't hold, naming the record", () => {
+    for (const number of [2 ** 31, 2 ** 31 - 1, 2 ** 53]) {
+      const parsed = parseIssueImport([{ title: "Fine", number: 1 }, { title: "Too big", number }]);
+      expect("error" in parsed && parsed.error).toStartWith(`Issue 2 ("Too big") has number ${number}`);
+    }
+  });
+});
+
+describe.skipIf(!hasTestDatabase)("issue export", () => {
+  let owner: TestUser;
+  let other: TestUser;
+  const repo = "export-masking";
+
+  beforeAll(async () => {
+    owner = await signUp("owner");
+    other = await signUp("other");
+    await request("/api/repositories", { method: "POST", user: owner, json: { name: repo, visibility: "public" } });
+
+    const issue = await (
+      await request(`/api/repositories/${owner.username}/${repo}/issues`, { method: "POST", user: other, json: { title: "Spam below" } })
+    ).json();
+    const comment = await (await request(`/api/issues/${issue.id}/comments`, { method: "POST", user: other, json: { body: "buy now" } })).json();
+    const hidden = await request(`/api/issues/comments/${comment.id}/hide`, { method: "POST", user: owner, json: { reason: "spam" } });
+    expect(hidden.status).toBe(200);
+  });
+
+  const exportedComment = async (user?: TestUser) => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/issues/export`, { user });
+    expect(response.status).toBe(200);
+    return (await response.json()).issues[0].comments[0];
+  };
+
+  test("masks hidden comment bodies for everyone but the owner", async () => {
+    for (const viewer of [undefined, other]) {
+      expect(await exportedComment(viewer)).toMatchObject({ body: null, hidden: { reason: "spam" } });
+    }
+    expect(await exportedComment(owner)).toMatchObject({ body: "buy now", hidden: { reason: "spam" } });
+  });
+
+  test("leaves hidden text out of the CSV too", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/issues/export?format=csv`, { user: other });
+    expect(await response.text()).not.toContain("buy now");
+  });
+});
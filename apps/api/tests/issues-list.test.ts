@@ -0,0 +1,63 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { db, issues, labels, issueLabels, issueAssignees, issueComments, setQueryObserver } from "@gitbruv/db";
+import { hasTestDatabase, request, signUp, type TestUser } from "./helpers";
+
+// Listing loads authors, labels, assignees, reactions and comment counts in batches; a page of
+// 100 issues has to cost the same handful of queries as a page of one.
+const QUERY_BUDGET = 15;
+
+describe.skipIf(!hasTestDatabase)("issue listing", () => {
+  let owner: TestUser;
+  let other: TestUser;
+  const repo = "many-issues";
+
+  beforeAll(async () => {
+    owner = await signUp("lister");
+    other = await signUp("reporter");
+    const created = await request("/api/repositories", { method: "POST", user: owner, json: { name: repo, visibility: "public" } });
+    const { id: repositoryId } = (await created.json()) as { id: string };
+
+    const [bug, docs] = await db
+      .insert(labels)
+      .values([
+        { repositoryId, name: "list-bug" },
+        { repositoryId, name: "list-docs" },
+      ])
+      .returning();
+    const rows = await db
+      .insert(issues)
+      .values(
+        Array.from({ length: 100 }, (_, i) => ({
+          repositoryId,
+          number: i + 1,
+          title: `Issue ${i + 1}`,
+          authorId: i % 2 === 0 ? owner.id : other.id,
+        }))
+      )
+      .returning({ id: issues.id });
+    await db.insert(issueLabels).values(rows.flatMap((row, i) => [{ issueId: row.id, labelId: bug.id }, ...(i % 3 === 0 ? [{ issueId: row.id, labelId: docs.id }] : [])]));
+    await db.insert(issueAssignees).values(rows.map((row) => ({ issueId: row.id, userId: owner.id })));
+    await db.insert(issueComments).values(rows.map((row) => ({ issueId: row.id, authorId: other.id, body: "Same here" })));
+  });
+
+  test("lists 100 issues with a bounded number of queries", async () => {
+    let queries = 0;
+    const previous = setQueryObserver((query) => {
+      queries++;
+      previous?.(query);
+    });
+
+    let response: Response;
+    try {
+      response = await request(`/api/repositories/${owner.username}/${repo}/issues?limit=100`);
+    } finally {
+      setQueryObserver(previous);
+    }
+
+    expect(response.status).toBe(200);
+    const body = (await response.json()) as { issues: { labels: unknown[]; assignees: unknown[] }[] };
+    expect(body.issues).toHaveLength(100);
+    expect(body.issues.every((issue) => issue.labels.length > 0 && issue.assignees.length === 1)).toBe(true);
+    expect(queries).toBeLessThanOrEqual(QUERY_BUDGET);
+  });
+});
@@ -0,0 +1,38 @@
+import { describe, expect, test } from "bun:test";
+import { findLargeFiles, largeFileWarnings } from "../src/git/large-files";
+import { objectId } from "./helpers";
+
+function tree(entries: { mode: string; name: string; oid: string }[]) {
+  const content = Buffer.concat(entries.map((entry) => Buffer.concat([Buffer.from(`${entry.mode} ${entry.name}\0`), Buffer.from(entry.oid, "hex")])));
+  return { oid: objectId({ type: "tree", content }), type: "tree", data: content };
+}
+
+function blob(content: Buffer) {
+  return { oid: objectId({ type: "blob", content }), type: "blob", data: content };
+}
+
+describe("findLargeFiles", () => {
+  const large = blob(Buffer.alloc(2048, 1));
+  const small = blob(Buffer.from("small\n"));
+  const assets = tree([{ mode: "100644", name: "video.mp4", oid: large.oid }]);
+  const root = tree([
+    { mode: "40000", name: "assets", oid: assets.oid },
+    { mode: "100644", name: "README.md", oid: small.oid },
+  ]);
+
+  test("reports blobs over the threshold with the path they were pushed at", () => {
+    expect(findLargeFiles([root, assets, large, small], 1024)).toEqual([{ oid: large.oid, size: 2048, path: "assets/video.mp4" }]);
+  });
+
+  test("falls back to no path when no pushed tree points at the blob", () => {
+    expect(findLargeFiles([large], 1024)).toEqual([{ oid: large.oid, size: 2048, path: null }]);
+  });
+
+  test("warns once per file and adds the LFS advice", () => {
+    const warnings = largeFileWarnings(findLargeFiles([root, assets, large, small], 1024), 1024, 4096);
+    expect(warnings).toHaveLength(2);
+    expect(warnings[0]).toStartWith("warning: File assets/video.mp4 is");
+    expect(warnings[1]).toContain("Git LFS");
+    expect(largeFileWarnings([], 1024, 4096)).toEqual([]);
+  });
+});
@@ -0,0 +1,36 @@
+import { describe, expect, test } from "bun:test";
+import { validateRepositoryName, validateUsername } from "@gitbruv/lib";
+
+describe("validateRepositoryName", () => {
+  test("accepts ordinary names, including dotfiles", () => {
+    for (const name of ["hello-world", "my_repo", "v1.2", ".github"]) {
+      expect(validateRepositoryName(name).valid).toBe(true);
+    }
+  });
+
+  test("rejects names that collide with paths or the bare repository layout", () => {
+    for (const name of [".", "..", ".git", "info", "objects", "refs", "HEAD", "site.git"]) {
+      expect(validateRepositoryName(name).valid).toBe(false);
+    }
+  });
+
+  test("rejects empty, overlong and out-of-charset names", () => {
+    expect(validateRepositoryName("").valid).toBe(false);
+    expect(validateRepositoryName("a".repeat(101)).valid).toBe(false);
+    expect(validateRepositoryName("a/b").valid).toBe(false);
+  });
+});
+
+describe("validateUsername", () => {
+  test("rejects names routed at the top level, in any case", () => {
+    expect(validateUsername("api").valid).toBe(false);
+    expect(validateUsername("Settings").valid).toBe(false);
+    expect(validateUsername("info").valid).toBe(false);
+  });
+
+  test("rejects misplaced hyphens", () => {
+    expect(validateUsername("-alice").valid).toBe(false);
+    expect(validateUsername("al--ice").valid).toBe(false);
+    expect(validateUsername("alice-b").valid).toBe(true);
+  });
+});
@@ -0,0 +1,380 @@
+import { describe, expect, test } from "bun:test";
+import { mkdtempSync, readFileSync, rmSync, writeFileSync } from "fs";
+import { tmpdir } from "os";
+import { join } from "path";
+import { createGitStore } from "../src/git";
+import { createPackIndex, findObjectInIndex, parsePackIndex, type PackIndexEntry } from "../src/git/pack-index";
+import { unpackPackFile } from "../src/git/unpack";
+import { applyDelta, PackFormatError, readDeltaVarint, readOfsOffset, readPackObjectHeader } from "../src/git/pack-parse";
+
+// Property checks over generated inputs. The generator is seeded so a failure reproduces; the
+// seed and iteration are part of every assertion message.
+const RUNS = 300;
+
+function random(seed: number) {
+  let state = seed >>> 0;
+  const next = () => {
+    state = (state + 0x6d2b79f5) >>> 0;
+    let t = state;
+    t = Math.imul(t ^ (t >>> 15), t | 1);
+    t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+  const int = (min: number, max: number) => min + Math.floor(next() * (max - min + 1));
+  const bytes = (length: number) => Buffer.from(Array.from({ length }, () => int(0, 255)));
+  return { int, bytes };
+}
+
+function forAll(name: string, property: (rng: ReturnType<typeof random>, run: number) => void) {
+  test(name, () => {
+    for (let run = 0; run < RUNS; run++) {
+      property(random(run * 7919 + 1), run);
+    }
+  });
+}
+
+// Malformed input may be rejected, but only with a PackFormatError: a RangeError or TypeError
+// means something read past a buffer.
+function parsesOrRejects<T>(fn: () => T, context: string): T | null {
+  try {
+    return fn();
+  } catch (error) {
+    if (!(error instanceof PackFormatError)) {
+      throw new Error(`${context}: threw ${error instanceof Error ? error.name : typeof error} instead of PackFormatError: ${error}`);
+    }
+    return null;
+  }
+}
+
+function expectRejected(fn: () => unknown, context: string) {
+  expect(() => fn(), context).toThrow(PackFormatError);
+}
+
+function encodeObjectHeader(type: number, size: number): Buffer {
+  const bytes = [(type << 4) | (size % 16)];
+  let rest = Math.floor(size / 16);
+  while (rest > 0) {
+    bytes[bytes.length - 1] |= 0x80;
+    bytes.push(rest % 128);
+    rest = Math.floor(rest / 128);
+  }
+  return Buffer.from(bytes);
+}
+
+function encodeOfsOffset(value: number): Buffer {
+  const bytes = [value % 128];
+  let rest = Math.floor(value / 128);
+  while (rest > 0) {
+    rest -= 1;
+    bytes.unshift(0x80 | (rest % 128));
+    rest = Math.floor(rest / 128);
+  }
+  return Buffer.from(bytes);
+}
+
+function encodeDeltaVarint(value: number): Buffer {
+  const bytes: number[] = [];
+  do {
+    let byte = value % 128;
+    value = Math.floor(value / 128);
+    if (value > 0) byte |= 0x80;
+    bytes.push(byte);
+  } while (value > 0);
+  return Buffer.from(bytes);
+}
+
+type DeltaOp = { copy: { offset: number; size: number } } | { insert: Buffer };
+
+function encodeDelta(baseSize: number, ops: DeltaOp[]): { delta: Buffer; resultSize: number } {
+  const parts: Buffer[] = [];
+  let resultSize = 0;
+  for (const op of ops) {
+    if ("insert" in op) {
+      parts.push(Buffer.from([op.insert.length]), op.insert);
+      resultSize += op.insert.length;
+      continue;
+    }
+    let cmd = 0x80;
+    const args: number[] = [];
+    for (let i = 0; i < 4; i++) {
+      const byte = Math.floor(op.copy.offset / 2 ** (8 * i)) % 256;
+      if (byte) {
+        cmd |= 1 << i;
+        args.push(byte);
+      }
+    }
+    // A size of 0x10000 is encoded as no size bytes at all.
+    const size = op.copy.size === 0x10000 ? 0 : op.copy.size;
+    for (let i = 0; i < 3; i++) {
+      const byte = Math.floor(size / 2 ** (8 * i)) % 256;
+      if (byte) {
+        cmd |= 0x10 << i;
+        args.push(byte);
+      }
+    }
+    parts.push(Buffer.from([cmd, ...args]));
+    resultSize += op.copy.size;
+  }
+  return { delta: Buffer.concat([encodeDeltaVarint(baseSize), encodeDeltaVarint(resultSize), ...parts]), resultSize };
+}
+
+function randomDelta(rng: ReturnType<typeof random>) {
+  const base = rng.bytes(rng.int(1, 400));
+  const ops: DeltaOp[] = [];
+  const expected: Buffer[] = [];
+  for (let i = rng.int(1, 12); i > 0; i--) {
+    if (rng.int(0, 1)) {
+      const offset = rng.int(0, base.length - 1);
+      const size = rng.int(1, base.length - offset);
+      ops.push({ copy: { offset, size } });
+      expected.push(base.subarray(offset, offset + size));
+    } else {
+      const insert = rng.bytes(rng.int(1, 127));
+      ops.push({ insert });
+      expected.push(insert);
+    }
+  }
+  return { base, ...encodeDelta(base.length, ops), expected: Buffer.concat(expected) };
+}
+
+describe("readPackObjectHeader", () => {
+  const types = [1, 2, 3, 4, 6, 7];
+
+  forAll("decodes what it encodes", (rng, run) => {
+    const type = types[rng.int(0, types.length - 1)];
+    const size = rng.int(0, 1) ? rng.int(0, 200) : rng.int(0, 2 ** 40);
+    const encoded = Buffer.concat([rng.bytes(rng.int(0, 3)), encodeObjectHeader(type, size)]);
+    const start = encoded.length - encodeObjectHeader(type, size).length;
+    expect(readPackObjectHeader(encoded, start), `run ${run}`).toEqual({ type, size, bytesRead: encoded.length - start });
+  });
+
+  forAll("rejects every truncation", (rng, run) => {
+    const encoded = encodeObjectHeader(types[rng.int(0, types.length - 1)], rng.int(16, 2 ** 40));
+    for (let length = 0; length < encoded.length; length++) {
+      expectRejected(() => readPackObjectHeader(encoded.subarray(0, length), 0), `run ${run}, length ${length}`);
+    }
+  });
+
+  forAll("never over-reads arbitrary bytes", (rng, run) => {
+    const buf = rng.bytes(rng.int(0, 12));
+    const offset = rng.int(0, buf.length + 2);
+    const header = parsesOrRejects(() => readPackObjectHeader(buf, offset), `run ${run}`);
+    if (header) {
+      expect(offset + header.bytesRead, `run ${run}`).toBeLessThanOrEqual(buf.length);
+      expect(Number.isSafeInteger(header.size), `run ${run}`).toBe(true);
+    }
+  });
+
+  test("rejects the reserved object types", () => {
+    expectRejected(() => readPackObjectHeader(Buffer.from([0x05]), 0), "type 0");
+    expectRejected(() => readPackObjectHeader(Buffer.from([0x55]), 0), "type 5");
+  });
+
+  test("rejects sizes that run on past eight bytes", () => {
+    expectRejected(() => readPackObjectHeader(Buffer.from([0x90, ...Array(12).fill(0x80), 0x00]), 0), "zero continuation bytes");
+  });
+});
+
+describe("readOfsOffset", () => {
+  forAll("decodes what it encodes", (rng, run) => {
+    const value = rng.int(0, 1) ? rng.int(0, 20000) : rng.int(0, 2 ** 45);
+    const encoded = encodeOfsOffset(value);
+    expect(readOfsOffset(encoded, 0), `run ${run}`).toEqual({ value, bytesRead: encoded.length });
+  });
+
+  forAll("rejects every truncation", (rng, run) => {
+    const encoded = encodeOfsOffset(rng.int(128, 2 ** 45));
+    for (let length = 0; length < encoded.length; length++) {
+      expectRejected(() => readOfsOffset(encoded.subarray(0, length), 0), `run ${run}, length ${length}`);
+    }
+  });
+
+  forAll("never over-reads arbitrary bytes", (rng, run) => {
+    const buf = rng.bytes(rng.int(0, 12));
+    const offset = rng.int(0, buf.length);
+    const result = parsesOrRejects(() => readOfsOffset(buf, offset), `run ${run}`);
+    if (result) {
+      expect(offset + result.bytesRead, `run ${run}`).toBeLessThanOrEqual(buf.length);
+      expect(Number.isSafeInteger(result.value), `run ${run}`).toBe(true);
+    }
+  });
+});
+
+describe("readDeltaVarint", () => {
+  forAll("decodes what it encodes", (rng, run) => {
+    const value = rng.int(0, 1) ? rng.int(0, 300) : rng.int(0, 2 ** 48);
+    const encoded = encodeDeltaVarint(value);
+    expect(readDeltaVarint(encoded, 0), `run ${run}`).toEqual({ value, bytesRead: encoded.length });
+  });
+
+  forAll("rejects every truncation", (rng, run) => {
+    const encoded = encodeDeltaVarint(rng.int(128, 2 ** 48));
+    for (let length = 0; length < encoded.length; length++) {
+      expectRejected(() => readDeltaVarint(encoded.subarray(0, length), 0), `run ${run}, length ${length}`);
+    }
+  });
+
+  test("rejects values past 2^53", () => {
+    expectRejected(() => readDeltaVarint(Buffer.from([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]), 0), "56 bits");
+  });
+});
+
+describe("applyDelta", () => {
+  forAll("reproduces the target", (rng, run) => {
+    const { base, delta, expected } = randomDelta(rng);
+    expect(applyDelta(base, delta).equals(expected), `run ${run}`).toBe(true);
+  });
+
+  forAll("rejects every truncation", (rng, run) => {
+    const { base, delta } = randomDelta(rng);
+    for (let length = 0; length < delta.length; length++) {
+      expectRejected(() => applyDelta(base, delta.subarray(0, length)), `run ${run}, length ${length}`);
+    }
+  });
+
+  forAll("rejects a base of the wrong size", (rng, run) => {
+    const { base, delta } = randomDelta(rng);
+    expectRejected(() => applyDelta(Buffer.concat([base, Buffer.from([0])]), delta), `run ${run}`);
+  });
+
+  forAll("never over-reads a corrupted delta", (rng, run) => {
+    const { base, delta } = randomDelta(rng);
+    const corrupted = Buffer.from(delta);
+    for (let i = rng.int(1, 4); i > 0; i--) {
+      corrupted[rng.int(0, corrupted.length - 1)] = rng.int(0, 255);
+    }
+    const result = parsesOrRejects(() => applyDelta(base, corrupted), `run ${run}`);
+    if (result) {
+      expect(result.length, `run ${run}`).toBe(readDeltaVarint(corrupted, readDeltaVarint(corrupted, 0).bytesRead).value);
+    }
+  });
+
+  test("rejects copies outside the base", () => {
+    const { delta } = encodeDelta(4, [{ copy: { offset: 2, size: 3 } }]);
+    expectRejected(() => applyDelta(Buffer.from("abcd"), delta), "copy past end");
+  });
+
+  test("rejects a declared size the instructions don't fill", () => {
+    const delta = Buffer.concat([encodeDeltaVarint(4), encodeDeltaVarint(2 ** 40), Buffer.from([2]), Buffer.from("hi")]);
+    expectRejected(() => applyDelta(Buffer.from("abcd"), delta), "huge result size");
+  });
+
+  test("rejects a declared result over the limit before applying it", () => {
+    // Each sizeless copy expands to 64 KiB, so these 1000 bytes would build 64 MB.
+    const base = Buffer.alloc(0x10000, "a");
+    const delta = Buffer.concat([encodeDeltaVarint(base.length), encodeDeltaVarint(1000 * 0x10000), Buffer.alloc(1000, 0x80)]);
+    expectRejected(() => applyDelta(base, delta, 1024 * 1024), "result over limit");
+  });
+
+  test("rejects the reserved opcode", () => {
+    const delta = Buffer.concat([encodeDeltaVarint(4), encodeDeltaVarint(1), Buffer.from([0])]);
+    expectRejected(() => applyDelta(Buffer.from("abcd"), delta), "opcode 0");
+  });
+});
+
+describe("findObjectInIndex", () => {
+  const randomEntries = (rng: ReturnType<typeof random>): PackIndexEntry[] => {
+    const byOid = new Map<string, PackIndexEntry>();
+    for (let i = rng.int(0, 60); i > 0; i--) {
+      const oid = rng.bytes(20).toString("hex");
+      // Some offsets land past 2 GiB so the 64-bit offset table gets exercised.
+      const offset = rng.int(0, 9) === 0 ? rng.int(2 ** 31, 2 ** 40) : rng.int(12, 2 ** 31 - 1);
+      byOid.set(oid, { oid, offset, crc32: rng.int(0, 2 ** 32 - 1) });
+    }
+    return [...byOid.values()];
+  };
+
+  forAll("finds every indexed object and nothing else", (rng, run) => {
+    const entries = randomEntries(rng);
+    const idx = createPackIndex(entries, rng.bytes(20));
+    const index = parsePackIndex(idx);
+    expect(index.objectCount, `run ${run}`).toBe(entries.length);
+    for (const entry of entries) {
+      expect(index.find(entry.oid), `run ${run}, ${entry.oid}`).toBe(entry.offset);
+    }
+    const absent = rng.bytes(20).toString("hex");
+    if (!entries.some((entry) => entry.oid === absent)) {
+      expect(findObjectInIndex(idx, absent), `run ${run}`).toBeNull();
+    }
+  });
+
+  forAll("rejects every truncation", (rng, run) => {
+    const idx = createPackIndex(randomEntries(rng), rng.bytes(20));
+    const length = rng.int(0, idx.length - 1);
+    // Cutting off a multiple of eight bytes can still leave a well-formed (smaller) large offset
+    // table, so only the lookups have to stay in bounds there.
+    const result = parsesOrRejects(() => parsePackIndex(idx.subarray(0, length)), `run ${run}, length ${length}`);
+    if (result) {
+      expect((idx.length - length) % 8, `run ${run}, length ${length}`).toBe(0);
+    }
+  });
+
+  forAll("never over-reads a corrupted index", (rng, run) => {
+    const entries = randomEntries(rng);
+    const idx = createPackIndex(entries, rng.bytes(20));
+    for (let i = rng.int(1, 6); i > 0; i--) {
+      idx[rng.int(0, idx.length - 1)] = rng.int(0, 255);
+    }
+    const index = parsesOrRejects(() => parsePackIndex(idx), `run ${run}`);
+    if (index) {
+      for (const entry of [...entries, { oid: rng.bytes(20).toString("hex") }]) {
+        const offset = parsesOrRejects(() => index.find(entry.oid), `run ${run}, ${entry.oid}`);
+        if (offset !== null) {
+          expect(Number.isSafeInteger(offset), `run ${run}`).toBe(true);
+        }
+      }
+    }
+  });
+
+  test("rejects an index that isn't version 2", () => {
+    const idx = createPackIndex([], Buffer.alloc(20));
+    idx.writeUInt32BE(3, 4);
+    expectRejected(() => parsePackIndex(idx), "version 3");
+  });
+
+  test("treats malformed object ids as absent", () => {
+    expect(findObjectInIndex(createPackIndex([], Buffer.alloc(20)), "not-an-oid")).toBeNull();
+  });
+});
+
+describe("createPackIndex", () => {
+  test("rejects a pack that lists an object twice", () => {
+    const oid = "ab".repeat(20);
+    expectRejected(
+      () => createPackIndex([{ oid, offset: 12, crc32: 1 }, { oid, offset: 40, crc32: 2 }], Buffer.alloc(20)),
+      "duplicate"
+    );
+  });
+
+  // A real pack from git, with deltas, indexed both ways.
+  test.skipIf(!Bun.which("git"))("writes the same index as git index-pack", async () => {
+    const dir = mkdtempSync(join(tmpdir(), "pack-index-"));
+    const git = (...args: string[]) => {
+      const result = Bun.spawnSync(["git", "-c", "user.name=Test", "-c", "user.email=test@example.com", "-c", "commit.gpgsign=false", ...args], {
+        cwd: dir,
+        stdin: "ignore",
+      });
+      if (result.exitCode !== 0) throw new Error(`git ${args[0]} failed: ${result.stderr.toString()}`);
+      return result.stdout;
+    };
+
+    try {
+      git("init", "-q");
+      for (let version = 1; version <= 3; version++) {
+        const lines = Array.from({ length: 200 }, (_, i) => (i === 50 ? `changed ${version}` : `line ${i}`));
+        writeFileSync(join(dir, "file.txt"), lines.join("\n") + "\n");
+        git("add", "file.txt");
+        git("commit", "-q", "-m", `version ${version}`);
+      }
+      writeFileSync(join(dir, "test.pack"), git("pack-objects", "--revs", "--all", "--delta-base-offset", "--stdout"));
+      git("index-pack", "-o", "expected.idx", "test.pack");
+
+      const result = await unpackPackFile({ path: join(dir, "test.pack") }, async () => {}, createGitStore("pack-index-test", "repo"), Infinity, true);
+      expect(result.success).toBe(true);
+      const idx = createPackIndex(result.indexEntries!, result.packChecksum!);
+      expect(idx.equals(readFileSync(join(dir, "expected.idx")))).toBe(true);
+    } finally {
+      rmSync(dir, { recursive: true, force: true });
+    }
+  });
+});
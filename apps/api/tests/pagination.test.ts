@@ -0,0 +1,36 @@
+import { describe, expect, test } from "bun:test";
+import { decodeCursor, encodeCursor } from "../src/pagination";
+
+describe("decodeCursor", () => {
+  const uuid = "0b7c1a62-4f3e-4d2a-9a51-6f3b2c1d0e9f";
+
+  test("round-trips values that match their parts", () => {
+    const values = ["2024-03-01 12:34:56.123456", uuid];
+    expect(decodeCursor(encodeCursor(values), ["timestamp", "uuid"])).toEqual(values);
+    expect(decodeCursor(encodeCursor(["42", uuid]), ["bigint", "uuid"])).toEqual(["42", uuid]);
+    expect(decodeCursor(encodeCursor(["a".repeat(40), "30"]), ["oid", "integer"])).toEqual(["a".repeat(40), "30"]);
+  });
+
+  test("rejects values Postgres couldn't cast", () => {
+    for (const values of [
+      ["not a date", uuid],
+      ["2024-02-30 00:00:00", uuid],
+      ["2024-03-01 12:34:56", "not-a-uuid"],
+      ["2024-03-01 12:34:56'; --", uuid],
+    ]) {
+      expect(decodeCursor(encodeCursor(values), ["timestamp", "uuid"])).toBeNull();
+    }
+    expect(decodeCursor(encodeCursor(["1e3", uuid]), ["bigint", "uuid"])).toBeNull();
+  });
+
+  test("rejects commit cursors that don't carry an oid and an offset", () => {
+    expect(decodeCursor(encodeCursor(["main", "0"]), ["oid", "integer"])).toBeNull();
+    expect(decodeCursor(encodeCursor(["a".repeat(40), "-1"]), ["oid", "integer"])).toBeNull();
+  });
+
+  test("rejects malformed cursors", () => {
+    expect(decodeCursor("%%%", ["text"])).toBeNull();
+    expect(decodeCursor(encodeCursor(["a", "b"]), ["text"])).toBeNull();
+    expect(decodeCursor(Buffer.from(JSON.stringify([1, 2])).toString("base64url"), ["text", "text"])).toBeNull();
+  });
+});
@@ -0,0 +1,48 @@
+import { describe, expect, test } from "bun:test";
+import { applyHunks, parsePatch } from "../src/git/patch";
+
+function apply(original: string, diff: string) {
+  const [file] = parsePatch(diff).files;
+  return applyHunks(original, file.hunks, file.newPath ?? file.oldPath!);
+}
+
+describe("parsePatch", () => {
+  test("adds a trailing newline the old file was missing", () => {
+    const diff = ["--- a/README", "+++ b/README", "@@ -1 +1 @@", "-hello", "\\ No newline at end of file", "+hello", ""].join("\n");
+    expect(apply("hello", diff)).toBe("hello\n");
+  });
+
+  test("removes the trailing newline when the new side is marked", () => {
+    const diff = ["--- a/README", "+++ b/README", "@@ -1 +1 @@", "-hello", "+hello", "\\ No newline at end of file", ""].join("\n");
+    expect(apply("hello\n", diff)).toBe("hello");
+  });
+
+  test("keeps a missing newline on unchanged context", () => {
+    const diff = ["--- a/README", "+++ b/README", "@@ -1,2 +1,2 @@", "-one", "+uno", " two", "\\ No newline at end of file", ""].join("\n");
+    expect(apply("one\ntwo", diff)).toBe("uno\ntwo");
+  });
+
+  test("rejects renames and mode changes", () => {
+    const rename = ["diff --git a/old b/new", "similarity index 100%", "rename from old", "rename to new", ""].join("\n");
+    const mode = ["diff --git a/run.sh b/run.sh", "old mode 100644", "new mode 100755", "--- a/run.sh", "+++ b/run.sh", "@@ -1 +1 @@", "-a", "+b", ""].join("\n");
+    expect(() => parsePatch(rename)).toThrow("rename from");
+    expect(() => parsePatch(mode)).toThrow("old mode");
+  });
+
+  test("ignores header-like lines in the commit message", () => {
+    const diff = [
+      "Subject: [PATCH] Explain the old mode",
+      "",
+      "old mode was wrong",
+      "---",
+      "diff --git a/README b/README",
+      "--- a/README",
+      "+++ b/README",
+      "@@ -1 +1 @@",
+      "-a",
+      "+b",
+      "",
+    ].join("\n");
+    expect(parsePatch(diff).files).toHaveLength(1);
+  });
+});
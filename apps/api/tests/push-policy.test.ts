@@ -0,0 +1,26 @@
+import { describe, expect, test } from "bun:test";
+import { findUnsafePattern } from "../src/push-policy";
+
+describe("findUnsafePattern", () => {
+  const safe = [
+    "^(feat|fix|chore)(\\(.+\\))?: .+",
+    "^[A-Z]+-\\d+ .+",
+    "^(?:Merge|Revert) ",
+    "^(?<type>\\w+): \\S",
+    "^\\[(?:[a-z-]+)\\] .{1,72}$",
+    "(ab){0,1}c+",
+  ];
+  const unsafe = ["(a+)+$", "(a*)*b", "(a|aa)+$", "((ab)*)+", "(?:x+){2,}", "(\\w+)\\1", "(?<word>\\w)\\k<word>"];
+
+  for (const pattern of safe) {
+    test(`accepts ${pattern}`, () => {
+      expect(findUnsafePattern(pattern)).toBeNull();
+    });
+  }
+
+  for (const pattern of unsafe) {
+    test(`refuses ${pattern}`, () => {
+      expect(findUnsafePattern(pattern)).not.toBeNull();
+    });
+  }
+});
@@ -0,0 +1,226 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { buildSingleFileCommit, fetchPack, hasTestDatabase, pushBranch, pushRef, request, signUp, ZERO_OID, type TestCommit, type TestUser } from "./helpers";
+
+// One repository is walked through its life in order: each test builds on the state the
+// previous one left behind.
+describe.skipIf(!hasTestDatabase)("repository flow", () => {
+  let owner: TestUser;
+  let other: TestUser;
+  let commit: TestCommit;
+  const repo = "hello-world";
+
+  beforeAll(async () => {
+    owner = await signUp("owner");
+    other = await signUp("other");
+    commit = buildSingleFileCommit("README.md", "# Hello\n", "Initial commit", { name: owner.username, email: owner.email });
+  });
+
+  test("creates a repository", async () => {
+    const response = await request("/api/repositories", {
+      method: "POST",
+      user: owner,
+      json: { name: repo, description: "A test repository", visibility: "public" },
+    });
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.name).toBe(repo);
+    expect(body.ownerId).toBe(owner.id);
+  });
+
+  test("starts out empty", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/tree`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.isEmpty).toBe(true);
+    expect(body.files).toEqual([]);
+  });
+
+  test("rejects a push from someone without write access", async () => {
+    const { status } = await pushBranch(other, repo, "main", ZERO_OID, commit);
+    expect(status).toBe(401);
+  });
+
+  test("accepts a push of a handcrafted pack", async () => {
+    const { status, report } = await pushBranch(owner, repo, "main", ZERO_OID, commit);
+    expect(status).toBe(200);
+    expect(report).toContain("unpack ok");
+    expect(report).toContain("ok refs/heads/main");
+  });
+
+  test("creates and deletes a branch without sending a pack", async () => {
+    const created = await pushRef(owner, repo, { ref: "refs/heads/feature", oldOid: ZERO_OID, newOid: commit.commitOid, pack: null });
+    expect(created.report).toContain("ok refs/heads/feature");
+
+    const deleted = await pushRef(owner, repo, { ref: "refs/heads/feature", oldOid: commit.commitOid, newOid: ZERO_OID, pack: null });
+    expect(deleted.report).toContain("ok refs/heads/feature");
+  });
+
+  test("rejects a ref whose objects were never sent", async () => {
+    const { report } = await pushRef(owner, repo, { ref: "refs/heads/ghost", oldOid: ZERO_OID, newOid: "1".repeat(40), pack: null });
+    expect(report).toContain("ng refs/heads/ghost missing necessary objects");
+  });
+
+  test("reports a corrupt pack per ref over side-band", async () => {
+    const corrupt = Buffer.from(commit.pack);
+    corrupt.fill(0xff, 12, 20);
+    const { status, report } = await pushRef(
+      owner,
+      repo,
+      { ref: "refs/heads/broken", oldOid: ZERO_OID, newOid: commit.commitOid, pack: corrupt },
+      "report-status side-band-64k"
+    );
+    expect(status).toBe(200);
+    // Band 2 carries the explanation git prints as "remote: error: ...".
+    expect(report).toContain("\x02error: ");
+    expect(report).toContain("ng refs/heads/broken unpacker error");
+  });
+
+  test("lists the pushed tree", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/tree`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.isEmpty).toBe(false);
+    expect(body.files.map((file: { name: string }) => file.name)).toEqual(["README.md"]);
+    expect(body.hasMore).toBe(false);
+  });
+
+  test("reads a file", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/file?path=README.md`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.content).toBe("# Hello\n");
+    expect(body.oid).toBe(commit.blobOid);
+  });
+
+  test("includes the file's last commit once it has been resolved", async () => {
+    let body: { lastCommit: { commitOid: string; message: string } | null; lastCommitPending: boolean } | undefined;
+    for (let attempt = 0; attempt < 20; attempt++) {
+      const response = await request(`/api/repositories/${owner.username}/${repo}/file?path=README.md&include=lastCommit`);
+      expect(response.status).toBe(200);
+      body = await response.json();
+      if (!body!.lastCommitPending) break;
+      await Bun.sleep(100);
+    }
+    expect(body!.lastCommitPending).toBe(false);
+    expect(body!.lastCommit?.commitOid).toBe(commit.commitOid);
+  });
+
+  test("highlights a blob with the language inferred from its path", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/highlight/${commit.blobOid}?path=README.md&format=tokens`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.language).toBe("markdown");
+    expect(body.lines.flat().map((token: { content: string }) => token.content).join("")).toBe("# Hello");
+  });
+
+  test("diffs one file between two refs", async () => {
+    const revised = buildSingleFileCommit("README.md", "# Hello, world\n", "Revise README", { name: owner.username, email: owner.email });
+    const { report } = await pushBranch(owner, repo, "revised", ZERO_OID, revised);
+    expect(report).toContain("ok refs/heads/revised");
+
+    const response = await request(`/api/repositories/${owner.username}/${repo}/diff?path=README.md&base=main&head=revised`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.base).toBe(commit.commitOid);
+    expect(body.head).toBe(revised.commitOid);
+    expect(body.file).toMatchObject({ path: "README.md", status: "modified", additions: 1, deletions: 1 });
+
+    const missing = await request(`/api/repositories/${owner.username}/${repo}/diff?path=missing.txt&base=main&head=revised`);
+    expect(missing.status).toBe(404);
+
+    await pushRef(owner, repo, { ref: "refs/heads/revised", oldOid: revised.commitOid, newOid: ZERO_OID, pack: null });
+  });
+
+  test("only sends objects a ref still reaches", async () => {
+    const served = await fetchPack(owner.username, repo, [commit.commitOid]);
+    expect(served.status).toBe(200);
+    expect(served.body.includes(Buffer.from("PACK"))).toBe(true);
+
+    const dropped = buildSingleFileCommit("README.md", "# Dropped\n", "Force-pushed away", { name: owner.username, email: owner.email });
+    await pushBranch(owner, repo, "dropped", ZERO_OID, dropped);
+    await pushRef(owner, repo, { ref: "refs/heads/dropped", oldOid: dropped.commitOid, newOid: ZERO_OID, pack: null });
+
+    for (const oid of [dropped.commitOid, dropped.blobOid]) {
+      const refused = await fetchPack(owner.username, repo, [oid]);
+      expect(refused.body.toString("latin1")).toContain(`not our ref ${oid}`);
+      expect(refused.body.includes(Buffer.from("PACK"))).toBe(false);
+    }
+  });
+
+  test("returns 404 for a missing file", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/file?path=missing.txt`);
+    expect(response.status).toBe(404);
+  });
+
+  test("lists commits", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/commits`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.commits).toHaveLength(1);
+    expect(body.commits[0].oid).toBe(commit.commitOid);
+    expect(body.commits[0].message.trim()).toBe("Initial commit");
+    expect(body.commits[0].author.userId).toBe(owner.id);
+    expect(body.hasMore).toBe(false);
+  });
+
+  test("reports the repository's size once scanned", async () => {
+    let response = await request(`/api/repositories/${owner.username}/${repo}/size`);
+    for (let attempt = 0; attempt < 20 && response.status === 202; attempt++) {
+      await Bun.sleep(100);
+      response = await request(`/api/repositories/${owner.username}/${repo}/size`);
+    }
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.totalBytes).toBeGreaterThan(0);
+    expect(body.largestBlobs.map((blob: { oid: string }) => blob.oid)).toContain(commit.blobOid);
+  });
+
+  test("stars by owner and name, idempotently", async () => {
+    const path = `/api/repositories/${owner.username}/${repo}/star`;
+    for (let attempt = 0; attempt < 2; attempt++) {
+      const response = await request(path, { method: "PUT", user: other });
+      expect(response.status).toBe(200);
+      expect(await response.json()).toEqual({ starred: true, starCount: 1 });
+    }
+
+    const repoId = (await (await request(`/api/repositories/${owner.username}/${repo}`)).json()).id;
+    const state = await request(`/api/repositories/starred?ids=${repoId},not-a-repo`, { user: other });
+    expect((await state.json()).starred).toEqual({ [repoId]: true, "not-a-repo": false });
+
+    const removed = await request(path, { method: "DELETE", user: other });
+    expect(await removed.json()).toEqual({ starred: false, starCount: 0 });
+  });
+
+  test("opens an issue", async () => {
+    const created = await request(`/api/repositories/${owner.username}/${repo}/issues`, {
+      method: "POST",
+      user: other,
+      json: { title: "README is too short", body: "Could it say more?" },
+    });
+    expect(created.status).toBe(200);
+    const issue = await created.json();
+    expect(issue.number).toBe(1);
+    expect(issue.state).toBe("open");
+    expect(issue.author.id).toBe(other.id);
+
+    const listed = await request(`/api/repositories/${owner.username}/${repo}/issues`);
+    expect(listed.status).toBe(200);
+    const body = await listed.json();
+    expect(body.issues.map((item: { id: string }) => item.id)).toEqual([issue.id]);
+  });
+
+  test("counts open issues, stars and forks in the repository info", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/info`);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.repo).toMatchObject({ openIssueCount: 1, starCount: 0, forkCount: 0, starred: false });
+  });
+
+  test("requires a session to open an issue", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${repo}/issues`, {
+      method: "POST",
+      json: { title: "Anonymous" },
+    });
+    expect(response.status).toBe(401);
+  });
+});
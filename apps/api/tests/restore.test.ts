@@ -0,0 +1,88 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { parseBundle } from "../src/git/bundle";
+import { PackFormatError } from "../src/git/pack-parse";
+import { buildSingleFileCommit, hasTestDatabase, request, signUp, type TestCommit, type TestUser } from "./helpers";
+
+function buildBundle(refs: string[], pack: Buffer, header = "# v2 git bundle") {
+  return Buffer.concat([Buffer.from(`${header}\n${refs.join("\n")}\n\n`), pack]);
+}
+
+describe("parseBundle", () => {
+  const commit = buildSingleFileCommit("README.md", "# Restored\n", "Initial commit", { name: "a", email: "a@example.com" });
+
+  test("reads refs, HEAD and the pack", () => {
+    const data = buildBundle([`${commit.commitOid} refs/heads/main`, `${commit.commitOid} HEAD`], commit.pack);
+    const bundle = parseBundle(data);
+    expect(bundle.refs).toEqual([{ name: "refs/heads/main", oid: commit.commitOid }]);
+    expect(bundle.head).toBe(commit.commitOid);
+    expect(data.subarray(bundle.packOffset).equals(commit.pack)).toBe(true);
+  });
+
+  test("skips refs outside branches and tags", () => {
+    const bundle = parseBundle(buildBundle([`${commit.commitOid} refs/heads/main`, `${commit.commitOid} refs/remotes/origin/main`], commit.pack));
+    expect(bundle.skipped).toEqual(["refs/remotes/origin/main"]);
+  });
+
+  test("rejects malformed bundles", () => {
+    expect(() => parseBundle(Buffer.from("not a bundle"))).toThrow(PackFormatError);
+    expect(() => parseBundle(buildBundle([], commit.pack))).toThrow(PackFormatError);
+    expect(() => parseBundle(buildBundle([`${commit.commitOid} refs/heads/../main`], commit.pack))).toThrow(PackFormatError);
+    expect(() => parseBundle(buildBundle([`${commit.commitOid} refs/heads/main`], Buffer.from("junk")))).toThrow(PackFormatError);
+  });
+});
+
+describe.skipIf(!hasTestDatabase)("repository restore", () => {
+  let owner: TestUser;
+  let other: TestUser;
+  let commit: TestCommit;
+  const repo = "restored";
+
+  function restore(user: TestUser, body: Buffer, force = false) {
+    return request(`/api/repositories/${owner.username}/${repo}/restore${force ? "?force=true" : ""}`, {
+      method: "POST",
+      user,
+      headers: { "Content-Type": "application/octet-stream" },
+      body,
+    });
+  }
+
+  beforeAll(async () => {
+    owner = await signUp("owner");
+    other = await signUp("other");
+    commit = buildSingleFileCommit("README.md", "# Restored\n", "Initial commit", { name: owner.username, email: owner.email });
+    await request("/api/repositories", { method: "POST", user: owner, json: { name: repo, visibility: "public" } });
+  });
+
+  test("only lets the owner restore", async () => {
+    const response = await restore(other, buildBundle([`${commit.commitOid} refs/heads/trunk`], commit.pack));
+    expect(response.status).toBe(403);
+  });
+
+  test("rejects a corrupt bundle", async () => {
+    const response = await restore(owner, Buffer.from("# v2 git bundle\n"));
+    expect(response.status).toBe(400);
+  });
+
+  test("restores an empty repository from a bundle", async () => {
+    const response = await restore(owner, buildBundle([`${commit.commitOid} refs/heads/trunk`, `${commit.commitOid} HEAD`], commit.pack));
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.refs).toEqual([{ name: "refs/heads/trunk", oid: commit.commitOid }]);
+    expect(body.defaultBranch).toBe("trunk");
+
+    const file = await request(`/api/repositories/${owner.username}/${repo}/file?path=README.md`);
+    expect(file.status).toBe(200);
+    expect((await file.json()).content).toBe("# Restored\n");
+  });
+
+  test("refuses to overwrite a repository with branches unless forced", async () => {
+    const bundle = buildBundle([`${commit.commitOid} refs/heads/main`], commit.pack);
+    expect((await restore(owner, bundle)).status).toBe(409);
+
+    const response = await restore(owner, bundle, true);
+    expect(response.status).toBe(200);
+    const body = await response.json();
+    expect(body.deleted).toEqual(["refs/heads/trunk"]);
+    expect(body.defaultBranch).toBe("main");
+  });
+});
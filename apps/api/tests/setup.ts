@@ -0,0 +1,38 @@
+import { mkdtempSync, rmSync, writeFileSync } from "fs";
+import { tmpdir } from "os";
+import { join, resolve } from "path";
+import { afterAll } from "bun:test";
+
+// Runs before any test file imports the API, so config and the db client pick these up. The
+// suite only ever talks to TEST_DATABASE_URL: without it, DATABASE_URL points nowhere (the
+// client connects lazily) and the database-backed tests skip rather than fall back to .env.
+const root = mkdtempSync(join(tmpdir(), "gitbruv-test-"));
+const emptyConfig = join(root, "config.toml");
+writeFileSync(emptyConfig, "");
+
+process.env.CONFIG_FILE = emptyConfig;
+process.env.STORAGE_BACKEND = "local";
+process.env.STORAGE_LOCAL_PATH = join(root, "storage");
+process.env.BETTER_AUTH_SECRET = "test-secret-that-is-at-least-32-characters";
+process.env.DATABASE_URL = process.env.TEST_DATABASE_URL || "postgres://test-database-not-configured@127.0.0.1:1/none";
+// Redis is opt-in the same way: the cache tests need TEST_REDIS_URL and skip without it.
+if (process.env.TEST_REDIS_URL) {
+  process.env.REDIS_URL = process.env.TEST_REDIS_URL;
+} else {
+  delete process.env.REDIS_URL;
+}
+
+if (process.env.TEST_DATABASE_URL) {
+  // The schema is pushed rather than migrated, the same way development databases are set up.
+  const push = Bun.spawnSync(
+    ["bunx", "drizzle-kit", "push", "--dialect", "postgresql", "--schema", "./src/schema.ts", "--url", process.env.TEST_DATABASE_URL, "--force"],
+    { cwd: resolve(import.meta.dir, "../../../packages/db"), stdout: "pipe", stderr: "pipe" }
+  );
+  if (push.exitCode !== 0) {
+    throw new Error(`Could not push the schema to TEST_DATABASE_URL:\n${push.stderr.toString()}`);
+  }
+}
+
+afterAll(() => {
+  rmSync(root, { recursive: true, force: true });
+});
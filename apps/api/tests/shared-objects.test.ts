@@ -0,0 +1,139 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { db, repositories } from "@gitbruv/db";
+import { eq } from "drizzle-orm";
+import { deleteObject, getObject, listObjects, objectExists, putObject } from "../src/s3";
+import { storageLayout } from "../src/storage-layout";
+import { ALTERNATES_PATH, listOwnLooseObjects, readAlternates } from "../src/git/shared-objects";
+import { migrateLegacyObjectStores } from "../src/jobs/shared-objects";
+import { computeRepoSize } from "../src/git/size";
+import { buildSingleFileCommit, hasTestDatabase, pushBranch, pushRef, request, signUp, ZERO_OID, type TestCommit, type TestUser } from "./helpers";
+
+function looseKey(prefix: string, oid: string) {
+  return `${prefix}/${oid.slice(0, 2)}/${oid.slice(2)}`;
+}
+
+async function repoId(user: TestUser, name: string): Promise<string> {
+  return (await (await request(`/api/repositories/${user.username}/${name}`)).json()).id;
+}
+
+async function networkOf(id: string) {
+  const repo = await db.query.repositories.findFirst({ where: eq(repositories.id, id), columns: { objectNetworkId: true } });
+  return repo?.objectNetworkId ?? null;
+}
+
+// Like the flow tests, each test builds on the state the previous one left behind.
+describe.skipIf(!hasTestDatabase)("shared object storage", () => {
+  let owner: TestUser;
+  let other: TestUser;
+  let commit: TestCommit;
+  let upstreamId: string;
+  const upstream = "network-root";
+
+  beforeAll(async () => {
+    owner = await signUp("owner");
+    other = await signUp("other");
+    commit = buildSingleFileCommit("README.md", "# Shared\n", "Initial commit", { name: owner.username, email: owner.email });
+    await request("/api/repositories", { method: "POST", user: owner, json: { name: upstream, visibility: "public" } });
+    await pushBranch(owner, upstream, "main", ZERO_OID, commit);
+    upstreamId = await repoId(owner, upstream);
+  });
+
+  test("writes a public repository's objects into its own network", async () => {
+    const prefix = storageLayout.repoPrefix(owner.id, upstream);
+    const shared = storageLayout.sharedObjectsPrefix(upstreamId);
+    expect(await readAlternates(prefix)).toBe(shared);
+    expect(await networkOf(upstreamId)).toBe(upstreamId);
+    expect(await objectExists(looseKey(shared, commit.commitOid))).toBe(true);
+    expect(await listOwnLooseObjects(prefix)).toEqual([]);
+  });
+
+  test("counts the network's objects towards the repository's size", async () => {
+    const size = await computeRepoSize(storageLayout.repoPrefix(owner.id, upstream));
+    expect(size.sharedObjectCount).toBe(3);
+    expect(size.looseObjectCount).toBe(3);
+    expect(size.sharedBytes).toBe(size.looseBytes);
+    expect(size.totalBytes).toBeGreaterThan(size.sharedBytes);
+    expect(size.largestBlobs.map((blob) => blob.oid)).toEqual([commit.blobOid]);
+  });
+
+  test("forks by copying refs into the same network", async () => {
+    const response = await request(`/api/repositories/${owner.username}/${upstream}/fork`, { method: "POST", user: other, json: {} });
+    expect(response.status).toBe(200);
+    const fork = (await response.json()).repo;
+    expect(await networkOf(fork.id)).toBe(upstreamId);
+
+    const forkPrefix = storageLayout.repoPrefix(other.id, upstream);
+    expect(await readAlternates(forkPrefix)).toBe(storageLayout.sharedObjectsPrefix(upstreamId));
+    expect(await listOwnLooseObjects(forkPrefix)).toEqual([]);
+
+    const file = await request(`/api/repositories/${other.username}/${upstream}/file?path=README.md`);
+    expect((await file.json()).content).toBe("# Shared\n");
+  });
+
+  test("keeps objects pushed while private out of the network", async () => {
+    const patched = await request(`/api/repositories/${upstreamId}`, { method: "PATCH", user: owner, json: { visibility: "private" } });
+    expect(patched.status).toBe(200);
+
+    const secret = buildSingleFileCommit("SECRET.md", "not for forks\n", "Private work", { name: owner.username, email: owner.email });
+    const { report } = await pushBranch(owner, upstream, "secret", ZERO_OID, secret);
+    expect(report).toContain("ok refs/heads/secret");
+
+    const prefix = storageLayout.repoPrefix(owner.id, upstream);
+    expect(await objectExists(looseKey(`${prefix}/objects`, secret.blobOid))).toBe(true);
+    expect(await objectExists(looseKey(storageLayout.sharedObjectsPrefix(upstreamId), secret.blobOid))).toBe(false);
+
+    // The fork shares the network but can neither point a ref at the private commit nor read its blob.
+    const pushed = await pushRef(other, upstream, { ref: "refs/heads/stolen", oldOid: ZERO_OID, newOid: secret.commitOid, pack: null });
+    expect(pushed.report).toContain("ng refs/heads/stolen missing necessary objects");
+    const highlighted = await request(`/api/repositories/${other.username}/${upstream}/highlight/${secret.blobOid}?path=SECRET.md`);
+    expect(highlighted.status).toBe(404);
+  });
+
+  test("moves those objects into the network once public again", async () => {
+    const patched = await request(`/api/repositories/${upstreamId}`, { method: "PATCH", user: owner, json: { visibility: "public" } });
+    expect(patched.status).toBe(200);
+
+    const prefix = storageLayout.repoPrefix(owner.id, upstream);
+    for (let attempt = 0; attempt < 20 && (await listOwnLooseObjects(prefix)).length > 0; attempt++) {
+      await Bun.sleep(100);
+    }
+    expect(await listOwnLooseObjects(prefix)).toEqual([]);
+  });
+
+  test("moves a legacy repository and a fork of it onto one network", async () => {
+    const legacy = "legacy-layout";
+    const legacyCommit = buildSingleFileCommit("README.md", "# Legacy\n", "Initial commit", { name: owner.username, email: owner.email });
+    await request("/api/repositories", { method: "POST", user: owner, json: { name: legacy, visibility: "public" } });
+    await pushBranch(owner, legacy, "main", ZERO_OID, legacyCommit);
+    const legacyId = await repoId(owner, legacy);
+
+    // Put the repository back on the per-repository layout it would have had before networks.
+    const prefix = storageLayout.repoPrefix(owner.id, legacy);
+    const shared = storageLayout.sharedObjectsPrefix(legacyId);
+    for (const key of await listObjects(`${shared}/`)) {
+      await putObject(`${prefix}/objects/${key.slice(shared.length + 1)}`, (await getObject(key))!);
+      await deleteObject(key);
+    }
+    await deleteObject(storageLayout.repoKey(prefix, ALTERNATES_PATH));
+    await db.update(repositories).set({ objectNetworkId: null }).where(eq(repositories.id, legacyId));
+
+    // A legacy source is copied whole rather than moved while the fork request waits.
+    const forked = await request(`/api/repositories/${owner.username}/${legacy}/fork`, { method: "POST", user: other, json: {} });
+    const forkId = (await forked.json()).repo.id;
+    const forkPrefix = storageLayout.repoPrefix(other.id, legacy);
+    expect(await networkOf(forkId)).toBeNull();
+    expect((await listOwnLooseObjects(forkPrefix)).length).toBeGreaterThan(0);
+
+    for (let run = 0; run < 20 && (await networkOf(forkId)) === null; run++) {
+      await migrateLegacyObjectStores();
+    }
+    expect(await networkOf(legacyId)).toBe(legacyId);
+    expect(await networkOf(forkId)).toBe(legacyId);
+    expect(await readAlternates(forkPrefix)).toBe(shared);
+    expect(await listOwnLooseObjects(prefix)).toEqual([]);
+    expect(await listOwnLooseObjects(forkPrefix)).toEqual([]);
+
+    const file = await request(`/api/repositories/${other.username}/${legacy}/file?path=README.md`);
+    expect((await file.json()).content).toBe("# Legacy\n");
+  });
+});
@@ -0,0 +1,52 @@
+import { describe, expect, test, beforeAll } from "bun:test";
+import { randomBytes } from "crypto";
+import { hasTestDatabase, request, signUp, type TestUser } from "./helpers";
+
+describe.skipIf(!hasTestDatabase)("username redirects", () => {
+  let user: TestUser;
+  let oldUsername: string;
+  const repo = "renamed-owner";
+
+  beforeAll(async () => {
+    user = await signUp("renamer");
+    oldUsername = user.username;
+    await request("/api/repositories", { method: "POST", user, json: { name: repo, visibility: "public" } });
+
+    const newUsername = `renamed-${randomBytes(4).toString("hex")}`;
+    const response = await request("/api/settings/profile", { method: "PATCH", user, json: { username: newUsername } });
+    expect(response.status).toBe(200);
+    user = { ...user, username: newUsername };
+  });
+
+  test("redirects API requests under the old username", async () => {
+    const response = await request(`/api/repositories/${oldUsername}/${repo}/branches?x=1`);
+    expect(response.status).toBe(301);
+    expect(response.headers.get("Location")).toBe(`/api/repositories/${user.username}/${repo}/branches?x=1`);
+  });
+
+  test("redirects the user profile", async () => {
+    const response = await request(`/api/users/${oldUsername}/profile`);
+    expect(response.status).toBe(301);
+    expect(response.headers.get("Location")).toBe(`/api/users/${user.username}/profile`);
+  });
+
+  test("redirects git clones, keeping the method for POSTs", async () => {
+    const refs = await request(`/${oldUsername}/${repo}/info/refs?service=git-upload-pack`);
+    expect(refs.status).toBe(301);
+    expect(refs.headers.get("Location")).toBe(`/${user.username}/${repo}/info/refs?service=git-upload-pack`);
+
+    const upload = await request(`/${oldUsername}/${repo}/git-upload-pack`, { method: "POST", body: "0000" });
+    expect(upload.status).toBe(308);
+  });
+
+  test("stops redirecting once someone else takes the old username", async () => {
+    const response = await request("/api/auth/sign-up/email", {
+      method: "POST",
+      json: { email: `${oldUsername}-2@example.com`, password: "correct-horse-battery-staple", name: oldUsername, username: oldUsername },
+    });
+    expect(response.ok).toBe(true);
+
+    const repoResponse = await request(`/api/repositories/${oldUsername}/${repo}`);
+    expect(repoResponse.status).toBe(404);
+  });
+});
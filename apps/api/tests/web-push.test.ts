@@ -0,0 +1,23 @@
+import { describe, expect, test } from "bun:test";
+import { encryptPayload } from "../src/web-push";
+
+describe("encryptPayload", () => {
+  // The worked example from RFC 8291 section 5, with its server key pair and salt fixed.
+  test("matches the RFC 8291 example", () => {
+    const body = encryptPayload(
+      {
+        p256dh: "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4",
+        auth: "BTBZMqHH6r4Tts7J_aSIgg",
+      },
+      "When I grow up, I want to be a watermelon",
+      {
+        serverPrivateKey: Buffer.from("yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw", "base64url"),
+        salt: Buffer.from("DGv6ra1nlYgDCS1FRnbzlw", "base64url"),
+      }
+    );
+    expect(body.toString("base64url")).toBe(
+      "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A_" +
+        "yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBWLVWGNWQexSgSxsj_Qulcy4a-fN"
+    );
+  });
+});
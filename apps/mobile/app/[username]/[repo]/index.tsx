@@ -4,6 +4,7 @@ import { useLocalSearchParams, Link, Stack, router } from "expo-router";
 import FontAwesome from "@expo/vector-icons/FontAwesome";
 import { BlurView } from "expo-blur";
 import { type FileEntry, useRepositoryInfo, useRepoTree, useRepoReadmeOid, useRepoReadme, useToggleStar, useForkRepository } from "@gitbruv/hooks";
+import { validateRepositoryName } from "@gitbruv/lib";
 import { useQueryClient } from "@tanstack/react-query";
 import Markdown from "react-native-markdown-display";
 import { markdownStyles } from "@/constants/markdownStyles";
@@ -78,8 +79,9 @@ export default function RepositoryScreen() {
       Alert.alert("Error", "Repository name is required");
       return;
     }
-    if (!/^[a-zA-Z0-9_.-]+$/.test(trimmed)) {
-      Alert.alert("Error", "Invalid repository name");
+    const nameValidation = validateRepositoryName(trimmed);
+    if (!nameValidation.valid) {
+      Alert.alert("Error", nameValidation.error ?? "Invalid repository name");
       return;
     }
     forkRepository.mutate({ name: trimmed }, {
@@ -43,7 +43,7 @@ export const api = {
 
       if (!res.ok) {
         const data = await res.json().catch(() => ({}));
-        throw new Error(data.error || "Failed to upload avatar");
+        throw new Error(data.message || "Failed to upload avatar");
       }
       return res.json();
     },
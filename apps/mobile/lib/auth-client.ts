@@ -1,6 +1,7 @@
 import { createAuthClient } from "better-auth/react";
 import * as SecureStore from "expo-secure-store";
 import { expoClient } from "@better-auth/expo/client";
+import { twoFactorClient } from "better-auth/client/plugins";
 
 const API_URL = process.env.EXPO_PUBLIC_API_URL || "http://localhost:3001";
 
@@ -13,6 +14,7 @@ export const authClient = createAuthClient({
       cookiePrefix: process.env.NODE_ENV === "production" ? "gitbruv" : "gitbruv_dev",
       storage: SecureStore,
     }),
+    twoFactorClient(),
   ],
 });
 
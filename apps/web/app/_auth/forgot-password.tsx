@@ -30,7 +30,7 @@ function ForgotPasswordPage() {
 
       if (!res.ok) {
         const data = await res.json();
-        toast.error(data.error || "Failed to send reset email");
+        toast.error(data.message || "Failed to send reset email");
         return;
       }
 
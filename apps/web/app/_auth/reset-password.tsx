@@ -49,7 +49,7 @@ function ResetPasswordPage() {
       const data = await res.json();
 
       if (!res.ok) {
-        setError(data.error || "Failed to reset password");
+        setError(data.message || "Failed to reset password");
         return;
       }
 
@@ -9,11 +9,12 @@ export const Route = createFileRoute("/_auth/verify-email")({
   component: VerifyEmailPage,
   validateSearch: (search: Record<string, unknown>) => ({
     token: (search.token as string) || "",
+    change: search.change === "1" || search.change === 1,
   }),
 });
 
 function VerifyEmailPage() {
-  const { token } = Route.useSearch();
+  const { token, change } = Route.useSearch();
   const [status, setStatus] = useState<"loading" | "success" | "error" | "no-token">("loading");
   const [errorMessage, setErrorMessage] = useState<string>("");
 
@@ -25,12 +26,18 @@ function VerifyEmailPage() {
 
     async function verifyEmail() {
       try {
-        const res = await fetch(`${getApiUrl()}/api/auth/verify-email?token=${token}`);
+        const res = change
+          ? await fetch(`${getApiUrl()}/api/settings/email/verify`, {
+              method: "POST",
+              headers: { "Content-Type": "application/json" },
+              body: JSON.stringify({ token }),
+            })
+          : await fetch(`${getApiUrl()}/api/auth/verify-email?token=${token}`);
         const data = await res.json();
 
         if (!res.ok) {
           setStatus("error");
-          setErrorMessage(data.error || "Failed to verify email");
+          setErrorMessage(data.message || "Failed to verify email");
           return;
         }
 
@@ -42,7 +49,7 @@ function VerifyEmailPage() {
     }
 
     verifyEmail();
-  }, [token]);
+  }, [token, change]);
 
   if (status === "loading") {
     return (
@@ -12,6 +12,7 @@ import {
   KanbanIcon,
 } from "@hugeicons-pro/core-stroke-standard";
 import { useForkRepository, useIssueCount, usePullRequestCount, useRepoBranches, useRepoCommitCount, useRepositoryInfo, useApi } from "@gitbruv/hooks";
+import { validateRepositoryName } from "@gitbruv/lib";
 import { useQueryClient } from "@tanstack/react-query";
 import { BranchSelector } from "@/components/branch-selector";
 import { CloneUrl } from "@/components/clone-url";
@@ -160,8 +161,9 @@ function RepoLayoutContent() {
       toast.error("Repository name is required");
       return;
     }
-    if (!/^[a-zA-Z0-9_.-]+$/.test(trimmed)) {
-      toast.error("Invalid repository name");
+    const nameValidation = validateRepositoryName(trimmed);
+    if (!nameValidation.valid) {
+      toast.error(nameValidation.error ?? "Invalid repository name");
       return;
     }
     forkMutation.mutate({ name: trimmed }, {
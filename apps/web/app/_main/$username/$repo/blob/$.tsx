@@ -1,5 +1,6 @@
 import { useSession } from "@/lib/auth-client";
 import { useRepoFile, useRepositoryWithStars, useWordWrapPreference } from "@gitbruv/hooks";
+import { timeAgo } from "@gitbruv/lib";
 import { ArrowRight01Icon, CodeIcon, HomeIcon } from "@hugeicons-pro/core-stroke-standard";
 import { HugeiconsIcon } from "@hugeicons/react";
 import {
@@ -46,7 +47,7 @@ function BlobPage() {
   const { data: wordWrapData } = useWordWrapPreference({ enabled: !!session?.user });
 
   const { data: repo, isLoading: repoLoading, error: repoError } = useRepositoryWithStars(username, repoName);
-  const { data: fileData, isLoading: fileLoading, error: fileError } = useRepoFile(username, repoName, branch, filePath);
+  const { data: fileData, isLoading: fileLoading, error: fileError } = useRepoFile(username, repoName, branch, filePath, { lastCommit: true });
 
   if (repoLoading) {
     return <PageSkeleton />;
@@ -97,6 +98,14 @@ function BlobPage() {
             <HugeiconsIcon icon={CodeIcon} strokeWidth={2} className="size-4" />
             <span>{fileName}</span>
           </div>
+          {fileData?.lastCommit && (
+            <div className="flex items-center gap-2 min-w-0 text-xs text-muted-foreground">
+              <span className="font-medium text-foreground shrink-0">{fileData.lastCommit.authorName}</span>
+              <span className="truncate">{fileData.lastCommit.message}</span>
+              <code className="font-mono shrink-0">{fileData.lastCommit.commitOid.substring(0, 7)}</code>
+              <span className="shrink-0">{timeAgo(fileData.lastCommit.timestamp)}</span>
+            </div>
+          )}
         </div>
 
         {fileLoading ? (
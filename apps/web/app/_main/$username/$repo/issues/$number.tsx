@@ -5,7 +5,7 @@ import { useQueryClient } from "@tanstack/react-query";
 import {
   useIssue,
   useLabels,
-  useIssueComments,
+  useInfiniteIssueComments,
   useUpdateIssue,
   useDeleteIssue,
   useAddLabelsToIssue,
@@ -17,6 +17,7 @@ import {
   useRepositoryInfo,
 } from "@gitbruv/hooks";
 import { IssueDetail, CommentList, CommentForm, StateBadge } from "@/components/issues";
+import { Button } from "@/components/ui/button";
 import { authClient } from "@/lib/auth-client";
 import { api } from "@/lib/api/client";
 
@@ -36,7 +37,13 @@ function IssueDetailPage() {
   const { data: repoInfo, isLoading: isLoadingRepo } = useRepositoryInfo(username, repo);
   const { data: issue, isLoading: isLoadingIssue } = useIssue(username, repo, issueNumber);
   const { data: labelsData, isLoading: isLoadingLabels } = useLabels(username, repo);
-  const { data: commentsData, isLoading: isLoadingComments } = useIssueComments(issue?.id || "");
+  const {
+    data: commentsData,
+    isLoading: isLoadingComments,
+    hasNextPage: hasMoreComments,
+    fetchNextPage: fetchMoreComments,
+    isFetchingNextPage: isFetchingMoreComments,
+  } = useInfiniteIssueComments(issue?.id || "");
 
   const updateIssue = useUpdateIssue(issue?.id || "", username, repo);
   const deleteIssue = useDeleteIssue(issue?.id || "", username, repo);
@@ -49,7 +56,7 @@ function IssueDetailPage() {
 
   const isLoading = isLoadingRepo || isLoadingIssue || isLoadingLabels;
   const labels = labelsData?.labels || [];
-  const comments = commentsData?.comments || [];
+  const comments = commentsData?.pages.flatMap((page) => page.comments) || [];
   const isOwner = repoInfo?.isOwner || false;
 
   const availableAssignees = issue
@@ -192,6 +199,14 @@ function IssueDetailPage() {
           />
         )}
 
+        {hasMoreComments && (
+          <div className="flex justify-center">
+            <Button variant="outline" onClick={() => fetchMoreComments()} disabled={isFetchingMoreComments}>
+              {isFetchingMoreComments ? "Loading..." : "Load more comments"}
+            </Button>
+          </div>
+        )}
+
         {currentUserId && !issue.locked && (
           <div className="mt-6 border-t border-border pt-6">
             <CommentForm
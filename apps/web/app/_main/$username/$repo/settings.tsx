@@ -81,9 +81,9 @@ function RepoSettingsPage() {
   }
 
   async function handleDelete() {
-    if (!repo || deleteConfirm !== repo.name) return;
+    if (!repo || deleteConfirm !== `${username}/${repo.name}`) return;
 
-    deleteRepo(undefined, {
+    deleteRepo(deleteConfirm, {
       onSuccess: () => {
         toast.success("Repository deleted");
         navigate({ to: "/$username", params: { username } });
@@ -241,15 +241,24 @@ function RepoSettingsPage() {
                 </DialogHeader>
                 <div className="space-y-2 py-4">
                   <Label htmlFor="confirm">
-                    Type <strong>{repo.name}</strong> to confirm
+                    Type{" "}
+                    <strong>
+                      {username}/{repo.name}
+                    </strong>{" "}
+                    to confirm
                   </Label>
-                  <Input id="confirm" value={deleteConfirm} onChange={(e) => setDeleteConfirm(e.target.value)} placeholder={repo.name} />
+                  <Input
+                    id="confirm"
+                    value={deleteConfirm}
+                    onChange={(e) => setDeleteConfirm(e.target.value)}
+                    placeholder={`${username}/${repo.name}`}
+                  />
                 </div>
                 <DialogFooter>
                   <Button variant="outline" onClick={() => setDeleteOpen(false)} disabled={deleting}>
                     Cancel
                   </Button>
-                  <Button variant="destructive" onClick={handleDelete} disabled={deleteConfirm !== repo.name || deleting}>
+                  <Button variant="destructive" onClick={handleDelete} disabled={deleteConfirm !== `${username}/${repo.name}` || deleting}>
                     {deleting && <HugeiconsIcon icon={Loading02Icon} strokeWidth={2} className="size-4 mr-2 animate-spin" />}
                     Delete repository
                   </Button>
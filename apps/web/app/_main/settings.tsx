@@ -55,6 +55,7 @@ import { Tabs, TabsContent, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { SocialLinksForm } from '@/components/settings/social-links-form';
 import type { OAuthClient, OAuthConsent } from '@/lib/hooks/use-oauth';
 import { DeleteAccount } from '@/components/settings/delete-account';
+import { AccountExport } from '@/components/settings/account-export';
 import { PasswordForm } from '@/components/settings/password-form';
 import { AvatarUpload } from '@/components/settings/avatar-upload';
 import { parseAsStringLiteral, useQueryState } from '@/lib/hooks';
@@ -206,6 +207,16 @@ function AccountTab() {
         </CardContent>
       </Card>
 
+      <Card>
+        <CardHeader>
+          <CardTitle>Export Account Data</CardTitle>
+          <CardDescription>Download a copy of your data</CardDescription>
+        </CardHeader>
+        <CardContent>
+          <AccountExport />
+        </CardContent>
+      </Card>
+
       <Card className="border-red-500/20">
         <CardHeader>
           <CardTitle className="text-red-500">Danger Zone</CardTitle>
@@ -20,7 +20,7 @@ interface CommentItemProps {
 
 export function CommentItem({ comment, currentUserId, onToggleReaction, onUpdate, onDelete }: CommentItemProps) {
   const [isEditing, setIsEditing] = useState(false);
-  const [editBody, setEditBody] = useState(comment.body);
+  const [editBody, setEditBody] = useState(comment.body ?? "");
   const [showMenu, setShowMenu] = useState(false);
   const [isSubmitting, setIsSubmitting] = useState(false);
 
@@ -128,13 +128,17 @@ export function CommentItem({ comment, currentUserId, onToggleReaction, onUpdate
                 size="sm"
                 onClick={() => {
                   setIsEditing(false);
-                  setEditBody(comment.body);
+                  setEditBody(comment.body ?? "");
                 }}
               >
                 Cancel
               </Button>
             </div>
           </div>
+        ) : comment.body === null ? (
+          <p className="text-sm text-muted-foreground italic">
+            This comment was hidden{comment.hidden ? ` as ${comment.hidden.reason.replace("_", " ")}` : ""}.
+          </p>
         ) : (
           <CodeViewer content={comment.body} language="markdown" className="p-0 md:p-0" />
         )}
@@ -0,0 +1,84 @@
+import { useState } from "react";
+import { Button } from "@/components/ui/button";
+import { Label } from "@/components/ui/label";
+import { useAccountExports, useRequestAccountExport } from "@gitbruv/hooks";
+import { HugeiconsIcon } from "@hugeicons/react";
+import { Loading02Icon } from "@hugeicons-pro/core-stroke-standard";
+
+function formatSize(bytes: number | null) {
+  if (bytes === null) return "";
+  if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+}
+
+export function AccountExport() {
+  const { data } = useAccountExports();
+  const { mutate, isPending } = useRequestAccountExport();
+  const [includeRepositories, setIncludeRepositories] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const exports = data?.exports ?? [];
+  const inProgress = exports.some((e) => e.status === "pending" || e.status === "running");
+
+  function handleRequest() {
+    setError(null);
+    mutate(
+      { includeRepositories },
+      {
+        onError: (err) => {
+          setError(err instanceof Error ? err.message : "Failed to start export");
+        },
+      }
+    );
+  }
+
+  return (
+    <div className="space-y-4">
+      <p className="text-sm text-muted-foreground">
+        Download an archive of your profile, repositories list, issues and comments. We&apos;ll notify you when it&apos;s ready; archives are kept for 7
+        days.
+      </p>
+
+      <div className="flex items-center justify-between">
+        <div className="space-y-0.5">
+          <Label htmlFor="includeRepositories">Include repositories</Label>
+          <p className="text-muted-foreground text-xs">Add a git bundle of each repository you own</p>
+        </div>
+        <input
+          id="includeRepositories"
+          type="checkbox"
+          checked={includeRepositories}
+          onChange={(e) => setIncludeRepositories(e.target.checked)}
+          className="h-4 w-4 border-gray-300"
+        />
+      </div>
+
+      {error && <div className="text-sm text-red-500 bg-red-500/10 border border-red-500/20 px-3 py-2">{error}</div>}
+
+      <Button onClick={handleRequest} disabled={isPending || inProgress}>
+        {(isPending || inProgress) && <HugeiconsIcon icon={Loading02Icon} strokeWidth={2} className="size-4 mr-2 animate-spin" />}
+        {inProgress ? "Preparing export..." : "Request Export"}
+      </Button>
+
+      {exports.length > 0 && (
+        <div className="divide-y border">
+          {exports.map((e) => (
+            <div key={e.id} className="flex items-center justify-between px-3 py-2 text-sm">
+              <div>
+                <p>{new Date(e.createdAt).toLocaleString()}</p>
+                <p className="text-muted-foreground text-xs">
+                  {e.status === "failed" ? e.error || "Export failed" : e.status === "completed" ? formatSize(e.sizeBytes) : "In progress"}
+                </p>
+              </div>
+              {e.downloadUrl && (
+                <a href={e.downloadUrl} className="text-sm underline">
+                  Download
+                </a>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
@@ -15,12 +15,12 @@ interface EmailFormProps {
 export function EmailForm({ currentEmail }: EmailFormProps) {
   const { mutate, isPending } = useUpdateEmail();
   const [error, setError] = useState<string | null>(null);
-  const [success, setSuccess] = useState(false);
+  const [pendingEmail, setPendingEmail] = useState<string | null>(null);
 
   async function handleSubmit(e: React.FormEvent<HTMLFormElement>) {
     e.preventDefault();
     setError(null);
-    setSuccess(false);
+    setPendingEmail(null);
 
     const formData = new FormData(e.currentTarget);
     const email = formData.get("email") as string;
@@ -33,9 +33,8 @@ export function EmailForm({ currentEmail }: EmailFormProps) {
     mutate(
       { email },
       {
-        onSuccess: () => {
-          setSuccess(true);
-          setTimeout(() => setSuccess(false), 3000);
+        onSuccess: (data) => {
+          setPendingEmail(data.pendingEmail);
         },
         onError: (err) => {
           setError(err instanceof Error ? err.message : "Failed to update email");
@@ -54,7 +53,11 @@ export function EmailForm({ currentEmail }: EmailFormProps) {
 
       {error && <div className="text-sm text-red-500 bg-red-500/10 border border-red-500/20 px-3 py-2">{error}</div>}
 
-      {success && <div className="text-sm text-green-500 bg-green-500/10 border border-green-500/20 px-3 py-2">Email updated successfully!</div>}
+      {pendingEmail && (
+        <div className="text-sm text-green-500 bg-green-500/10 border border-green-500/20 px-3 py-2">
+          We sent a confirmation link to {pendingEmail}. Your current email stays active until you confirm.
+        </div>
+      )}
 
       <Button type="submit" disabled={isPending}>
         {isPending && <HugeiconsIcon icon={Loading02Icon} strokeWidth={2} className="size-4 mr-2 animate-spin" />}
@@ -1,11 +1,12 @@
 import { oauthProviderClient } from '@better-auth/oauth-provider/client';
 import { passkeyClient } from '@better-auth/passkey/client';
+import { twoFactorClient } from 'better-auth/client/plugins';
 import { createAuthClient } from 'better-auth/react';
 import { getApiUrl } from './utils';
 
 export const authClient = createAuthClient({
   baseURL: getApiUrl(),
-  plugins: [oauthProviderClient(), passkeyClient()],
+  plugins: [oauthProviderClient(), passkeyClient(), twoFactorClient()],
 });
 
 export const { signIn, signOut, useSession } = authClient;
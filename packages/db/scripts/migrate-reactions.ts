@@ -0,0 +1,103 @@
+// One-off migration from issue_reactions, pr_reactions and discussion_reactions to the shared
+// reactions table. Run it before `db:push` so the push finds the table already in shape and has
+// no data to drop. Safe to re-run: copied rows keep their ids, and each step checks what exists.
+import postgres from 'postgres';
+import { config } from 'dotenv';
+import { resolve } from 'path';
+
+if (!process.env.DATABASE_URL) {
+  for (const envPath of [resolve(process.cwd(), '.env'), resolve(process.cwd(), '../.env'), resolve(process.cwd(), '../../.env')]) {
+    if (config({ path: envPath }).parsed?.DATABASE_URL) break;
+  }
+}
+
+// Each legacy row holds either its parent's id or a comment's id, never both.
+const LEGACY_SOURCES = [
+  { table: 'issue_reactions', parentType: 'issue', parentColumn: 'issue_id', commentType: 'issue_comment' },
+  { table: 'pr_reactions', parentType: 'pull_request', parentColumn: 'pull_request_id', commentType: 'pr_comment' },
+  { table: 'discussion_reactions', parentType: 'discussion', parentColumn: 'discussion_id', commentType: 'discussion_comment' },
+];
+
+const SUBJECT_REPOSITORIES: Record<string, string> = {
+  issue: 'SELECT id, repository_id FROM issues',
+  issue_comment: 'SELECT c.id, i.repository_id FROM issue_comments c JOIN issues i ON i.id = c.issue_id',
+  pull_request: 'SELECT id, repository_id FROM pull_requests',
+  pr_comment: 'SELECT c.id, p.repository_id FROM pr_comments c JOIN pull_requests p ON p.id = c.pull_request_id',
+  discussion: 'SELECT id, repository_id FROM discussions',
+  discussion_comment: 'SELECT c.id, d.repository_id FROM discussion_comments c JOIN discussions d ON d.id = c.discussion_id',
+};
+
+const sql = postgres(process.env.DATABASE_URL!);
+
+try {
+  await sql.begin(async (tx) => {
+    await tx.unsafe(`
+      CREATE TABLE IF NOT EXISTS reactions (
+        id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+        subject_type text NOT NULL,
+        subject_id uuid NOT NULL,
+        user_id text NOT NULL,
+        emoji text NOT NULL,
+        created_at timestamp NOT NULL DEFAULT now(),
+        CONSTRAINT reactions_user_id_users_id_fk FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+      )
+    `);
+    await tx.unsafe('ALTER TABLE reactions ADD COLUMN IF NOT EXISTS repository_id uuid');
+
+    for (const source of LEGACY_SOURCES) {
+      const [{ exists }] = await tx.unsafe<{ exists: boolean }[]>(`SELECT to_regclass('${source.table}') IS NOT NULL AS exists`);
+      if (!exists) continue;
+
+      const copied = await tx.unsafe(`
+        INSERT INTO reactions (id, subject_type, subject_id, user_id, emoji, created_at)
+        SELECT id,
+          CASE WHEN comment_id IS NULL THEN '${source.parentType}' ELSE '${source.commentType}' END,
+          COALESCE(comment_id, ${source.parentColumn}),
+          user_id, emoji, created_at
+        FROM ${source.table}
+        WHERE COALESCE(comment_id, ${source.parentColumn}) IS NOT NULL
+        ON CONFLICT (id) DO NOTHING
+      `);
+      console.log(`[migrate-reactions] copied ${copied.count} rows from ${source.table}`);
+    }
+
+    // Copied rows, and rows written before reactions carried a repository, get one from their
+    // subject; rows whose subject is gone have nothing to attach to.
+    for (const [subjectType, query] of Object.entries(SUBJECT_REPOSITORIES)) {
+      await tx.unsafe(`
+        UPDATE reactions r SET repository_id = s.repository_id
+        FROM (${query}) s
+        WHERE r.subject_type = '${subjectType}' AND r.subject_id = s.id AND r.repository_id IS NULL
+      `);
+    }
+    const orphans = await tx.unsafe('DELETE FROM reactions WHERE repository_id IS NULL');
+    console.log(`[migrate-reactions] removed ${orphans.count} reactions on deleted subjects`);
+
+    // The old tables never enforced one reaction per user and emoji; keep the earliest of each.
+    const duplicates = await tx.unsafe(`
+      DELETE FROM reactions a USING reactions b
+      WHERE a.subject_type = b.subject_type AND a.subject_id = b.subject_id AND a.user_id = b.user_id AND a.emoji = b.emoji
+        AND (a.created_at, a.id) > (b.created_at, b.id)
+    `);
+    console.log(`[migrate-reactions] removed ${duplicates.count} duplicate reactions`);
+
+    await tx.unsafe('ALTER TABLE reactions ALTER COLUMN repository_id SET NOT NULL');
+    await tx.unsafe('ALTER TABLE reactions DROP CONSTRAINT IF EXISTS reactions_repository_id_repositories_id_fk');
+    await tx.unsafe(`
+      ALTER TABLE reactions ADD CONSTRAINT reactions_repository_id_repositories_id_fk
+      FOREIGN KEY (repository_id) REFERENCES repositories(id) ON DELETE CASCADE
+    `);
+    await tx.unsafe('CREATE INDEX IF NOT EXISTS reactions_subject_idx ON reactions (subject_type, subject_id)');
+    await tx.unsafe('CREATE INDEX IF NOT EXISTS reactions_repository_id_idx ON reactions (repository_id)');
+    await tx.unsafe(
+      'CREATE UNIQUE INDEX IF NOT EXISTS reactions_subject_user_emoji_unique ON reactions (subject_type, subject_id, user_id, emoji)'
+    );
+
+    for (const source of LEGACY_SOURCES) {
+      await tx.unsafe(`DROP TABLE IF EXISTS ${source.table}`);
+    }
+  });
+  console.log('[migrate-reactions] done');
+} finally {
+  await sql.end();
+}
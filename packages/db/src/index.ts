@@ -24,10 +24,23 @@ export function createDatabase(connectionString: string) {
   return drizzle(client, { schema });
 }
 
+let queryObserver: ((query: string) => void) | null = null;
+
+// Lets the API attach request context to statements without this package knowing about it.
+// Returns the observer it replaced so a caller can chain to it and put it back.
+export function setQueryObserver(observer: ((query: string) => void) | null) {
+  const previous = queryObserver;
+  queryObserver = observer;
+  return previous;
+}
+
 const connectionString = process.env.DATABASE_URL!;
 const client = postgres(connectionString);
 export const db = drizzle(client, {
   schema,
+  logger: {
+    logQuery: (query) => queryObserver?.(query),
+  },
   // USE REDIS
   // cache: upstashCache({
   //   url: process.env.UPSTASH_REDIS_REST_URL,
@@ -21,6 +21,18 @@ const tsvector = customType<{ data: string }>({
   },
 });
 
+export type RepoLanguageStat = {
+  language: string;
+  bytes: number;
+  percentage: number;
+};
+
+export type ExternalReference = {
+  prefix: string;
+  urlTemplate: string;
+  alphanumeric: boolean;
+};
+
 export type UserPreferences = {
   emailNotifications?: boolean;
   theme?: 'light' | 'dark' | 'system';
@@ -42,7 +54,9 @@ export const users = pgTable('users', {
   avatarUrl: text('avatar_url'),
   company: text('company'),
   lastActiveAt: timestamp('last_active_at'),
+  dashboardVisitedAt: timestamp('dashboard_visited_at'),
   gitEmail: text('git_email'),
+  twoFactorEnabled: boolean('two_factor_enabled').notNull().default(false),
   defaultRepositoryVisibility: text('default_repository_visibility', {
     enum: ['public', 'private'],
   })
@@ -59,6 +73,20 @@ export const users = pgTable('users', {
   updatedAt: timestamp('updated_at').notNull().defaultNow(),
 });
 
+// Usernames a user has given up, so links under the old name keep working. A name is removed
+// from here as soon as anyone takes it again.
+export const usernameHistory = pgTable(
+  'username_history',
+  {
+    username: text('username').primaryKey(),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    changedAt: timestamp('changed_at').notNull().defaultNow(),
+  },
+  (table) => [index('username_history_user_id_idx').on(table.userId)],
+);
+
 export const sessions = pgTable('sessions', {
   id: text('id').primaryKey(),
   expiresAt: timestamp('expires_at').notNull(),
@@ -115,6 +143,28 @@ export const repositories = pgTable(
       .notNull()
       .default('public'),
     defaultBranch: text('default_branch').notNull().default('main'),
+    reviewReminderDays: integer('review_reminder_days'),
+    deleteBranchOnMerge: boolean('delete_branch_on_merge').notNull().default(false),
+    isTemplate: boolean('is_template').notNull().default(false),
+    externalReferences: jsonb('external_references').$type<ExternalReference[]>(),
+    languages: jsonb('languages').$type<RepoLanguageStat[]>(),
+    languagesCommitOid: text('languages_commit_oid'),
+    // SPDX identifier detected from the default branch's LICENSE file, or 'other' when unrecognised.
+    license: text('license'),
+    // Fork network whose shared prefix holds this repository's loose objects; null while they are
+    // still stored under the repository's own prefix.
+    objectNetworkId: uuid('object_network_id'),
+    topics: text('topics')
+      .array()
+      .notNull()
+      .default(sql`'{}'::text[]`),
+    mirrorUrl: text('mirror_url'),
+    mirrorIntervalMinutes: integer('mirror_interval_minutes'),
+    mirrorSyncStatus: text('mirror_sync_status', { enum: ['pending', 'syncing', 'succeeded', 'failed'] }),
+    // Set while a sync holds the mirror, so one abandoned by a lost instance can be reclaimed.
+    mirrorSyncStartedAt: timestamp('mirror_sync_started_at'),
+    mirrorLastSyncedAt: timestamp('mirror_last_synced_at'),
+    mirrorLastError: text('mirror_last_error'),
     searchVector: tsvector('search_vector'),
     createdAt: timestamp('created_at').notNull().defaultNow(),
     updatedAt: timestamp('updated_at').notNull().defaultNow(),
@@ -122,9 +172,27 @@ export const repositories = pgTable(
   (table) => [
     index('repositories_forked_from_id_idx').on(table.forkedFromId),
     index('repositories_search_idx').using('gin', table.searchVector),
+    index('repositories_topics_idx').using('gin', table.topics),
+    index('repositories_object_network_id_idx').on(table.objectNetworkId),
   ],
 );
 
+// Parsed from the default branch's CODEOWNERS file on push. Rules are kept in file order because
+// the last matching pattern decides a path's owners.
+export const codeOwnerRules = pgTable(
+  'code_owner_rules',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    position: integer('position').notNull(),
+    pattern: text('pattern').notNull(),
+    owners: text('owners').array().notNull(),
+  },
+  (table) => [index('code_owner_rules_repository_id_idx').on(table.repositoryId, table.position)],
+);
+
 export const repoBranchMetadata = pgTable(
   'repo_branch_metadata',
   {
@@ -140,8 +208,16 @@ export const repoBranchMetadata = pgTable(
     lastCommitAuthorEmail: text('last_commit_author_email').notNull(),
     lastCommitTimestamp: timestamp('last_commit_timestamp').notNull(),
     readmeOid: text('readme_oid'),
-    rootTree:
-      jsonb('root_tree').$type<{ name: string; type: string; oid: string; path: string }[]>(),
+    rootTree: jsonb('root_tree').$type<
+      {
+        name: string;
+        type: string;
+        oid: string;
+        path: string;
+        mode?: string;
+        submoduleUrl?: string | null;
+      }[]
+    >(),
     createdAt: timestamp('created_at').notNull().defaultNow(),
     updatedAt: timestamp('updated_at').notNull().defaultNow(),
   },
@@ -151,6 +227,108 @@ export const repoBranchMetadata = pgTable(
   ],
 );
 
+// One row per commit: its generation number (1 + the highest parent generation) and the number of
+// commits reachable from it, itself included. A row only exists once all of its ancestors have one.
+export const commitCounts = pgTable(
+  'commit_counts',
+  {
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    oid: text('oid').notNull(),
+    generation: integer('generation').notNull(),
+    count: bigint('count', { mode: 'number' }).notNull(),
+  },
+  (table) => [primaryKey({ columns: [table.repositoryId, table.oid] })],
+);
+
+// The newest commit that changed each entry of a directory, keyed by the directory's tree oid and
+// the entry name. A null commit means the history walk gave up before finding one.
+export const treeEntryCommits = pgTable(
+  'tree_entry_commits',
+  {
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    treeOid: text('tree_oid').notNull(),
+    name: text('name').notNull(),
+    commitOid: text('commit_oid'),
+    message: text('message'),
+    authorName: text('author_name'),
+    timestamp: bigint('timestamp', { mode: 'number' }),
+  },
+  (table) => [primaryKey({ columns: [table.repositoryId, table.treeOid, table.name] })],
+);
+
+export const repoInsights = pgTable('repo_insights', {
+  repositoryId: uuid('repository_id')
+    .primaryKey()
+    .references(() => repositories.id, { onDelete: 'cascade' }),
+  headOid: text('head_oid').notNull(),
+  // [week start (unix seconds, Sunday UTC), additions, -deletions]
+  codeFrequency: jsonb('code_frequency').$type<[number, number, number][]>().notNull(),
+  // [weekday (0 = Sunday), hour, commits]
+  punchCard: jsonb('punch_card').$type<[number, number, number][]>().notNull(),
+  commitCount: integer('commit_count').notNull(),
+  truncated: boolean('truncated').notNull().default(false),
+  computedAt: timestamp('computed_at').notNull().defaultNow(),
+});
+
+export const repoSizes = pgTable('repo_sizes', {
+  repositoryId: uuid('repository_id')
+    .primaryKey()
+    .references(() => repositories.id, { onDelete: 'cascade' }),
+  totalBytes: bigint('total_bytes', { mode: 'number' }).notNull(),
+  packCount: integer('pack_count').notNull(),
+  packBytes: bigint('pack_bytes', { mode: 'number' }).notNull(),
+  looseObjectCount: integer('loose_object_count').notNull(),
+  looseBytes: bigint('loose_bytes', { mode: 'number' }).notNull(),
+  sharedObjectCount: integer('shared_object_count').notNull().default(0),
+  sharedBytes: bigint('shared_bytes', { mode: 'number' }).notNull().default(0),
+  // Largest first; `size` is the blob's own size, `storedBytes` what it takes up compressed.
+  largestBlobs: jsonb('largest_blobs').$type<{ oid: string; size: number; storedBytes: number }[]>().notNull(),
+  computedAt: timestamp('computed_at').notNull().defaultNow(),
+});
+
+export const activityEvents = pgTable(
+  'activity_events',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    type: text('type').notNull(),
+    actorId: text('actor_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    payload: jsonb('payload').$type<Record<string, unknown>>().notNull().default({}),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('activity_events_actor_idx').on(table.actorId, table.createdAt),
+    index('activity_events_repository_idx').on(table.repositoryId, table.createdAt),
+    index('activity_events_created_at_idx').on(table.createdAt),
+  ],
+);
+
+export const refLog = pgTable(
+  'ref_log',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    ref: text('ref').notNull(),
+    oldOid: text('old_oid').notNull(),
+    newOid: text('new_oid').notNull(),
+    // Null for deploy-key pushes, which have no user behind them.
+    actorId: text('actor_id').references(() => users.id, { onDelete: 'set null' }),
+    source: text('source', { enum: ['push', 'commit', 'patch', 'merge', 'branch_delete', 'restore'] }).notNull(),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('ref_log_repository_ref_idx').on(table.repositoryId, table.ref, table.createdAt)],
+);
+
 export const branchProtectionRules = pgTable(
   'branch_protection_rules',
   {
@@ -174,6 +352,96 @@ export const branchProtectionRules = pgTable(
   ],
 );
 
+// Server-side checks applied to every commit a push introduces. A missing row means no policy.
+export const pushPolicies = pgTable('push_policies', {
+  repositoryId: uuid('repository_id')
+    .primaryKey()
+    .references(() => repositories.id, { onDelete: 'cascade' }),
+  commitMessagePattern: text('commit_message_pattern'),
+  requirePusherEmail: boolean('require_pusher_email').notNull().default(false),
+  maxFileSizeMb: integer('max_file_size_mb'),
+  forbiddenPaths: text('forbidden_paths')
+    .array()
+    .notNull()
+    .default(sql`'{}'::text[]`),
+  updatedAt: timestamp('updated_at').notNull().defaultNow(),
+});
+
+export const repoReadTokens = pgTable(
+  'repo_read_tokens',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    createdById: text('created_by_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    name: text('name'),
+    tokenHash: text('token_hash').notNull().unique(),
+    path: text('path').notNull().default(''),
+    expiresAt: timestamp('expires_at').notNull(),
+    lastUsedAt: timestamp('last_used_at'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('repo_read_tokens_repository_id_idx').on(table.repositoryId)],
+);
+
+export const deployKeys = pgTable(
+  'deploy_keys',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    createdById: text('created_by_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    title: text('title').notNull(),
+    keyHash: text('key_hash').notNull().unique(),
+    readOnly: boolean('read_only').notNull().default(true),
+    lastUsedAt: timestamp('last_used_at'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('deploy_keys_repository_id_idx').on(table.repositoryId)],
+);
+
+export const gitCredentials = pgTable(
+  'git_credentials',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    tokenHash: text('token_hash').notNull(),
+    expiresAt: timestamp('expires_at').notNull(),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('git_credentials_user_id_idx').on(table.userId)],
+);
+
+export const commitStatuses = pgTable(
+  'commit_statuses',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    commitOid: text('commit_oid').notNull(),
+    state: text('state', { enum: ['pending', 'success', 'failure', 'error'] }).notNull(),
+    context: text('context').notNull().default('default'),
+    targetUrl: text('target_url'),
+    description: text('description'),
+    creatorId: text('creator_id').references(() => users.id, { onDelete: 'set null' }),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+    updatedAt: timestamp('updated_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('commit_statuses_repo_commit_idx').on(table.repositoryId, table.commitOid),
+    uniqueIndex('commit_statuses_repo_commit_context_unique').on(table.repositoryId, table.commitOid, table.context),
+  ],
+);
+
 export const stars = pgTable(
   'stars',
   {
@@ -188,6 +456,23 @@ export const stars = pgTable(
   (table) => [primaryKey({ columns: [table.userId, table.repositoryId] })],
 );
 
+export const follows = pgTable(
+  'follows',
+  {
+    followerId: text('follower_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    followingId: text('following_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    primaryKey({ columns: [table.followerId, table.followingId] }),
+    index('follows_following_id_idx').on(table.followingId, table.createdAt),
+  ],
+);
+
 export const issues = pgTable(
   'issues',
   {
@@ -205,6 +490,7 @@ export const issues = pgTable(
       .notNull()
       .default('open'),
     locked: boolean('locked').notNull().default(false),
+    stateReason: text('state_reason', { enum: ['completed', 'not_planned', 'duplicate'] }),
     closedAt: timestamp('closed_at'),
     closedById: text('closed_by_id').references(() => users.id),
     searchVector: tsvector('search_vector'),
@@ -218,6 +504,13 @@ export const issues = pgTable(
   ],
 );
 
+export const issueCounters = pgTable('issue_counters', {
+  repositoryId: uuid('repository_id')
+    .primaryKey()
+    .references(() => repositories.id, { onDelete: 'cascade' }),
+  lastNumber: integer('last_number').notNull().default(0),
+});
+
 export const labels = pgTable(
   'labels',
   {
@@ -230,7 +523,10 @@ export const labels = pgTable(
     color: text('color').notNull().default('6b7280'),
     createdAt: timestamp('created_at').notNull().defaultNow(),
   },
-  (table) => [index('labels_repository_id_idx').on(table.repositoryId)],
+  (table) => [
+    index('labels_repository_id_idx').on(table.repositoryId),
+    uniqueIndex('labels_repository_name_unique').on(table.repositoryId, table.name),
+  ],
 );
 
 export const issueLabels = pgTable(
@@ -271,18 +567,57 @@ export const issueComments = pgTable(
       .notNull()
       .references(() => users.id, { onDelete: 'cascade' }),
     body: text('body').notNull(),
+    hiddenReason: text('hidden_reason', { enum: ['spam', 'abuse', 'off_topic', 'outdated', 'resolved', 'duplicate'] }),
+    hiddenAt: timestamp('hidden_at'),
+    hiddenById: text('hidden_by_id').references(() => users.id, { onDelete: 'set null' }),
     createdAt: timestamp('created_at').notNull().defaultNow(),
     updatedAt: timestamp('updated_at').notNull().defaultNow(),
   },
   (table) => [index('issue_comments_issue_id_idx').on(table.issueId)],
 );
 
-export const issueReactions = pgTable(
-  'issue_reactions',
+export const contentReports = pgTable(
+  'content_reports',
   {
     id: uuid('id').primaryKey().defaultRandom(),
-    issueId: uuid('issue_id').references(() => issues.id, { onDelete: 'cascade' }),
-    commentId: uuid('comment_id').references(() => issueComments.id, { onDelete: 'cascade' }),
+    reporterId: text('reporter_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    subjectType: text('subject_type', { enum: ['issue', 'issue_comment'] }).notNull(),
+    subjectId: uuid('subject_id').notNull(),
+    reason: text('reason', { enum: ['spam', 'abuse', 'off_topic', 'other'] }).notNull(),
+    details: text('details'),
+    status: text('status', { enum: ['open', 'resolved', 'dismissed'] })
+      .notNull()
+      .default('open'),
+    resolvedById: text('resolved_by_id').references(() => users.id, { onDelete: 'set null' }),
+    resolvedAt: timestamp('resolved_at'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('content_reports_repository_status_idx').on(table.repositoryId, table.status, table.createdAt),
+    index('content_reports_status_created_at_idx').on(table.status, table.createdAt),
+    index('content_reports_subject_idx').on(table.subjectType, table.subjectId),
+    uniqueIndex('content_reports_reporter_subject_unique').on(table.reporterId, table.subjectType, table.subjectId),
+  ],
+);
+
+export const reactions = pgTable(
+  'reactions',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    subjectType: text('subject_type', {
+      enum: ['issue', 'issue_comment', 'pull_request', 'pr_comment', 'discussion', 'discussion_comment'],
+    }).notNull(),
+    subjectId: uuid('subject_id').notNull(),
+    // Subjects can't be foreign keys, but their repository can, so deleting a repository still
+    // takes its reactions with it.
+    repositoryId: uuid('repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
     userId: text('user_id')
       .notNull()
       .references(() => users.id, { onDelete: 'cascade' }),
@@ -290,8 +625,73 @@ export const issueReactions = pgTable(
     createdAt: timestamp('created_at').notNull().defaultNow(),
   },
   (table) => [
-    index('issue_reactions_issue_id_idx').on(table.issueId),
-    index('issue_reactions_comment_id_idx').on(table.commentId),
+    index('reactions_subject_idx').on(table.subjectType, table.subjectId),
+    index('reactions_repository_id_idx').on(table.repositoryId),
+    uniqueIndex('reactions_subject_user_emoji_unique').on(table.subjectType, table.subjectId, table.userId, table.emoji),
+  ],
+);
+
+export const issueReferences = pgTable(
+  'issue_references',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    targetIssueId: uuid('target_issue_id')
+      .notNull()
+      .references(() => issues.id, { onDelete: 'cascade' }),
+    sourceType: text('source_type', { enum: ['issue', 'pull_request', 'commit'] }).notNull(),
+    sourceRepositoryId: uuid('source_repository_id')
+      .notNull()
+      .references(() => repositories.id, { onDelete: 'cascade' }),
+    sourceIssueId: uuid('source_issue_id').references(() => issues.id, { onDelete: 'cascade' }),
+    sourcePullRequestId: uuid('source_pull_request_id').references(() => pullRequests.id, {
+      onDelete: 'cascade',
+    }),
+    sourceCommitOid: text('source_commit_oid'),
+    actorId: text('actor_id').references(() => users.id, { onDelete: 'set null' }),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('issue_references_target_issue_id_idx').on(table.targetIssueId),
+    index('issue_references_source_issue_id_idx').on(table.sourceIssueId),
+    index('issue_references_source_pull_request_id_idx').on(table.sourcePullRequestId),
+    uniqueIndex('issue_references_issue_unique').on(table.targetIssueId, table.sourceIssueId),
+    uniqueIndex('issue_references_pull_request_unique').on(table.targetIssueId, table.sourcePullRequestId),
+    uniqueIndex('issue_references_commit_unique').on(table.targetIssueId, table.sourceCommitOid),
+  ],
+);
+
+export const issueDuplicates = pgTable(
+  'issue_duplicates',
+  {
+    issueId: uuid('issue_id')
+      .primaryKey()
+      .references(() => issues.id, { onDelete: 'cascade' }),
+    duplicateOfId: uuid('duplicate_of_id')
+      .notNull()
+      .references(() => issues.id, { onDelete: 'cascade' }),
+    actorId: text('actor_id').references(() => users.id, { onDelete: 'set null' }),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('issue_duplicates_duplicate_of_id_idx').on(table.duplicateOfId)],
+);
+
+export const issueMentions = pgTable(
+  'issue_mentions',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    issueId: uuid('issue_id')
+      .notNull()
+      .references(() => issues.id, { onDelete: 'cascade' }),
+    commentId: uuid('comment_id').references(() => issueComments.id, { onDelete: 'cascade' }),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    actorId: text('actor_id').references(() => users.id, { onDelete: 'set null' }),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('issue_mentions_issue_id_idx').on(table.issueId),
+    uniqueIndex('issue_mentions_issue_user_unique').on(table.issueId, table.userId),
   ],
 );
 
@@ -451,26 +851,6 @@ export const prReviewers = pgTable(
   (table) => [primaryKey({ columns: [table.pullRequestId, table.userId] })],
 );
 
-export const prReactions = pgTable(
-  'pr_reactions',
-  {
-    id: uuid('id').primaryKey().defaultRandom(),
-    pullRequestId: uuid('pull_request_id').references(() => pullRequests.id, {
-      onDelete: 'cascade',
-    }),
-    commentId: uuid('comment_id').references(() => prComments.id, { onDelete: 'cascade' }),
-    userId: text('user_id')
-      .notNull()
-      .references(() => users.id, { onDelete: 'cascade' }),
-    emoji: text('emoji').notNull(),
-    createdAt: timestamp('created_at').notNull().defaultNow(),
-  },
-  (table) => [
-    index('pr_reactions_pull_request_id_idx').on(table.pullRequestId),
-    index('pr_reactions_comment_id_idx').on(table.commentId),
-  ],
-);
-
 export const discussionCategories = pgTable(
   'discussion_categories',
   {
@@ -539,24 +919,6 @@ export const discussionComments = pgTable(
   ],
 );
 
-export const discussionReactions = pgTable(
-  'discussion_reactions',
-  {
-    id: uuid('id').primaryKey().defaultRandom(),
-    discussionId: uuid('discussion_id').references(() => discussions.id, { onDelete: 'cascade' }),
-    commentId: uuid('comment_id').references(() => discussionComments.id, { onDelete: 'cascade' }),
-    userId: text('user_id')
-      .notNull()
-      .references(() => users.id, { onDelete: 'cascade' }),
-    emoji: text('emoji').notNull(),
-    createdAt: timestamp('created_at').notNull().defaultNow(),
-  },
-  (table) => [
-    index('discussion_reactions_discussion_id_idx').on(table.discussionId),
-    index('discussion_reactions_comment_id_idx').on(table.commentId),
-  ],
-);
-
 export const discussionCategoryRelations = relations(discussionCategories, ({ one, many }) => ({
   repository: one(repositories, {
     fields: [discussionCategories.repositoryId],
@@ -579,7 +941,6 @@ export const discussionRelations = relations(discussions, ({ one, many }) => ({
     references: [users.id],
   }),
   comments: many(discussionComments),
-  reactions: many(discussionReactions),
 }));
 
 export const discussionCommentRelations = relations(discussionComments, ({ one, many }) => ({
@@ -597,22 +958,6 @@ export const discussionCommentRelations = relations(discussionComments, ({ one,
     relationName: 'parentChild',
   }),
   replies: many(discussionComments, { relationName: 'parentChild' }),
-  reactions: many(discussionReactions),
-}));
-
-export const discussionReactionRelations = relations(discussionReactions, ({ one }) => ({
-  discussion: one(discussions, {
-    fields: [discussionReactions.discussionId],
-    references: [discussions.id],
-  }),
-  comment: one(discussionComments, {
-    fields: [discussionReactions.commentId],
-    references: [discussionComments.id],
-  }),
-  user: one(users, {
-    fields: [discussionReactions.userId],
-    references: [users.id],
-  }),
 }));
 
 export const projects = pgTable(
@@ -639,6 +984,7 @@ export const projectColumns = pgTable(
       .references(() => projects.id, { onDelete: 'cascade' }),
     name: text('name').notNull(),
     position: integer('position').notNull(),
+    moveClosedIssues: boolean('move_closed_issues').notNull().default(false),
     createdAt: timestamp('created_at').notNull().defaultNow(),
   },
   (table) => [index('project_columns_project_id_idx').on(table.projectId)],
@@ -665,6 +1011,7 @@ export const projectItems = pgTable(
   (table) => [
     index('project_items_column_id_idx').on(table.columnId),
     index('project_items_project_id_idx').on(table.projectId),
+    index('project_items_issue_id_idx').on(table.issueId),
   ],
 );
 
@@ -730,6 +1077,22 @@ export const notifications = pgTable(
   ],
 );
 
+export const pushSubscriptions = pgTable(
+  'push_subscriptions',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    endpoint: text('endpoint').notNull().unique(),
+    p256dh: text('p256dh').notNull(),
+    auth: text('auth').notNull(),
+    userAgent: text('user_agent'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [index('push_subscriptions_user_id_idx').on(table.userId)],
+);
+
 export const notificationRelations = relations(notifications, ({ one }) => ({
   user: one(users, {
     fields: [notifications.userId],
@@ -741,6 +1104,103 @@ export const notificationRelations = relations(notifications, ({ one }) => ({
   }),
 }));
 
+export const pushSubscriptionRelations = relations(pushSubscriptions, ({ one }) => ({
+  user: one(users, {
+    fields: [pushSubscriptions.userId],
+    references: [users.id],
+  }),
+}));
+
+export const attachments = pgTable(
+  'attachments',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    uploaderId: text('uploader_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    repositoryId: uuid('repository_id').references(() => repositories.id, { onDelete: 'cascade' }),
+    storageKey: text('storage_key').notNull(),
+    filename: text('filename').notNull(),
+    contentType: text('content_type').notNull(),
+    sizeBytes: bigint('size_bytes', { mode: 'number' }).notNull(),
+    status: text('status', { enum: ['pending', 'finalized'] })
+      .notNull()
+      .default('pending'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+    finalizedAt: timestamp('finalized_at'),
+  },
+  (table) => [
+    index('attachments_uploader_id_idx').on(table.uploaderId),
+    index('attachments_status_created_at_idx').on(table.status, table.createdAt),
+  ],
+);
+
+export const accountExports = pgTable(
+  'account_exports',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+    status: text('status', { enum: ['pending', 'running', 'completed', 'failed'] })
+      .notNull()
+      .default('pending'),
+    includeRepositories: boolean('include_repositories').notNull().default(false),
+    storageKey: text('storage_key'),
+    sizeBytes: bigint('size_bytes', { mode: 'number' }),
+    error: text('error'),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+    completedAt: timestamp('completed_at'),
+    expiresAt: timestamp('expires_at'),
+  },
+  (table) => [index('account_exports_user_id_idx').on(table.userId)],
+);
+
+// Outlives whatever it describes: target ids aren't foreign keys and the actor is kept as a
+// snapshot so the record still reads correctly after the user or repository is gone.
+export const auditLogs = pgTable(
+  'audit_logs',
+  {
+    id: uuid('id').primaryKey().defaultRandom(),
+    actorId: text('actor_id').references(() => users.id, { onDelete: 'set null' }),
+    actorUsername: text('actor_username').notNull(),
+    action: text('action').notNull(),
+    targetType: text('target_type').notNull(),
+    targetId: text('target_id').notNull(),
+    metadata: jsonb('metadata').$type<Record<string, unknown>>().notNull().default({}),
+    createdAt: timestamp('created_at').notNull().defaultNow(),
+  },
+  (table) => [
+    index('audit_logs_actor_id_idx').on(table.actorId, table.createdAt),
+    index('audit_logs_target_idx').on(table.targetType, table.targetId),
+  ],
+);
+
+export const attachmentRelations = relations(attachments, ({ one }) => ({
+  uploader: one(users, {
+    fields: [attachments.uploaderId],
+    references: [users.id],
+  }),
+  repository: one(repositories, {
+    fields: [attachments.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const accountExportRelations = relations(accountExports, ({ one }) => ({
+  user: one(users, {
+    fields: [accountExports.userId],
+    references: [users.id],
+  }),
+}));
+
+export const auditLogRelations = relations(auditLogs, ({ one }) => ({
+  actor: one(users, {
+    fields: [auditLogs.actorId],
+    references: [users.id],
+  }),
+}));
+
 export const passkeys = pgTable(
   'passkey',
   {
@@ -764,6 +1224,19 @@ export const passkeys = pgTable(
   ],
 );
 
+export const twoFactors = pgTable(
+  'two_factor',
+  {
+    id: text('id').primaryKey(),
+    secret: text('secret').notNull(),
+    backupCodes: text('backup_codes').notNull(),
+    userId: text('user_id')
+      .notNull()
+      .references(() => users.id, { onDelete: 'cascade' }),
+  },
+  (table) => [index('two_factor_userId_idx').on(table.userId)],
+);
+
 export const jwks = pgTable('jwks', {
   id: text('id').primaryKey(),
   publicKey: text('public_key').notNull(),
@@ -854,6 +1327,13 @@ export const passkeyRelations = relations(passkeys, ({ one }) => ({
   }),
 }));
 
+export const twoFactorRelations = relations(twoFactors, ({ one }) => ({
+  user: one(users, {
+    fields: [twoFactors.userId],
+    references: [users.id],
+  }),
+}));
+
 export const oauthClientRelations = relations(oauthClients, ({ one, many }) => ({
   user: one(users, {
     fields: [oauthClients.userId],
@@ -915,6 +1395,7 @@ export const userRelations = relations(users, ({ many }) => ({
   accounts: many(accounts),
   apikeys: many(apiKeys),
   passkeys: many(passkeys),
+  twoFactors: many(twoFactors),
   oauthClients: many(oauthClients),
   oauthRefreshTokens: many(oauthRefreshTokens),
   oauthAccessTokens: many(oauthAccessTokens),
@@ -951,6 +1432,63 @@ export const repoBranchMetadataRelations = relations(repoBranchMetadata, ({ one
   }),
 }));
 
+export const codeOwnerRuleRelations = relations(codeOwnerRules, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [codeOwnerRules.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const commitCountRelations = relations(commitCounts, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [commitCounts.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const repoInsightRelations = relations(repoInsights, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [repoInsights.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const repoSizeRelations = relations(repoSizes, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [repoSizes.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const activityEventRelations = relations(activityEvents, ({ one }) => ({
+  actor: one(users, {
+    fields: [activityEvents.actorId],
+    references: [users.id],
+  }),
+  repository: one(repositories, {
+    fields: [activityEvents.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const gitCredentialRelations = relations(gitCredentials, ({ one }) => ({
+  user: one(users, {
+    fields: [gitCredentials.userId],
+    references: [users.id],
+  }),
+}));
+
+export const refLogRelations = relations(refLog, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [refLog.repositoryId],
+    references: [repositories.id],
+  }),
+  actor: one(users, {
+    fields: [refLog.actorId],
+    references: [users.id],
+  }),
+}));
+
 export const branchProtectionRuleRelations = relations(branchProtectionRules, ({ one }) => ({
   repository: one(repositories, {
     fields: [branchProtectionRules.repositoryId],
@@ -958,6 +1496,46 @@ export const branchProtectionRuleRelations = relations(branchProtectionRules, ({
   }),
 }));
 
+export const pushPolicyRelations = relations(pushPolicies, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [pushPolicies.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const repoReadTokenRelations = relations(repoReadTokens, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [repoReadTokens.repositoryId],
+    references: [repositories.id],
+  }),
+  createdBy: one(users, {
+    fields: [repoReadTokens.createdById],
+    references: [users.id],
+  }),
+}));
+
+export const deployKeyRelations = relations(deployKeys, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [deployKeys.repositoryId],
+    references: [repositories.id],
+  }),
+  createdBy: one(users, {
+    fields: [deployKeys.createdById],
+    references: [users.id],
+  }),
+}));
+
+export const commitStatusRelations = relations(commitStatuses, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [commitStatuses.repositoryId],
+    references: [repositories.id],
+  }),
+  creator: one(users, {
+    fields: [commitStatuses.creatorId],
+    references: [users.id],
+  }),
+}));
+
 export const issueRelations = relations(issues, ({ one, many }) => ({
   repository: one(repositories, {
     fields: [issues.repositoryId],
@@ -974,7 +1552,6 @@ export const issueRelations = relations(issues, ({ one, many }) => ({
   labels: many(issueLabels),
   assignees: many(issueAssignees),
   comments: many(issueComments),
-  reactions: many(issueReactions),
 }));
 
 export const labelRelations = relations(labels, ({ one, many }) => ({
@@ -1008,7 +1585,7 @@ export const issueAssigneeRelations = relations(issueAssignees, ({ one }) => ({
   }),
 }));
 
-export const issueCommentRelations = relations(issueComments, ({ one, many }) => ({
+export const issueCommentRelations = relations(issueComments, ({ one }) => ({
   issue: one(issues, {
     fields: [issueComments.issueId],
     references: [issues.id],
@@ -1017,20 +1594,83 @@ export const issueCommentRelations = relations(issueComments, ({ one, many }) =>
     fields: [issueComments.authorId],
     references: [users.id],
   }),
-  reactions: many(issueReactions),
 }));
 
-export const issueReactionRelations = relations(issueReactions, ({ one }) => ({
+export const reactionRelations = relations(reactions, ({ one }) => ({
+  repository: one(repositories, {
+    fields: [reactions.repositoryId],
+    references: [repositories.id],
+  }),
+  user: one(users, {
+    fields: [reactions.userId],
+    references: [users.id],
+  }),
+}));
+
+export const issueDuplicateRelations = relations(issueDuplicates, ({ one }) => ({
+  issue: one(issues, {
+    fields: [issueDuplicates.issueId],
+    references: [issues.id],
+  }),
+  duplicateOf: one(issues, {
+    fields: [issueDuplicates.duplicateOfId],
+    references: [issues.id],
+  }),
+  actor: one(users, {
+    fields: [issueDuplicates.actorId],
+    references: [users.id],
+  }),
+}));
+
+export const contentReportRelations = relations(contentReports, ({ one }) => ({
+  reporter: one(users, {
+    fields: [contentReports.reporterId],
+    references: [users.id],
+  }),
+  repository: one(repositories, {
+    fields: [contentReports.repositoryId],
+    references: [repositories.id],
+  }),
+}));
+
+export const issueReferenceRelations = relations(issueReferences, ({ one }) => ({
+  targetIssue: one(issues, {
+    fields: [issueReferences.targetIssueId],
+    references: [issues.id],
+  }),
+  sourceRepository: one(repositories, {
+    fields: [issueReferences.sourceRepositoryId],
+    references: [repositories.id],
+  }),
+  sourceIssue: one(issues, {
+    fields: [issueReferences.sourceIssueId],
+    references: [issues.id],
+  }),
+  sourcePullRequest: one(pullRequests, {
+    fields: [issueReferences.sourcePullRequestId],
+    references: [pullRequests.id],
+  }),
+  actor: one(users, {
+    fields: [issueReferences.actorId],
+    references: [users.id],
+  }),
+}));
+
+export const issueMentionRelations = relations(issueMentions, ({ one }) => ({
   issue: one(issues, {
-    fields: [issueReactions.issueId],
+    fields: [issueMentions.issueId],
     references: [issues.id],
   }),
   comment: one(issueComments, {
-    fields: [issueReactions.commentId],
+    fields: [issueMentions.commentId],
     references: [issueComments.id],
   }),
   user: one(users, {
-    fields: [issueReactions.userId],
+    fields: [issueMentions.userId],
+    references: [users.id],
+  }),
+  actor: one(users, {
+    fields: [issueMentions.actorId],
     references: [users.id],
   }),
 }));
@@ -1071,7 +1711,6 @@ export const pullRequestRelations = relations(pullRequests, ({ one, many }) => (
   reviewers: many(prReviewers),
   reviews: many(prReviews),
   comments: many(prComments),
-  reactions: many(prReactions),
 }));
 
 export const prReviewRelations = relations(prReviews, ({ one }) => ({
@@ -1085,7 +1724,7 @@ export const prReviewRelations = relations(prReviews, ({ one }) => ({
   }),
 }));
 
-export const prCommentRelations = relations(prComments, ({ one, many }) => ({
+export const prCommentRelations = relations(prComments, ({ one }) => ({
   pullRequest: one(pullRequests, {
     fields: [prComments.pullRequestId],
     references: [pullRequests.id],
@@ -1094,7 +1733,6 @@ export const prCommentRelations = relations(prComments, ({ one, many }) => ({
     fields: [prComments.authorId],
     references: [users.id],
   }),
-  reactions: many(prReactions),
 }));
 
 export const prLabelRelations = relations(prLabels, ({ one }) => ({
@@ -1130,17 +1768,3 @@ export const prReviewerRelations = relations(prReviewers, ({ one }) => ({
   }),
 }));
 
-export const prReactionRelations = relations(prReactions, ({ one }) => ({
-  pullRequest: one(pullRequests, {
-    fields: [prReactions.pullRequestId],
-    references: [pullRequests.id],
-  }),
-  comment: one(prComments, {
-    fields: [prReactions.commentId],
-    references: [prComments.id],
-  }),
-  user: one(users, {
-    fields: [prReactions.userId],
-    references: [users.id],
-  }),
-}));
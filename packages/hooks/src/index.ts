@@ -9,3 +9,6 @@ export * from "./search";
 export * from "./notifications";
 export * from "./discussions";
 export * from "./projects";
+export * from "./reactions";
+export * from "./uploads";
+export * from "./moderation";
@@ -1,6 +1,6 @@
-import { useQuery, useMutation, useQueryClient } from "@tanstack/react-query";
+import { useQuery, useInfiniteQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { useApi } from "./context";
-import type { IssueFilters } from "./types";
+import type { IssueFilters, IssueStateReason } from "./types";
 
 export function useIssues(owner: string, repo: string, filters?: IssueFilters) {
   const api = useApi();
@@ -29,6 +29,24 @@ export function useIssueCount(owner: string, repo: string) {
   });
 }
 
+export function useIssueTemplates(owner: string, repo: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["issues", owner, repo, "templates"],
+    queryFn: () => api.issues.listTemplates(owner, repo),
+    enabled: !!owner && !!repo,
+  });
+}
+
+export function useIssueForms(owner: string, repo: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["issues", owner, repo, "forms"],
+    queryFn: () => api.issues.listForms(owner, repo),
+    enabled: !!owner && !!repo,
+  });
+}
+
 export function useLabels(owner: string, repo: string) {
   const api = useApi();
   return useQuery({
@@ -38,6 +56,15 @@ export function useLabels(owner: string, repo: string) {
   });
 }
 
+export function useIssueReferences(issueId: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["issue", issueId, "references"],
+    queryFn: () => api.issues.getReferences(issueId),
+    enabled: !!issueId,
+  });
+}
+
 export function useIssueComments(issueId: string) {
   const api = useApi();
   return useQuery({
@@ -47,6 +74,17 @@ export function useIssueComments(issueId: string) {
   });
 }
 
+export function useInfiniteIssueComments(issueId: string, order: "oldest" | "newest" = "oldest") {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["issue", issueId, "comments", "pages", order],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.issues.listComments(issueId, { order, after: pageParam }),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!issueId,
+  });
+}
+
 export function useCreateIssue(owner: string, repo: string) {
   const api = useApi();
   const queryClient = useQueryClient();
@@ -59,11 +97,23 @@ export function useCreateIssue(owner: string, repo: string) {
   });
 }
 
+export function useImportIssues(owner: string, repo: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (data: { issues: unknown[] } | unknown[]) => api.issues.import(owner, repo, data),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["issues", owner, repo] });
+      queryClient.invalidateQueries({ queryKey: ["labels", owner, repo] });
+    },
+  });
+}
+
 export function useUpdateIssue(id: string, owner: string, repo: string) {
   const api = useApi();
   const queryClient = useQueryClient();
   return useMutation({
-    mutationFn: (data: { title?: string; body?: string; state?: "open" | "closed"; locked?: boolean }) =>
+    mutationFn: (data: { title?: string; body?: string; state?: "open" | "closed"; stateReason?: IssueStateReason; duplicateOf?: number; locked?: boolean }) =>
       api.issues.update(id, data),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["issues", owner, repo] });
@@ -118,6 +168,31 @@ export function useDeleteLabel(id: string, owner: string, repo: string) {
   });
 }
 
+export function useApplyDefaultLabels(owner: string, repo: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: () => api.issues.applyDefaultLabels(owner, repo),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["labels", owner, repo] });
+    },
+  });
+}
+
+export function useBulkLabelIssues(owner: string, repo: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ labelId, issues, remove }: { labelId: string; issues: number[]; remove?: boolean }) =>
+      remove ? api.issues.bulkRemoveLabel(owner, repo, labelId, issues) : api.issues.bulkAddLabel(owner, repo, labelId, issues),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["labels", owner, repo] });
+      queryClient.invalidateQueries({ queryKey: ["issues", owner, repo] });
+      queryClient.invalidateQueries({ queryKey: ["issue", owner, repo] });
+    },
+  });
+}
+
 export function useAddLabelsToIssue(issueId: string, owner: string, repo: string, issueNumber: number) {
   const api = useApi();
   const queryClient = useQueryClient();
@@ -154,6 +229,18 @@ export function useAddAssignees(issueId: string, owner: string, repo: string, is
   });
 }
 
+export function useAssignSelf(issueId: string, owner: string, repo: string, issueNumber: number) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: () => api.issues.assignSelf(issueId),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["issues", owner, repo] });
+      queryClient.invalidateQueries({ queryKey: ["issue", owner, repo, issueNumber] });
+    },
+  });
+}
+
 export function useRemoveAssignee(issueId: string, owner: string, repo: string, issueNumber: number) {
   const api = useApi();
   const queryClient = useQueryClient();
@@ -0,0 +1,46 @@
+import { useInfiniteQuery, useMutation, useQueryClient } from "@tanstack/react-query";
+import { useApi } from "./context";
+import type { ContentReport, HiddenReason, ReportReason } from "./types";
+
+export function useReportContent() {
+  const api = useApi();
+  return useMutation({
+    mutationFn: (data: { subjectType: "issue" | "issue_comment"; subjectId: string; reason: ReportReason; details?: string }) =>
+      api.reports.create(data),
+  });
+}
+
+export function useRepositoryReports(owner: string, repo: string, status: ContentReport["status"] = "open") {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["reports", owner, repo, status],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.reports.listForRepository(owner, repo, { status, cursor: pageParam }),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!owner && !!repo,
+  });
+}
+
+export function useUpdateReport(owner: string, repo: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ id, status }: { id: string; status: ContentReport["status"] }) => api.reports.update(id, status),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["reports", owner, repo] });
+    },
+  });
+}
+
+export function useHideComment(commentId: string, issueId: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (reason: HiddenReason | null) =>
+      reason ? api.issues.hideComment(commentId, reason) : api.issues.unhideComment(commentId),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["issue", issueId, "comments"] });
+      queryClient.invalidateQueries({ queryKey: ["reports"] });
+    },
+  });
+}
@@ -86,3 +86,16 @@ export function useAddProjectColumn(projectId: string) {
     },
   });
 }
+
+export function useUpdateProjectColumn(projectId: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: ({ columnId, ...data }: { columnId: string; name?: string; position?: number; moveClosedIssues?: boolean }) =>
+      api.projects.updateColumn(columnId, data),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["project", projectId] });
+    },
+  });
+}
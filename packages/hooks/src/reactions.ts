@@ -0,0 +1,12 @@
+import { useQuery } from "@tanstack/react-query";
+import { useApi } from "./context";
+import type { ReactionSubject } from "./types";
+
+export function useReactionUsers(subject: ReactionSubject, id: string, options?: { emoji?: string; limit?: number; offset?: number }) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["reactions", subject, id, options],
+    queryFn: () => api.reactions.list(subject, id, options),
+    enabled: !!id,
+  });
+}
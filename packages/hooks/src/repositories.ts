@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { useQuery, useMutation, useInfiniteQuery, useQueryClient } from "@tanstack/react-query";
 import { useApi } from "./context";
+import type { ExternalReference, FileContentChange, HighlightOptions, PushPolicy, RepositoryFilters } from "./types";
 
 export function useRepoPageData(owner: string, name: string) {
   const api = useApi();
@@ -29,33 +30,98 @@ export function useRepositoryWithStars(owner: string, name: string) {
   });
 }
 
-export function useUserRepositories(username: string) {
+export function useUserRepositories(username: string, options?: { sortBy?: "stars" | "name" | "updated"; limit?: number; offset?: number }) {
   const api = useApi();
   return useQuery({
-    queryKey: ["repositories", "user", username],
-    queryFn: () => api.repositories.getUserRepos(username),
+    queryKey: ["repositories", "user", username, options?.sortBy, options?.limit, options?.offset],
+    queryFn: () => api.repositories.getUserRepos(username, options),
     enabled: !!username,
   });
 }
 
-export function usePublicRepositories(sortBy: "stars" | "updated" | "created" = "updated", limit = 20, offset = 0) {
+export function usePublicRepositories(sortBy: "stars" | "updated" | "created" = "updated", limit = 20, offset = 0, filters?: RepositoryFilters) {
   const api = useApi();
   return useQuery({
-    queryKey: ["repositories", "public", sortBy, limit, offset],
-    queryFn: () => api.repositories.getPublic(sortBy, limit, offset),
+    queryKey: ["repositories", "public", sortBy, limit, offset, filters?.topic, filters?.language],
+    queryFn: () => api.repositories.getPublic(sortBy, limit, offset, filters),
   });
 }
 
-export function useInfinitePublicRepositories(sortBy: "stars" | "updated" | "created" = "updated", pageSize = 20) {
+export function useInfinitePublicRepositories(sortBy: "stars" | "updated" | "created" = "updated", pageSize = 20, filters?: RepositoryFilters) {
   const api = useApi();
   return useInfiniteQuery({
-    queryKey: ["repositories", "public", "infinite", sortBy],
-    queryFn: ({ pageParam = 0 }) => api.repositories.getPublic(sortBy, pageSize, pageParam),
+    queryKey: ["repositories", "public", "infinite", sortBy, filters?.topic, filters?.language],
+    queryFn: ({ pageParam = 0 }) => api.repositories.getPublic(sortBy, pageSize, pageParam, filters),
     getNextPageParam: (lastPage, allPages) => (lastPage.hasMore ? allPages.length * pageSize : undefined),
     initialPageParam: 0,
   });
 }
 
+export function useStaleBranches(owner: string, name: string, days = 90) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "branches", "stale", days],
+    queryFn: () => api.repositories.getStaleBranches(owner, name, days),
+    enabled: !!owner && !!name,
+  });
+}
+
+export function useDeleteBranches(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (branches: string[]) => api.repositories.deleteBranches(owner, name, branches),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository", owner, name] });
+    },
+  });
+}
+
+export function useInfiniteReflog(owner: string, name: string, branch: string) {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["repository", owner, name, "reflog", branch],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.repositories.getReflog(owner, name, branch, pageParam),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!owner && !!name && !!branch,
+  });
+}
+
+export function useRefreshRepoMetadata(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: () => api.repositories.refreshMetadata(owner, name),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository", owner, name] });
+    },
+  });
+}
+
+export function useRestoreRepository(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ bundle, force }: { bundle: Blob; force?: boolean }) => api.repositories.restore(owner, name, bundle, { force }),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository", owner, name] });
+    },
+  });
+}
+
+export function useUpdateFileContents(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ path, ...change }: FileContentChange & { path: string }) =>
+      api.repositories.updateContents(owner, name, path, change),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository", owner, name] });
+    },
+  });
+}
+
 export function useRepoTree(owner: string, name: string, branch: string, path = "") {
   const api = useApi();
   return useQuery({
@@ -65,21 +131,42 @@ export function useRepoTree(owner: string, name: string, branch: string, path =
   });
 }
 
-export function useTreeCommits(owner: string, name: string, branch: string, path = "") {
+export function useInfiniteRepoTree(owner: string, name: string, branch: string, path = "") {
   const api = useApi();
-  return useQuery({
-    queryKey: ["repository", owner, name, "tree-commits", branch, path],
-    queryFn: () => api.repositories.getTreeCommits(owner, name, branch, path),
+  return useInfiniteQuery({
+    queryKey: ["repository", owner, name, "tree", branch, path, "pages"],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.repositories.getTree(owner, name, branch, path, pageParam),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
     enabled: !!owner && !!name && !!branch,
   });
 }
 
-export function useRepoFile(owner: string, name: string, branch: string, path: string) {
+// Pass the cursor of the tree page being shown to resolve last commits for just that page, or
+// `lastCommits: false` to skip resolving them, e.g. for directories too large to be worth it.
+export function useTreeCommits(
+  owner: string,
+  name: string,
+  branch: string,
+  path = "",
+  options: { cursor?: string; lastCommits?: boolean } = {}
+) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "tree-commits", branch, path, options.cursor],
+    queryFn: () => api.repositories.getTreeCommits(owner, name, branch, path, options.cursor),
+    enabled: !!owner && !!name && !!branch && options.lastCommits !== false,
+    refetchInterval: (query) => (query.state.data?.pending ? 2000 : false),
+  });
+}
+
+export function useRepoFile(owner: string, name: string, branch: string, path: string, options: { lastCommit?: boolean } = {}) {
   const api = useApi();
   return useQuery({
-    queryKey: ["repository", owner, name, "file", branch, path],
-    queryFn: () => api.repositories.getFile(owner, name, branch, path),
+    queryKey: ["repository", owner, name, "file", branch, path, !!options.lastCommit],
+    queryFn: () => api.repositories.getFile(owner, name, branch, path, options),
     enabled: !!owner && !!name && !!branch && !!path,
+    refetchInterval: (query) => (query.state.data?.lastCommitPending ? 2000 : false),
   });
 }
 
@@ -110,6 +197,15 @@ export function useRepoCommitCount(owner: string, name: string, branch: string)
   });
 }
 
+export function useCommit(owner: string, name: string, ref: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "commit", ref],
+    queryFn: () => api.repositories.getCommit(owner, name, ref),
+    enabled: !!owner && !!name && !!ref,
+  });
+}
+
 export function useCommitDiff(owner: string, name: string, oid: string) {
   const api = useApi();
   return useQuery({
@@ -119,6 +215,24 @@ export function useCommitDiff(owner: string, name: string, oid: string) {
   });
 }
 
+export function useFileDiff(owner: string, name: string, path: string, base: string, head: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "fileDiff", path, base, head],
+    queryFn: () => api.repositories.getFileDiff(owner, name, path, base, head),
+    enabled: !!owner && !!name && !!path && !!base && !!head,
+  });
+}
+
+export function useCommitStatuses(owner: string, name: string, oid: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "commit", oid, "statuses"],
+    queryFn: () => api.repositories.getCommitStatuses(owner, name, oid),
+    enabled: !!owner && !!name && !!oid,
+  });
+}
+
 export function useRepoReadmeOid(owner: string, name: string, branch: string) {
   const api = useApi();
   return useQuery({
@@ -128,6 +242,63 @@ export function useRepoReadmeOid(owner: string, name: string, branch: string) {
   });
 }
 
+export function useRepoOverview(owner: string, name: string, branch?: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "overview", branch],
+    queryFn: () => api.repositories.getOverview(owner, name, branch),
+    enabled: !!owner && !!name,
+  });
+}
+
+export function useRepoLanguages(owner: string, name: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "languages"],
+    queryFn: () => api.repositories.getLanguages(owner, name),
+    enabled: !!owner && !!name,
+  });
+}
+
+export function useCodeFrequency(owner: string, name: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "stats", "code-frequency"],
+    queryFn: () => api.repositories.getCodeFrequency(owner, name),
+    enabled: !!owner && !!name,
+    refetchInterval: (query) => (query.state.data && "status" in query.state.data ? 3000 : false),
+  });
+}
+
+export function usePunchCard(owner: string, name: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "stats", "punch-card"],
+    queryFn: () => api.repositories.getPunchCard(owner, name),
+    enabled: !!owner && !!name,
+    refetchInterval: (query) => (query.state.data && "status" in query.state.data ? 3000 : false),
+  });
+}
+
+export function useRepoSize(owner: string, name: string, limit?: number) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "size", limit],
+    queryFn: () => api.repositories.getSize(owner, name, limit),
+    enabled: !!owner && !!name,
+    refetchInterval: (query) => (query.state.data && "status" in query.state.data ? 3000 : false),
+  });
+}
+
+export function useRenderedMarkdown(owner: string, name: string, text: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "render", text],
+    queryFn: () => api.repositories.render(owner, name, text),
+    enabled: !!owner && !!name && !!text,
+  });
+}
+
 export function useRepoReadme(owner: string, name: string, oid: string | null) {
   const api = useApi();
   return useQuery({
@@ -137,11 +308,23 @@ export function useRepoReadme(owner: string, name: string, oid: string | null) {
   });
 }
 
+// Results never change for an oid, so they're kept for the whole session.
+export function useHighlightedBlob(owner: string, name: string, oid: string | null, options: HighlightOptions = {}) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "highlight", oid, options],
+    queryFn: () => api.repositories.highlight(owner, name, oid!, options),
+    enabled: !!owner && !!name && !!oid,
+    staleTime: Infinity,
+  });
+}
+
 export function useCreateRepository() {
   const api = useApi();
   const queryClient = useQueryClient();
   return useMutation({
-    mutationFn: (data: { name: string; description?: string; visibility: "public" | "private" }) => api.repositories.create(data),
+    mutationFn: (data: { name: string; description?: string; visibility?: "public" | "private"; templateId?: string; defaultLabels?: boolean }) =>
+      api.repositories.create(data),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["repositories"] });
     },
@@ -178,7 +361,19 @@ export function useUpdateRepository(id: string) {
   const api = useApi();
   const queryClient = useQueryClient();
   return useMutation({
-    mutationFn: (data: { name?: string; description?: string; visibility?: "public" | "private" }) => api.repositories.update(id, data),
+    mutationFn: (data: { name?: string; description?: string; visibility?: "public" | "private"; deleteBranchOnMerge?: boolean; externalReferences?: ExternalReference[] | null }) => api.repositories.update(id, data),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository"] });
+      queryClient.invalidateQueries({ queryKey: ["repositories"] });
+    },
+  });
+}
+
+export function useUpdateRepoTopics(id: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (topics: string[]) => api.repositories.updateTopics(id, topics),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["repository"] });
       queryClient.invalidateQueries({ queryKey: ["repositories"] });
@@ -186,11 +381,34 @@ export function useUpdateRepository(id: string) {
   });
 }
 
+export function useSetDefaultBranch(id: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (branch: string) => api.repositories.setDefaultBranch(id, branch),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository"] });
+      queryClient.invalidateQueries({ queryKey: ["repositories"] });
+    },
+  });
+}
+
+export function useSyncMirror(id: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: () => api.repositories.syncMirror(id),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository"] });
+    },
+  });
+}
+
 export function useDeleteRepository(id: string) {
   const api = useApi();
   const queryClient = useQueryClient();
   return useMutation({
-    mutationFn: () => api.repositories.delete(id),
+    mutationFn: (confirm: string) => api.repositories.delete(id, confirm),
     onSuccess: () => {
       queryClient.invalidateQueries({ queryKey: ["repository"] });
       queryClient.invalidateQueries({ queryKey: ["repositories"] });
@@ -198,6 +416,15 @@ export function useDeleteRepository(id: string) {
   });
 }
 
+export function useRepositoryDeletionPreview(id: string, enabled = true) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", id, "deletion-preview"],
+    queryFn: () => api.repositories.previewDeletion(id),
+    enabled: !!id && enabled,
+  });
+}
+
 export function useBranchProtectionRules(owner: string, name: string) {
   const api = useApi();
   return useQuery({
@@ -263,6 +490,26 @@ export function useDeleteBranchProtectionRule(owner: string, name: string) {
   });
 }
 
+export function usePushPolicy(owner: string, name: string) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["repository", owner, name, "pushPolicy"],
+    queryFn: () => api.repositories.getPushPolicy(owner, name),
+    enabled: !!owner && !!name,
+  });
+}
+
+export function useUpdatePushPolicy(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (data: Partial<PushPolicy>) => api.repositories.updatePushPolicy(owner, name, data),
+    onSuccess: (policy) => {
+      queryClient.setQueryData(["repository", owner, name, "pushPolicy"], policy);
+    },
+  });
+}
+
 export function useIsStarredByUser(repoId: string) {
   const api = useApi();
   return useQuery({
@@ -284,6 +531,30 @@ export function useToggleStar(repoId: string) {
   });
 }
 
+// Starred state for a whole listing in one request; ids are sorted so the same set of rows
+// shares a cache entry whatever order they render in.
+export function useStarredState(ids: string[]) {
+  const api = useApi();
+  const sortedIds = [...ids].sort();
+  return useQuery({
+    queryKey: ["repositories", "starred", sortedIds],
+    queryFn: () => api.repositories.getStarredState(sortedIds),
+    enabled: sortedIds.length > 0,
+  });
+}
+
+export function useSetStar(owner: string, name: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (starred: boolean) => (starred ? api.repositories.star(owner, name) : api.repositories.unstar(owner, name)),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["repository"] });
+      queryClient.invalidateQueries({ queryKey: ["repositories"] });
+    },
+  });
+}
+
 export function useStarRepository(repoId: string, initialStarCount?: number) {
   const api = useApi();
   const queryClient = useQueryClient();
@@ -134,3 +134,31 @@ export function useDeleteAccount() {
     },
   });
 }
+
+export function useAccountExports() {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["settings", "exports"],
+    queryFn: () => api.settings.listExports(),
+    refetchInterval: (query) =>
+      query.state.data?.exports.some((e) => e.status === "pending" || e.status === "running") ? 5000 : false,
+  });
+}
+
+export function useRequestAccountExport() {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (data: { includeRepositories?: boolean }) => api.settings.requestExport(data),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["settings", "exports"] });
+    },
+  });
+}
+
+export function useCreateGitCredential() {
+  const api = useApi();
+  return useMutation({
+    mutationFn: () => api.settings.createGitCredential(),
+  });
+}
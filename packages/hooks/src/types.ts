@@ -13,22 +13,62 @@ export type Repository = {
   defaultBranch: string;
   ownerId: string;
   forkedFromId?: string | null;
+  topics?: string[];
+  deleteBranchOnMerge?: boolean;
+  isTemplate?: boolean;
+  externalReferences?: ExternalReference[] | null;
+  license?: string | null;
   createdAt: string;
   updatedAt: string;
 };
 
+export type RepositoryDeletionReport = {
+  repository: string;
+  objects: number;
+  bytes: number;
+  issues: number;
+  pullRequests: number;
+};
+
+export type ExternalReference = {
+  prefix: string;
+  urlTemplate: string;
+  alphanumeric: boolean;
+};
+
+export type RenderedMarkdown = {
+  markdown: string;
+  links: { text: string; url: string; start: number; end: number }[];
+  externalReferences: ExternalReference[];
+};
+
+export type RepositoryFilters = {
+  topic?: string;
+  language?: string;
+};
+
 export type ForkedFrom = {
   id: string;
   name: string;
   owner: Owner;
 };
 
+export type RepositoryMirror = {
+  url: string;
+  intervalMinutes: number;
+  status: "pending" | "syncing" | "succeeded" | "failed" | null;
+  lastSyncedAt: string | null;
+  lastError: string | null;
+};
+
 export type RepositoryWithOwner = Repository & {
   owner: Owner;
   starCount: number;
   starred: boolean;
   forkedFrom?: ForkedFrom | null;
   forkCount?: number;
+  openIssueCount?: number;
+  mirror?: RepositoryMirror | null;
 };
 
 export type RepositoryWithStars = Repository & {
@@ -36,13 +76,18 @@ export type RepositoryWithStars = Repository & {
   starCount: number;
   forkedFrom?: ForkedFrom | null;
   forkCount?: number;
+  openIssueCount?: number;
+  primaryLanguage?: string | null;
+  lastPushedAt?: string | null;
 };
 
 export type FileEntry = {
   name: string;
-  type: "blob" | "tree";
+  type: "blob" | "tree" | "commit" | "symlink";
   oid: string;
   path: string;
+  mode?: string;
+  submoduleUrl?: string | null;
 };
 
 export type FileLastCommit = {
@@ -53,6 +98,24 @@ export type FileLastCommit = {
   timestamp: number;
 };
 
+// `lastCommit` is only present when asked for; `lastCommitPending` means it's still being
+// resolved and a later request will have it.
+export type RepoFile = {
+  content: string;
+  oid: string;
+  path: string;
+  lastCommit?: FileLastCommit | null;
+  lastCommitPending?: boolean;
+};
+
+export type HighlightToken = { content: string; color?: string; fontStyle?: number };
+
+export type HighlightedBlob =
+  | { language: string; theme: "light" | "dark"; format: "html"; html: string }
+  | { language: string; theme: "light" | "dark"; format: "tokens"; lines: HighlightToken[][] };
+
+export type HighlightOptions = { path?: string; lang?: string; theme?: "light" | "dark"; format?: "html" | "tokens" };
+
 export type RepoInfo = {
   repo: RepositoryWithOwner;
   isOwner: boolean;
@@ -61,9 +124,59 @@ export type RepoInfo = {
 export type TreeResponse = {
   files: FileEntry[];
   isEmpty: boolean;
+  total: number;
+  hasMore: boolean;
+  nextCursor: string | null;
   readmeOid?: string | null;
 };
 
+export type RepoLanguage = {
+  language: string;
+  bytes: number;
+  percentage: number;
+};
+
+// While statistics are being computed the API answers 202 with { status: "computing" }.
+export type RepoStatsComputing = { status: "computing" };
+
+export type CodeFrequencyStats = {
+  weeks: [week: number, additions: number, deletions: number][];
+  truncated: boolean;
+};
+
+export type PunchCardStats = {
+  punchCard: [day: number, hour: number, commits: number][];
+  truncated: boolean;
+};
+
+export type RepoSize = {
+  totalBytes: number;
+  packCount: number;
+  packBytes: number;
+  looseObjectCount: number;
+  looseBytes: number;
+  sharedObjectCount: number;
+  sharedBytes: number;
+  largestBlobs: { oid: string; size: number; storedBytes: number }[];
+  computedAt: string;
+};
+
+export type RepoOverview = {
+  repo: RepositoryWithOwner;
+  isOwner: boolean;
+  branch: string;
+  branches: string[];
+  files: FileEntry[];
+  isEmpty: boolean;
+  commitCount: number;
+  lastCommit: Commit | null;
+  readme: { oid: string; content: string } | null;
+  counts: {
+    openIssues: number;
+    openPullRequests: number;
+  };
+};
+
 export type RepoPageData = {
   repo: RepositoryWithOwner;
   files: FileEntry[];
@@ -83,6 +196,114 @@ export type Commit = {
     avatarUrl?: string;
   };
   timestamp: number;
+  status?: CombinedCommitState | null;
+  coAuthors?: CommitPerson[];
+  trailers?: {
+    coAuthors: { name: string; email: string }[];
+    signedOffBy: { name: string; email: string }[];
+    reviewedBy: { name: string; email: string }[];
+  };
+};
+
+export type CommitPerson = {
+  name: string;
+  email: string;
+  username?: string;
+  userId?: string;
+  avatarUrl?: string | null;
+};
+
+export type CommitDetails = Omit<Commit, "author"> & {
+  author: Commit["author"] & { email: string };
+  committer: Commit["author"] & { email: string; timestamp: number };
+  parents: string[];
+  tree: string;
+};
+
+export type FileContentChange = {
+  branch?: string;
+  message?: string;
+  content?: string;
+  encoding?: "utf-8" | "base64";
+  delete?: boolean;
+  sha?: string;
+  expectedHeadOid?: string;
+};
+
+export type FileContentCommit = {
+  branch: string;
+  path: string;
+  commitOid: string;
+  parentOid: string;
+  blobOid: string | null;
+  status: "added" | "modified" | "deleted";
+};
+
+export type FsckReport = {
+  ok: boolean;
+  refs: { ref: string; oid: string | null; ok: boolean; error?: string }[];
+  objects: {
+    checked: number;
+    missing: { oid: string; error: string }[];
+    corrupt: { oid: string; error: string }[];
+    truncated: boolean;
+  };
+  packs: { name: string; ok: boolean; error?: string }[];
+  durationMs: number;
+};
+
+export type RefLogEntry = {
+  id: string;
+  ref: string;
+  oldOid: string;
+  newOid: string;
+  source: "push" | "commit" | "patch" | "merge" | "branch_delete" | "restore";
+  createdAt: string;
+  actor: { id: string; username: string; name: string; avatarUrl: string | null } | null;
+};
+
+export type RestoreRepositoryResult = {
+  refs: { name: string; oid: string }[];
+  deleted: string[];
+  skipped: string[];
+  objectCount: number;
+  defaultBranch: string;
+  metadata: { rebuilt: string[]; removed: string[]; failed: string[] };
+};
+
+export type StaleBranch = {
+  name: string;
+  headOid: string;
+  lastCommit: {
+    message: string;
+    author: string;
+    timestamp: number;
+  };
+};
+
+export type CommitStatusState = "pending" | "success" | "failure" | "error";
+
+export type CombinedCommitState = "pending" | "success" | "failure";
+
+export type CommitStatus = {
+  id: string;
+  state: CommitStatusState;
+  context: string;
+  targetUrl: string | null;
+  description: string | null;
+  createdAt: string;
+  updatedAt: string;
+  creator: {
+    id: string;
+    username: string;
+    avatarUrl: string | null;
+  } | null;
+};
+
+export type CommitStatusSummary = {
+  oid: string;
+  state: CombinedCommitState | null;
+  statuses: CommitStatus[];
 };
 
 export type DiffHunkLine = {
@@ -107,6 +328,11 @@ export type FileDiff = {
   deletions: number;
   hunks: DiffHunk[];
   oldPath?: string;
+  isBinary?: boolean;
+  tooLarge?: boolean;
+  oldSize?: number | null;
+  newSize?: number | null;
+  mimeType?: string;
 };
 
 export type DiffStats = {
@@ -122,6 +348,12 @@ export type CommitDiff = {
   stats: DiffStats;
 };
 
+export type FileDiffBetween = {
+  base: string;
+  head: string;
+  file: FileDiff;
+};
+
 export type UserPreferences = {
   emailNotifications?: boolean;
   theme?: "light" | "dark" | "system";
@@ -130,6 +362,18 @@ export type UserPreferences = {
   wordWrap?: boolean;
 };
 
+export type AccountExport = {
+  id: string;
+  status: "pending" | "running" | "completed" | "failed";
+  includeRepositories: boolean;
+  sizeBytes: number | null;
+  error: string | null;
+  createdAt: string;
+  completedAt: string | null;
+  expiresAt: string | null;
+  downloadUrl: string | null;
+};
+
 export type UserProfile = {
   id: string;
   name: string;
@@ -154,6 +398,18 @@ export type UserProfile = {
   } | null;
   createdAt: string;
   updatedAt: string;
+  followerCount?: number;
+  followingCount?: number;
+  isFollowing?: boolean;
+};
+
+export type FollowUser = {
+  id: string;
+  username: string;
+  name: string;
+  avatarUrl: string | null;
+  bio: string | null;
+  followedAt: string;
 };
 
 export type PublicUser = {
@@ -166,6 +422,57 @@ export type PublicUser = {
   repoCount: number;
 };
 
+export type ActivityEventType =
+  | "repository_created"
+  | "repository_forked"
+  | "starred"
+  | "pushed"
+  | "issue_opened"
+  | "issue_closed"
+  | "issue_commented"
+  | "pull_request_opened"
+  | "pull_request_merged";
+
+export type ActivityEvent = {
+  id: string;
+  type: ActivityEventType;
+  actor: { id: string; username: string; name: string; avatarUrl: string | null };
+  repository: { id: string; name: string; owner: string };
+  payload: Record<string, unknown>;
+  createdAt: string;
+};
+
+export type GitCredential = {
+  id: string;
+  username: string;
+  token: string;
+  expiresAt: string;
+};
+
+export type Dashboard = {
+  since: string;
+  repos: {
+    id: string;
+    name: string;
+    description: string | null;
+    visibility: "public" | "private";
+    updatedAt: string;
+    recentActivity: { id: string; type: ActivityEventType; actor: string; payload: Record<string, unknown>; createdAt: string }[];
+  }[];
+  starredUpdates: { id: string; name: string; owner: string; description: string | null; newPushes: number; lastPushedAt: string }[];
+  followedActivity: ActivityEvent[];
+  assignedIssues: { id: string; number: number; title: string; updatedAt: string; repository: { name: string; owner: string } }[];
+  reviewRequests: {
+    id: string;
+    number: number;
+    title: string;
+    isDraft: boolean;
+    requestedAt: string;
+    updatedAt: string;
+    repository: { name: string; owner: string };
+  }[];
+};
+
 export type UserSummary = {
   name: string;
   avatarUrl: string | null;
@@ -176,6 +483,47 @@ export type Label = {
   name: string;
   description: string | null;
   color: string;
+  openIssues?: number;
+  totalIssues?: number;
+};
+
+export type IssueFormField = {
+  type: "markdown" | "input" | "textarea" | "dropdown" | "checkboxes";
+  id?: string;
+  attributes: {
+    label?: string;
+    description?: string;
+    placeholder?: string;
+    value?: string;
+    render?: string;
+    multiple?: boolean;
+    options?: (string | { label: string; required?: boolean })[];
+  };
+  validations?: {
+    required?: boolean;
+  };
+};
+
+export type IssueForm = {
+  id: string;
+  name: string;
+  description: string;
+  title?: string;
+  labels: string[];
+  assignees: string[];
+  body: IssueFormField[];
+};
+
+export type IssueFormValues = Record<string, string | string[] | boolean[]>;
+
+export type IssueTemplate = {
+  id: string;
+  name: string;
+  about: string;
+  title?: string;
+  labels: string[];
+  assignees: string[];
+  body: string;
 };
 
 export type IssueAuthor = {
@@ -191,12 +539,20 @@ export type ReactionSummary = {
   reacted: boolean;
 };
 
+export type ReactionSubject = "issue" | "issue_comment" | "pull_request" | "pr_comment" | "discussion" | "discussion_comment";
+
+export type ReactionUserList = {
+  reactions: { emoji: string; createdAt: string; user: IssueAuthor }[];
+  hasMore: boolean;
+};
+
 export type Issue = {
   id: string;
   number: number;
   title: string;
   body: string | null;
   state: "open" | "closed";
+  stateReason: IssueStateReason | null;
   locked: boolean;
   author: IssueAuthor;
   labels: Label[];
@@ -207,23 +563,127 @@ export type Issue = {
   updatedAt: string;
   closedAt: string | null;
   closedBy: IssueAuthor | null;
+  referencedBy?: IssueReferences["referencedBy"];
+  references?: IssueReferences["references"];
+  mentions?: IssueAuthor[];
+  duplicateOf?: IssueReferences["duplicateOf"];
+  duplicates?: IssueReferences["duplicates"];
+};
+
+export type IssueStateReason = "completed" | "not_planned" | "duplicate";
+
+export type HiddenReason = "spam" | "abuse" | "off_topic" | "outdated" | "resolved" | "duplicate";
+
+export type ReportReason = "spam" | "abuse" | "off_topic" | "other";
+
+export type ContentReport = {
+  id: string;
+  subjectType: "issue" | "issue_comment";
+  subjectId: string;
+  reason: ReportReason;
+  details: string | null;
+  status: "open" | "resolved" | "dismissed";
+  createdAt: string;
+  resolvedAt: string | null;
+  reporter: { id: string; username: string; avatarUrl: string | null };
+  repository: { id: string; name: string; owner: string };
+  subject: { id: string; number: number; title?: string; body: string | null; authorId: string; hiddenReason?: HiddenReason | null } | null;
 };
 
 export type IssueComment = {
   id: string;
-  body: string;
+  body: string | null;
+  hidden: { reason: HiddenReason; hiddenAt: string } | null;
   author: IssueAuthor;
   reactions: ReactionSummary[];
   createdAt: string;
   updatedAt: string;
 };
 
+export type IssueCommentPageOptions = {
+  order?: "oldest" | "newest";
+  after?: string;
+  before?: string;
+  limit?: number;
+  reactions?: boolean;
+};
+
+export type IssueCommentPage = {
+  comments: IssueComment[];
+  hasMore: boolean;
+  nextCursor: string | null;
+  previousCursor: string | null;
+};
+
+export type IssueReferenceItem =
+  | { type: "issue"; id: string; number: number; title: string; state: "open" | "closed"; createdAt: string }
+  | { type: "pull_request"; id: string; number: number; title: string; state: "open" | "closed" | "merged"; createdAt: string }
+  | { type: "commit"; oid: string; message: string | null; createdAt: string };
+
+export type IssueReferences = {
+  referencedBy: IssueReferenceItem[];
+  references: Extract<IssueReferenceItem, { type: "issue" }>[];
+  mentions: IssueAuthor[];
+  duplicateOf: Omit<Extract<IssueReferenceItem, { type: "issue" }>, "type"> | null;
+  duplicates: Omit<Extract<IssueReferenceItem, { type: "issue" }>, "type">[];
+};
+
+export type IssueExportRecord = {
+  number: number;
+  title: string;
+  body: string | null;
+  state: "open" | "closed";
+  locked: boolean;
+  author: string | null;
+  labels: string[];
+  assignees: string[];
+  createdAt: string;
+  updatedAt: string;
+  closedAt: string | null;
+  comments: {
+    author: string | null;
+    body: string | null;
+    hidden: { reason: HiddenReason; hiddenAt: string | null } | null;
+    createdAt: string;
+    updatedAt: string;
+  }[];
+};
+
+export type IssueExport = {
+  repository: string;
+  exportedAt: string;
+  issues: IssueExportRecord[];
+};
+
+export type IssueImportResult = {
+  imported: number;
+  renumbered: { from: number; to: number }[];
+};
+
+export type PendingUpload = {
+  id: string;
+  uploadUrl: string;
+  method: "PUT";
+  headers: Record<string, string>;
+  expiresAt: string;
+};
+
+export type Attachment = {
+  id: string;
+  filename: string;
+  contentType: string;
+  size: number;
+  url: string;
+  markdown: string;
+};
+
 export type IssueFilters = {
   state?: "open" | "closed" | "all";
   label?: string;
   assignee?: string;
   limit?: number;
   offset?: number;
+  cursor?: string;
 };
 
 export type PRRepoInfo = {
@@ -333,30 +793,74 @@ export type BranchProtectionRule = {
   updatedAt: string;
 };
 
+export type PushPolicy = {
+  commitMessagePattern: string | null;
+  requirePusherEmail: boolean;
+  maxFileSizeMb: number | null;
+  forbiddenPaths: string[];
+};
+
+export type GraphQLResponse<T> = {
+  data: T | null;
+  errors?: { message: string; path?: (string | number)[] }[];
+};
+
 export type ApiClient = {
   repositories: {
-    create: (data: { name: string; description?: string; visibility: "public" | "private" }) => Promise<Repository>;
+    create: (data: {
+      name: string;
+      description?: string;
+      visibility?: "public" | "private";
+      mirrorUrl?: string;
+      mirrorIntervalMinutes?: number;
+      templateId?: string;
+      defaultLabels?: boolean;
+    }) => Promise<Repository>;
     fork: (owner: string, name: string, data?: { name?: string; description?: string }) => Promise<RepoInfo>;
     getForks: (owner: string, name: string, limit?: number, offset?: number) => Promise<{ forks: RepositoryWithOwner[] }>;
     get: (owner: string, name: string) => Promise<RepositoryWithOwner>;
     getWithStars: (owner: string, name: string) => Promise<RepositoryWithOwner>;
     getInfo: (owner: string, name: string) => Promise<RepoInfo>;
     getPageData: (owner: string, name: string) => Promise<RepoPageData>;
-    getUserRepos: (username: string) => Promise<{ repos: RepositoryWithStars[] }>;
-    getPublic: (sortBy: "stars" | "updated" | "created", limit: number, offset: number) => Promise<{ repos: RepositoryWithStars[]; hasMore: boolean }>;
-    update: (id: string, data: { name?: string; description?: string; visibility?: "public" | "private" }) => Promise<Repository>;
-    delete: (id: string) => Promise<{ success: boolean }>;
+    getUserRepos: (username: string, options?: { sortBy?: "stars" | "name" | "updated"; limit?: number; offset?: number }) => Promise<{ repos: (RepositoryWithStars & { starred: boolean })[]; hasMore: boolean }>;
+    getPublic: (sortBy: "stars" | "updated" | "created", limit: number, offset: number, filters?: RepositoryFilters, cursor?: string) => Promise<{ repos: RepositoryWithStars[]; hasMore: boolean; nextCursor: string | null }>;
+    update: (id: string, data: { name?: string; description?: string; visibility?: "public" | "private"; deleteBranchOnMerge?: boolean; isTemplate?: boolean; externalReferences?: ExternalReference[] | null; mirrorIntervalMinutes?: number }) => Promise<Repository>;
+    updateTopics: (id: string, topics: string[]) => Promise<{ topics: string[] }>;
+    setDefaultBranch: (id: string, branch: string) => Promise<{ defaultBranch: string }>;
+    syncMirror: (id: string) => Promise<{ status: "syncing" }>;
+    delete: (id: string, confirm: string) => Promise<{ success: boolean } & RepositoryDeletionReport>;
+    previewDeletion: (id: string) => Promise<{ dryRun: true } & RepositoryDeletionReport>;
     toggleStar: (id: string) => Promise<{ starred: boolean }>;
     isStarred: (id: string) => Promise<{ starred: boolean }>;
+    star: (owner: string, name: string) => Promise<{ starred: boolean; starCount: number }>;
+    unstar: (owner: string, name: string) => Promise<{ starred: boolean; starCount: number }>;
+    getStarredState: (ids: string[]) => Promise<{ starred: Record<string, boolean> }>;
     getBranches: (owner: string, name: string) => Promise<{ branches: string[] }>;
-    getTree: (owner: string, name: string, branch: string, path?: string) => Promise<TreeResponse>;
-    getTreeCommits: (owner: string, name: string, branch: string, path?: string) => Promise<{ files: FileLastCommit[] }>;
-    getFile: (owner: string, name: string, branch: string, path: string) => Promise<{ content: string; oid: string; path: string }>;
-    getCommits: (owner: string, name: string, branch: string, limit?: number, skip?: number) => Promise<{ commits: Commit[]; hasMore: boolean }>;
+    getStaleBranches: (owner: string, name: string, days?: number) => Promise<{ branches: StaleBranch[]; defaultBranch: string; days: number }>;
+    deleteBranches: (owner: string, name: string, branches: string[]) => Promise<{ deleted: string[]; skipped: { branch: string; reason: string }[] }>;
+    getTree: (owner: string, name: string, branch: string, path?: string, cursor?: string) => Promise<TreeResponse>;
+    getTreeCommits: (owner: string, name: string, branch: string, path?: string, cursor?: string) => Promise<{ files: FileLastCommit[]; pending: boolean }>;
+    getFile: (owner: string, name: string, branch: string, path: string, options?: { lastCommit?: boolean }) => Promise<RepoFile>;
+    updateContents: (owner: string, name: string, path: string, change: FileContentChange) => Promise<FileContentCommit>;
+    getCommits: (owner: string, name: string, branch: string, limit?: number, skip?: number, cursor?: string) => Promise<{ commits: Commit[]; hasMore: boolean; nextCursor: string | null }>;
     getCommitCount: (owner: string, name: string, branch: string) => Promise<{ count: number }>;
+    getCommit: (owner: string, name: string, ref: string) => Promise<{ commit: CommitDetails }>;
     getCommitDiff: (owner: string, name: string, oid: string) => Promise<CommitDiff>;
+    getFileDiff: (owner: string, name: string, path: string, base: string, head: string) => Promise<FileDiffBetween>;
+    getCommitStatuses: (owner: string, name: string, oid: string) => Promise<CommitStatusSummary>;
     getReadme: (owner: string, name: string, oid: string) => Promise<{ content: string }>;
     getReadmeOid: (owner: string, name: string, branch: string) => Promise<{ readmeOid: string | null }>;
+    highlight: (owner: string, name: string, oid: string, options?: HighlightOptions) => Promise<HighlightedBlob>;
+    getOverview: (owner: string, name: string, branch?: string) => Promise<RepoOverview>;
+    getLanguages: (owner: string, name: string) => Promise<{ languages: RepoLanguage[] }>;
+    getCodeFrequency: (owner: string, name: string) => Promise<CodeFrequencyStats | RepoStatsComputing>;
+    getPunchCard: (owner: string, name: string) => Promise<PunchCardStats | RepoStatsComputing>;
+    getSize: (owner: string, name: string, limit?: number) => Promise<RepoSize | RepoStatsComputing>;
+    fsck: (owner: string, name: string) => Promise<FsckReport>;
+    refreshMetadata: (owner: string, name: string) => Promise<{ rebuilt: string[]; removed: string[]; failed: string[] }>;
+    restore: (owner: string, name: string, bundle: Blob, options?: { force?: boolean }) => Promise<RestoreRepositoryResult>;
+    getReflog: (owner: string, name: string, branch: string, cursor?: string) => Promise<{ entries: RefLogEntry[]; hasMore: boolean; nextCursor: string | null }>;
+    render: (owner: string, name: string, text: string) => Promise<RenderedMarkdown>;
     getBranchProtection: (owner: string, name: string) => Promise<{ rules: BranchProtectionRule[] }>;
     createBranchProtection: (owner: string, name: string, data: {
       branchName: string;
@@ -374,13 +878,21 @@ export type ApiClient = {
       requiredReviewCount?: number;
     }) => Promise<BranchProtectionRule>;
     deleteBranchProtection: (owner: string, name: string, ruleId: string) => Promise<{ success: boolean }>;
+    getPushPolicy: (owner: string, name: string) => Promise<PushPolicy>;
+    updatePushPolicy: (owner: string, name: string, data: Partial<PushPolicy>) => Promise<PushPolicy>;
   };
   users: {
     getProfile: (username: string) => Promise<UserProfile>;
     getSummary: () => Promise<UserSummary>;
-    getStarred: (username: string) => Promise<{ repos: RepositoryWithStars[] }>;
+    getDashboard: () => Promise<Dashboard>;
+    getStarred: (username: string, limit?: number, offset?: number) => Promise<{ repos: RepositoryWithStars[]; hasMore: boolean; total: number }>;
     getAvatarByUsername: (username: string) => Promise<{ avatarUrl: string | null }>;
-    getPublic: (sortBy: "newest" | "oldest", limit: number, offset: number) => Promise<{ users: PublicUser[]; hasMore: boolean }>;
+    getPublic: (sortBy: "newest" | "oldest", limit: number, offset: number, cursor?: string) => Promise<{ users: PublicUser[]; hasMore: boolean; nextCursor: string | null }>;
+    getEvents: (username: string, cursor?: string) => Promise<{ events: ActivityEvent[]; hasMore: boolean; nextCursor: string | null }>;
+    follow: (username: string) => Promise<{ following: boolean }>;
+    unfollow: (username: string) => Promise<{ following: boolean }>;
+    getFollowers: (username: string, cursor?: string) => Promise<{ users: FollowUser[]; hasMore: boolean; nextCursor: string | null }>;
+    getFollowing: (username: string, cursor?: string) => Promise<{ users: FollowUser[]; hasMore: boolean; nextCursor: string | null }>;
   };
   settings: {
     getCurrentUser: () => Promise<{ user: UserProfile }>;
@@ -399,31 +911,46 @@ export type ApiClient = {
     updatePreferences: (data: Partial<UserPreferences>) => Promise<{ success: boolean }>;
     updateWordWrap: (data: { wordWrap: boolean }) => Promise<{ success: boolean; wordWrap: boolean }>;
     updateSocialLinks?: (data: { github?: string; twitter?: string; linkedin?: string; custom?: string[] }) => Promise<{ success: boolean }>;
-    updateEmail: (data: { email: string }) => Promise<{ success: boolean } | UserProfile>;
+    updateEmail: (data: { email: string }) => Promise<{ success: boolean; pendingEmail: string }>;
     updatePassword?: (data: { currentPassword: string; newPassword: string }) => Promise<{ success: boolean }>;
     updateAvatar: (file: File) => Promise<{ success: boolean; avatarUrl: string }>;
     deleteAvatar: () => Promise<{ success: boolean; avatarUrl: string | null }>;
     deleteAccount: () => Promise<{ success: boolean }>;
+    requestExport: (data: { includeRepositories?: boolean }) => Promise<{ export: AccountExport }>;
+    listExports: () => Promise<{ exports: AccountExport[] }>;
+    createGitCredential: () => Promise<GitCredential>;
+    revokeGitCredential: (id: string) => Promise<{ success: boolean }>;
   };
   issues: {
-    list: (owner: string, repo: string, filters?: IssueFilters) => Promise<{ issues: Issue[]; hasMore: boolean }>;
+    list: (owner: string, repo: string, filters?: IssueFilters) => Promise<{ issues: Issue[]; hasMore: boolean; nextCursor: string | null }>;
     get: (owner: string, repo: string, number: number) => Promise<Issue>;
-    create: (owner: string, repo: string, data: { title: string; body?: string; labels?: string[]; assignees?: string[] }) => Promise<Issue>;
-    update: (id: string, data: { title?: string; body?: string; state?: "open" | "closed"; locked?: boolean }) => Promise<{ success: boolean }>;
+    create: (owner: string, repo: string, data: { title: string; body?: string; labels?: string[]; assignees?: string[]; form?: string; fields?: IssueFormValues }) => Promise<Issue>;
+    update: (id: string, data: { title?: string; body?: string; state?: "open" | "closed"; stateReason?: IssueStateReason; duplicateOf?: number; locked?: boolean }) => Promise<{ success: boolean }>;
     delete: (id: string) => Promise<{ success: boolean }>;
     getCount: (owner: string, repo: string) => Promise<{ open: number; closed: number }>;
+    export: (owner: string, repo: string) => Promise<IssueExport>;
+    import: (owner: string, repo: string, data: { issues: unknown[] } | unknown[]) => Promise<IssueImportResult>;
+    listForms: (owner: string, repo: string) => Promise<{ forms: IssueForm[] }>;
+    listTemplates: (owner: string, repo: string) => Promise<{ templates: IssueTemplate[] }>;
     listLabels: (owner: string, repo: string) => Promise<{ labels: Label[] }>;
     createLabel: (owner: string, repo: string, data: { name: string; description?: string; color: string }) => Promise<Label>;
     updateLabel: (id: string, data: { name?: string; description?: string; color?: string }) => Promise<Label>;
     deleteLabel: (id: string) => Promise<{ success: boolean }>;
+    applyDefaultLabels: (owner: string, repo: string) => Promise<{ labels: Label[] }>;
+    bulkAddLabel: (owner: string, repo: string, labelId: string, issues: number[]) => Promise<{ updated: number }>;
+    bulkRemoveLabel: (owner: string, repo: string, labelId: string, issues: number[]) => Promise<{ updated: number }>;
     addLabels: (issueId: string, labels: string[]) => Promise<{ success: boolean }>;
     removeLabel: (issueId: string, labelId: string) => Promise<{ success: boolean }>;
-    addAssignees: (issueId: string, assignees: string[]) => Promise<{ success: boolean }>;
+    addAssignees: (issueId: string, assignees: string[]) => Promise<{ assignees: IssueAuthor[] }>;
+    assignSelf: (issueId: string) => Promise<{ assignees: IssueAuthor[] }>;
     removeAssignee: (issueId: string, userId: string) => Promise<{ success: boolean }>;
-    listComments: (issueId: string) => Promise<{ comments: IssueComment[] }>;
+    getReferences: (issueId: string) => Promise<IssueReferences>;
+    listComments: (issueId: string, options?: IssueCommentPageOptions) => Promise<IssueCommentPage>;
     createComment: (issueId: string, body: string) => Promise<IssueComment>;
     updateComment: (commentId: string, body: string) => Promise<{ success: boolean }>;
     deleteComment: (commentId: string) => Promise<{ success: boolean }>;
+    hideComment: (commentId: string, reason: HiddenReason) => Promise<{ success: boolean }>;
+    unhideComment: (commentId: string) => Promise<{ success: boolean }>;
     toggleIssueReaction: (issueId: string, emoji: string) => Promise<{ added: boolean }>;
     toggleCommentReaction: (commentId: string, emoji: string) => Promise<{ added: boolean }>;
   };
@@ -446,7 +973,7 @@ export type ApiClient = {
     getCount: (owner: string, repo: string) => Promise<PRCount>;
     getDiff: (id: string) => Promise<PRDiff>;
     getCommits: (id: string, limit?: number, skip?: number) => Promise<{ commits: Commit[]; hasMore: boolean }>;
-    merge: (id: string, data?: { commitMessage?: string }) => Promise<{ success: boolean; mergeCommitOid: string }>;
+    merge: (id: string, data?: { commitMessage?: string }) => Promise<{ success: boolean; mergeCommitOid: string; branchDeleted: boolean }>;
     listReviews: (id: string) => Promise<{ reviews: PRReview[] }>;
     submitReview: (id: string, data: { body?: string; state: "approved" | "changes_requested" | "commented" }) => Promise<PRReview>;
     listComments: (id: string, options?: { groupByFile?: boolean; filePath?: string }) => Promise<{ comments: PRComment[] } | GroupedPRComments>;
@@ -473,6 +1000,9 @@ export type ApiClient = {
     markRead: (id: string) => Promise<{ success: boolean }>;
     markAllRead: () => Promise<{ success: boolean }>;
     delete: (id: string) => Promise<{ success: boolean }>;
+    getPushPublicKey: () => Promise<{ publicKey: string }>;
+    subscribePush: (subscription: { endpoint: string; keys: { p256dh: string; auth: string } }) => Promise<{ success: boolean }>;
+    unsubscribePush: (endpoint: string) => Promise<{ success: boolean }>;
   };
   discussions: {
     list: (owner: string, repo: string, options?: { category?: string; limit?: number; offset?: number }) => Promise<{ discussions: Discussion[]; hasMore: boolean }>;
@@ -494,13 +1024,32 @@ export type ApiClient = {
     update: (id: string, data: { name?: string; description?: string }) => Promise<{ success: boolean }>;
     delete: (id: string) => Promise<{ success: boolean }>;
     addColumn: (projectId: string, name: string) => Promise<ProjectColumn>;
-    updateColumn: (columnId: string, data: { name?: string; position?: number }) => Promise<{ success: boolean }>;
+    updateColumn: (columnId: string, data: { name?: string; position?: number; moveClosedIssues?: boolean }) => Promise<{ success: boolean }>;
     deleteColumn: (columnId: string) => Promise<{ success: boolean }>;
     addItem: (projectId: string, data: { columnId: string; issueId?: string; pullRequestId?: string; noteContent?: string }) => Promise<ProjectItem>;
     updateItem: (itemId: string, data: { columnId?: string; position?: number; noteContent?: string }) => Promise<{ success: boolean }>;
     reorderItems: (items: { id: string; columnId: string; position: number }[]) => Promise<{ success: boolean }>;
     deleteItem: (itemId: string) => Promise<{ success: boolean }>;
   };
+  reactions: {
+    list: (subject: ReactionSubject, id: string, options?: { emoji?: string; limit?: number; offset?: number }) => Promise<ReactionUserList>;
+  };
+  reports: {
+    create: (data: { subjectType: "issue" | "issue_comment"; subjectId: string; reason: ReportReason; details?: string }) => Promise<{ id: string }>;
+    listForRepository: (
+      owner: string,
+      repo: string,
+      options?: { status?: ContentReport["status"]; cursor?: string }
+    ) => Promise<{ reports: ContentReport[]; hasMore: boolean; nextCursor: string | null }>;
+    update: (id: string, status: ContentReport["status"]) => Promise<{ success: boolean }>;
+  };
+  uploads: {
+    create: (data: { filename: string; contentType: string; size: number; repositoryId?: string }) => Promise<PendingUpload>;
+    finalize: (id: string) => Promise<Attachment>;
+  };
+  graphql: {
+    query: <T = Record<string, unknown>>(query: string, variables?: Record<string, unknown>) => Promise<GraphQLResponse<T>>;
+  };
 };
 
 export type SearchResultType = "repository" | "issue" | "pull_request" | "user";
@@ -606,6 +1155,7 @@ export type ProjectColumn = {
   id: string;
   name: string;
   position: number;
+  moveClosedIssues: boolean;
   items: ProjectItem[];
 };
 
@@ -0,0 +1,21 @@
+import { useMutation } from "@tanstack/react-query";
+import { useApi } from "./context";
+
+// The file goes straight to storage through the pre-signed URL; the API only sees the
+// request for the URL and the finalize call.
+export function useUploadAttachment() {
+  const api = useApi();
+  return useMutation({
+    mutationFn: async ({ file, filename, repositoryId }: { file: Blob; filename: string; repositoryId?: string }) => {
+      const contentType = file.type || "application/octet-stream";
+      const upload = await api.uploads.create({ filename, contentType, size: file.size, repositoryId });
+
+      const res = await fetch(upload.uploadUrl, { method: upload.method, headers: upload.headers, body: file });
+      if (!res.ok) {
+        throw new Error(`Upload failed with status ${res.status}`);
+      }
+
+      return api.uploads.finalize(upload.id);
+    },
+  });
+}
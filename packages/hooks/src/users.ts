@@ -1,4 +1,4 @@
-import { useQuery, useInfiniteQuery } from "@tanstack/react-query";
+import { useQuery, useInfiniteQuery, useMutation, useQueryClient } from "@tanstack/react-query";
 import { useApi } from "./context";
 
 export function useCurrentUserSummary(enabled = true) {
@@ -10,6 +10,15 @@ export function useCurrentUserSummary(enabled = true) {
   });
 }
 
+export function useDashboard(enabled = true) {
+  const api = useApi();
+  return useQuery({
+    queryKey: ["user", "me", "dashboard"],
+    queryFn: () => api.users.getDashboard(),
+    enabled,
+  });
+}
+
 export function useUserProfile(username: string) {
   const api = useApi();
   return useQuery({
@@ -19,11 +28,22 @@ export function useUserProfile(username: string) {
   });
 }
 
-export function useUserStarredRepos(username: string) {
+export function useUserStarredRepos(username: string, limit = 30, offset = 0) {
   const api = useApi();
   return useQuery({
-    queryKey: ["user", username, "starred"],
-    queryFn: () => api.users.getStarred(username),
+    queryKey: ["user", username, "starred", limit, offset],
+    queryFn: () => api.users.getStarred(username, limit, offset),
+    enabled: !!username,
+  });
+}
+
+export function useInfiniteUserStarredRepos(username: string, pageSize = 30) {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["user", username, "starred", "infinite"],
+    queryFn: ({ pageParam = 0 }) => api.users.getStarred(username, pageSize, pageParam),
+    getNextPageParam: (lastPage, allPages) => (lastPage.hasMore ? allPages.length * pageSize : undefined),
+    initialPageParam: 0,
     enabled: !!username,
   });
 }
@@ -54,3 +74,48 @@ export function useInfinitePublicUsers(sortBy: "newest" | "oldest" = "newest", p
     initialPageParam: 0,
   });
 }
+
+export function useInfiniteUserEvents(username: string) {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["user", username, "events"],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.users.getEvents(username, pageParam),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!username,
+  });
+}
+
+export function useInfiniteFollowers(username: string) {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["user", username, "followers"],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.users.getFollowers(username, pageParam),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!username,
+  });
+}
+
+export function useInfiniteFollowing(username: string) {
+  const api = useApi();
+  return useInfiniteQuery({
+    queryKey: ["user", username, "following"],
+    queryFn: ({ pageParam }: { pageParam: string | undefined }) => api.users.getFollowing(username, pageParam),
+    getNextPageParam: (lastPage) => lastPage.nextCursor ?? undefined,
+    initialPageParam: undefined as string | undefined,
+    enabled: !!username,
+  });
+}
+
+export function useFollowUser(username: string) {
+  const api = useApi();
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (follow: boolean) => (follow ? api.users.follow(username) : api.users.unfollow(username)),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["user", username] });
+      queryClient.invalidateQueries({ queryKey: ["user", "me", "dashboard"] });
+    },
+  });
+}
@@ -1,31 +1,86 @@
 import type {
+  AccountExport,
+  ActivityEvent,
   ApiClient,
+  Attachment,
   BranchProtectionRule,
+  CodeFrequencyStats,
   Commit,
+  CommitDetails,
   CommitDiff,
+  CommitStatusSummary,
+  ContentReport,
+  Dashboard,
+  ExternalReference,
+  FileContentChange,
+  FileContentCommit,
+  FileDiffBetween,
   FileLastCommit,
+  FollowUser,
+  FsckReport,
+  GitCredential,
+  GraphQLResponse,
+  HiddenReason,
+  HighlightOptions,
+  HighlightedBlob,
   Issue,
+  IssueAuthor,
   IssueComment,
+  IssueCommentPage,
+  IssueCommentPageOptions,
+  IssueExport,
   IssueFilters,
+  IssueForm,
+  IssueFormValues,
+  IssueImportResult,
+  IssueReferences,
+  IssueStateReason,
+  IssueTemplate,
   Label,
   PRComment,
   PRCount,
   PRDiff,
   PRFilters,
   PRReview,
+  PendingUpload,
   PublicUser,
   PullRequest,
+  PunchCardStats,
+  PushPolicy,
+  ReactionSubject,
+  ReactionUserList,
+  RefLogEntry,
+  RenderedMarkdown,
+  RepoFile,
   RepoInfo,
+  RepoLanguage,
+  RepoOverview,
   RepoPageData,
+  RepoSize,
+  RepoStatsComputing,
+  ReportReason,
   Repository,
+  RepositoryDeletionReport,
+  RepositoryFilters,
   RepositoryWithOwner,
   RepositoryWithStars,
+  RestoreRepositoryResult,
+  StaleBranch,
   TreeResponse,
   UserPreferences,
   UserProfile,
   UserSummary,
 } from "@gitbruv/hooks";
 
+const REACTION_SUBJECT_PATHS: Record<ReactionSubject, string> = {
+  issue: "/api/issues",
+  issue_comment: "/api/issues/comments",
+  pull_request: "/api/pulls",
+  pr_comment: "/api/pulls/comments",
+  discussion: "/api/discussions",
+  discussion_comment: "/api/discussions/comments",
+};
+
 export interface ApiClientConfig {
   baseUrl: string;
   getAuthHeaders: () => Promise<HeadersInit>;
@@ -54,7 +109,7 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
 
     if (!res.ok) {
       const data = await res.json().catch(() => ({}));
-      throw new Error(data.error || `Request failed: ${res.status}`);
+      throw new Error(data.message || `Request failed: ${res.status}`);
     }
 
     return res.json();
@@ -62,7 +117,7 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
 
   return {
     repositories: {
-      create: (data: { name: string; description?: string; visibility: "public" | "private" }) =>
+      create: (data: { name: string; description?: string; visibility?: "public" | "private"; mirrorUrl?: string; mirrorIntervalMinutes?: number; templateId?: string; defaultLabels?: boolean }) =>
         apiFetch<Repository>("/api/repositories", {
           method: "POST",
           body: JSON.stringify(data),
@@ -88,22 +143,58 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
 
       getPageData: (owner: string, name: string) => apiFetch<RepoPageData>(`/api/repositories/${owner}/${name}/page-data`),
 
-      getUserRepos: (username: string) => apiFetch<{ repos: RepositoryWithStars[] }>(`/api/repositories/user/${username}`),
+      getUserRepos: (username: string, options?: { sortBy?: "stars" | "name" | "updated"; limit?: number; offset?: number }) => {
+        const params = new URLSearchParams();
+        if (options?.sortBy) params.set("sortBy", options.sortBy);
+        if (options?.limit) params.set("limit", String(options.limit));
+        if (options?.offset) params.set("offset", String(options.offset));
+        const query = params.toString();
+        return apiFetch<{ repos: (RepositoryWithStars & { starred: boolean })[]; hasMore: boolean }>(
+          `/api/repositories/user/${username}${query ? `?${query}` : ""}`
+        );
+      },
 
-      getPublic: (sortBy: "stars" | "updated" | "created" = "updated", limit = 20, offset = 0) =>
-        apiFetch<{ repos: RepositoryWithStars[]; hasMore: boolean }>(
-          `/api/repositories/public?sortBy=${sortBy}&limit=${limit}&offset=${offset}`
-        ),
+      getPublic: (sortBy: "stars" | "updated" | "created" = "updated", limit = 20, offset = 0, filters?: RepositoryFilters, cursor?: string) => {
+        const params = new URLSearchParams({ sortBy, limit: String(limit), offset: String(offset) });
+        if (filters?.topic) params.set("topic", filters.topic);
+        if (filters?.language) params.set("language", filters.language);
+        if (cursor) params.set("cursor", cursor);
+        return apiFetch<{ repos: RepositoryWithStars[]; hasMore: boolean; nextCursor: string | null }>(`/api/repositories/public?${params.toString()}`);
+      },
 
-      update: (id: string, data: { name?: string; description?: string; visibility?: "public" | "private" }) =>
+      update: (id: string, data: { name?: string; description?: string; visibility?: "public" | "private"; deleteBranchOnMerge?: boolean; isTemplate?: boolean; externalReferences?: ExternalReference[] | null; mirrorIntervalMinutes?: number }) =>
         apiFetch<Repository>(`/api/repositories/${id}`, {
           method: "PATCH",
           body: JSON.stringify(data),
         }),
 
-      delete: (id: string) =>
-        apiFetch<{ success: boolean }>(`/api/repositories/${id}`, {
+      updateTopics: (id: string, topics: string[]) =>
+        apiFetch<{ topics: string[] }>(`/api/repositories/${id}/topics`, {
+          method: "PATCH",
+          body: JSON.stringify({ topics }),
+        }),
+
+      setDefaultBranch: (id: string, branch: string) =>
+        apiFetch<{ defaultBranch: string }>(`/api/repositories/${id}/default-branch`, {
+          method: "PATCH",
+          body: JSON.stringify({ branch }),
+        }),
+
+      syncMirror: (id: string) =>
+        apiFetch<{ status: "syncing" }>(`/api/repositories/${id}/mirror/sync`, {
+          method: "POST",
+        }),
+
+      delete: (id: string, confirm: string) =>
+        apiFetch<{ success: boolean } & RepositoryDeletionReport>(`/api/repositories/${id}`, {
           method: "DELETE",
+          body: JSON.stringify({ confirm }),
+        }),
+
+      previewDeletion: (id: string) =>
+        apiFetch<{ dryRun: true } & RepositoryDeletionReport>(`/api/repositories/${id}`, {
+          method: "DELETE",
+          body: JSON.stringify({ dryRun: true }),
         }),
 
       toggleStar: (id: string) =>
@@ -113,41 +204,132 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
 
       isStarred: (id: string) => apiFetch<{ starred: boolean }>(`/api/repositories/${id}/is-starred`),
 
+      star: (owner: string, name: string) =>
+        apiFetch<{ starred: boolean; starCount: number }>(`/api/repositories/${owner}/${name}/star`, {
+          method: "PUT",
+        }),
+
+      unstar: (owner: string, name: string) =>
+        apiFetch<{ starred: boolean; starCount: number }>(`/api/repositories/${owner}/${name}/star`, {
+          method: "DELETE",
+        }),
+
+      getStarredState: (ids: string[]) =>
+        apiFetch<{ starred: Record<string, boolean> }>(`/api/repositories/starred?ids=${encodeURIComponent(ids.join(","))}`),
+
       getBranches: (owner: string, name: string) =>
         apiFetch<{ branches: string[] }>(`/api/repositories/${owner}/${name}/branches`),
 
-      getTree: (owner: string, name: string, branch: string, path = "") =>
+      getStaleBranches: (owner: string, name: string, days = 90) =>
+        apiFetch<{ branches: StaleBranch[]; defaultBranch: string; days: number }>(
+          `/api/repositories/${owner}/${name}/branches/stale?days=${days}`
+        ),
+
+      deleteBranches: (owner: string, name: string, branches: string[]) =>
+        apiFetch<{ deleted: string[]; skipped: { branch: string; reason: string }[] }>(`/api/repositories/${owner}/${name}/branches`, {
+          method: "DELETE",
+          body: JSON.stringify({ branches }),
+        }),
+
+      getTree: (owner: string, name: string, branch: string, path = "", cursor?: string) =>
         apiFetch<TreeResponse>(
-          `/api/repositories/${owner}/${name}/tree?branch=${branch}&path=${encodeURIComponent(path)}`
+          `/api/repositories/${owner}/${name}/tree?branch=${branch}&path=${encodeURIComponent(path)}${cursor ? `&cursor=${cursor}` : ""}`
         ),
 
-      getTreeCommits: (owner: string, name: string, branch: string, path = "") =>
-        apiFetch<{ files: FileLastCommit[] }>(
-          `/api/repositories/${owner}/${name}/tree-commits?branch=${branch}&path=${encodeURIComponent(path)}`
+      getTreeCommits: (owner: string, name: string, branch: string, path = "", cursor?: string) =>
+        apiFetch<{ files: FileLastCommit[]; pending: boolean }>(
+          `/api/repositories/${owner}/${name}/tree-commits?branch=${branch}&path=${encodeURIComponent(path)}${cursor ? `&cursor=${cursor}` : ""}`
         ),
 
-      getFile: (owner: string, name: string, branch: string, path: string) =>
-        apiFetch<{ content: string; oid: string; path: string }>(
-          `/api/repositories/${owner}/${name}/file?branch=${branch}&path=${encodeURIComponent(path)}`
+      getFile: (owner: string, name: string, branch: string, path: string, options?: { lastCommit?: boolean }) =>
+        apiFetch<RepoFile>(
+          `/api/repositories/${owner}/${name}/file?branch=${branch}&path=${encodeURIComponent(path)}${options?.lastCommit ? "&include=lastCommit" : ""}`
         ),
 
-      getCommits: (owner: string, name: string, branch: string, limit = 30, skip = 0) =>
-        apiFetch<{ commits: Commit[]; hasMore: boolean }>(
-          `/api/repositories/${owner}/${name}/commits?branch=${branch}&limit=${limit}&skip=${skip}`
+      updateContents: (owner: string, name: string, path: string, change: FileContentChange) =>
+        apiFetch<FileContentCommit>(`/api/repositories/${owner}/${name}/contents/${path.split("/").map(encodeURIComponent).join("/")}`, {
+          method: "POST",
+          body: JSON.stringify(change),
+        }),
+
+      getCommits: (owner: string, name: string, branch: string, limit = 30, skip = 0, cursor?: string) =>
+        apiFetch<{ commits: Commit[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/repositories/${owner}/${name}/commits?branch=${branch}&limit=${limit}&skip=${skip}${cursor ? `&cursor=${cursor}` : ""}`
         ),
 
       getCommitCount: (owner: string, name: string, branch: string) =>
         apiFetch<{ count: number }>(`/api/repositories/${owner}/${name}/commits/count?branch=${branch}`),
 
+      getCommit: (owner: string, name: string, ref: string) =>
+        apiFetch<{ commit: CommitDetails }>(`/api/repositories/${owner}/${name}/commits/${encodeURIComponent(ref)}`),
+
       getCommitDiff: (owner: string, name: string, oid: string) =>
         apiFetch<CommitDiff>(`/api/repositories/${owner}/${name}/commits/${oid}/diff`),
 
+      getFileDiff: (owner: string, name: string, path: string, base: string, head: string) =>
+        apiFetch<FileDiffBetween>(
+          `/api/repositories/${owner}/${name}/diff?path=${encodeURIComponent(path)}&base=${encodeURIComponent(base)}&head=${encodeURIComponent(head)}`
+        ),
+
+      getCommitStatuses: (owner: string, name: string, oid: string) =>
+        apiFetch<CommitStatusSummary>(`/api/repositories/${owner}/${name}/statuses/${oid}`),
+
       getReadme: (owner: string, name: string, oid: string) =>
         apiFetch<{ content: string }>(`/api/repositories/${owner}/${name}/readme?oid=${oid}`),
 
       getReadmeOid: (owner: string, name: string, branch: string) =>
         apiFetch<{ readmeOid: string | null }>(`/api/repositories/${owner}/${name}/readme-oid?branch=${branch}`),
 
+      highlight: (owner: string, name: string, oid: string, options: HighlightOptions = {}) => {
+        const params = new URLSearchParams();
+        for (const [key, value] of Object.entries(options)) {
+          if (value) params.set(key, value);
+        }
+        const query = params.toString();
+        return apiFetch<HighlightedBlob>(`/api/repositories/${owner}/${name}/highlight/${oid}${query ? `?${query}` : ""}`);
+      },
+
+      getOverview: (owner: string, name: string, branch?: string) =>
+        apiFetch<RepoOverview>(`/api/repositories/${owner}/${name}/overview${branch ? `?branch=${branch}` : ""}`),
+
+      getLanguages: (owner: string, name: string) =>
+        apiFetch<{ languages: RepoLanguage[] }>(`/api/repositories/${owner}/${name}/languages`),
+
+      getCodeFrequency: (owner: string, name: string) =>
+        apiFetch<CodeFrequencyStats | RepoStatsComputing>(`/api/repositories/${owner}/${name}/stats/code-frequency`),
+
+      getPunchCard: (owner: string, name: string) =>
+        apiFetch<PunchCardStats | RepoStatsComputing>(`/api/repositories/${owner}/${name}/stats/punch-card`),
+
+      getSize: (owner: string, name: string, limit?: number) =>
+        apiFetch<RepoSize | RepoStatsComputing>(`/api/repositories/${owner}/${name}/size${limit ? `?limit=${limit}` : ""}`),
+
+      fsck: (owner: string, name: string) =>
+        apiFetch<FsckReport>(`/api/repositories/${owner}/${name}/fsck`, { method: "POST" }),
+
+      refreshMetadata: (owner: string, name: string) =>
+        apiFetch<{ rebuilt: string[]; removed: string[]; failed: string[] }>(`/api/repositories/${owner}/${name}/metadata/refresh`, {
+          method: "POST",
+        }),
+
+      restore: (owner: string, name: string, bundle: Blob, options?: { force?: boolean }) =>
+        apiFetch<RestoreRepositoryResult>(`/api/repositories/${owner}/${name}/restore${options?.force ? "?force=true" : ""}`, {
+          method: "POST",
+          body: bundle,
+          headers: { "Content-Type": "application/octet-stream" },
+        }),
+
+      getReflog: (owner: string, name: string, branch: string, cursor?: string) =>
+        apiFetch<{ entries: RefLogEntry[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/repositories/${owner}/${name}/reflog/${branch}${cursor ? `?cursor=${cursor}` : ""}`
+        ),
+
+      render: (owner: string, name: string, text: string) =>
+        apiFetch<RenderedMarkdown>(`/api/repositories/${owner}/${name}/render`, {
+          method: "POST",
+          body: JSON.stringify({ text }),
+        }),
+
       getBranchProtection: (owner: string, name: string) =>
         apiFetch<{ rules: BranchProtectionRule[] }>(`/api/repositories/${owner}/${name}/branch-protection`),
 
@@ -180,16 +362,43 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         apiFetch<{ success: boolean }>(`/api/repositories/${owner}/${name}/branch-protection/${ruleId}`, {
           method: "DELETE",
         }),
+
+      getPushPolicy: (owner: string, name: string) =>
+        apiFetch<PushPolicy>(`/api/repositories/${owner}/${name}/push-policy`),
+
+      updatePushPolicy: (owner: string, name: string, data: Partial<PushPolicy>) =>
+        apiFetch<PushPolicy>(`/api/repositories/${owner}/${name}/push-policy`, {
+          method: "PUT",
+          body: JSON.stringify(data),
+        }),
     },
 
     users: {
       getProfile: (username: string) => apiFetch<UserProfile>(`/api/users/${username}/profile`),
       getSummary: () => apiFetch<UserSummary>(`/api/users/me/summary`),
-      getStarred: (username: string) => apiFetch<{ repos: RepositoryWithStars[] }>(`/api/users/${username}/starred`),
+      getDashboard: () => apiFetch<Dashboard>("/api/dashboard"),
+      getStarred: (username: string, limit = 30, offset = 0) =>
+        apiFetch<{ repos: RepositoryWithStars[]; hasMore: boolean; total: number }>(
+          `/api/users/${username}/starred?limit=${limit}&offset=${offset}`
+        ),
       getAvatarByUsername: (username: string) => apiFetch<{ avatarUrl: string | null }>(`/api/users/${username}/avatar`),
-      getPublic: (sortBy: "newest" | "oldest" = "newest", limit = 20, offset = 0) =>
-        apiFetch<{ users: PublicUser[]; hasMore: boolean }>(
-          `/api/users/public?sortBy=${sortBy}&limit=${limit}&offset=${offset}`
+      getPublic: (sortBy: "newest" | "oldest" = "newest", limit = 20, offset = 0, cursor?: string) =>
+        apiFetch<{ users: PublicUser[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/users/public?sortBy=${sortBy}&limit=${limit}&offset=${offset}${cursor ? `&cursor=${cursor}` : ""}`
+        ),
+      getEvents: (username: string, cursor?: string) =>
+        apiFetch<{ events: ActivityEvent[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/users/${username}/events${cursor ? `?cursor=${cursor}` : ""}`
+        ),
+      follow: (username: string) => apiFetch<{ following: boolean }>(`/api/users/${username}/follow`, { method: "PUT" }),
+      unfollow: (username: string) => apiFetch<{ following: boolean }>(`/api/users/${username}/follow`, { method: "DELETE" }),
+      getFollowers: (username: string, cursor?: string) =>
+        apiFetch<{ users: FollowUser[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/users/${username}/followers${cursor ? `?cursor=${cursor}` : ""}`
+        ),
+      getFollowing: (username: string, cursor?: string) =>
+        apiFetch<{ users: FollowUser[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/users/${username}/following${cursor ? `?cursor=${cursor}` : ""}`
         ),
     },
 
@@ -232,7 +441,7 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         }),
 
       updateEmail: (data: { email: string }) =>
-        apiFetch<{ success: boolean }>("/api/settings/email", {
+        apiFetch<{ success: boolean; pendingEmail: string }>("/api/settings/email", {
           method: "PATCH",
           body: JSON.stringify(data),
         }),
@@ -252,6 +461,16 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         apiFetch<{ success: boolean }>("/api/settings/account", {
           method: "DELETE",
         }),
+
+      requestExport: (data: { includeRepositories?: boolean }) =>
+        apiFetch<{ export: AccountExport }>("/api/settings/export", {
+          method: "POST",
+          body: JSON.stringify(data),
+        }),
+
+      listExports: () => apiFetch<{ exports: AccountExport[] }>("/api/settings/export"),
+      createGitCredential: () => apiFetch<GitCredential>("/api/git/credentials", { method: "POST" }),
+      revokeGitCredential: (id: string) => apiFetch<{ success: boolean }>(`/api/git/credentials/${id}`, { method: "DELETE" }),
     },
 
     issues: {
@@ -262,21 +481,22 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         if (filters?.assignee) params.set("assignee", filters.assignee);
         if (filters?.limit) params.set("limit", String(filters.limit));
         if (filters?.offset) params.set("offset", String(filters.offset));
+        if (filters?.cursor) params.set("cursor", filters.cursor);
         const query = params.toString();
-        return apiFetch<{ issues: Issue[]; hasMore: boolean }>(
+        return apiFetch<{ issues: Issue[]; hasMore: boolean; nextCursor: string | null }>(
           `/api/repositories/${owner}/${repo}/issues${query ? `?${query}` : ""}`
         );
       },
 
       get: (owner: string, repo: string, number: number) => apiFetch<Issue>(`/api/repositories/${owner}/${repo}/issues/${number}`),
 
-      create: (owner: string, repo: string, data: { title: string; body?: string; labels?: string[]; assignees?: string[] }) =>
+      create: (owner: string, repo: string, data: { title: string; body?: string; labels?: string[]; assignees?: string[]; form?: string; fields?: IssueFormValues }) =>
         apiFetch<Issue>(`/api/repositories/${owner}/${repo}/issues`, {
           method: "POST",
           body: JSON.stringify(data),
         }),
 
-      update: (id: string, data: { title?: string; body?: string; state?: "open" | "closed"; locked?: boolean }) =>
+      update: (id: string, data: { title?: string; body?: string; state?: "open" | "closed"; stateReason?: IssueStateReason; duplicateOf?: number; locked?: boolean }) =>
         apiFetch<{ success: boolean }>(`/api/issues/${id}`, {
           method: "PATCH",
           body: JSON.stringify(data),
@@ -290,6 +510,20 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
       getCount: (owner: string, repo: string) =>
         apiFetch<{ open: number; closed: number }>(`/api/repositories/${owner}/${repo}/issues/count`),
 
+      export: (owner: string, repo: string) => apiFetch<IssueExport>(`/api/repositories/${owner}/${repo}/issues/export`),
+
+      import: (owner: string, repo: string, data: { issues: unknown[] } | unknown[]) =>
+        apiFetch<IssueImportResult>(`/api/repositories/${owner}/${repo}/issues/import`, {
+          method: "POST",
+          body: JSON.stringify(data),
+        }),
+
+      listForms: (owner: string, repo: string) =>
+        apiFetch<{ forms: IssueForm[] }>(`/api/repositories/${owner}/${repo}/issue-forms`),
+
+      listTemplates: (owner: string, repo: string) =>
+        apiFetch<{ templates: IssueTemplate[] }>(`/api/repositories/${owner}/${repo}/issue-templates`),
+
       listLabels: (owner: string, repo: string) => apiFetch<{ labels: Label[] }>(`/api/repositories/${owner}/${repo}/labels`),
 
       createLabel: (owner: string, repo: string, data: { name: string; description?: string; color: string }) =>
@@ -309,6 +543,23 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
           method: "DELETE",
         }),
 
+      applyDefaultLabels: (owner: string, repo: string) =>
+        apiFetch<{ labels: Label[] }>(`/api/repositories/${owner}/${repo}/labels/defaults`, {
+          method: "POST",
+        }),
+
+      bulkAddLabel: (owner: string, repo: string, labelId: string, issues: number[]) =>
+        apiFetch<{ updated: number }>(`/api/repositories/${owner}/${repo}/labels/${labelId}/issues`, {
+          method: "POST",
+          body: JSON.stringify({ issues }),
+        }),
+
+      bulkRemoveLabel: (owner: string, repo: string, labelId: string, issues: number[]) =>
+        apiFetch<{ updated: number }>(`/api/repositories/${owner}/${repo}/labels/${labelId}/issues`, {
+          method: "DELETE",
+          body: JSON.stringify({ issues }),
+        }),
+
       addLabels: (issueId: string, labels: string[]) =>
         apiFetch<{ success: boolean }>(`/api/issues/${issueId}/labels`, {
           method: "POST",
@@ -321,17 +572,32 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         }),
 
       addAssignees: (issueId: string, assignees: string[]) =>
-        apiFetch<{ success: boolean }>(`/api/issues/${issueId}/assignees`, {
+        apiFetch<{ assignees: IssueAuthor[] }>(`/api/issues/${issueId}/assignees`, {
           method: "POST",
           body: JSON.stringify({ assignees }),
         }),
 
+      assignSelf: (issueId: string) =>
+        apiFetch<{ assignees: IssueAuthor[] }>(`/api/issues/${issueId}/assignees/me`, {
+          method: "POST",
+        }),
+
       removeAssignee: (issueId: string, userId: string) =>
         apiFetch<{ success: boolean }>(`/api/issues/${issueId}/assignees/${userId}`, {
           method: "DELETE",
         }),
 
-      listComments: (issueId: string) => apiFetch<{ comments: IssueComment[] }>(`/api/issues/${issueId}/comments`),
+      getReferences: (issueId: string) => apiFetch<IssueReferences>(`/api/issues/${issueId}/references`),
+
+      listComments: (issueId: string, options?: IssueCommentPageOptions) => {
+        const params = new URLSearchParams();
+        if (options?.order) params.set("order", options.order);
+        if (options?.after) params.set("after", options.after);
+        if (options?.before) params.set("before", options.before);
+        if (options?.limit) params.set("limit", String(options.limit));
+        if (options?.reactions !== false) params.set("include", "reactions");
+        return apiFetch<IssueCommentPage>(`/api/issues/${issueId}/comments?${params}`);
+      },
 
       createComment: (issueId: string, body: string) =>
         apiFetch<IssueComment>(`/api/issues/${issueId}/comments`, {
@@ -350,6 +616,17 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
           method: "DELETE",
         }),
 
+      hideComment: (commentId: string, reason: HiddenReason) =>
+        apiFetch<{ success: boolean }>(`/api/issues/comments/${commentId}/hide`, {
+          method: "POST",
+          body: JSON.stringify({ reason }),
+        }),
+
+      unhideComment: (commentId: string) =>
+        apiFetch<{ success: boolean }>(`/api/issues/comments/${commentId}/hide`, {
+          method: "DELETE",
+        }),
+
       toggleIssueReaction: (issueId: string, emoji: string) =>
         apiFetch<{ added: boolean }>(`/api/issues/${issueId}/reactions`, {
           method: "POST",
@@ -422,7 +699,7 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         apiFetch<{ commits: Commit[]; hasMore: boolean }>(`/api/pulls/${id}/commits?limit=${limit}&skip=${skip}`),
 
       merge: (id: string, data?: { commitMessage?: string }) =>
-        apiFetch<{ success: boolean; mergeCommitOid: string }>(`/api/pulls/${id}/merge`, {
+        apiFetch<{ success: boolean; mergeCommitOid: string; branchDeleted: boolean }>(`/api/pulls/${id}/merge`, {
           method: "POST",
           body: JSON.stringify(data || {}),
         }),
@@ -554,6 +831,20 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
         apiFetch<{ success: boolean }>(`/api/notifications/${id}`, {
           method: "DELETE",
         }),
+
+      getPushPublicKey: () => apiFetch<{ publicKey: string }>(`/api/notifications/push/public-key`),
+
+      subscribePush: (subscription: { endpoint: string; keys: { p256dh: string; auth: string } }) =>
+        apiFetch<{ success: boolean }>(`/api/notifications/push/subscriptions`, {
+          method: "POST",
+          body: JSON.stringify(subscription),
+        }),
+
+      unsubscribePush: (endpoint: string) =>
+        apiFetch<{ success: boolean }>(`/api/notifications/push/subscriptions`, {
+          method: "DELETE",
+          body: JSON.stringify({ endpoint }),
+        }),
     },
 
     discussions: {
@@ -645,7 +936,7 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
           body: JSON.stringify({ name }),
         }),
 
-      updateColumn: (columnId: string, data: { name?: string; position?: number }) =>
+      updateColumn: (columnId: string, data: { name?: string; position?: number; moveClosedIssues?: boolean }) =>
         apiFetch<{ success: boolean }>(`/api/projects/columns/${columnId}`, {
           method: "PATCH",
           body: JSON.stringify(data),
@@ -679,5 +970,55 @@ export function createApiClient(config: ApiClientConfig): Omit<ApiClient, "setti
           method: "DELETE",
         }),
     },
+
+    reactions: {
+      list: (subject: ReactionSubject, id: string, options?: { emoji?: string; limit?: number; offset?: number }) => {
+        const params = new URLSearchParams();
+        if (options?.emoji) params.set("emoji", options.emoji);
+        if (options?.limit) params.set("limit", String(options.limit));
+        if (options?.offset) params.set("offset", String(options.offset));
+        const query = params.toString();
+        return apiFetch<ReactionUserList>(`${REACTION_SUBJECT_PATHS[subject]}/${id}/reactions${query ? `?${query}` : ""}`);
+      },
+    },
+
+    reports: {
+      create: (data: { subjectType: "issue" | "issue_comment"; subjectId: string; reason: ReportReason; details?: string }) =>
+        apiFetch<{ id: string }>("/api/reports", {
+          method: "POST",
+          body: JSON.stringify(data),
+        }),
+      listForRepository: (owner: string, repo: string, options?: { status?: ContentReport["status"]; cursor?: string }) => {
+        const params = new URLSearchParams();
+        if (options?.status) params.set("status", options.status);
+        if (options?.cursor) params.set("cursor", options.cursor);
+        const query = params.toString();
+        return apiFetch<{ reports: ContentReport[]; hasMore: boolean; nextCursor: string | null }>(
+          `/api/repositories/${owner}/${repo}/reports${query ? `?${query}` : ""}`
+        );
+      },
+      update: (id: string, status: ContentReport["status"]) =>
+        apiFetch<{ success: boolean }>(`/api/reports/${id}`, {
+          method: "PATCH",
+          body: JSON.stringify({ status }),
+        }),
+    },
+
+    uploads: {
+      create: (data: { filename: string; contentType: string; size: number; repositoryId?: string }) =>
+        apiFetch<PendingUpload>("/api/uploads", {
+          method: "POST",
+          body: JSON.stringify(data),
+        }),
+      finalize: (id: string) => apiFetch<Attachment>(`/api/uploads/${id}/finalize`, { method: "POST" }),
+    },
+
+    graphql: {
+      query: <T = Record<string, unknown>>(query: string, variables?: Record<string, unknown>) =>
+        apiFetch<GraphQLResponse<T>>("/api/graphql", {
+          method: "POST",
+          body: JSON.stringify({ query, variables }),
+        }),
+    },
   };
 }
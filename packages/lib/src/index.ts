@@ -4,7 +4,12 @@ export {
   USERNAME_MAX_LENGTH,
   USERNAME_PATTERN,
   PASSWORD_MIN_LENGTH,
+  REPOSITORY_NAME_MAX_LENGTH,
+  REPOSITORY_NAME_PATTERN,
+  RESERVED_USERNAMES,
+  RESERVED_REPOSITORY_NAMES,
   validateUsername,
+  validateRepositoryName,
   validatePassword,
   isValidEmail,
   type ValidationResult,
@@ -2,6 +2,45 @@ export const USERNAME_MIN_LENGTH = 3;
 export const USERNAME_MAX_LENGTH = 39;
 export const USERNAME_PATTERN = /^[a-zA-Z0-9_-]+$/;
 export const PASSWORD_MIN_LENGTH = 8;
+export const REPOSITORY_NAME_MAX_LENGTH = 100;
+export const REPOSITORY_NAME_PATTERN = /^[a-zA-Z0-9_.-]+$/;
+
+// Usernames are the first path segment on the web and in git URLs, so they can't be anything
+// the web app or the API already routes at the top level or next to a username.
+export const RESERVED_USERNAMES = new Set([
+  "about",
+  "admin",
+  "api",
+  "assets",
+  "badges",
+  "explore",
+  "file",
+  "forgot-password",
+  "health",
+  "healthz",
+  "help",
+  "info",
+  "login",
+  "logout",
+  "me",
+  "new",
+  "oauth",
+  "public",
+  "readyz",
+  "register",
+  "reset-password",
+  "search",
+  "settings",
+  "signup",
+  "static",
+  "user",
+  "users",
+  "verify-email",
+]);
+
+// Repository names become a directory in the bucket, so the names git itself uses inside a bare
+// repository, and the relative path segments, are kept out.
+export const RESERVED_REPOSITORY_NAMES = new Set([".", "..", ".git", "head", "hooks", "info", "objects", "refs"]);
 
 export type ValidationResult = {
   valid: boolean;
@@ -21,6 +60,47 @@ export function validateUsername(username: string): ValidationResult {
     return { valid: false, error: "Username can only contain letters, numbers, hyphens, and underscores" };
   }
 
+  if (!/[a-zA-Z0-9]/.test(username)) {
+    return { valid: false, error: "Username must contain at least one letter or number" };
+  }
+
+  if (username.startsWith("-") || username.endsWith("-")) {
+    return { valid: false, error: "Username cannot start or end with a hyphen" };
+  }
+
+  if (username.includes("--")) {
+    return { valid: false, error: "Username cannot contain consecutive hyphens" };
+  }
+
+  if (RESERVED_USERNAMES.has(username.toLowerCase())) {
+    return { valid: false, error: "This username is reserved" };
+  }
+
+  return { valid: true };
+}
+
+export function validateRepositoryName(name: string): ValidationResult {
+  if (name.length === 0) {
+    return { valid: false, error: "Repository name is required" };
+  }
+
+  if (name.length > REPOSITORY_NAME_MAX_LENGTH) {
+    return { valid: false, error: `Repository name must be at most ${REPOSITORY_NAME_MAX_LENGTH} characters` };
+  }
+
+  if (!REPOSITORY_NAME_PATTERN.test(name)) {
+    return { valid: false, error: "Repository name can only contain letters, numbers, periods, hyphens, and underscores" };
+  }
+
+  if (RESERVED_REPOSITORY_NAMES.has(name.toLowerCase())) {
+    return { valid: false, error: "This repository name is reserved" };
+  }
+
+  // Git URLs drop a trailing ".git", so such a repository couldn't be cloned by its own name.
+  if (name.toLowerCase().endsWith(".git")) {
+    return { valid: false, error: "Repository name cannot end with .git" };
+  }
+
   return { valid: true };
 }
 